@@ -24,6 +24,7 @@ async fn run(name: &str, client: ObjectStoreClient) -> Result<(), Box<dyn std::e
             delimiter: None,
             max_results: None,
             continue_from: None,
+            ..Default::default()
         })
         .await?;
     if resp.objects.is_empty() {
@@ -12,7 +12,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("==> gRPC Client Example");
 
     // Create a gRPC client
-    let mut client = ObjectStoreClient::grpc("http://localhost:50051").await?;
+    let client = ObjectStoreClient::grpc("http://localhost:50051").await?;
     println!("✓ Created gRPC client");
 
     // Health check
@@ -36,7 +36,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "mcp" => {
             // Optional auth: set GO_OBJSTORE_TOKEN / GO_OBJSTORE_TENANT env vars.
             let auth = AuthConfig {
-                token: env::var("GO_OBJSTORE_TOKEN").ok(),
+                token: env::var("GO_OBJSTORE_TOKEN").ok().map(Into::into),
                 tenant_id: env::var("GO_OBJSTORE_TENANT").ok(),
                 ..Default::default()
             };
@@ -11,7 +11,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create a QUIC client
     let addr = "127.0.0.1:4433".parse()?;
-    let mut client = ObjectStoreClient::quic(addr, "localhost").await?;
+    let client = ObjectStoreClient::quic(addr, "localhost").await?;
     println!("✓ Created QUIC client");
 
     // Health check
@@ -10,7 +10,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("==> REST Client Example");
 
     // Create a REST client
-    let mut client = ObjectStoreClient::rest("http://localhost:8080")?;
+    let client = ObjectStoreClient::rest("http://localhost:8080")?;
     println!("✓ Created REST client");
 
     // Health check
@@ -3,7 +3,7 @@ use go_objstore::{ObjectStore, ObjectStoreClient};
 
 /// Demonstrates using the unified ObjectStore trait
 async fn test_client(
-    mut client: impl ObjectStore,
+    client: impl ObjectStore,
     name: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n==> Testing {} client", name);
@@ -59,7 +59,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Box::new(ObjectStoreClient::grpc("http://localhost:50051").await?),
     ];
 
-    for (i, mut client) in clients.into_iter().enumerate() {
+    for (i, client) in clients.into_iter().enumerate() {
         let key = &format!("examples/trait-object-{}.txt", i);
         let data = Bytes::from(format!("Trait object {}", i));
 
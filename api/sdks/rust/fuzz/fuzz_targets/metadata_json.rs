@@ -0,0 +1,6 @@
+#![no_main]
+
+use go_objstore::fuzzing::{run, Target};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| run(Target::MetadataJson, data));
@@ -0,0 +1,490 @@
+//! Parquet and DataFusion integration.
+//!
+//! With the `parquet` feature, [`ObjectStoreClient::open_parquet`] opens a
+//! Parquet object through the seekable [`ObjectReader`], so reading a few
+//! columns or row groups fetches the footer and those column chunks rather
+//! than the whole file:
+//!
+//! ```no_run
+//! use futures::TryStreamExt;
+//! use go_objstore::analytics::parquet::arrow::ProjectionMask;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let builder = client.open_parquet("warehouse/orders/2024-06.parquet").await?;
+//! println!("{} rows", builder.metadata().file_metadata().num_rows());
+//! let mask = ProjectionMask::roots(builder.parquet_schema(), [0, 2]);
+//! let batches: Vec<_> = builder.with_projection(mask).build()?.try_collect().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `datafusion` feature (which implies `parquet`),
+//! [`ObjectStoreClient::register_parquet_table`] registers every `.parquet`
+//! object under a prefix as one table of a DataFusion `SessionContext`:
+//!
+//! ```no_run
+//! # #[cfg(feature = "datafusion")]
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use go_objstore::analytics::datafusion::prelude::SessionContext;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let ctx = SessionContext::new();
+//! client.register_parquet_table(&ctx, "orders", "warehouse/orders/").await?;
+//! ctx.sql("SELECT region, sum(total) FROM orders GROUP BY region")
+//!     .await?
+//!     .show()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The table reads through an [`ObjectStoreAdapter`], a read-only
+//! [`object_store`] implementation over the client: DataFusion lists the
+//! prefix, plans with each file's footer, and fetches the byte ranges it
+//! needs.  Range reads are REST-only; other transports download each
+//! object a read touches.  The `parquet` and `datafusion` crates are
+//! re-exported so callers use the versions this crate was built with.
+
+use crate::client::ObjectStoreClient;
+use crate::error::{Error, Result};
+use crate::reader::ObjectReader;
+use futures::TryStreamExt;
+use parquet::arrow::ParquetRecordBatchStreamBuilder;
+use parquet::errors::ParquetError;
+
+pub use arrow_array::RecordBatch;
+#[cfg(feature = "datafusion")]
+pub use datafusion;
+pub use parquet;
+
+#[cfg(feature = "datafusion")]
+pub use table::ObjectStoreAdapter;
+
+impl ObjectStoreClient {
+    /// Open the Parquet object `key` for reading record batches.  See the
+    /// [`analytics`](crate::analytics) module.
+    pub async fn open_parquet(
+        &self,
+        key: &str,
+    ) -> Result<ParquetRecordBatchStreamBuilder<ObjectReader>> {
+        let reader = self.reader(key).await?;
+        ParquetRecordBatchStreamBuilder::new(reader)
+            .await
+            .map_err(|e| parquet_error(key, e))
+    }
+
+    /// Read every record batch of the Parquet object `key`.
+    pub async fn read_parquet(&self, key: &str) -> Result<Vec<RecordBatch>> {
+        self.open_parquet(key)
+            .await?
+            .build()
+            .map_err(|e| parquet_error(key, e))?
+            .try_collect()
+            .await
+            .map_err(|e| parquet_error(key, e))
+    }
+}
+
+fn parquet_error(key: &str, e: ParquetError) -> Error {
+    Error::OperationFailed(format!("reading parquet object {key}: {e}"))
+}
+
+#[cfg(feature = "datafusion")]
+mod table {
+    use crate::client::{ObjectStore as _, ObjectStoreClient, LIST_PAGE_SIZE};
+    use crate::error::{Error, Result};
+    use crate::types::{ListRequest, ListResponse, Metadata};
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use datafusion::prelude::{ParquetReadOptions, SessionContext};
+    use futures::stream::BoxStream;
+    use futures::{StreamExt, TryStreamExt};
+    use object_store::path::Path;
+    use object_store::{
+        CopyOptions, GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload,
+        ObjectMeta, PutMultipartOptions, PutOptions, PutPayload, PutResult,
+    };
+    use std::ops::Range;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    /// Name reported in `object_store` errors.
+    const STORE: &str = "go-objstore";
+    /// URL scheme the adapters are registered under.
+    const SCHEME: &str = "goobjstore";
+
+    /// Distinguishes the adapters registered by each call.
+    static NEXT_STORE: AtomicU64 = AtomicU64::new(0);
+
+    /// A read-only [`object_store::ObjectStore`] over a client, for query
+    /// engines built on the `object_store` crate.  Writes, copies, and
+    /// deletes fail with `NotImplemented`.
+    #[derive(Clone)]
+    pub struct ObjectStoreAdapter {
+        client: ObjectStoreClient,
+    }
+
+    impl ObjectStoreAdapter {
+        /// Serve `client`'s objects.
+        pub fn new(client: ObjectStoreClient) -> Self {
+            Self { client }
+        }
+
+        /// The bytes of `range` of `key`: a `Range` request on REST, the
+        /// whole object cut down elsewhere.
+        async fn fetch(&self, key: &str, range: Range<u64>) -> Result<Bytes> {
+            let (data, at) = match &self.client {
+                ObjectStoreClient::Rest(rest) => match rest.get_range(key, range.clone()).await? {
+                    (data, Some(start)) => (data, start),
+                    (data, None) => (data, 0),
+                },
+                client => (client.get(key).await?.0, 0),
+            };
+            let start = range.start.checked_sub(at);
+            let end = (range.end - at.min(range.end)) as usize;
+            match start {
+                Some(start) if end <= data.len() => Ok(data.slice(start as usize..end)),
+                _ => Err(Error::InvalidResponse(format!(
+                    "short read of {key} bytes {}-{}",
+                    range.start, range.end
+                ))),
+            }
+        }
+    }
+
+    impl std::fmt::Debug for ObjectStoreAdapter {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ObjectStoreAdapter")
+                .field("protocol", &self.client.protocol())
+                .finish()
+        }
+    }
+
+    impl std::fmt::Display for ObjectStoreAdapter {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{STORE}({})", self.client.protocol())
+        }
+    }
+
+    #[async_trait]
+    impl object_store::ObjectStore for ObjectStoreAdapter {
+        async fn put_opts(
+            &self,
+            _: &Path,
+            _: PutPayload,
+            _: PutOptions,
+        ) -> object_store::Result<PutResult> {
+            Err(read_only("put"))
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            _: &Path,
+            _: PutMultipartOptions,
+        ) -> object_store::Result<Box<dyn MultipartUpload>> {
+            Err(read_only("put_multipart"))
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            let key = location.as_ref();
+            let metadata = self
+                .client
+                .get_metadata(key)
+                .await
+                .map_err(|e| store_error(key, e))?;
+            let meta = object_meta(location.clone(), &metadata);
+            options.check_preconditions(&meta)?;
+            let range = match &options.range {
+                Some(range) => {
+                    range
+                        .as_range(meta.size)
+                        .map_err(|e| object_store::Error::Generic {
+                            store: STORE,
+                            source: Box::new(e),
+                        })?
+                }
+                None => 0..meta.size,
+            };
+            let data = if options.head || range.is_empty() {
+                Bytes::new()
+            } else {
+                self.fetch(key, range.clone())
+                    .await
+                    .map_err(|e| store_error(key, e))?
+            };
+            Ok(GetResult {
+                payload: GetResultPayload::Stream(
+                    futures::stream::once(async { Ok(data) }).boxed(),
+                ),
+                meta,
+                range,
+                attributes: Default::default(),
+            })
+        }
+
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, object_store::Result<Path>>,
+        ) -> BoxStream<'static, object_store::Result<Path>> {
+            locations.map(|_| Err(read_only("delete"))).boxed()
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> BoxStream<'static, object_store::Result<ObjectMeta>> {
+            pages(self.client.clone(), list_request(prefix, None))
+                .map_ok(|page| futures::stream::iter(page.objects).map(Ok))
+                .try_flatten()
+                // Directory markers have no `object_store` path.
+                .try_filter(|object| futures::future::ready(!object.key.ends_with('/')))
+                .map_ok(|object| object_meta(Path::from(object.key.as_str()), &object.metadata))
+                .map_err(|e| store_error("", e))
+                .boxed()
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            let mut result = ListResult {
+                common_prefixes: Vec::new(),
+                objects: Vec::new(),
+            };
+            let mut listing = pages(self.client.clone(), list_request(prefix, Some("/")));
+            while let Some(page) = listing.next().await {
+                let page = page.map_err(|e| store_error("", e))?;
+                result.common_prefixes.extend(
+                    page.common_prefixes
+                        .iter()
+                        .map(|p| Path::from(p.trim_end_matches('/'))),
+                );
+                result.objects.extend(
+                    page.objects
+                        .iter()
+                        .filter(|o| !o.key.ends_with('/'))
+                        .map(|o| object_meta(Path::from(o.key.as_str()), &o.metadata)),
+                );
+            }
+            Ok(result)
+        }
+
+        async fn copy_opts(&self, _: &Path, _: &Path, _: CopyOptions) -> object_store::Result<()> {
+            Err(read_only("copy"))
+        }
+    }
+
+    impl ObjectStoreClient {
+        /// Register the `.parquet` objects under `prefix` as the DataFusion
+        /// table `table`.  See the [`analytics`](crate::analytics) module.
+        pub async fn register_parquet_table(
+            &self,
+            ctx: &SessionContext,
+            table: &str,
+            prefix: &str,
+        ) -> Result<()> {
+            let id = NEXT_STORE.fetch_add(1, Ordering::Relaxed);
+            let base = url::Url::parse(&format!("{SCHEME}://store-{id}/"))?;
+            ctx.register_object_store(&base, Arc::new(ObjectStoreAdapter::new(self.clone())));
+            let location = base.join(prefix)?;
+            ctx.register_parquet(table, location.as_str(), ParquetReadOptions::default())
+                .await
+                .map_err(|e| Error::OperationFailed(format!("registering table {table}: {e}")))
+        }
+    }
+
+    fn list_request(prefix: Option<&Path>, delimiter: Option<&str>) -> ListRequest {
+        ListRequest {
+            prefix: prefix
+                .filter(|p| !p.as_ref().is_empty())
+                .map(|p| format!("{p}/")),
+            delimiter: delimiter.map(str::to_string),
+            max_results: Some(LIST_PAGE_SIZE),
+            ..Default::default()
+        }
+    }
+
+    /// Listing pages from an owned client, so the stream is `'static`.
+    fn pages(
+        client: ObjectStoreClient,
+        request: ListRequest,
+    ) -> BoxStream<'static, Result<ListResponse>> {
+        futures::stream::try_unfold(Some(request), move |next| {
+            let client = client.clone();
+            async move {
+                let Some(req) = next else {
+                    return Ok(None);
+                };
+                let page = client.list(req.clone()).await?;
+                let next = match (&page.next_token, page.truncated) {
+                    (Some(token), true) if !token.is_empty() => Some(ListRequest {
+                        continue_from: Some(token.clone()),
+                        ..req
+                    }),
+                    _ => None,
+                };
+                Ok(Some((page, next)))
+            }
+        })
+        .boxed()
+    }
+
+    fn object_meta(location: Path, metadata: &Metadata) -> ObjectMeta {
+        ObjectMeta {
+            location,
+            last_modified: metadata.last_modified.unwrap_or_default(),
+            size: metadata.size.max(0) as u64,
+            e_tag: metadata.etag.clone(),
+            version: None,
+        }
+    }
+
+    fn store_error(key: &str, e: Error) -> object_store::Error {
+        match e {
+            Error::NotFound(_) => object_store::Error::NotFound {
+                path: key.to_string(),
+                source: Box::new(e),
+            },
+            Error::PreconditionFailed(_) => object_store::Error::Precondition {
+                path: key.to_string(),
+                source: Box::new(e),
+            },
+            e => object_store::Error::Generic {
+                store: STORE,
+                source: Box::new(e),
+            },
+        }
+    }
+
+    fn read_only(operation: &str) -> object_store::Error {
+        object_store::Error::NotImplemented {
+            operation: operation.to_string(),
+            implementer: "read-only ObjectStoreAdapter".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use arrow_array::{Int64Array, StringArray};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    fn orders(regions: &[&str], totals: &[i64]) -> Vec<u8> {
+        let batch = RecordBatch::try_from_iter([
+            (
+                "region",
+                Arc::new(StringArray::from(regions.to_vec())) as Arc<dyn arrow_array::Array>,
+            ),
+            ("total", Arc::new(Int64Array::from(totals.to_vec())) as _),
+        ])
+        .unwrap();
+        let mut file = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut file, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        file
+    }
+
+    /// Serve `file` under `key` from a server that ignores `Range`.
+    async fn serve(server: &mut mockito::Server, key: &str, file: Vec<u8>) {
+        let path = key.replace('/', "%2F");
+        server
+            .mock("GET", format!("/metadata/{path}").as_str())
+            .with_body(format!(r#"{{"key":"{key}","size":{}}}"#, file.len()))
+            .create_async()
+            .await;
+        server
+            .mock("GET", format!("/objects/{path}").as_str())
+            .with_body(file)
+            .create_async()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn reads_parquet_objects() {
+        let mut server = mockito::Server::new_async().await;
+        serve(
+            &mut server,
+            "t/orders.parquet",
+            orders(&["eu", "us"], &[5, 7]),
+        )
+        .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let builder = client.open_parquet("t/orders.parquet").await.unwrap();
+        assert_eq!(builder.metadata().file_metadata().num_rows(), 2);
+        let batches = client.read_parquet("t/orders.parquet").await.unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].schema().field(1).name(), "total");
+    }
+
+    #[tokio::test]
+    async fn rejects_non_parquet_objects() {
+        let mut server = mockito::Server::new_async().await;
+        serve(&mut server, "notes.txt", b"not a parquet file".to_vec()).await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let err = client.read_parquet("notes.txt").await.unwrap_err();
+        assert!(matches!(err, Error::OperationFailed(_)), "{err:?}");
+    }
+
+    #[cfg(feature = "datafusion")]
+    #[tokio::test]
+    async fn queries_a_registered_prefix() {
+        use datafusion::prelude::SessionContext;
+        use mockito::Matcher;
+
+        let a = orders(&["eu", "us"], &[5, 7]);
+        let b = orders(&["eu"], &[30]);
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded(
+                "prefix".into(),
+                "warehouse/orders/".into(),
+            ))
+            .with_body(format!(
+                r#"{{"objects":[{{"key":"warehouse/orders/","size":0}},
+                               {{"key":"warehouse/orders/a.parquet","size":{}}},
+                               {{"key":"warehouse/orders/b.parquet","size":{}}}]}}"#,
+                a.len(),
+                b.len()
+            ))
+            .create_async()
+            .await;
+        serve(&mut server, "warehouse/orders/a.parquet", a).await;
+        serve(&mut server, "warehouse/orders/b.parquet", b).await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let ctx = SessionContext::new();
+        client
+            .register_parquet_table(&ctx, "orders", "warehouse/orders/")
+            .await
+            .unwrap();
+        let batches = ctx
+            .sql("SELECT region, sum(total) AS total FROM orders GROUP BY region ORDER BY region")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let batch = &batches[0];
+        let totals = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(totals.values(), &[35, 7]);
+    }
+}
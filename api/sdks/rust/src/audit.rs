@@ -0,0 +1,333 @@
+//! Typed access to the server's audit trail.
+//!
+//! go-objstore servers write one JSON line per audited request (who, what,
+//! which key, when, and whether it succeeded) through their audit logger,
+//! but expose no endpoint to query it; the log goes wherever the server's
+//! audit output points.  Deployments that ship that output into the store,
+//! e.g. one object per day under `audit/`, can read it back with
+//! [`ObjectStoreClient::get_audit_events`], which streams the log object
+//! and yields the [`AuditEvent`]s matching an [`AuditFilter`]:
+//!
+//! ```no_run
+//! use chrono::{Duration, Utc};
+//! use futures::StreamExt;
+//! use go_objstore::audit::{AuditFilter, AuditResult};
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let filter = AuditFilter {
+//!     since: Some(Utc::now() - Duration::hours(1)),
+//!     result: Some(AuditResult::Failure),
+//!     ..Default::default()
+//! };
+//! let mut events = client.get_audit_events("audit/2026-10-15.log", filter).await?;
+//! while let Some(event) = events.next().await {
+//!     let event = event?;
+//!     println!("{:?} {} {:?}", event.principal, event.action, event.key);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`audit_events`] does the same for any byte stream, such as a log file
+//! opened locally.  Lines that are not audit records (other server logs
+//! interleaved on the same output, or text-format lines) are skipped.
+
+use crate::client::{ByteStream, ObjectStore, ObjectStoreClient};
+use crate::error::Result;
+use crate::timestamp::parse_timestamp;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde::Deserialize;
+
+/// Outcome of an audited operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditResult {
+    Success,
+    Failure,
+    /// A result this SDK does not know.
+    #[serde(other)]
+    Unknown,
+}
+
+/// One audited request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// When the request was handled.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Event category, e.g. `OBJECT_CREATED` or `AUTH_FAILURE`.
+    pub event_type: String,
+    /// Operation performed, e.g. `PUT` or `grpc:/objstore.v1.ObjectStore/Get`.
+    pub action: String,
+    pub result: AuditResult,
+    pub user_id: Option<String>,
+    pub principal: Option<String>,
+    pub bucket: Option<String>,
+    pub key: Option<String>,
+    /// Error message of a failed request.
+    pub error: Option<String>,
+    pub ip_address: Option<String>,
+    pub request_id: Option<String>,
+    pub method: Option<String>,
+    pub status_code: Option<u16>,
+}
+
+#[derive(Deserialize)]
+struct RawEvent {
+    #[serde(default)]
+    timestamp: String,
+    event_type: String,
+    #[serde(default)]
+    action: String,
+    result: AuditResult,
+    user_id: Option<String>,
+    principal: Option<String>,
+    bucket: Option<String>,
+    key: Option<String>,
+    error: Option<String>,
+    ip_address: Option<String>,
+    request_id: Option<String>,
+    method: Option<String>,
+    status_code: Option<u16>,
+}
+
+impl From<RawEvent> for AuditEvent {
+    fn from(raw: RawEvent) -> Self {
+        Self {
+            timestamp: parse_timestamp(&raw.timestamp),
+            event_type: raw.event_type,
+            action: raw.action,
+            result: raw.result,
+            user_id: raw.user_id,
+            principal: raw.principal,
+            bucket: raw.bucket,
+            key: raw.key,
+            error: raw.error,
+            ip_address: raw.ip_address,
+            request_id: raw.request_id,
+            method: raw.method,
+            status_code: raw.status_code,
+        }
+    }
+}
+
+impl AuditEvent {
+    /// Parse one line of the server's JSON audit output, or `None` if the
+    /// line is not an audit record.
+    pub fn parse_line(line: &[u8]) -> Option<Self> {
+        serde_json::from_slice::<RawEvent>(line)
+            .ok()
+            .map(Into::into)
+    }
+}
+
+/// Which [`AuditEvent`]s to return.  Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only events at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only events before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Only events by this principal (or user ID).
+    pub principal: Option<String>,
+    /// Only events on keys under this prefix.
+    pub key_prefix: Option<String>,
+    /// Only events of these types; empty matches all.
+    pub event_types: Vec<String>,
+    pub result: Option<AuditResult>,
+}
+
+impl AuditFilter {
+    /// Whether `event` passes the filter.  Events without a timestamp fail
+    /// any time bound.
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        let in_window = match (self.since, self.until) {
+            (None, None) => true,
+            (since, until) => event
+                .timestamp
+                .is_some_and(|t| since.is_none_or(|s| t >= s) && until.is_none_or(|u| t < u)),
+        };
+        in_window
+            && self.principal.as_deref().is_none_or(|p| {
+                event.principal.as_deref() == Some(p) || event.user_id.as_deref() == Some(p)
+            })
+            && self
+                .key_prefix
+                .as_deref()
+                .is_none_or(|p| event.key.as_deref().is_some_and(|k| k.starts_with(p)))
+            && (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && self.result.is_none_or(|r| event.result == r)
+    }
+}
+
+/// Parse `body` as newline-delimited audit output and yield the events
+/// matching `filter`.  Errors reading `body` end the stream.
+pub fn audit_events(
+    body: ByteStream,
+    filter: AuditFilter,
+) -> BoxStream<'static, Result<AuditEvent>> {
+    futures::stream::unfold(
+        (body, Vec::new(), false),
+        |(mut body, mut buf, mut eof)| async move {
+            loop {
+                if let Some(end) = buf.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = buf.drain(..=end).collect();
+                    return Some((Ok(line), (body, buf, eof)));
+                }
+                if eof {
+                    if buf.is_empty() {
+                        return None;
+                    }
+                    let line = std::mem::take(&mut buf);
+                    return Some((Ok(line), (body, buf, eof)));
+                }
+                match body.next().await {
+                    Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                    Some(Err(e)) => return Some((Err(e), (body, Vec::new(), true))),
+                    None => eof = true,
+                }
+            }
+        },
+    )
+    .filter_map(move |line| {
+        let event = match line {
+            Ok(line) => AuditEvent::parse_line(&line)
+                .filter(|event| filter.matches(event))
+                .map(Ok),
+            Err(e) => Some(Err(e)),
+        };
+        futures::future::ready(event)
+    })
+    .boxed()
+}
+
+impl ObjectStoreClient {
+    /// Stream the events matching `filter` from the audit log stored at
+    /// `log_key`.  See the [`audit`](crate::audit) module.
+    pub async fn get_audit_events(
+        &self,
+        log_key: &str,
+        filter: AuditFilter,
+    ) -> Result<BoxStream<'static, Result<AuditEvent>>> {
+        let (body, _) = self.get_stream(log_key).await?;
+        Ok(audit_events(body, filter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::rest_client::RestClient;
+    use bytes::Bytes;
+
+    const LOG: &str = concat!(
+        r#"{"time":"2026-10-15T09:00:00Z","level":"INFO","msg":"Audit event: PUT","timestamp":"2026-10-15T09:00:00Z","event_type":"OBJECT_CREATED","action":"PUT","result":"SUCCESS","principal":"alice","key":"orders/1.json","status_code":201}"#,
+        "\n",
+        "server listening on :8080\n",
+        r#"{"time":"2026-10-15T10:00:00Z","level":"INFO","msg":"Audit event: GET","timestamp":"2026-10-15T10:00:00Z","event_type":"OBJECT_ACCESSED","action":"GET","result":"FAILURE","user_id":"bob","key":"orders/2.json","error":"forbidden"}"#,
+        "\n",
+        r#"{"time":"2026-10-15T11:00:00Z","level":"INFO","msg":"Audit event: DELETE","timestamp":"2026-10-15T11:00:00Z","event_type":"OBJECT_DELETED","action":"DELETE","result":"SUCCESS","principal":"alice","key":"tmp/x"}"#,
+    );
+
+    fn chunked(log: &'static str, size: usize) -> ByteStream {
+        let chunks: Vec<Result<Bytes>> = log
+            .as_bytes()
+            .chunks(size)
+            .map(|c| Ok(Bytes::copy_from_slice(c)))
+            .collect();
+        futures::stream::iter(chunks).boxed()
+    }
+
+    #[tokio::test]
+    async fn parses_lines_split_across_chunks() {
+        let events: Vec<AuditEvent> = audit_events(chunked(LOG, 7), AuditFilter::default())
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].principal.as_deref(), Some("alice"));
+        assert_eq!(events[0].status_code, Some(201));
+        assert_eq!(events[1].result, AuditResult::Failure);
+        assert_eq!(events[1].error.as_deref(), Some("forbidden"));
+        assert_eq!(events[2].key.as_deref(), Some("tmp/x"));
+    }
+
+    #[test]
+    fn filters_combine() {
+        let events: Vec<AuditEvent> = LOG
+            .lines()
+            .filter_map(|l| AuditEvent::parse_line(l.as_bytes()))
+            .collect();
+        let count = |filter: AuditFilter| events.iter().filter(|e| filter.matches(e)).count();
+
+        assert_eq!(
+            count(AuditFilter {
+                principal: Some("bob".into()),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(AuditFilter {
+                key_prefix: Some("orders/".into()),
+                result: Some(AuditResult::Success),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(AuditFilter {
+                since: "2026-10-15T09:30:00Z".parse().ok(),
+                until: "2026-10-15T11:00:00Z".parse().ok(),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(AuditFilter {
+                event_types: vec!["OBJECT_DELETED".into(), "OBJECT_CREATED".into()],
+                ..Default::default()
+            }),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_the_log_object() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects/audit%2F2026-10-15.log")
+            .with_status(200)
+            .with_body(LOG)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/audit%2Fmissing.log")
+            .with_status(404)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let filter = AuditFilter {
+            principal: Some("alice".into()),
+            ..Default::default()
+        };
+        let events: Vec<_> = client
+            .get_audit_events("audit/2026-10-15.log", filter)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            client
+                .get_audit_events("audit/missing.log", AuditFilter::default())
+                .await,
+            Err(Error::NotFound(_))
+        ));
+    }
+}
@@ -1,5 +1,7 @@
+use crate::secret::{SecretString, REDACTED};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 
 /// Optional authentication/tenant configuration added to every client.
@@ -11,10 +13,12 @@ use std::str::FromStr;
 ///
 /// Unix-socket connections perform no header injection (auth is handled
 /// by the server via peer credentials).
-#[derive(Debug, Clone, Default)]
+///
+/// `Debug` output redacts the token and all extra header values.
+#[derive(Clone, Default)]
 pub struct AuthConfig {
     /// Bearer token inserted as `Authorization: Bearer <token>`.
-    pub token: Option<String>,
+    pub token: Option<SecretString>,
 
     /// Arbitrary extra headers forwarded on every request.
     pub extra_headers: HashMap<String, String>,
@@ -38,8 +42,9 @@ impl AuthConfig {
         let mut map = HeaderMap::new();
 
         if let Some(token) = &self.token {
-            let value = format!("Bearer {token}");
-            if let Ok(v) = HeaderValue::from_str(&value) {
+            let value = bearer(token);
+            if let Ok(mut v) = HeaderValue::from_str(value.expose_secret()) {
+                v.set_sensitive(true);
                 map.insert(reqwest::header::AUTHORIZATION, v);
             }
         }
@@ -75,12 +80,14 @@ impl AuthConfig {
         let mut entries = Vec::new();
 
         if let Some(token) = &self.token {
-            let value = format!("Bearer {token}");
+            let value = bearer(token);
             if let (Ok(k), Ok(v)) = (
                 tonic::metadata::MetadataKey::<tonic::metadata::Ascii>::from_bytes(
                     b"authorization",
                 ),
-                tonic::metadata::MetadataValue::<tonic::metadata::Ascii>::try_from(value.as_str()),
+                tonic::metadata::MetadataValue::<tonic::metadata::Ascii>::try_from(
+                    value.expose_secret(),
+                ),
             ) {
                 entries.push((k, v));
             }
@@ -108,6 +115,25 @@ impl AuthConfig {
     }
 }
 
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: HashMap<&str, &str> = self
+            .extra_headers
+            .keys()
+            .map(|k| (k.as_str(), REDACTED))
+            .collect();
+        f.debug_struct("AuthConfig")
+            .field("token", &self.token)
+            .field("extra_headers", &headers)
+            .field("tenant_id", &self.tenant_id)
+            .finish()
+    }
+}
+
+fn bearer(token: &SecretString) -> SecretString {
+    SecretString::new(format!("Bearer {}", token.expose_secret()))
+}
+
 /// Apply [`AuthConfig`] headers to a [`reqwest::RequestBuilder`].
 pub fn apply_auth(builder: reqwest::RequestBuilder, auth: &AuthConfig) -> reqwest::RequestBuilder {
     if auth.is_empty() {
@@ -128,7 +154,7 @@ mod tests {
     #[test]
     fn auth_config_token_sets_authorization_header() {
         let auth = AuthConfig {
-            token: Some("secret".to_string()),
+            token: Some("secret".into()),
             ..Default::default()
         };
         assert!(!auth.is_empty());
@@ -175,7 +201,7 @@ mod tests {
         let mut extra = HashMap::new();
         extra.insert("x-req-id".to_string(), "123".to_string());
         let auth = AuthConfig {
-            token: Some("tok".to_string()),
+            token: Some("tok".into()),
             tenant_id: Some("tenant1".to_string()),
             extra_headers: extra,
         };
@@ -185,4 +211,19 @@ mod tests {
         assert!(map.contains_key("x-tenant-id"));
         assert!(map.contains_key("x-req-id"));
     }
+
+    #[test]
+    fn auth_config_debug_redacts_secrets() {
+        let mut extra = HashMap::new();
+        extra.insert("x-api-key".to_string(), "k3y".to_string());
+        let auth = AuthConfig {
+            token: Some("tok".into()),
+            tenant_id: Some("tenant1".to_string()),
+            extra_headers: extra,
+        };
+        let debug = format!("{auth:?}");
+        assert!(!debug.contains("tok\"") && !debug.contains("k3y"));
+        assert!(debug.contains("x-api-key") && debug.contains("tenant1"));
+        assert!(auth.to_header_map()[reqwest::header::AUTHORIZATION].is_sensitive());
+    }
 }
@@ -0,0 +1,289 @@
+//! Managing the storage backends a server serves.
+//!
+//! [`list_backends`](ObjectStoreClient::list_backends),
+//! [`add_backend`](ObjectStoreClient::add_backend) and
+//! [`remove_backend`](ObjectStoreClient::remove_backend) wrap a server's
+//! backend administration endpoints, so automation can reconfigure storage
+//! without shelling out to the Go CLI:
+//!
+//! ```no_run
+//! use go_objstore::backends::BackendConfig;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! client
+//!     .add_backend(BackendConfig {
+//!         id: "archive".into(),
+//!         backend_type: "s3".into(),
+//!         settings: [("bucket".to_string(), "cold".to_string())].into(),
+//!     })
+//!     .await?;
+//! for backend in client.list_backends().await? {
+//!     println!("{} ({})", backend.id, backend.backend_type);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The endpoints are `GET` and `POST` on [`BACKENDS_PATH`] and `DELETE` on
+//! `BACKENDS_PATH/{id}`, over REST and QUIC.  The go-objstore servers in
+//! this repository do not serve them yet: each process serves the one
+//! backend chosen at startup.  A server that answers 404 (without a JSON
+//! error body), 405, or 501, and the gRPC, MCP, and Unix-socket transports,
+//! fail with [`Error::UnsupportedByProtocol`] — changing backends there
+//! still means reconfiguring and restarting the server.
+//!
+//! Backend settings can hold credentials; [`list_backends`] returns them as
+//! the server reports them.
+//!
+//! [`list_backends`]: ObjectStoreClient::list_backends
+
+use crate::client::{ObjectStoreClient, Operation};
+use crate::error::{error_from_http_status, Error, Result};
+use crate::response::RawResponse;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Path of the backend administration endpoints.
+pub const BACKENDS_PATH: &str = "/admin/backends";
+
+/// A storage backend, as added with
+/// [`add_backend`](ObjectStoreClient::add_backend).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendConfig {
+    /// Name the backend is addressed by.
+    pub id: String,
+    /// Backend type, e.g. `local`, `s3`, `gcs`.
+    #[serde(rename = "type")]
+    pub backend_type: String,
+    /// Type-specific settings, as taken by the Go CLI's `--backend-*` flags.
+    #[serde(default)]
+    pub settings: HashMap<String, String>,
+}
+
+impl ObjectStoreClient {
+    /// The backends the server serves.  See the
+    /// [`backends`](crate::backends) module.
+    pub async fn list_backends(&self) -> Result<Vec<BackendConfig>> {
+        #[derive(Deserialize)]
+        struct ListResponse {
+            #[serde(default)]
+            backends: Vec<BackendConfig>,
+        }
+
+        let response = self
+            .admin(
+                Operation::ListBackends,
+                http::Method::GET,
+                BACKENDS_PATH,
+                None,
+            )
+            .await?;
+        let parsed: ListResponse = serde_json::from_slice(&response.body)?;
+        Ok(parsed.backends)
+    }
+
+    /// Add `backend` to the server.  An existing backend with the same id
+    /// fails with [`Error::AlreadyExists`].
+    pub async fn add_backend(&self, backend: BackendConfig) -> Result<()> {
+        if backend.id.is_empty() {
+            return Err(Error::InvalidArgument(
+                "backend id must not be empty".to_string(),
+            ));
+        }
+        let body = Bytes::from(serde_json::to_vec(&backend)?);
+        self.admin(
+            Operation::AddBackend,
+            http::Method::POST,
+            BACKENDS_PATH,
+            Some(body),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Remove the backend named `id`.  A backend the server does not have
+    /// fails with [`Error::NotFound`].
+    pub async fn remove_backend(&self, id: &str) -> Result<()> {
+        if id.is_empty() {
+            return Err(Error::InvalidArgument(
+                "backend id must not be empty".to_string(),
+            ));
+        }
+        let path = format!("{BACKENDS_PATH}/{}", urlencoding::encode(id));
+        self.admin(Operation::RemoveBackend, http::Method::DELETE, &path, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Send one backend administration request, mapping an unserved
+    /// endpoint to [`Error::UnsupportedByProtocol`] and other error
+    /// statuses to the canonical errors.
+    async fn admin(
+        &self,
+        operation: Operation,
+        method: http::Method,
+        path: &str,
+        body: Option<Bytes>,
+    ) -> Result<RawResponse> {
+        if !matches!(
+            self,
+            ObjectStoreClient::Rest(_) | ObjectStoreClient::Quic(_)
+        ) {
+            return Err(self.unsupported(operation));
+        }
+        let headers: &[(&str, &str)] = if body.is_some() {
+            &[("content-type", "application/json")]
+        } else {
+            &[]
+        };
+        let response = self.raw_request(method, path, headers, body).await?;
+        if response.is_success() {
+            return Ok(response);
+        }
+        // A 404 with a JSON body is the handler's: the backend is missing.
+        let json = response
+            .headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        match response.status {
+            404 if !json => Err(self.unsupported(operation)),
+            405 | 501 => Err(self.unsupported(operation)),
+            status => Err(error_from_http_status(
+                status,
+                None,
+                format!(
+                    "{operation} failed: {}",
+                    String::from_utf8_lossy(&response.body)
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Protocol;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn rest_lists_adds_and_removes_backends() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/admin/backends")
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"backends":[{"id":"default","type":"local","settings":{"path":"/data"}}]}"#,
+            )
+            .create_async()
+            .await;
+        let add = server
+            .mock("POST", "/admin/backends")
+            .match_body(Matcher::Json(serde_json::json!({
+                "id": "archive",
+                "type": "s3",
+                "settings": {"bucket": "cold"},
+            })))
+            .with_status(201)
+            .create_async()
+            .await;
+        let remove = server
+            .mock("DELETE", "/admin/backends/old%20disk")
+            .with_status(204)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let backends = client.list_backends().await.unwrap();
+        assert_eq!(backends.len(), 1);
+        assert_eq!(backends[0].id, "default");
+        assert_eq!(backends[0].backend_type, "local");
+        assert_eq!(backends[0].settings["path"], "/data");
+
+        client
+            .add_backend(BackendConfig {
+                id: "archive".into(),
+                backend_type: "s3".into(),
+                settings: [("bucket".to_string(), "cold".to_string())].into(),
+            })
+            .await
+            .unwrap();
+        client.remove_backend("old disk").await.unwrap();
+        add.assert_async().await;
+        remove.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn unserved_endpoint_is_unsupported() {
+        let mut server = mockito::Server::new_async().await;
+        // The router's bare 404: no admin endpoints.
+        server
+            .mock("GET", "/admin/backends")
+            .with_status(404)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let err = client.list_backends().await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::UnsupportedByProtocol {
+                    operation: Operation::ListBackends,
+                    protocol: Protocol::Rest,
+                }
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn handler_errors_map_to_canonical_errors() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("DELETE", "/admin/backends/gone")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":"backend not found"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/admin/backends")
+            .with_status(409)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":"backend exists"}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let err = client.remove_backend("gone").await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)), "{err:?}");
+        let err = client
+            .add_backend(BackendConfig {
+                id: "default".into(),
+                backend_type: "local".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::AlreadyExists(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn other_transports_are_unsupported_without_a_request() {
+        let client = ObjectStoreClient::unix("/run/objstore.sock").unwrap();
+        let err = client.remove_backend("archive").await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedByProtocol {
+                operation: Operation::RemoveBackend,
+                protocol: Protocol::Unix,
+            }
+        ));
+    }
+}
@@ -0,0 +1,395 @@
+//! Bounded memory for buffered downloads.
+//!
+//! A `get` holds the whole object in memory, so a burst of concurrent large
+//! reads can grow a service without bound.  A [`MemoryBudget`] in
+//! [`ClientConfig::memory_budget`] caps the bytes a client and its clones
+//! hold from buffered downloads at once:
+//!
+//! ```no_run
+//! use go_objstore::{ClientConfig, MemoryBudget, ObjectStore, RestClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     memory_budget: Some(MemoryBudget::new(256 * 1024 * 1024)),
+//!     ..Default::default()
+//! };
+//! let client = RestClient::new_with_config("http://localhost:8080", config)?;
+//! let (data, _) = client.get("exports/huge.csv").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A download reserves its length from the budget before reading the body,
+//! waiting while other downloads hold the memory.  A body larger than the
+//! whole budget is [over budget](OverBudget): it fails with
+//! [`Error::PayloadTooLarge`] or, with the `spill` feature and
+//! [`MemoryBudget::spill_to`], is written to an anonymous temporary file and
+//! returned as a memory-mapped [`Bytes`] backed by the page cache instead of
+//! the heap.  Bodies of unknown length reserve memory as they arrive without
+//! waiting, and are over budget as soon as a reservation cannot be met.
+//!
+//! The reservation travels with the returned [`Bytes`]: it is released when
+//! the bytes and every clone and slice of them are dropped, so the budget
+//! bounds what callers hold as well as what the client reads.  Bytes that
+//! are decompressed after reading are counted as received.  Streaming calls
+//! (`get_stream`, `put_stream`, `put_mmap`) do not buffer and are not
+//! counted, so prefer them for objects of unbounded size.  The budget is
+//! enforced by the REST, gRPC, and QUIC clients; the Unix socket and MCP
+//! clients ignore it.
+//!
+//! [`ClientConfig::memory_budget`]: crate::ClientConfig::memory_budget
+
+use crate::error::{Error, Result};
+use crate::in_flight::body_stream;
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+#[cfg(feature = "spill")]
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::Arc;
+#[cfg(feature = "spill")]
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Granularity of reservations: the budget is counted in KiB.
+const UNIT: u64 = 1024;
+
+/// A cap on the bytes a client buffers across concurrent operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryBudget {
+    /// Total bytes held from buffered downloads at once.
+    pub max_bytes: u64,
+    /// What happens to a download that does not fit.
+    pub over_budget: OverBudget,
+}
+
+/// How a download that does not fit in the [`MemoryBudget`] is handled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OverBudget {
+    /// Fail with [`Error::PayloadTooLarge`], whose `limit` is the budget.
+    #[default]
+    Reject,
+    /// Write the body to an anonymous temporary file in this directory and
+    /// return it memory-mapped.  Requires the `spill` feature.
+    #[cfg(feature = "spill")]
+    Spill(PathBuf),
+}
+
+impl MemoryBudget {
+    /// Cap buffering at `max_bytes`, rejecting downloads that do not fit.
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            over_budget: OverBudget::Reject,
+        }
+    }
+
+    /// Spill downloads that do not fit to temporary files in `dir`.
+    #[cfg(feature = "spill")]
+    pub fn spill_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.over_budget = OverBudget::Spill(dir.into());
+        self
+    }
+}
+
+/// A client's memory budget, shared by its clones; unlimited by default.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Budget(Option<Arc<Limit>>);
+
+#[derive(Debug)]
+struct Limit {
+    config: MemoryBudget,
+    units: Arc<Semaphore>,
+}
+
+impl Budget {
+    pub(crate) fn new(config: Option<MemoryBudget>) -> Self {
+        Self(config.map(|config| {
+            let units = units(config.max_bytes).clamp(1, Semaphore::MAX_PERMITS as u64);
+            Arc::new(Limit {
+                units: Arc::new(Semaphore::new(units as usize)),
+                config,
+            })
+        }))
+    }
+
+    /// Read `response`'s body for `key` within the budget.
+    pub(crate) async fn read(&self, key: &str, response: reqwest::Response) -> Result<Bytes> {
+        match &self.0 {
+            None => Ok(response.bytes().await?),
+            Some(limit) => {
+                let length = response.content_length();
+                limit.collect(key, length, body_stream(response)).await
+            }
+        }
+    }
+
+    /// Buffer `body` for `key` within the budget; `length`, when known, is
+    /// reserved up front.
+    pub(crate) async fn collect(
+        &self,
+        key: &str,
+        length: Option<u64>,
+        body: impl Stream<Item = Result<Bytes>>,
+    ) -> Result<Bytes> {
+        match &self.0 {
+            None => {
+                let mut body = pin!(body);
+                let mut buffer = BytesMut::with_capacity(length.unwrap_or(0) as usize);
+                while let Some(chunk) = body.next().await {
+                    buffer.extend_from_slice(&chunk?);
+                }
+                Ok(buffer.freeze())
+            }
+            Some(limit) => limit.collect(key, length, body).await,
+        }
+    }
+}
+
+/// Downloaded bytes that keep their reservation until dropped.
+struct Reserved {
+    data: Bytes,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl AsRef<[u8]> for Reserved {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Whole units needed for `bytes`.
+fn units(bytes: u64) -> u64 {
+    bytes.div_ceil(UNIT)
+}
+
+/// [`units`] for `bytes`, capped at the largest single reservation.
+fn reservation(bytes: u64) -> u32 {
+    units(bytes).min(u32::MAX as u64) as u32
+}
+
+impl Limit {
+    async fn collect(
+        &self,
+        key: &str,
+        length: Option<u64>,
+        body: impl Stream<Item = Result<Bytes>>,
+    ) -> Result<Bytes> {
+        let mut body = pin!(body);
+        let mut buffer = BytesMut::new();
+        let mut held = match length {
+            Some(length) if length > self.config.max_bytes => {
+                return self.over_budget(key, Vec::new(), None, body).await;
+            }
+            Some(length) => {
+                // The semaphore is never closed.
+                let permit = self
+                    .units
+                    .clone()
+                    .acquire_many_owned(reservation(length))
+                    .await
+                    .expect("budget semaphore closed");
+                buffer.reserve(length as usize);
+                Some(permit)
+            }
+            None => None,
+        };
+
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk?;
+            let total = (buffer.len() + chunk.len()) as u64;
+            let have = held.as_ref().map_or(0, |p| p.num_permits() as u64);
+            let more = units(total).saturating_sub(have);
+            if more > 0 {
+                let permit = if total > self.config.max_bytes {
+                    None
+                } else {
+                    self.units.clone().try_acquire_many_owned(more as u32).ok()
+                };
+                match (permit, held.as_mut()) {
+                    (Some(permit), Some(held)) => held.merge(permit),
+                    (Some(permit), None) => held = Some(permit),
+                    (None, _) => {
+                        let head = vec![buffer.freeze(), chunk];
+                        return self.over_budget(key, head, held, body).await;
+                    }
+                }
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+        let data = buffer.freeze();
+        Ok(match held {
+            Some(permit) if !data.is_empty() => Bytes::from_owner(Reserved {
+                data,
+                _permit: permit,
+            }),
+            _ => data,
+        })
+    }
+
+    /// Handle a body that does not fit: `head` is what has been read so
+    /// far, under `held`.
+    #[cfg_attr(not(feature = "spill"), allow(unused_variables))]
+    async fn over_budget(
+        &self,
+        key: &str,
+        head: Vec<Bytes>,
+        held: Option<OwnedSemaphorePermit>,
+        body: impl Stream<Item = Result<Bytes>>,
+    ) -> Result<Bytes> {
+        match &self.config.over_budget {
+            OverBudget::Reject => Err(Error::PayloadTooLarge {
+                key: key.to_string(),
+                limit: self.config.max_bytes,
+            }),
+            #[cfg(feature = "spill")]
+            OverBudget::Spill(dir) => spill(dir, head, held, body).await,
+        }
+    }
+}
+
+/// Write `head` and the rest of `body` to a temporary file in `dir`,
+/// releasing `held` once `head` is on disk, and map the file.
+#[cfg(feature = "spill")]
+async fn spill(
+    dir: &Path,
+    head: Vec<Bytes>,
+    held: Option<OwnedSemaphorePermit>,
+    body: impl Stream<Item = Result<Bytes>>,
+) -> Result<Bytes> {
+    let mut file = tokio::fs::File::from_std(tempfile::tempfile_in(dir)?);
+    let mut len = head.iter().map(Bytes::len).sum::<usize>();
+    for chunk in head {
+        file.write_all(&chunk).await?;
+    }
+    drop(held);
+    let mut body = pin!(body);
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        len += chunk.len();
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    let file = file.into_std().await;
+    if len == 0 {
+        return Ok(Bytes::new());
+    }
+    // SAFETY: the file is anonymous, so nothing else can truncate or
+    // rewrite it, and the mapping is read-only.
+    let mmap = unsafe { memmap2::Mmap::map(&file) }?;
+    Ok(Bytes::from_owner(mmap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientConfig;
+    use crate::rest_client::RestClient;
+    use futures::stream;
+
+    fn limit(max_bytes: u64, over_budget: OverBudget) -> Arc<Limit> {
+        Budget::new(Some(MemoryBudget {
+            max_bytes,
+            over_budget,
+        }))
+        .0
+        .unwrap()
+    }
+
+    fn chunks(n: usize, size: usize) -> impl Stream<Item = Result<Bytes>> {
+        stream::iter((0..n).map(move |i| Ok(Bytes::from(vec![i as u8; size]))))
+    }
+
+    #[tokio::test]
+    async fn reservations_last_as_long_as_the_bytes() {
+        let limit = limit(4 * UNIT, OverBudget::Reject);
+        let data = limit.collect("k", Some(4 * UNIT), chunks(4, 1024)).await;
+        assert_eq!(data.unwrap().len(), 4096);
+        let data = limit.collect("k", None, chunks(3, 1024)).await.unwrap();
+        assert_eq!(data.len(), 3072);
+        assert_eq!(limit.units.available_permits(), 1);
+
+        // A slice keeps the whole reservation.
+        let head = data.slice(..10);
+        drop(data);
+        assert_eq!(limit.units.available_permits(), 1);
+        drop(head);
+        assert_eq!(limit.units.available_permits(), 4);
+    }
+
+    #[tokio::test]
+    async fn held_bytes_make_other_downloads_wait() {
+        let limit = limit(4 * UNIT, OverBudget::Reject);
+        let held = limit
+            .collect("a", Some(3 * UNIT), chunks(3, 1024))
+            .await
+            .unwrap();
+
+        let next = limit.collect("b", Some(2 * UNIT), chunks(2, 1024));
+        let mut next = std::pin::pin!(next);
+        assert!(futures::poll!(next.as_mut()).is_pending());
+        drop(held);
+        assert_eq!(next.await.unwrap().len(), 2048);
+        assert_eq!(limit.units.available_permits(), 4);
+    }
+
+    #[tokio::test]
+    async fn over_budget_bodies_are_rejected() {
+        let limit = limit(2 * UNIT, OverBudget::Reject);
+        for length in [Some(3 * UNIT), None] {
+            let err = limit.collect("big", length, chunks(3, 1024)).await;
+            assert!(
+                matches!(err, Err(Error::PayloadTooLarge { ref key, limit: 2048 }) if key == "big"),
+                "{err:?}"
+            );
+        }
+
+        // An unsized body is over budget when others hold the memory.
+        let held = limit.units.clone().acquire_many_owned(2).await.unwrap();
+        let err = limit.collect("small", None, chunks(1, 10)).await;
+        assert!(matches!(err, Err(Error::PayloadTooLarge { .. })));
+        drop(held);
+        assert_eq!(limit.units.available_permits(), 2);
+    }
+
+    #[cfg(feature = "spill")]
+    #[tokio::test]
+    async fn over_budget_bodies_spill_to_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let limit = limit(2 * UNIT, OverBudget::Spill(dir.path().into()));
+        let expected: Vec<u8> = (0..5u8).flat_map(|i| vec![i; 1024]).collect();
+        for length in [Some(5 * UNIT), None] {
+            let data = limit.collect("big", length, chunks(5, 1024)).await.unwrap();
+            assert_eq!(data, expected);
+        }
+        assert_eq!(limit.units.available_permits(), 2);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn rest_get_respects_the_budget() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects/big")
+            .with_body(vec![b'x'; 4096])
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/small")
+            .with_body("hello")
+            .create_async()
+            .await;
+        let config = ClientConfig {
+            memory_budget: Some(MemoryBudget::new(1024)),
+            ..Default::default()
+        };
+        let client = RestClient::new_with_config(server.url(), config).unwrap();
+
+        assert_eq!(client.get("small").await.unwrap().0, "hello");
+        let err = client.get("big").await.unwrap_err();
+        assert!(
+            matches!(err, Error::PayloadTooLarge { limit: 1024, .. }),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Adaptive chunk sizing for streaming uploads.
+//!
+//! REST and QUIC `put_stream` bodies are re-chunked by an AIMD controller:
+//! while each chunk completes within [`ChunkSizing::target_interval`] the
+//! chunk size grows additively by [`ChunkSizing::min`]; when a chunk takes
+//! longer the size is halved.  The size always stays within
+//! `[min, max]`.  On a fast LAN the size quickly climbs towards `max`,
+//! amortising per-chunk overhead; on a slow or lossy WAN it settles low
+//! enough that progress is reported (and backpressure applied) regularly.
+//!
+//! The interval measured for a chunk spans from the previous chunk being
+//! handed downstream to this one being handed downstream, so it covers both
+//! the time to read the chunk from its source and the time the network took
+//! to accept the previous one.
+//!
+//! Downloads are not re-chunked: their bytes have already arrived by the
+//! time they could be regrouped, so sizing them would not change how fast
+//! they are read, and `get_stream` yields chunks as the transport delivers
+//! them.
+
+use crate::error::Result;
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use std::time::{Duration, Instant};
+
+/// Bounds and target for adaptive chunk sizing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSizing {
+    /// Smallest chunk size in bytes; also the additive increase step.
+    pub min: usize,
+    /// Largest chunk size in bytes.
+    pub max: usize,
+    /// Chunk size used for the first chunk of a transfer.
+    pub initial: usize,
+    /// Per-chunk time budget.  Faster chunks grow the size, slower chunks
+    /// halve it.
+    pub target_interval: Duration,
+}
+
+impl Default for ChunkSizing {
+    fn default() -> Self {
+        Self {
+            min: 16 * 1024,
+            max: 4 * 1024 * 1024,
+            initial: 256 * 1024,
+            target_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+impl ChunkSizing {
+    /// A fixed chunk size (no adaptation).
+    pub fn fixed(size: usize) -> Self {
+        Self {
+            min: size,
+            max: size,
+            initial: size,
+            ..Default::default()
+        }
+    }
+
+    fn clamp(&self, size: usize) -> usize {
+        let min = self.min.max(1);
+        size.clamp(min, self.max.max(min))
+    }
+}
+
+/// AIMD chunk size controller.
+#[derive(Debug, Clone)]
+pub(crate) struct AdaptiveChunker {
+    sizing: ChunkSizing,
+    size: usize,
+}
+
+impl AdaptiveChunker {
+    pub(crate) fn new(sizing: ChunkSizing) -> Self {
+        Self {
+            size: sizing.clamp(sizing.initial),
+            sizing,
+        }
+    }
+
+    /// The size the next chunk should have.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Record that a chunk took `elapsed` end to end.
+    pub(crate) fn observe(&mut self, elapsed: Duration) {
+        let next = if elapsed <= self.sizing.target_interval {
+            self.size.saturating_add(self.sizing.min.max(1))
+        } else {
+            self.size / 2
+        };
+        self.size = self.sizing.clamp(next);
+    }
+}
+
+struct RechunkState<S> {
+    source: S,
+    buf: BytesMut,
+    chunker: AdaptiveChunker,
+    last_yield: Option<Instant>,
+    exhausted: bool,
+}
+
+/// Re-chunk `source` into chunks sized by an [`AdaptiveChunker`].
+pub(crate) fn adaptive_chunks<S>(
+    source: S,
+    sizing: ChunkSizing,
+) -> impl Stream<Item = Result<Bytes>> + Send + 'static
+where
+    S: Stream<Item = Result<Bytes>> + Send + 'static,
+{
+    let state = RechunkState {
+        source: source.boxed(),
+        buf: BytesMut::new(),
+        chunker: AdaptiveChunker::new(sizing),
+        last_yield: None,
+        exhausted: false,
+    };
+
+    futures::stream::try_unfold(state, |mut st| async move {
+        if let Some(at) = st.last_yield.take() {
+            st.chunker.observe(at.elapsed());
+        }
+
+        while !st.exhausted && st.buf.len() < st.chunker.size() {
+            match st.source.next().await {
+                Some(chunk) => st.buf.extend_from_slice(&chunk?),
+                None => st.exhausted = true,
+            }
+        }
+
+        if st.buf.is_empty() {
+            return Ok(None);
+        }
+
+        let take = st.chunker.size().min(st.buf.len());
+        let chunk = st.buf.split_to(take).freeze();
+        st.last_yield = Some(Instant::now());
+        Ok(Some((chunk, st)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn sizing() -> ChunkSizing {
+        ChunkSizing {
+            min: 4,
+            max: 32,
+            initial: 8,
+            target_interval: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn additive_increase_multiplicative_decrease() {
+        let mut c = AdaptiveChunker::new(sizing());
+        assert_eq!(c.size(), 8);
+        c.observe(Duration::from_millis(1));
+        assert_eq!(c.size(), 12);
+        c.observe(Duration::from_millis(1));
+        assert_eq!(c.size(), 16);
+        c.observe(Duration::from_millis(500));
+        assert_eq!(c.size(), 8);
+    }
+
+    #[test]
+    fn size_stays_within_bounds() {
+        let mut c = AdaptiveChunker::new(sizing());
+        for _ in 0..100 {
+            c.observe(Duration::ZERO);
+        }
+        assert_eq!(c.size(), 32);
+        for _ in 0..100 {
+            c.observe(Duration::from_secs(1));
+        }
+        assert_eq!(c.size(), 4);
+    }
+
+    #[test]
+    fn initial_is_clamped_and_fixed_does_not_adapt() {
+        let c = AdaptiveChunker::new(ChunkSizing {
+            initial: 1000,
+            ..sizing()
+        });
+        assert_eq!(c.size(), 32);
+
+        let mut fixed = AdaptiveChunker::new(ChunkSizing::fixed(10));
+        fixed.observe(Duration::ZERO);
+        fixed.observe(Duration::from_secs(5));
+        assert_eq!(fixed.size(), 10);
+    }
+
+    #[tokio::test]
+    async fn rechunking_preserves_bytes_and_grows() {
+        let input: Vec<Result<Bytes>> = (0..20u8).map(|i| Ok(Bytes::from(vec![i; 3]))).collect();
+        let chunks: Vec<Bytes> = adaptive_chunks(futures::stream::iter(input), sizing())
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+
+        let joined: Vec<u8> = chunks.iter().flat_map(|c| c.iter().copied()).collect();
+        let expected: Vec<u8> = (0..20u8).flat_map(|i| [i; 3]).collect();
+        assert_eq!(joined, expected);
+        // A consumer that keeps up grows the chunk size from 8 upwards.
+        assert_eq!(chunks[0].len(), 8);
+        assert_eq!(chunks[1].len(), 12);
+    }
+
+    #[tokio::test]
+    async fn rechunking_propagates_errors() {
+        let input = futures::stream::iter(vec![
+            Ok(Bytes::from_static(b"ab")),
+            Err(Error::Generic("boom".to_string())),
+        ]);
+        let results: Vec<Result<Bytes>> = adaptive_chunks(input, sizing()).collect().await;
+        assert!(matches!(results.last(), Some(Err(Error::Generic(_)))));
+    }
+}
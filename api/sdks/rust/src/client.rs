@@ -1,16 +1,64 @@
+//! The [`ObjectStore`] trait and the protocol-independent
+//! [`ObjectStoreClient`].
+//!
+//! Code written against [`ObjectStore`] works with every transport and with
+//! the crate's store wrappers.  The trait is also implemented for `&T`,
+//! `Box<T>`, and `Arc<T>` (including `dyn ObjectStore`), and
+//! [`into_shared`](ObjectStore::into_shared) upcasts any store to
+//! `Arc<dyn ObjectStore>`:
+//!
+//! ```no_run
+//! use go_objstore::{ObjectStore, ObjectStoreClient, Protocol, RetryPolicy, Retrying};
+//! use std::sync::Arc;
+//!
+//! fn build() -> go_objstore::Result<Retrying<Arc<dyn ObjectStore>>> {
+//!     let store = ObjectStoreClient::rest("http://localhost:8080")?.into_shared();
+//!     Ok(Retrying::new(store, Protocol::Rest, RetryPolicy::default()))
+//! }
+//! ```
+//!
+//! [`ObjectStoreClient::protocol`], [`endpoint`](ObjectStoreClient::endpoint),
+//! and [`tls_info`](ObjectStoreClient::tls_info) report which transport a
+//! client speaks, where it connects, and how the connection is secured, for
+//! labelling logs and metrics.  [`Protocol`] displays as `REST`, `gRPC`,
+//! ..., and its lowercase [`as_str`](Protocol::as_str) form (`rest`,
+//! `grpc`, ...), used by config files and metric labels, parses back with
+//! `"grpc".parse::<Protocol>()`.
+//!
+//! Endpoints without a typed method can be reached with
+//! [`raw_request`](ObjectStoreClient::raw_request) (REST and QUIC), which
+//! applies the client's auth headers and returns error statuses rather
+//! than raising them, and [`grpc_raw`](ObjectStoreClient::grpc_raw), which
+//! sends an encoded request message and returns the encoded response.  An
+//! operation the client's transport does not offer fails with
+//! [`Error::UnsupportedByProtocol`] naming the [`Operation`].
+
 use crate::auth::AuthConfig;
-use crate::error::Result;
+use crate::config::ClientConfig;
+use crate::error::{Error, Result};
 use crate::grpc_client::GrpcClient;
 use crate::mcp_client::McpClient;
-use crate::quic_client::QuicClient;
+use crate::quic_client::{QuicClient, TlsVerification};
+use crate::response::RawResponse;
 use crate::rest_client::RestClient;
+use crate::retry::{RetryPolicy, Retrying};
+use crate::streaming::collect_stream;
+use crate::tenant::TenantClient;
+use crate::tls::TlsInfo;
 use crate::types::*;
 use crate::unix_client::UnixClient;
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::stream::{BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Trait for object store operations
+///
+/// The trait is object safe, and is implemented for `&T`, `Box<T>`, and
+/// `Arc<T>` of any implementor (including `dyn ObjectStore`), so shared and
+/// boxed stores can be passed anywhere a store is expected.
 #[async_trait]
 pub trait ObjectStore: Send + Sync {
     /// Put an object into storage
@@ -36,9 +84,301 @@ pub trait ObjectStore: Send + Sync {
 
     /// Health check
     async fn health(&self) -> Result<HealthResponse>;
+
+    /// Get an object as a stream of chunks.
+    ///
+    /// The default implementation buffers the object with
+    /// [`get`](Self::get); transports that can stream override it.
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        let (data, metadata) = self.get(key).await?;
+        Ok((
+            futures::stream::once(async move { Ok(data) }).boxed(),
+            metadata,
+        ))
+    }
+
+    /// Put an object from a stream of chunks.
+    ///
+    /// The default implementation buffers the stream and calls
+    /// [`put`](Self::put); transports that can stream override it.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.put(key, collect_stream(stream).await?, metadata).await
+    }
+
+    /// Borrow as a trait object, for helpers taking `&dyn ObjectStore`.
+    fn as_dyn(&self) -> &dyn ObjectStore
+    where
+        Self: Sized,
+    {
+        self
+    }
+
+    /// Move into a shared trait object, for dependency injection.
+    fn into_shared(self) -> Arc<dyn ObjectStore>
+    where
+        Self: Sized + 'static,
+    {
+        Arc::new(self)
+    }
+}
+
+/// Forward every [`ObjectStore`] method through a pointer type.
+macro_rules! forward_object_store {
+    ($($pointer:ty),+) => {$(
+        #[async_trait]
+        impl<T: ObjectStore + ?Sized> ObjectStore for $pointer {
+            async fn put(
+                &self,
+                key: &str,
+                data: Bytes,
+                metadata: Option<Metadata>,
+            ) -> Result<PutResponse> {
+                (**self).put(key, data, metadata).await
+            }
+
+            async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+                (**self).get(key).await
+            }
+
+            async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+                (**self).delete(key).await
+            }
+
+            async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+                (**self).list(list_req).await
+            }
+
+            async fn exists(&self, key: &str) -> Result<bool> {
+                (**self).exists(key).await
+            }
+
+            async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+                (**self).get_metadata(key).await
+            }
+
+            async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+                (**self).update_metadata(key, metadata).await
+            }
+
+            async fn health(&self) -> Result<HealthResponse> {
+                (**self).health().await
+            }
+
+            async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+                (**self).get_stream(key).await
+            }
+
+            async fn put_stream(
+                &self,
+                key: &str,
+                stream: ByteStream,
+                metadata: Option<Metadata>,
+            ) -> Result<PutResponse> {
+                (**self).put_stream(key, stream, metadata).await
+            }
+        }
+    )+};
+}
+
+forward_object_store!(&T, Box<T>, Arc<T>);
+
+/// A boxed stream of object bytes.
+pub type ByteStream = BoxStream<'static, Result<Bytes>>;
+
+/// Objects requested per page by helpers that walk whole listings.
+pub(crate) const LIST_PAGE_SIZE: i32 = 1000;
+
+/// Page through a listing, following continuation tokens until the server
+/// stops reporting the listing as truncated.
+pub(crate) fn list_pages(
+    store: &dyn ObjectStore,
+    list_req: ListRequest,
+) -> BoxStream<'_, Result<ListResponse>> {
+    futures::stream::try_unfold(Some(list_req), move |next| async move {
+        let Some(req) = next else {
+            return Ok(None);
+        };
+        let page = store.list(req.clone()).await?;
+        let next = match (&page.next_token, page.truncated) {
+            (Some(token), true) if !token.is_empty() => Some(ListRequest {
+                continue_from: Some(token.clone()),
+                ..req
+            }),
+            _ => None,
+        };
+        Ok(Some((page, next)))
+    })
+    .boxed()
+}
+
+/// A client operation, as reported by
+/// [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    Put,
+    PutWithOptions,
+    Get,
+    GetWithOptions,
+    GetStream,
+    GetTransformed,
+    Delete,
+    List,
+    ListWithOptions,
+    Exists,
+    GetMetadata,
+    UpdateMetadata,
+    Health,
+    Archive,
+    AddPolicy,
+    RemovePolicy,
+    GetPolicies,
+    ApplyPolicies,
+    AddReplicationPolicy,
+    RemoveReplicationPolicy,
+    GetReplicationPolicies,
+    GetReplicationPolicy,
+    TriggerReplication,
+    GetReplicationStatus,
+    RawRequest,
+    GrpcRaw,
+    ForTenant,
+    ListBackends,
+    AddBackend,
+    RemoveBackend,
+    ResumeUpload,
+}
+
+impl Operation {
+    /// The method name, e.g. `"get_metadata"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Operation::Put => "put",
+            Operation::PutWithOptions => "put_with_options",
+            Operation::Get => "get",
+            Operation::GetWithOptions => "get_with_options",
+            Operation::GetStream => "get_stream",
+            Operation::GetTransformed => "get_transformed",
+            Operation::Delete => "delete",
+            Operation::List => "list",
+            Operation::ListWithOptions => "list_with_options",
+            Operation::Exists => "exists",
+            Operation::GetMetadata => "get_metadata",
+            Operation::UpdateMetadata => "update_metadata",
+            Operation::Health => "health",
+            Operation::Archive => "archive",
+            Operation::AddPolicy => "add_policy",
+            Operation::RemovePolicy => "remove_policy",
+            Operation::GetPolicies => "get_policies",
+            Operation::ApplyPolicies => "apply_policies",
+            Operation::AddReplicationPolicy => "add_replication_policy",
+            Operation::RemoveReplicationPolicy => "remove_replication_policy",
+            Operation::GetReplicationPolicies => "get_replication_policies",
+            Operation::GetReplicationPolicy => "get_replication_policy",
+            Operation::TriggerReplication => "trigger_replication",
+            Operation::GetReplicationStatus => "get_replication_status",
+            Operation::RawRequest => "raw_request",
+            Operation::GrpcRaw => "grpc_raw",
+            Operation::ForTenant => "for_tenant",
+            Operation::ListBackends => "list_backends",
+            Operation::AddBackend => "add_backend",
+            Operation::RemoveBackend => "remove_backend",
+            Operation::ResumeUpload => "resume_upload",
+        }
+    }
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The transport a client speaks.
+///
+/// Displays as its conventional name (`REST`, `gRPC`, ...) and parses, case
+/// insensitively, from that or from [`as_str`](Self::as_str), the form used
+/// in config files and metric labels:
+///
+/// ```
+/// use go_objstore::Protocol;
+///
+/// let protocol: Protocol = "grpc".parse().unwrap();
+/// assert_eq!(protocol, Protocol::Grpc);
+/// assert_eq!(protocol.to_string(), "gRPC");
+/// assert_eq!(protocol.as_str(), "grpc");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// HTTP/1.1 or HTTP/2 REST ([`RestClient`]).
+    Rest,
+    /// gRPC over HTTP/2 ([`GrpcClient`]).
+    Grpc,
+    /// HTTP/3 over QUIC ([`QuicClient`]).
+    Quic,
+    /// MCP JSON-RPC 2.0 over HTTP ([`McpClient`]).
+    Mcp,
+    /// JSON-RPC 2.0 over a Unix socket ([`UnixClient`]).
+    Unix,
+}
+
+impl Protocol {
+    /// Every protocol, in declaration order.
+    pub const ALL: [Protocol; 5] = [
+        Protocol::Rest,
+        Protocol::Grpc,
+        Protocol::Quic,
+        Protocol::Mcp,
+        Protocol::Unix,
+    ];
+
+    /// The lowercase identifier: `rest`, `grpc`, `quic`, `mcp`, or `unix`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Rest => "rest",
+            Protocol::Grpc => "grpc",
+            Protocol::Quic => "quic",
+            Protocol::Mcp => "mcp",
+            Protocol::Unix => "unix",
+        }
+    }
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Protocol::Rest => "REST",
+            Protocol::Grpc => "gRPC",
+            Protocol::Quic => "QUIC",
+            Protocol::Mcp => "MCP",
+            Protocol::Unix => "Unix",
+        })
+    }
+}
+
+impl std::str::FromStr for Protocol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Protocol::ALL
+            .into_iter()
+            .find(|p| p.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "unknown protocol {s:?}; expected one of rest, grpc, quic, mcp, unix"
+                ))
+            })
+    }
 }
 
 /// Unified client that supports multiple protocols
+#[derive(Clone)]
 pub enum ObjectStoreClient {
     Rest(RestClient),
     Grpc(GrpcClient),
@@ -80,10 +420,126 @@ impl ObjectStoreClient {
         )?))
     }
 
+    /// Create a new REST client from a [`ClientConfig`]
+    pub fn rest_with_config(base_url: impl Into<String>, config: ClientConfig) -> Result<Self> {
+        Ok(ObjectStoreClient::Rest(RestClient::new_with_config(
+            base_url, config,
+        )?))
+    }
+
+    /// Create a new gRPC client from a [`ClientConfig`]
+    pub async fn grpc_with_config(
+        endpoint: impl Into<String>,
+        config: ClientConfig,
+    ) -> Result<Self> {
+        Ok(ObjectStoreClient::Grpc(
+            GrpcClient::new_with_config(endpoint, config).await?,
+        ))
+    }
+
+    /// Create a new QUIC/HTTP3 client from a [`ClientConfig`], resolving
+    /// `server_name` through the configured resolver
+    pub async fn quic_with_config(
+        server_name: impl Into<String>,
+        port: u16,
+        config: ClientConfig,
+    ) -> Result<Self> {
+        Ok(ObjectStoreClient::Quic(
+            QuicClient::new_with_config(server_name, port, TlsVerification::Enabled, config)
+                .await?,
+        ))
+    }
+
+    /// Create a new MCP client from a [`ClientConfig`]
+    pub fn mcp_with_config(base_url: impl Into<String>, config: ClientConfig) -> Result<Self> {
+        Ok(ObjectStoreClient::Mcp(McpClient::new_with_config(
+            base_url, config,
+        )?))
+    }
+
     /// Create a new Unix-socket (JSON-RPC 2.0) client
     pub fn unix(socket_path: impl AsRef<std::path::Path>) -> Result<Self> {
         Ok(ObjectStoreClient::Unix(UnixClient::new(socket_path)?))
     }
+
+    /// Return a handle scoped to `tenant_id`.
+    ///
+    /// Every call made through the handle carries the tenant as an
+    /// `X-Tenant-ID` header (REST, QUIC, MCP) or `x-tenant-id` metadata
+    /// (gRPC).  Connections are shared with this client.  Unix-socket
+    /// requests carry no headers, so there the call fails with
+    /// [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol)
+    /// rather than return a handle that sends no tenant.
+    pub fn for_tenant(&self, tenant_id: impl Into<String>) -> crate::Result<TenantClient> {
+        let tenant_id = tenant_id.into();
+        let client = match self {
+            ObjectStoreClient::Rest(client) => {
+                ObjectStoreClient::Rest(client.with_tenant(&tenant_id))
+            }
+            ObjectStoreClient::Grpc(client) => {
+                ObjectStoreClient::Grpc(client.with_tenant(&tenant_id))
+            }
+            ObjectStoreClient::Quic(client) => {
+                ObjectStoreClient::Quic(client.with_tenant(&tenant_id))
+            }
+            ObjectStoreClient::Mcp(client) => {
+                ObjectStoreClient::Mcp(client.with_tenant(&tenant_id))
+            }
+            ObjectStoreClient::Unix(_) => return Err(self.unsupported(Operation::ForTenant)),
+        };
+        Ok(TenantClient::new(client, tenant_id))
+    }
+
+    /// Wrap this client in a retry layer using `policy`.
+    pub fn with_retry(self, policy: RetryPolicy) -> Retrying<Self> {
+        let protocol = self.protocol();
+        Retrying::new(self, protocol, policy)
+    }
+
+    /// The error for calling `operation` on a client whose protocol lacks it.
+    pub(crate) fn unsupported(&self, operation: Operation) -> crate::Error {
+        crate::Error::UnsupportedByProtocol {
+            operation,
+            protocol: self.protocol(),
+        }
+    }
+
+    /// The transport this client speaks.
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            ObjectStoreClient::Rest(_) => Protocol::Rest,
+            ObjectStoreClient::Grpc(_) => Protocol::Grpc,
+            ObjectStoreClient::Quic(_) => Protocol::Quic,
+            ObjectStoreClient::Mcp(_) => Protocol::Mcp,
+            ObjectStoreClient::Unix(_) => Protocol::Unix,
+        }
+    }
+
+    /// Where the client connects: the base URL for REST and MCP,
+    /// `https://<server name>:<port>` for QUIC, the socket path for Unix,
+    /// and the endpoint URL for gRPC (empty for a client built on an
+    /// existing channel).  For log lines and metric labels.
+    pub fn endpoint(&self) -> String {
+        match self {
+            ObjectStoreClient::Rest(client) => client.endpoint().to_string(),
+            ObjectStoreClient::Grpc(client) => client.endpoint().unwrap_or_default().to_string(),
+            ObjectStoreClient::Quic(client) => client.endpoint(),
+            ObjectStoreClient::Mcp(client) => client.endpoint().to_string(),
+            ObjectStoreClient::Unix(client) => client.socket_path().display().to_string(),
+        }
+    }
+
+    /// How the client's connections are secured, or `None` when they are
+    /// not (plain HTTP, Unix sockets).
+    pub fn tls_info(&self) -> Option<TlsInfo> {
+        match self {
+            ObjectStoreClient::Rest(client) => client.tls_info(),
+            ObjectStoreClient::Grpc(client) => client.tls_info(),
+            ObjectStoreClient::Quic(client) => Some(client.tls_info()),
+            ObjectStoreClient::Mcp(client) => client.tls_info(),
+            ObjectStoreClient::Unix(_) => None,
+        }
+    }
 }
 
 #[async_trait]
@@ -119,13 +575,15 @@ impl ObjectStore for ObjectStoreClient {
     }
 
     async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
-        match self {
+        let markers = list_req.dir_markers;
+        let page = match self {
             ObjectStoreClient::Rest(client) => client.list(list_req).await,
             ObjectStoreClient::Grpc(client) => client.list(list_req).await,
             ObjectStoreClient::Quic(client) => client.list(list_req).await,
             ObjectStoreClient::Mcp(client) => client.list(list_req).await,
             ObjectStoreClient::Unix(client) => client.list(list_req).await,
-        }
+        }?;
+        Ok(crate::dir_markers::apply(markers, page))
     }
 
     async fn exists(&self, key: &str) -> Result<bool> {
@@ -169,6 +627,50 @@ impl ObjectStore for ObjectStoreClient {
             ObjectStoreClient::Unix(client) => client.health().await,
         }
     }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        match self {
+            ObjectStoreClient::Rest(client) => {
+                let (stream, metadata) = client.get_stream(key).await?;
+                Ok((stream.boxed(), metadata))
+            }
+            ObjectStoreClient::Grpc(client) => {
+                let (stream, metadata) = client.get_stream(key.to_string()).await?;
+                Ok((stream.boxed(), metadata))
+            }
+            ObjectStoreClient::Quic(client) => {
+                let (stream, metadata) = client.get_stream(key).await?;
+                Ok((stream.boxed(), metadata))
+            }
+            ObjectStoreClient::Mcp(client) => {
+                let (stream, metadata) = client.get_stream(key).await?;
+                Ok((stream.boxed(), metadata))
+            }
+            ObjectStoreClient::Unix(client) => {
+                let (stream, metadata) = client.get_stream(key).await?;
+                Ok((stream.boxed(), metadata))
+            }
+        }
+    }
+
+    /// Streams over REST and QUIC; gRPC, MCP, and Unix buffer the stream.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        match self {
+            ObjectStoreClient::Rest(client) => client.put_stream(key, stream, metadata).await,
+            ObjectStoreClient::Grpc(client) => {
+                client.put_stream(key.to_string(), stream, metadata).await
+            }
+            ObjectStoreClient::Quic(client) => client.put_stream(key, stream, metadata).await,
+            ObjectStoreClient::Mcp(_) | ObjectStoreClient::Unix(_) => {
+                self.put(key, collect_stream(stream).await?, metadata).await
+            }
+        }
+    }
 }
 
 /// Extended operations available on all transports (REST, gRPC, QUIC).
@@ -242,8 +744,8 @@ impl ObjectStoreClient {
         }
     }
 
-    /// Apply all lifecycle policies, returning (policies_count, objects_processed)
-    pub async fn apply_policies(&self) -> Result<(i32, i32)> {
+    /// Apply all lifecycle policies.
+    pub async fn apply_policies(&self) -> Result<ApplyPoliciesResult> {
         match self {
             ObjectStoreClient::Rest(client) => client.apply_policies().await,
             ObjectStoreClient::Grpc(client) => client.apply_policies().await,
@@ -346,6 +848,116 @@ impl ObjectStoreClient {
         }
     }
 
+    /// Put an object with per-call [`RequestOptions`].
+    ///
+    /// A non-default [`Durability`] is sent over REST, QUIC, and gRPC to
+    /// servers that advertise support for it, and must be acknowledged (see
+    /// [`features`](crate::features)); MCP and Unix-socket clients fail with
+    /// [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol)
+    /// rather than write with a weaker guarantee than requested, and do not
+    /// return consistency tokens.  `body_format` only affects REST.
+    pub async fn put_with_options(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: Option<Metadata>,
+        options: &RequestOptions,
+    ) -> Result<PutResponse> {
+        match self {
+            ObjectStoreClient::Rest(client) => {
+                client.put_with_options(key, data, metadata, options).await
+            }
+            ObjectStoreClient::Grpc(client) => {
+                client
+                    .put_with_options(key.to_string(), data, metadata, options)
+                    .await
+            }
+            ObjectStoreClient::Quic(client) => {
+                client.put_with_options(key, data, metadata, options).await
+            }
+            other if options.needs_headers() => Err(other.unsupported(Operation::PutWithOptions)),
+            other => other.put(key, data, metadata).await,
+        }
+    }
+
+    /// Get an object with per-call [`RequestOptions`], e.g. to read back a
+    /// write with [`RequestOptions::min_consistency_token`].  MCP and
+    /// Unix-socket clients fail with
+    /// [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol)
+    /// when a token is set.
+    pub async fn get_with_options(
+        &self,
+        key: &str,
+        options: &RequestOptions,
+    ) -> Result<(Bytes, Metadata)> {
+        match self {
+            ObjectStoreClient::Rest(client) => client.get_with_options(key, options).await,
+            ObjectStoreClient::Grpc(client) => {
+                client.get_with_options(key.to_string(), options).await
+            }
+            ObjectStoreClient::Quic(client) => client.get_with_options(key, options).await,
+            other if options.needs_headers() => Err(other.unsupported(Operation::GetWithOptions)),
+            other => other.get(key).await,
+        }
+    }
+
+    /// List objects with per-call [`RequestOptions`]; see
+    /// [`get_with_options`](Self::get_with_options).
+    pub async fn list_with_options(
+        &self,
+        list_req: ListRequest,
+        options: &RequestOptions,
+    ) -> Result<ListResponse> {
+        let markers = list_req.dir_markers;
+        let page = match self {
+            ObjectStoreClient::Rest(client) => client.list_with_options(list_req, options).await,
+            ObjectStoreClient::Grpc(client) => client.list_with_options(list_req, options).await,
+            ObjectStoreClient::Quic(client) => client.list_with_options(list_req, options).await,
+            other if options.needs_headers() => {
+                return Err(other.unsupported(Operation::ListWithOptions))
+            }
+            other => return other.list(list_req).await,
+        }?;
+        Ok(crate::dir_markers::apply(markers, page))
+    }
+
+    /// Send an arbitrary HTTP request to `path` on a REST or QUIC server;
+    /// see [`RestClient::raw_request`].  Use [`grpc_raw`](Self::grpc_raw)
+    /// for gRPC.  Other clients fail with
+    /// [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol).
+    pub async fn raw_request(
+        &self,
+        method: http::Method,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: Option<Bytes>,
+    ) -> Result<RawResponse> {
+        match self {
+            ObjectStoreClient::Rest(client) => {
+                client.raw_request(method, path, headers, body).await
+            }
+            ObjectStoreClient::Quic(client) => {
+                client.raw_request(method, path, headers, body).await
+            }
+            other => Err(other.unsupported(Operation::RawRequest)),
+        }
+    }
+
+    /// Call a gRPC method with an encoded request message; see
+    /// [`GrpcClient::grpc_raw`].  Other clients fail with
+    /// [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol).
+    pub async fn grpc_raw(
+        &self,
+        service: &str,
+        method: &str,
+        message: Bytes,
+    ) -> Result<RawResponse> {
+        match self {
+            ObjectStoreClient::Grpc(client) => client.grpc_raw(service, method, message).await,
+            other => Err(other.unsupported(Operation::GrpcRaw)),
+        }
+    }
+
     /// Close the client, releasing any underlying resources.
     pub async fn close(&self) -> Result<()> {
         match self {
@@ -520,7 +1132,7 @@ mod tests {
         assert!(matches!(
             err,
             Error::QuicConnection(_)
-                | Error::H3(_)
+                | Error::H3 { .. }
                 | Error::Configuration(_)
                 | Error::OperationFailed(_)
         ));
@@ -697,7 +1309,11 @@ mod tests {
             .unwrap();
         client.remove_policy("p1").await.unwrap();
         assert!(client.get_policies(None).await.unwrap().is_empty());
-        assert_eq!(client.apply_policies().await.unwrap(), (1, 2));
+        let applied = client.apply_policies().await.unwrap();
+        assert_eq!(
+            (applied.policies_evaluated, applied.objects_processed),
+            (1, 2)
+        );
 
         archive.assert_async().await;
         add.assert_async().await;
@@ -841,6 +1457,74 @@ mod tests {
         assert!(ObjectStoreClient::rest("http://localhost:8080").is_ok());
     }
 
+    #[test]
+    fn introspection_reports_protocol_endpoint_and_tls() {
+        let plain = rest("http://objstore:8080".into());
+        assert_eq!(plain.protocol(), Protocol::Rest);
+        assert_eq!(plain.endpoint(), "http://objstore:8080");
+        assert_eq!(plain.tls_info(), None);
+
+        let secure = ObjectStoreClient::mcp("https://objstore.example:8443").unwrap();
+        assert_eq!(secure.protocol(), Protocol::Mcp);
+        let tls = secure.tls_info().unwrap();
+        assert_eq!(tls.server_name.as_deref(), Some("objstore.example"));
+        assert!(tls.verify_server && !tls.client_certificate);
+
+        let unix = ObjectStoreClient::unix("/run/objstore.sock").unwrap();
+        assert_eq!(unix.endpoint(), "/run/objstore.sock");
+        assert_eq!(unix.tls_info(), None);
+    }
+
+    #[tokio::test]
+    async fn unsupported_operations_name_operation_and_protocol() {
+        let client = ObjectStoreClient::mcp("http://localhost:8081").unwrap();
+        let err = client
+            .raw_request(http::Method::GET, "/health", &[], None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedByProtocol {
+                operation: Operation::RawRequest,
+                protocol: Protocol::Mcp,
+            }
+        ));
+        assert_eq!(err.to_string(), "raw_request is not supported over MCP");
+
+        let client = rest("http://localhost:8080".into());
+        let err = client.grpc_raw("s", "m", Bytes::new()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedByProtocol {
+                operation: Operation::GrpcRaw,
+                protocol: Protocol::Rest,
+            }
+        ));
+
+        let client = ObjectStoreClient::unix("/run/objstore.sock").unwrap();
+        let options = RequestOptions::default().durability(Durability::Fsync);
+        let err = client
+            .put_with_options("k", Bytes::new(), None, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedByProtocol {
+                operation: Operation::PutWithOptions,
+                protocol: Protocol::Unix,
+            }
+        ));
+        let options = RequestOptions::default().min_consistency_token("seq-1");
+        let err = client.get_with_options("k", &options).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedByProtocol {
+                operation: Operation::GetWithOptions,
+                protocol: Protocol::Unix,
+            }
+        ));
+    }
+
     #[tokio::test]
     async fn unified_usable_as_trait_object() {
         let client = ObjectStoreClient::rest("http://localhost:8080").unwrap();
@@ -848,6 +1532,32 @@ mod tests {
         let _ = boxed.health().await;
     }
 
+    #[tokio::test]
+    async fn pointer_types_implement_object_store() {
+        async fn healthy(store: impl ObjectStore) -> bool {
+            store.health().await.is_ok()
+        }
+
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/health")
+            .with_status(200)
+            .with_body(r#"{"status":"healthy"}"#)
+            .expect(5)
+            .create_async()
+            .await;
+
+        let client = rest(server.url());
+        assert!(healthy(&client).await);
+        assert!(healthy(client.as_dyn()).await);
+        let shared: Arc<dyn ObjectStore> = client.into_shared();
+        assert!(healthy(shared.clone()).await);
+        assert!(healthy(&shared).await);
+        let boxed: Box<dyn ObjectStore> = Box::new(shared);
+        assert!(healthy(boxed).await);
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn unified_concurrent_usage() {
         use std::sync::Arc;
@@ -0,0 +1,270 @@
+//! Checking a server's routes against what the client expects.
+//!
+//! Servers built from different go-objstore versions do not all expose the
+//! same endpoints — some serve object metadata at `/objects/{key}/metadata`
+//! rather than `/metadata/{key}`, and lifecycle policies are optional.  A
+//! mismatch otherwise surfaces as a confusing `NotFound` on the first call
+//! that needs the route; [`compat_check`](ObjectStoreClient::compat_check)
+//! probes them up front:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let report = client.compat_check().await?;
+//! if !report.is_compatible() {
+//!     for problem in report.problems() {
+//!         eprintln!("objstore server: {problem}");
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Probes only read: they list at most one object and look up the metadata
+//! of a key that does not exist.  Over REST each route is requested
+//! directly, and a route the server lacks is told apart from a missing
+//! object by the server's JSON error body.  The other transports call the
+//! corresponding operations instead, so a capability counts as supported
+//! whenever the operation is answered — including with `NotFound`.
+
+use crate::client::{ObjectStore, ObjectStoreClient, Protocol};
+use crate::error::{Error, Result};
+use crate::rest_client::RestClient;
+use crate::types::ListRequest;
+use reqwest::Method;
+
+/// Key looked up by the metadata probe; it is never expected to exist.
+const PROBE_KEY: &str = ".objstore-compat-probe";
+
+/// Whether the server offers one capability.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Support {
+    /// Served where the client expects it.
+    Supported,
+    /// Served, but at this other route, which the client does not use.
+    Alternate(String),
+    /// Not served.
+    Missing,
+    /// The probe got an answer that settles neither way, e.g. a 403.
+    Unknown(String),
+}
+
+impl Support {
+    /// Whether the client can use the capability.
+    pub fn is_supported(&self) -> bool {
+        matches!(self, Support::Supported)
+    }
+}
+
+/// What [`compat_check`](ObjectStoreClient::compat_check) found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatReport {
+    /// The transport probed.
+    pub protocol: Protocol,
+    /// Object reads, writes, and listings (`/objects`).
+    pub objects: Support,
+    /// Object metadata lookups and updates (`/metadata/{key}`).
+    pub metadata: Support,
+    /// The health check (`/health`).
+    pub health: Support,
+    /// Lifecycle policies (`/policies`); optional.
+    pub policies: Support,
+    /// The listing page-size cap the server advertises, if it does (see
+    /// [`paging`](crate::paging)).
+    pub max_page_size: Option<u32>,
+}
+
+impl CompatReport {
+    /// Whether every required capability is supported.  Lifecycle
+    /// policies are optional and do not count.
+    pub fn is_compatible(&self) -> bool {
+        self.objects.is_supported() && self.metadata.is_supported() && self.health.is_supported()
+    }
+
+    /// A line describing each capability that is not supported, optional
+    /// ones included.
+    pub fn problems(&self) -> Vec<String> {
+        let capabilities = [
+            ("objects", "/objects", &self.objects),
+            ("metadata", "/metadata/{key}", &self.metadata),
+            ("health", "/health", &self.health),
+            ("policies", "/policies", &self.policies),
+        ];
+        capabilities
+            .into_iter()
+            .filter_map(|(name, route, support)| match support {
+                Support::Supported => None,
+                Support::Alternate(other) => Some(format!(
+                    "{name}: served at {other}, but the client uses {route}"
+                )),
+                Support::Missing => Some(format!("{name}: not served ({route})")),
+                Support::Unknown(detail) => Some(format!("{name}: could not tell ({detail})")),
+            })
+            .collect()
+    }
+}
+
+impl ObjectStoreClient {
+    /// Probe the server's endpoints and report which the client can use.
+    /// Fails only when the server cannot be reached.  See the
+    /// [`compat`](crate::compat) module.
+    pub async fn compat_check(&self) -> Result<CompatReport> {
+        if let ObjectStoreClient::Rest(client) = self {
+            return rest_report(client).await;
+        }
+
+        let list = ListRequest {
+            max_results: Some(1),
+            ..Default::default()
+        };
+        let objects = classify(self.list(list).await.map(drop))?;
+        let metadata = classify(self.get_metadata(PROBE_KEY).await.map(drop))?;
+        let health = classify(self.health().await.map(drop))?;
+        let policies = classify(self.get_policies(None).await.map(drop))?;
+        let max_page_size = match self {
+            ObjectStoreClient::Quic(client) => client.page_limit().discovered(),
+            _ => None,
+        };
+        Ok(CompatReport {
+            protocol: self.protocol(),
+            objects,
+            metadata,
+            health,
+            policies,
+            max_page_size,
+        })
+    }
+}
+
+/// The support an operation's outcome shows.  Connection failures are
+/// returned as errors: they say nothing about the server's routes.
+fn classify(outcome: Result<()>) -> Result<Support> {
+    match outcome {
+        Ok(()) | Err(Error::NotFound(_)) => Ok(Support::Supported),
+        Err(Error::UnsupportedByProtocol { .. }) => Ok(Support::Missing),
+        Err(Error::GrpcStatus(status)) if status.code() == tonic::Code::Unimplemented => {
+            Ok(Support::Missing)
+        }
+        Err(
+            e @ (Error::GrpcTransport(_)
+            | Error::Http(_)
+            | Error::Timeout(_)
+            | Error::QuicConnection(_)
+            | Error::QuicUnavailable { .. }
+            | Error::Io(_)),
+        ) => Err(e),
+        Err(e) => Ok(Support::Unknown(e.to_string())),
+    }
+}
+
+async fn rest_report(client: &RestClient) -> Result<CompatReport> {
+    let probe = urlencoding::encode(PROBE_KEY);
+    let objects = probe_route(client, "/objects?limit=1").await?;
+    let mut metadata = probe_route(client, &format!("/metadata/{probe}")).await?;
+    if metadata == Support::Missing {
+        // Servers routing every `/objects/...` path to the object handler
+        // answer this too, so it is only a hint once `/metadata` is missing.
+        let alternate = "/objects/{key}/metadata";
+        if probe_route(client, &format!("/objects/{probe}/metadata")).await? == Support::Supported {
+            metadata = Support::Alternate(alternate.to_string());
+        }
+    }
+    Ok(CompatReport {
+        protocol: Protocol::Rest,
+        objects,
+        metadata,
+        health: probe_route(client, "/health").await?,
+        policies: probe_route(client, "/policies").await?,
+        max_page_size: client.page_limit().discovered(),
+    })
+}
+
+/// Request `GET path` and classify the answer.  A 404 with a JSON body came
+/// from a handler (the object is missing, the route is not); a bare 404 is
+/// the router's.
+async fn probe_route(client: &RestClient, path: &str) -> Result<Support> {
+    let url = format!("{}{}", client.endpoint(), path);
+    let response = client.send(client.request(Method::GET, &url)).await?;
+    // Listings may advertise the server's page-size cap.
+    client.page_limit().observe(response.headers());
+    let status = response.status();
+    let json = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    Ok(match status.as_u16() {
+        _ if status.is_success() => Support::Supported,
+        404 if json => Support::Supported,
+        404 | 405 | 501 => Support::Missing,
+        _ => Support::Unknown(format!("HTTP {status} from {path}")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rest_reports_each_route() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects?limit=1")
+            .with_header("x-max-page-size", "500")
+            .with_body(r#"{"objects":[]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/metadata/.objstore-compat-probe")
+            .with_status(404)
+            .with_body("404 page not found")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/.objstore-compat-probe/metadata")
+            .with_status(404)
+            .with_header("content-type", "application/json; charset=utf-8")
+            .with_body(r#"{"error":"Not Found","code":404}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/health")
+            .with_body(r#"{"status":"healthy"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/policies")
+            .with_status(403)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client.compat_check().await.unwrap();
+        assert_eq!(report.objects, Support::Supported);
+        assert_eq!(
+            report.metadata,
+            Support::Alternate("/objects/{key}/metadata".into())
+        );
+        assert_eq!(report.health, Support::Supported);
+        assert!(matches!(report.policies, Support::Unknown(ref d) if d.contains("403")));
+        assert_eq!(report.max_page_size, Some(500));
+        assert!(!report.is_compatible());
+        let problems = report.problems();
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("/objects/{key}/metadata"));
+    }
+
+    #[test]
+    fn operation_outcomes_classify() {
+        assert_eq!(classify(Ok(())).unwrap(), Support::Supported);
+        assert_eq!(
+            classify(Err(Error::NotFound("k".into()))).unwrap(),
+            Support::Supported
+        );
+        let unimplemented = Error::GrpcStatus(Box::new(tonic::Status::unimplemented("no")));
+        assert_eq!(classify(Err(unimplemented)).unwrap(), Support::Missing);
+        assert!(classify(Err(Error::Io(std::io::ErrorKind::ConnectionRefused.into()))).is_err());
+    }
+}
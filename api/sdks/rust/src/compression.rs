@@ -0,0 +1,162 @@
+//! Transparent response decompression for `ClientConfig::decompress`.
+//!
+//! REST and QUIC `get` calls advertise [`ACCEPT_ENCODING`] and pass the
+//! body through [`decompress`], which undoes a `gzip` or `zstd`
+//! `Content-Encoding` and records the on-the-wire size in
+//! [`Metadata::compressed_size`].
+
+use crate::error::{Error, Result};
+use crate::types::Metadata;
+use bytes::Bytes;
+use std::io::Read;
+
+/// `Accept-Encoding` value sent when decompression is enabled.
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, zstd";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Coding {
+    Gzip,
+    Zstd,
+    Identity,
+}
+
+impl Coding {
+    fn parse(token: &str) -> Option<Self> {
+        match token.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Coding::Gzip),
+            "zstd" => Some(Coding::Zstd),
+            "identity" | "" => Some(Coding::Identity),
+            _ => None,
+        }
+    }
+
+    fn decode(self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut decoded = Vec::new();
+        match self {
+            Coding::Gzip => {
+                flate2::read::MultiGzDecoder::new(body).read_to_end(&mut decoded)?;
+            }
+            Coding::Zstd => decoded = zstd::stream::decode_all(body)?,
+            Coding::Identity => decoded.extend_from_slice(body),
+        }
+        Ok(decoded)
+    }
+}
+
+/// Undo the body's `Content-Encoding` (as recorded in `metadata`), updating
+/// `size`, `compressed_size`, and `content_encoding` to describe the
+/// decoded body.
+///
+/// Bodies with no encoding, or with a coding other than gzip and zstd, are
+/// returned untouched.
+pub(crate) fn decompress(metadata: &mut Metadata, body: Bytes) -> Result<Bytes> {
+    let Some(encoding) = metadata.content_encoding.as_deref() else {
+        return Ok(body);
+    };
+    // Codings are listed in the order they were applied.
+    let Some(codings) = encoding
+        .split(',')
+        .rev()
+        .map(Coding::parse)
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Ok(body);
+    };
+    if codings.iter().all(|c| *c == Coding::Identity) {
+        return Ok(body);
+    }
+
+    let compressed_size = body.len() as i64;
+    let mut decoded = body.to_vec();
+    for coding in codings {
+        decoded = coding.decode(&decoded).map_err(|e| {
+            Error::InvalidResponse(format!("failed to decode {encoding} body: {e}"))
+        })?;
+    }
+    metadata.size = decoded.len() as i64;
+    metadata.compressed_size = Some(compressed_size);
+    metadata.content_encoding = None;
+    Ok(Bytes::from(decoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientConfig, RestClient};
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn encoded(encoding: &str) -> Metadata {
+        Metadata {
+            content_encoding: Some(encoding.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decodes_gzip_and_zstd() {
+        let text = b"hello hello hello hello".repeat(20);
+
+        let mut metadata = encoded("gzip");
+        let wire = gzip(&text);
+        let body = decompress(&mut metadata, Bytes::from(wire.clone())).unwrap();
+        assert_eq!(&body[..], &text[..]);
+        assert_eq!(metadata.size, text.len() as i64);
+        assert_eq!(metadata.compressed_size, Some(wire.len() as i64));
+        assert_eq!(metadata.content_encoding, None);
+
+        let mut metadata = encoded("gzip, zstd");
+        let wire = zstd::stream::encode_all(&gzip(&text)[..], 0).unwrap();
+        let body = decompress(&mut metadata, Bytes::from(wire)).unwrap();
+        assert_eq!(&body[..], &text[..]);
+    }
+
+    #[test]
+    fn leaves_unknown_codings_alone() {
+        let mut metadata = encoded("br");
+        let body = decompress(&mut metadata, Bytes::from_static(b"opaque")).unwrap();
+        assert_eq!(&body[..], b"opaque");
+        assert_eq!(metadata.compressed_size, None);
+        assert_eq!(metadata.content_encoding.as_deref(), Some("br"));
+    }
+
+    #[test]
+    fn corrupt_body_is_an_error() {
+        let mut metadata = encoded("gzip");
+        assert!(matches!(
+            decompress(&mut metadata, Bytes::from_static(b"not gzip")),
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rest_get_negotiates_and_decodes() {
+        let text = b"compressible ".repeat(100);
+        let wire = gzip(&text);
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/objects/k")
+            .match_header("accept-encoding", ACCEPT_ENCODING)
+            .with_status(200)
+            .with_header("content-encoding", "gzip")
+            .with_body(&wire)
+            .create_async()
+            .await;
+
+        let config = ClientConfig {
+            decompress: true,
+            ..Default::default()
+        };
+        let client = RestClient::new_with_config(server.url(), config).unwrap();
+        let (data, metadata) = client.get("k").await.unwrap();
+        get.assert_async().await;
+        assert_eq!(&data[..], &text[..]);
+        assert_eq!(metadata.size, text.len() as i64);
+        assert_eq!(metadata.compressed_size, Some(wire.len() as i64));
+    }
+}
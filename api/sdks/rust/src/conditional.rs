@@ -0,0 +1,280 @@
+//! Conditional reads for cache revalidation, and conditional writes.
+//!
+//! [`get_if_none_match`] fetches an object only if it changed since a
+//! cached copy was taken, so a cache can revalidate large objects without
+//! downloading them again:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example(cached: bytes::Bytes, cached_etag: String) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let data = match client.get_if_none_match("models/latest.bin", &cached_etag).await? {
+//!     None => cached,
+//!     Some((data, metadata)) => {
+//!         println!("refreshed, now {:?}", metadata.etag);
+//!         data
+//!     }
+//! };
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Over REST, [`ObjectStoreClient::get_if_none_match`] sends
+//! `If-None-Match` and a `304 Not Modified` answer returns `None`.  Servers
+//! that ignore the header answer `200` with the object's `ETag`; if it
+//! matches, the response is dropped before its body is read.  Other
+//! transports look up the object's metadata first and only download it if
+//! the ETag differs, which costs an extra round trip when it does.
+//!
+//! ETags are compared weakly, without surrounding quotes or a `W/` marker.
+//! An `etag` of `*` matches any existing object.  A missing object fails
+//! with [`Error::NotFound`](crate::Error::NotFound).
+//!
+//! # Conditional writes
+//!
+//! [`ObjectStoreClient::put_if_not_exists`] creates an object only if the
+//! key is free, and [`ObjectStoreClient::put_if_match`] replaces one only
+//! if nobody changed it since its ETag was read — optimistic concurrency
+//! for read-modify-write cycles:
+//!
+//! ```no_run
+//! use go_objstore::{Error, ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let (data, metadata) = client.get("counters/visits").await?;
+//! let next: u64 = std::str::from_utf8(&data)?.parse::<u64>()? + 1;
+//! let etag = metadata.etag.unwrap_or_default();
+//! match client.put_if_match("counters/visits", next.to_string().into(), None, &etag).await {
+//!     Ok(_) => {}
+//!     Err(Error::PreconditionFailed(_)) => println!("lost the race, retry"),
+//!     Err(e) => return Err(e.into()),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Both send a [`Precondition`] through
+//! [`RequestOptions::precondition`]: `If-None-Match: *` or `If-Match` on
+//! REST and QUIC, and the same keys as request metadata on gRPC, whose
+//! proto has no precondition fields.  A failed precondition is
+//! [`Error::PreconditionFailed`](crate::Error::PreconditionFailed).  MCP
+//! and Unix socket clients cannot send them and fail with
+//! [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol).
+//! So do REST, QUIC, and gRPC clients, without sending the write, unless
+//! the server advertises support for conditional writes — a server that
+//! ignored the headers would overwrite without an error.  See the
+//! [`features`](crate::features) module.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::Result;
+use crate::types::{Metadata, Precondition, PutResponse, RequestOptions};
+use crate::verify::normalize;
+use bytes::Bytes;
+
+/// Get `key` unless its ETag still matches `etag`, looking up its
+/// metadata first.  Works with any store.
+pub async fn get_if_none_match(
+    store: &dyn ObjectStore,
+    key: &str,
+    etag: &str,
+) -> Result<Option<(Bytes, Metadata)>> {
+    let metadata = store.get_metadata(key).await?;
+    if etag_matches(metadata.etag.as_deref(), etag) {
+        return Ok(None);
+    }
+    let (data, metadata) = store.get(key).await?;
+    // The object may have changed back between the two calls.
+    if etag_matches(metadata.etag.as_deref(), etag) {
+        return Ok(None);
+    }
+    Ok(Some((data, metadata)))
+}
+
+/// Whether an existing object's `actual` ETag satisfies the
+/// `If-None-Match` value `expected`.
+pub(crate) fn etag_matches(actual: Option<&str>, expected: &str) -> bool {
+    expected == "*" || actual.is_some_and(|actual| normalize(actual) == normalize(expected))
+}
+
+impl ObjectStoreClient {
+    /// Get `key` unless its ETag still matches `etag`; `None` means the
+    /// cached copy is current.  See the [`conditional`](crate::conditional)
+    /// module.
+    pub async fn get_if_none_match(
+        &self,
+        key: &str,
+        etag: &str,
+    ) -> Result<Option<(Bytes, Metadata)>> {
+        match self {
+            ObjectStoreClient::Rest(client) => client.get_if_none_match(key, etag).await,
+            _ => get_if_none_match(self, key, etag).await,
+        }
+    }
+
+    /// Put `key` only if it does not exist yet.  See the
+    /// [`conditional`](crate::conditional) module.
+    pub async fn put_if_not_exists(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        let options = RequestOptions::default().precondition(Precondition::NotExists);
+        self.put_with_options(key, data, metadata, &options).await
+    }
+
+    /// Put `key` only if its stored ETag is still `etag`.  See the
+    /// [`conditional`](crate::conditional) module.
+    pub async fn put_if_match(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: Option<Metadata>,
+        etag: &str,
+    ) -> Result<PutResponse> {
+        let options = RequestOptions::default().precondition(Precondition::Match(etag.to_string()));
+        self.put_with_options(key, data, metadata, &options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::rest_client::RestClient;
+
+    #[test]
+    fn weak_comparison() {
+        assert!(etag_matches(Some("\"abc\""), "abc"));
+        assert!(etag_matches(Some("W/\"abc\""), "\"abc\""));
+        assert!(etag_matches(None, "*"));
+        assert!(!etag_matches(None, "abc"));
+        assert!(!etag_matches(Some("abd"), "abc"));
+    }
+
+    #[tokio::test]
+    async fn rest_revalidates() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects/cached")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .create_async()
+            .await;
+        // A server ignoring the header; the matching ETag still counts.
+        server
+            .mock("GET", "/objects/ignored")
+            .match_header("if-none-match", "v1")
+            .with_header("etag", "\"v1\"")
+            .with_body("unchanged body")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/changed")
+            .match_header("if-none-match", "\"v1\"")
+            .with_header("etag", "\"v2\"")
+            .with_body("new")
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        assert!(client
+            .get_if_none_match("cached", "\"v1\"")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(client
+            .get_if_none_match("ignored", "v1")
+            .await
+            .unwrap()
+            .is_none());
+        let (data, metadata) = client
+            .get_if_none_match("changed", "\"v1\"")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data, "new");
+        assert_eq!(metadata.etag.as_deref(), Some("\"v2\""));
+    }
+
+    #[tokio::test]
+    async fn rest_sends_write_preconditions() {
+        let mut server = mockito::Server::new_async().await;
+        let health = server
+            .mock("GET", "/health")
+            .with_header("x-objstore-features", "conditional-writes")
+            .with_body(r#"{"status":"healthy"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("PUT", "/objects/new")
+            .match_header("if-none-match", "*")
+            .with_status(412)
+            .with_body(r#"{"error":"object exists"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("PUT", "/objects/counter")
+            .match_header("if-match", "\"v1\"")
+            .with_status(201)
+            .with_header("etag", "\"v2\"")
+            .with_body(r#"{"message":"ok"}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let err = client
+            .put_if_not_exists("new", Bytes::from("x"), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PreconditionFailed(_)), "{err:?}");
+        let put = client
+            .put_if_match("counter", Bytes::from("2"), None, "\"v1\"")
+            .await
+            .unwrap();
+        assert!(put.success);
+        health.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_refuses_preconditions_the_server_ignores() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_body(r#"{"status":"healthy"}"#)
+            .create_async()
+            .await;
+        let put = server
+            .mock("PUT", "/objects/new")
+            .expect(0)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let err = client
+            .put_if_not_exists("new", Bytes::from("x"), None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::UnsupportedByProtocol { .. }),
+            "{err:?}"
+        );
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn mcp_rejects_write_preconditions() {
+        let client = ObjectStoreClient::mcp("http://localhost:1").unwrap();
+        let err = client
+            .put_if_not_exists("k", Bytes::from("x"), None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::UnsupportedByProtocol { .. }),
+            "{err:?}"
+        );
+    }
+}
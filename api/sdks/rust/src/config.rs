@@ -0,0 +1,121 @@
+use crate::auth::AuthConfig;
+use crate::budget::MemoryBudget;
+use crate::chunking::ChunkSizing;
+use crate::decoder::ResponseDecoder;
+use crate::grpc_client::{GrpcCompression, Keepalive};
+use crate::key_encoding::KeyEncoding;
+use crate::quic_client::HandshakeRetry;
+use crate::resolver::Resolver;
+use crate::timeouts::Timeouts;
+use crate::tls::ClientIdentity;
+use crate::types::Metadata;
+use std::sync::Arc;
+
+/// Transport-independent client configuration.
+///
+/// Accepted by the `new_with_config` constructors of the REST, gRPC, QUIC,
+/// and MCP clients.  Fields not relevant to a transport are ignored (the
+/// Unix-socket client takes no configuration).
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// Authentication and tenant headers sent on every request.
+    pub auth: AuthConfig,
+
+    /// Host name resolution (static overrides / custom resolver).
+    pub resolver: Resolver,
+
+    /// Adaptive chunk sizing for REST and QUIC `put_stream` uploads.
+    pub chunking: ChunkSizing,
+
+    /// Client certificate presented during the QUIC handshake (mutual TLS).
+    pub client_identity: Option<ClientIdentity>,
+
+    /// Record a per-request [`Timing`](crate::timing::Timing) breakdown,
+    /// returned by [`with_timing`](crate::ResponseExt::with_timing) and
+    /// attached to the headers captured by
+    /// [`with_headers`](crate::ResponseExt::with_headers).
+    pub collect_timings: bool,
+
+    /// Send `Accept-Encoding: gzip, zstd` on REST and QUIC `get` calls and
+    /// decompress gzip / zstd bodies transparently, recording the transfer
+    /// size in [`Metadata::compressed_size`](crate::Metadata::compressed_size).
+    /// This also decodes objects stored with such a `content_encoding`.
+    pub decompress: bool,
+
+    /// gRPC keepalive pings and background health probing.  Only applied
+    /// by `GrpcClient::new_with_config`; channels passed to `with_channel`
+    /// are used as configured.
+    pub grpc_keepalive: Option<Keepalive>,
+
+    /// Compress gRPC `List` and `Get` messages (see [`GrpcCompression`]).
+    /// `None` (the default) sends and accepts them uncompressed.
+    pub grpc_compression: Option<GrpcCompression>,
+
+    /// HTTP statuses the REST and QUIC clients treat as a successful `put`.
+    /// `None` accepts any 2xx; go-objstore answers 201, but proxies in
+    /// front of it may rewrite that to 200 or 204.
+    pub write_statuses: Option<Vec<u16>>,
+
+    /// Retry failed QUIC handshakes with backoff.  `None` makes a single
+    /// attempt, bounded only by the connection idle timeout.
+    pub quic_handshake: Option<HandshakeRetry>,
+
+    /// Cap on requests in flight at once across all operations of a client
+    /// and its clones; further calls wait for a slot.  A request holds its
+    /// slot until its response body is read (or its download stream is
+    /// dropped).  For gRPC this is the channel's concurrency limit, applied
+    /// only by `GrpcClient::new_with_config`.  `None` (the default) is
+    /// unlimited; `Some(0)` is treated as one.
+    pub max_in_flight: Option<usize>,
+
+    /// Cap on bytes held from buffered downloads at once across a client
+    /// and its clones, with over-budget downloads rejected or spilled to
+    /// disk (see the [`budget`](crate::budget) module).  Applied by the
+    /// REST, gRPC, and QUIC clients.  `None` (the default) is unlimited.
+    pub memory_budget: Option<MemoryBudget>,
+
+    /// Per-stage time limits; a request that runs out fails with
+    /// [`Error::Timeout`](crate::Error::Timeout) naming the stage.  See the
+    /// [`timeouts`](crate::timeouts) module for how each transport applies
+    /// them.  Unbounded by default.
+    pub timeouts: Timeouts,
+
+    /// Detect the content type of buffered `put` payloads that have none
+    /// from their leading bytes (see the [`sniff`](crate::sniff) module).
+    pub sniff_content_type: bool,
+
+    /// Custom decoding of REST and QUIC list entries and REST metadata
+    /// bodies, for servers that return extra fields (see the
+    /// [`decoder`](crate::decoder) module).  `None` uses the SDK's own.
+    pub response_decoder: Option<Arc<dyn ResponseDecoder>>,
+
+    /// Most objects the server returns per listing page; `list` requests
+    /// for more are split into several pages and merged (see the
+    /// [`paging`](crate::paging) module).  `None` uses the limit the server
+    /// advertises, or go-objstore's 1000.
+    pub max_page_size: Option<u32>,
+
+    /// How the REST and QUIC clients write keys into request paths; by
+    /// default `/` is percent-encoded (see the
+    /// [`key_encoding`](crate::key_encoding) module).
+    pub key_encoding: KeyEncoding,
+}
+
+impl ClientConfig {
+    /// Whether `status` counts as success for a write operation.
+    pub(crate) fn accepts_write(&self, status: u16) -> bool {
+        match &self.write_statuses {
+            Some(statuses) => statuses.contains(&status),
+            None => (200..300).contains(&status),
+        }
+    }
+
+    /// The metadata to upload `data` with, after content-type sniffing.
+    pub(crate) fn put_metadata(&self, data: &[u8], metadata: Option<Metadata>) -> Option<Metadata> {
+        if self.sniff_content_type {
+            crate::sniff::with_detected_type(data, metadata)
+        } else {
+            metadata
+        }
+    }
+}
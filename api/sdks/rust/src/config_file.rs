@@ -0,0 +1,423 @@
+//! Declarative client configuration from TOML files.
+//!
+//! A config file holds named profiles, each describing one endpoint:
+//!
+//! ```toml
+//! default_profile = "prod"
+//!
+//! [profiles.prod]
+//! protocol = "grpc"
+//! endpoint = "http://objstore.internal:50051"
+//!
+//! [profiles.prod.auth]
+//! token = "${OBJSTORE_TOKEN}"
+//! tenant_id = "acme"
+//! headers = { X-Request-Source = "nightly-sync" }
+//!
+//! [profiles.prod.retry]
+//! max_attempts = 5
+//! initial_backoff = "200ms"
+//! max_backoff = "10s"
+//!
+//! [profiles.edge]
+//! protocol = "quic"
+//! endpoint = "edge.example.com:4433"
+//!
+//! [profiles.edge.tls]
+//! client_cert = "${HOME}/.objstore/client.crt"
+//! client_key = "${HOME}/.objstore/client.key"
+//! ```
+//!
+//! Every string value may reference environment variables as `${VAR}` or
+//! `${VAR:-fallback}`; `$$` is a literal `$`.  Referencing an unset variable
+//! without a fallback is an error, so secrets never silently become empty.
+//!
+//! [`ObjectStoreClient::from_config_file`] connects with the selected
+//! profile:
+//!
+//! ```no_run
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::from_config_file("objstore.toml").await?;
+//! client.health().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The profile is the one named by `OBJSTORE_PROFILE` if set, otherwise
+//! `default_profile`, otherwise the profile called `default`;
+//! [`ObjectStoreClient::from_config_profile`] names one explicitly.  The
+//! client comes back wrapped in [`Retrying`], which never retries when the
+//! profile has no `retry` section.
+//!
+//! [`ObjectStoreClient::from_config_file`]: crate::ObjectStoreClient::from_config_file
+//! [`ObjectStoreClient::from_config_profile`]: crate::ObjectStoreClient::from_config_profile
+
+use crate::auth::AuthConfig;
+use crate::client::{ObjectStoreClient, Protocol};
+use crate::config::ClientConfig;
+use crate::duration::parse_go_duration;
+use crate::error::{Error, Result};
+use crate::quic_client::{QuicClient, TlsVerification};
+use crate::retry::{RetryPolicy, Retrying};
+use crate::secret::SecretString;
+use crate::tls::ClientIdentity;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable selecting the profile to use.
+pub const PROFILE_ENV: &str = "OBJSTORE_PROFILE";
+
+/// A parsed config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Profile used when `OBJSTORE_PROFILE` is not set.
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// One named client configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub protocol: Protocol,
+    /// Base URL (REST, MCP), endpoint URL (gRPC), `host:port` (QUIC), or
+    /// socket path (Unix).
+    pub endpoint: String,
+    #[serde(default)]
+    pub auth: AuthSection,
+    #[serde(default)]
+    pub tls: TlsSection,
+    /// Retries are disabled when the section is absent.
+    #[serde(default)]
+    pub retry: Option<RetrySection>,
+}
+
+/// Transport named by a profile; profiles now use [`Protocol`] directly.
+#[deprecated(note = "use `Protocol`")]
+pub type ProfileProtocol = Protocol;
+
+/// `[profiles.<name>.auth]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthSection {
+    pub token: Option<SecretString>,
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// `[profiles.<name>.tls]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsSection {
+    /// Verify the server certificate (QUIC).  Defaults to true.
+    #[serde(default = "default_true")]
+    pub verify: bool,
+    /// PEM or DER client certificate chain for mutual TLS (QUIC).
+    pub client_cert: Option<PathBuf>,
+    /// PEM or DER private key matching `client_cert`.
+    pub client_key: Option<PathBuf>,
+}
+
+impl Default for TlsSection {
+    fn default() -> Self {
+        Self {
+            verify: true,
+            client_cert: None,
+            client_key: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// `[profiles.<name>.retry]`; omitted fields take [`RetryPolicy::default`]
+/// values.  Backoffs use Go duration syntax (`"250ms"`, `"2s"`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetrySection {
+    pub max_attempts: Option<u32>,
+    pub initial_backoff: Option<String>,
+    pub max_backoff: Option<String>,
+    pub jitter: Option<bool>,
+}
+
+impl RetrySection {
+    /// Build the retry policy this section describes.
+    pub fn to_policy(&self) -> Result<RetryPolicy> {
+        let mut policy = RetryPolicy::default();
+        if let Some(max_attempts) = self.max_attempts {
+            policy = policy.with_max_attempts(max_attempts);
+        }
+        let duration = |field: &str, value: &Option<String>| {
+            value
+                .as_deref()
+                .map(|s| {
+                    parse_go_duration(s).ok_or_else(|| {
+                        Error::Configuration(format!("retry.{field}: invalid duration {s:?}"))
+                    })
+                })
+                .transpose()
+        };
+        let initial = duration("initial_backoff", &self.initial_backoff)?;
+        let max = duration("max_backoff", &self.max_backoff)?;
+        if let Some(initial) = initial {
+            policy.initial_backoff = initial;
+        }
+        if let Some(max) = max {
+            policy.max_backoff = max;
+        }
+        if let Some(jitter) = self.jitter {
+            policy.jitter = jitter;
+        }
+        Ok(policy)
+    }
+}
+
+impl ConfigFile {
+    /// Read and parse `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Configuration(format!("cannot read {}: {e}", path.display())))?;
+        Self::parse(&text)
+    }
+
+    /// Parse TOML text, expanding environment variables in string values.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut value: toml::Value =
+            toml::from_str(text).map_err(|e| Error::Configuration(e.to_string()))?;
+        expand_env(&mut value)?;
+        value
+            .try_into()
+            .map_err(|e: toml::de::Error| Error::Configuration(e.to_string()))
+    }
+
+    /// The profile named `name`.
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| Error::Configuration(format!("no profile named {name:?}")))
+    }
+
+    /// The profile selected by `OBJSTORE_PROFILE`, `default_profile`, or
+    /// named `default`, in that order.
+    pub fn selected_profile(&self) -> Result<&Profile> {
+        let name = std::env::var(PROFILE_ENV)
+            .ok()
+            .filter(|n| !n.is_empty())
+            .or_else(|| self.default_profile.clone())
+            .unwrap_or_else(|| "default".to_string());
+        self.profile(&name)
+    }
+}
+
+impl Profile {
+    /// The [`ClientConfig`] described by the auth and TLS sections.
+    pub fn client_config(&self) -> Result<ClientConfig> {
+        let client_identity = match (&self.tls.client_cert, &self.tls.client_key) {
+            (Some(cert), Some(key)) => Some(ClientIdentity::from_files(cert, key)?),
+            (None, None) => None,
+            _ => {
+                return Err(Error::Configuration(
+                    "tls.client_cert and tls.client_key must be set together".into(),
+                ))
+            }
+        };
+        Ok(ClientConfig {
+            auth: AuthConfig {
+                token: self.auth.token.clone(),
+                extra_headers: self.auth.headers.clone(),
+                tenant_id: self.auth.tenant_id.clone(),
+            },
+            client_identity,
+            ..Default::default()
+        })
+    }
+
+    /// The retry policy for this profile; [`RetryPolicy::none`] when the
+    /// profile has no `retry` section.
+    pub fn retry_policy(&self) -> Result<RetryPolicy> {
+        self.retry
+            .as_ref()
+            .map_or_else(|| Ok(RetryPolicy::none()), RetrySection::to_policy)
+    }
+
+    /// Build a client for this profile.
+    pub async fn connect(&self) -> Result<Retrying<ObjectStoreClient>> {
+        let config = self.client_config()?;
+        let endpoint = self.endpoint.clone();
+        let client = match self.protocol {
+            Protocol::Rest => ObjectStoreClient::rest_with_config(endpoint, config)?,
+            Protocol::Grpc => ObjectStoreClient::grpc_with_config(endpoint, config).await?,
+            Protocol::Mcp => ObjectStoreClient::mcp_with_config(endpoint, config)?,
+            Protocol::Unix => ObjectStoreClient::unix(endpoint)?,
+            Protocol::Quic => {
+                let (host, port) = endpoint
+                    .rsplit_once(':')
+                    .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+                    .ok_or_else(|| {
+                        Error::Configuration(format!(
+                            "QUIC endpoint must be host:port, got {endpoint:?}"
+                        ))
+                    })?;
+                let verification = if self.tls.verify {
+                    TlsVerification::Enabled
+                } else {
+                    TlsVerification::Disabled
+                };
+                ObjectStoreClient::Quic(
+                    QuicClient::new_with_config(host, port, verification, config).await?,
+                )
+            }
+        };
+        Ok(Retrying::new(client, self.protocol, self.retry_policy()?))
+    }
+}
+
+/// Expand `${VAR}` / `${VAR:-fallback}` in every string of `value`.
+fn expand_env(value: &mut toml::Value) -> Result<()> {
+    match value {
+        toml::Value::String(s) => *s = interpolate(s)?,
+        toml::Value::Array(items) => items.iter_mut().try_for_each(expand_env)?,
+        toml::Value::Table(table) => table.iter_mut().try_for_each(|(_, v)| expand_env(v))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn interpolate(input: &str) -> Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            let end = after.find('}').ok_or_else(|| {
+                Error::Configuration(format!("unterminated ${{...}} in {input:?}"))
+            })?;
+            let (name, fallback) = match after[..end].split_once(":-") {
+                Some((name, fallback)) => (name, Some(fallback)),
+                None => (&after[..end], None),
+            };
+            match (std::env::var(name), fallback) {
+                (Ok(v), _) => out.push_str(&v),
+                (Err(_), Some(fallback)) => out.push_str(fallback),
+                (Err(_), None) => {
+                    return Err(Error::Configuration(format!(
+                        "environment variable {name} is not set"
+                    )))
+                }
+            }
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+impl ObjectStoreClient {
+    /// Connect using the selected profile of the TOML config file at
+    /// `path`.  See [`config_file`](crate::config_file) for the format and
+    /// profile selection.
+    pub async fn from_config_file(path: impl AsRef<Path>) -> Result<Retrying<ObjectStoreClient>> {
+        ConfigFile::load(path)?.selected_profile()?.connect().await
+    }
+
+    /// Connect using the profile called `profile` in the config file at
+    /// `path`.
+    pub async fn from_config_profile(
+        path: impl AsRef<Path>,
+        profile: &str,
+    ) -> Result<Retrying<ObjectStoreClient>> {
+        ConfigFile::load(path)?.profile(profile)?.connect().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const FILE: &str = r#"
+        default_profile = "prod"
+
+        [profiles.prod]
+        protocol = "rest"
+        endpoint = "${CONFIG_FILE_TEST_HOST:-http://localhost:8080}"
+
+        [profiles.prod.auth]
+        token = "${CONFIG_FILE_TEST_TOKEN}"
+        headers = { X-Cost = "$$5" }
+
+        [profiles.prod.retry]
+        max_attempts = 5
+        initial_backoff = "250ms"
+
+        [profiles.edge]
+        protocol = "quic"
+        endpoint = "edge.example.com:4433"
+        tls = { verify = false }
+    "#;
+
+    #[test]
+    fn parses_profiles_with_env_interpolation() {
+        std::env::set_var("CONFIG_FILE_TEST_TOKEN", "s3cret");
+        let file = ConfigFile::parse(FILE).unwrap();
+
+        let prod = file.profile("prod").unwrap();
+        assert_eq!(prod.protocol, Protocol::Rest);
+        assert_eq!(prod.endpoint, "http://localhost:8080");
+        let config = prod.client_config().unwrap();
+        assert_eq!(config.auth.token.unwrap().expose_secret(), "s3cret");
+        assert_eq!(config.auth.extra_headers["X-Cost"], "$5");
+
+        let policy = prod.retry_policy().unwrap();
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(250));
+        assert_eq!(policy.max_backoff, RetryPolicy::default().max_backoff);
+
+        let edge = file.profile("edge").unwrap();
+        assert!(!edge.tls.verify);
+        assert_eq!(edge.retry_policy().unwrap().max_attempts, 1);
+        assert!(file.profile("missing").is_err());
+    }
+
+    #[test]
+    fn rejects_unset_variables_and_unknown_fields() {
+        let err = ConfigFile::parse(
+            "[profiles.a]\nprotocol = \"rest\"\nendpoint = \"${CONFIG_FILE_TEST_UNSET}\"",
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Configuration(m) if m.contains("CONFIG_FILE_TEST_UNSET")));
+
+        let err =
+            ConfigFile::parse("[profiles.a]\nprotocol = \"rest\"\nendpoint = \"x\"\nport = 1")
+                .unwrap_err();
+        assert!(matches!(err, Error::Configuration(_)));
+    }
+
+    #[tokio::test]
+    async fn connects_rest_profile() {
+        let file = ConfigFile::parse(
+            "[profiles.default]\nprotocol = \"rest\"\nendpoint = \"http://localhost:1\"",
+        )
+        .unwrap();
+        let client = file.profile("default").unwrap().connect().await.unwrap();
+        assert!(matches!(client.inner(), ObjectStoreClient::Rest(_)));
+        assert_eq!(client.policy().max_attempts, 1);
+    }
+}
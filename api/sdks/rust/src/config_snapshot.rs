@@ -0,0 +1,389 @@
+//! Capturing a server's configuration and auditing it for drift.
+//!
+//! [`snapshot_config`](ObjectStoreClient::snapshot_config) records the
+//! lifecycle and replication policies and what the server reports about
+//! itself in one serializable [`ConfigSnapshot`].  Saved as a baseline, it
+//! lets a scheduled job check that nothing changed behind its back with
+//! [`detect_drift`](ObjectStoreClient::detect_drift):
+//!
+//! ```no_run
+//! use go_objstore::config_snapshot::ConfigSnapshot;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let baseline: ConfigSnapshot =
+//!     serde_json::from_slice(&std::fs::read("objstore-baseline.json")?)?;
+//! let report = client.detect_drift(&baseline).await?;
+//! for change in &report.changes {
+//!     eprintln!("drift: {change}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Changes are reported per field, with `/`-separated paths such as
+//! `lifecycle/archive-logs/retention_seconds` or
+//! `replication/dr/destination_settings/bucket`; a policy that appeared or
+//! disappeared is reported as a whole.  Policies are matched by id.  Values
+//! that change on their own — the capture time, the health status, and each
+//! replication policy's last sync time — are recorded but not compared.
+//!
+//! go-objstore servers do not expose the settings of their primary backend,
+//! so backend settings are captured as they appear in policies: lifecycle
+//! archive destinations and replication sources and destinations.  Those
+//! settings can hold credentials; store snapshots accordingly.  Policy
+//! kinds the server does not offer are recorded as `None`, and a kind that
+//! starts or stops being offered is itself drift.
+
+use crate::client::{ObjectStore, ObjectStoreClient, Protocol};
+use crate::compat::Support;
+use crate::error::{Error, Result};
+use crate::types::{HealthStatus, LifecyclePolicy, ReplicationPolicy};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A server's configuration at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    /// When the snapshot was taken.
+    pub taken_at: DateTime<Utc>,
+    pub server: ServerInfo,
+    /// Lifecycle policies, sorted by id; `None` if the server offers none.
+    pub lifecycle_policies: Option<Vec<LifecyclePolicy>>,
+    /// Replication policies, sorted by id; `None` if the server offers
+    /// none.
+    pub replication_policies: Option<Vec<ReplicationPolicy>>,
+}
+
+/// What a server reports about itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub protocol: Protocol,
+    pub endpoint: String,
+    /// The server version, from REST health checks.
+    pub version: Option<String>,
+    /// Whether the health check reported the server as serving.
+    pub serving: bool,
+    /// Each capability [`compat_check`](ObjectStoreClient::compat_check)
+    /// probes, with its support (`supported`, `missing`, `alternate
+    /// <route>`, or `unknown: <detail>`).
+    pub capabilities: BTreeMap<String, String>,
+    /// The listing page-size cap the server advertises, if it does.
+    pub max_page_size: Option<u32>,
+}
+
+/// One field that differs between two snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    /// Where the field is, e.g. `lifecycle/<id>/action`.
+    pub path: String,
+    /// The value in the baseline, `None` if it was absent.
+    pub before: Option<Value>,
+    /// The current value, `None` if it is absent.
+    pub after: Option<Value>,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.before, &self.after) {
+            (None, Some(after)) => write!(f, "{}: added {after}", self.path),
+            (Some(before), None) => write!(f, "{}: removed {before}", self.path),
+            (Some(before), Some(after)) => write!(f, "{}: {before} -> {after}", self.path),
+            (None, None) => write!(f, "{}: unchanged", self.path),
+        }
+    }
+}
+
+/// What [`detect_drift`](ObjectStoreClient::detect_drift) found.
+#[derive(Debug, Clone)]
+pub struct DriftReport {
+    /// The live state the baseline was compared with.
+    pub current: ConfigSnapshot,
+    /// Fields that differ, sorted by path.
+    pub changes: Vec<ConfigChange>,
+}
+
+impl DriftReport {
+    /// Whether the live state matches the baseline.
+    pub fn is_clean(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl ConfigSnapshot {
+    /// The fields that differ from `baseline` to `self`.
+    pub fn diff(&self, baseline: &ConfigSnapshot) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+        compare("", &baseline.audited(), &self.audited(), &mut changes);
+        changes
+    }
+
+    /// The compared fields, with policies keyed by id.
+    fn audited(&self) -> Value {
+        let server = &self.server;
+        let mut audited = Map::new();
+        audited.insert(
+            "server".into(),
+            serde_json::json!({
+                "protocol": server.protocol,
+                "endpoint": server.endpoint,
+                "version": server.version,
+                "capabilities": server.capabilities,
+                "max_page_size": server.max_page_size,
+            }),
+        );
+        let lifecycle = self
+            .lifecycle_policies
+            .as_ref()
+            .map(|policies| by_id(policies.iter().map(|p| (p.id.clone(), to_value(p)))));
+        audited.insert("lifecycle".into(), lifecycle.unwrap_or(Value::Null));
+        let replication = self.replication_policies.as_ref().map(|policies| {
+            by_id(policies.iter().map(|p| {
+                let mut value = to_value(p);
+                if let Value::Object(fields) = &mut value {
+                    fields.remove("last_sync_time");
+                }
+                (p.id.clone(), value)
+            }))
+        });
+        audited.insert("replication".into(), replication.unwrap_or(Value::Null));
+        Value::Object(audited)
+    }
+}
+
+fn to_value(policy: &impl Serialize) -> Value {
+    serde_json::to_value(policy).unwrap_or(Value::Null)
+}
+
+fn by_id(policies: impl Iterator<Item = (String, Value)>) -> Value {
+    Value::Object(policies.collect())
+}
+
+/// Record where `before` and `after` differ, descending into objects both
+/// sides have.
+fn compare(path: &str, before: &Value, after: &Value, changes: &mut Vec<ConfigChange>) {
+    let (Value::Object(before), Value::Object(after)) = (before, after) else {
+        if before != after {
+            changes.push(ConfigChange {
+                path: path.to_string(),
+                before: Some(before.clone()).filter(|v| !v.is_null()),
+                after: Some(after.clone()).filter(|v| !v.is_null()),
+            });
+        }
+        return;
+    };
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}/{key}")
+        };
+        let before = before.get(key).unwrap_or(&Value::Null);
+        let after = after.get(key).unwrap_or(&Value::Null);
+        compare(&path, before, after, changes);
+    }
+}
+
+/// The policies, or `None` if the server does not offer them.
+fn offered<T>(policies: Result<Vec<T>>) -> Result<Option<Vec<T>>> {
+    match policies {
+        Ok(policies) => Ok(Some(policies)),
+        Err(Error::NotFound(_) | Error::UnsupportedByProtocol { .. }) => Ok(None),
+        Err(Error::GrpcStatus(status)) if status.code() == tonic::Code::Unimplemented => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn support(support: &Support) -> String {
+    match support {
+        Support::Supported => "supported".to_string(),
+        Support::Missing => "missing".to_string(),
+        Support::Alternate(route) => format!("alternate {route}"),
+        Support::Unknown(detail) => format!("unknown: {detail}"),
+    }
+}
+
+impl ObjectStoreClient {
+    /// Capture the server's policies and what it reports about itself.
+    /// See the [`config_snapshot`](crate::config_snapshot) module.
+    pub async fn snapshot_config(&self) -> Result<ConfigSnapshot> {
+        let compat = self.compat_check().await?;
+        let health = self.health().await?;
+        let mut lifecycle_policies = offered(self.get_policies(None).await)?;
+        let mut replication_policies = offered(self.get_replication_policies().await)?;
+        if let Some(policies) = &mut lifecycle_policies {
+            policies.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+        if let Some(policies) = &mut replication_policies {
+            policies.sort_by(|a, b| a.id.cmp(&b.id));
+        }
+
+        let capabilities = [
+            ("objects", &compat.objects),
+            ("metadata", &compat.metadata),
+            ("health", &compat.health),
+            ("policies", &compat.policies),
+        ];
+        Ok(ConfigSnapshot {
+            taken_at: Utc::now(),
+            server: ServerInfo {
+                protocol: self.protocol(),
+                endpoint: self.endpoint(),
+                version: health.message.filter(|_| self.protocol() == Protocol::Rest),
+                serving: health.status == HealthStatus::Serving,
+                capabilities: capabilities
+                    .into_iter()
+                    .map(|(name, s)| (name.to_string(), support(s)))
+                    .collect(),
+                max_page_size: compat.max_page_size,
+            },
+            lifecycle_policies,
+            replication_policies,
+        })
+    }
+
+    /// Take a snapshot and compare it with `baseline`.  See the
+    /// [`config_snapshot`](crate::config_snapshot) module.
+    pub async fn detect_drift(&self, baseline: &ConfigSnapshot) -> Result<DriftReport> {
+        let current = self.snapshot_config().await?;
+        let changes = current.diff(baseline);
+        Ok(DriftReport { current, changes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use serde_json::json;
+
+    async fn mock_server(server: &mut mockito::ServerGuard, policies: &str) -> Vec<mockito::Mock> {
+        let routes = [
+            ("/objects?limit=1", r#"{"objects":[]}"#.to_string()),
+            (
+                "/health",
+                r#"{"status":"healthy","version":"1.4.0"}"#.to_string(),
+            ),
+            ("/policies", policies.to_string()),
+            ("/replication/policies", r#"{"policies":[]}"#.to_string()),
+        ];
+        let mut mocks = Vec::new();
+        for (path, body) in routes {
+            let mock = server
+                .mock("GET", path)
+                .with_header("content-type", "application/json")
+                .with_body(body)
+                .create_async()
+                .await;
+            mocks.push(mock);
+        }
+        let metadata = server
+            .mock("GET", "/metadata/.objstore-compat-probe")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":"Not Found","code":404}"#)
+            .create_async()
+            .await;
+        mocks.push(metadata);
+        mocks
+    }
+
+    #[tokio::test]
+    async fn detects_changed_policies() {
+        let mut server = mockito::Server::new_async().await;
+        let archive = json!({"id": "archive", "prefix": "logs/", "retention_seconds": 86400,
+            "action": "archive", "destination_type": "s3",
+            "destination_settings": {"bucket": "cold"}});
+        let expire = json!({"id": "expire", "prefix": "tmp/", "retention_seconds": 3600,
+            "action": "delete"});
+        let mocks = mock_server(
+            &mut server,
+            &json!({"policies": [archive, expire]}).to_string(),
+        )
+        .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let baseline = client.snapshot_config().await.unwrap();
+        assert_eq!(baseline.server.version.as_deref(), Some("1.4.0"));
+        assert_eq!(baseline.server.capabilities["policies"], "supported");
+        assert_eq!(baseline.lifecycle_policies.as_ref().unwrap().len(), 2);
+        assert!(baseline.replication_policies.as_ref().unwrap().is_empty());
+
+        // Round-trips through the JSON a cron job would keep.
+        let baseline: ConfigSnapshot =
+            serde_json::from_str(&serde_json::to_string(&baseline).unwrap()).unwrap();
+        assert!(client.detect_drift(&baseline).await.unwrap().is_clean());
+
+        drop(mocks);
+        let mut archive = archive;
+        archive["destination_settings"]["bucket"] = json!("colder");
+        let added =
+            json!({"id": "added", "prefix": "x/", "retention_seconds": 1, "action": "delete"});
+        mock_server(
+            &mut server,
+            &json!({"policies": [added, archive]}).to_string(),
+        )
+        .await;
+
+        let report = client.detect_drift(&baseline).await.unwrap();
+        let paths: Vec<&str> = report.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "lifecycle/added",
+                "lifecycle/archive/destination_settings/bucket",
+                "lifecycle/expire",
+            ]
+        );
+        assert_eq!(
+            report.changes[1].to_string(),
+            r#"lifecycle/archive/destination_settings/bucket: "cold" -> "colder""#
+        );
+        assert!(report.changes[0].before.is_none());
+        assert!(report.changes[2].after.is_none());
+    }
+
+    #[test]
+    fn volatile_fields_are_not_drift() {
+        let snapshot = |serving, replication: Option<Vec<ReplicationPolicy>>| ConfigSnapshot {
+            taken_at: Utc::now(),
+            server: ServerInfo {
+                protocol: Protocol::Grpc,
+                endpoint: "http://localhost:9090".into(),
+                version: None,
+                serving,
+                capabilities: BTreeMap::new(),
+                max_page_size: None,
+            },
+            lifecycle_policies: None,
+            replication_policies: replication,
+        };
+        let policy: ReplicationPolicy = serde_json::from_value(json!({
+            "id": "dr", "source_backend": "local", "source_settings": {},
+            "source_prefix": "", "destination_backend": "s3",
+            "destination_settings": {"bucket": "dr"}, "check_interval_seconds": 60,
+            "last_sync_time": null, "enabled": true, "encryption": null,
+            "replication_mode": "transparent"
+        }))
+        .unwrap();
+        let mut synced = policy.clone();
+        synced.last_sync_time = Some(Utc::now());
+
+        let baseline = snapshot(true, Some(vec![policy]));
+        assert!(snapshot(false, Some(vec![synced]))
+            .diff(&baseline)
+            .is_empty());
+
+        let changes = snapshot(true, None).diff(&baseline);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "replication");
+        assert!(changes[0].after.is_none());
+    }
+}
@@ -0,0 +1,250 @@
+//! Bearer-token lookup from the environment, files, or application code.
+//!
+//! A [`CredentialsChain`] asks each provider in turn and uses the first
+//! token found, in the spirit of the AWS SDK's default chain:
+//!
+//! ```no_run
+//! use go_objstore::credentials::{CallbackCredentials, CredentialsChain};
+//! use go_objstore::{AuthConfig, ClientConfig, ObjectStoreClient, SecretString};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! // OBJSTORE_TOKEN, then ~/.objstore/token, then a vault lookup.
+//! let chain = CredentialsChain::default_chain().with(CallbackCredentials::new(|| async {
+//!     Ok(Some(SecretString::from("from-vault")))
+//! }));
+//! let auth = AuthConfig::default().with_credentials(&chain).await?;
+//! let client = ObjectStoreClient::rest_with_config(
+//!     "http://localhost:8080",
+//!     ClientConfig { auth, ..Default::default() },
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::auth::AuthConfig;
+use crate::error::Result;
+use crate::secret::SecretString;
+use async_trait::async_trait;
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Environment variable read by [`EnvCredentials::default`].
+pub const TOKEN_ENV: &str = "OBJSTORE_TOKEN";
+
+/// A source of bearer tokens.
+#[async_trait]
+pub trait CredentialsProvider: Send + Sync {
+    /// Return the token, or `None` when this provider has none so that a
+    /// chain moves on to the next one.
+    async fn token(&self) -> Result<Option<SecretString>>;
+}
+
+/// Reads the token from an environment variable (`OBJSTORE_TOKEN` by
+/// default).  Unset or empty variables yield no token.
+#[derive(Debug, Clone)]
+pub struct EnvCredentials {
+    var: String,
+}
+
+impl EnvCredentials {
+    /// Read from `var`.
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+}
+
+impl Default for EnvCredentials {
+    fn default() -> Self {
+        Self::new(TOKEN_ENV)
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for EnvCredentials {
+    async fn token(&self) -> Result<Option<SecretString>> {
+        Ok(std::env::var(&self.var)
+            .ok()
+            .filter(|t| !t.is_empty())
+            .map(SecretString::from))
+    }
+}
+
+/// Reads the token from a file, ignoring surrounding whitespace.  A missing
+/// or empty file yields no token; other I/O errors are returned.
+#[derive(Debug, Clone)]
+pub struct FileCredentials {
+    path: PathBuf,
+}
+
+impl FileCredentials {
+    /// Read from `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `$HOME/.objstore/token`, when `HOME` is set.
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".objstore").join("token"))
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for FileCredentials {
+    async fn token(&self) -> Result<Option<SecretString>> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => {
+                let contents = SecretString::from(contents);
+                let token = contents.expose_secret().trim();
+                Ok((!token.is_empty()).then(|| SecretString::from(token)))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Obtains the token from application code, e.g. a secrets manager.
+pub struct CallbackCredentials<F> {
+    callback: F,
+}
+
+impl<F, Fut> CallbackCredentials<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<SecretString>>> + Send,
+{
+    /// Call `callback` for every lookup.
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+#[async_trait]
+impl<F, Fut> CredentialsProvider for CallbackCredentials<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<SecretString>>> + Send,
+{
+    async fn token(&self) -> Result<Option<SecretString>> {
+        (self.callback)().await
+    }
+}
+
+impl<F> fmt::Debug for CallbackCredentials<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackCredentials")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Providers consulted in order; the first token found wins.  Errors stop
+/// the chain.
+#[derive(Clone, Default)]
+pub struct CredentialsChain {
+    providers: Vec<Arc<dyn CredentialsProvider>>,
+}
+
+impl CredentialsChain {
+    /// An empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `OBJSTORE_TOKEN`, then `$HOME/.objstore/token`.
+    pub fn default_chain() -> Self {
+        let chain = Self::new().with(EnvCredentials::default());
+        match FileCredentials::default_path() {
+            Some(path) => chain.with(FileCredentials::new(path)),
+            None => chain,
+        }
+    }
+
+    /// Append `provider` to the chain.
+    pub fn with(mut self, provider: impl CredentialsProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for CredentialsChain {
+    async fn token(&self) -> Result<Option<SecretString>> {
+        for provider in &self.providers {
+            if let Some(token) = provider.token().await? {
+                return Ok(Some(token));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl fmt::Debug for CredentialsChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CredentialsChain")
+            .field("providers", &self.providers.len())
+            .finish()
+    }
+}
+
+impl AuthConfig {
+    /// Set `token` from `provider`, leaving it unchanged when the provider
+    /// has none.
+    pub async fn with_credentials(mut self, provider: &dyn CredentialsProvider) -> Result<Self> {
+        if let Some(token) = provider.token().await? {
+            self.token = Some(token);
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn chain_uses_first_provider_with_a_token() {
+        let dir = std::env::temp_dir().join(format!("objstore-creds-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("token");
+        std::fs::write(&file, "  from-file\n").unwrap();
+
+        let chain = CredentialsChain::new()
+            .with(EnvCredentials::new("CREDENTIALS_TEST_UNSET"))
+            .with(FileCredentials::new(dir.join("missing")))
+            .with(FileCredentials::new(&file))
+            .with(CallbackCredentials::new(|| async {
+                Ok(Some(SecretString::from("from-callback")))
+            }));
+        let token = chain.token().await.unwrap().unwrap();
+        assert_eq!(token.expose_secret(), "from-file");
+
+        std::env::set_var("CREDENTIALS_TEST_SET", "from-env");
+        let auth = AuthConfig::default()
+            .with_credentials(
+                &CredentialsChain::new().with(EnvCredentials::new("CREDENTIALS_TEST_SET")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(auth.token.unwrap().expose_secret(), "from-env");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn provider_errors_stop_the_chain() {
+        let chain = CredentialsChain::new()
+            .with(CallbackCredentials::new(|| async {
+                Err(Error::Unauthenticated("vault sealed".into()))
+            }))
+            .with(CallbackCredentials::new(|| async {
+                Ok(Some(SecretString::from("unreachable")))
+            }));
+        assert!(matches!(
+            chain.token().await,
+            Err(Error::Unauthenticated(_))
+        ));
+        assert!(CredentialsChain::new().token().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,197 @@
+//! Pluggable decoding of list and metadata responses.
+//!
+//! Servers extended beyond go-objstore's schema may return extra fields —
+//! checksums, storage classes, retention dates — that the SDK's types do
+//! not model.  A [`ResponseDecoder`] set on
+//! [`ClientConfig::response_decoder`] receives each list entry and metadata
+//! body as raw JSON, so those fields can be captured (typically into
+//! custom metadata) without waiting for an SDK release:
+//!
+//! ```no_run
+//! use go_objstore::decoder::{self, ResponseDecoder};
+//! use go_objstore::{ClientConfig, ObjectInfo, ObjectStoreClient, Result};
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct StorageClass;
+//!
+//! impl ResponseDecoder for StorageClass {
+//!     fn decode_object(&self, entry: &[u8]) -> Result<ObjectInfo> {
+//!         let mut object = decoder::decode_object(entry)?;
+//!         let raw: serde_json::Value = serde_json::from_slice(entry)?;
+//!         if let Some(class) = raw["storage_class"].as_str() {
+//!             object.metadata.custom.insert("storage-class".into(), class.into());
+//!         }
+//!         Ok(object)
+//!     }
+//! }
+//!
+//! # fn example() -> Result<()> {
+//! let config = ClientConfig {
+//!     response_decoder: Some(Arc::new(StorageClass)),
+//!     ..Default::default()
+//! };
+//! let client = ObjectStoreClient::rest_with_config("http://localhost:8080", config)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Both methods default to the SDK's own decoding, available as
+//! [`decode_object`] and [`decode_metadata`] for decoders that extend it.
+//! List entries are decoded this way by the REST and QUIC clients
+//! (including `list_stream`), and metadata bodies by the REST client's
+//! `get_metadata`.  QUIC reads metadata from response headers, and gRPC,
+//! MCP, and Unix-socket responses are typed by their protocols, so the
+//! decoder does not apply there.
+//!
+//! [`ClientConfig::response_decoder`]: crate::ClientConfig::response_decoder
+
+use crate::error::Result;
+use crate::timestamp::parse_timestamp;
+use crate::types::{Metadata, ObjectInfo};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Turns raw response JSON into the SDK's types.  See the
+/// [module docs](self).
+pub trait ResponseDecoder: fmt::Debug + Send + Sync {
+    /// Decode one entry of a list response's `objects` array.
+    fn decode_object(&self, entry: &[u8]) -> Result<ObjectInfo> {
+        decode_object(entry)
+    }
+
+    /// Decode the body of a `GET /metadata/{key}` response.
+    fn decode_metadata(&self, body: &[u8]) -> Result<Metadata> {
+        decode_metadata(body)
+    }
+}
+
+/// One entry of a list response.
+#[derive(Debug, Deserialize)]
+struct WireObject {
+    key: String,
+    size: i64,
+    modified: Option<String>,
+    etag: Option<String>,
+    metadata: Option<HashMap<String, String>>,
+}
+
+/// The body of a metadata response.
+#[derive(Debug, Deserialize)]
+struct WireMetadata {
+    #[serde(default)]
+    content_type: Option<String>,
+    size: i64,
+    modified: Option<String>,
+    etag: Option<String>,
+    /// Custom metadata is returned as the `metadata` JSON field (not `custom`).
+    metadata: Option<HashMap<String, String>>,
+}
+
+/// The SDK's decoding of a list entry: `key`, `size`, `modified`, `etag`,
+/// and the `metadata` map.  Other fields are ignored.
+pub fn decode_object(entry: &[u8]) -> Result<ObjectInfo> {
+    let obj: WireObject = serde_json::from_slice(entry)?;
+    Ok(ObjectInfo {
+        key: obj.key,
+        metadata: Metadata {
+            size: obj.size,
+            last_modified: obj.modified.and_then(|s| parse_timestamp(&s)),
+            etag: obj.etag,
+            custom: obj.metadata.unwrap_or_default(),
+            ..Default::default()
+        },
+    })
+}
+
+/// The SDK's decoding of a metadata body: `content_type`, `size`,
+/// `modified`, `etag`, and the `metadata` map.  Other fields are ignored.
+///
+/// `size` is the stored object's size, not the length of the JSON body.
+pub fn decode_metadata(body: &[u8]) -> Result<Metadata> {
+    let body: WireMetadata = serde_json::from_slice(body)?;
+    Ok(Metadata {
+        content_type: body.content_type,
+        size: body.size,
+        last_modified: body.modified.and_then(|s| parse_timestamp(&s)),
+        etag: body.etag,
+        custom: body.metadata.unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+/// The SDK's own decoding, used when no decoder is configured.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultDecoder;
+
+impl ResponseDecoder for DefaultDecoder {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use crate::{ClientConfig, ObjectStore, ObjectStoreClient};
+    use std::sync::Arc;
+
+    /// Copies a `checksum` field into custom metadata.
+    #[derive(Debug)]
+    struct Checksums;
+
+    impl Checksums {
+        fn capture(raw: &[u8], custom: &mut HashMap<String, String>) -> Result<()> {
+            let raw: serde_json::Value = serde_json::from_slice(raw)?;
+            if let Some(sum) = raw["checksum"].as_str() {
+                custom.insert("checksum".into(), sum.into());
+            }
+            Ok(())
+        }
+    }
+
+    impl ResponseDecoder for Checksums {
+        fn decode_object(&self, entry: &[u8]) -> Result<ObjectInfo> {
+            let mut object = decode_object(entry)?;
+            Self::capture(entry, &mut object.metadata.custom)?;
+            Ok(object)
+        }
+
+        fn decode_metadata(&self, body: &[u8]) -> Result<Metadata> {
+            let mut metadata = decode_metadata(body)?;
+            Self::capture(body, &mut metadata.custom)?;
+            Ok(metadata)
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_decoder_captures_extended_fields() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects")
+            .with_body(r#"{"objects":[{"key":"a","size":1,"checksum":"crc32c:1f"}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/metadata/a")
+            .with_body(r#"{"key":"a","size":1,"checksum":"crc32c:1f","metadata":{"o":"p"}}"#)
+            .create_async()
+            .await;
+        let config = ClientConfig {
+            response_decoder: Some(Arc::new(Checksums)),
+            ..Default::default()
+        };
+        let client =
+            ObjectStoreClient::Rest(RestClient::new_with_config(server.url(), config).unwrap());
+
+        let listed = client.list(Default::default()).await.unwrap();
+        assert_eq!(listed.objects[0].metadata.custom["checksum"], "crc32c:1f");
+        let metadata = client.get_metadata("a").await.unwrap();
+        assert_eq!(metadata.custom["checksum"], "crc32c:1f");
+        assert_eq!(metadata.custom["o"], "p");
+
+        // The default decoder ignores the extra field.
+        let plain = DefaultDecoder
+            .decode_object(br#"{"key":"a","size":1,"checksum":"x"}"#)
+            .unwrap();
+        assert!(plain.metadata.custom.is_empty());
+    }
+}
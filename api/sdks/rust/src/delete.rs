@@ -0,0 +1,367 @@
+//! Deleting many objects at once.
+//!
+//! [`delete_many`] deletes a list of keys and returns each key's own
+//! result, so one failure does not hide the others, and
+//! [`delete_prefix`] deletes everything under a prefix:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let results = client.delete_many(&["tmp/a", "tmp/b", "tmp/c"]).await?;
+//! for failed in results.iter().filter(|r| !r.is_ok()) {
+//!     eprintln!("{}: {}", failed.key, failed.error.as_ref().unwrap());
+//! }
+//!
+//! let report = client.delete_prefix("test-runs/1234/").await?;
+//! println!("{} of {} deleted", report.deleted, report.scanned);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Over REST, [`ObjectStoreClient::delete_many`] sends the keys to the
+//! server's `POST /delete` bulk endpoint, [`DELETE_BATCH_SIZE`] keys per
+//! request.  Servers without the endpoint, and every other transport —
+//! gRPC has no batch RPC yet — delete key by key with
+//! [`DELETE_CONCURRENCY`] requests in flight.  Either way there is one
+//! result per distinct key, in the order given; the client method fails as
+//! a whole only when a bulk request itself cannot be made.
+//!
+//! [`delete_prefix`] lists the prefix a page at a time and deletes each
+//! page the same way, so it holds one page of keys in memory however many
+//! objects there are.  Objects written under the prefix while it runs may
+//! or may not be deleted.  An empty prefix is rejected rather than taken
+//! to mean the whole store.  Versions recorded by
+//! [`put_versioned`](crate::versions::put_versioned) are skipped, so past
+//! reads keep working; remove them with
+//! [`delete_versions`](crate::versions::delete_versions).
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::types::{ListRequest, ListResponse};
+use crate::versions::is_version_key;
+use futures::StreamExt;
+use std::collections::HashSet;
+
+/// Keys sent per bulk delete request.
+pub const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Deletes kept in flight when deleting key by key.
+pub const DELETE_CONCURRENCY: usize = 16;
+
+/// The outcome of deleting one key.
+#[derive(Debug)]
+pub struct DeleteResult {
+    pub key: String,
+    /// Why the key was not deleted, if it was not.
+    pub error: Option<Error>,
+}
+
+impl DeleteResult {
+    /// Whether the key was deleted.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// What [`delete_prefix`] did.
+#[derive(Debug, Default)]
+pub struct DeletePrefixReport {
+    /// Objects listed under the prefix.
+    pub scanned: u64,
+    /// Objects deleted.
+    pub deleted: u64,
+    /// Keys that could not be deleted, with the error.
+    pub failed: Vec<(String, Error)>,
+}
+
+impl DeletePrefixReport {
+    /// Whether every listed object was deleted.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Count `results`, noting failed keys in `failed` as well.
+    fn add(&mut self, results: Vec<DeleteResult>, failed: &mut HashSet<String>) {
+        for result in results {
+            match result.error {
+                None => self.deleted += 1,
+                Some(e) => {
+                    failed.insert(result.key.clone());
+                    self.failed.push((result.key, e));
+                }
+            }
+        }
+    }
+}
+
+/// Delete every key in `keys` one request at a time, with at most
+/// `concurrency` (minimum 1) in flight.  Works with any store.
+pub async fn delete_many(
+    store: &dyn ObjectStore,
+    keys: &[&str],
+    concurrency: usize,
+) -> Vec<DeleteResult> {
+    delete_each(store, &distinct(keys), concurrency).await
+}
+
+/// Delete every object under `prefix`, `concurrency` (minimum 1) at a
+/// time.  Works with any store; fails only if `prefix` is empty or
+/// listing it fails.
+pub async fn delete_prefix(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    concurrency: usize,
+) -> Result<DeletePrefixReport> {
+    let mut report = DeletePrefixReport::default();
+    let mut failed = HashSet::new();
+    let mut pages = list_pages(store, prefix_request(prefix)?);
+    while let Some(page) = pages.next().await {
+        let keys = page_keys(page?, &mut report, &failed);
+        report.add(delete_each(store, &keys, concurrency).await, &mut failed);
+    }
+    Ok(report)
+}
+
+fn prefix_request(prefix: &str) -> Result<ListRequest> {
+    if prefix.is_empty() {
+        return Err(Error::InvalidArgument(
+            "refusing to delete with an empty prefix".to_string(),
+        ));
+    }
+    Ok(ListRequest {
+        prefix: Some(prefix.to_string()),
+        max_results: Some(LIST_PAGE_SIZE),
+        ..Default::default()
+    })
+}
+
+/// The keys listed in `page` that have not already failed, counted as
+/// scanned.  Recorded versions are not listed.  Stores that restart a listing from the beginning once its
+/// continuation key is deleted, as the local backend does, list the keys
+/// that could not be deleted again; they are neither retried nor counted
+/// twice.
+fn page_keys(
+    page: ListResponse,
+    report: &mut DeletePrefixReport,
+    failed: &HashSet<String>,
+) -> Vec<String> {
+    let keys: Vec<String> = page
+        .objects
+        .into_iter()
+        .map(|o| o.key)
+        .filter(|key| !failed.contains(key) && !is_version_key(key))
+        .collect();
+    report.scanned += keys.len() as u64;
+    keys
+}
+
+async fn delete_each(
+    store: &dyn ObjectStore,
+    keys: &[String],
+    concurrency: usize,
+) -> Vec<DeleteResult> {
+    futures::stream::iter(keys)
+        .map(|key| async move {
+            DeleteResult {
+                key: key.clone(),
+                error: store.delete(key).await.err(),
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// `keys` without repeats, in first-seen order.
+fn distinct(keys: &[&str]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    keys.iter()
+        .filter(|key| seen.insert(**key))
+        .map(|key| key.to_string())
+        .collect()
+}
+
+impl ObjectStoreClient {
+    /// Delete every key in `keys`, in bulk where the server supports it.
+    /// See the [`delete`](crate::delete) module.
+    pub async fn delete_many(&self, keys: &[&str]) -> Result<Vec<DeleteResult>> {
+        let keys = distinct(keys);
+        let mut results = Vec::with_capacity(keys.len());
+        if let ObjectStoreClient::Rest(client) = self {
+            for batch in keys.chunks(DELETE_BATCH_SIZE) {
+                match client.delete_batch(batch).await? {
+                    Some(deleted) => results.extend(deleted),
+                    None => break,
+                }
+            }
+        }
+        let rest = &keys[results.len()..];
+        results.extend(delete_each(self, rest, DELETE_CONCURRENCY).await);
+        Ok(results)
+    }
+
+    /// Delete every object under `prefix`, a listing page at a time with
+    /// [`delete_many`](Self::delete_many).  See the
+    /// [`delete`](crate::delete) module.
+    pub async fn delete_prefix(&self, prefix: &str) -> Result<DeletePrefixReport> {
+        let mut report = DeletePrefixReport::default();
+        let mut failed = HashSet::new();
+        let mut pages = list_pages(self, prefix_request(prefix)?);
+        while let Some(page) = pages.next().await {
+            let keys = page_keys(page?, &mut report, &failed);
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            report.add(self.delete_many(&keys).await?, &mut failed);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::rest_client::RestClient;
+    use crate::test_support::MemoryStore;
+    use mockito::Matcher;
+
+    /// Lists two keys at a time and refuses to delete keys ending in
+    /// `locked`.
+    fn keys(keys: &[&str]) -> MemoryStore {
+        let store = MemoryStore::default().with_page_size(2);
+        for key in keys {
+            store.insert(key, "");
+        }
+        store.fail(Operation::Delete, |key| {
+            key.ends_with("locked")
+                .then(|| Error::Forbidden(key.to_string()))
+        });
+        store
+    }
+
+    #[tokio::test]
+    async fn reports_each_key_in_order() {
+        let store = keys(&["a", "c"]);
+
+        let results = delete_many(&store, &["c", "b", "a", "c"], 2).await;
+        let outcome: Vec<_> = results
+            .iter()
+            .map(|r| (r.key.as_str(), r.is_ok()))
+            .collect();
+        assert_eq!(outcome, [("c", true), ("b", false), ("a", true)]);
+        assert!(matches!(results[1].error, Some(Error::NotFound(_))));
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn deletes_a_prefix_page_by_page() {
+        let store = keys(&["t/1", "t/2", "t/3-locked", "t/4", "t/5", "u/1"]);
+
+        let report = delete_prefix(&store, "t/", 2).await.unwrap();
+        assert_eq!((report.scanned, report.deleted), (5, 4));
+        assert!(!report.is_complete());
+        assert_eq!(report.failed[0].0, "t/3-locked");
+        assert_eq!(store.keys(), ["t/3-locked", "u/1"]);
+
+        let err = delete_prefix(&store, "", 2).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)));
+    }
+
+    #[tokio::test]
+    async fn recorded_versions_survive_a_prefix_delete() {
+        let version = crate::versions::version_key("t/1", chrono::Utc::now());
+        let store = keys(&["t/1", &version]);
+
+        let report = delete_prefix(&store, "t/", 2).await.unwrap();
+        assert_eq!((report.scanned, report.deleted), (1, 1));
+        assert_eq!(store.keys(), [version]);
+    }
+
+    #[tokio::test]
+    async fn failed_keys_relisted_after_a_restart_count_once() {
+        let store = keys(&["t/1", "t/2-locked", "t/3", "t/4", "t/5"]).restarting_unknown_tokens();
+
+        let report = delete_prefix(&store, "t/", 2).await.unwrap();
+        assert_eq!((report.scanned, report.deleted), (5, 4));
+        let failed: Vec<_> = report.failed.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(failed, ["t/2-locked"]);
+        assert_eq!(store.calls(Operation::Delete), 5);
+        assert_eq!(store.keys(), ["t/2-locked"]);
+    }
+
+    #[tokio::test]
+    async fn rest_deletes_in_bulk() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/delete")
+            .match_body(Matcher::Json(serde_json::json!({"keys": ["a", "b", "c"]})))
+            .with_body(
+                r#"{"deleted":["a","c"],
+                    "errors":[{"key":"b","code":404,"message":"object not found"}]}"#,
+            )
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let results = client.delete_many(&["a", "b", "c"]).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(matches!(results[1].error, Some(Error::NotFound(ref k)) if k == "b"));
+    }
+
+    #[tokio::test]
+    async fn rest_deletes_a_prefix_in_bulk() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "t/".into()))
+            .with_body(r#"{"objects":[{"key":"t/a","size":1},{"key":"t/b","size":1}]}"#)
+            .create_async()
+            .await;
+        let bulk = server
+            .mock("POST", "/delete")
+            .match_body(Matcher::Json(serde_json::json!({"keys": ["t/a", "t/b"]})))
+            .with_body(r#"{"deleted":["t/a","t/b"]}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client.delete_prefix("t/").await.unwrap();
+        assert_eq!((report.scanned, report.deleted), (2, 2));
+        assert!(report.is_complete());
+        bulk.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_falls_back_to_single_deletes() {
+        let mut server = mockito::Server::new_async().await;
+        let bulk = server
+            .mock("POST", "/delete")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("DELETE", "/objects/a")
+            .with_body(r#"{"message":"deleted"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("DELETE", "/objects/b")
+            .with_status(404)
+            .with_body(r#"{"error":"object not found"}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let results = client.delete_many(&["a", "b"]).await.unwrap();
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1].error, Some(Error::NotFound(_))));
+
+        // The missing endpoint is remembered, also by clones.
+        let results = client.clone().delete_many(&["a"]).await.unwrap();
+        assert!(results[0].is_ok());
+        bulk.assert_async().await;
+    }
+}
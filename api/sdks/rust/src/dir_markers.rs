@@ -0,0 +1,139 @@
+//! Zero-byte "directory marker" objects.
+//!
+//! Object stores have no directories, but many tools (S3 consoles, FUSE
+//! mounts, sync clients) create an empty object named `photos/2024/` so an
+//! empty folder shows up in tree views.  [`create_dir_marker`] writes one
+//! the same way, [`is_dir_marker`] recognises them, and
+//! [`DirMarkers::Hide`] on a [`ListRequest`](crate::ListRequest) drops them
+//! from `ObjectStoreClient` listings so they do not appear as files:
+//!
+//! ```no_run
+//! use go_objstore::{DirMarkers, ListRequest, ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! client.create_dir_marker("photos/2024").await?;
+//!
+//! let page = client
+//!     .list(ListRequest {
+//!         prefix: Some("photos/".into()),
+//!         delimiter: Some("/".into()),
+//!         dir_markers: DirMarkers::Hide,
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! assert_eq!(page.common_prefixes, ["photos/2024/"]);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::{DirMarkers, ListResponse, Metadata, ObjectInfo, PutResponse};
+use bytes::Bytes;
+
+/// Content type given to markers by [`create_dir_marker`].
+pub const DIR_MARKER_CONTENT_TYPE: &str = "application/x-directory";
+
+/// Create a marker for `prefix`; a trailing `/` is added if missing.
+pub async fn create_dir_marker(store: &dyn ObjectStore, prefix: &str) -> Result<PutResponse> {
+    if prefix.trim_end_matches('/').is_empty() {
+        return Err(Error::InvalidArgument(
+            "directory marker prefix must not be empty".into(),
+        ));
+    }
+    let key = if prefix.ends_with('/') {
+        prefix.to_string()
+    } else {
+        format!("{prefix}/")
+    };
+    let metadata = Metadata {
+        content_type: Some(DIR_MARKER_CONTENT_TYPE.to_string()),
+        ..Default::default()
+    };
+    store.put(&key, Bytes::new(), Some(metadata)).await
+}
+
+/// Whether `info` is a directory marker: an empty object whose key ends
+/// in `/`.
+pub fn is_dir_marker(info: &ObjectInfo) -> bool {
+    info.key.ends_with('/') && info.metadata.size == 0
+}
+
+/// Apply a request's [`DirMarkers`] option to a listing page.
+pub(crate) fn apply(markers: DirMarkers, mut page: ListResponse) -> ListResponse {
+    if markers == DirMarkers::Hide {
+        page.objects.retain(|o| !is_dir_marker(o));
+    }
+    page
+}
+
+impl ObjectStoreClient {
+    /// Create a directory marker for `prefix`.  See the
+    /// [`dir_markers`](crate::dir_markers) module.
+    pub async fn create_dir_marker(&self, prefix: &str) -> Result<PutResponse> {
+        create_dir_marker(self, prefix).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use crate::types::ListRequest;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn creates_marker_with_trailing_slash() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/photos%2F2024%2F")
+            .match_header("content-type", DIR_MARKER_CONTENT_TYPE)
+            .match_body("")
+            .with_status(201)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        client.create_dir_marker("photos/2024").await.unwrap();
+        put.assert_async().await;
+        assert!(matches!(
+            client.create_dir_marker("/").await,
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn hides_markers_only_when_asked() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"objects":[
+                    {"key":"photos/","size":0},
+                    {"key":"photos/a.jpg","size":0},
+                    {"key":"photos/b.jpg","size":12}
+                ],"truncated":false}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+        let keys = |page: ListResponse| -> Vec<String> {
+            page.objects.into_iter().map(|o| o.key).collect()
+        };
+
+        let shown = client.list(ListRequest::default()).await.unwrap();
+        assert_eq!(keys(shown), ["photos/", "photos/a.jpg", "photos/b.jpg"]);
+        let hidden = client
+            .list(ListRequest {
+                dir_markers: DirMarkers::Hide,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(keys(hidden), ["photos/a.jpg", "photos/b.jpg"]);
+    }
+}
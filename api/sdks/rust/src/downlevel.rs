@@ -0,0 +1,48 @@
+//! One-time warnings for request fields a server silently drops.
+//!
+//! Some transports accept a request but ignore part of it — the MCP list
+//! tool has no delimiter, and QUIC custom metadata travels as header names,
+//! which lose their case.  Rather than failing, the clients call
+//! [`warn_once`], which logs a structured `tracing` warning under the
+//! [`TARGET`] target the first time each protocol/feature pair is hit in the
+//! process, so the gap is visible without flooding the log.
+
+use crate::client::Protocol;
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+/// `tracing` target of down-level warnings, for filtering or routing them.
+pub(crate) const TARGET: &str = "go_objstore::downlevel";
+
+static WARNED: OnceLock<Mutex<HashSet<(Protocol, &'static str)>>> = OnceLock::new();
+
+/// Warn that `feature` is dropped over `protocol`, unless already warned.
+/// Returns whether the warning was emitted.
+pub(crate) fn warn_once(protocol: Protocol, feature: &'static str, detail: &str) -> bool {
+    let first = WARNED
+        .get_or_init(Mutex::default)
+        .lock()
+        .map(|mut warned| warned.insert((protocol, feature)))
+        .unwrap_or(false);
+    if first {
+        tracing::warn!(
+            target: TARGET,
+            protocol = protocol.as_str(),
+            feature,
+            "server ignores {feature} over this transport: {detail}"
+        );
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_once_per_protocol_and_feature() {
+        assert!(warn_once(Protocol::Mcp, "test-feature", "dropped"));
+        assert!(!warn_once(Protocol::Mcp, "test-feature", "dropped"));
+        assert!(warn_once(Protocol::Quic, "test-feature", "dropped"));
+    }
+}
@@ -15,6 +15,15 @@ pub fn parse_go_duration_ms(s: &str) -> i64 {
         .unwrap_or(0)
 }
 
+/// Parse a Go `time.Duration` string (such as `"250ms"` or `"1m30s"`),
+/// returning `None` on malformed or negative input.
+pub(crate) fn parse_go_duration(s: &str) -> Option<std::time::Duration> {
+    let nanos = parse_go_duration_nanos(s)?;
+    u64::try_from(nanos)
+        .ok()
+        .map(std::time::Duration::from_nanos)
+}
+
 /// Parse a Go `time.Duration` string into nanoseconds, returning `None` on
 /// malformed input.
 fn parse_go_duration_nanos(s: &str) -> Option<i64> {
@@ -0,0 +1,370 @@
+//! Client-side encryption of sensitive metadata fields.
+//!
+//! Custom metadata is returned in plaintext by `get_metadata` and by every
+//! listing, so secrets stored there (customer IDs, source paths, API
+//! references) are visible to anyone who can list the bucket.
+//! [`EncryptedMetadataStore`] wraps a store and encrypts the values of the
+//! custom metadata keys marked sensitive before they leave the client,
+//! decrypting them again on reads and listings:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::encrypted_metadata::{EncryptedMetadataStore, MetadataCipher};
+//! use go_objstore::{Metadata, ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example(key: [u8; 32]) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let store = EncryptedMetadataStore::new(client, MetadataCipher::new(&key)?)
+//!     .sensitive(["customer-id", "source-path"]);
+//!
+//! let mut metadata = Metadata::default();
+//! metadata.custom.insert("customer-id".into(), "cus_8213".into());
+//! store.put("invoices/1.pdf", Bytes::from("..."), Some(metadata)).await?;
+//!
+//! let metadata = store.get_metadata("invoices/1.pdf").await?;
+//! assert_eq!(metadata.custom["customer-id"], "cus_8213");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Values are sealed with AES-256-GCM under a fresh random nonce and stored
+//! as [`ENCRYPTED_PREFIX`] followed by base64 of the nonce and ciphertext.
+//! The object key and the metadata key are bound in as associated data, so
+//! a sealed value cannot be moved to another field or object.  Reads
+//! decrypt every prefixed value, whether or not its key is still marked
+//! sensitive; one that fails to decrypt (wrong key or tampering) fails the
+//! call with [`Error::InvalidResponse`].  Writes seal every sensitive value,
+//! and any other value that starts with the prefix, so that it reads back
+//! as written.  Only metadata values are encrypted: object bodies, keys,
+//! and metadata key names are stored as given.
+//!
+//! Server-side copies and renames keep the sealed values of the source
+//! key, which then fail to decrypt; copy through the wrapper instead.
+
+use crate::client::{ByteStream, ObjectStore};
+use crate::error::{Error, Result};
+use crate::types::*;
+use async_trait::async_trait;
+use base64::Engine as _;
+use bytes::Bytes;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Prefix tagging an encrypted metadata value.
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// AES-256-GCM key for sealing metadata values.
+pub struct MetadataCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl MetadataCipher {
+    /// Build a cipher from a 32-byte key.
+    pub fn new(key: &[u8]) -> Result<Self> {
+        let key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| Error::Configuration("metadata encryption key must be 32 bytes".into()))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Seal `value` as the metadata field `field` of the object `key`.
+    pub fn encrypt(&self, key: &str, field: &str, value: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| Error::Generic("failed to generate nonce".into()))?;
+        let mut sealed = value.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad(key, field)),
+                &mut sealed,
+            )
+            .map_err(|_| Error::Generic(format!("failed to encrypt metadata field {field}")))?;
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&sealed);
+        Ok(format!(
+            "{ENCRYPTED_PREFIX}{}",
+            base64::engine::general_purpose::STANDARD.encode(out)
+        ))
+    }
+
+    /// Open a value produced by [`encrypt`](Self::encrypt) for `key` and
+    /// `field`.  Values without [`ENCRYPTED_PREFIX`] are returned unchanged.
+    pub fn decrypt(&self, key: &str, field: &str, value: &str) -> Result<String> {
+        let Some(encoded) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let invalid = || Error::InvalidResponse(format!("cannot decrypt metadata field {field}"));
+        let mut raw = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| invalid())?;
+        if raw.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let mut sealed = raw.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&raw).map_err(|_| invalid())?;
+        let plain = self
+            .key
+            .open_in_place(nonce, Aad::from(aad(key, field)), &mut sealed)
+            .map_err(|_| invalid())?;
+        String::from_utf8(plain.to_vec()).map_err(|_| invalid())
+    }
+}
+
+/// `key || 0 || field`; object keys cannot contain NUL.
+fn aad(key: &str, field: &str) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(key.len() + 1 + field.len());
+    aad.extend_from_slice(key.as_bytes());
+    aad.push(0);
+    aad.extend_from_slice(field.as_bytes());
+    aad
+}
+
+impl fmt::Debug for MetadataCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetadataCipher")
+            .field("key", &crate::secret::REDACTED)
+            .finish()
+    }
+}
+
+/// A store that encrypts sensitive custom metadata values.  See the
+/// [module docs](self).
+pub struct EncryptedMetadataStore<S> {
+    inner: S,
+    cipher: MetadataCipher,
+    sensitive: BTreeSet<String>,
+}
+
+impl<S: ObjectStore> EncryptedMetadataStore<S> {
+    /// Wrap `inner`; no fields are sensitive until marked with
+    /// [`sensitive`](Self::sensitive).
+    pub fn new(inner: S, cipher: MetadataCipher) -> Self {
+        Self {
+            inner,
+            cipher,
+            sensitive: BTreeSet::new(),
+        }
+    }
+
+    /// Mark custom metadata keys whose values are encrypted on write.
+    pub fn sensitive<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.sensitive.extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn seal(&self, key: &str, mut metadata: Metadata) -> Result<Metadata> {
+        for (field, value) in metadata.custom.iter_mut() {
+            if self.sensitive.contains(field) || value.starts_with(ENCRYPTED_PREFIX) {
+                *value = self.cipher.encrypt(key, field, value)?;
+            }
+        }
+        Ok(metadata)
+    }
+
+    fn open(&self, key: &str, mut metadata: Metadata) -> Result<Metadata> {
+        for (field, value) in metadata.custom.iter_mut() {
+            if value.starts_with(ENCRYPTED_PREFIX) {
+                *value = self.cipher.decrypt(key, field, value)?;
+            }
+        }
+        Ok(metadata)
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for EncryptedMetadataStore<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        let metadata = metadata.map(|m| self.seal(key, m)).transpose()?;
+        self.inner.put(key, data, metadata).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        let (data, metadata) = self.inner.get(key).await?;
+        Ok((data, self.open(key, metadata)?))
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.inner.delete(key).await
+    }
+
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        let mut page = self.inner.list(list_req).await?;
+        for object in &mut page.objects {
+            object.metadata = self.open(&object.key, std::mem::take(&mut object.metadata))?;
+        }
+        Ok(page)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.open(key, self.inner.get_metadata(key).await?)
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.inner
+            .update_metadata(key, self.seal(key, metadata)?)
+            .await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.inner.health().await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        let (stream, metadata) = self.inner.get_stream(key).await?;
+        Ok((stream, self.open(key, metadata)?))
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        let metadata = metadata.map(|m| self.seal(key, m)).transpose()?;
+        self.inner.put_stream(key, stream, metadata).await
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for EncryptedMetadataStore<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedMetadataStore")
+            .field("inner", &self.inner)
+            .field("sensitive", &self.sensitive)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+
+    fn metadata(fields: &[(&str, &str)]) -> Metadata {
+        Metadata {
+            custom: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn store(key: u8) -> EncryptedMetadataStore<MemoryStore> {
+        EncryptedMetadataStore::new(
+            MemoryStore::default(),
+            MetadataCipher::new(&[key; 32]).unwrap(),
+        )
+        .sensitive(["owner"])
+    }
+
+    #[tokio::test]
+    async fn sensitive_values_are_sealed_at_rest() {
+        let store = store(7);
+        let fields = [("owner", "alice@example.com"), ("kind", "invoice")];
+        store
+            .put("k", Bytes::new(), Some(metadata(&fields)))
+            .await
+            .unwrap();
+
+        let raw = store.inner().object("k").unwrap().1;
+        assert!(raw.custom["owner"].starts_with(ENCRYPTED_PREFIX));
+        assert!(!raw.custom["owner"].contains("alice"));
+        assert_eq!(raw.custom["kind"], "invoice");
+
+        let read = store.get_metadata("k").await.unwrap();
+        assert_eq!(read.custom, metadata(&fields).custom);
+        let page = store.list(ListRequest::default()).await.unwrap();
+        assert_eq!(
+            page.objects[0].metadata.custom["owner"],
+            "alice@example.com"
+        );
+
+        store
+            .update_metadata("k", metadata(&[("owner", "bob")]))
+            .await
+            .unwrap();
+        let raw = store.inner().object("k").unwrap().1;
+        assert!(raw.custom["owner"].starts_with(ENCRYPTED_PREFIX));
+        assert_eq!(store.get("k").await.unwrap().1.custom["owner"], "bob");
+    }
+
+    #[test]
+    fn ciphertexts_are_bound_to_key_and_field() {
+        let cipher = MetadataCipher::new(&[1; 32]).unwrap();
+        let sealed = cipher.encrypt("k", "owner", "alice").unwrap();
+        assert_ne!(sealed, cipher.encrypt("k", "owner", "alice").unwrap());
+        assert_eq!(cipher.decrypt("k", "owner", &sealed).unwrap(), "alice");
+        assert_eq!(cipher.decrypt("k", "owner", "plain").unwrap(), "plain");
+
+        assert!(matches!(
+            cipher.decrypt("k", "tenant", &sealed),
+            Err(Error::InvalidResponse(_))
+        ));
+        assert!(cipher.decrypt("other", "owner", &sealed).is_err());
+        // The separator keeps key and field from running together.
+        assert!(cipher.decrypt("k\0own", "er", &sealed).is_err());
+        let other = MetadataCipher::new(&[2; 32]).unwrap();
+        assert!(other.decrypt("k", "owner", &sealed).is_err());
+        assert!(cipher.decrypt("k", "owner", "enc:v1:!!").is_err());
+        assert!(matches!(
+            MetadataCipher::new(&[0; 16]),
+            Err(Error::Configuration(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn values_that_look_sealed_read_back_as_written() {
+        let store = store(7);
+        let fields = [("owner", "enc:v1:alice"), ("kind", "enc:v1:invoice")];
+        store
+            .put("k", Bytes::new(), Some(metadata(&fields)))
+            .await
+            .unwrap();
+
+        let raw = store.inner().object("k").unwrap().1;
+        assert_ne!(raw.custom["owner"], "enc:v1:alice");
+        assert_ne!(raw.custom["kind"], "enc:v1:invoice");
+        let read = store.get_metadata("k").await.unwrap();
+        assert_eq!(read.custom, metadata(&fields).custom);
+    }
+
+    #[tokio::test]
+    async fn sealed_values_do_not_open_under_another_key() {
+        let store = store(7);
+        store
+            .put("a", Bytes::new(), Some(metadata(&[("owner", "alice")])))
+            .await
+            .unwrap();
+        let raw = store.inner().object("a").unwrap().1;
+        store
+            .inner()
+            .put("b", Bytes::new(), Some(raw))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            store.get_metadata("b").await,
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+}
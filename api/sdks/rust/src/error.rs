@@ -1,3 +1,4 @@
+use crate::client::{Operation, Protocol};
 use thiserror::Error;
 
 /// Result type alias for the go-objstore SDK
@@ -8,15 +9,19 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     /// gRPC transport error
     #[error("gRPC error: {0}")]
-    GrpcTransport(#[from] tonic::transport::Error),
+    GrpcTransport(tonic::transport::Error),
 
     /// gRPC status error without a dedicated SDK variant
     #[error("gRPC status error: {0}")]
-    GrpcStatus(tonic::Status),
+    GrpcStatus(Box<tonic::Status>),
 
     /// HTTP request error
     #[error("HTTP error: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(reqwest::Error),
+
+    /// A request stage ran past its limit (see [`Timeouts`](crate::Timeouts))
+    #[error("Timed out: {0}")]
+    Timeout(TimeoutStage),
 
     /// QUIC/HTTP3 connection error
     #[error("QUIC connection error: {0}")]
@@ -30,9 +35,31 @@ pub enum Error {
     #[error("QUIC write error: {0}")]
     QuicWrite(#[from] quinn::WriteError),
 
-    /// H3 error
-    #[error("H3 error: {0}")]
-    H3(String),
+    /// QUIC handshakes kept failing; `suggest` is a protocol to fall back
+    /// to (see `HandshakeRetry::fallback`)
+    #[error("QUIC unavailable after {attempts} handshake attempt(s): {reason}; try {suggest}")]
+    QuicUnavailable {
+        attempts: u32,
+        reason: String,
+        suggest: Protocol,
+    },
+
+    /// The HTTP/3 session ended under an in-flight request: the server
+    /// sent GOAWAY or rejected the request, or the connection was lost
+    /// (e.g. after a NAT rebind it could not migrate across).
+    /// `unprocessed` is set when the server guarantees it did not act on
+    /// the request, so even non-idempotent requests may be re-sent.
+    #[error("QUIC session closed: {reason}")]
+    QuicSessionClosed { reason: String, unprocessed: bool },
+
+    /// HTTP/3 error, with the code the peer or the local endpoint sent and
+    /// whether it failed one request stream or the whole connection
+    #[error("H3 {scope} error {code}: {message}")]
+    H3 {
+        code: H3ErrorCode,
+        scope: H3ErrorScope,
+        message: String,
+    },
 
     /// Serialization error
     #[error("Serialization error: {0}")]
@@ -74,6 +101,22 @@ pub enum Error {
     #[error("Operation failed: {0}")]
     OperationFailed(String),
 
+    /// A write precondition did not hold (HTTP 412, gRPC `FailedPrecondition`)
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    /// Object larger than the caller allowed (see `get_limited`)
+    #[error("Payload too large: {key} exceeds {limit} bytes")]
+    PayloadTooLarge { key: String, limit: u64 },
+
+    /// A write rejected by a client-side policy (see `PolicyGuard`)
+    #[error("Policy violation: {key}: {reason}")]
+    PolicyViolation { key: String, reason: String },
+
+    /// Operation cancelled before completing
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
     /// Invalid response
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
@@ -86,17 +129,180 @@ pub enum Error {
     #[error("TLS error: {0}")]
     Tls(String),
 
+    /// The client's protocol does not offer the operation
+    #[error("{operation} is not supported over {protocol}")]
+    UnsupportedByProtocol {
+        operation: Operation,
+        protocol: Protocol,
+    },
+
     /// Generic error
     #[error("{0}")]
     Generic(String),
 }
 
+/// An HTTP/3 application error code (RFC 9114, section 8.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct H3ErrorCode(pub u64);
+
+impl H3ErrorCode {
+    pub const NO_ERROR: Self = Self(0x100);
+    pub const GENERAL_PROTOCOL_ERROR: Self = Self(0x101);
+    pub const INTERNAL_ERROR: Self = Self(0x102);
+    pub const STREAM_CREATION_ERROR: Self = Self(0x103);
+    pub const CLOSED_CRITICAL_STREAM: Self = Self(0x104);
+    pub const FRAME_UNEXPECTED: Self = Self(0x105);
+    pub const FRAME_ERROR: Self = Self(0x106);
+    pub const EXCESSIVE_LOAD: Self = Self(0x107);
+    pub const ID_ERROR: Self = Self(0x108);
+    pub const SETTINGS_ERROR: Self = Self(0x109);
+    pub const MISSING_SETTINGS: Self = Self(0x10a);
+    pub const REQUEST_REJECTED: Self = Self(0x10b);
+    pub const REQUEST_CANCELLED: Self = Self(0x10c);
+    pub const REQUEST_INCOMPLETE: Self = Self(0x10d);
+    pub const MESSAGE_ERROR: Self = Self(0x10e);
+    pub const CONNECT_ERROR: Self = Self(0x10f);
+
+    /// The code's registered name, e.g. `H3_EXCESSIVE_LOAD`.
+    pub fn name(self) -> Option<&'static str> {
+        Some(match self.0 {
+            0x100 => "H3_NO_ERROR",
+            0x101 => "H3_GENERAL_PROTOCOL_ERROR",
+            0x102 => "H3_INTERNAL_ERROR",
+            0x103 => "H3_STREAM_CREATION_ERROR",
+            0x104 => "H3_CLOSED_CRITICAL_STREAM",
+            0x105 => "H3_FRAME_UNEXPECTED",
+            0x106 => "H3_FRAME_ERROR",
+            0x107 => "H3_EXCESSIVE_LOAD",
+            0x108 => "H3_ID_ERROR",
+            0x109 => "H3_SETTINGS_ERROR",
+            0x10a => "H3_MISSING_SETTINGS",
+            0x10b => "H3_REQUEST_REJECTED",
+            0x10c => "H3_REQUEST_CANCELLED",
+            0x10d => "H3_REQUEST_INCOMPLETE",
+            0x10e => "H3_MESSAGE_ERROR",
+            0x10f => "H3_CONNECT_ERROR",
+            _ => return None,
+        })
+    }
+
+    /// Whether the code reports a condition that may clear on its own
+    /// (load, cancellation, a dropped connection) rather than a protocol
+    /// violation that a retry would repeat.
+    pub fn is_transient(self) -> bool {
+        matches!(
+            self,
+            Self::NO_ERROR
+                | Self::INTERNAL_ERROR
+                | Self::EXCESSIVE_LOAD
+                | Self::REQUEST_CANCELLED
+                | Self::REQUEST_INCOMPLETE
+                | Self::CONNECT_ERROR
+        )
+    }
+}
+
+impl std::fmt::Display for H3ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{:#x}", self.0),
+        }
+    }
+}
+
+impl From<h3::error::Code> for H3ErrorCode {
+    fn from(code: h3::error::Code) -> Self {
+        Self(code.value())
+    }
+}
+
+/// What an [`Error::H3`] failed: one request stream, or the connection
+/// and every request on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum H3ErrorScope {
+    Stream,
+    Connection,
+}
+
+impl std::fmt::Display for H3ErrorScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Stream => "stream",
+            Self::Connection => "connection",
+        })
+    }
+}
+
+/// The stage of a request that an [`Error::Timeout`] ran out of time in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeoutStage {
+    /// Opening the connection (TCP connect or QUIC handshake).
+    Connect,
+    /// Completing the TLS handshake on an open connection.
+    TlsHandshake,
+    /// Sending the request headers and body.
+    RequestSend,
+    /// Waiting for the response headers after the request was sent.
+    ResponseHeaders,
+    /// Receiving the response body.
+    BodyTransfer,
+}
+
+impl std::fmt::Display for TimeoutStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Connect => "connect",
+            Self::TlsHandshake => "TLS handshake",
+            Self::RequestSend => "request send",
+            Self::ResponseHeaders => "response headers",
+            Self::BodyTransfer => "body transfer",
+        })
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    /// Timeouts become [`Error::Timeout`] with the stage reqwest was in;
+    /// everything else is [`Error::Http`].
+    fn from(err: reqwest::Error) -> Self {
+        if !err.is_timeout() {
+            Error::Http(err)
+        } else if err.is_connect() {
+            Error::Timeout(TimeoutStage::Connect)
+        } else if err.is_body() || err.is_decode() {
+            Error::Timeout(TimeoutStage::BodyTransfer)
+        } else {
+            Error::Timeout(TimeoutStage::ResponseHeaders)
+        }
+    }
+}
+
+impl From<tonic::transport::Error> for Error {
+    /// A channel connect that ran past its timeout becomes
+    /// [`Error::Timeout`] in [`TimeoutStage::Connect`]; other failures are
+    /// [`Error::GrpcTransport`].
+    fn from(err: tonic::transport::Error) -> Self {
+        let mut source = std::error::Error::source(&err);
+        while let Some(inner) = source {
+            let timed_out = inner
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::TimedOut);
+            if timed_out {
+                return Error::Timeout(TimeoutStage::Connect);
+            }
+            source = inner.source();
+        }
+        Error::GrpcTransport(err)
+    }
+}
+
 /// Map an HTTP status code to the canonical SDK [`Error`].
 ///
 /// Canonical table: 400 -> [`Error::InvalidArgument`], 401 ->
 /// [`Error::Unauthenticated`], 403 -> [`Error::Forbidden`], 404 ->
-/// [`Error::NotFound`], 409 -> [`Error::AlreadyExists`], 429 ->
-/// [`Error::RateLimited`]; any other failure status ->
+/// [`Error::NotFound`], 409 -> [`Error::AlreadyExists`], 412 ->
+/// [`Error::PreconditionFailed`], 429 -> [`Error::RateLimited`]; any other
+/// failure status ->
 /// [`Error::OperationFailed`].
 ///
 /// `resource` names the object key or policy id involved and is carried by
@@ -114,6 +320,7 @@ pub(crate) fn error_from_http_status(
         403 => Error::Forbidden(message),
         404 => Error::NotFound(resource.map_or(message, str::to_string)),
         409 => Error::AlreadyExists(resource.map_or(message, str::to_string)),
+        412 => Error::PreconditionFailed(resource.map_or(message, str::to_string)),
         429 => Error::RateLimited(message),
         _ => Error::OperationFailed(message),
     }
@@ -124,9 +331,12 @@ impl From<tonic::Status> for Error {
     ///
     /// Canonical table: `NotFound` -> [`Error::NotFound`], `PermissionDenied`
     /// -> [`Error::Forbidden`], `Unauthenticated` -> [`Error::Unauthenticated`],
-    /// `AlreadyExists` -> [`Error::AlreadyExists`], `ResourceExhausted` ->
+    /// `AlreadyExists` -> [`Error::AlreadyExists`], `FailedPrecondition` ->
+    /// [`Error::PreconditionFailed`], `ResourceExhausted` ->
     /// [`Error::RateLimited`], `InvalidArgument` -> [`Error::InvalidArgument`];
-    /// any other code is surfaced as [`Error::GrpcStatus`].
+    /// any other code is surfaced as [`Error::GrpcStatus`].  The `Cancelled`
+    /// status tonic reports when the channel's request timeout expires
+    /// becomes [`Error::Timeout`] in [`TimeoutStage::ResponseHeaders`].
     fn from(status: tonic::Status) -> Self {
         let message = status.message().to_string();
         match status.code() {
@@ -134,25 +344,132 @@ impl From<tonic::Status> for Error {
             tonic::Code::PermissionDenied => Error::Forbidden(message),
             tonic::Code::Unauthenticated => Error::Unauthenticated(message),
             tonic::Code::AlreadyExists => Error::AlreadyExists(message),
+            tonic::Code::FailedPrecondition => Error::PreconditionFailed(message),
             tonic::Code::ResourceExhausted => Error::RateLimited(message),
             tonic::Code::InvalidArgument => Error::InvalidArgument(message),
-            _ => Error::GrpcStatus(status),
+            tonic::Code::Cancelled if message == "Timeout expired" => {
+                Error::Timeout(TimeoutStage::ResponseHeaders)
+            }
+            _ => Error::GrpcStatus(Box::new(status)),
         }
     }
 }
 
 impl From<h3::error::ConnectionError> for Error {
     fn from(err: h3::error::ConnectionError) -> Self {
-        Error::H3(err.to_string())
+        connection_lost(&err).unwrap_or_else(|| connection_error(&err))
     }
 }
 
 impl From<h3::error::StreamError> for Error {
     fn from(err: h3::error::StreamError) -> Self {
-        Error::H3(err.to_string())
+        use h3::error::{Code, StreamError};
+
+        let closed = |reason: &str| Error::QuicSessionClosed {
+            reason: reason.to_string(),
+            unprocessed: true,
+        };
+        match &err {
+            StreamError::RemoteClosing { .. } => closed("server sent GOAWAY"),
+            StreamError::StreamError { code, .. } | StreamError::RemoteTerminate { code, .. }
+                if *code == Code::H3_REQUEST_REJECTED =>
+            {
+                closed("server rejected the request")
+            }
+            StreamError::ConnectionError { 0: conn, .. } => {
+                connection_lost(conn).unwrap_or_else(|| connection_error(conn))
+            }
+            StreamError::StreamError { code, .. } | StreamError::RemoteTerminate { code, .. } => {
+                Error::H3 {
+                    code: (*code).into(),
+                    scope: H3ErrorScope::Stream,
+                    message: err.to_string(),
+                }
+            }
+            StreamError::HeaderTooBig { .. } => Error::H3 {
+                code: H3ErrorCode::MESSAGE_ERROR,
+                scope: H3ErrorScope::Stream,
+                message: err.to_string(),
+            },
+            _ => Error::H3 {
+                code: H3ErrorCode::INTERNAL_ERROR,
+                scope: H3ErrorScope::Stream,
+                message: err.to_string(),
+            },
+        }
     }
 }
 
+/// Map a connection error that [`connection_lost`] did not classify.  QUIC
+/// transport failures keep their quinn error; everything else carries the
+/// HTTP/3 code the connection was closed with.
+fn connection_error(err: &h3::error::ConnectionError) -> Error {
+    use h3::error::{ConnectionError, LocalError};
+    use h3::quic::ConnectionErrorIncoming;
+
+    let code = match err {
+        ConnectionError::Local {
+            error: LocalError::Application { code, .. },
+            ..
+        } => (*code).into(),
+        ConnectionError::Local { .. } => H3ErrorCode::NO_ERROR,
+        ConnectionError::Remote {
+            0: ConnectionErrorIncoming::ApplicationClose { error_code },
+            ..
+        } => H3ErrorCode(*error_code),
+        ConnectionError::Remote {
+            0: ConnectionErrorIncoming::Undefined(e),
+            ..
+        } => {
+            if let Some(quic) = e.downcast_ref::<quinn::ConnectionError>() {
+                return Error::QuicConnection(quic.clone());
+            }
+            H3ErrorCode::INTERNAL_ERROR
+        }
+        _ => H3ErrorCode::INTERNAL_ERROR,
+    };
+    Error::H3 {
+        code,
+        scope: H3ErrorScope::Connection,
+        message: err.to_string(),
+    }
+}
+
+/// Classify a connection that went away under a request: closed without
+/// error after a GOAWAY, idle timeout, or a stateless reset (what a
+/// server sends for packets from an address it no longer recognises).
+fn connection_lost(err: &h3::error::ConnectionError) -> Option<Error> {
+    use h3::error::ConnectionError;
+    use h3::quic::ConnectionErrorIncoming;
+
+    let reason = if err.is_h3_no_error() {
+        "connection closed by server"
+    } else {
+        match err {
+            ConnectionError::Timeout { .. }
+            | ConnectionError::Remote {
+                0: ConnectionErrorIncoming::Timeout,
+                ..
+            } => "connection timed out",
+            ConnectionError::Remote {
+                0: ConnectionErrorIncoming::Undefined(e),
+                ..
+            } if matches!(
+                e.downcast_ref::<quinn::ConnectionError>(),
+                Some(quinn::ConnectionError::Reset)
+            ) =>
+            {
+                "connection reset"
+            }
+            _ => return None,
+        }
+    };
+    Some(Error::QuicSessionClosed {
+        reason: reason.to_string(),
+        unprocessed: false,
+    })
+}
+
 impl From<String> for Error {
     fn from(s: String) -> Self {
         Error::Generic(s)
@@ -240,6 +557,10 @@ mod tests {
             error_from_http_status(409, Some("k"), msg()),
             Error::AlreadyExists(resource) if resource == "k"
         ));
+        assert!(matches!(
+            error_from_http_status(412, Some("k"), msg()),
+            Error::PreconditionFailed(resource) if resource == "k"
+        ));
         assert!(matches!(
             error_from_http_status(429, Some("k"), msg()),
             Error::RateLimited(_)
@@ -282,6 +603,10 @@ mod tests {
             Error::from(tonic::Status::already_exists("dup")),
             Error::AlreadyExists(_)
         ));
+        assert!(matches!(
+            Error::from(tonic::Status::failed_precondition("etag changed")),
+            Error::PreconditionFailed(_)
+        ));
         assert!(matches!(
             Error::from(tonic::Status::resource_exhausted("throttled")),
             Error::RateLimited(_)
@@ -295,5 +620,29 @@ mod tests {
             Error::from(tonic::Status::internal("boom")),
             Error::GrpcStatus(_)
         ));
+        // tonic's channel request timeout.
+        assert!(matches!(
+            Error::from(tonic::Status::cancelled("Timeout expired")),
+            Error::Timeout(TimeoutStage::ResponseHeaders)
+        ));
+        assert!(matches!(
+            Error::from(tonic::Status::cancelled("client went away")),
+            Error::GrpcStatus(_)
+        ));
+    }
+
+    #[test]
+    fn test_h3_error_code_display() {
+        assert_eq!(H3ErrorCode::EXCESSIVE_LOAD.to_string(), "H3_EXCESSIVE_LOAD");
+        assert_eq!(H3ErrorCode(0x21).to_string(), "0x21");
+        let err = Error::H3 {
+            code: H3ErrorCode::FRAME_ERROR,
+            scope: H3ErrorScope::Connection,
+            message: "bad frame".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "H3 connection error H3_FRAME_ERROR: bad frame"
+        );
     }
 }
@@ -0,0 +1,211 @@
+//! Optional write features the server must confirm.
+//!
+//! A [`precondition`](RequestOptions::precondition) and a non-default
+//! [`Durability`] travel as request headers (gRPC: request metadata) that
+//! are not part of the base protocol.  A server that does not understand
+//! them writes unconditionally, at its default durability, and reports
+//! success, so the client only sends them to servers that advertise
+//! support: an `X-Objstore-Features` header (gRPC: `x-objstore-features`
+//! response metadata) on the health response listing, comma-separated,
+//! [`CONDITIONAL_WRITES`] and [`DURABILITY`].
+//!
+//! REST, QUIC, and gRPC clients ask once, with a health check before the
+//! first write that needs a feature, and clones share the answer.  A write
+//! needing a feature the server does not list fails with
+//! [`Error::UnsupportedByProtocol`] without being sent.  The go-objstore
+//! servers in this repository advertise neither feature yet.
+//!
+//! A write with a non-default durability must also be acknowledged: the
+//! server echoes the level it met in `X-Durability`.  A response without
+//! the echo fails with [`Error::OperationFailed`] — the object was written,
+//! but the requested durability was not confirmed.
+
+use crate::client::{Operation, Protocol};
+use crate::error::{Error, Result};
+use crate::response::ResponseHeaders;
+use crate::types::{Durability, RequestOptions, DURABILITY_HEADER};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Health response header in which a server lists the features it honours.
+pub const FEATURES_HEADER: &str = "x-objstore-features";
+
+/// Feature name for `If-Match` / `If-None-Match` preconditions on writes.
+pub const CONDITIONAL_WRITES: &str = "conditional-writes";
+
+/// Feature name for the `X-Durability` write acknowledgement level.
+pub const DURABILITY: &str = "durability";
+
+/// The features a client's server advertises, discovered on first use and
+/// shared by clones.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ServerFeatures(Arc<OnceCell<HashSet<String>>>);
+
+impl ServerFeatures {
+    /// Check that the server honours everything `options` needs, calling
+    /// `probe` for the health response headers the first time.
+    pub(crate) async fn require<F, Fut>(
+        &self,
+        options: &RequestOptions,
+        protocol: Protocol,
+        probe: F,
+    ) -> Result<()>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<ResponseHeaders>>,
+    {
+        let needed = needed(options);
+        if needed.is_empty() {
+            return Ok(());
+        }
+        let advertised = self
+            .0
+            .get_or_try_init(|| async { Ok::<_, Error>(advertised(&probe().await?)) })
+            .await?;
+        if needed.iter().all(|feature| advertised.contains(*feature)) {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedByProtocol {
+                operation: Operation::PutWithOptions,
+                protocol,
+            })
+        }
+    }
+}
+
+/// The features a write with `options` relies on.
+fn needed(options: &RequestOptions) -> Vec<&'static str> {
+    let mut needed = Vec::new();
+    if options.precondition.is_some() {
+        needed.push(CONDITIONAL_WRITES);
+    }
+    if options.durability != Durability::Local {
+        needed.push(DURABILITY);
+    }
+    needed
+}
+
+/// The features listed in `headers`.
+fn advertised(headers: &ResponseHeaders) -> HashSet<String> {
+    headers
+        .get_all(FEATURES_HEADER)
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|feature| feature.trim().to_ascii_lowercase())
+        .filter(|feature| !feature.is_empty())
+        .collect()
+}
+
+/// Check that the put response `headers` for `key` acknowledge the
+/// durability `options` asked for.
+pub(crate) fn check_acknowledged(
+    key: &str,
+    options: &RequestOptions,
+    headers: &http::HeaderMap,
+) -> Result<()> {
+    if options.durability == Durability::Local {
+        return Ok(());
+    }
+    let expected = options.durability.to_string();
+    match headers.get(DURABILITY_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(level) if level.trim().eq_ignore_ascii_case(&expected) => Ok(()),
+        level => Err(Error::OperationFailed(format!(
+            "{key} was written, but the server acknowledged durability {} instead of {expected}",
+            level.unwrap_or("none")
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Precondition;
+    use http::{HeaderMap, HeaderValue};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn headers(features: &str) -> ResponseHeaders {
+        let mut map = HeaderMap::new();
+        map.insert(FEATURES_HEADER, HeaderValue::from_str(features).unwrap());
+        map.into()
+    }
+
+    #[tokio::test]
+    async fn probes_once_and_checks_each_feature() {
+        let probes = AtomicUsize::new(0);
+        let probe = |features: &'static str| {
+            let probes = &probes;
+            move || async move {
+                probes.fetch_add(1, Ordering::Relaxed);
+                Ok(headers(features))
+            }
+        };
+        let conditional = RequestOptions::default().precondition(Precondition::NotExists);
+
+        let supported = ServerFeatures::default();
+        supported
+            .require(&RequestOptions::default(), Protocol::Rest, probe("other"))
+            .await
+            .unwrap();
+        assert_eq!(probes.load(Ordering::Relaxed), 0);
+        supported
+            .require(
+                &conditional,
+                Protocol::Rest,
+                probe(" Conditional-Writes ,other"),
+            )
+            .await
+            .unwrap();
+
+        let unsupported = ServerFeatures::default();
+        for _ in 0..2 {
+            let err = unsupported
+                .clone()
+                .require(&conditional, Protocol::Rest, probe("other"))
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    Error::UnsupportedByProtocol {
+                        operation: Operation::PutWithOptions,
+                        protocol: Protocol::Rest,
+                    }
+                ),
+                "{err:?}"
+            );
+        }
+        assert_eq!(probes.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn durability_needs_its_own_feature() {
+        let features = ServerFeatures::default();
+        let probe = || async { Ok(headers("conditional-writes")) };
+        let durable = RequestOptions::default().durability(Durability::Fsync);
+        let err = features
+            .require(&durable, Protocol::Grpc, probe)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::UnsupportedByProtocol { .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn durability_must_be_echoed() {
+        let options = RequestOptions::default().durability(Durability::Replicated(2));
+        let mut headers = HeaderMap::new();
+        check_acknowledged("k", &RequestOptions::default(), &headers).unwrap();
+        assert!(matches!(
+            check_acknowledged("k", &options, &headers),
+            Err(Error::OperationFailed(_))
+        ));
+        headers.insert(DURABILITY_HEADER, HeaderValue::from_static("local"));
+        assert!(check_acknowledged("k", &options, &headers).is_err());
+        headers.insert(DURABILITY_HEADER, HeaderValue::from_static("replicated=2"));
+        check_acknowledged("k", &options, &headers).unwrap();
+    }
+}
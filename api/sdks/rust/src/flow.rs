@@ -0,0 +1,370 @@
+//! Backpressure-aware uploads for producers that generate data on the fly.
+//!
+//! `put_stream` pulls chunks from its stream only as fast as the transport
+//! sends them, but a producer feeding the stream through an unbounded
+//! channel still buffers without limit.  A [`PutWriter`] is the push-style
+//! alternative: it holds at most `max_buffered` bytes the transport has
+//! not yet taken, [`ready`](PutWriter::ready) waits for room before the
+//! producer generates the next chunk, and [`stats`](PutWriter::stats)
+//! reports how much the transport is holding it back:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example(rows: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let mut writer = client.put_writer("exports/rows.csv", None, 1 << 20);
+//! for row in rows {
+//!     writer.ready().await?; // generate only once the transport has room
+//!     writer.send(Bytes::from(row)).await?;
+//! }
+//! let stats = writer.stats();
+//! println!("stalled {} times for {:?}", stats.stalls, stats.stalled);
+//! writer.finish().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Over REST and QUIC the body is streamed, so buffered bytes drain only as
+//! the connection's send window opens.  gRPC, MCP, and Unix-socket uploads
+//! gather the whole payload before sending; their writers never stall,
+//! and memory use is the object size as with `put`.
+//!
+//! Dropping a writer without calling [`finish`](PutWriter::finish) aborts
+//! the upload rather than storing a truncated object.
+
+use crate::client::{ByteStream, ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::{Metadata, PutResponse};
+use bytes::Bytes;
+use futures::channel::mpsc;
+use futures::StreamExt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, TryAcquireError};
+use tokio::task::JoinHandle;
+
+/// A snapshot of a [`PutWriter`]'s flow control.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlowStats {
+    /// Bytes passed to [`send`](PutWriter::send).
+    pub bytes_written: u64,
+    /// Bytes the transport has taken for sending.
+    pub bytes_sent: u64,
+    /// Bytes written but not yet taken by the transport.
+    pub buffered: u64,
+    /// How many `ready` / `send` calls had to wait for room.
+    pub stalls: u64,
+    /// Total time spent waiting for room.
+    pub stalled: Duration,
+}
+
+#[derive(Default)]
+struct Counters {
+    written: AtomicU64,
+    sent: AtomicU64,
+    stalls: AtomicU64,
+    stalled_nanos: AtomicU64,
+}
+
+/// A chunk in flight and the buffer room it holds.
+type Queued = Result<(Bytes, u32)>;
+
+/// A push-style streaming upload with a bounded buffer.  Created by
+/// [`ObjectStoreClient::put_writer`]; see the [module docs](self).
+pub struct PutWriter {
+    chunks: Option<mpsc::UnboundedSender<Queued>>,
+    room: Arc<Semaphore>,
+    capacity: u32,
+    counters: Arc<Counters>,
+    upload: Option<JoinHandle<Result<PutResponse>>>,
+}
+
+/// Closes the buffer when the transport drops the body stream, so a
+/// producer waiting for room learns the upload has ended.
+struct CloseOnDrop(Arc<Semaphore>);
+
+impl Drop for CloseOnDrop {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+impl PutWriter {
+    fn start<F>(max_buffered: usize, upload: impl FnOnce(ByteStream) -> F) -> Self
+    where
+        F: Future<Output = Result<PutResponse>> + Send + 'static,
+    {
+        let capacity = max_buffered.clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize));
+        let room = Arc::new(Semaphore::new(capacity));
+        let counters = Arc::new(Counters::default());
+        let (tx, rx) = mpsc::unbounded::<Queued>();
+
+        let (release, sent) = (CloseOnDrop(room.clone()), counters.clone());
+        let stream = rx.map(move |queued| {
+            let (chunk, permits) = queued?;
+            release.0.add_permits(permits as usize);
+            sent.sent.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            Ok(chunk)
+        });
+        Self {
+            chunks: Some(tx),
+            room,
+            capacity: capacity as u32,
+            counters,
+            upload: Some(tokio::spawn(crate::response::carry(upload(stream.boxed())))),
+        }
+    }
+
+    /// Wait until the buffer has room for more data.  Fails with the
+    /// upload's error if it has already ended.
+    pub async fn ready(&mut self) -> Result<()> {
+        self.reserve(1).await?;
+        self.room.add_permits(1);
+        Ok(())
+    }
+
+    /// Bytes that can be sent without waiting.
+    pub fn capacity(&self) -> usize {
+        self.room.available_permits()
+    }
+
+    /// Queue `chunk` for upload, waiting for room first.  A chunk larger
+    /// than the whole buffer waits until the buffer is empty.
+    pub async fn send(&mut self, chunk: Bytes) -> Result<()> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        let len = chunk.len() as u64;
+        let permits = len.min(self.capacity as u64) as u32;
+        self.reserve(permits).await?;
+        let queued = self
+            .chunks
+            .as_ref()
+            .is_some_and(|tx| tx.unbounded_send(Ok((chunk, permits))).is_ok());
+        if !queued {
+            return Err(self.upload_error().await);
+        }
+        self.counters.written.fetch_add(len, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// End the body and wait for the server's response.
+    pub async fn finish(mut self) -> Result<PutResponse> {
+        self.chunks.take();
+        match self.upload.take() {
+            Some(upload) => upload
+                .await
+                .map_err(|e| Error::OperationFailed(format!("upload task failed: {e}")))?,
+            None => Err(Error::OperationFailed("upload already finished".into())),
+        }
+    }
+
+    /// The writer's flow-control counters.
+    pub fn stats(&self) -> FlowStats {
+        let written = self.counters.written.load(Ordering::Relaxed);
+        let sent = self.counters.sent.load(Ordering::Relaxed);
+        FlowStats {
+            bytes_written: written,
+            bytes_sent: sent,
+            buffered: written.saturating_sub(sent),
+            stalls: self.counters.stalls.load(Ordering::Relaxed),
+            stalled: Duration::from_nanos(self.counters.stalled_nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Take `permits` of buffer room, to be returned by the body stream
+    /// once the transport takes the chunk.  Counts a stall if the room is
+    /// not free yet.
+    async fn reserve(&mut self, permits: u32) -> Result<()> {
+        let room = self.room.clone();
+        let acquired = match room.try_acquire_many(permits) {
+            Ok(permit) => Some(permit),
+            Err(TryAcquireError::Closed) => None,
+            Err(TryAcquireError::NoPermits) => {
+                self.counters.stalls.fetch_add(1, Ordering::Relaxed);
+                let started = Instant::now();
+                let acquired = room.acquire_many(permits).await.ok();
+                self.counters
+                    .stalled_nanos
+                    .fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                acquired
+            }
+        };
+        match acquired {
+            Some(permit) => {
+                permit.forget();
+                Ok(())
+            }
+            None => Err(self.upload_error().await),
+        }
+    }
+
+    /// Why the upload stopped taking data.
+    async fn upload_error(&mut self) -> Error {
+        self.chunks.take();
+        match self.upload.take() {
+            Some(upload) => match upload.await {
+                Ok(Err(e)) => e,
+                Ok(Ok(_)) => Error::OperationFailed("upload ended before the writer".into()),
+                Err(e) => Error::OperationFailed(format!("upload task failed: {e}")),
+            },
+            None => Error::OperationFailed("upload already finished".into()),
+        }
+    }
+}
+
+impl Drop for PutWriter {
+    fn drop(&mut self) {
+        if let Some(tx) = self.chunks.take() {
+            let _ = tx.unbounded_send(Err(Error::Cancelled(
+                "put writer dropped before finish".into(),
+            )));
+        }
+    }
+}
+
+impl std::fmt::Debug for PutWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PutWriter")
+            .field("capacity", &self.capacity)
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ObjectStoreClient {
+    /// Start an upload of `key` fed chunk by chunk through a [`PutWriter`]
+    /// that buffers at most `max_buffered` bytes ahead of the transport.
+    /// See the [`flow`](crate::flow) module.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn put_writer(
+        &self,
+        key: &str,
+        metadata: Option<Metadata>,
+        max_buffered: usize,
+    ) -> PutWriter {
+        let (client, key) = (self.clone(), key.to_string());
+        PutWriter::start(max_buffered, move |chunks| async move {
+            client.put_stream(&key, chunks, metadata).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    fn response(total: usize) -> PutResponse {
+        PutResponse {
+            success: true,
+            message: Some(total.to_string()),
+            etag: None,
+            consistency_token: None,
+            region: None,
+        }
+    }
+
+    type Pulls = mpsc::UnboundedSender<oneshot::Sender<Bytes>>;
+
+    /// A writer whose "transport" takes one chunk each time the test pulls,
+    /// then the rest once the puller is dropped.
+    fn paced(max_buffered: usize) -> (PutWriter, Pulls) {
+        let (pulls, mut pull_rx) = mpsc::unbounded::<oneshot::Sender<Bytes>>();
+        let writer = PutWriter::start(max_buffered, move |mut chunks| async move {
+            let mut total = 0;
+            while let Some(reply) = pull_rx.next().await {
+                let Some(chunk) = chunks.next().await else {
+                    break;
+                };
+                let chunk = chunk?;
+                total += chunk.len();
+                let _ = reply.send(chunk);
+            }
+            while let Some(chunk) = chunks.next().await {
+                total += chunk?.len();
+            }
+            Ok(response(total))
+        });
+        (writer, pulls)
+    }
+
+    async fn pull(pulls: &Pulls) -> Bytes {
+        let (tx, rx) = oneshot::channel();
+        pulls.unbounded_send(tx).unwrap();
+        rx.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn producer_waits_for_the_transport() {
+        let (mut writer, pulls) = paced(8);
+        writer.send(Bytes::from("abcdef")).await.unwrap();
+        assert_eq!(writer.capacity(), 2);
+        writer.send(Bytes::from("gh")).await.unwrap();
+        assert_eq!(writer.stats().buffered, 8);
+
+        // Full: nothing more fits until the transport takes a chunk.
+        let full = tokio::time::timeout(Duration::from_millis(50), writer.ready()).await;
+        assert!(full.is_err());
+        assert_eq!(pull(&pulls).await, "abcdef");
+        writer.ready().await.unwrap();
+        assert_eq!(writer.capacity(), 6);
+
+        // An 8-byte chunk needs the whole buffer: it waits for "gh" to go.
+        let (sent, pulled) = tokio::join!(writer.send(Bytes::from("ijklmnop")), pull(&pulls));
+        sent.unwrap();
+        assert_eq!(pulled, "gh");
+
+        let stats = writer.stats();
+        assert_eq!((stats.bytes_written, stats.bytes_sent), (16, 8));
+        assert_eq!(stats.stalls, 2);
+        drop(pulls);
+        let response = writer.finish().await.unwrap();
+        assert_eq!(response.message.as_deref(), Some("16"));
+    }
+
+    #[tokio::test]
+    async fn failed_and_dropped_uploads() {
+        let (done_tx, done_rx) = oneshot::channel();
+        let mut writer = PutWriter::start(4, move |chunks| async move {
+            let result = crate::streaming::collect_stream(chunks).await;
+            let _ = done_tx.send(result.is_err());
+            result.map(|data| response(data.len()))
+        });
+        writer.send(Bytes::from("partial")).await.unwrap();
+        drop(writer);
+        assert!(done_rx.await.unwrap(), "truncated body must not be stored");
+
+        // A rejected upload surfaces its error to the producer.
+        let mut writer =
+            PutWriter::start(4, |_| async { Err(Error::Forbidden("no writes".into())) });
+        let err = writer.send(Bytes::from("abcd")).await.unwrap_err();
+        assert!(matches!(err, Error::Forbidden(_)));
+    }
+
+    #[tokio::test]
+    async fn rest_writer_streams_the_body() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/k")
+            .match_body("hello world")
+            .with_status(201)
+            .create_async()
+            .await;
+        let client =
+            ObjectStoreClient::Rest(crate::rest_client::RestClient::new(server.url()).unwrap());
+
+        let mut writer = client.put_writer("k", None, 4);
+        for word in ["hello", " ", "world"] {
+            writer.ready().await.unwrap();
+            writer.send(Bytes::from(word)).await.unwrap();
+        }
+        writer.finish().await.unwrap();
+        put.assert_async().await;
+    }
+}
@@ -0,0 +1,281 @@
+//! Human-readable and JSON renderings of listings.
+//!
+//! [`table`] lays out objects in aligned columns with humanized sizes and,
+//! optionally, relative timestamps; [`listing_table`] adds a listing's
+//! common prefixes as `PRE` rows.  [`object_json`], [`listing_json`], and
+//! [`json_lines`] give the same data a flat, stable JSON shape for
+//! `--json` style output:
+//!
+//! ```no_run
+//! use chrono::Utc;
+//! use go_objstore::format::{listing_table, TableOptions};
+//! use go_objstore::{ListRequest, ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let listing = client.list(ListRequest::default()).await?;
+//! let options = TableOptions {
+//!     relative_to: Some(Utc::now()),
+//!     ..Default::default()
+//! };
+//! print!("{}", listing_table(&listing, &options));
+//! // SIZE     MODIFIED  TYPE      KEY
+//! //     PRE  -         -         logs/
+//! // 1.2 MiB  3h ago    text/csv  report.csv
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::types::{ListResponse, ObjectInfo};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// How [`table`] renders sizes and timestamps.
+#[derive(Debug, Clone, Copy)]
+pub struct TableOptions {
+    /// Show sizes as `1.5 KiB` rather than a byte count.  Defaults to true.
+    pub human_sizes: bool,
+    /// Show modification times relative to this instant (`3h ago`) rather
+    /// than as RFC 3339.
+    pub relative_to: Option<DateTime<Utc>>,
+    /// Print a header row.  Defaults to true.
+    pub header: bool,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            human_sizes: true,
+            relative_to: None,
+            header: true,
+        }
+    }
+}
+
+/// `size` in binary units with one decimal, e.g. `512 B` or `1.5 GiB`.
+/// Negative (unknown) sizes render as `-`.
+pub fn human_size(size: i64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    if size < 0 {
+        return "-".to_string();
+    }
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// `at` relative to `now`: `just now`, `42s ago`, `5m ago`, `3h ago`,
+/// `12d ago`, or `in 5m` for future times.  Times more than 30 days away
+/// render as a date.
+pub fn relative_time(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - at).num_seconds();
+    let span = secs.unsigned_abs();
+    let amount = match span {
+        0..5 => return "just now".to_string(),
+        5..60 => format!("{span}s"),
+        60..3_600 => format!("{}m", span / 60),
+        3_600..86_400 => format!("{}h", span / 3_600),
+        86_400..2_592_000 => format!("{}d", span / 86_400),
+        _ => return at.format("%Y-%m-%d").to_string(),
+    };
+    if secs < 0 {
+        format!("in {amount}")
+    } else {
+        format!("{amount} ago")
+    }
+}
+
+fn row(object: &ObjectInfo, options: &TableOptions) -> [String; 4] {
+    let meta = &object.metadata;
+    let size = if options.human_sizes {
+        human_size(meta.size)
+    } else {
+        meta.size.to_string()
+    };
+    let modified = match (meta.last_modified, options.relative_to) {
+        (None, _) => "-".to_string(),
+        (Some(at), Some(now)) => relative_time(at, now),
+        (Some(at), None) => at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    };
+    let content_type = meta.content_type.clone().unwrap_or_else(|| "-".into());
+    [size, modified, content_type, object.key.clone()]
+}
+
+fn render(rows: Vec<[String; 4]>, header: bool) -> String {
+    let mut rows = rows;
+    if header {
+        rows.insert(0, ["SIZE", "MODIFIED", "TYPE", "KEY"].map(String::from));
+    }
+    let mut widths = [0usize; 3];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for (i, [size, modified, content_type, key]) in rows.iter().enumerate() {
+        // Sizes are right-aligned, except in the header.
+        let size = if header && i == 0 {
+            format!("{size:<0$}", widths[0])
+        } else {
+            format!("{size:>0$}", widths[0])
+        };
+        out.push_str(&format!(
+            "{size}  {modified:<w1$}  {content_type:<w2$}  {key}\n",
+            w1 = widths[1],
+            w2 = widths[2],
+        ));
+    }
+    out
+}
+
+/// `objects` as aligned `SIZE  MODIFIED  TYPE  KEY` rows, one per line.
+pub fn table(objects: &[ObjectInfo], options: &TableOptions) -> String {
+    render(
+        objects.iter().map(|o| row(o, options)).collect(),
+        options.header,
+    )
+}
+
+/// Like [`table`], with the listing's common prefixes first as `PRE` rows.
+pub fn listing_table(listing: &ListResponse, options: &TableOptions) -> String {
+    let prefixes = listing
+        .common_prefixes
+        .iter()
+        .map(|p| ["PRE".into(), "-".into(), "-".into(), p.clone()]);
+    let objects = listing.objects.iter().map(|o| row(o, options));
+    render(prefixes.chain(objects).collect(), options.header)
+}
+
+/// `object` as a flat JSON record.  Every field is always present (`null`
+/// when unknown) and custom metadata keys are sorted.
+pub fn object_json(object: &ObjectInfo) -> Value {
+    let meta = &object.metadata;
+    let custom: BTreeMap<_, _> = meta.custom.iter().collect();
+    json!({
+        "key": object.key,
+        "size": meta.size,
+        "content_type": meta.content_type,
+        "content_encoding": meta.content_encoding,
+        "last_modified": meta
+            .last_modified
+            .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        "etag": meta.etag,
+        "metadata": custom,
+    })
+}
+
+/// `listing` as JSON: its objects as [`object_json`] records plus the
+/// common prefixes and paging fields.
+pub fn listing_json(listing: &ListResponse) -> Value {
+    json!({
+        "objects": listing.objects.iter().map(object_json).collect::<Vec<_>>(),
+        "common_prefixes": listing.common_prefixes,
+        "next_token": listing.next_token,
+        "truncated": listing.truncated,
+    })
+}
+
+/// `objects` as newline-delimited [`object_json`] records.
+pub fn json_lines<'a>(objects: impl IntoIterator<Item = &'a ObjectInfo>) -> String {
+    objects
+        .into_iter()
+        .map(|o| object_json(o).to_string() + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Metadata;
+    use chrono::Duration;
+
+    fn listing() -> ListResponse {
+        let at: DateTime<Utc> = "2026-10-15T09:00:00Z".parse().unwrap();
+        let object = |key: &str, size, content_type: Option<&str>| ObjectInfo {
+            key: key.into(),
+            metadata: Metadata {
+                size,
+                content_type: content_type.map(Into::into),
+                last_modified: Some(at),
+                custom: [("owner".to_string(), "ops".to_string())].into(),
+                ..Default::default()
+            },
+        };
+        ListResponse {
+            objects: vec![
+                object("report.csv", 1_300_000, Some("text/csv")),
+                object("a", 12, None),
+            ],
+            common_prefixes: vec!["logs/".into()],
+            next_token: Some("t".into()),
+            truncated: true,
+        }
+    }
+
+    #[test]
+    fn sizes_and_times() {
+        assert_eq!(human_size(0), "0 B");
+        assert_eq!(human_size(1023), "1023 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(5 << 30), "5.0 GiB");
+        assert_eq!(human_size(-1), "-");
+
+        let now: DateTime<Utc> = "2026-10-15T12:00:00Z".parse().unwrap();
+        assert_eq!(relative_time(now, now), "just now");
+        assert_eq!(relative_time(now - Duration::seconds(42), now), "42s ago");
+        assert_eq!(relative_time(now - Duration::hours(3), now), "3h ago");
+        assert_eq!(relative_time(now + Duration::minutes(5), now), "in 5m");
+        assert_eq!(relative_time(now - Duration::days(90), now), "2026-07-17");
+    }
+
+    #[test]
+    fn table_aligns_columns() {
+        let now: DateTime<Utc> = "2026-10-15T12:00:00Z".parse().unwrap();
+        let options = TableOptions {
+            relative_to: Some(now),
+            ..Default::default()
+        };
+        assert_eq!(
+            listing_table(&listing(), &options),
+            concat!(
+                "SIZE     MODIFIED  TYPE      KEY\n",
+                "    PRE  -         -         logs/\n",
+                "1.2 MiB  3h ago    text/csv  report.csv\n",
+                "   12 B  3h ago    -         a\n",
+            )
+        );
+        let raw = TableOptions {
+            human_sizes: false,
+            header: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            table(&listing().objects[1..], &raw),
+            "12  2026-10-15T09:00:00Z  -  a\n"
+        );
+    }
+
+    #[test]
+    fn json_is_flat_and_stable() {
+        let value = listing_json(&listing());
+        assert_eq!(value["objects"][0]["size"], 1_300_000);
+        assert_eq!(value["objects"][0]["last_modified"], "2026-10-15T09:00:00Z");
+        assert_eq!(value["objects"][0]["metadata"]["owner"], "ops");
+        assert!(value["objects"][1]["content_type"].is_null());
+        assert_eq!(value["common_prefixes"][0], "logs/");
+        assert_eq!(value["truncated"], true);
+
+        let lines = json_lines(&listing().objects);
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines.starts_with(r#"{"content_encoding":null"#));
+    }
+}
@@ -0,0 +1,310 @@
+//! Fuzz targets for the response parsers.
+//!
+//! Everything the SDK reads from a server — list bodies, metadata JSON and
+//! headers, protobuf messages, timestamps, durations, zip directories — is
+//! parsed by code that must turn malformed input into an [`Error`], never a
+//! panic.  [`run`] feeds arbitrary bytes to one parser, discarding the
+//! result; the `fuzz/` directory of the repository wraps each [`Target`] for
+//! `cargo fuzz`:
+//!
+//! ```text
+//! cargo +nightly fuzz run list_body
+//! ```
+//!
+//! [`run_corpus`] replays a directory of inputs on stable Rust and reports
+//! which ones panicked, so CI can check a saved corpus — the seeds under
+//! `fuzz/seeds/`, or crashes found by earlier fuzzing — without libFuzzer:
+//!
+//! ```no_run
+//! use go_objstore::fuzzing::{run_corpus, Target};
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! for target in Target::ALL {
+//!     let report = run_corpus(target, format!("fuzz/seeds/{}", target.name()))?;
+//!     assert!(report.is_clean(), "{}: {:?}", target.name(), report.panics);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Available with the `fuzzing` feature.
+
+use crate::error::{Error, Result};
+use crate::grpc_client::{
+    convert_from_pb_replication_policy, convert_pb_list, convert_pb_metadata, pb,
+};
+use crate::list_parser::ListBodyParser;
+use crate::zip::ZipArchive;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use prost::Message;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A parser [`run`] can exercise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// The incremental REST/QUIC list body parser.  The first byte picks
+    /// the chunk size the rest is fed in.
+    ListBody,
+    /// A single list entry and a metadata body, as JSON.
+    MetadataJson,
+    /// REST and QUIC response headers, one `name: value` per line, and the
+    /// `Content-Range` header.
+    Headers,
+    /// gRPC list, metadata, and replication policy messages.
+    Proto,
+    /// Timestamps and Go durations, as text.
+    Timestamps,
+    /// A zip archive's central directory and entries.
+    Zip,
+}
+
+impl Target {
+    /// Every target.
+    pub const ALL: [Target; 6] = [
+        Target::ListBody,
+        Target::MetadataJson,
+        Target::Headers,
+        Target::Proto,
+        Target::Timestamps,
+        Target::Zip,
+    ];
+
+    /// The target's name, as used for its `cargo fuzz` binary and seed
+    /// directory, e.g. `"list_body"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::ListBody => "list_body",
+            Target::MetadataJson => "metadata_json",
+            Target::Headers => "headers",
+            Target::Proto => "proto",
+            Target::Timestamps => "timestamps",
+            Target::Zip => "zip",
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Target {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Target::ALL
+            .into_iter()
+            .find(|t| t.name() == s)
+            .ok_or_else(|| Error::InvalidArgument(format!("unknown fuzz target: {s}")))
+    }
+}
+
+/// Feed `data` to `target`'s parsers, discarding what they return.
+pub fn run(target: Target, data: &[u8]) {
+    match target {
+        Target::ListBody => list_body(data),
+        Target::MetadataJson => {
+            let _ = crate::decoder::decode_object(data);
+            let _ = crate::decoder::decode_metadata(data);
+        }
+        Target::Headers => headers(data),
+        Target::Proto => {
+            if let Ok(list) = pb::ListResponse::decode(data) {
+                let _ = convert_pb_list(list);
+            }
+            if let Ok(metadata) = pb::Metadata::decode(data) {
+                let _ = convert_pb_metadata(metadata);
+            }
+            if let Ok(policy) = pb::ReplicationPolicy::decode(data) {
+                convert_from_pb_replication_policy(policy);
+            }
+        }
+        Target::Timestamps => {
+            let text = String::from_utf8_lossy(data);
+            crate::timestamp::parse_timestamp(&text);
+            crate::duration::parse_go_duration_ms(&text);
+        }
+        Target::Zip => zip(data),
+    }
+}
+
+fn list_body(data: &[u8]) {
+    let Some((&size, body)) = data.split_first() else {
+        return;
+    };
+    let mut parser = ListBodyParser::new(None);
+    for chunk in body.chunks(usize::from(size).max(1)) {
+        if parser.push(chunk).is_err() {
+            return;
+        }
+    }
+    let _ = parser.finish();
+}
+
+fn headers(data: &[u8]) {
+    let mut headers = HeaderMap::new();
+    for line in data.split(|&b| b == b'\n') {
+        let Some(colon) = line.iter().position(|&b| b == b':') else {
+            continue;
+        };
+        let value = line[colon + 1..].trim_ascii();
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(&line[..colon]),
+            HeaderValue::from_bytes(value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let _ = crate::rest_client::metadata_from_headers_pub(&headers);
+    let _ = crate::quic_client::metadata_from_headers(&headers);
+    for range in headers.get_all(http::header::CONTENT_RANGE) {
+        if let Ok(range) = range.to_str() {
+            crate::streaming::parse_content_range(range);
+        }
+    }
+}
+
+fn zip(data: &[u8]) {
+    futures::executor::block_on(async {
+        let reader = std::io::Cursor::new(data);
+        let Ok(mut archive) = ZipArchive::new(reader).await else {
+            return;
+        };
+        for index in 0..archive.entries().len() {
+            let _ = archive.read_index(index).await;
+        }
+    });
+}
+
+/// What [`run_corpus`] found.
+#[derive(Debug, Default)]
+pub struct CorpusReport {
+    /// Inputs run.
+    pub inputs: usize,
+    /// Inputs that panicked, with the panic message.
+    pub panics: Vec<(PathBuf, String)>,
+}
+
+impl CorpusReport {
+    /// Whether no input panicked.
+    pub fn is_clean(&self) -> bool {
+        self.panics.is_empty()
+    }
+}
+
+/// Run every file in `dir` through `target`, catching panics.  Fails only
+/// if the directory or a file in it cannot be read.  Panics still reach
+/// the panic hook, so their messages are printed as usual.
+pub fn run_corpus(target: Target, dir: impl AsRef<Path>) -> Result<CorpusReport> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    let mut report = CorpusReport::default();
+    for path in paths {
+        let data = std::fs::read(&path)?;
+        report.inputs += 1;
+        if let Err(panic) = catch_unwind(AssertUnwindSafe(|| run(target, &data))) {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "non-string panic payload".to_string());
+            report.panics.push((path, message));
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeds(target: Target) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fuzz/seeds")
+            .join(target.name())
+    }
+
+    #[test]
+    fn seed_corpora_are_clean() {
+        for target in Target::ALL {
+            let report = run_corpus(target, seeds(target)).unwrap();
+            assert!(report.inputs > 0, "no seeds for {target}");
+            assert!(report.is_clean(), "{target}: {:?}", report.panics);
+            assert_eq!(target.name().parse::<Target>().unwrap(), target);
+        }
+    }
+
+    /// The seeds are well-formed, so mutations of them reach past the
+    /// first sanity check of each parser.
+    #[test]
+    fn seeds_parse() {
+        let read = |target, name| std::fs::read(seeds(target).join(name)).unwrap();
+
+        let page = read(Target::ListBody, "page");
+        let mut parser = ListBodyParser::new(None);
+        assert_eq!(parser.push(&page[1..]).unwrap().len(), 2);
+        assert!(parser.finish().unwrap().truncated);
+
+        let list = pb::ListResponse::decode(&read(Target::Proto, "list")[..]).unwrap();
+        let list = convert_pb_list(list).unwrap();
+        assert_eq!(list.objects[0].metadata.custom["owner"], "ops");
+        assert!(list.objects[0].metadata.last_modified.is_some());
+
+        futures::executor::block_on(async {
+            let zip = read(Target::Zip, "archive");
+            let mut archive = ZipArchive::new(std::io::Cursor::new(zip)).await.unwrap();
+            assert_eq!(archive.entries().len(), 3);
+            assert_eq!(archive.read("stored.txt").await.unwrap(), "hello zip");
+            assert_eq!(archive.read_index(1).await.unwrap().len(), 220);
+        });
+    }
+
+    /// A cheap stand-in for the fuzzer: seeds with bytes flipped, dropped,
+    /// and duplicated, from a fixed xorshift sequence.
+    #[test]
+    fn mutated_seeds_do_not_panic() {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for target in Target::ALL {
+            for entry in std::fs::read_dir(seeds(target)).unwrap() {
+                let seed = std::fs::read(entry.unwrap().path()).unwrap();
+                for _ in 0..200 {
+                    let mut data = seed.clone();
+                    for _ in 0..1 + next() % 4 {
+                        if data.is_empty() {
+                            break;
+                        }
+                        let at = (next() % data.len() as u64) as usize;
+                        match next() % 3 {
+                            0 => data[at] = next() as u8,
+                            1 => {
+                                data.remove(at);
+                            }
+                            _ => {
+                                let tail = data[at..].to_vec();
+                                data.extend_from_slice(&tail[..tail.len().min(16)]);
+                            }
+                        }
+                    }
+                    run(target, &data);
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,89 @@
-use crate::error::{Error, Result};
+//! gRPC transport.
+//!
+//! [`GrpcClient`] calls the `objstore.v1.ObjectStore` service with tonic.
+//! [`GrpcClient::with_channel`] wraps a channel configured elsewhere, and
+//! [`GrpcClient::with_interceptor`] registers tonic-style interceptors that
+//! run on every call after the auth metadata is applied, e.g. for trace
+//! propagation or custom auth schemes:
+//!
+//! ```no_run
+//! use go_objstore::GrpcClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = GrpcClient::new("http://localhost:50051")
+//!     .await?
+//!     .with_interceptor(|mut req| {
+//!         req.metadata_mut().insert("x-request-source", "nightly-sync".parse().unwrap());
+//!         Ok(req)
+//!     });
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Keepalive
+//!
+//! [`ClientConfig::grpc_keepalive`] sends HTTP/2 keepalive pings, so
+//! half-open connections (e.g. through a NAT that dropped its mapping) are
+//! detected instead of hanging calls.  With [`Keepalive::probe`] set (the
+//! default) a background task also calls `Health` every interval; while it
+//! fails, [`GrpcClient::is_healthy`] is false and calls fail fast with
+//! `Unavailable`, which the retry layer treats as transient.
+//!
+//! # Compression
+//!
+//! Listings of long keys compress well.  [`ClientConfig::grpc_compression`]
+//! sends `List` and `Get` messages compressed with gzip or zstd and accepts
+//! compressed responses; grpc-go servers reply in the codec the request
+//! used.  The server must have the codec registered (grpc-go ships gzip,
+//! zstd needs a third-party codec), otherwise these calls fail with
+//! `Unimplemented`.
+//!
+//! ```no_run
+//! use go_objstore::{ClientConfig, GrpcClient, GrpcCompression, Keepalive};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     grpc_keepalive: Some(Keepalive {
+//!         interval: Duration::from_secs(20),
+//!         timeout: Duration::from_secs(5),
+//!         ..Default::default()
+//!     }),
+//!     grpc_compression: Some(GrpcCompression::Gzip),
+//!     ..Default::default()
+//! };
+//! let client = GrpcClient::new_with_config("http://localhost:50051", config).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ClientConfig::grpc_keepalive`]: crate::ClientConfig::grpc_keepalive
+//! [`ClientConfig::grpc_compression`]: crate::ClientConfig::grpc_compression
+
+use crate::auth::AuthConfig;
+use crate::budget::Budget;
+use crate::client::Protocol;
+use crate::config::ClientConfig;
+use crate::error::{Error, Result, TimeoutStage};
+use crate::features::{check_acknowledged, ServerFeatures};
+use crate::paging::{list_split, PageLimit};
+use crate::resolver::Resolver;
+use crate::response::{
+    grpc_headers, record_grpc, response_started, start_timing, RawResponse, ResponseExt,
+};
+use crate::timeouts::Timeouts;
+use crate::timestamp::from_proto;
 use crate::types::*;
 use bytes::Bytes;
-use futures::StreamExt;
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
 use std::collections::HashMap;
-use tonic::transport::Channel;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::{CancellationToken, DropGuard};
+use tonic::codec::CompressionEncoding;
+use tonic::transport::{Channel, Endpoint};
 
 // Include the generated protobuf code
 pub mod pb {
@@ -12,24 +92,328 @@ pub mod pb {
 
 use pb::object_store_client::ObjectStoreClient as GrpcObjectStoreClient;
 
+/// A per-call gRPC interceptor.
+///
+/// Receives the outgoing request (metadata and extensions; the message is
+/// `()`), after the [`AuthConfig`] metadata has been applied, and may add or
+/// replace metadata or reject the call by returning a [`tonic::Status`].
+/// Same shape as tonic's own `Interceptor` closures.
+pub type Interceptor = Arc<
+    dyn Fn(tonic::Request<()>) -> std::result::Result<tonic::Request<()>, tonic::Status>
+        + Send
+        + Sync,
+>;
+
+/// HTTP/2 keepalive and health probing for gRPC channels.
+///
+/// Keepalive pings detect half-open connections (e.g. a NAT that dropped
+/// its mapping) and close them, so calls fail instead of hanging.  With
+/// `probe` set, a background task also calls `Health` every `interval`;
+/// while a probe fails, [`GrpcClient::is_healthy`] is false and calls fail
+/// fast with `Unavailable` instead of waiting on the dead connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    /// Interval between HTTP/2 PING frames (and health probes).
+    pub interval: Duration,
+    /// How long to wait for a PING ack or probe response before the
+    /// connection is considered dead.
+    pub timeout: Duration,
+    /// Send pings even when no calls are in flight.
+    pub permit_without_calls: bool,
+    /// Run the background health probe.
+    pub probe: bool,
+}
+
+impl Default for Keepalive {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            permit_without_calls: true,
+            probe: true,
+        }
+    }
+}
+
+impl Keepalive {
+    fn apply(&self, endpoint: Endpoint) -> Endpoint {
+        endpoint
+            .http2_keep_alive_interval(self.interval)
+            .keep_alive_timeout(self.timeout)
+            .keep_alive_while_idle(self.permit_without_calls)
+    }
+}
+
+/// Message compression for the gRPC `List` and `Get` calls.
+///
+/// Listings of long keys compress extremely well, so inventory jobs that
+/// page through large prefixes spend most of their bandwidth on them.  With
+/// compression set, `List` and `Get` requests are sent compressed with the
+/// chosen codec and both gzip and zstd responses are accepted; grpc-go
+/// servers answer in the codec the request used.  The server must have the
+/// codec registered (grpc-go ships gzip; zstd needs a third-party codec) or
+/// it rejects the calls with `Unimplemented`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrpcCompression {
+    Gzip,
+    Zstd,
+}
+
+impl GrpcCompression {
+    fn encoding(self) -> CompressionEncoding {
+        match self {
+            GrpcCompression::Gzip => CompressionEncoding::Gzip,
+            GrpcCompression::Zstd => CompressionEncoding::Zstd,
+        }
+    }
+}
+
+/// Channel health as last observed by the background probe.  Dropping the
+/// last client clone stops the probe.
+struct ChannelHealth {
+    healthy: AtomicBool,
+    _stop: DropGuard,
+}
+
 /// gRPC client for go-objstore
 #[derive(Clone)]
 pub struct GrpcClient {
     client: GrpcObjectStoreClient<Channel>,
+    /// The channel under `client`, for [`grpc_raw`](Self::grpc_raw).
+    channel: Channel,
+    /// The endpoint URL; unknown for clients built on a caller's channel.
+    endpoint: Option<String>,
+    config: ClientConfig,
+    interceptors: Vec<Interceptor>,
+    health: Option<Arc<ChannelHealth>>,
+    page_limit: PageLimit,
+    features: ServerFeatures,
+    budget: Budget,
 }
 
 impl GrpcClient {
     /// Create a new gRPC client
     pub async fn new(endpoint: impl Into<String>) -> Result<Self> {
+        Self::new_with_auth(endpoint, AuthConfig::default()).await
+    }
+
+    /// Create a new gRPC client with authentication configuration.
+    ///
+    /// The token, tenant, and extra headers are sent as request metadata on
+    /// every RPC.
+    pub async fn new_with_auth(endpoint: impl Into<String>, auth: AuthConfig) -> Result<Self> {
+        Self::new_with_config(
+            endpoint,
+            ClientConfig {
+                auth,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Create a new gRPC client from a [`ClientConfig`].
+    ///
+    /// When the config carries resolver overrides or a custom resolver, the
+    /// endpoint host is resolved once here and each address is tried in
+    /// order; the original endpoint is kept as the request origin.
+    ///
+    /// `config.grpc_keepalive` configures keepalive pings on the channel and
+    /// starts the background health probe.  `config.timeouts` sets the
+    /// channel's connect and request timeouts.
+    pub async fn new_with_config(
+        endpoint: impl Into<String>,
+        config: ClientConfig,
+    ) -> Result<Self> {
         let endpoint = endpoint.into();
-        let channel = Channel::from_shared(endpoint)
-            .map_err(|e| Error::Configuration(e.to_string()))?
+        let keepalive = config.grpc_keepalive;
+        let max_in_flight = config.max_in_flight;
+        let timeouts = config.timeouts;
+        let configure = |e: Endpoint| {
+            let mut e = match &keepalive {
+                Some(keepalive) => keepalive.apply(e),
+                None => e,
+            };
+            if let Some(limit) = timeouts.connect {
+                e = e.connect_timeout(limit);
+            }
+            if let Some(limit) = timeouts.response_headers {
+                e = e.timeout(limit);
+            }
+            match max_in_flight {
+                Some(limit) => e.concurrency_limit(limit.max(1)),
+                None => e,
+            }
+        };
+        let channel = if config.resolver.is_system() {
+            configure(
+                Channel::from_shared(endpoint.clone())
+                    .map_err(|e| Error::Configuration(e.to_string()))?,
+            )
             .connect()
-            .await?;
+            .await?
+        } else {
+            connect_resolved(&endpoint, &config.resolver, configure).await?
+        };
 
-        Ok(Self {
-            client: GrpcObjectStoreClient::new(channel),
-        })
+        let mut client = Self::with_channel_and_config(channel, config);
+        client.endpoint = Some(endpoint);
+        if let Some(keepalive) = keepalive.filter(|k| k.probe) {
+            client.health = Some(client.spawn_probe(keepalive));
+        }
+        Ok(client)
+    }
+
+    /// The endpoint URL, or `None` for a client built with
+    /// [`with_channel`](Self::with_channel).
+    pub fn endpoint(&self) -> Option<&str> {
+        self.endpoint.as_deref()
+    }
+
+    /// TLS settings, or `None` for a plaintext (`http://`) or unknown
+    /// endpoint.
+    pub fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+        self.endpoint
+            .as_deref()
+            .and_then(crate::tls::TlsInfo::for_url)
+    }
+
+    /// Whether the background health probe last reached the server.  Always
+    /// true when no probe is running.
+    pub fn is_healthy(&self) -> bool {
+        self.health
+            .as_ref()
+            .is_none_or(|h| h.healthy.load(Ordering::Relaxed))
+    }
+
+    /// Probe the server every `keepalive.interval` until the returned
+    /// handle's last clone is dropped.
+    fn spawn_probe(&self, keepalive: Keepalive) -> Arc<ChannelHealth> {
+        let stop = CancellationToken::new();
+        let health = Arc::new(ChannelHealth {
+            healthy: AtomicBool::new(true),
+            _stop: stop.clone().drop_guard(),
+        });
+        // The prober holds the flag weakly and has no probe of its own, so
+        // its calls are never failed fast and it doesn't keep itself alive.
+        let prober = self.clone();
+        let flag = Arc::downgrade(&health);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = stop.cancelled() => return,
+                    _ = tokio::time::sleep(keepalive.interval) => {}
+                }
+                let ok = matches!(
+                    tokio::time::timeout(keepalive.timeout, prober.health(None)).await,
+                    Ok(Ok(_))
+                );
+                let Some(health) = flag.upgrade() else { return };
+                if health.healthy.swap(ok, Ordering::Relaxed) != ok {
+                    if ok {
+                        tracing::info!("gRPC channel healthy again");
+                    } else {
+                        tracing::warn!("gRPC health probe failed; marking channel unhealthy");
+                    }
+                }
+            }
+        });
+        health
+    }
+
+    /// Create a gRPC client on top of an existing [`Channel`].
+    ///
+    /// Use this to share a channel (and its connections) with other tonic
+    /// clients, or to apply TLS, proxy, or timeout settings configured
+    /// elsewhere.
+    pub fn with_channel(channel: Channel) -> Self {
+        Self::with_channel_and_config(channel, ClientConfig::default())
+    }
+
+    /// Create a gRPC client on top of an existing [`Channel`] with a
+    /// [`ClientConfig`].  `config.resolver` is ignored; the channel is
+    /// already bound to its endpoint.
+    pub fn with_channel_and_config(channel: Channel, config: ClientConfig) -> Self {
+        Self {
+            client: GrpcObjectStoreClient::new(channel.clone()),
+            channel,
+            endpoint: None,
+            page_limit: PageLimit::new(config.max_page_size),
+            features: ServerFeatures::default(),
+            budget: Budget::new(config.memory_budget.clone()),
+            config,
+            interceptors: Vec::new(),
+            health: None,
+        }
+    }
+
+    /// Return a copy of this client that sends `x-tenant-id: <tenant_id>`
+    /// metadata on every RPC.  The underlying channel is shared.
+    pub fn with_tenant(&self, tenant_id: impl Into<String>) -> Self {
+        let mut scoped = self.clone();
+        scoped.config.auth.tenant_id = Some(tenant_id.into());
+        scoped
+    }
+
+    /// Return a copy of this client that runs `interceptor` on every call.
+    ///
+    /// Interceptors run in registration order, after the auth metadata has
+    /// been applied, so they can attach tracing context or custom auth
+    /// schemes.  An interceptor returning `Err(status)` fails the call with
+    /// that status (mapped like any server status) without sending it.
+    ///
+    /// ```no_run
+    /// use go_objstore::GrpcClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = GrpcClient::new("http://localhost:50051")
+    ///     .await?
+    ///     .with_interceptor(|mut req| {
+    ///         req.metadata_mut()
+    ///             .insert("x-request-source", "batch-job".parse().unwrap());
+    ///         Ok(req)
+    ///     });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_interceptor<F>(&self, interceptor: F) -> Self
+    where
+        F: Fn(tonic::Request<()>) -> std::result::Result<tonic::Request<()>, tonic::Status>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let mut scoped = self.clone();
+        scoped.interceptors.push(Arc::new(interceptor));
+        scoped
+    }
+
+    /// Wrap a message in a [`tonic::Request`] carrying the auth metadata,
+    /// then run the registered interceptors over it.
+    fn request<T>(&self, message: T) -> Result<tonic::Request<T>> {
+        if !self.is_healthy() {
+            return Err(Error::GrpcStatus(Box::new(tonic::Status::unavailable(
+                "gRPC channel marked unhealthy by the keepalive probe",
+            ))));
+        }
+        if self.config.collect_timings {
+            start_timing();
+        }
+        let mut request = tonic::Request::new(message);
+        for (key, value) in self.config.auth.to_grpc_metadata() {
+            request.metadata_mut().insert(key, value);
+        }
+        if self.interceptors.is_empty() {
+            return Ok(request);
+        }
+
+        let (metadata, extensions, message) = request.into_parts();
+        let mut bare = tonic::Request::from_parts(metadata, extensions, ());
+        for interceptor in &self.interceptors {
+            bare = interceptor(bare)?;
+        }
+        let (metadata, extensions, ()) = bare.into_parts();
+        Ok(tonic::Request::from_parts(metadata, extensions, message))
     }
 
     /// Put an object into storage
@@ -39,26 +423,57 @@ impl GrpcClient {
         data: Bytes,
         metadata: Option<Metadata>,
     ) -> Result<PutResponse> {
+        self.put_with_options(key, data, metadata, &RequestOptions::default())
+            .await
+    }
+
+    /// Put an object with per-call [`RequestOptions`].  A non-default
+    /// [`Durability`] is sent as `x-durability` request metadata and a
+    /// [`region`](RequestOptions::region) as `x-region`, and a
+    /// [`precondition`](RequestOptions::precondition) as `if-match` or
+    /// `if-none-match: *` since the proto has no precondition fields;
+    /// `x-consistency-token` and `x-region` response metadata are returned
+    /// as [`PutResponse::consistency_token`] and [`PutResponse::region`];
+    /// `body_format` is REST-only and ignored.  A precondition or non-default
+    /// durability is only sent to servers that advertise support for it,
+    /// and the durability must be acknowledged (see
+    /// [`features`](crate::features)).
+    pub async fn put_with_options(
+        &self,
+        key: String,
+        data: Bytes,
+        metadata: Option<Metadata>,
+        options: &RequestOptions,
+    ) -> Result<PutResponse> {
+        self.features
+            .require(options, Protocol::Grpc, || async {
+                Ok(self.health(None).with_headers().await?.1)
+            })
+            .await?;
         let mut client = self.client.clone();
-        let metadata_pb = metadata.map(|m| pb::Metadata {
-            content_type: m.content_type.unwrap_or_default(),
-            content_encoding: m.content_encoding.unwrap_or_default(),
-            size: m.size,
-            last_modified: m.last_modified.map(|dt| prost_types::Timestamp {
-                seconds: dt.timestamp(),
-                nanos: dt.timestamp_subsec_nanos() as i32,
-            }),
-            etag: m.etag.unwrap_or_default(),
-            custom: m.custom,
-        });
+        let metadata = self.config.put_metadata(&data, metadata);
+        let metadata_pb = metadata.map(convert_to_pb_metadata);
 
-        let request = tonic::Request::new(pb::PutRequest {
-            key,
+        let mut request = self.request(pb::PutRequest {
+            key: key.clone(),
             data: data.to_vec(),
             metadata: metadata_pb,
-        });
-
-        let response = client.put(request).await?.into_inner();
+        })?;
+        with_options(&mut request, options)?;
+
+        let response = client.put(request).await.map_err(status_error)?;
+        record_grpc(response.metadata());
+        check_acknowledged(&key, options, &grpc_headers(response.metadata()))?;
+        let header = |name: &str| {
+            response
+                .metadata()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let consistency_token = header(CONSISTENCY_TOKEN_HEADER);
+        let region = header(REGION_HEADER);
+        let response = response.into_inner();
 
         Ok(PutResponse {
             success: response.success,
@@ -72,37 +487,111 @@ impl GrpcClient {
             } else {
                 Some(response.etag)
             },
+            consistency_token,
+            region,
         })
     }
 
     /// Get an object from storage
     pub async fn get(&self, key: String) -> Result<(Bytes, Metadata)> {
-        let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::GetRequest { key });
+        self.get_with_options(key, &RequestOptions::default()).await
+    }
 
-        let mut stream = client.get(request).await?.into_inner();
+    /// Get an object with per-call [`RequestOptions`], sent as request
+    /// metadata.
+    pub async fn get_with_options(
+        &self,
+        key: String,
+        options: &RequestOptions,
+    ) -> Result<(Bytes, Metadata)> {
+        let mut client = self.bulk_client();
+        let request_key = key.clone();
+        let mut request = self.request(pb::GetRequest { key })?;
+        with_options(&mut request, options)?;
+
+        let response = client.get(request).await.map_err(status_error)?;
+        response_started();
+        let mut headers = response.metadata().clone().into_headers();
+        let mut stream = response.into_inner();
+
+        // The first message carries the metadata, whose size is reserved
+        // from the memory budget before the rest is read.
+        let timeouts = &self.config.timeouts;
+        let (first, metadata) = match next_message(&mut stream, timeouts).await? {
+            Some(chunk) => {
+                let chunk = chunk.map_err(status_error)?;
+                let metadata = chunk.metadata.map(convert_pb_metadata).transpose()?;
+                (Bytes::from(chunk.data), metadata)
+            }
+            None => (Bytes::new(), None),
+        };
+        let length = metadata
+            .as_ref()
+            .and_then(|m| u64::try_from(m.size).ok())
+            .filter(|&size| size > 0);
+        let rest = futures::stream::try_unfold(&mut stream, |stream| async move {
+            match next_message(stream, timeouts).await? {
+                Some(chunk) => {
+                    let chunk = chunk.map_err(status_error)?;
+                    Ok(Some((Bytes::from(chunk.data), stream)))
+                }
+                None => Ok(None),
+            }
+        });
+        let body = futures::stream::iter((!first.is_empty()).then_some(Ok(first))).chain(rest);
+        let data = self.budget.collect(&request_key, length, body).await?;
 
-        let mut data = Vec::new();
-        let mut metadata: Option<Metadata> = None;
+        if let Ok(Some(trailers)) = stream.trailers().await {
+            headers.extend(trailers.into_headers());
+        }
+        record_grpc(&tonic::metadata::MetadataMap::from_headers(headers));
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            data.extend_from_slice(&chunk.data);
+        Ok((data, metadata.unwrap_or_default()))
+    }
 
-            if metadata.is_none() && chunk.metadata.is_some() {
-                metadata = chunk.metadata.map(convert_pb_metadata);
+    /// Open a `Get` call for the streaming `get_stream` extension: reads
+    /// the first message, which carries the metadata, and streams the data
+    /// of the rest as it arrives.
+    pub(crate) async fn open_get(
+        &self,
+        key: String,
+    ) -> Result<(BoxStream<'static, Result<Bytes>>, Metadata)> {
+        let mut client = self.bulk_client();
+        let request = self.request(pb::GetRequest { key })?;
+
+        let response = client.get(request).await.map_err(status_error)?;
+        response_started();
+        record_grpc(response.metadata());
+        let mut stream = response.into_inner();
+
+        let timeouts = self.config.timeouts;
+        let (first, metadata) = match next_message(&mut stream, &timeouts).await? {
+            Some(chunk) => {
+                let chunk = chunk.map_err(status_error)?;
+                let metadata = chunk.metadata.map(convert_pb_metadata).transpose()?;
+                (Bytes::from(chunk.data), metadata.unwrap_or_default())
             }
-        }
-
-        Ok((Bytes::from(data), metadata.unwrap_or_default()))
+            None => (Bytes::new(), Metadata::default()),
+        };
+        let rest = futures::stream::try_unfold(stream, move |mut stream| async move {
+            match next_message(&mut stream, &timeouts).await? {
+                Some(chunk) => {
+                    let chunk = chunk.map_err(status_error)?;
+                    Ok(Some((Bytes::from(chunk.data), stream)))
+                }
+                None => Ok(None),
+            }
+        });
+        let body = futures::stream::iter((!first.is_empty()).then_some(Ok(first))).chain(rest);
+        Ok((body.boxed(), metadata))
     }
 
     /// Delete an object from storage
     pub async fn delete(&self, key: String) -> Result<DeleteResponse> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::DeleteRequest { key });
+        let request = self.request(pb::DeleteRequest { key })?;
 
-        let response = client.delete(request).await?.into_inner();
+        let response = into_inner(client.delete(request).await)?;
 
         Ok(DeleteResponse {
             success: response.success,
@@ -116,41 +605,65 @@ impl GrpcClient {
 
     /// List objects with optional prefix filtering
     pub async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
-        let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::ListRequest {
+        self.list_with_options(list_req, &RequestOptions::default())
+            .await
+    }
+
+    /// List objects with per-call [`RequestOptions`], sent as request
+    /// metadata.
+    pub async fn list_with_options(
+        &self,
+        list_req: ListRequest,
+        options: &RequestOptions,
+    ) -> Result<ListResponse> {
+        list_split(list_req, self.page_limit.get(), |req| {
+            self.list_page(req, options)
+        })
+        .await
+    }
+
+    /// The page size list requests are split at.
+    pub(crate) fn page_limit(&self) -> &PageLimit {
+        &self.page_limit
+    }
+
+    /// The stub for `List` and `Get`, compressed per
+    /// [`ClientConfig::grpc_compression`].
+    fn bulk_client(&self) -> GrpcObjectStoreClient<Channel> {
+        let client = self.client.clone();
+        match self.config.grpc_compression {
+            Some(compression) => client
+                .send_compressed(compression.encoding())
+                .accept_compressed(CompressionEncoding::Gzip)
+                .accept_compressed(CompressionEncoding::Zstd),
+            None => client,
+        }
+    }
+
+    async fn list_page(
+        &self,
+        list_req: ListRequest,
+        options: &RequestOptions,
+    ) -> Result<ListResponse> {
+        let mut client = self.bulk_client();
+        let mut request = self.request(pb::ListRequest {
             prefix: list_req.prefix.unwrap_or_default(),
             delimiter: list_req.delimiter.unwrap_or_default(),
             max_results: list_req.max_results.unwrap_or(100),
             continue_from: list_req.continue_from.unwrap_or_default(),
-        });
-
-        let response = client.list(request).await?.into_inner();
+        })?;
+        with_options(&mut request, options)?;
 
-        Ok(ListResponse {
-            objects: response
-                .objects
-                .into_iter()
-                .map(|obj| ObjectInfo {
-                    key: obj.key,
-                    metadata: obj.metadata.map(convert_pb_metadata).unwrap_or_default(),
-                })
-                .collect(),
-            common_prefixes: response.common_prefixes,
-            next_token: if response.next_token.is_empty() {
-                None
-            } else {
-                Some(response.next_token)
-            },
-            truncated: response.truncated,
-        })
+        let response = into_inner(client.list(request).await)?;
+        convert_pb_list(response)
     }
 
     /// Check if an object exists
     pub async fn exists(&self, key: String) -> Result<bool> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::ExistsRequest { key });
+        let request = self.request(pb::ExistsRequest { key })?;
 
-        let response = client.exists(request).await?.into_inner();
+        let response = into_inner(client.exists(request).await)?;
 
         Ok(response.exists)
     }
@@ -158,9 +671,9 @@ impl GrpcClient {
     /// Get metadata for an object
     pub async fn get_metadata(&self, key: String) -> Result<Metadata> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::GetMetadataRequest { key });
+        let request = self.request(pb::GetMetadataRequest { key })?;
 
-        let response = client.get_metadata(request).await?.into_inner();
+        let response = into_inner(client.get_metadata(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -173,28 +686,19 @@ impl GrpcClient {
         Ok(response
             .metadata
             .map(convert_pb_metadata)
+            .transpose()?
             .unwrap_or_default())
     }
 
     /// Update metadata for an object
     pub async fn update_metadata(&self, key: String, metadata: Metadata) -> Result<()> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::UpdateMetadataRequest {
+        let request = self.request(pb::UpdateMetadataRequest {
             key,
-            metadata: Some(pb::Metadata {
-                content_type: metadata.content_type.unwrap_or_default(),
-                content_encoding: metadata.content_encoding.unwrap_or_default(),
-                size: metadata.size,
-                last_modified: metadata.last_modified.map(|dt| prost_types::Timestamp {
-                    seconds: dt.timestamp(),
-                    nanos: dt.timestamp_subsec_nanos() as i32,
-                }),
-                etag: metadata.etag.unwrap_or_default(),
-                custom: metadata.custom,
-            }),
-        });
+            metadata: Some(convert_to_pb_metadata(metadata)),
+        })?;
 
-        let response = client.update_metadata(request).await?.into_inner();
+        let response = into_inner(client.update_metadata(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -210,11 +714,11 @@ impl GrpcClient {
     /// Health check
     pub async fn health(&self, service: Option<String>) -> Result<HealthResponse> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::HealthRequest {
+        let request = self.request(pb::HealthRequest {
             service: service.unwrap_or_default(),
-        });
+        })?;
 
-        let response = client.health(request).await?.into_inner();
+        let response = into_inner(client.health(request).await)?;
 
         Ok(HealthResponse {
             status: match pb::health_response::Status::try_from(response.status) {
@@ -238,13 +742,13 @@ impl GrpcClient {
         destination_settings: HashMap<String, String>,
     ) -> Result<()> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::ArchiveRequest {
+        let request = self.request(pb::ArchiveRequest {
             key,
             destination_type,
             destination_settings,
-        });
+        })?;
 
-        let response = client.archive(request).await?.into_inner();
+        let response = into_inner(client.archive(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -260,7 +764,7 @@ impl GrpcClient {
     /// Add a lifecycle policy
     pub async fn add_policy(&self, policy: LifecyclePolicy) -> Result<()> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::AddPolicyRequest {
+        let request = self.request(pb::AddPolicyRequest {
             policy: Some(pb::LifecyclePolicy {
                 id: policy.id,
                 prefix: policy.prefix,
@@ -269,9 +773,9 @@ impl GrpcClient {
                 destination_type: policy.destination_type.unwrap_or_default(),
                 destination_settings: policy.destination_settings,
             }),
-        });
+        })?;
 
-        let response = client.add_policy(request).await?.into_inner();
+        let response = into_inner(client.add_policy(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -287,9 +791,9 @@ impl GrpcClient {
     /// Remove a lifecycle policy
     pub async fn remove_policy(&self, id: String) -> Result<()> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::RemovePolicyRequest { id });
+        let request = self.request(pb::RemovePolicyRequest { id })?;
 
-        let response = client.remove_policy(request).await?.into_inner();
+        let response = into_inner(client.remove_policy(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -305,11 +809,11 @@ impl GrpcClient {
     /// Get all lifecycle policies
     pub async fn get_policies(&self, prefix: Option<String>) -> Result<Vec<LifecyclePolicy>> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::GetPoliciesRequest {
+        let request = self.request(pb::GetPoliciesRequest {
             prefix: prefix.unwrap_or_default(),
-        });
+        })?;
 
-        let response = client.get_policies(request).await?.into_inner();
+        let response = into_inner(client.get_policies(request).await)?;
 
         Ok(response
             .policies
@@ -329,32 +833,24 @@ impl GrpcClient {
             .collect())
     }
 
-    /// Apply all lifecycle policies
-    pub async fn apply_policies(&self) -> Result<(i32, i32)> {
+    /// Apply all lifecycle policies.  The result has no per-action
+    /// breakdown or per-object errors; see [`ApplyPoliciesResult`].
+    pub async fn apply_policies(&self) -> Result<ApplyPoliciesResult> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::ApplyPoliciesRequest {});
-
-        let response = client.apply_policies(request).await?.into_inner();
-
-        if !response.success {
-            return Err(Error::OperationFailed(if response.message.is_empty() {
-                "Failed to apply policies".to_string()
-            } else {
-                response.message
-            }));
-        }
+        let request = self.request(pb::ApplyPoliciesRequest {})?;
 
-        Ok((response.policies_count, response.objects_processed))
+        let response = into_inner(client.apply_policies(request).await)?;
+        convert_pb_apply_policies(response)
     }
 
     /// Add a replication policy
     pub async fn add_replication_policy(&self, policy: ReplicationPolicy) -> Result<()> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::AddReplicationPolicyRequest {
+        let request = self.request(pb::AddReplicationPolicyRequest {
             policy: Some(convert_to_pb_replication_policy(policy)),
-        });
+        })?;
 
-        let response = client.add_replication_policy(request).await?.into_inner();
+        let response = into_inner(client.add_replication_policy(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -370,12 +866,9 @@ impl GrpcClient {
     /// Remove a replication policy
     pub async fn remove_replication_policy(&self, id: String) -> Result<()> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::RemoveReplicationPolicyRequest { id });
+        let request = self.request(pb::RemoveReplicationPolicyRequest { id })?;
 
-        let response = client
-            .remove_replication_policy(request)
-            .await?
-            .into_inner();
+        let response = into_inner(client.remove_replication_policy(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -391,9 +884,9 @@ impl GrpcClient {
     /// Get all replication policies
     pub async fn get_replication_policies(&self) -> Result<Vec<ReplicationPolicy>> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::GetReplicationPoliciesRequest {});
+        let request = self.request(pb::GetReplicationPoliciesRequest {})?;
 
-        let response = client.get_replication_policies(request).await?.into_inner();
+        let response = into_inner(client.get_replication_policies(request).await)?;
 
         Ok(response
             .policies
@@ -405,9 +898,9 @@ impl GrpcClient {
     /// Get a specific replication policy
     pub async fn get_replication_policy(&self, id: String) -> Result<ReplicationPolicy> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::GetReplicationPolicyRequest { id });
+        let request = self.request(pb::GetReplicationPolicyRequest { id })?;
 
-        let response = client.get_replication_policy(request).await?.into_inner();
+        let response = into_inner(client.get_replication_policy(request).await)?;
 
         response
             .policy
@@ -423,13 +916,13 @@ impl GrpcClient {
         worker_count: i32,
     ) -> Result<SyncResult> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::TriggerReplicationRequest {
+        let request = self.request(pb::TriggerReplicationRequest {
             policy_id: policy_id.unwrap_or_default(),
             parallel,
             worker_count,
-        });
+        })?;
 
-        let response = client.trigger_replication(request).await?.into_inner();
+        let response = into_inner(client.trigger_replication(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -456,9 +949,9 @@ impl GrpcClient {
     /// Get replication status
     pub async fn get_replication_status(&self, id: String) -> Result<ReplicationStatus> {
         let mut client = self.client.clone();
-        let request = tonic::Request::new(pb::GetReplicationStatusRequest { id });
+        let request = self.request(pb::GetReplicationStatusRequest { id })?;
 
-        let response = client.get_replication_status(request).await?.into_inner();
+        let response = into_inner(client.get_replication_status(request).await)?;
 
         if !response.success {
             return Err(Error::OperationFailed(if response.message.is_empty() {
@@ -479,16 +972,64 @@ impl GrpcClient {
                 total_objects_deleted: s.total_objects_deleted,
                 total_bytes_synced: s.total_bytes_synced,
                 total_errors: s.total_errors,
-                last_sync_time: s.last_sync_time.map(|ts| {
-                    chrono::DateTime::from_timestamp(ts.seconds, ts.nanos as u32)
-                        .unwrap_or_default()
-                }),
+                last_sync_time: s.last_sync_time.and_then(|ts| from_proto(&ts)),
                 average_sync_duration_ms: s.average_sync_duration_ms,
                 sync_count: s.sync_count,
+                recent_errors: Vec::new(),
             })
             .ok_or_else(|| Error::InvalidResponse("Missing replication status".to_string()))
     }
 
+    /// Call `/<service>/<method>` with an already-encoded request message,
+    /// returning the encoded response message, for RPCs without a typed
+    /// method yet.  Auth metadata and interceptors apply as for typed calls.
+    ///
+    /// ```no_run
+    /// # async fn example(client: go_objstore::GrpcClient) -> go_objstore::Result<()> {
+    /// // An empty HealthRequest encodes to no bytes.
+    /// let response = client
+    ///     .grpc_raw("objstore.v1.ObjectStore", "Health", bytes::Bytes::new())
+    ///     .await?;
+    /// println!("{} response bytes", response.body.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Non-OK statuses fail with [`Error::GrpcStatus`], unnormalised.
+    pub async fn grpc_raw(
+        &self,
+        service: &str,
+        method: &str,
+        message: Bytes,
+    ) -> Result<RawResponse> {
+        if service.is_empty() || method.is_empty() {
+            return Err(Error::InvalidArgument(
+                "gRPC service and method must not be empty".into(),
+            ));
+        }
+        let path =
+            tonic::codegen::http::uri::PathAndQuery::try_from(format!("/{service}/{method}"))
+                .map_err(|e| Error::InvalidArgument(format!("gRPC method path: {e}")))?;
+        let request = self.request(message)?;
+        let mut grpc = tonic::client::Grpc::new(self.channel.clone());
+        grpc.ready().await.map_err(|e| {
+            Error::GrpcStatus(Box::new(tonic::Status::unavailable(format!(
+                "service was not ready: {e}"
+            ))))
+        })?;
+        let response = grpc
+            .unary(request, path, RawCodec)
+            .await
+            .map_err(|s| Error::GrpcStatus(Box::new(s)))?;
+        record_grpc(response.metadata());
+        let (metadata, body, _) = response.into_parts();
+        Ok(RawResponse {
+            status: 0,
+            headers: grpc_headers(&metadata),
+            body,
+        })
+    }
+
     /// Close the client, releasing any underlying resources.
     ///
     /// The tonic channel manages its own connection lifecycle and is closed
@@ -506,10 +1047,224 @@ impl GrpcClient {
     }
 }
 
+/// Connect to `endpoint` by dialling the addresses `resolver` returns for
+/// its host, keeping `endpoint` as the origin.
+async fn connect_resolved(
+    endpoint: &str,
+    resolver: &Resolver,
+    configure: impl Fn(Endpoint) -> Endpoint,
+) -> Result<Channel> {
+    let origin: tonic::codegen::http::Uri = endpoint
+        .parse()
+        .map_err(|e: tonic::codegen::http::uri::InvalidUri| Error::Configuration(e.to_string()))?;
+    let scheme = origin.scheme_str().unwrap_or("http");
+    let host = origin
+        .host()
+        .ok_or_else(|| Error::Configuration(format!("endpoint has no host: {endpoint}")))?;
+    let port = origin
+        .port_u16()
+        .unwrap_or(if scheme == "https" { 443 } else { 80 });
+
+    let mut last_err = None;
+    for addr in resolver.lookup(host, port).await? {
+        let attempt = configure(
+            Channel::from_shared(format!("{scheme}://{addr}"))
+                .map_err(|e| Error::Configuration(e.to_string()))?
+                .origin(origin.clone()),
+        )
+        .connect()
+        .await;
+        match attempt {
+            Ok(channel) => return Ok(channel),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    // lookup() never returns an empty list, so an error was recorded.
+    Err(last_err
+        .map(Error::from)
+        .unwrap_or_else(|| Error::Configuration(format!("no addresses for {endpoint}"))))
+}
+
+/// The next message of a server stream, waiting at most the
+/// `body_transfer` limit for it.
+async fn next_message<T>(
+    stream: &mut tonic::Streaming<T>,
+    timeouts: &Timeouts,
+) -> Result<Option<std::result::Result<T, tonic::Status>>> {
+    timeouts
+        .within(
+            TimeoutStage::BodyTransfer,
+            stream.next().map(Ok::<_, Error>),
+        )
+        .await
+}
+
 // Helper functions for converting between protobuf and SDK types
 
-fn convert_pb_metadata(m: pb::Metadata) -> Metadata {
-    Metadata {
+/// Unwrap a unary response, recording its metadata (headers and trailers)
+/// and normalizing a failure status with [`status_error`].
+/// Passes already-encoded messages through unchanged, for
+/// [`GrpcClient::grpc_raw`].
+#[derive(Debug, Clone, Copy, Default)]
+struct RawCodec;
+
+impl tonic::codec::Codec for RawCodec {
+    type Encode = Bytes;
+    type Decode = Bytes;
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawCodec
+    }
+}
+
+impl tonic::codec::Encoder for RawCodec {
+    type Item = Bytes;
+    type Error = tonic::Status;
+
+    fn encode(
+        &mut self,
+        item: Bytes,
+        dst: &mut tonic::codec::EncodeBuf<'_>,
+    ) -> std::result::Result<(), Self::Error> {
+        bytes::BufMut::put(dst, item);
+        Ok(())
+    }
+}
+
+impl tonic::codec::Decoder for RawCodec {
+    type Item = Bytes;
+    type Error = tonic::Status;
+
+    fn decode(
+        &mut self,
+        src: &mut tonic::codec::DecodeBuf<'_>,
+    ) -> std::result::Result<Option<Bytes>, Self::Error> {
+        use bytes::Buf;
+        Ok(Some(src.copy_to_bytes(src.remaining())))
+    }
+}
+
+/// Attach [`RequestOptions`] headers as request metadata.
+fn with_options<T>(request: &mut tonic::Request<T>, options: &RequestOptions) -> Result<()> {
+    for (name, value) in options.headers() {
+        let value = value
+            .parse()
+            .map_err(|_| Error::InvalidArgument(format!("invalid {name} value {value:?}")))?;
+        request.metadata_mut().insert(name, value);
+    }
+    Ok(())
+}
+
+fn into_inner<T>(response: std::result::Result<tonic::Response<T>, tonic::Status>) -> Result<T> {
+    let response = response.map_err(status_error)?;
+    record_grpc(response.metadata());
+    Ok(response.into_inner())
+}
+
+type CanonicalError = fn(String) -> Error;
+
+/// Server error patterns reported with a non-canonical status code, as
+/// `(code, message fragment, canonical error)`.  The fragment is matched
+/// case-insensitively.
+///
+/// The server surfaces some filesystem-backend failures as `Internal` (or
+/// `Unknown`) with the OS error text, e.g. `UpdateMetadata` on a missing key.
+const STATUS_NORMALIZATIONS: &[(tonic::Code, &str, CanonicalError)] = &[
+    (
+        tonic::Code::Internal,
+        "no such file or directory",
+        Error::NotFound,
+    ),
+    (
+        tonic::Code::Unknown,
+        "no such file or directory",
+        Error::NotFound,
+    ),
+];
+
+/// Map a gRPC status to the canonical SDK [`Error`], first translating the
+/// known server error patterns in [`STATUS_NORMALIZATIONS`].
+pub(crate) fn status_error(status: tonic::Status) -> Error {
+    let message = status.message().to_ascii_lowercase();
+    for (code, fragment, canonical) in STATUS_NORMALIZATIONS {
+        if status.code() == *code && message.contains(fragment) {
+            return canonical(status.message().to_string());
+        }
+    }
+    Error::from(status)
+}
+
+pub(crate) fn convert_pb_list(response: pb::ListResponse) -> Result<ListResponse> {
+    Ok(ListResponse {
+        objects: response
+            .objects
+            .into_iter()
+            .map(|obj| {
+                Ok(ObjectInfo {
+                    key: obj.key,
+                    metadata: obj
+                        .metadata
+                        .map(convert_pb_metadata)
+                        .transpose()?
+                        .unwrap_or_default(),
+                })
+            })
+            .collect::<Result<_>>()?,
+        common_prefixes: response.common_prefixes,
+        next_token: if response.next_token.is_empty() {
+            None
+        } else {
+            Some(response.next_token)
+        },
+        truncated: response.truncated,
+    })
+}
+
+/// Convert an `ApplyPoliciesResponse`.  The proto carries no per-action
+/// breakdown or per-object errors, so those are left `None` and empty.
+fn convert_pb_apply_policies(response: pb::ApplyPoliciesResponse) -> Result<ApplyPoliciesResult> {
+    if !response.success {
+        return Err(Error::OperationFailed(if response.message.is_empty() {
+            "Failed to apply policies".to_string()
+        } else {
+            response.message
+        }));
+    }
+    Ok(ApplyPoliciesResult {
+        policies_evaluated: response.policies_count,
+        objects_processed: response.objects_processed,
+        ..Default::default()
+    })
+}
+
+/// Convert metadata received from the server, failing with
+/// [`Error::InvalidResponse`] on a negative size or an out-of-range
+/// timestamp.
+pub(crate) fn convert_pb_metadata(m: pb::Metadata) -> Result<Metadata> {
+    if m.size < 0 {
+        return Err(Error::InvalidResponse(format!(
+            "negative object size {}",
+            m.size
+        )));
+    }
+    let last_modified = m
+        .last_modified
+        .map(|ts| {
+            from_proto(&ts).ok_or_else(|| {
+                Error::InvalidResponse(format!(
+                    "timestamp out of range: {}s {}ns",
+                    ts.seconds, ts.nanos
+                ))
+            })
+        })
+        .transpose()?;
+    Ok(Metadata {
         content_type: if m.content_type.is_empty() {
             None
         } else {
@@ -521,15 +1276,31 @@ fn convert_pb_metadata(m: pb::Metadata) -> Metadata {
             Some(m.content_encoding)
         },
         size: m.size,
-        last_modified: m.last_modified.map(|ts| {
-            chrono::DateTime::from_timestamp(ts.seconds, ts.nanos as u32).unwrap_or_default()
-        }),
+        last_modified,
         etag: if m.etag.is_empty() {
             None
         } else {
             Some(m.etag)
         },
         custom: m.custom,
+        ..Default::default()
+    }
+    .lift_web_headers())
+}
+
+/// The inverse of [`convert_pb_metadata`].  The proto has no fields for the
+/// web-serving headers, so they travel in the custom map.
+fn convert_to_pb_metadata(m: Metadata) -> pb::Metadata {
+    pb::Metadata {
+        custom: m.wire_custom().into_owned(),
+        content_type: m.content_type.unwrap_or_default(),
+        content_encoding: m.content_encoding.unwrap_or_default(),
+        size: m.size,
+        last_modified: m.last_modified.map(|dt| prost_types::Timestamp {
+            seconds: dt.timestamp(),
+            nanos: dt.timestamp_subsec_nanos() as i32,
+        }),
+        etag: m.etag.unwrap_or_default(),
     }
 }
 
@@ -571,7 +1342,7 @@ fn convert_to_pb_replication_policy(p: ReplicationPolicy) -> pb::ReplicationPoli
     }
 }
 
-fn convert_from_pb_replication_policy(p: pb::ReplicationPolicy) -> ReplicationPolicy {
+pub(crate) fn convert_from_pb_replication_policy(p: pb::ReplicationPolicy) -> ReplicationPolicy {
     ReplicationPolicy {
         id: p.id,
         source_backend: p.source_backend,
@@ -580,9 +1351,7 @@ fn convert_from_pb_replication_policy(p: pb::ReplicationPolicy) -> ReplicationPo
         destination_backend: p.destination_backend,
         destination_settings: p.destination_settings,
         check_interval_seconds: p.check_interval_seconds,
-        last_sync_time: p.last_sync_time.map(|ts| {
-            chrono::DateTime::from_timestamp(ts.seconds, ts.nanos as u32).unwrap_or_default()
-        }),
+        last_sync_time: p.last_sync_time.and_then(|ts| from_proto(&ts)),
         enabled: p.enabled,
         encryption: p.encryption.map(|e| EncryptionPolicy {
             backend: e.backend.map(|c| EncryptionConfig {
@@ -633,17 +1402,7 @@ mod tests {
     // ---- request builders (mirror the async methods' request construction) ----
 
     fn build_put_request(key: String, data: Bytes, metadata: Option<Metadata>) -> pb::PutRequest {
-        let metadata_pb = metadata.map(|m| pb::Metadata {
-            content_type: m.content_type.unwrap_or_default(),
-            content_encoding: m.content_encoding.unwrap_or_default(),
-            size: m.size,
-            last_modified: m.last_modified.map(|dt| prost_types::Timestamp {
-                seconds: dt.timestamp(),
-                nanos: dt.timestamp_subsec_nanos() as i32,
-            }),
-            etag: m.etag.unwrap_or_default(),
-            custom: m.custom,
-        });
+        let metadata_pb = metadata.map(convert_to_pb_metadata);
         pb::PutRequest {
             key,
             data: data.to_vec(),
@@ -663,17 +1422,7 @@ mod tests {
     fn build_update_metadata_request(key: String, metadata: Metadata) -> pb::UpdateMetadataRequest {
         pb::UpdateMetadataRequest {
             key,
-            metadata: Some(pb::Metadata {
-                content_type: metadata.content_type.unwrap_or_default(),
-                content_encoding: metadata.content_encoding.unwrap_or_default(),
-                size: metadata.size,
-                last_modified: metadata.last_modified.map(|dt| prost_types::Timestamp {
-                    seconds: dt.timestamp(),
-                    nanos: dt.timestamp_subsec_nanos() as i32,
-                }),
-                etag: metadata.etag.unwrap_or_default(),
-                custom: metadata.custom,
-            }),
+            metadata: Some(convert_to_pb_metadata(metadata)),
         }
     }
 
@@ -692,6 +1441,8 @@ mod tests {
             } else {
                 Some(response.etag)
             },
+            consistency_token: None,
+            region: None,
         })
     }
 
@@ -701,7 +1452,7 @@ mod tests {
         for chunk in chunks {
             data.extend_from_slice(&chunk.data);
             if metadata.is_none() && chunk.metadata.is_some() {
-                metadata = chunk.metadata.map(convert_pb_metadata);
+                metadata = chunk.metadata.map(convert_pb_metadata).transpose().unwrap();
             }
         }
         (Bytes::from(data), metadata.unwrap_or_default())
@@ -725,7 +1476,10 @@ mod tests {
                 .into_iter()
                 .map(|obj| ObjectInfo {
                     key: obj.key,
-                    metadata: obj.metadata.map(convert_pb_metadata).unwrap_or_default(),
+                    metadata: obj
+                        .metadata
+                        .map(|m| convert_pb_metadata(m).unwrap())
+                        .unwrap_or_default(),
                 })
                 .collect(),
             common_prefixes: response.common_prefixes,
@@ -749,6 +1503,7 @@ mod tests {
         Ok(response
             .metadata
             .map(convert_pb_metadata)
+            .transpose()?
             .unwrap_or_default())
     }
 
@@ -791,17 +1546,6 @@ mod tests {
         Ok(())
     }
 
-    fn handle_apply_policies(response: pb::ApplyPoliciesResponse) -> Result<(i32, i32)> {
-        if !response.success {
-            return Err(Error::OperationFailed(if response.message.is_empty() {
-                "Failed to apply policies".to_string()
-            } else {
-                response.message
-            }));
-        }
-        Ok((response.policies_count, response.objects_processed))
-    }
-
     fn handle_get_policies(response: pb::GetPoliciesResponse) -> Vec<LifecyclePolicy> {
         response
             .policies
@@ -873,12 +1617,10 @@ mod tests {
                 total_objects_deleted: s.total_objects_deleted,
                 total_bytes_synced: s.total_bytes_synced,
                 total_errors: s.total_errors,
-                last_sync_time: s.last_sync_time.map(|ts| {
-                    chrono::DateTime::from_timestamp(ts.seconds, ts.nanos as u32)
-                        .unwrap_or_default()
-                }),
+                last_sync_time: s.last_sync_time.and_then(|ts| from_proto(&ts)),
                 average_sync_duration_ms: s.average_sync_duration_ms,
                 sync_count: s.sync_count,
+                recent_errors: Vec::new(),
             })
             .ok_or_else(|| Error::InvalidResponse("Missing replication status".to_string()))
     }
@@ -893,6 +1635,10 @@ mod tests {
             last_modified: None,
             etag: Some("abc123".to_string()),
             custom,
+            compressed_size: None,
+            cache_control: Some("max-age=60".to_string()),
+            content_disposition: None,
+            content_language: None,
         }
     }
 
@@ -1051,6 +1797,7 @@ mod tests {
             delimiter: Some("/".to_string()),
             max_results: Some(5),
             continue_from: Some("tok".to_string()),
+            ..Default::default()
         });
         assert_eq!(req.prefix, "a/");
         assert_eq!(req.delimiter, "/");
@@ -1390,21 +2137,25 @@ mod tests {
     fn grpc_apply_policies_success() {
         // NOTE: conversion-layer.
         let _req = pb::ApplyPoliciesRequest {};
-        let (count, processed) = handle_apply_policies(pb::ApplyPoliciesResponse {
+        let result = convert_pb_apply_policies(pb::ApplyPoliciesResponse {
             success: true,
             policies_count: 3,
             objects_processed: 42,
             message: String::new(),
         })
         .unwrap();
-        assert_eq!(count, 3);
-        assert_eq!(processed, 42);
+        assert_eq!(result.policies_evaluated, 3);
+        assert_eq!(result.objects_processed, 42);
+        assert_eq!(
+            (result.objects_deleted, result.objects_archived),
+            (None, None)
+        );
     }
 
     #[test]
     fn grpc_apply_policies_error() {
         // NOTE: conversion-layer.
-        let err = handle_apply_policies(pb::ApplyPoliciesResponse {
+        let err = convert_pb_apply_policies(pb::ApplyPoliciesResponse {
             success: false,
             policies_count: 0,
             objects_processed: 0,
@@ -1655,8 +2406,9 @@ mod tests {
     fn grpc_metadata_round_trip() {
         // NOTE: conversion-layer. Metadata travels in proto message fields:
         // SDK Metadata -> pb::Metadata (as put/update build it) -> back via
-        // convert_pb_metadata. content_type, content_encoding and the custom
-        // map must all survive the round trip.
+        // convert_pb_metadata. content_type, content_encoding, the custom
+        // map and the web-serving fields (carried in the custom map) must all
+        // survive the round trip.
         let original = sample_metadata();
 
         // Rides inside a PutRequest.metadata field.
@@ -1672,6 +2424,10 @@ mod tests {
             pb_meta.custom.get("owner").map(String::as_str),
             Some("alice")
         );
+        assert_eq!(
+            pb_meta.custom.get("cache-control").map(String::as_str),
+            Some("max-age=60")
+        );
 
         // Rides back inside a GetResponse.metadata field and converts back.
         let (_data, round_tripped) = handle_get(vec![pb::GetResponse {
@@ -1685,6 +2441,8 @@ mod tests {
             round_tripped.custom.get("owner").map(String::as_str),
             Some("alice")
         );
+        assert_eq!(round_tripped.cache_control, original.cache_control);
+        assert!(!round_tripped.custom.contains_key("cache-control"));
     }
 
     #[test]
@@ -1749,7 +2507,8 @@ mod tests {
             last_modified: None,
             etag: String::new(),
             custom: HashMap::new(),
-        });
+        })
+        .unwrap();
         assert!(meta.content_type.is_none());
         assert!(meta.content_encoding.is_none());
         assert!(meta.etag.is_none());
@@ -1761,11 +2520,43 @@ mod tests {
             last_modified: None,
             etag: "abc123".to_string(),
             custom: HashMap::new(),
-        });
+        })
+        .unwrap();
         assert_eq!(full.content_type, Some("application/json".to_string()));
         assert_eq!(full.size, 1024);
     }
 
+    #[test]
+    fn grpc_convert_pb_metadata_rejects_malformed_fields() {
+        let bad_time = pb::Metadata {
+            last_modified: Some(prost_types::Timestamp {
+                seconds: i64::MAX,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        let bad_size = pb::Metadata {
+            size: -1,
+            ..Default::default()
+        };
+        for m in [bad_time.clone(), bad_size] {
+            let err = convert_pb_metadata(m).unwrap_err();
+            assert!(matches!(err, Error::InvalidResponse(_)), "{err:?}");
+        }
+
+        let list = pb::ListResponse {
+            objects: vec![pb::ObjectInfo {
+                key: "k".to_string(),
+                metadata: Some(bad_time),
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            convert_pb_list(list),
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+
     #[test]
     fn grpc_replication_mode_round_trip() {
         // Both replication modes survive SDK <-> pb conversion.
@@ -1777,4 +2568,283 @@ mod tests {
             assert_eq!(back.replication_mode, mode);
         }
     }
+
+    // ---- interceptors ----
+
+    fn lazy_client(config: ClientConfig) -> GrpcClient {
+        let channel = Channel::from_static("http://127.0.0.1:1").connect_lazy();
+        GrpcClient::with_channel_and_config(channel, config)
+    }
+
+    // Interceptors return tonic's own `Result<_, Status>`.
+    #[allow(clippy::result_large_err)]
+    #[tokio::test]
+    async fn grpc_interceptors_run_in_order_after_auth() {
+        let config = ClientConfig {
+            auth: AuthConfig {
+                token: Some("t".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let client = lazy_client(config)
+            .with_interceptor(|mut req| {
+                req.metadata_mut().insert("x-trace", "a".parse().unwrap());
+                Ok(req)
+            })
+            .with_interceptor(|mut req| {
+                let prev = req.metadata().get("x-trace").unwrap().to_str().unwrap();
+                let next = format!("{prev}b").parse().unwrap();
+                req.metadata_mut().insert("x-trace", next);
+                Ok(req)
+            });
+
+        let request = client.request(pb::GetRequest { key: "k".into() }).unwrap();
+        assert_eq!(request.metadata().get("x-trace").unwrap(), "ab");
+        assert_eq!(request.metadata().get("authorization").unwrap(), "Bearer t");
+        assert_eq!(request.get_ref().key, "k");
+    }
+
+    #[test]
+    fn grpc_status_normalization() {
+        let err = status_error(tonic::Status::internal(
+            "open /data/k: No such file or directory",
+        ));
+        assert!(matches!(err, Error::NotFound(m) if m.contains("/data/k")));
+        let err = status_error(tonic::Status::unknown("stat k: no such file or directory"));
+        assert!(matches!(err, Error::NotFound(_)));
+
+        // Other Internal errors and the canonical table are unaffected.
+        let err = status_error(tonic::Status::internal("disk full"));
+        assert!(matches!(err, Error::GrpcStatus(s) if s.code() == tonic::Code::Internal));
+        let err = status_error(tonic::Status::already_exists("k"));
+        assert!(matches!(err, Error::AlreadyExists(_)));
+    }
+
+    // ---- in-process server ----
+
+    /// Hand-written `List` and `Get` endpoints (there is no generated
+    /// server) that accept and answer gzip, recording the request headers.
+    #[derive(Clone, Default)]
+    struct TestServer(Arc<std::sync::Mutex<Option<tonic::codegen::http::HeaderMap>>>);
+
+    impl TestServer {
+        async fn start(&self) -> (std::net::SocketAddr, tokio::task::JoinHandle<()>) {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let incoming =
+                tonic::transport::server::TcpIncoming::from_listener(listener, true, None).unwrap();
+            let server = tonic::transport::Server::builder()
+                .add_service(self.clone())
+                .serve_with_incoming(incoming);
+            (addr, tokio::spawn(async move { server.await.unwrap() }))
+        }
+    }
+
+    impl tonic::server::NamedService for TestServer {
+        const NAME: &'static str = "objstore.v1.ObjectStore";
+    }
+
+    impl tower_service::Service<tonic::codegen::http::Request<tonic::transport::Body>> for TestServer {
+        type Response = tonic::codegen::http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future =
+            futures::future::BoxFuture<'static, std::result::Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(
+            &mut self,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::result::Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(
+            &mut self,
+            req: tonic::codegen::http::Request<tonic::transport::Body>,
+        ) -> Self::Future {
+            *self.0.lock().unwrap() = Some(req.headers().clone());
+            Box::pin(async move {
+                let response = if req.uri().path().ends_with("/Get") {
+                    let codec =
+                        tonic::codec::ProstCodec::<pb::GetResponse, pb::GetRequest>::default();
+                    tonic::server::Grpc::new(codec)
+                        .accept_compressed(CompressionEncoding::Gzip)
+                        .send_compressed(CompressionEncoding::Gzip)
+                        .server_streaming(GetHandler, req)
+                        .await
+                } else {
+                    let codec =
+                        tonic::codec::ProstCodec::<pb::ListResponse, pb::ListRequest>::default();
+                    tonic::server::Grpc::new(codec)
+                        .accept_compressed(CompressionEncoding::Gzip)
+                        .send_compressed(CompressionEncoding::Gzip)
+                        .unary(ListHandler, req)
+                        .await
+                };
+                Ok(response)
+            })
+        }
+    }
+
+    /// Lists 100 objects under the requested prefix.
+    struct ListHandler;
+
+    impl tonic::server::UnaryService<pb::ListRequest> for ListHandler {
+        type Response = pb::ListResponse;
+        type Future = futures::future::Ready<
+            std::result::Result<tonic::Response<pb::ListResponse>, tonic::Status>,
+        >;
+
+        fn call(&mut self, request: tonic::Request<pb::ListRequest>) -> Self::Future {
+            let prefix = request.into_inner().prefix;
+            let objects = (0..100)
+                .map(|i| pb::ObjectInfo {
+                    key: format!("{prefix}{i:04}"),
+                    ..Default::default()
+                })
+                .collect();
+            futures::future::ready(Ok(tonic::Response::new(pb::ListResponse {
+                objects,
+                ..Default::default()
+            })))
+        }
+    }
+
+    /// Sends the key back in three chunks, the first with metadata.
+    struct GetHandler;
+
+    impl tonic::server::ServerStreamingService<pb::GetRequest> for GetHandler {
+        type Response = pb::GetResponse;
+        type ResponseStream =
+            BoxStream<'static, std::result::Result<pb::GetResponse, tonic::Status>>;
+        type Future = futures::future::Ready<
+            std::result::Result<tonic::Response<Self::ResponseStream>, tonic::Status>,
+        >;
+
+        #[allow(clippy::result_large_err)]
+        fn call(&mut self, request: tonic::Request<pb::GetRequest>) -> Self::Future {
+            let key = request.into_inner().key;
+            let chunks: Vec<_> = key
+                .as_bytes()
+                .chunks(key.len().div_ceil(3))
+                .enumerate()
+                .map(|(i, data)| {
+                    Ok(pb::GetResponse {
+                        data: data.to_vec(),
+                        metadata: (i == 0).then(|| pb::Metadata {
+                            size: key.len() as i64,
+                            content_type: "text/plain".into(),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+            futures::future::ready(Ok(tonic::Response::new(
+                futures::stream::iter(chunks).boxed(),
+            )))
+        }
+    }
+
+    #[tokio::test]
+    async fn grpc_compression_applies_to_list() {
+        let service = TestServer::default();
+        let (addr, server) = service.start().await;
+
+        let config = ClientConfig {
+            grpc_compression: Some(GrpcCompression::Gzip),
+            ..Default::default()
+        };
+        let client = GrpcClient::new_with_config(format!("http://{addr}"), config)
+            .await
+            .unwrap();
+        let page = client
+            .list(ListRequest {
+                prefix: Some("inventory/2024/".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.objects.len(), 100);
+        assert_eq!(page.objects[99].key, "inventory/2024/0099");
+
+        let headers = service.0.lock().unwrap().take().unwrap();
+        assert_eq!(headers["grpc-encoding"], "gzip");
+        let accepted = headers["grpc-accept-encoding"].to_str().unwrap();
+        assert!(
+            accepted.contains("gzip") && accepted.contains("zstd"),
+            "{accepted}"
+        );
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn grpc_get_stream_yields_messages_as_they_arrive() {
+        let (addr, server) = TestServer::default().start().await;
+        let client = GrpcClient::new(format!("http://{addr}")).await.unwrap();
+
+        let (stream, metadata) = client.get_stream("abcdefghi".into()).await.unwrap();
+        assert_eq!(metadata.size, 9);
+        assert_eq!(metadata.content_type.as_deref(), Some("text/plain"));
+        let chunks: Vec<Bytes> = stream.map(|c| c.unwrap()).collect().await;
+        assert_eq!(chunks, ["abc", "def", "ghi"]);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn keepalive_probe_marks_unreachable_channel_unhealthy() {
+        let mut client = lazy_client(ClientConfig::default());
+        assert!(client.is_healthy());
+        client.health = Some(client.spawn_probe(Keepalive {
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_millis(500),
+            ..Default::default()
+        }));
+
+        for _ in 0..100 {
+            if !client.is_healthy() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!client.is_healthy());
+        let err = client.request(pb::HealthRequest::default()).unwrap_err();
+        assert!(matches!(err, Error::GrpcStatus(s) if s.code() == tonic::Code::Unavailable));
+    }
+
+    #[allow(clippy::result_large_err)]
+    #[tokio::test]
+    async fn grpc_interceptor_rejection_fails_call() {
+        let client = lazy_client(ClientConfig::default())
+            .with_interceptor(|_| Err(tonic::Status::unauthenticated("no token")));
+
+        let err = client.request(pb::HealthRequest::default()).unwrap_err();
+        assert!(matches!(err, Error::Unauthenticated(_)));
+        let err = client.exists("k".to_string()).await.unwrap_err();
+        assert!(matches!(err, Error::Unauthenticated(_)));
+        let err = client
+            .grpc_raw("objstore.v1.ObjectStore", "Health", Bytes::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Unauthenticated(_)));
+    }
+
+    #[tokio::test]
+    async fn grpc_raw_rejects_bad_method_paths() {
+        let client = lazy_client(ClientConfig::default());
+        for (service, method) in [
+            ("", "Health"),
+            ("objstore.v1.ObjectStore", ""),
+            ("a b", "c"),
+        ] {
+            let err = client
+                .grpc_raw(service, method, Bytes::new())
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, Error::InvalidArgument(_)),
+                "{service}/{method}"
+            );
+        }
+    }
 }
@@ -0,0 +1,379 @@
+//! Client-side enforcement of naming and upload policies.
+//!
+//! [`PolicyGuard`] wraps a store and checks every write against a set of
+//! [`PolicyRules`] before it is sent, so org-wide guardrails (where objects
+//! may live, how large they may be, which metadata they must carry, which
+//! content types are banned) live in one place instead of in every caller:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::guard::PolicyRules;
+//! use go_objstore::{Error, ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let rules = PolicyRules::new()
+//!     .allow_prefix("teams/")
+//!     .allow_prefix("shared/")
+//!     .max_object_size(512 << 20)
+//!     .require_metadata("owner")
+//!     .forbid_content_type("application/x-msdownload");
+//! let store = ObjectStoreClient::rest("http://localhost:8080")?.with_policy(rules);
+//!
+//! let err = store.put("tmp/x", Bytes::from("..."), None).await.unwrap_err();
+//! assert!(matches!(err, Error::PolicyViolation { .. }));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! `put` is checked against every rule, `update_metadata` against the prefix,
+//! metadata, and content-type rules, and `delete` against the prefix rule.
+//! `put_stream` is checked like `put`, except that an oversized stream fails
+//! once it passes the limit, after its first bytes may have been sent.
+//! Reads and listings are never rejected, so objects written before a rule
+//! existed stay readable.  A rejected call fails with
+//! [`Error::PolicyViolation`] without contacting the server.
+
+use crate::client::{ByteStream, ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+
+/// The checks a [`PolicyGuard`] applies.  Every rule is off by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyRules {
+    allowed_prefixes: Vec<String>,
+    max_object_size: Option<u64>,
+    required_metadata: Vec<String>,
+    forbidden_content_types: Vec<String>,
+}
+
+impl PolicyRules {
+    /// Rules that allow everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow writes under `prefix`.  Once any prefix is allowed, writes to
+    /// keys under none of them are rejected.
+    pub fn allow_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.allowed_prefixes.push(prefix.into());
+        self
+    }
+
+    /// Reject objects larger than `bytes`.
+    pub fn max_object_size(mut self, bytes: u64) -> Self {
+        self.max_object_size = Some(bytes);
+        self
+    }
+
+    /// Require custom metadata key `key` (with a non-empty value) on every
+    /// write.
+    pub fn require_metadata(mut self, key: impl Into<String>) -> Self {
+        self.required_metadata.push(key.into());
+        self
+    }
+
+    /// Reject objects whose content type matches `pattern`: an exact media
+    /// type (`text/html`) or a whole top-level type (`video/*`).  Matching
+    /// ignores case and parameters such as `; charset=utf-8`.
+    pub fn forbid_content_type(mut self, pattern: impl Into<String>) -> Self {
+        self.forbidden_content_types
+            .push(pattern.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Check that `key` may be written or deleted.
+    pub fn check_key(&self, key: &str) -> Result<()> {
+        if self.allowed_prefixes.is_empty()
+            || self.allowed_prefixes.iter().any(|p| key.starts_with(p))
+        {
+            return Ok(());
+        }
+        Err(violation(
+            key,
+            format!(
+                "key is not under an allowed prefix ({})",
+                self.allowed_prefixes.join(", ")
+            ),
+        ))
+    }
+
+    /// Check that `metadata` carries the required keys and an allowed
+    /// content type.
+    pub fn check_metadata(&self, key: &str, metadata: Option<&Metadata>) -> Result<()> {
+        let custom = metadata.map(|m| &m.custom);
+        for required in &self.required_metadata {
+            let present = custom
+                .and_then(|c| c.get(required))
+                .is_some_and(|v| !v.is_empty());
+            if !present {
+                return Err(violation(
+                    key,
+                    format!("missing required metadata: {required}"),
+                ));
+            }
+        }
+        if let Some(content_type) = metadata.and_then(|m| m.content_type.as_deref()) {
+            let essence = content_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            let forbidden = self.forbidden_content_types.iter().find(|pattern| {
+                match pattern.strip_suffix("/*") {
+                    Some(top) => essence.split('/').next() == Some(top),
+                    None => essence == **pattern,
+                }
+            });
+            if let Some(pattern) = forbidden {
+                return Err(violation(
+                    key,
+                    format!("content type {essence} is forbidden ({pattern})"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check an upload of `size` bytes against every rule.
+    pub fn check_put(&self, key: &str, size: u64, metadata: Option<&Metadata>) -> Result<()> {
+        self.check_key(key)?;
+        if let Some(limit) = self.max_object_size.filter(|&limit| size > limit) {
+            return Err(violation(
+                key,
+                format!("object is {size} bytes, over the {limit} byte limit"),
+            ));
+        }
+        self.check_metadata(key, metadata)
+    }
+}
+
+fn violation(key: &str, reason: String) -> Error {
+    Error::PolicyViolation {
+        key: key.to_string(),
+        reason,
+    }
+}
+
+/// A store that rejects writes breaking its [`PolicyRules`].  See the
+/// [module docs](self).
+#[derive(Debug, Clone)]
+pub struct PolicyGuard<S> {
+    inner: S,
+    rules: PolicyRules,
+}
+
+impl<S: ObjectStore> PolicyGuard<S> {
+    /// Wrap `inner`, enforcing `rules`.
+    pub fn new(inner: S, rules: PolicyRules) -> Self {
+        Self { inner, rules }
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The rules being enforced.
+    pub fn rules(&self) -> &PolicyRules {
+        &self.rules
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for PolicyGuard<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        self.rules
+            .check_put(key, data.len() as u64, metadata.as_ref())?;
+        self.inner.put(key, data, metadata).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.inner.get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.rules.check_key(key)?;
+        self.inner.delete(key).await
+    }
+
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.inner.list(list_req).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.inner.get_metadata(key).await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.rules.check_key(key)?;
+        self.rules.check_metadata(key, Some(&metadata))?;
+        self.inner.update_metadata(key, metadata).await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.inner.health().await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        self.inner.get_stream(key).await
+    }
+
+    /// The key, metadata, and content type are checked before sending; the
+    /// size limit fails the stream once it runs past the limit.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.rules.check_put(key, 0, metadata.as_ref())?;
+        let stream = match self.rules.max_object_size {
+            Some(limit) => size_limited(key, stream, limit),
+            None => stream,
+        };
+        self.inner.put_stream(key, stream, metadata).await
+    }
+}
+
+/// Fail `stream` with a policy violation once it yields more than `limit`
+/// bytes.
+fn size_limited(key: &str, stream: ByteStream, limit: u64) -> ByteStream {
+    let key = key.to_string();
+    let mut size = 0u64;
+    stream
+        .map(move |chunk| {
+            let chunk = chunk?;
+            size += chunk.len() as u64;
+            if size > limit {
+                return Err(violation(
+                    &key,
+                    format!("object is over the {limit} byte limit"),
+                ));
+            }
+            Ok(chunk)
+        })
+        .boxed()
+}
+
+impl ObjectStoreClient {
+    /// Wrap this client in a [`PolicyGuard`] enforcing `rules`.  See the
+    /// [`guard`](crate::guard) module.
+    pub fn with_policy(self, rules: PolicyRules) -> PolicyGuard<Self> {
+        PolicyGuard::new(self, rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+
+    fn metadata(content_type: &str, owner: Option<&str>) -> Metadata {
+        Metadata {
+            content_type: Some(content_type.into()),
+            custom: owner
+                .map(|o| [("owner".to_string(), o.to_string())].into())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rules_check_each_constraint() {
+        let rules = PolicyRules::new()
+            .allow_prefix("teams/")
+            .max_object_size(10)
+            .require_metadata("owner")
+            .forbid_content_type("Video/*")
+            .forbid_content_type("text/html");
+        let ok = metadata("text/plain", Some("ops"));
+        rules.check_put("teams/a", 10, Some(&ok)).unwrap();
+
+        let rejected = |result: Result<()>| match result {
+            Err(Error::PolicyViolation { reason, .. }) => reason,
+            other => panic!("expected a violation, got {other:?}"),
+        };
+        assert!(rejected(rules.check_put("tmp/a", 1, Some(&ok))).contains("allowed prefix"));
+        assert!(rejected(rules.check_put("teams/a", 11, Some(&ok))).contains("11 bytes"));
+        assert!(rejected(rules.check_put("teams/a", 1, None)).contains("owner"));
+        let empty = metadata("text/plain", Some(""));
+        assert!(rejected(rules.check_put("teams/a", 1, Some(&empty))).contains("owner"));
+        let html = metadata("text/html; charset=utf-8", Some("ops"));
+        assert!(rejected(rules.check_put("teams/a", 1, Some(&html))).contains("text/html"));
+        let video = metadata("video/mp4", Some("ops"));
+        assert!(rejected(rules.check_put("teams/a", 1, Some(&video))).contains("video/*"));
+
+        PolicyRules::new()
+            .check_put("anything", u64::MAX, None)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn guard_rejects_before_the_server() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/teams%2Fok")
+            .with_status(201)
+            .create_async()
+            .await;
+        let get = server
+            .mock("GET", "/objects/legacy")
+            .with_body("old")
+            .create_async()
+            .await;
+        let store = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap())
+            .with_policy(PolicyRules::new().allow_prefix("teams/"));
+
+        store.put("teams/ok", Bytes::from("x"), None).await.unwrap();
+        let err = store
+            .put("other", Bytes::from("x"), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PolicyViolation { ref key, .. } if key == "other"));
+        assert!(store.delete("other").await.is_err());
+        // Reads are never rejected.
+        let (data, _) = store.get("legacy").await.unwrap();
+        assert_eq!(data, "old");
+        put.assert_async().await;
+        get.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn put_stream_is_checked_as_it_is_read() {
+        let store = PolicyGuard::new(
+            crate::test_support::MemoryStore::default(),
+            PolicyRules::new().allow_prefix("teams/").max_object_size(8),
+        );
+        let stream = |chunks: &[&'static str]| {
+            let chunks: Vec<_> = chunks.iter().map(|c| Ok(Bytes::from(*c))).collect();
+            futures::stream::iter(chunks).boxed()
+        };
+
+        store
+            .put_stream("teams/a", stream(&["1234", "5678"]), None)
+            .await
+            .unwrap();
+        let err = store
+            .put_stream("other", stream(&["x"]), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PolicyViolation { ref key, .. } if key == "other"));
+        let err = store
+            .put_stream("teams/b", stream(&["12345", "6789"]), None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::PolicyViolation { ref reason, .. } if reason.contains("8 byte")),
+            "{err:?}"
+        );
+        assert_eq!(store.inner().keys(), ["teams/a"]);
+    }
+}
@@ -0,0 +1,563 @@
+//! Importing tar archives into the store.
+//!
+//! [`import_tar`] reads a tar or gzip-compressed tar archive and uploads
+//! each regular file under a prefix, keeping the entry's path below it.
+//! The archive is read as a stream — nothing is unpacked to disk — so it
+//! can come straight from a file, a socket, or another object:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let archive = tokio::fs::File::open("site.tar.gz").await?;
+//! let report = client.import_tar(archive, "sites/docs/").await?;
+//! println!("imported {} objects, {} bytes", report.objects, report.bytes);
+//! for (path, reason) in &report.skipped {
+//!     eprintln!("skipped {path}: {reason}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Gzip compression is detected from the first bytes.  Entries are
+//! uploaded one at a time, in archive order, with their modification time
+//! and permission bits recorded as the [`MTIME_KEY`] (RFC 3339) and
+//! [`MODE_KEY`] (octal) custom metadata.  REST and QUIC clients stream each
+//! entry into its upload; other transports and [`ObjectStore`]
+//! implementations buffer one entry at a time.
+//!
+//! ustar, GNU long names, and pax `path`, `size`, and `mtime` records are
+//! understood.  Directories, links, and special files are not uploaded but
+//! listed in [`ImportReport::skipped`], as are entries whose path is
+//! absolute or climbs out with `..` after leading `/` and `./` are removed.
+//! An upload failure stops the import; entries uploaded before it stay.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::Metadata;
+use async_compression::tokio::bufread::GzipDecoder;
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, SecondsFormat};
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+
+/// Custom metadata key holding an imported entry's modification time.
+pub const MTIME_KEY: &str = "mtime";
+/// Custom metadata key holding an imported entry's permission bits.
+pub const MODE_KEY: &str = "mode";
+
+const BLOCK: usize = 512;
+/// Largest chunk read from an entry at a time.
+const CHUNK: u64 = 64 * 1024;
+
+/// What [`import_tar`] did.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Files uploaded.
+    pub objects: u64,
+    /// Bytes uploaded.
+    pub bytes: u64,
+    /// Entries not uploaded, with the reason, in archive order.
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Upload every file in the tar archive `reader` under `prefix`.  Works
+/// with any store; each entry is buffered before it is uploaded.
+pub async fn import_tar<R>(store: &dyn ObjectStore, reader: R, prefix: &str) -> Result<ImportReport>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    import(Sink::Buffered(store), reader, prefix).await
+}
+
+impl ObjectStoreClient {
+    /// Upload every file in the tar archive `reader` under `prefix`.  See
+    /// the [`import`](crate::import) module.
+    pub async fn import_tar<R>(&self, reader: R, prefix: &str) -> Result<ImportReport>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        match self {
+            ObjectStoreClient::Rest(_) | ObjectStoreClient::Quic(_) => {
+                import(Sink::Streamed(self), reader, prefix).await
+            }
+            _ => import(Sink::Buffered(self), reader, prefix).await,
+        }
+    }
+}
+
+/// Where entries are uploaded.
+#[derive(Clone, Copy)]
+enum Sink<'a> {
+    Buffered(&'a dyn ObjectStore),
+    Streamed(&'a ObjectStoreClient),
+}
+
+async fn import<R>(sink: Sink<'_>, reader: R, prefix: &str) -> Result<ImportReport>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    let mut tar = TarReader::new(decompressed(reader).await?);
+    let mut report = ImportReport::default();
+    while let Some(entry) = tar.next_entry().await? {
+        let reason = match entry.kind {
+            0 | b'0' | b'7' => None,
+            b'5' => Some("directory"),
+            b'1' | b'2' => Some("link"),
+            _ => Some("not a regular file"),
+        };
+        if let Some(reason) = reason {
+            report.skipped.push((entry.path, reason.to_string()));
+            continue;
+        }
+        let Some(key) = object_key(prefix, &entry.path) else {
+            report.skipped.push((entry.path, "unsafe path".to_string()));
+            continue;
+        };
+
+        let metadata = entry.metadata();
+        match sink {
+            Sink::Buffered(store) => {
+                let mut data = BytesMut::with_capacity(entry.size.min(CHUNK * 16) as usize);
+                while let Some(chunk) = tar.chunk().await? {
+                    data.extend_from_slice(&chunk);
+                }
+                store.put(&key, data.freeze(), Some(metadata)).await?;
+            }
+            Sink::Streamed(client) => {
+                let (tx, rx) = futures::channel::mpsc::channel(4);
+                let upload = client.put_stream(&key, rx.boxed(), Some(metadata));
+                let feed = async {
+                    // Owned, so the body ends when the entry does.
+                    let mut tx = tx;
+                    loop {
+                        match tar.chunk().await {
+                            Ok(Some(chunk)) => {
+                                if tx.send(Ok(chunk)).await.is_err() {
+                                    // The upload failed and says why.
+                                    return Ok(());
+                                }
+                            }
+                            Ok(None) => return Ok(()),
+                            Err(e) => {
+                                // Fail the upload rather than end it short.
+                                let cut = Error::OperationFailed(format!("archive truncated: {e}"));
+                                let _ = tx.send(Err(cut)).await;
+                                return Err(e);
+                            }
+                        }
+                    }
+                };
+                let (uploaded, fed) = tokio::join!(upload, feed);
+                fed?;
+                uploaded?;
+            }
+        }
+        report.objects += 1;
+        report.bytes += entry.size;
+    }
+    Ok(report)
+}
+
+/// `reader`, gunzipped if it starts with the gzip magic bytes.
+async fn decompressed<'a, R>(reader: R) -> Result<Box<dyn AsyncRead + Unpin + Send + 'a>>
+where
+    R: AsyncRead + Unpin + Send + 'a,
+{
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf().await?.starts_with(&[0x1f, 0x8b]) {
+        let mut decoder = GzipDecoder::new(reader);
+        decoder.multiple_members(true);
+        Ok(Box::new(decoder))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// The key for the entry at `path`, or `None` if the path is unsafe.
+fn object_key(prefix: &str, path: &str) -> Option<String> {
+    let mut path = path;
+    while let Some(rest) = path.strip_prefix("./").or_else(|| path.strip_prefix('/')) {
+        path = rest;
+    }
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment => segments.push(segment),
+        }
+    }
+    if segments.is_empty() {
+        return None;
+    }
+    Some(format!("{prefix}{}", segments.join("/")))
+}
+
+/// One archive entry's header.
+struct Entry {
+    path: String,
+    kind: u8,
+    size: u64,
+    mode: u64,
+    mtime: i64,
+}
+
+impl Entry {
+    fn metadata(&self) -> Metadata {
+        let mut custom = HashMap::from([(MODE_KEY.to_string(), format!("{:o}", self.mode))]);
+        let modified = DateTime::from_timestamp(self.mtime, 0);
+        if let Some(modified) = modified {
+            custom.insert(
+                MTIME_KEY.to_string(),
+                modified.to_rfc3339_opts(SecondsFormat::Secs, true),
+            );
+        }
+        Metadata {
+            size: self.size as i64,
+            last_modified: modified,
+            custom,
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads tar headers and entry bodies from a stream.
+struct TarReader<R> {
+    inner: R,
+    /// Body bytes of the current entry not yet read.
+    remaining: u64,
+    /// Padding after the current entry's body.
+    padding: u64,
+}
+
+impl<R: AsyncRead + Unpin> TarReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            remaining: 0,
+            padding: 0,
+        }
+    }
+
+    /// The next file, directory, or other entry, skipping whatever is left
+    /// of the current one.  `None` at the end of the archive.
+    async fn next_entry(&mut self) -> Result<Option<Entry>> {
+        while self.chunk().await?.is_some() {}
+        let mut long_name = None;
+        let mut pax = HashMap::new();
+        loop {
+            let mut block = [0u8; BLOCK];
+            if !self.read_block(&mut block).await? || block.iter().all(|b| *b == 0) {
+                return Ok(None);
+            }
+            verify_checksum(&block)?;
+            let size = parse_number(&block[124..136])?;
+            let kind = block[156];
+            match kind {
+                b'L' => {
+                    long_name = Some(text(&self.body(size).await?));
+                    continue;
+                }
+                b'x' => {
+                    pax.extend(parse_pax(&self.body(size).await?));
+                    continue;
+                }
+                b'g' | b'K' => {
+                    self.body(size).await?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let path = match (pax.remove("path"), long_name) {
+                (Some(path), _) | (None, Some(path)) => path,
+                (None, None) => header_path(&block),
+            };
+            let size = match pax.get("size") {
+                Some(size) => size
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid pax size {size:?}")))?,
+                None => size,
+            };
+            let mtime = match pax.get("mtime").and_then(|t| t.split('.').next()) {
+                Some(secs) => secs
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid pax mtime {secs:?}")))?,
+                None => parse_number(&block[136..148])? as i64,
+            };
+            // Directories and links carry no body whatever their size.
+            let body = if matches!(kind, b'1' | b'2' | b'5') {
+                0
+            } else {
+                size
+            };
+            self.start(body);
+            return Ok(Some(Entry {
+                path,
+                kind,
+                size: body,
+                mode: parse_number(&block[100..108])? & 0o7777,
+                mtime,
+            }));
+        }
+    }
+
+    /// The next chunk of the current entry's body, or `None` once it has
+    /// been read (including its padding).
+    async fn chunk(&mut self) -> Result<Option<Bytes>> {
+        if self.remaining == 0 {
+            if self.padding > 0 {
+                let mut padding = [0u8; BLOCK];
+                self.inner
+                    .read_exact(&mut padding[..self.padding as usize])
+                    .await?;
+                self.padding = 0;
+            }
+            return Ok(None);
+        }
+        let mut chunk = vec![0; self.remaining.min(CHUNK) as usize];
+        self.inner.read_exact(&mut chunk).await?;
+        self.remaining -= chunk.len() as u64;
+        Ok(Some(chunk.into()))
+    }
+
+    fn start(&mut self, size: u64) {
+        self.remaining = size;
+        self.padding = (BLOCK as u64 - size % BLOCK as u64) % BLOCK as u64;
+    }
+
+    /// The whole body of a metadata entry (long name or pax record).
+    async fn body(&mut self, size: u64) -> Result<Vec<u8>> {
+        if size > 1024 * 1024 {
+            return Err(invalid(format!("{size}-byte extended header")));
+        }
+        self.start(size);
+        let mut body = Vec::with_capacity(size as usize);
+        while let Some(chunk) = self.chunk().await? {
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    /// Fill `block`; `false` if the stream ended before its first byte.
+    async fn read_block(&mut self, block: &mut [u8; BLOCK]) -> Result<bool> {
+        let mut filled = 0;
+        while filled < BLOCK {
+            let n = self.inner.read(&mut block[filled..]).await?;
+            if n == 0 {
+                if filled == 0 {
+                    return Ok(false);
+                }
+                return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+            }
+            filled += n;
+        }
+        Ok(true)
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidArgument(format!("malformed tar archive: {message}"))
+}
+
+fn verify_checksum(block: &[u8; BLOCK]) -> Result<()> {
+    let expected = parse_number(&block[148..156])?;
+    let actual: u64 = block
+        .iter()
+        .enumerate()
+        .map(|(i, b)| if (148..156).contains(&i) { b' ' } else { *b } as u64)
+        .sum();
+    if actual != expected {
+        return Err(invalid("header checksum mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// An octal header field, or a base-256 one (GNU) if the high bit is set.
+fn parse_number(field: &[u8]) -> Result<u64> {
+    if field.first().is_some_and(|b| b & 0x80 != 0) {
+        return Ok(field[1..]
+            .iter()
+            .fold(u64::from(field[0] & 0x7f), |n, b| (n << 8) | u64::from(*b)));
+    }
+    let digits = text(field);
+    let digits = digits.trim_matches(|c: char| c == ' ' || c == '\0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 8).map_err(|_| invalid(format!("invalid number {digits:?}")))
+}
+
+/// A NUL-terminated string field.
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// The name field, joined to the ustar prefix field when there is one.
+fn header_path(block: &[u8; BLOCK]) -> String {
+    let name = text(&block[..100]);
+    if &block[257..262] != b"ustar" {
+        return name;
+    }
+    let prefix = text(&block[345..500]);
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{prefix}/{name}")
+    }
+}
+
+/// The `key=value` records of a pax extended header (`<len> key=value\n`).
+fn parse_pax(body: &[u8]) -> HashMap<String, String> {
+    let mut records = HashMap::new();
+    let mut rest = body;
+    while let Some(space) = rest.iter().position(|b| *b == b' ') {
+        let Some(len) = std::str::from_utf8(&rest[..space])
+            .ok()
+            .and_then(|l| l.parse::<usize>().ok())
+            .filter(|l| *l > space && *l <= rest.len())
+        else {
+            break;
+        };
+        let record = String::from_utf8_lossy(&rest[space + 1..len]);
+        if let Some((key, value)) = record.trim_end_matches('\n').split_once('=') {
+            records.insert(key.to_string(), value.to_string());
+        }
+        rest = &rest[len..];
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use crate::test_support::MemoryStore;
+    use std::io::Write;
+
+    /// A tar entry: header plus padded body.
+    fn entry(name: &str, kind: u8, body: &[u8]) -> Vec<u8> {
+        let mut header = [0u8; BLOCK];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", body.len()).as_bytes());
+        header[136..147].copy_from_slice(b"14544400200"); // 2024-01-01
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].fill(b' ');
+        let sum: u32 = header.iter().map(|b| *b as u32).sum();
+        header[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+        let mut out = header.to_vec();
+        out.extend_from_slice(body);
+        out.resize(out.len().div_ceil(BLOCK) * BLOCK, 0);
+        out
+    }
+
+    fn archive() -> Vec<u8> {
+        let long = format!("deep/{}/file.txt", "d".repeat(120));
+        let mut tar = Vec::new();
+        tar.extend(entry("./docs/", b'5', b""));
+        tar.extend(entry("./docs/index.html", b'0', b"<h1>hi</h1>"));
+        tar.extend(entry("././@LongLink", b'L', format!("{long}\0").as_bytes()));
+        tar.extend(entry("truncated-name", b'0', &[7u8; 600]));
+        tar.extend(entry("PaxHeader", b'x', b"30 mtime=1700000000.123456789\n"));
+        tar.extend(entry("pax.txt", b'0', b"pax"));
+        tar.extend(entry("link", b'2', b""));
+        tar.extend(entry("../escape", b'0', b"x"));
+        tar.extend([0u8; BLOCK * 2]);
+        tar
+    }
+
+    #[test]
+    fn object_keys() {
+        assert_eq!(object_key("p/", "./a/b").as_deref(), Some("p/a/b"));
+        assert_eq!(object_key("p/", "/abs//x").as_deref(), Some("p/abs/x"));
+        assert_eq!(object_key("p/", "a/../../b"), None);
+        assert_eq!(object_key("p/", "./"), None);
+    }
+
+    #[tokio::test]
+    async fn imports_plain_and_gzipped_archives() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(&archive()).unwrap();
+        let gzipped = gz.finish().unwrap();
+
+        for bytes in [archive(), gzipped] {
+            let store = MemoryStore::default();
+            let report = import_tar(&store, bytes.as_slice(), "site/").await.unwrap();
+            assert_eq!((report.objects, report.bytes), (3, 11 + 600 + 3));
+            let skipped: Vec<_> = report
+                .skipped
+                .iter()
+                .map(|(p, r)| format!("{p}: {r}"))
+                .collect();
+            assert_eq!(
+                skipped,
+                ["./docs/: directory", "link: link", "../escape: unsafe path"]
+            );
+
+            let (data, metadata) = store.object("site/docs/index.html").unwrap();
+            assert_eq!(data, "<h1>hi</h1>");
+            assert_eq!(metadata.custom[MODE_KEY], "644");
+            assert_eq!(metadata.custom[MTIME_KEY], "2024-01-01T00:00:00Z");
+            let long = format!("site/deep/{}/file.txt", "d".repeat(120));
+            assert_eq!(store.data(&long).len(), 600);
+            assert_eq!(
+                store.object("site/pax.txt").unwrap().1.custom[MTIME_KEY],
+                "2023-11-14T22:13:20Z"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_corrupt_headers() {
+        let mut tar = archive();
+        tar[0] = b'X';
+        let result = import_tar(&MemoryStore::default(), tar.as_slice(), "").await;
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+
+        // Cut off inside an entry body.
+        let tar = &archive()[..BLOCK * 2 + 4];
+        let result = import_tar(&MemoryStore::default(), tar, "").await;
+        assert!(matches!(result, Err(Error::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn rest_streams_each_entry() {
+        let mut server = mockito::Server::new_async().await;
+        let mut puts = Vec::new();
+        for (key, body) in [("docs%2Findex.html", "<h1>hi</h1>"), ("pax.txt", "pax")] {
+            let put = server
+                .mock("PUT", format!("/objects/site%2F{key}").as_str())
+                .match_body(body)
+                .with_status(201)
+                .expect(1)
+                .create_async()
+                .await;
+            puts.push(put);
+        }
+        server
+            .mock(
+                "PUT",
+                mockito::Matcher::Regex("^/objects/site%2Fdeep".into()),
+            )
+            .with_status(201)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client
+            .import_tar(archive().as_slice(), "site/")
+            .await
+            .unwrap();
+        assert_eq!(report.objects, 3);
+        for put in puts {
+            put.assert_async().await;
+        }
+    }
+}
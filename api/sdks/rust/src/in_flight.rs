@@ -0,0 +1,94 @@
+//! Crate-private enforcement of [`ClientConfig::max_in_flight`].
+//!
+//! Each REST, MCP, and QUIC client owns one [`InFlight`] semaphore, shared
+//! by its clones (including tenant-scoped ones).  A request takes a
+//! [`Slot`] before it is sent and holds it until its response body has been
+//! read: HTTP responses carry the slot in their extensions, and
+//! [`body_stream`] moves it into the stream so a streamed download keeps its
+//! slot until the stream is dropped.  gRPC uses tonic's own per-channel
+//! concurrency limit instead.
+//!
+//! [`ClientConfig::max_in_flight`]: crate::ClientConfig::max_in_flight
+
+use crate::error::{Error, Result, TimeoutStage};
+use crate::response::send;
+use crate::timeouts::Timeouts;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// One request's claim on the limit, released when the last clone drops.
+#[derive(Debug, Clone)]
+pub(crate) struct Slot {
+    _permit: Arc<OwnedSemaphorePermit>,
+}
+
+/// A client's in-flight request limit; unlimited by default.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InFlight(Option<Arc<Semaphore>>);
+
+impl InFlight {
+    /// Allow at most `limit` requests at once (at least one).
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self(limit.map(|n| Arc::new(Semaphore::new(n.max(1)))))
+    }
+
+    /// Wait for a free slot, or `None` when unlimited.
+    pub(crate) async fn acquire(&self) -> Option<Slot> {
+        let semaphore = self.0.clone()?;
+        // The semaphore is never closed.
+        let permit = semaphore.acquire_owned().await.ok()?;
+        Some(Slot {
+            _permit: Arc::new(permit),
+        })
+    }
+
+    /// [`send`] an HTTP request once a slot is free, keeping the slot in
+    /// the response's extensions.  The wait for the slot does not count
+    /// toward the `response_headers` timeout.
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        timeouts: &Timeouts,
+    ) -> Result<reqwest::Response> {
+        let slot = self.acquire().await;
+        let mut response = timeouts
+            .within(TimeoutStage::ResponseHeaders, send(request))
+            .await?;
+        if let Some(slot) = slot {
+            response.extensions_mut().insert(slot);
+        }
+        Ok(response)
+    }
+}
+
+/// The response body as a stream that holds the response's slot until it
+/// is dropped.
+pub(crate) fn body_stream(
+    response: reqwest::Response,
+) -> impl Stream<Item = Result<Bytes>> + Send + 'static {
+    let slot = response.extensions().get::<Slot>().cloned();
+    response.bytes_stream().map(move |chunk| {
+        let _held = &slot;
+        chunk.map_err(Error::from)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn slots_are_released_on_drop() {
+        let limit = InFlight::new(Some(1));
+        let first = limit.acquire().await;
+        assert!(first.is_some());
+        let blocked = tokio::time::timeout(Duration::from_millis(20), limit.acquire()).await;
+        assert!(blocked.is_err());
+        drop(first);
+        assert!(limit.acquire().await.is_some());
+        assert!(InFlight::default().acquire().await.is_none());
+    }
+}
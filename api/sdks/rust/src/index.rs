@@ -0,0 +1,301 @@
+//! A persistent local index of listings.
+//!
+//! Read-mostly workloads that look up object metadata far more often than
+//! objects change can answer from a local copy of the listing instead of
+//! the server.  A [`LocalIndex`] keeps one in a [sled] database: [`run`]
+//! applies [`watch`] events as they arrive and periodically reconciles
+//! against a full listing, while [`lookup`] and [`list`] read the database
+//! without touching the network:
+//!
+//! ```no_run
+//! use go_objstore::index::{IndexOptions, LocalIndex};
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let index = LocalIndex::open("/var/cache/objstore-index", "datasets/")?;
+//!
+//! let updater = index.clone();
+//! tokio::spawn(async move {
+//!     if let Err(e) = updater.run(&client, IndexOptions::default()).await {
+//!         eprintln!("index stopped: {e}");
+//!     }
+//! });
+//!
+//! if let Some(object) = index.lookup("datasets/users.parquet")? {
+//!     println!("{} bytes", object.metadata.size);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The index is only as fresh as the last poll: answers may be up to one
+//! watch interval stale, and changes the watch misses (see the [`watch`]
+//! module) are corrected at the next reconciliation.  It records listing
+//! metadata only — size, ETag, modification time, and the custom metadata
+//! servers include in listings.
+//!
+//! Requires the `index` feature.
+//!
+//! [`run`]: LocalIndex::run
+//! [`lookup`]: LocalIndex::lookup
+//! [`list`]: LocalIndex::list
+
+use crate::client::ObjectStore;
+use crate::error::{Error, Result};
+use crate::types::ObjectInfo;
+use crate::watch::{self, WatchEvent, WatchOptions};
+use futures::StreamExt;
+use std::path::Path;
+use std::time::Duration;
+
+/// How [`LocalIndex::run`] keeps the index current.
+#[derive(Debug, Clone)]
+pub struct IndexOptions {
+    /// Delay between watch polls.  Defaults to 5 seconds.
+    pub watch_interval: Duration,
+    /// Delay between full reconciliations.  Defaults to 10 minutes.
+    pub reconcile_interval: Duration,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self {
+            watch_interval: Duration::from_secs(5),
+            reconcile_interval: Duration::from_secs(600),
+        }
+    }
+}
+
+impl IndexOptions {
+    /// Set the delay between watch polls.
+    pub fn with_watch_interval(mut self, interval: Duration) -> Self {
+        self.watch_interval = interval;
+        self
+    }
+
+    /// Set the delay between full reconciliations.
+    pub fn with_reconcile_interval(mut self, interval: Duration) -> Self {
+        self.reconcile_interval = interval;
+        self
+    }
+}
+
+/// What a [`LocalIndex::reconcile`] changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// Objects listed by the server but missing from the index.
+    pub added: u64,
+    /// Indexed objects whose size, ETag, or modification time differed.
+    pub updated: u64,
+    /// Indexed objects the server no longer lists.
+    pub removed: u64,
+}
+
+/// A local index of the objects under one prefix.  See the
+/// [module docs](self).
+///
+/// Clones share the database, so one clone can [`run`](Self::run) while
+/// others read.
+#[derive(Debug, Clone)]
+pub struct LocalIndex {
+    tree: sled::Tree,
+    prefix: String,
+}
+
+impl LocalIndex {
+    /// Open (or create) the index database at `path` for objects under
+    /// `prefix`.  Each prefix is kept separately, so one database can hold
+    /// several indexes.
+    pub fn open(path: impl AsRef<Path>, prefix: &str) -> Result<Self> {
+        let db = sled::open(path).map_err(index_error)?;
+        Self::with_db(&db, prefix)
+    }
+
+    /// Use an already open sled database.
+    pub fn with_db(db: &sled::Db, prefix: &str) -> Result<Self> {
+        let tree = db
+            .open_tree(format!("objects:{prefix}"))
+            .map_err(index_error)?;
+        Ok(Self {
+            tree,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// The prefix this index covers.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The indexed state of `key`.  Keys outside the prefix are never
+    /// indexed.
+    pub fn lookup(&self, key: &str) -> Result<Option<ObjectInfo>> {
+        match self.tree.get(key).map_err(index_error)? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The indexed objects under `prefix`, in key order.
+    pub fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        self.tree
+            .scan_prefix(prefix)
+            .map(|entry| {
+                let (_, value) = entry.map_err(index_error)?;
+                Ok(serde_json::from_slice(&value)?)
+            })
+            .collect()
+    }
+
+    /// Number of indexed objects.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Whether the index holds no objects.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Apply one watch event.
+    pub fn apply(&self, event: &WatchEvent) -> Result<()> {
+        match event {
+            WatchEvent::Created(object) | WatchEvent::Updated(object) => self.insert(object),
+            WatchEvent::Deleted(object) => {
+                self.tree.remove(&object.key).map_err(index_error)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace the index contents with a full listing of the prefix.
+    pub async fn reconcile(&self, store: &dyn ObjectStore) -> Result<ReconcileReport> {
+        let mut current = watch::snapshot(store, &self.prefix).await?;
+        let mut report = ReconcileReport::default();
+        for entry in self.tree.iter() {
+            let (key, value) = entry.map_err(index_error)?;
+            let indexed: ObjectInfo = serde_json::from_slice(&value)?;
+            match current.remove(&indexed.key) {
+                None => {
+                    self.tree.remove(key).map_err(index_error)?;
+                    report.removed += 1;
+                }
+                Some(object) if watch::changed(&indexed, &object) => {
+                    self.insert(&object)?;
+                    report.updated += 1;
+                }
+                Some(_) => {}
+            }
+        }
+        for object in current.values() {
+            self.insert(object)?;
+            report.added += 1;
+        }
+        self.tree.flush_async().await.map_err(index_error)?;
+        Ok(report)
+    }
+
+    /// Keep the index current: reconcile now and every
+    /// [`IndexOptions::reconcile_interval`], applying watch events in
+    /// between.  Failed polls and reconciliations are logged and retried;
+    /// only failing to record a watch event stops the loop.
+    pub async fn run(&self, store: &dyn ObjectStore, options: IndexOptions) -> Result<()> {
+        let mut reconcile = tokio::time::interval(options.reconcile_interval);
+        reconcile.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let watch_options = WatchOptions::default().with_interval(options.watch_interval);
+        let mut events = watch::watch(store, &self.prefix, watch_options);
+        loop {
+            tokio::select! {
+                _ = reconcile.tick() => match self.reconcile(store).await {
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(prefix = %self.prefix, "index reconciliation failed: {e}"),
+                },
+                event = events.next() => match event {
+                    Some(Ok(event)) => self.apply(&event)?,
+                    Some(Err(e)) => tracing::warn!(prefix = %self.prefix, "index watch poll failed: {e}"),
+                    None => return Ok(()),
+                },
+            }
+        }
+    }
+
+    fn insert(&self, object: &ObjectInfo) -> Result<()> {
+        self.tree
+            .insert(&object.key, serde_json::to_vec(object)?)
+            .map_err(index_error)?;
+        Ok(())
+    }
+}
+
+/// Database failures surface as I/O errors.
+fn index_error(e: sled::Error) -> Error {
+    Error::Io(e.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+
+    fn keys(objects: Vec<ObjectInfo>) -> Vec<String> {
+        objects.into_iter().map(|o| o.key).collect()
+    }
+
+    #[tokio::test]
+    async fn reconcile_and_events_keep_the_index_current() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = sled::open(dir.path()).unwrap();
+        let index = LocalIndex::with_db(&db, "d/").unwrap();
+        let store = MemoryStore::default();
+        store.reset(&[("d/a", 1), ("d/b", 2), ("e/x", 3)]);
+
+        let report = index.reconcile(&store).await.unwrap();
+        assert_eq!(report.added, 2);
+        assert_eq!(index.lookup("d/b").unwrap().unwrap().metadata.size, 2);
+        assert!(index.lookup("e/x").unwrap().is_none());
+
+        let removed = index.lookup("d/a").unwrap().unwrap();
+        index.apply(&WatchEvent::Deleted(removed)).unwrap();
+        assert_eq!(keys(index.list("d/").unwrap()), ["d/b"]);
+
+        store.reset(&[("d/b", 5), ("d/c", 1)]);
+        let report = index.reconcile(&store).await.unwrap();
+        assert_eq!(
+            report,
+            ReconcileReport {
+                added: 1,
+                updated: 1,
+                removed: 0
+            }
+        );
+        assert_eq!(keys(index.list("d/").unwrap()), ["d/b", "d/c"]);
+
+        // Another handle on the prefix sees the same index; other prefixes
+        // are kept apart.  (Reopening the path right after a drop races
+        // sled's flusher thread for the file lock.)
+        assert_eq!(LocalIndex::with_db(&db, "d/").unwrap().len(), 2);
+        assert!(LocalIndex::with_db(&db, "e/").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_applies_watch_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = LocalIndex::open(dir.path(), "").unwrap();
+        let store = MemoryStore::default();
+        store.reset(&[("a", 1)]);
+        let options = IndexOptions::default()
+            .with_watch_interval(Duration::from_millis(10))
+            .with_reconcile_interval(Duration::from_secs(3600));
+
+        let run = tokio::time::timeout(Duration::from_millis(300), index.run(&store, options));
+        let change = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(keys(index.list("").unwrap()), ["a"]);
+            store.reset(&[("b", 1)]);
+        };
+        let (timed_out, ()) = futures::join!(run, change);
+        assert!(timed_out.is_err());
+        assert_eq!(keys(index.list("").unwrap()), ["b"]);
+    }
+}
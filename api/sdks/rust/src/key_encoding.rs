@@ -0,0 +1,94 @@
+//! How object keys are written into request paths.
+//!
+//! The REST and QUIC clients put the key in the URL path
+//! (`/objects/{key}`, `/metadata/{key}`).  By default every reserved
+//! character is percent-encoded, slashes included, so `logs/2024/app.log`
+//! is requested as `/objects/logs%2F2024%2Fapp.log`.  go-objstore servers
+//! accept both forms, but some proxies and routers in front of them reject
+//! `%2F` or route it differently from a literal slash.
+//! [`KeyEncoding::PreserveSlashes`] encodes each segment and keeps the
+//! slashes between them:
+//!
+//! ```no_run
+//! use go_objstore::{ClientConfig, KeyEncoding, ObjectStoreClient};
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     key_encoding: KeyEncoding::PreserveSlashes,
+//!     ..Default::default()
+//! };
+//! // `get("logs/2024/app.log")` now requests /objects/logs/2024/app.log
+//! let client = ObjectStoreClient::rest_with_config("http://localhost:8080", config)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With slashes preserved, keys containing `.` or `..` segments are
+//! rejected with [`Error::InvalidArgument`]: URL parsers resolve those
+//! segments (encoded or not), which would address a different object.
+//! Empty segments (`a//b`) are sent as is, though some routers collapse
+//! them.  Query parameters such as listing prefixes are always fully
+//! encoded.
+
+use crate::error::{Error, Result};
+use std::borrow::Cow;
+
+/// How the REST and QUIC clients encode keys in request paths.  See the
+/// [module docs](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyEncoding {
+    /// Percent-encode every reserved character, `/` included.
+    #[default]
+    EncodeAll,
+    /// Percent-encode each `/`-separated segment, keeping the slashes.
+    PreserveSlashes,
+}
+
+impl KeyEncoding {
+    /// `key` encoded as a path component.
+    pub fn encode<'a>(&self, key: &'a str) -> Result<Cow<'a, str>> {
+        match self {
+            KeyEncoding::EncodeAll => Ok(urlencoding::encode(key)),
+            KeyEncoding::PreserveSlashes => {
+                if key.split('/').any(|s| s == "." || s == "..") {
+                    return Err(Error::InvalidArgument(format!(
+                        "key {key:?} has a dot segment, which cannot be sent with slashes preserved"
+                    )));
+                }
+                if !key.contains('/') {
+                    return Ok(urlencoding::encode(key));
+                }
+                let segments: Vec<_> = key.split('/').map(urlencoding::encode).collect();
+                Ok(Cow::Owned(segments.join("/")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_keys() {
+        let key = "logs/2024/app 1.log";
+        assert_eq!(
+            KeyEncoding::EncodeAll.encode(key).unwrap(),
+            "logs%2F2024%2Fapp%201.log"
+        );
+        assert_eq!(
+            KeyEncoding::PreserveSlashes.encode(key).unwrap(),
+            "logs/2024/app%201.log"
+        );
+        assert_eq!(KeyEncoding::PreserveSlashes.encode("a//b").unwrap(), "a//b");
+        assert_eq!(
+            KeyEncoding::PreserveSlashes.encode("q?#%").unwrap(),
+            "q%3F%23%25"
+        );
+        assert!(KeyEncoding::PreserveSlashes.encode("a/../b").is_err());
+        assert_eq!(
+            KeyEncoding::EncodeAll.encode("a/../b").unwrap(),
+            "a%2F..%2Fb"
+        );
+    }
+}
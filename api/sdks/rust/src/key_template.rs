@@ -0,0 +1,437 @@
+//! Shared object key layouts.
+//!
+//! A [`KeyTemplate`] such as `"{tenant}/{yyyy}/{mm}/{dd}/{uuid}.json"`
+//! fills in keys from named fields plus the built-in date and random
+//! fields, and parses keys back into their fields, so teams writing to
+//! the same store agree on one layout:
+//!
+//! ```
+//! use chrono::{TimeZone, Utc};
+//! use go_objstore::key_template::KeyTemplate;
+//!
+//! let template = KeyTemplate::parse("{tenant}/{yyyy}/{mm}/{dd}/{uuid}.json")?;
+//! let at = Utc.with_ymd_and_hms(2024, 5, 6, 12, 0, 0).unwrap();
+//! let key = template.fill_at(&[("tenant", "acme")], at)?;
+//! assert!(key.starts_with("acme/2024/05/06/"));
+//!
+//! let parts = template.parse_key(&key).unwrap();
+//! assert_eq!(parts.get("tenant"), Some("acme"));
+//! assert_eq!(parts.date(), Some(at.date_naive()));
+//! # Ok::<(), go_objstore::Error>(())
+//! ```
+//!
+//! Built-in fields are `{yyyy}`, `{mm}`, `{dd}`, and `{hh}` (UTC),
+//! `{uuid}` (a random version 4 UUID), and `{rand}` (a
+//! [`RANDOM_SUFFIX_LEN`]-character [`random_suffix`]).  Any other name is a
+//! caller-supplied field.  For keys to parse back unambiguously, a
+//! caller-supplied field must be followed by literal text or end the
+//! template, and its values may not contain `/` or that literal text.
+//!
+//! [`with_random_suffix`] and [`unique_key`] make ad-hoc keys collision
+//! safe without a template.
+
+use crate::client::ObjectStore;
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::{BTreeMap, HashSet};
+
+/// Length of the `{rand}` field.
+pub const RANDOM_SUFFIX_LEN: usize = 8;
+
+/// Lowercase Crockford base32: no `i`, `l`, `o`, or `u` to misread.
+const SUFFIX_ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+
+/// Candidates [`unique_key`] tries before giving up.
+const UNIQUE_KEY_ATTEMPTS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Field {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Uuid,
+    Rand,
+    Named(String),
+}
+
+impl Field {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "yyyy" => Field::Year,
+            "mm" => Field::Month,
+            "dd" => Field::Day,
+            "hh" => Field::Hour,
+            "uuid" => Field::Uuid,
+            "rand" => Field::Rand,
+            other => Field::Named(other.to_string()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Field::Year => "yyyy",
+            Field::Month => "mm",
+            Field::Day => "dd",
+            Field::Hour => "hh",
+            Field::Uuid => "uuid",
+            Field::Rand => "rand",
+            Field::Named(name) => name,
+        }
+    }
+
+    /// Length of the built-in fields' values; `None` for named fields.
+    fn width(&self) -> Option<usize> {
+        match self {
+            Field::Year => Some(4),
+            Field::Month | Field::Day | Field::Hour => Some(2),
+            Field::Uuid => Some(36),
+            Field::Rand => Some(RANDOM_SUFFIX_LEN),
+            Field::Named(_) => None,
+        }
+    }
+
+    /// Whether `value` has the shape this built-in field produces.
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            Field::Year | Field::Month | Field::Day | Field::Hour => {
+                value.bytes().all(|b| b.is_ascii_digit())
+            }
+            Field::Uuid => value.bytes().enumerate().all(|(i, b)| match i {
+                8 | 13 | 18 | 23 => b == b'-',
+                _ => b.is_ascii_hexdigit(),
+            }),
+            Field::Rand => value.bytes().all(|b| SUFFIX_ALPHABET.contains(&b)),
+            Field::Named(_) => !value.is_empty() && !value.contains('/'),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// A key layout with `{field}` placeholders.  See the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyTemplate {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+impl KeyTemplate {
+    /// Parse `template`.
+    ///
+    /// Fails with [`Error::InvalidArgument`] for unbalanced braces, empty or
+    /// repeated field names, and named fields directly followed by another
+    /// field, whose boundary could not be found when parsing keys back.
+    pub fn parse(template: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            Err(Error::InvalidArgument(format!(
+                "key template {template:?}: {reason}"
+            )))
+        };
+        let mut segments = Vec::new();
+        let mut rest = template;
+        let mut names = HashSet::new();
+        while !rest.is_empty() {
+            let Some(open) = rest.find('{') else {
+                if rest.contains('}') {
+                    return invalid("unmatched '}'");
+                }
+                segments.push(Segment::Literal(rest.to_string()));
+                break;
+            };
+            let literal = &rest[..open];
+            if literal.contains('}') {
+                return invalid("unmatched '}'");
+            }
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(literal.to_string()));
+            }
+            let Some(close) = rest[open..].find('}') else {
+                return invalid("unmatched '{'");
+            };
+            let name = &rest[open + 1..open + close];
+            if name.is_empty() || name.contains('{') {
+                return invalid("empty or nested field name");
+            }
+            if !names.insert(name) {
+                return invalid(&format!("field {{{name}}} appears twice"));
+            }
+            if let Some(Segment::Field(Field::Named(prev))) = segments.last() {
+                return invalid(&format!(
+                    "field {{{prev}}} needs literal text before {{{name}}}"
+                ));
+            }
+            segments.push(Segment::Field(Field::from_name(name)));
+            rest = &rest[open + close + 1..];
+        }
+        Ok(Self {
+            template: template.to_string(),
+            segments,
+        })
+    }
+
+    /// The template text.
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// Names of the caller-supplied fields, in template order.
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|s| match s {
+            Segment::Field(Field::Named(name)) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Fill in a key for the current time.  See [`fill_at`](Self::fill_at).
+    pub fn fill(&self, values: &[(&str, &str)]) -> Result<String> {
+        self.fill_at(values, Utc::now())
+    }
+
+    /// Fill in a key with the date fields taken from `at`.
+    ///
+    /// `values` supplies the named fields; extra entries are ignored.
+    /// Fails with [`Error::InvalidArgument`] if a field is missing or its
+    /// value would not parse back.
+    pub fn fill_at(&self, values: &[(&str, &str)], at: DateTime<Utc>) -> Result<String> {
+        let mut key = String::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Literal(text) => key.push_str(text),
+                Segment::Field(Field::Year) => key.push_str(&at.format("%Y").to_string()),
+                Segment::Field(Field::Month) => key.push_str(&at.format("%m").to_string()),
+                Segment::Field(Field::Day) => key.push_str(&at.format("%d").to_string()),
+                Segment::Field(Field::Hour) => key.push_str(&at.format("%H").to_string()),
+                Segment::Field(Field::Uuid) => key.push_str(&random_uuid()?),
+                Segment::Field(Field::Rand) => key.push_str(&random_suffix(RANDOM_SUFFIX_LEN)?),
+                Segment::Field(field @ Field::Named(name)) => {
+                    let Some(&(_, value)) = values.iter().find(|(n, _)| n == name) else {
+                        return Err(Error::InvalidArgument(format!(
+                            "key template {:?}: missing field {{{name}}}",
+                            self.template
+                        )));
+                    };
+                    let next = match self.segments.get(i + 1) {
+                        Some(Segment::Literal(text)) => Some(text.as_str()),
+                        _ => None,
+                    };
+                    if !field.accepts(value) || next.is_some_and(|n| value.contains(n)) {
+                        return Err(Error::InvalidArgument(format!(
+                            "key template {:?}: invalid value {value:?} for {{{name}}}",
+                            self.template
+                        )));
+                    }
+                    key.push_str(value);
+                }
+            }
+        }
+        Ok(key)
+    }
+
+    /// Split `key` back into its fields, or `None` if it does not follow
+    /// the template.
+    pub fn parse_key(&self, key: &str) -> Option<KeyParts> {
+        let mut fields = BTreeMap::new();
+        let mut rest = key;
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Literal(text) => rest = rest.strip_prefix(text.as_str())?,
+                Segment::Field(field) => {
+                    let len = match (field.width(), self.segments.get(i + 1)) {
+                        (Some(width), _) => width,
+                        (None, Some(Segment::Literal(next))) => rest.find(next.as_str())?,
+                        (None, _) => rest.len(),
+                    };
+                    let value = rest.get(..len)?;
+                    if !field.accepts(value) {
+                        return None;
+                    }
+                    fields.insert(field.name().to_string(), value.to_string());
+                    rest = &rest[len..];
+                }
+            }
+        }
+        rest.is_empty().then_some(KeyParts { fields })
+    }
+
+    /// Whether `key` follows the template.
+    pub fn matches(&self, key: &str) -> bool {
+        self.parse_key(key).is_some()
+    }
+}
+
+/// Fields of a key parsed by [`KeyTemplate::parse_key`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyParts {
+    /// Every field by name, built-ins included (`"yyyy"`, `"uuid"`, ...).
+    pub fields: BTreeMap<String, String>,
+}
+
+impl KeyParts {
+    /// The value of field `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// The date from the `{yyyy}`, `{mm}`, and `{dd}` fields, if the
+    /// template has all three and they form a valid date.
+    pub fn date(&self) -> Option<NaiveDate> {
+        let num = |name| self.get(name)?.parse().ok();
+        NaiveDate::from_ymd_opt(num("yyyy")? as i32, num("mm")?, num("dd")?)
+    }
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    SystemRandom::new()
+        .fill(&mut buf)
+        .map_err(|_| Error::Generic("system random source failed".into()))?;
+    Ok(buf)
+}
+
+/// `len` random lowercase base32 characters (5 bits each).
+pub fn random_suffix(len: usize) -> Result<String> {
+    Ok(random_bytes(len)?
+        .into_iter()
+        .map(|b| SUFFIX_ALPHABET[(b & 31) as usize] as char)
+        .collect())
+}
+
+fn random_uuid() -> Result<String> {
+    let mut b = random_bytes(16)?;
+    b[6] = (b[6] & 0x0f) | 0x40; // version 4
+    b[8] = (b[8] & 0x3f) | 0x80; // RFC 4122 variant
+    let hex: String = b.iter().map(|b| format!("{b:02x}")).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Insert `-<suffix>` of `len` random characters before the extension of
+/// `key`'s last path segment: `reports/q1.csv` becomes
+/// `reports/q1-7k2m9x4a.csv`.
+pub fn with_random_suffix(key: &str, len: usize) -> Result<String> {
+    let suffix = random_suffix(len)?;
+    let name_start = key.rfind('/').map_or(0, |i| i + 1);
+    // A leading dot (".env") starts a name, not an extension.
+    let split = match key[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => name_start + dot,
+        _ => key.len(),
+    };
+    Ok(format!("{}-{suffix}{}", &key[..split], &key[split..]))
+}
+
+/// A suffixed variant of `key` (see [`with_random_suffix`]) that does not
+/// exist in `store` yet.
+///
+/// The check and a later put are not atomic, but with
+/// [`RANDOM_SUFFIX_LEN`] characters a concurrent writer picking the same
+/// key is a one in 2^40 event.  Fails with [`Error::AlreadyExists`] if
+/// every candidate is taken.
+pub async fn unique_key(store: &dyn ObjectStore, key: &str) -> Result<String> {
+    for _ in 0..UNIQUE_KEY_ATTEMPTS {
+        let candidate = with_random_suffix(key, RANDOM_SUFFIX_LEN)?;
+        if !store.exists(&candidate).await? {
+            return Ok(candidate);
+        }
+    }
+    Err(Error::AlreadyExists(format!(
+        "no free suffixed key for {key} after {UNIQUE_KEY_ATTEMPTS} attempts"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use crate::ObjectStoreClient;
+    use chrono::TimeZone;
+    use mockito::Matcher;
+
+    #[test]
+    fn fills_and_parses_back() {
+        let template = KeyTemplate::parse("{tenant}/{yyyy}{mm}{dd}/{hh}-{uuid}.{ext}").unwrap();
+        assert_eq!(template.fields().collect::<Vec<_>>(), ["tenant", "ext"]);
+        let at = Utc.with_ymd_and_hms(2024, 5, 6, 7, 0, 0).unwrap();
+        let key = template
+            .fill_at(&[("tenant", "acme"), ("ext", "json"), ("unused", "x")], at)
+            .unwrap();
+        assert!(key.starts_with("acme/20240506/07-"), "{key}");
+        assert!(key.ends_with(".json"));
+
+        let parts = template.parse_key(&key).unwrap();
+        assert_eq!(parts.get("tenant"), Some("acme"));
+        assert_eq!(parts.get("hh"), Some("07"));
+        assert_eq!(parts.get("uuid").map(|u| &u[14..15]), Some("4"));
+        assert_eq!(parts.date(), NaiveDate::from_ymd_opt(2024, 5, 6));
+        assert!(!template.matches("acme/2024056/07-x.json"));
+        assert!(!template.matches(&format!("{key}/x")));
+    }
+
+    #[test]
+    fn rejects_ambiguous_templates_and_values() {
+        for bad in ["{tenant}{yyyy}", "{a}/{a}", "{}", "x}", "{open"] {
+            assert!(KeyTemplate::parse(bad).is_err(), "{bad}");
+        }
+        let template = KeyTemplate::parse("{team}-{rand}/{name}").unwrap();
+        for (team, name) in [("a-b", "n"), ("a/b", "n"), ("", "n"), ("a", "n/m")] {
+            assert!(
+                template.fill(&[("team", team), ("name", name)]).is_err(),
+                "{team} {name}"
+            );
+        }
+        assert!(template.fill(&[("team", "a")]).is_err());
+        let key = template.fill(&[("team", "ops"), ("name", "x")]).unwrap();
+        assert_eq!(template.parse_key(&key).unwrap().fields.len(), 3);
+    }
+
+    #[test]
+    fn suffix_goes_before_the_extension() {
+        let key = with_random_suffix("reports/q1.tar.gz", 6).unwrap();
+        assert!(
+            key.starts_with("reports/q1.tar-") && key.ends_with(".gz"),
+            "{key}"
+        );
+        assert_eq!(key.len(), "reports/q1.tar.gz".len() + 7);
+        assert!(with_random_suffix("dir.d/.env", 4)
+            .unwrap()
+            .starts_with("dir.d/.env-"));
+    }
+
+    #[tokio::test]
+    async fn unique_key_skips_taken_candidates() {
+        let mut server = mockito::Server::new_async().await;
+        let free = server
+            .mock("HEAD", Matcher::Regex(r"^/objects/".into()))
+            .with_status(404)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+        let key = unique_key(&client, "out/report.csv").await.unwrap();
+        free.assert_async().await;
+        assert!(key.starts_with("out/report-") && key.ends_with(".csv"));
+
+        free.remove_async().await;
+        let taken = server
+            .mock("HEAD", Matcher::Regex(r"^/objects/".into()))
+            .with_status(200)
+            .expect(UNIQUE_KEY_ATTEMPTS)
+            .create_async()
+            .await;
+        assert!(matches!(
+            unique_key(&client, "out/report.csv").await,
+            Err(Error::AlreadyExists(_))
+        ));
+        taken.assert_async().await;
+    }
+}
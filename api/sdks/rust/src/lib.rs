@@ -12,7 +12,216 @@
 //! - **Unified interface**: Common trait for all protocols
 //! - **App-layer auth**: Optional `Authorization: Bearer`, `X-Tenant-ID`, and
 //!   arbitrary extra headers injected by [`AuthConfig`]
-//! - **Streaming**: `get_stream` / `put_stream` on REST, gRPC, and QUIC clients
+//! - **Config files**: `ObjectStoreClient::from_config_file("objstore.toml")`
+//!   loads named profiles (endpoint, protocol, auth, TLS, retry) with
+//!   `${ENV}` interpolation; see [`config_file`]
+//! - **Credentials**: tokens are held in a redacted, zeroizing
+//!   [`SecretString`]; [`credentials::CredentialsChain`] looks them up from
+//!   the environment, a file, or a callback
+//! - **Custom resolution**: static host→IP overrides or a custom [`Resolve`]
+//!   implementation via [`ClientConfig::resolver`]
+//! - **Multi-tenancy**: `client.for_tenant("acme")` returns a [`TenantClient`]
+//!   that tags every call with the tenant and can prefix keys
+//! - **Raw headers**: `.with_headers()` from [`ResponseExt`] returns the raw
+//!   response headers / gRPC metadata alongside any call's result
+//! - **Introspection**: `client.protocol()`, `client.endpoint()`, and
+//!   `client.tls_info()` identify the transport behind each request;
+//!   [`Protocol`] implements `Display` and `FromStr`
+//! - **Write durability**: `RequestOptions::durability(Durability::Fsync)`
+//!   asks the server to acknowledge a put only once it is durable, and
+//!   fails unless the server confirms the level (see [`features`])
+//! - **Read-your-writes**: `PutResponse::consistency_token` and
+//!   `RequestOptions::min_consistency_token` for replicated deployments
+//! - **Region pinning**: `RequestOptions::region("eu-west-1")` routes calls
+//!   through multi-region gateways; `PutResponse::region` echoes placement
+//! - **Raw requests**: `client.raw_request(...)` (REST, QUIC) and
+//!   `client.grpc_raw(...)` reach endpoints that have no typed method yet
+//! - **Retries**: `client.with_retry(policy)` re-issues transient failures
+//!   with backoff; a [`RetryClassifier`] decides per protocol what is retried,
+//!   [`RetryPolicy::subscribe`] streams a [`RetryEvent`] per retry, and a
+//!   [`RetryBudget`] caps retries at a share of recent calls
+//! - **Metrics**: `client.with_metrics(metrics)` records latency, errors,
+//!   and bytes per operation; [`metrics::PushExporter`] pushes them to a
+//!   Prometheus Pushgateway or OTLP collector for short-lived jobs
+//! - **Bulk stat**: `client.get_metadata_many(keys)` fetches metadata for
+//!   many keys concurrently, with a result per key
+//! - **Bulk delete**: `client.delete_many(keys)` deletes a key list in
+//!   bulk requests where the REST server supports it and with bounded
+//!   concurrency otherwise, with a result per key; `client.delete_prefix`
+//!   deletes everything under a prefix, page by page (see [`delete`])
+//! - **Post-write verification**: `client.verify_prefix(prefix, expected)`
+//!   checks keys against expected ETags by HEAD or, for large sets, by
+//!   listing, and reports drift (see [`verify`])
+//! - **Listing export**: [`export::export_listing`] writes paginated listings
+//!   as NDJSON or CSV for inventory jobs
+//! - **Tar import**: `client.import_tar(reader, prefix)` streams a tar or
+//!   tar.gz archive into objects, keeping entry paths and recording mtimes
+//!   as metadata (see [`import`])
+//! - **Inventory diff**: [`diff::diff`] compares two stores' listings and
+//!   reports missing, extra, and mismatched keys
+//! - **Checksum manifests**: [`manifest::generate_manifest`] records signed
+//!   SHA-256 digests of a prefix; [`manifest::verify_manifest`] re-hashes and
+//!   reports corruption
+//! - **Merkle verification**: [`merkle::put_with_merkle`] records a chunk
+//!   hash tree so [`merkle::verify`] can check ranges or random samples
+//! - **Per-key ordering**: [`queue::KeyedUploadQueue`] runs calls on
+//!   different keys in parallel and calls on the same key in issue order
+//! - **Worker pool**: [`pool::WorkerPool`] runs caller-submitted keyed tasks
+//!   with bounded concurrency, backpressure, and cancellation
+//! - **Presigned URLs**: [`presign::presign`] issues time-limited object
+//!   URLs and [`presign::verify_presigned`] validates them in gateways
+//! - **Encrypted metadata**: [`encrypted_metadata::EncryptedMetadataStore`]
+//!   seals the values of sensitive custom metadata keys client-side, keeping
+//!   them out of plaintext listings
+//! - **Tiered stores**: [`tiered::TieredObjectStore`] caches a slow store in
+//!   a fast one, reading through on misses and writing through to both
+//! - **Sharding**: [`sharded::ShardedObjectStore`] spreads keys over several
+//!   servers by consistent hashing; [`sharded::migrate`] moves keys after
+//!   the fleet changes
+//! - **Prefix routing**: [`routing::RoutingObjectStore`] sends each key to
+//!   the store registered for its longest matching prefix, with a default
+//! - **Client-side policies**: `client.with_policy(rules)` rejects writes
+//!   outside allowed prefixes, over a size limit, missing required metadata,
+//!   or with a forbidden content type (see [`guard`])
+//! - **Provenance stamping**: `client.with_stamper(MetadataStamper::from_env())`
+//!   adds created-by, created-at, SDK version, app version, and git SHA
+//!   metadata to every put (see [`stamp`])
+//! - **Negative caching**: `client.with_negative_cache(ttl, capacity)`
+//!   answers repeated `exists` / `get_metadata` checks for missing keys
+//!   locally for a short TTL (see [`negative_cache`])
+//! - **Object TTLs**: `client.put_with_ttl(key, data, ttl)` records an
+//!   expiry in metadata; `put_with_ttl_policy` also ensures a matching
+//!   lifecycle delete policy (see [`ttl`])
+//! - **Policy simulation**: `client.simulate_policy(policy, prefix)` reports
+//!   which objects a lifecycle policy would delete or archive, and when,
+//!   before it is registered (see [`simulate`])
+//! - **Metadata queries**: `client.query(MetadataQuery)` finds objects by
+//!   custom metadata (equals, prefix, numeric range), via the server's search
+//!   endpoint or by filtering listings (see [`query`])
+//! - **Recent changes**: `client.list_recent(since, prefix)` finds objects
+//!   modified since a timestamp for incremental processors (see [`recent`])
+//! - **Cleanup jobs**: `client.retain(prefix, keep)` deletes the objects a
+//!   predicate rejects, listing the whole prefix before deleting anything
+//! - **Bulk tagging**: `client.tag_prefix(prefix, tags)` merges tags into
+//!   every object's metadata under a prefix, server-side where the REST
+//!   server supports it and object by object otherwise (see [`tags`])
+//! - **Rename**: `client.rename(src, dst)` moves an object to a new key,
+//!   atomically where the REST server supports it and by copy-then-delete
+//!   otherwise (see [`rename`])
+//! - **Usage reports**: `client.usage_by_prefix(depth)` totals bytes and
+//!   object counts grouped `depth` directory levels deep, like `du -d`,
+//!   aggregated by the server when it can and by parallel listing otherwise
+//!   (see [`usage`])
+//! - **Point-in-time reads**: `client.put_versioned(...)` keeps each write
+//!   as a version; `client.get_at(key, at)` / `metadata_at` read the version
+//!   current at a timestamp (see [`versions`])
+//! - **Key templates**: [`key_template::KeyTemplate`] fills in and parses
+//!   back shared key layouts such as `"{tenant}/{yyyy}/{mm}/{dd}/{uuid}.json"`,
+//!   with collision-safe random suffix helpers
+//! - **Directory markers**: `client.create_dir_marker(prefix)` creates an
+//!   empty folder placeholder and `DirMarkers::Hide` drops them from
+//!   listings (see [`dir_markers`])
+//! - **Temporary objects**: `client.temp_object(prefix, data)` returns a
+//!   [`temp::TempObject`] guard that deletes the object when dropped
+//! - **Audit trail**: `client.get_audit_events(log_key, filter)` streams
+//!   typed [`audit::AuditEvent`]s from a stored copy of the server's audit
+//!   log
+//! - **Watch**: [`watch::watch`] turns periodic listing diffs into a stream
+//!   of created/updated/deleted events on every protocol
+//! - **Local index**: with the `index` feature, `index::LocalIndex` keeps
+//!   a sled database of a prefix's listing current from watch events and
+//!   periodic reconciliation, for lookups without a round trip
+//! - **Analytics**: with the `parquet` feature, `client.open_parquet(key)`
+//!   reads Parquet objects as Arrow record batches over range reads; with
+//!   `datafusion`, `client.register_parquet_table(&ctx, table, prefix)`
+//!   makes a prefix queryable with SQL
+//! - **Fuzzing**: with the `fuzzing` feature, `fuzzing::run` exposes each
+//!   response parser as a fuzz target and `fuzzing::run_corpus` replays a
+//!   corpus on stable, reporting inputs that panic
+//! - **Composable trait**: `ObjectStore` is implemented for `&T`, `Box<T>`,
+//!   and `Arc<T>`, and `into_shared()` upcasts to `Arc<dyn ObjectStore>`
+//! - **Request timings**: with `ClientConfig::collect_timings`,
+//!   `.with_timing()` returns a DNS / connect / TLS / TTFB / transfer
+//!   breakdown with the result of a call (see [`timing`])
+//! - **Compatibility check**: `client.compat_check()` probes the server's
+//!   object, metadata, health, and policy routes up front and reports any
+//!   the client cannot use (see [`compat`])
+//! - **Configuration drift**: `client.snapshot_config()` captures policies
+//!   and server info in a serializable `ConfigSnapshot`, and
+//!   `client.detect_drift(&baseline)` lists the fields that changed since
+//!   (see [`config_snapshot`])
+//! - **Stage timeouts**: `ClientConfig::timeouts` bounds connect, TLS,
+//!   send, response-header, and body stages separately and reports which one
+//!   expired as `Error::Timeout(stage)` (see [`timeouts`])
+//! - **Content sniffing**: with `ClientConfig::sniff_content_type`, puts
+//!   without a content type get one detected from the payload's magic bytes
+//!   (see [`sniff`])
+//! - **Decompression**: with `ClientConfig::decompress`, REST and QUIC gets
+//!   negotiate gzip / zstd and report both transfer and decoded sizes
+//! - **gRPC compression**: `ClientConfig::grpc_compression` compresses `List`
+//!   and `Get` messages with gzip or zstd
+//! - **Custom decoders**: `ClientConfig::response_decoder` plugs in a
+//!   [`decoder::ResponseDecoder`] to capture extended list and metadata
+//!   fields such as checksums or storage classes
+//! - **Web-serving headers**: typed `Metadata::cache_control`,
+//!   `content_disposition`, and `content_language`, sent as HTTP headers on
+//!   REST/QUIC and kept in the custom map on every transport
+//! - **Page-size limits**: `list` requests for more objects than the
+//!   server's page cap are split into pages and merged instead of being
+//!   silently truncated (see [`paging`])
+//! - **Key encoding**: `ClientConfig::key_encoding` chooses whether REST and
+//!   QUIC request paths keep the slashes of nested keys or percent-encode them
+//! - **Timestamps**: `last_modified` and sync times are parsed from RFC 3339,
+//!   HTTP dates, or Unix epochs by [`timestamp::parse_timestamp`], in UTC
+//! - **Delta uploads**: [`delta::put_delta`] uploads only the blocks of a
+//!   large object that changed since its previous version (rsync-style)
+//! - **Sparse files**: [`sparse::put_sparse`] stores disk images without
+//!   their zero blocks and [`sparse::get_sparse`] restores the holes
+//! - **Streaming**: `get_stream` returns an object's metadata and a byte
+//!   stream on every client (chunk by chunk on REST, gRPC, and QUIC);
+//!   `put_stream` on REST, gRPC, and QUIC clients
+//! - **Backpressure**: `client.put_writer(key, metadata, max_buffered)`
+//!   returns a [`flow::PutWriter`] whose `ready()` waits for the transport
+//!   to drain, with stall and buffer stats for throttling producers
+//! - **Memory budget**: `ClientConfig::memory_budget` caps the bytes held
+//!   from buffered downloads until they are dropped, rejecting or (with the
+//!   `spill` feature) spilling to disk the ones that do not fit (see
+//!   [`budget`])
+//! - **Memory-mapped uploads**: `client.put_mmap(key, path, metadata)`
+//!   uploads a file from a memory mapping instead of a heap copy (see
+//!   [`mmap`])
+//! - **Listing output**: aligned tables with humanized sizes and relative
+//!   times, and flat JSON records, for printing listings (see [`format`])
+//! - **Conditional reads**: `client.get_if_none_match(key, etag)` returns
+//!   `None` while a cached copy is current and the new data otherwise (see
+//!   [`conditional`])
+//! - **Conditional writes**: `client.put_if_not_exists(key, data, meta)` and
+//!   `client.put_if_match(key, data, meta, etag)` fail with
+//!   `Error::PreconditionFailed` instead of overwriting a concurrent change,
+//!   on servers that advertise support (see [`features`])
+//! - **Image transforms**: `client.get_transformed(key, &[Transform::Resize
+//!   { .. }, Transform::Format(..)])` fetches a server-resized or re-encoded
+//!   image after checking the server advertises the transforms (see
+//!   [`transform`])
+//! - **Bounded downloads**: `client.get_limited(key, max_bytes)` stops the
+//!   transfer and fails with `Error::PayloadTooLarge` once an object passes
+//!   the limit (see [`limited`])
+//! - **Random access**: `client.reader(key)` is an `AsyncRead + AsyncSeek`
+//!   over an object that fetches and caches aligned blocks with `Range`
+//!   requests (see [`reader`])
+//! - **Zip archives**: `client.open_zip(key)` reads a remote zip's central
+//!   directory with range requests and extracts single entries on demand
+//!   (see [`zip`])
+//! - **Resumable downloads**: `client.resume_download(key, path)` continues
+//!   an interrupted download after checking the object's etag (see
+//!   [`resume`])
+//! - **Resumable uploads**: `client.resume_upload(key, path, checkpoint)`
+//!   uploads a file in parts and continues from a JSON checkpoint after an
+//!   interruption or restart (see [`upload`])
+//! - **On-demand replication**: `client.replicate_object(key, policy_id)`
+//!   returns once a replication sync covering the object has finished
+//! - **Long-running jobs**: [`lro::Operation`] handles with `status()`,
+//!   `wait()`, and `cancel()`, e.g. from `client.start_replication(...)`
 //! - **Advanced features**: Lifecycle policies, replication, archiving
 //!
 //! ## Quick Start
@@ -100,7 +309,7 @@
 //! // With auth:
 //! let auth_client = McpClient::new_with_auth(
 //!     "http://localhost:8081",
-//!     AuthConfig { token: Some("mytoken".to_string()), ..Default::default() },
+//!     AuthConfig { token: Some("mytoken".into()), ..Default::default() },
 //! )?;
 //! let resp = client.put("k", Bytes::from("v"), None).await?;
 //! # Ok(())
@@ -120,29 +329,122 @@
 //! # }
 //! ```
 
+#[cfg(feature = "parquet")]
+pub mod analytics;
+pub mod audit;
 pub mod auth;
+pub mod backends;
+pub mod budget;
+pub mod chunking;
 pub mod client;
+pub mod compat;
+pub(crate) mod compression;
+pub mod conditional;
+pub mod config;
+pub mod config_file;
+pub mod config_snapshot;
+pub mod credentials;
+pub mod decoder;
+pub mod delete;
+pub mod delta;
+pub mod diff;
+pub mod dir_markers;
+pub(crate) mod downlevel;
 pub mod duration;
+pub mod encrypted_metadata;
 pub mod error;
+pub mod export;
+pub mod features;
+pub mod flow;
+pub mod format;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
 pub mod grpc_client;
+pub mod guard;
+pub mod import;
+pub(crate) mod in_flight;
+#[cfg(feature = "index")]
+pub mod index;
 pub(crate) mod jsonrpc;
+pub mod key_encoding;
+pub mod key_template;
+pub mod limited;
+pub(crate) mod list_parser;
+pub mod lro;
+pub mod manifest;
 pub mod mcp_client;
+pub mod merkle;
+pub mod metrics;
+pub mod mmap;
+pub mod negative_cache;
+pub mod paging;
+pub mod pool;
+pub mod presign;
+pub mod query;
+pub mod queue;
 pub mod quic_client;
+pub mod reader;
+pub mod recent;
+pub mod rename;
+pub mod replicate;
+pub mod resolver;
+pub mod response;
 pub mod rest_client;
+pub mod resume;
+pub mod retain;
+pub mod retry;
+pub mod routing;
+pub mod secret;
+pub mod sharded;
+pub mod simulate;
+pub mod sniff;
+pub mod sparse;
+pub mod stamp;
+pub mod stat;
 pub mod streaming;
+pub mod tags;
+pub mod temp;
+pub mod tenant;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod tiered;
+pub mod timeouts;
+pub mod timestamp;
+pub mod timing;
+pub mod tls;
+pub mod transform;
+pub mod ttl;
 pub mod types;
 pub mod unix_client;
+pub mod upload;
+pub mod usage;
+pub mod verify;
+pub mod versions;
+pub mod watch;
+pub mod zip;
 
 // Re-export main types for convenience
 pub use auth::AuthConfig;
-pub use client::{ObjectStore, ObjectStoreClient};
-pub use error::{Error, Result};
+pub use budget::{MemoryBudget, OverBudget};
+pub use chunking::ChunkSizing;
+pub use client::{ByteStream, ObjectStore, ObjectStoreClient, Operation, Protocol};
+pub use config::ClientConfig;
+pub use error::{Error, H3ErrorCode, H3ErrorScope, Result, TimeoutStage};
+pub use key_encoding::KeyEncoding;
+pub use resolver::{Resolve, Resolver};
+pub use response::{RawResponse, ResponseExt, ResponseHeaders};
+pub use retry::{RetryBudget, RetryClassifier, RetryDecision, RetryEvent, RetryPolicy, Retrying};
+pub use secret::SecretString;
+pub use tenant::TenantClient;
+pub use timeouts::Timeouts;
+pub use timing::Timing;
+pub use tls::{ClientIdentity, TlsInfo};
 pub use types::*;
 
 // Re-export individual clients
-pub use grpc_client::GrpcClient;
+pub use grpc_client::{GrpcClient, GrpcCompression, Interceptor, Keepalive};
 pub use mcp_client::McpClient;
-pub use quic_client::{QuicClient, TlsVerification};
+pub use quic_client::{HandshakeRetry, QuicClient, TlsVerification};
 pub use rest_client::RestClient;
 pub use unix_client::UnixClient;
 
@@ -0,0 +1,111 @@
+//! Size-bounded downloads.
+//!
+//! [`get_limited`] reads an object only if it fits in `max_bytes`, so a
+//! request handler cannot be made to buffer an arbitrarily large object:
+//!
+//! ```no_run
+//! use go_objstore::{Error, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! match client.get_limited("uploads/avatar.png", 5 * 1024 * 1024).await {
+//!     Ok((data, _)) => println!("{} bytes", data.len()),
+//!     Err(Error::PayloadTooLarge { limit, .. }) => println!("over {limit} bytes"),
+//!     Err(e) => return Err(e.into()),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! An object whose reported size is over the limit is rejected as soon as
+//! its headers arrive.  Otherwise the body is read from
+//! [`get_stream`](ObjectStore::get_stream) and the stream is dropped, which
+//! closes the transfer, the moment the bytes received pass the limit.
+//! Transports without streaming downloads (MCP, Unix) receive the whole
+//! object before the check can run.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::Metadata;
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
+
+/// Get `key` if it is at most `max_bytes` long, or fail with
+/// [`Error::PayloadTooLarge`].
+pub async fn get_limited(
+    store: &dyn ObjectStore,
+    key: &str,
+    max_bytes: u64,
+) -> Result<(Bytes, Metadata)> {
+    let too_large = || Error::PayloadTooLarge {
+        key: key.to_string(),
+        limit: max_bytes,
+    };
+    let (mut stream, metadata) = store.get_stream(key).await?;
+    if u64::try_from(metadata.size).is_ok_and(|size| size > max_bytes) {
+        return Err(too_large());
+    }
+    let mut data = BytesMut::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if (data.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok((data.freeze(), metadata))
+}
+
+impl ObjectStoreClient {
+    /// Get `key` if it is at most `max_bytes` long.  See the
+    /// [`limited`](crate::limited) module.
+    pub async fn get_limited(&self, key: &str, max_bytes: u64) -> Result<(Bytes, Metadata)> {
+        get_limited(self, key, max_bytes).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+
+    #[tokio::test]
+    async fn enforces_the_limit() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects/small")
+            .with_status(200)
+            .with_body("hello")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/big")
+            .with_status(200)
+            .with_body(vec![0u8; 4096])
+            .create_async()
+            .await;
+        // Streamed without a Content-Length, so only the byte count can
+        // catch it.
+        server
+            .mock("GET", "/objects/chunked")
+            .with_status(200)
+            .with_chunked_body(|w| {
+                for _ in 0..64 {
+                    w.write_all(&[1u8; 1024])?;
+                }
+                Ok(())
+            })
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let (data, _) = client.get_limited("small", 5).await.unwrap();
+        assert_eq!(data, "hello");
+        for key in ["big", "chunked"] {
+            assert!(matches!(
+                client.get_limited(key, 1000).await,
+                Err(Error::PayloadTooLarge { limit: 1000, .. })
+            ));
+        }
+    }
+}
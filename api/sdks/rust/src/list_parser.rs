@@ -0,0 +1,406 @@
+//! Crate-private incremental parser for the REST/QUIC list response body.
+//!
+//! The server answers `GET /objects` with a single JSON document:
+//!
+//! ```json
+//! {"objects":[{"key":"a","size":1,...},...],"common_prefixes":[...],
+//!  "next_token":"...","truncated":true}
+//! ```
+//!
+//! [`ListBodyParser`] consumes that document chunk by chunk and yields each
+//! entry of `objects` as soon as its closing brace arrives, so neither the
+//! raw body nor the full entry list has to be held in memory at once.
+
+use crate::decoder::{DefaultDecoder, ResponseDecoder};
+use crate::error::{Error, Result};
+use crate::types::{ListRequest, ListResponse, ObjectInfo};
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{Future, Stream, StreamExt};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// The non-`objects` fields of a list response.
+#[derive(Debug, Default)]
+pub(crate) struct ListTail {
+    pub common_prefixes: Vec<String>,
+    pub next_token: Option<String>,
+    pub truncated: bool,
+}
+
+#[derive(Debug)]
+enum State {
+    Start,
+    KeyOrEnd,
+    Colon(String),
+    Value(String),
+    Objects,
+    AfterValue,
+    Done,
+}
+
+/// Push parser for a list response body.
+#[derive(Debug)]
+pub(crate) struct ListBodyParser {
+    buf: Vec<u8>,
+    state: State,
+    tail: ListTail,
+    decoder: Arc<dyn ResponseDecoder>,
+}
+
+impl ListBodyParser {
+    /// A parser decoding entries with `decoder`, or the SDK's own decoding.
+    pub(crate) fn new(decoder: Option<Arc<dyn ResponseDecoder>>) -> Self {
+        Self {
+            buf: Vec::new(),
+            state: State::Start,
+            tail: ListTail::default(),
+            decoder: decoder.unwrap_or_else(|| Arc::new(DefaultDecoder)),
+        }
+    }
+
+    /// Feed the next chunk of the body, returning any entries it completed.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Result<Vec<ObjectInfo>> {
+        self.buf.extend_from_slice(chunk);
+
+        let mut out = Vec::new();
+        let mut pos = 0;
+        loop {
+            pos = skip_ws(&self.buf, pos);
+            let Some(&b) = self.buf.get(pos) else { break };
+
+            match std::mem::replace(&mut self.state, State::Done) {
+                State::Start => {
+                    expect(b, b'{')?;
+                    pos += 1;
+                    self.state = State::KeyOrEnd;
+                }
+                State::KeyOrEnd if b == b'}' => pos += 1,
+                State::KeyOrEnd => {
+                    expect(b, b'"')?;
+                    let Some(end) = value_end(&self.buf, pos) else {
+                        self.state = State::KeyOrEnd;
+                        break;
+                    };
+                    let key: String = serde_json::from_slice(&self.buf[pos..end])?;
+                    pos = end;
+                    self.state = State::Colon(key);
+                }
+                State::Colon(key) => {
+                    expect(b, b':')?;
+                    pos += 1;
+                    self.state = State::Value(key);
+                }
+                State::Value(key) if key == "objects" && b == b'[' => {
+                    pos += 1;
+                    self.state = State::Objects;
+                }
+                State::Value(key) => {
+                    let Some(end) = value_end(&self.buf, pos) else {
+                        self.state = State::Value(key);
+                        break;
+                    };
+                    let raw = self.buf[pos..end].to_vec();
+                    self.apply_tail_field(&key, &raw)?;
+                    pos = end;
+                    self.state = State::AfterValue;
+                }
+                State::Objects if b == b']' => {
+                    pos += 1;
+                    self.state = State::AfterValue;
+                }
+                State::Objects if b == b',' => {
+                    pos += 1;
+                    self.state = State::Objects;
+                }
+                State::Objects => {
+                    self.state = State::Objects;
+                    let Some(end) = value_end(&self.buf, pos) else {
+                        break;
+                    };
+                    let mut object = self.decoder.decode_object(&self.buf[pos..end])?;
+                    object.metadata = object.metadata.lift_web_headers();
+                    out.push(object);
+                    pos = end;
+                }
+                State::AfterValue if b == b',' => {
+                    pos += 1;
+                    self.state = State::KeyOrEnd;
+                }
+                State::AfterValue => {
+                    expect(b, b'}')?;
+                    pos += 1;
+                }
+                State::Done => {
+                    return Err(Error::InvalidResponse(
+                        "unexpected data after list response".to_string(),
+                    ))
+                }
+            }
+        }
+
+        self.buf.drain(..pos);
+        Ok(out)
+    }
+
+    /// Signal end of body, returning the trailing fields.
+    pub(crate) fn finish(self) -> Result<ListTail> {
+        match self.state {
+            State::Done => Ok(self.tail),
+            _ => Err(Error::InvalidResponse(
+                "list response ended unexpectedly".to_string(),
+            )),
+        }
+    }
+
+    fn apply_tail_field(&mut self, key: &str, raw: &[u8]) -> Result<()> {
+        match key {
+            "common_prefixes" => {
+                let prefixes: Option<Vec<String>> = serde_json::from_slice(raw)?;
+                self.tail.common_prefixes = prefixes.unwrap_or_default();
+            }
+            "next_token" => self.tail.next_token = serde_json::from_slice(raw)?,
+            "truncated" => self.tail.truncated = serde_json::from_slice(raw)?,
+            "objects" => {
+                // `"objects": null` — nothing to emit.
+                let _: Option<Vec<serde_json::Value>> = serde_json::from_slice(raw)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn expect(found: u8, wanted: u8) -> Result<()> {
+    if found == wanted {
+        Ok(())
+    } else {
+        Err(Error::InvalidResponse(format!(
+            "malformed list response: expected '{}', found '{}'",
+            wanted as char, found as char
+        )))
+    }
+}
+
+fn skip_ws(buf: &[u8], mut pos: usize) -> usize {
+    while buf.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Return the end (exclusive) of the JSON value starting at `start`, or
+/// `None` if the buffer does not yet hold the whole value.
+fn value_end(buf: &[u8], start: usize) -> Option<usize> {
+    match buf[start] {
+        b'"' => string_end(buf, start),
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut i = start;
+            while i < buf.len() {
+                match buf[i] {
+                    b'"' => {
+                        i = string_end(buf, i)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(i + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            None
+        }
+        // Scalars end at the next delimiter; the enclosing object always
+        // supplies one.
+        _ => buf[start..]
+            .iter()
+            .position(|c| matches!(c, b',' | b'}' | b']') || c.is_ascii_whitespace())
+            .map(|n| start + n),
+    }
+}
+
+fn string_end(buf: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < buf.len() {
+        match buf[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Parse a complete list response from a body stream without buffering the
+/// raw body.
+pub(crate) async fn collect_list(
+    body: impl Stream<Item = Result<Bytes>> + Send,
+    decoder: Option<Arc<dyn ResponseDecoder>>,
+) -> Result<ListResponse> {
+    futures::pin_mut!(body);
+    let mut parser = ListBodyParser::new(decoder);
+    let mut objects = Vec::new();
+    while let Some(chunk) = body.next().await {
+        objects.extend(parser.push(&chunk?)?);
+    }
+    let tail = parser.finish()?;
+    Ok(ListResponse {
+        objects,
+        common_prefixes: tail.common_prefixes,
+        next_token: tail.next_token,
+        truncated: tail.truncated,
+    })
+}
+
+struct PageState<F> {
+    fetch: F,
+    next: Option<ListRequest>,
+    body: Option<BoxStream<'static, Result<Bytes>>>,
+    parser: ListBodyParser,
+    decoder: Option<Arc<dyn ResponseDecoder>>,
+    pending: VecDeque<ObjectInfo>,
+}
+
+/// Stream every object matching `list_req`, following continuation tokens
+/// across pages.  `fetch` issues one list request and returns its body.
+pub(crate) fn object_stream<F, Fut>(
+    list_req: ListRequest,
+    decoder: Option<Arc<dyn ResponseDecoder>>,
+    fetch: F,
+) -> impl Stream<Item = Result<ObjectInfo>> + Send
+where
+    F: Fn(ListRequest) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<BoxStream<'static, Result<Bytes>>>> + Send,
+{
+    let state = PageState {
+        fetch,
+        next: Some(list_req),
+        body: None,
+        parser: ListBodyParser::new(decoder.clone()),
+        decoder,
+        pending: VecDeque::new(),
+    };
+
+    futures::stream::try_unfold(state, |mut st| async move {
+        loop {
+            if let Some(obj) = st.pending.pop_front() {
+                return Ok(Some((obj, st)));
+            }
+
+            if let Some(body) = st.body.as_mut() {
+                match body.next().await {
+                    Some(chunk) => st.pending.extend(st.parser.push(&chunk?)?),
+                    None => {
+                        st.body = None;
+                        let parser = std::mem::replace(
+                            &mut st.parser,
+                            ListBodyParser::new(st.decoder.clone()),
+                        );
+                        let tail = parser.finish()?;
+                        if let (true, Some(token)) = (tail.truncated, tail.next_token) {
+                            if let Some(req) = st.next.as_mut() {
+                                req.continue_from = Some(token);
+                            }
+                        } else {
+                            st.next = None;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            match st.next.clone() {
+                Some(req) => st.body = Some((st.fetch)(req).await?),
+                None => return Ok(None),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"{"objects":[{"key":"a","size":1,"etag":"e1"},
+        {"key":"b}\"[","size":2,"metadata":{"k":"v"}}],
+        "common_prefixes":["p/"],"next_token":"tok","truncated":true}"#;
+
+    #[test]
+    fn parses_whole_body() {
+        let mut parser = ListBodyParser::new(None);
+        let objects = parser.push(BODY.as_bytes()).unwrap();
+        let tail = parser.finish().unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[1].key, "b}\"[");
+        assert_eq!(objects[1].metadata.custom.get("k").unwrap(), "v");
+        assert_eq!(tail.common_prefixes, vec!["p/".to_string()]);
+        assert_eq!(tail.next_token.as_deref(), Some("tok"));
+        assert!(tail.truncated);
+    }
+
+    #[test]
+    fn emits_entries_as_chunks_complete() {
+        // Feed one byte at a time: each entry must be emitted exactly once,
+        // as soon as its closing brace has arrived.
+        let mut parser = ListBodyParser::new(None);
+        let mut seen = Vec::new();
+        for b in BODY.as_bytes() {
+            for obj in parser.push(std::slice::from_ref(b)).unwrap() {
+                seen.push(obj.key);
+            }
+        }
+        assert_eq!(seen, vec!["a".to_string(), "b}\"[".to_string()]);
+        assert!(parser.finish().unwrap().truncated);
+    }
+
+    #[test]
+    fn null_and_empty_fields() {
+        let mut parser = ListBodyParser::new(None);
+        let objects = parser
+            .push(br#"{"objects":null,"common_prefixes":null,"truncated":false}"#)
+            .unwrap();
+        assert!(objects.is_empty());
+        assert!(parser.finish().unwrap().common_prefixes.is_empty());
+    }
+
+    #[test]
+    fn truncated_body_is_an_error() {
+        let mut parser = ListBodyParser::new(None);
+        parser.push(br#"{"objects":[{"key":"a","size":1}"#).unwrap();
+        assert!(matches!(parser.finish(), Err(Error::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn malformed_body_is_an_error() {
+        let mut parser = ListBodyParser::new(None);
+        assert!(parser.push(b"[1,2]").is_err());
+    }
+
+    #[tokio::test]
+    async fn object_stream_follows_continuation_tokens() {
+        let pages = std::sync::Arc::new(vec![
+            r#"{"objects":[{"key":"a","size":1}],"next_token":"t1","truncated":true}"#,
+            r#"{"objects":[{"key":"b","size":1}],"truncated":false}"#,
+        ]);
+        let stream = object_stream(ListRequest::default(), None, move |req| {
+            let pages = pages.clone();
+            async move {
+                let page = match req.continue_from.as_deref() {
+                    None => pages[0],
+                    Some("t1") => pages[1],
+                    Some(other) => panic!("unexpected token {other}"),
+                };
+                let body = futures::stream::iter(vec![Ok(Bytes::from(page))]);
+                Ok(body.boxed())
+            }
+        });
+        let keys: Vec<String> = stream.map(|r| r.unwrap().key).collect().await;
+        assert_eq!(keys, vec!["a".to_string(), "b".to_string()]);
+    }
+}
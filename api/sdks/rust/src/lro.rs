@@ -0,0 +1,356 @@
+//! Handles for long-running server jobs.
+//!
+//! An [`Operation<T>`] tracks a job that finishes with a `T`: check it with
+//! [`status`](Operation::status), block on it with
+//! [`wait`](Operation::wait), or stop it with
+//! [`cancel`](Operation::cancel).  Operations come in two kinds:
+//!
+//! - [`Operation::spawn`] runs a call that only returns when the job is done
+//!   (today's `trigger_replication`, `apply_policies`, `archive`) on a
+//!   background task, so the caller gets a handle right away.  Cancelling
+//!   aborts the client-side call; the server may still finish the job.
+//! - [`Operation::poll`] wraps a job the server tracks, polling its status
+//!   with exponential backoff between [`POLL_INITIAL`] and [`POLL_MAX`]
+//!   until it reports a result.  [`wait_notified`](Operation::wait_notified)
+//!   re-polls as soon as a stream (e.g. a [`watch`](crate::watch::watch)
+//!   on the job's output prefix) yields, instead of sleeping out the
+//!   backoff.
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let mut run = client.start_replication(Some("nightly".into()), true, 8);
+//! let status = run.status().await;
+//! println!("replication {} is {status:?}", run.id());
+//! let result = run.wait().await?;
+//! println!("synced {} objects", result.synced);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! This `Operation<T>` is unrelated to the [`Operation`](crate::Operation)
+//! enum naming client methods in errors.
+
+use crate::client::ObjectStoreClient;
+use crate::error::{Error, Result};
+use crate::retry::RetryPolicy;
+use crate::types::{ApplyPoliciesResult, SyncResult};
+use futures::future::BoxFuture;
+use futures::{Stream, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// First delay between status polls.
+pub const POLL_INITIAL: Duration = Duration::from_millis(500);
+
+/// Longest delay between status polls.
+pub const POLL_MAX: Duration = Duration::from_secs(30);
+
+/// One observation of a polled job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Progress<T> {
+    /// Still running.
+    Running,
+    /// Finished with a result.
+    Done(T),
+}
+
+/// Where an [`Operation`] stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+type PollFn<T> = Box<dyn Fn() -> BoxFuture<'static, Result<Progress<T>>> + Send + Sync>;
+type CancelFn = Box<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+enum Job<T> {
+    Task(JoinHandle<Result<T>>),
+    Polled {
+        poll: PollFn<T>,
+        cancel: Option<CancelFn>,
+    },
+}
+
+/// Handle to a long-running job.  See the [module docs](self).
+pub struct Operation<T> {
+    id: String,
+    job: Job<T>,
+    /// Set once the job has finished, failed, or been cancelled.
+    outcome: Option<Result<T>>,
+    cancelled: bool,
+    backoff: RetryPolicy,
+}
+
+impl<T: Send + 'static> Operation<T> {
+    /// Run `call` on a background task and track it as operation `id`.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn spawn<F>(id: impl Into<String>, call: F) -> Self
+    where
+        F: Future<Output = Result<T>> + Send + 'static,
+    {
+        Self::new(id.into(), Job::Task(tokio::spawn(call)))
+    }
+
+    /// Track a server-side job through `poll`, which reports its progress
+    /// and returns an error if the job failed.
+    pub fn poll<P, Fut>(id: impl Into<String>, poll: P) -> Self
+    where
+        P: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Progress<T>>> + Send + 'static,
+    {
+        let poll: PollFn<T> = Box::new(move || Box::pin(poll()));
+        Self::new(id.into(), Job::Polled { poll, cancel: None })
+    }
+
+    fn new(id: String, job: Job<T>) -> Self {
+        Self {
+            id,
+            job,
+            outcome: None,
+            cancelled: false,
+            backoff: RetryPolicy::default().with_backoff(POLL_INITIAL, POLL_MAX),
+        }
+    }
+
+    /// Let [`cancel`](Self::cancel) stop a polled job by calling `cancel`.
+    pub fn with_cancel<C, Fut>(mut self, cancel: C) -> Self
+    where
+        C: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        if let Job::Polled { cancel: slot, .. } = &mut self.job {
+            *slot = Some(Box::new(move || Box::pin(cancel())));
+        }
+        self
+    }
+
+    /// Poll between `initial` and `max` apart (doubling, with jitter)
+    /// instead of [`POLL_INITIAL`] and [`POLL_MAX`].
+    pub fn with_poll_interval(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = self.backoff.with_backoff(initial, max);
+        self
+    }
+
+    /// The operation's identifier.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Check on the job without waiting for it.  Polled jobs are polled
+    /// once; a failed poll marks the operation failed.
+    pub async fn status(&mut self) -> OperationStatus {
+        if self.outcome.is_none() {
+            match &mut self.job {
+                Job::Task(handle) if handle.is_finished() => {
+                    self.outcome = Some(join(&self.id, handle).await);
+                }
+                Job::Task(_) => {}
+                Job::Polled { poll, .. } => match poll().await {
+                    Ok(Progress::Running) => {}
+                    Ok(Progress::Done(value)) => self.outcome = Some(Ok(value)),
+                    Err(e) => self.outcome = Some(Err(e)),
+                },
+            }
+        }
+        match &self.outcome {
+            None => OperationStatus::Running,
+            Some(Ok(_)) => OperationStatus::Succeeded,
+            Some(Err(_)) if self.cancelled => OperationStatus::Cancelled,
+            Some(Err(_)) => OperationStatus::Failed,
+        }
+    }
+
+    /// Wait for the job to finish and return its result.
+    pub async fn wait(self) -> Result<T> {
+        self.wait_notified(futures::stream::pending::<()>()).await
+    }
+
+    /// Like [`wait`](Self::wait), but re-poll a polled job whenever
+    /// `events` yields rather than only on the backoff schedule.
+    pub async fn wait_notified<S>(mut self, events: S) -> Result<T>
+    where
+        S: Stream + Send,
+    {
+        if let Some(outcome) = self.outcome.take() {
+            return outcome;
+        }
+        let poll = match &mut self.job {
+            Job::Task(handle) => return join(&self.id, handle).await,
+            Job::Polled { poll, .. } => poll,
+        };
+        futures::pin_mut!(events);
+        let mut events_open = true;
+        let mut attempt = 0;
+        loop {
+            if let Progress::Done(value) = poll().await? {
+                return Ok(value);
+            }
+            attempt += 1;
+            let delay = tokio::time::sleep(self.backoff.backoff(attempt));
+            if events_open {
+                tokio::select! {
+                    _ = delay => {}
+                    event = events.next() => events_open = event.is_some(),
+                }
+            } else {
+                delay.await;
+            }
+        }
+    }
+
+    /// Cancel the job.  Spawned calls are aborted client-side; polled jobs
+    /// need a [`with_cancel`](Self::with_cancel) hook and otherwise fail
+    /// with [`Error::InvalidArgument`].  Finished jobs are left as they are.
+    pub async fn cancel(&mut self) -> Result<()> {
+        if self.outcome.is_some() {
+            return Ok(());
+        }
+        match &self.job {
+            Job::Task(handle) => handle.abort(),
+            Job::Polled {
+                cancel: Some(cancel),
+                ..
+            } => cancel().await?,
+            Job::Polled { cancel: None, .. } => {
+                return Err(Error::InvalidArgument(format!(
+                    "operation {} cannot be cancelled",
+                    self.id
+                )))
+            }
+        }
+        self.cancelled = true;
+        self.outcome = Some(Err(cancelled(&self.id)));
+        Ok(())
+    }
+}
+
+impl<T> std::fmt::Debug for Operation<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Operation")
+            .field("id", &self.id)
+            .field("finished", &self.outcome.is_some())
+            .field("cancelled", &self.cancelled)
+            .finish()
+    }
+}
+
+fn cancelled(id: &str) -> Error {
+    Error::OperationFailed(format!("operation {id} was cancelled"))
+}
+
+async fn join<T>(id: &str, handle: &mut JoinHandle<Result<T>>) -> Result<T> {
+    match handle.await {
+        Ok(result) => result,
+        Err(e) if e.is_cancelled() => Err(cancelled(id)),
+        Err(e) => Err(Error::OperationFailed(format!(
+            "operation {id} panicked: {e}"
+        ))),
+    }
+}
+
+impl ObjectStoreClient {
+    /// Start a replication sync in the background; see
+    /// [`trigger_replication`](Self::trigger_replication) and the
+    /// [`lro`](crate::lro) module.
+    pub fn start_replication(
+        &self,
+        policy_id: Option<String>,
+        parallel: bool,
+        worker_count: i32,
+    ) -> Operation<SyncResult> {
+        let id = format!("replication:{}", policy_id.as_deref().unwrap_or("*"));
+        let client = self.clone();
+        Operation::spawn(id, async move {
+            client
+                .trigger_replication(policy_id, parallel, worker_count)
+                .await
+        })
+    }
+
+    /// Start applying all lifecycle policies in the background; see
+    /// [`apply_policies`](Self::apply_policies).
+    pub fn start_apply_policies(&self) -> Operation<ApplyPoliciesResult> {
+        let client = self.clone();
+        Operation::spawn(
+            "apply_policies",
+            async move { client.apply_policies().await },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn polled(done_after: usize) -> (Operation<&'static str>, Arc<AtomicUsize>) {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = polls.clone();
+        let op = Operation::poll("job-1", move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                Ok(if n >= done_after {
+                    Progress::Done("archived")
+                } else {
+                    Progress::Running
+                })
+            }
+        })
+        .with_poll_interval(Duration::from_millis(1), Duration::from_millis(4));
+        (op, polls)
+    }
+
+    #[tokio::test]
+    async fn polls_until_done() {
+        let (mut op, polls) = polled(4);
+        assert_eq!(op.status().await, OperationStatus::Running);
+        assert_eq!(op.wait().await.unwrap(), "archived");
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn events_trigger_early_polls() {
+        let (op, polls) = polled(3);
+        let op = op.with_poll_interval(Duration::from_secs(60), Duration::from_secs(60));
+        let events = futures::stream::iter([(), ()]);
+        let result = tokio::time::timeout(Duration::from_secs(5), op.wait_notified(events))
+            .await
+            .expect("events should cut the backoff short");
+        assert_eq!(result.unwrap(), "archived");
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn spawned_calls_report_and_cancel() {
+        let mut done = Operation::spawn("quick", async { Ok(7) });
+        tokio::task::yield_now().await;
+        while done.status().await == OperationStatus::Running {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(done.status().await, OperationStatus::Succeeded);
+        assert_eq!(done.wait().await.unwrap(), 7);
+
+        let mut slow = Operation::spawn("slow", async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        });
+        slow.cancel().await.unwrap();
+        assert_eq!(slow.status().await, OperationStatus::Cancelled);
+        assert!(matches!(slow.wait().await, Err(Error::OperationFailed(_))));
+
+        let (mut polled, _) = polled(10);
+        assert!(matches!(
+            polled.cancel().await,
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
@@ -1,7 +1,14 @@
 use crate::auth::{apply_auth, AuthConfig};
+use crate::client::Protocol;
+use crate::config::ClientConfig;
+use crate::downlevel;
 use crate::duration::parse_go_duration_ms;
 use crate::error::{Error, Result};
+use crate::in_flight::InFlight;
 use crate::jsonrpc::{string_map, JsonRpcRequest, JsonRpcResponse};
+use crate::resolver::http_client;
+use crate::response::start_timing;
+use crate::timestamp::parse_timestamp;
 use crate::types::*;
 use base64::Engine as _;
 use bytes::Bytes;
@@ -36,8 +43,9 @@ use std::sync::atomic::{AtomicU64, Ordering};
 pub struct McpClient {
     base_url: String,
     client: Client,
-    auth: AuthConfig,
+    config: ClientConfig,
     next_id: std::sync::Arc<AtomicU64>,
+    in_flight: InFlight,
 }
 
 // ── wire types ─────────────────────────────────────────────────────────────
@@ -68,17 +76,34 @@ impl McpClient {
 
     /// Create a new MCP client with authentication configuration.
     pub fn new_with_auth(base_url: impl Into<String>, auth: AuthConfig) -> Result<Self> {
-        let client = Client::builder()
-            .build()
-            .map_err(|e| Error::Configuration(e.to_string()))?;
+        Self::new_with_config(
+            base_url,
+            ClientConfig {
+                auth,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create a new MCP client from a [`ClientConfig`].
+    pub fn new_with_config(base_url: impl Into<String>, config: ClientConfig) -> Result<Self> {
         Ok(Self {
             base_url: base_url.into(),
-            client,
-            auth,
+            client: http_client(&config)?,
             next_id: std::sync::Arc::new(AtomicU64::new(1)),
+            in_flight: InFlight::new(config.max_in_flight),
+            config,
         })
     }
 
+    /// Return a copy of this client that sends `X-Tenant-ID: <tenant_id>` on
+    /// every request.  The connection pool is shared with the original.
+    pub fn with_tenant(&self, tenant_id: impl Into<String>) -> Self {
+        let mut scoped = self.clone();
+        scoped.config.auth.tenant_id = Some(tenant_id.into());
+        scoped
+    }
+
     // ── low-level tool call ──────────────────────────────────────────────
 
     async fn call_tool(&self, tool_name: &str, arguments: Value) -> Result<Value> {
@@ -95,9 +120,12 @@ impl McpClient {
 
         let url = self.base_url.trim_end_matches('/').to_string() + "/";
         let req = self.client.post(&url).json(&request_body);
-        let req = apply_auth(req, &self.auth);
+        let req = apply_auth(req, &self.config.auth);
+        if self.config.collect_timings {
+            start_timing();
+        }
 
-        let response = req.send().await?;
+        let response = self.in_flight.send(req, &self.config.timeouts).await?;
 
         if !response.status().is_success() {
             return Err(Error::OperationFailed(format!(
@@ -136,6 +164,7 @@ impl McpClient {
         data: Bytes,
         metadata: Option<Metadata>,
     ) -> Result<PutResponse> {
+        let metadata = self.config.put_metadata(&data, metadata);
         let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
         let mut args = serde_json::json!({ "key": key, "data": encoded });
 
@@ -147,9 +176,9 @@ impl McpClient {
             if let Some(ce) = &meta.content_encoding {
                 meta_obj.insert("content_encoding".to_string(), Value::String(ce.clone()));
             }
-            if !meta.custom.is_empty() {
-                let custom: serde_json::Map<String, Value> = meta
-                    .custom
+            let custom = meta.wire_custom();
+            if !custom.is_empty() {
+                let custom: serde_json::Map<String, Value> = custom
                     .iter()
                     .map(|(k, v)| (k.clone(), Value::String(v.clone())))
                     .collect();
@@ -163,6 +192,8 @@ impl McpClient {
             success: true,
             message: None,
             etag: None,
+            consistency_token: None,
+            region: None,
         })
     }
 
@@ -213,6 +244,13 @@ impl McpClient {
         if let Some(prefix) = &list_req.prefix {
             args["prefix"] = Value::String(prefix.clone());
         }
+        if list_req.delimiter.is_some() {
+            downlevel::warn_once(
+                Protocol::Mcp,
+                "delimiter",
+                "objstore_list has no delimiter; objects are listed flat with no common prefixes",
+            );
+        }
         if let Some(max) = list_req.max_results {
             args["max_results"] = Value::from(max);
         }
@@ -282,11 +320,7 @@ impl McpClient {
         let last_modified = result["last_modified"]
             .as_str()
             .filter(|s| !s.is_empty())
-            .and_then(|s| {
-                chrono::DateTime::parse_from_rfc3339(s)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-            });
+            .and_then(parse_timestamp);
 
         Ok(Metadata {
             content_type,
@@ -295,7 +329,9 @@ impl McpClient {
             last_modified,
             etag,
             custom,
-        })
+            ..Default::default()
+        }
+        .lift_web_headers())
     }
 
     /// Update metadata for an object.
@@ -307,11 +343,11 @@ impl McpClient {
         if let Some(ce) = &metadata.content_encoding {
             meta_obj.insert("content_encoding".to_string(), Value::String(ce.clone()));
         }
-        if !metadata.custom.is_empty() {
-            let custom: serde_json::Map<String, Value> = metadata
-                .custom
-                .into_iter()
-                .map(|(k, v)| (k, Value::String(v)))
+        let custom = metadata.wire_custom();
+        if !custom.is_empty() {
+            let custom: serde_json::Map<String, Value> = custom
+                .iter()
+                .map(|(k, v)| (k.clone(), Value::String(v.clone())))
                 .collect();
             meta_obj.insert("custom".to_string(), Value::Object(custom));
         }
@@ -415,14 +451,12 @@ impl McpClient {
             .collect())
     }
 
-    /// Apply all lifecycle policies, returning (policies_count, objects_processed).
-    pub async fn apply_policies(&self) -> Result<(i32, i32)> {
+    /// Apply all lifecycle policies.
+    pub async fn apply_policies(&self) -> Result<ApplyPoliciesResult> {
         let result = self
             .call_tool("objstore_apply_policies", serde_json::json!({}))
             .await?;
-        let count = result["policies_count"].as_i64().unwrap_or(0) as i32;
-        let processed = result["objects_processed"].as_i64().unwrap_or(0) as i32;
-        Ok((count, processed))
+        Ok(serde_json::from_value(result)?)
     }
 
     /// Add a replication policy.
@@ -507,7 +541,7 @@ impl McpClient {
         Ok(SyncResult {
             policy_id: inner["policy_id"]
                 .as_str()
-                .or_else(|| policy_id.as_deref())
+                .or(policy_id.as_deref())
                 .unwrap_or("")
                 .to_string(),
             synced: inner["synced"].as_i64().unwrap_or(0) as i32,
@@ -548,18 +582,31 @@ impl McpClient {
             last_sync_time: result["last_sync_time"]
                 .as_str()
                 .filter(|s| !s.is_empty())
-                .and_then(|s| {
-                    chrono::DateTime::parse_from_rfc3339(s)
-                        .ok()
-                        .map(|dt| dt.with_timezone(&chrono::Utc))
-                }),
+                .and_then(parse_timestamp),
             average_sync_duration_ms: parse_go_duration_ms(
                 result["average_sync_duration"].as_str().unwrap_or(""),
             ),
             sync_count: result["sync_count"].as_i64().unwrap_or(0),
+            recent_errors: serde_json::from_value::<Vec<ReplicationErrorWire>>(
+                result["recent_errors"].clone(),
+            )
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect(),
         })
     }
 
+    /// The server base URL.
+    pub fn endpoint(&self) -> &str {
+        &self.base_url
+    }
+
+    /// TLS settings, or `None` for a plain `http://` URL.
+    pub fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+        crate::tls::TlsInfo::for_url(&self.base_url)
+    }
+
     /// Close the client.  The underlying connection pool is managed by reqwest
     /// and does not need explicit cleanup.
     pub async fn close(&self) -> Result<()> {
@@ -583,11 +630,7 @@ fn mcp_replication_policy_from_value(v: Value) -> ReplicationPolicy {
         last_sync_time: v["last_sync_time"]
             .as_str()
             .filter(|s| !s.is_empty())
-            .and_then(|s| {
-                chrono::DateTime::parse_from_rfc3339(s)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-            }),
+            .and_then(parse_timestamp),
         enabled: v["enabled"].as_bool().unwrap_or(false),
         encryption: None,
         replication_mode: mode,
@@ -664,7 +707,7 @@ mod tests {
     #[test]
     fn mcp_client_new_with_auth() {
         let auth = AuthConfig {
-            token: Some("tok".to_string()),
+            token: Some("tok".into()),
             tenant_id: Some("acme".to_string()),
             ..Default::default()
         };
@@ -720,8 +763,10 @@ mod tests {
         let mut server = Server::new_async().await;
         let _m = mcp_post(&mut server, mcp_ok(serde_json::json!({ "success": true })));
         let c = client(server.url());
-        let mut meta = Metadata::default();
-        meta.content_type = Some("text/plain".to_string());
+        let mut meta = Metadata {
+            content_type: Some("text/plain".to_string()),
+            ..Default::default()
+        };
         meta.custom.insert("owner".to_string(), "alice".to_string());
         c.put("k", Bytes::from_static(b"hi"), Some(meta))
             .await
@@ -960,9 +1005,9 @@ mod tests {
             })),
         );
         let c = client(server.url());
-        let (count, processed) = c.apply_policies().await.unwrap();
-        assert_eq!(count, 3);
-        assert_eq!(processed, 7);
+        let result = c.apply_policies().await.unwrap();
+        assert_eq!(result.policies_evaluated, 3);
+        assert_eq!(result.objects_processed, 7);
     }
 
     // ── replication ops ───────────────────────────────────────────────────────
@@ -1236,7 +1281,7 @@ mod tests {
             .with_body(mcp_ok(serde_json::json!({ "status": "healthy" })))
             .create();
         let auth = AuthConfig {
-            token: Some("mytoken".to_string()),
+            token: Some("mytoken".into()),
             ..Default::default()
         };
         let c = McpClient::new_with_auth(server.url(), auth).unwrap();
@@ -0,0 +1,366 @@
+//! Chunk-level hash trees for partial verification of large objects.
+//!
+//! [`put_with_merkle`] splits an object into fixed-size chunks, hashes
+//! them into a Merkle tree, and records the tree's root and chunk size in
+//! the object's custom metadata; the leaf hashes are stored in a
+//! `<key>.merkle` sidecar object.  [`verify`] then checks the sidecar
+//! against the root and re-hashes only the chunks asked for — a byte range
+//! or a random sample — instead of the whole object:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::merkle::{put_with_merkle, verify, Selection};
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let data = Bytes::from(std::fs::read("archive.tar")?);
+//! put_with_merkle(&client, "archives/2024.tar", data, 1 << 20).await?;
+//!
+//! let report = verify(&client, "archives/2024.tar", Selection::Sample(16)).await?;
+//! assert!(report.is_intact(), "corrupt chunks: {:?}", report.corrupted);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! go-objstore servers do not serve byte ranges, so [`verify`] streams the
+//! object with `get_stream` and stops after the last selected chunk:
+//! checking early chunks is cheap, while a chunk near the end still costs
+//! reading (but not hashing) everything before it.
+
+use crate::client::ObjectStore;
+use crate::error::{Error, Result};
+use crate::manifest::{from_hex, to_hex};
+use crate::types::{Metadata, PutResponse};
+use bytes::Bytes;
+use futures::StreamExt;
+use ring::digest::{self, SHA256, SHA256_OUTPUT_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Custom metadata key holding the hex Merkle root.
+pub const MERKLE_ROOT_METADATA: &str = "merkle-root";
+
+/// Custom metadata key holding the chunk size in bytes.
+pub const MERKLE_CHUNK_SIZE_METADATA: &str = "merkle-chunk-size";
+
+type Hash = [u8; SHA256_OUTPUT_LEN];
+
+/// Leaf hashes of an object and the tree they form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree {
+    chunk_size: u64,
+    leaves: Vec<Hash>,
+}
+
+impl MerkleTree {
+    /// Hash `data` in chunks of `chunk_size` bytes.
+    pub fn build(data: &[u8], chunk_size: u64) -> Result<Self> {
+        if chunk_size == 0 {
+            return Err(Error::InvalidArgument(
+                "Merkle chunk size must be positive".into(),
+            ));
+        }
+        let leaves = data.chunks(chunk_size as usize).map(leaf_hash).collect();
+        Ok(Self { chunk_size, leaves })
+    }
+
+    /// Chunk size in bytes.
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Number of chunks.
+    pub fn chunks(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Hex-encoded root hash.
+    pub fn root(&self) -> String {
+        let mut level = self.leaves.clone();
+        if level.is_empty() {
+            return to_hex(&leaf_hash(&[]));
+        }
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [odd] => *odd,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        to_hex(&level[0])
+    }
+}
+
+/// Which chunks [`verify`] re-hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// Every chunk.
+    All,
+    /// The chunks overlapping this byte range.
+    Range(Range<u64>),
+    /// Up to this many distinct chunks chosen at random.
+    Sample(usize),
+}
+
+/// Outcome of [`verify`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MerkleReport {
+    /// Indices of the chunks that were re-hashed, ascending.
+    pub checked: Vec<usize>,
+    /// Indices of checked chunks whose hash did not match (or that were
+    /// missing because the object is shorter than recorded).
+    pub corrupted: Vec<usize>,
+}
+
+impl MerkleReport {
+    /// Return true when every checked chunk matched.
+    pub fn is_intact(&self) -> bool {
+        self.corrupted.is_empty()
+    }
+}
+
+/// Put `data` at `key` with a Merkle tree over `chunk_size`-byte chunks,
+/// writing the leaf sidecar first and the root into the object's custom
+/// metadata.
+pub async fn put_with_merkle(
+    store: &dyn ObjectStore,
+    key: &str,
+    data: Bytes,
+    chunk_size: u64,
+) -> Result<PutResponse> {
+    let tree = MerkleTree::build(&data, chunk_size)?;
+    let leaves: Vec<u8> = tree.leaves.iter().flatten().copied().collect();
+    store
+        .put(&sidecar_key(key), Bytes::from(leaves), None)
+        .await?;
+
+    let mut metadata = Metadata::default();
+    metadata
+        .custom
+        .insert(MERKLE_ROOT_METADATA.to_string(), tree.root());
+    metadata.custom.insert(
+        MERKLE_CHUNK_SIZE_METADATA.to_string(),
+        chunk_size.to_string(),
+    );
+    store.put(key, data, Some(metadata)).await
+}
+
+/// Re-hash the `selection` of `key`'s chunks against its Merkle tree.
+///
+/// Fails with [`Error::InvalidArgument`] when the object carries no tree,
+/// and with [`Error::InvalidResponse`] when the sidecar does not match the
+/// recorded root.
+pub async fn verify(
+    store: &dyn ObjectStore,
+    key: &str,
+    selection: Selection,
+) -> Result<MerkleReport> {
+    let tree = load_tree(store, key).await?;
+    let wanted = select(&tree, selection)?;
+    let Some(&last) = wanted.iter().next_back() else {
+        return Ok(MerkleReport::default());
+    };
+
+    let mut report = MerkleReport {
+        checked: wanted.iter().copied().collect(),
+        ..Default::default()
+    };
+    let chunk_size = tree.chunk_size as usize;
+    let (mut stream, _) = store.get_stream(key).await?;
+    let (mut index, mut filled) = (0usize, 0usize);
+    // Bytes of the current chunk, kept only when it is selected.
+    let mut chunk: Vec<u8> = Vec::new();
+
+    'read: while let Some(bytes) = stream.next().await {
+        let mut bytes = &bytes?[..];
+        while !bytes.is_empty() {
+            let take = (chunk_size - filled).min(bytes.len());
+            if wanted.contains(&index) {
+                chunk.extend_from_slice(&bytes[..take]);
+            }
+            filled += take;
+            bytes = &bytes[take..];
+            if filled == chunk_size {
+                if wanted.contains(&index) && leaf_hash(&chunk) != tree.leaves[index] {
+                    report.corrupted.push(index);
+                }
+                chunk.clear();
+                filled = 0;
+                index += 1;
+                if index > last {
+                    break 'read;
+                }
+            }
+        }
+    }
+    if filled > 0 {
+        // Final short chunk.
+        if wanted.contains(&index) && leaf_hash(&chunk) != tree.leaves[index] {
+            report.corrupted.push(index);
+        }
+        index += 1;
+    }
+    // Selected chunks the object ended before.
+    report.corrupted.extend(wanted.range(index..));
+    Ok(report)
+}
+
+async fn load_tree(store: &dyn ObjectStore, key: &str) -> Result<MerkleTree> {
+    let metadata = store.get_metadata(key).await?;
+    let field = |name: &str| {
+        metadata.custom.get(name).ok_or_else(|| {
+            Error::InvalidArgument(format!("{key} has no Merkle tree ({name} metadata)"))
+        })
+    };
+    let root = field(MERKLE_ROOT_METADATA)?.clone();
+    let chunk_size: u64 = field(MERKLE_CHUNK_SIZE_METADATA)?
+        .parse()
+        .ok()
+        .filter(|&s| s > 0)
+        .ok_or_else(|| Error::InvalidResponse(format!("{key} has an invalid Merkle chunk size")))?;
+
+    let (sidecar, _) = store.get(&sidecar_key(key)).await?;
+    if sidecar.len() % SHA256_OUTPUT_LEN != 0 {
+        return Err(Error::InvalidResponse(format!(
+            "Merkle sidecar for {key} is truncated"
+        )));
+    }
+    let tree = MerkleTree {
+        chunk_size,
+        leaves: sidecar
+            .chunks(SHA256_OUTPUT_LEN)
+            .map(|h| h.try_into().unwrap())
+            .collect(),
+    };
+    if from_hex(&root).as_deref() != from_hex(&tree.root()).as_deref() {
+        return Err(Error::InvalidResponse(format!(
+            "Merkle sidecar for {key} does not match the recorded root"
+        )));
+    }
+    Ok(tree)
+}
+
+fn select(tree: &MerkleTree, selection: Selection) -> Result<BTreeSet<usize>> {
+    let count = tree.chunks();
+    Ok(match selection {
+        Selection::All => (0..count).collect(),
+        Selection::Range(range) if range.start >= range.end => BTreeSet::new(),
+        Selection::Range(range) => {
+            let first = (range.start / tree.chunk_size) as usize;
+            let last = ((range.end - 1) / tree.chunk_size) as usize;
+            (first..=last.min(count.saturating_sub(1)))
+                .filter(|&i| i < count)
+                .collect()
+        }
+        Selection::Sample(n) if n >= count => (0..count).collect(),
+        Selection::Sample(n) => {
+            let rng = SystemRandom::new();
+            let mut picked = BTreeSet::new();
+            while picked.len() < n {
+                let mut buf = [0u8; 8];
+                rng.fill(&mut buf)
+                    .map_err(|_| Error::Generic("system random source failed".into()))?;
+                picked.insert((u64::from_le_bytes(buf) % count as u64) as usize);
+            }
+            picked
+        }
+    })
+}
+
+fn sidecar_key(key: &str) -> String {
+    format!("{key}.merkle")
+}
+
+fn leaf_hash(chunk: &[u8]) -> Hash {
+    hash(&[&[0u8], chunk])
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    hash(&[&[1u8], left, right])
+}
+
+fn hash(parts: &[&[u8]]) -> Hash {
+    let mut ctx = digest::Context::new(&SHA256);
+    for part in parts {
+        ctx.update(part);
+    }
+    ctx.finish().as_ref().try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+
+    #[test]
+    fn root_depends_on_every_chunk() {
+        let data = vec![7u8; 1000];
+        let tree = MerkleTree::build(&data, 100).unwrap();
+        assert_eq!(tree.chunks(), 10);
+        let mut changed = data.clone();
+        changed[999] = 8;
+        assert_ne!(
+            tree.root(),
+            MerkleTree::build(&changed, 100).unwrap().root()
+        );
+        assert!(MerkleTree::build(&data, 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn verifies_selected_chunks_only() {
+        let store = MemoryStore::default();
+        let data: Vec<u8> = (0..1050u32).map(|i| i as u8).collect();
+        put_with_merkle(&store, "big", Bytes::from(data), 100)
+            .await
+            .unwrap();
+        assert!(verify(&store, "big", Selection::All)
+            .await
+            .unwrap()
+            .is_intact());
+
+        store.tamper("big", |data| data[512] ^= 0xff);
+        let report = verify(&store, "big", Selection::Range(0..300))
+            .await
+            .unwrap();
+        assert_eq!(report.checked, [0, 1, 2]);
+        assert!(report.is_intact());
+
+        let report = verify(&store, "big", Selection::Range(450..1050))
+            .await
+            .unwrap();
+        assert_eq!(report.checked, (4..=10).collect::<Vec<_>>());
+        assert_eq!(report.corrupted, [5]);
+
+        let report = verify(&store, "big", Selection::Sample(3)).await.unwrap();
+        assert_eq!(report.checked.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn detects_tampered_sidecar_and_truncation() {
+        let store = MemoryStore::default();
+        put_with_merkle(&store, "obj", Bytes::from(vec![1u8; 250]), 100)
+            .await
+            .unwrap();
+
+        // Truncate the object: the final chunks are reported corrupt.
+        store.tamper("obj", |data| data.truncate(100));
+        let report = verify(&store, "obj", Selection::All).await.unwrap();
+        assert_eq!(report.corrupted, [1, 2]);
+
+        store.tamper("obj.merkle", |data| data[0] ^= 0xff);
+        assert!(matches!(
+            verify(&store, "obj", Selection::All).await,
+            Err(Error::InvalidResponse(_))
+        ));
+        store.put("plain", Bytes::new(), None).await.unwrap();
+        assert!(matches!(
+            verify(&store, "plain", Selection::All).await,
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,795 @@
+//! Client-side metrics with push export for short-lived jobs.
+//!
+//! [`Metered`] wraps any [`ObjectStore`] and records, per operation, the
+//! number of calls and errors, a latency histogram, and bytes sent and
+//! received into a shared [`Metrics`] registry.  Batch jobs often exit
+//! before a Prometheus scrape, so [`PushExporter`] pushes the registry to a
+//! Prometheus Pushgateway or an OTLP/HTTP collector on an interval, and once
+//! more on shutdown:
+//!
+//! ```no_run
+//! use go_objstore::metrics::{Metrics, PushExporter};
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let metrics = Metrics::new();
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?.with_metrics(metrics.clone());
+//! let exporter = PushExporter::pushgateway(metrics, "http://pushgateway:9091", "nightly-backup")?
+//!     .spawn();
+//!
+//! client.health().await?;
+//! // ... the job's work ...
+//!
+//! exporter.shutdown().await?; // final push before exiting
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Exported series (all labelled with `operation`, and with `protocol` —
+//! `rest`, `grpc`, ... — when recorded through
+//! [`ObjectStoreClient::with_metrics`] or [`Metered::with_protocol`]):
+//! `objstore_client_requests_total`, `objstore_client_errors_total`,
+//! `objstore_client_request_duration_seconds` (histogram),
+//! `objstore_client_sent_bytes_total`, and
+//! `objstore_client_received_bytes_total`.  A [`RetryBudget`] registered
+//! with [`Metrics::track_retry_budget`] adds the gauges
+//! `objstore_client_retry_budget_requests`, `_retries`, and `_available`
+//! (over the budget's window) and the counter
+//! `objstore_client_retry_budget_exhausted_total`.
+//!
+//! [`PushExporter::otlp`] pushes the same series to an OpenTelemetry
+//! collector as OTLP/HTTP JSON.
+
+use crate::client::{ByteStream, ObjectStore, ObjectStoreClient, Protocol};
+use crate::error::{Error, Result};
+use crate::retry::{RetryBudget, RetryBudgetState};
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Upper bounds (seconds) of the latency histogram buckets.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Counters for one operation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OperationStats {
+    pub requests: u64,
+    pub errors: u64,
+    /// Observations per [`LATENCY_BUCKETS`] bucket (not cumulative); the
+    /// last entry counts observations above the largest bound.
+    pub latency_buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    pub latency_sum: Duration,
+    pub sent_bytes: u64,
+    pub received_bytes: u64,
+}
+
+impl OperationStats {
+    fn merge(&mut self, other: &OperationStats) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        for (total, count) in self.latency_buckets.iter_mut().zip(other.latency_buckets) {
+            *total += count;
+        }
+        self.latency_sum += other.latency_sum;
+        self.sent_bytes += other.sent_bytes;
+        self.received_bytes += other.received_bytes;
+    }
+}
+
+/// An operation, and the protocol it was recorded for if known.
+pub type Series = (&'static str, Option<Protocol>);
+
+/// Reads one counter out of [`OperationStats`].
+type Counter = fn(&OperationStats) -> u64;
+
+/// Shared registry of per-operation metrics.  Cloning shares the registry.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    operations: Arc<Mutex<BTreeMap<Series, OperationStats>>>,
+    retry_budget: Arc<Mutex<Option<RetryBudget>>>,
+    started: SystemTime,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            operations: Arc::default(),
+            retry_budget: Arc::default(),
+            started: SystemTime::now(),
+        }
+    }
+}
+
+impl Metrics {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A copy of the current counters, by operation name, summed over
+    /// protocols.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, OperationStats> {
+        let mut totals = BTreeMap::<&'static str, OperationStats>::new();
+        for ((operation, _), stats) in self.snapshot_by_protocol() {
+            totals.entry(operation).or_default().merge(&stats);
+        }
+        totals
+    }
+
+    /// A copy of the current counters, by operation and protocol.
+    pub fn snapshot_by_protocol(&self) -> BTreeMap<Series, OperationStats> {
+        self.operations
+            .lock()
+            .map(|o| o.clone())
+            .unwrap_or_default()
+    }
+
+    /// Export `budget`'s state with the other metrics, replacing any budget
+    /// tracked before.
+    pub fn track_retry_budget(&self, budget: &RetryBudget) {
+        if let Ok(mut tracked) = self.retry_budget.lock() {
+            *tracked = Some(budget.clone());
+        }
+    }
+
+    /// The tracked retry budget's current state.
+    pub fn retry_budget_state(&self) -> Option<RetryBudgetState> {
+        self.retry_budget
+            .lock()
+            .ok()?
+            .as_ref()
+            .map(RetryBudget::state)
+    }
+
+    fn update(&self, series: Series, f: impl FnOnce(&mut OperationStats)) {
+        if let Ok(mut operations) = self.operations.lock() {
+            f(operations.entry(series).or_default());
+        }
+    }
+
+    fn observe(&self, series: Series, elapsed: Duration, failed: bool) {
+        self.update(series, |stats| {
+            stats.requests += 1;
+            stats.errors += u64::from(failed);
+            let bucket = LATENCY_BUCKETS
+                .iter()
+                .position(|&bound| elapsed.as_secs_f64() <= bound)
+                .unwrap_or(LATENCY_BUCKETS.len());
+            stats.latency_buckets[bucket] += 1;
+            stats.latency_sum += elapsed;
+        });
+    }
+
+    /// Render in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let snapshot = self.snapshot_by_protocol();
+        let mut out = String::new();
+        let counters: [(&str, &str, Counter); 4] = [
+            ("requests_total", "Object store calls.", |s| s.requests),
+            ("errors_total", "Object store calls that failed.", |s| {
+                s.errors
+            }),
+            ("sent_bytes_total", "Object bytes uploaded.", |s| {
+                s.sent_bytes
+            }),
+            ("received_bytes_total", "Object bytes downloaded.", |s| {
+                s.received_bytes
+            }),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP objstore_client_{name} {help}");
+            let _ = writeln!(out, "# TYPE objstore_client_{name} counter");
+            for (series, stats) in &snapshot {
+                let labels = prometheus_labels(series);
+                let _ = writeln!(out, "objstore_client_{name}{{{labels}}} {}", value(stats));
+            }
+        }
+
+        let name = "objstore_client_request_duration_seconds";
+        let _ = writeln!(out, "# HELP {name} Object store call latency.");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        for (series, stats) in &snapshot {
+            let labels = prometheus_labels(series);
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(&stats.latency_buckets) {
+                cumulative += count;
+                let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}");
+            }
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{labels},le=\"+Inf\"}} {}",
+                stats.requests
+            );
+            let _ = writeln!(
+                out,
+                "{name}_sum{{{labels}}} {}",
+                stats.latency_sum.as_secs_f64()
+            );
+            let _ = writeln!(out, "{name}_count{{{labels}}} {}", stats.requests);
+        }
+
+        if let Some(budget) = self.retry_budget_state() {
+            let gauges = [
+                (
+                    "requests",
+                    "Calls counted by the retry budget's window.",
+                    budget.requests,
+                ),
+                (
+                    "retries",
+                    "Retries made in the retry budget's window.",
+                    budget.retries,
+                ),
+                (
+                    "available",
+                    "Retries the retry budget still allows.",
+                    budget.available,
+                ),
+            ];
+            for (name, help, value) in gauges {
+                let _ = writeln!(out, "# HELP objstore_client_retry_budget_{name} {help}");
+                let _ = writeln!(out, "# TYPE objstore_client_retry_budget_{name} gauge");
+                let _ = writeln!(out, "objstore_client_retry_budget_{name} {value}");
+            }
+            let name = "objstore_client_retry_budget_exhausted_total";
+            let _ = writeln!(out, "# HELP {name} Retries refused by the retry budget.");
+            let _ = writeln!(out, "# TYPE {name} counter");
+            let _ = writeln!(out, "{name} {}", budget.exhausted);
+        }
+        out
+    }
+
+    /// Render as an OTLP/HTTP JSON `ExportMetricsServiceRequest` with
+    /// cumulative temporality.
+    pub fn render_otlp_json(&self, service_name: &str) -> Value {
+        let start = nanos(self.started);
+        let now = nanos(SystemTime::now());
+        let snapshot = self.snapshot_by_protocol();
+        let point = |series: &Series, value: u64| {
+            json!({
+                "attributes": otlp_attributes(series),
+                "startTimeUnixNano": start,
+                "timeUnixNano": now,
+                "asInt": value.to_string(),
+            })
+        };
+        let sum = |name: &str, unit: &str, value: Counter| {
+            json!({
+                "name": format!("objstore.client.{name}"),
+                "unit": unit,
+                "sum": {
+                    "aggregationTemporality": 2,
+                    "isMonotonic": true,
+                    "dataPoints": snapshot.iter().map(|(series, s)| point(series, value(s))).collect::<Vec<_>>(),
+                },
+            })
+        };
+        let histogram_points: Vec<Value> = snapshot
+            .iter()
+            .map(|(series, s)| {
+                json!({
+                    "attributes": otlp_attributes(series),
+                    "startTimeUnixNano": start,
+                    "timeUnixNano": now,
+                    "count": s.requests.to_string(),
+                    "sum": s.latency_sum.as_secs_f64(),
+                    "bucketCounts": s.latency_buckets.iter().map(u64::to_string).collect::<Vec<_>>(),
+                    "explicitBounds": LATENCY_BUCKETS,
+                })
+            })
+            .collect();
+
+        let mut metrics = vec![
+            sum("requests", "{request}", |s| s.requests),
+            sum("errors", "{request}", |s| s.errors),
+            sum("sent_bytes", "By", |s| s.sent_bytes),
+            sum("received_bytes", "By", |s| s.received_bytes),
+            json!({
+                "name": "objstore.client.request.duration",
+                "unit": "s",
+                "histogram": {"aggregationTemporality": 2, "dataPoints": histogram_points},
+            }),
+        ];
+        if let Some(budget) = self.retry_budget_state() {
+            let point = |value: u64| json!({"timeUnixNano": now, "asInt": value.to_string()});
+            for (name, value) in [
+                ("requests", budget.requests),
+                ("retries", budget.retries),
+                ("available", budget.available),
+            ] {
+                metrics.push(json!({
+                    "name": format!("objstore.client.retry_budget.{name}"),
+                    "unit": "{request}",
+                    "gauge": {"dataPoints": [point(value)]},
+                }));
+            }
+            metrics.push(json!({
+                "name": "objstore.client.retry_budget.exhausted",
+                "unit": "{request}",
+                "sum": {
+                    "aggregationTemporality": 2,
+                    "isMonotonic": true,
+                    "dataPoints": [{
+                        "startTimeUnixNano": start,
+                        "timeUnixNano": now,
+                        "asInt": budget.exhausted.to_string(),
+                    }],
+                },
+            }));
+        }
+
+        json!({
+            "resourceMetrics": [{
+                "resource": {"attributes": [
+                    {"key": "service.name", "value": {"stringValue": service_name}}
+                ]},
+                "scopeMetrics": [{
+                    "scope": {"name": "go-objstore", "version": env!("CARGO_PKG_VERSION")},
+                    "metrics": metrics,
+                }],
+            }],
+        })
+    }
+}
+
+fn prometheus_labels((operation, protocol): &Series) -> String {
+    match protocol {
+        Some(protocol) => format!(
+            "operation=\"{operation}\",protocol=\"{}\"",
+            protocol.as_str()
+        ),
+        None => format!("operation=\"{operation}\""),
+    }
+}
+
+fn otlp_attributes((operation, protocol): &Series) -> Value {
+    let mut attributes = vec![json!({"key": "operation", "value": {"stringValue": operation}})];
+    if let Some(protocol) = protocol {
+        attributes.push(json!({"key": "protocol", "value": {"stringValue": protocol.as_str()}}));
+    }
+    Value::Array(attributes)
+}
+
+fn nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// An [`ObjectStore`] that records [`Metrics`] for every call.
+#[derive(Debug, Clone)]
+pub struct Metered<S> {
+    inner: S,
+    metrics: Metrics,
+    protocol: Option<Protocol>,
+}
+
+impl<S: ObjectStore> Metered<S> {
+    /// Wrap `inner`, recording into `metrics`.
+    pub fn new(inner: S, metrics: Metrics) -> Self {
+        Self {
+            inner,
+            metrics,
+            protocol: None,
+        }
+    }
+
+    /// Label the recorded series with `protocol`.
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The registry being recorded into.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    async fn timed<T>(
+        &self,
+        operation: &'static str,
+        call: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let start = Instant::now();
+        let result = call.await;
+        self.metrics
+            .observe((operation, self.protocol), start.elapsed(), result.is_err());
+        result
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for Metered<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        let len = data.len() as u64;
+        let result = self.timed("put", self.inner.put(key, data, metadata)).await;
+        if result.is_ok() {
+            self.metrics
+                .update(("put", self.protocol), |s| s.sent_bytes += len);
+        }
+        result
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        let result = self.timed("get", self.inner.get(key)).await;
+        if let Ok((data, _)) = &result {
+            let len = data.len() as u64;
+            self.metrics
+                .update(("get", self.protocol), |s| s.received_bytes += len);
+        }
+        result
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.timed("delete", self.inner.delete(key)).await
+    }
+
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.timed("list", self.inner.list(list_req)).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.timed("exists", self.inner.exists(key)).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.timed("get_metadata", self.inner.get_metadata(key))
+            .await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.timed("update_metadata", self.inner.update_metadata(key, metadata))
+            .await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.timed("health", self.inner.health()).await
+    }
+
+    /// Latency covers opening the stream; bytes are counted as they are
+    /// read.
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        let (stream, metadata) = self.timed("get_stream", self.inner.get_stream(key)).await?;
+        let metrics = self.metrics.clone();
+        let series = ("get_stream", self.protocol);
+        let stream = stream
+            .inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    let len = chunk.len() as u64;
+                    metrics.update(series, |s| s.received_bytes += len);
+                }
+            })
+            .boxed();
+        Ok((stream, metadata))
+    }
+
+    /// Bytes are counted as the transport takes them, and recorded once
+    /// the upload succeeds.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        let sent = Arc::new(AtomicU64::new(0));
+        let counter = sent.clone();
+        let stream = stream
+            .inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    counter.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                }
+            })
+            .boxed();
+        let result = self
+            .timed("put_stream", self.inner.put_stream(key, stream, metadata))
+            .await;
+        if result.is_ok() {
+            let len = sent.load(Ordering::Relaxed);
+            self.metrics
+                .update(("put_stream", self.protocol), |s| s.sent_bytes += len);
+        }
+        result
+    }
+}
+
+impl ObjectStoreClient {
+    /// Wrap this client in a layer recording [`Metrics`], labelled with
+    /// the client's protocol.
+    pub fn with_metrics(self, metrics: Metrics) -> Metered<Self> {
+        let protocol = self.protocol();
+        Metered::new(self, metrics).with_protocol(protocol)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    Pushgateway(String),
+    Otlp { url: String, service_name: String },
+}
+
+/// Pushes a [`Metrics`] registry to a Pushgateway or OTLP collector.
+#[derive(Debug, Clone)]
+pub struct PushExporter {
+    metrics: Metrics,
+    target: Target,
+    client: reqwest::Client,
+    interval: Duration,
+}
+
+impl PushExporter {
+    /// Push to the Prometheus Pushgateway at `base_url` under `job`.  Each
+    /// push replaces the job's previous metrics.
+    pub fn pushgateway(metrics: Metrics, base_url: &str, job: &str) -> Result<Self> {
+        if job.is_empty() {
+            return Err(Error::InvalidArgument(
+                "Pushgateway job must not be empty".into(),
+            ));
+        }
+        let url = format!(
+            "{}/metrics/job/{}",
+            base_url.trim_end_matches('/'),
+            urlencoding::encode(job)
+        );
+        Ok(Self::with_target(metrics, Target::Pushgateway(url)))
+    }
+
+    /// Push to an OTLP/HTTP metrics endpoint, e.g.
+    /// `http://collector:4318/v1/metrics`, as JSON.
+    pub fn otlp(metrics: Metrics, url: &str, service_name: &str) -> Self {
+        Self::with_target(
+            metrics,
+            Target::Otlp {
+                url: url.to_string(),
+                service_name: service_name.to_string(),
+            },
+        )
+    }
+
+    fn with_target(metrics: Metrics, target: Target) -> Self {
+        Self {
+            metrics,
+            target,
+            client: reqwest::Client::new(),
+            interval: Duration::from_secs(15),
+        }
+    }
+
+    /// Set the push interval used by [`spawn`](Self::spawn).  Defaults to
+    /// 15 seconds.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Push the current metrics once.
+    pub async fn push(&self) -> Result<()> {
+        let request = match &self.target {
+            Target::Pushgateway(url) => self
+                .client
+                .put(url)
+                .header("content-type", "text/plain; version=0.0.4")
+                .body(self.metrics.render_prometheus()),
+            Target::Otlp { url, service_name } => self
+                .client
+                .post(url)
+                .json(&self.metrics.render_otlp_json(service_name)),
+        };
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(Error::OperationFailed(format!(
+                "metrics push failed: HTTP {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Push every interval on a background task until
+    /// [`ExporterHandle::shutdown`], which pushes a final time.
+    pub fn spawn(self) -> ExporterHandle {
+        let cancel = CancellationToken::new();
+        let stop = cancel.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = stop.cancelled() => return self.push().await,
+                    _ = tokio::time::sleep(self.interval) => {
+                        if let Err(error) = self.push().await {
+                            tracing::warn!(%error, "periodic metrics push failed");
+                        }
+                    }
+                }
+            }
+        });
+        ExporterHandle { cancel, task }
+    }
+}
+
+/// A running [`PushExporter`].
+#[derive(Debug)]
+pub struct ExporterHandle {
+    cancel: CancellationToken,
+    task: JoinHandle<Result<()>>,
+}
+
+impl ExporterHandle {
+    /// Stop pushing periodically and push the final metrics.
+    pub async fn shutdown(self) -> Result<()> {
+        self.cancel.cancel();
+        self.task
+            .await
+            .map_err(|e| Error::Generic(format!("metrics exporter task failed: {e}")))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    async fn metered(server: &mockito::ServerGuard) -> (Metered<ObjectStoreClient>, Metrics) {
+        let metrics = Metrics::new();
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+        (client.with_metrics(metrics.clone()), metrics)
+    }
+
+    #[tokio::test]
+    async fn records_calls_errors_and_bytes() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("PUT", "/objects/k")
+            .with_status(201)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/k")
+            .with_status(200)
+            .with_body("hello")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/missing")
+            .with_status(404)
+            .create_async()
+            .await;
+        let (client, metrics) = metered(&server).await;
+
+        client.put("k", Bytes::from("abc"), None).await.unwrap();
+        client.get("k").await.unwrap();
+        assert!(client.get("missing").await.is_err());
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot["put"].requests, 1);
+        assert_eq!(snapshot["put"].sent_bytes, 3);
+        assert_eq!(snapshot["get"].requests, 2);
+        assert_eq!(snapshot["get"].errors, 1);
+        assert_eq!(snapshot["get"].received_bytes, 5);
+        let by_protocol = metrics.snapshot_by_protocol();
+        assert_eq!(by_protocol[&("get", Some(Protocol::Rest))].requests, 2);
+
+        let text = metrics.render_prometheus();
+        assert!(
+            text.contains("objstore_client_errors_total{operation=\"get\",protocol=\"rest\"} 1")
+        );
+        assert!(text.contains(
+            "objstore_client_request_duration_seconds_bucket{operation=\"get\",protocol=\"rest\",le=\"+Inf\"} 2"
+        ));
+
+        let otlp = metrics.render_otlp_json("job");
+        let names: Vec<&str> = otlp["resourceMetrics"][0]["scopeMetrics"][0]["metrics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"objstore.client.request.duration"));
+    }
+
+    #[tokio::test]
+    async fn exports_retry_budget_state() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects/k")
+            .with_status(503)
+            .create_async()
+            .await;
+        let metrics = Metrics::new();
+        let budget = crate::RetryBudget::default().with_min_retries(1);
+        metrics.track_retry_budget(&budget);
+        let policy = crate::RetryPolicy::default()
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(1))
+            .with_budget(budget);
+        let client =
+            ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap()).with_retry(policy);
+
+        assert!(client.get("k").await.is_err());
+        let state = metrics.retry_budget_state().unwrap();
+        assert_eq!((state.requests, state.retries, state.exhausted), (1, 1, 1));
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains("objstore_client_retry_budget_retries 1\n"));
+        assert!(text.contains("objstore_client_retry_budget_available 0\n"));
+        assert!(text.contains("objstore_client_retry_budget_exhausted_total 1\n"));
+        let otlp = metrics.render_otlp_json("job");
+        let exhausted = otlp["resourceMetrics"][0]["scopeMetrics"][0]["metrics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["name"] == "objstore.client.retry_budget.exhausted")
+            .unwrap();
+        assert_eq!(exhausted["sum"]["dataPoints"][0]["asInt"], "1");
+    }
+
+    #[tokio::test]
+    async fn shutdown_pushes_final_metrics() {
+        let mut server = mockito::Server::new_async().await;
+        let push = server
+            .mock("PUT", "/metrics/job/nightly%20backup")
+            .match_body(Matcher::Regex(
+                "objstore_client_requests_total\\{operation=\"health\",protocol=\"rest\"\\} 1"
+                    .into(),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/health")
+            .with_status(200)
+            .with_body(r#"{"status":"healthy"}"#)
+            .create_async()
+            .await;
+        let (client, metrics) = metered(&server).await;
+
+        let exporter = PushExporter::pushgateway(metrics, &server.url(), "nightly backup")
+            .unwrap()
+            .with_interval(Duration::from_secs(3600))
+            .spawn();
+        client.health().await.unwrap();
+        exporter.shutdown().await.unwrap();
+        push.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn otlp_push_posts_json() {
+        let mut server = mockito::Server::new_async().await;
+        let push = server
+            .mock("POST", "/v1/metrics")
+            .match_header("content-type", "application/json")
+            .match_body(Matcher::PartialJson(json!({
+                "resourceMetrics": [{"resource": {"attributes": [
+                    {"key": "service.name", "value": {"stringValue": "etl"}}
+                ]}}]
+            })))
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let exporter = PushExporter::otlp(
+            Metrics::new(),
+            &format!("{}/v1/metrics", server.url()),
+            "etl",
+        );
+        exporter.push().await.unwrap();
+        push.assert_async().await;
+    }
+}
@@ -0,0 +1,160 @@
+//! Uploads from memory-mapped files.
+//!
+//! [`put_mmap`] maps a local file and hands the mapping to `put` as a
+//! [`Bytes`] value backed by the page cache instead of a heap copy, so
+//! uploading many large files at once does not need the files' size in
+//! memory:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! client.put_mmap("datasets/big.parquet", "big.parquet", None).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! REST and QUIC send the mapped pages as they are; gRPC copies them into
+//! its request message.  Files that cannot be mapped (pipes, some network
+//! and FUSE file systems) fall back to buffered reads: REST and QUIC stream
+//! the file with `put_stream`, other transports read it into memory.
+//!
+//! The file must not be truncated or rewritten while the upload runs;
+//! doing so can make the upload send mixed contents or fail with `SIGBUS`.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::{Metadata, PutResponse};
+use bytes::Bytes;
+use futures::StreamExt;
+use std::path::Path;
+use tokio::fs::File;
+use tokio_util::io::ReaderStream;
+
+/// Read size for the buffered fallback.
+const READ_CHUNK: usize = 256 * 1024;
+
+/// Map `file` read-only, or `None` if it cannot be mapped.
+fn map(file: &std::fs::File) -> Option<Bytes> {
+    // SAFETY: the mapping is read-only and shared with the page cache.  A
+    // concurrent truncation of the file is the caller's responsibility
+    // (see the module docs); it cannot cause writes through the mapping.
+    let mmap = unsafe { memmap2::Mmap::map(file) }.ok()?;
+    Some(Bytes::from_owner(mmap))
+}
+
+/// Open `path` and map it, returning the file for the fallback when
+/// mapping fails.
+async fn open(path: &Path) -> Result<std::result::Result<Bytes, File>> {
+    let file = File::open(path).await?.into_std().await;
+    let (file, mapped) = tokio::task::spawn_blocking(move || {
+        let mapped = map(&file);
+        (file, mapped)
+    })
+    .await
+    .map_err(|e| Error::Io(e.into()))?;
+    Ok(mapped.ok_or_else(|| File::from_std(file)))
+}
+
+/// Upload the file at `path` under `key` from a memory mapping.
+pub async fn put_mmap(
+    store: &dyn ObjectStore,
+    key: &str,
+    path: impl AsRef<Path>,
+    metadata: Option<Metadata>,
+) -> Result<PutResponse> {
+    let data = match open(path.as_ref()).await? {
+        Ok(mapped) => mapped,
+        Err(file) => read_all(file).await?,
+    };
+    store.put(key, data, metadata).await
+}
+
+async fn read_all(mut file: File) -> Result<Bytes> {
+    use tokio::io::AsyncReadExt;
+
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).await?;
+    Ok(data.into())
+}
+
+fn chunks(file: File) -> impl futures::Stream<Item = Result<Bytes>> + Send + 'static {
+    ReaderStream::with_capacity(file, READ_CHUNK).map(|chunk| Ok(chunk?))
+}
+
+impl ObjectStoreClient {
+    /// Upload the file at `path` under `key` from a memory mapping.  See
+    /// the [`mmap`](crate::mmap) module.
+    pub async fn put_mmap(
+        &self,
+        key: &str,
+        path: impl AsRef<Path>,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        match open(path.as_ref()).await? {
+            Ok(mapped) => self.put(key, mapped, metadata).await,
+            Err(file) => match self {
+                ObjectStoreClient::Rest(client) => {
+                    client.put_stream(key, chunks(file), metadata).await
+                }
+                ObjectStoreClient::Quic(client) => {
+                    client.put_stream(key, chunks(file), metadata).await
+                }
+                _ => self.put(key, read_all(file).await?, metadata).await,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn uploads_the_mapped_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.bin");
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        tokio::fs::write(&path, &data).await.unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/big.bin")
+            .match_body(Matcher::from(data.clone()))
+            .with_status(201)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        client.put_mmap("big.bin", &path, None).await.unwrap();
+        put.assert_async().await;
+
+        let missing = client.put_mmap("x", dir.path().join("missing"), None).await;
+        assert!(matches!(missing, Err(Error::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn fallback_reads_match_the_mapping() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f");
+        let data = vec![7u8; READ_CHUNK + 10];
+        tokio::fs::write(&path, &data).await.unwrap();
+
+        let mapped = open(&path).await.unwrap().unwrap();
+        assert_eq!(mapped, data);
+
+        let streamed: Vec<Bytes> = chunks(File::open(&path).await.unwrap())
+            .map(|c| c.unwrap())
+            .collect()
+            .await;
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed.concat(), data);
+        assert_eq!(
+            read_all(File::open(&path).await.unwrap()).await.unwrap(),
+            data
+        );
+    }
+}
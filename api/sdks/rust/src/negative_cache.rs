@@ -0,0 +1,394 @@
+//! Short-lived caching of missing keys.
+//!
+//! Code that polls for an object — waiting for a marker file, or checking
+//! a hot key that is usually absent — sends the server a request per check.
+//! [`NegativeCache`] remembers `exists` and `get_metadata` misses for a
+//! short TTL and answers repeated checks locally:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let store = ObjectStoreClient::rest("http://localhost:8080")?
+//!     .with_negative_cache(Duration::from_secs(2), 10_000);
+//!
+//! // At most one request every two seconds while the key is absent.
+//! while !store.exists("jobs/42/_SUCCESS").await? {
+//!     tokio::time::sleep(Duration::from_millis(100)).await;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only misses are cached; keys that exist are always looked up.  A `put`
+//! or `update_metadata` through the wrapper forgets the key at once, as
+//! does a successful `get`, and a miss answered while such a write was in
+//! flight is not cached: the server may have looked before the write.
+//! Objects created by other clients are seen when the cached miss expires,
+//! so pick a TTL the callers can tolerate being late by.  When `capacity`
+//! misses are cached, the least recently used is dropped to make room.
+
+use crate::client::{ByteStream, ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Cached misses, least recently used first.
+#[derive(Debug)]
+struct Misses {
+    ttl: Duration,
+    capacity: usize,
+    /// Key to (expiry, position in `order`).
+    entries: HashMap<String, (Instant, u64)>,
+    order: BTreeMap<u64, String>,
+    next: u64,
+    /// Bumped as each local write starts and ends; a miss is recorded
+    /// only if none did while it was looked up.
+    writes: u64,
+}
+
+impl Misses {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next: 0,
+            writes: 0,
+        }
+    }
+
+    /// Whether `key` is a live cached miss at `now`; marks it recently used.
+    fn contains(&mut self, key: &str, now: Instant) -> bool {
+        let Some(&(expires, position)) = self.entries.get(key) else {
+            return false;
+        };
+        if now >= expires {
+            self.remove(key);
+            return false;
+        }
+        self.order.remove(&position);
+        let position = self.position(key);
+        self.entries.get_mut(key).expect("entry present").1 = position;
+        true
+    }
+
+    fn insert(&mut self, key: &str, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(key);
+        while self.entries.len() >= self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        let position = self.position(key);
+        self.entries
+            .insert(key.to_string(), (now + self.ttl, position));
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((_, position)) = self.entries.remove(key) {
+            self.order.remove(&position);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn position(&mut self, key: &str) -> u64 {
+        let position = self.next;
+        self.next += 1;
+        self.order.insert(position, key.to_string());
+        position
+    }
+}
+
+/// A store that remembers missing keys for a short time.  See the
+/// [module docs](self).
+#[derive(Debug)]
+pub struct NegativeCache<S> {
+    inner: S,
+    misses: Mutex<Misses>,
+}
+
+impl<S: ObjectStore> NegativeCache<S> {
+    /// Wrap `inner`, caching up to `capacity` misses for `ttl` each.
+    pub fn new(inner: S, ttl: Duration, capacity: usize) -> Self {
+        Self {
+            inner,
+            misses: Mutex::new(Misses::new(ttl, capacity)),
+        }
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Forget any cached miss for `key`, e.g. after another client
+    /// reports creating it.
+    pub fn invalidate(&self, key: &str) {
+        self.lock().remove(key);
+    }
+
+    /// Forget every cached miss.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// The number of cached misses, including any expired but not yet
+    /// dropped.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether no misses are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Misses> {
+        self.misses.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// `None` if `key` is a cached miss, otherwise the write count to pass
+    /// to [`record_miss`](Self::record_miss) once the key is looked up.
+    fn uncached(&self, key: &str) -> Option<u64> {
+        let mut misses = self.lock();
+        (!misses.contains(key, Instant::now())).then_some(misses.writes)
+    }
+
+    /// Cache a miss for `key` looked up when the write count was `writes`.
+    fn record_miss(&self, key: &str, writes: u64) {
+        let mut misses = self.lock();
+        if misses.writes == writes {
+            misses.insert(key, Instant::now());
+        }
+    }
+
+    /// Note a local write to `key` starting or ending.
+    fn wrote(&self, key: &str) {
+        let mut misses = self.lock();
+        misses.writes += 1;
+        misses.remove(key);
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for NegativeCache<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        self.wrote(key);
+        let result = self.inner.put(key, data, metadata).await;
+        self.wrote(key);
+        result
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        let object = self.inner.get(key).await?;
+        self.invalidate(key);
+        Ok(object)
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.inner.delete(key).await
+    }
+
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.inner.list(list_req).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let Some(writes) = self.uncached(key) else {
+            return Ok(false);
+        };
+        let exists = self.inner.exists(key).await?;
+        if exists {
+            self.invalidate(key);
+        } else {
+            self.record_miss(key, writes);
+        }
+        Ok(exists)
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        let Some(writes) = self.uncached(key) else {
+            return Err(Error::NotFound(key.to_string()));
+        };
+        match self.inner.get_metadata(key).await {
+            Err(Error::NotFound(resource)) => {
+                self.record_miss(key, writes);
+                Err(Error::NotFound(resource))
+            }
+            result => {
+                if result.is_ok() {
+                    self.invalidate(key);
+                }
+                result
+            }
+        }
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.wrote(key);
+        let result = self.inner.update_metadata(key, metadata).await;
+        self.wrote(key);
+        result
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.inner.health().await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        let object = self.inner.get_stream(key).await?;
+        self.invalidate(key);
+        Ok(object)
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.wrote(key);
+        let result = self.inner.put_stream(key, stream, metadata).await;
+        self.wrote(key);
+        result
+    }
+}
+
+impl ObjectStoreClient {
+    /// Wrap this client so `exists` and `get_metadata` misses are cached
+    /// for `ttl`, up to `capacity` keys.  See the
+    /// [`negative_cache`](crate::negative_cache) module.
+    pub fn with_negative_cache(self, ttl: Duration, capacity: usize) -> NegativeCache<Self> {
+        NegativeCache::new(self, ttl, capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::rest_client::RestClient;
+    use crate::test_support::MemoryStore;
+
+    #[test]
+    fn misses_expire_and_evict_least_recently_used() {
+        let start = Instant::now();
+        let ttl = Duration::from_secs(1);
+        let mut misses = Misses::new(ttl, 2);
+        misses.insert("a", start);
+        misses.insert("b", start);
+        assert!(misses.contains("a", start));
+        misses.insert("c", start);
+        assert!(!misses.contains("b", start), "b was least recently used");
+        assert!(misses.contains("a", start) && misses.contains("c", start));
+
+        assert!(!misses.contains("a", start + ttl));
+        assert_eq!(misses.entries.len(), 1);
+        assert_eq!(misses.order.len(), 1);
+
+        let mut disabled = Misses::new(ttl, 0);
+        disabled.insert("a", start);
+        assert!(!disabled.contains("a", start));
+    }
+
+    #[tokio::test]
+    async fn repeated_misses_are_answered_locally() {
+        let mut server = mockito::Server::new_async().await;
+        let head = server
+            .mock("HEAD", "/objects/marker")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+        let metadata = server
+            .mock("GET", "/metadata/marker")
+            .with_status(404)
+            .expect(0)
+            .create_async()
+            .await;
+        let store = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap())
+            .with_negative_cache(Duration::from_secs(60), 100);
+
+        for _ in 0..3 {
+            assert!(!store.exists("marker").await.unwrap());
+        }
+        let err = store.get_metadata("marker").await.unwrap_err();
+        assert!(
+            matches!(err, Error::NotFound(ref k) if k == "marker"),
+            "{err:?}"
+        );
+        head.assert_async().await;
+        metadata.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn local_put_invalidates() {
+        let mut server = mockito::Server::new_async().await;
+        let missing = server
+            .mock("HEAD", "/objects/marker")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("PUT", "/objects/marker")
+            .with_status(201)
+            .with_body(r#"{"message":"ok"}"#)
+            .create_async()
+            .await;
+        let store = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap())
+            .with_negative_cache(Duration::from_secs(60), 100);
+
+        assert!(!store.exists("marker").await.unwrap());
+        assert_eq!(store.len(), 1);
+        missing.remove_async().await;
+        server
+            .mock("HEAD", "/objects/marker")
+            .with_status(200)
+            .create_async()
+            .await;
+        store.put("marker", Bytes::new(), None).await.unwrap();
+        assert!(store.is_empty());
+        assert!(store.exists("marker").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn misses_answered_during_a_local_write_are_not_cached() {
+        // The server answers at once, but the answer takes a while back.
+        let inner = MemoryStore::default().with_delay(|operation, _, _| match operation {
+            Operation::Exists | Operation::GetMetadata => Duration::from_millis(50),
+            _ => Duration::ZERO,
+        });
+        let store = NegativeCache::new(inner, Duration::from_secs(60), 100);
+        let put_soon = || async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            store.put("marker", Bytes::new(), None).await
+        };
+
+        let (exists, put) = futures::join!(store.exists("marker"), put_soon());
+        assert!(!exists.unwrap());
+        put.unwrap();
+        assert!(store.is_empty());
+        assert!(store.exists("marker").await.unwrap());
+
+        store.inner().remove("marker");
+        let (metadata, put) = futures::join!(store.get_metadata("marker"), put_soon());
+        assert!(matches!(metadata, Err(Error::NotFound(_))));
+        put.unwrap();
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+//! Server page-size limits for listings.
+//!
+//! go-objstore servers cap a listing page at 1000 objects and silently
+//! return fewer than a larger `max_results` asked for.  The REST, QUIC,
+//! and gRPC clients instead split such a request into pages the server
+//! will honor and merge them, so `list` returns up to `max_results`
+//! objects as requested:
+//!
+//! ```no_run
+//! use go_objstore::{ListRequest, ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let page = client
+//!     .list(ListRequest {
+//!         max_results: Some(5000), // fetched as five requests of 1000
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! assert!(page.objects.len() <= 5000);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The limit is, in order of preference: [`ClientConfig::max_page_size`];
+//! the value a REST or QUIC server advertises in a [`MAX_PAGE_SIZE_HEADER`]
+//! response header on listings (remembered by the client and its clones,
+//! and reported by [`compat_check`](crate::ObjectStoreClient::compat_check));
+//! or [`DEFAULT_MAX_PAGE_SIZE`].  The merged response carries the last
+//! page's continuation token, so paging on from it works as usual.
+//!
+//! [`ClientConfig::max_page_size`]: crate::ClientConfig::max_page_size
+
+use crate::client::ObjectStoreClient;
+use crate::error::Result;
+use crate::types::{ListRequest, ListResponse};
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// The page-size cap of go-objstore servers (`MaxListLimit`).
+pub const DEFAULT_MAX_PAGE_SIZE: u32 = 1000;
+
+/// Response header in which a server may advertise its page-size cap.
+pub const MAX_PAGE_SIZE_HEADER: &str = "x-max-page-size";
+
+/// A client's page-size cap: configured, or discovered from responses.
+#[derive(Debug, Clone)]
+pub(crate) struct PageLimit {
+    configured: Option<u32>,
+    /// Last advertised cap; 0 until a server advertises one.
+    discovered: Arc<AtomicU32>,
+}
+
+impl PageLimit {
+    pub(crate) fn new(configured: Option<u32>) -> Self {
+        Self {
+            configured: configured.filter(|&n| n > 0),
+            discovered: Arc::default(),
+        }
+    }
+
+    /// The cap to split requests at.
+    pub(crate) fn get(&self) -> u32 {
+        self.configured
+            .or_else(|| self.discovered())
+            .unwrap_or(DEFAULT_MAX_PAGE_SIZE)
+    }
+
+    /// The cap a server advertised, if any has.
+    pub(crate) fn discovered(&self) -> Option<u32> {
+        Some(self.discovered.load(Ordering::Relaxed)).filter(|&n| n > 0)
+    }
+
+    /// Remember the cap advertised in `headers`, if any.
+    pub(crate) fn observe(&self, headers: &http::HeaderMap) {
+        if let Some(limit) = advertised(headers) {
+            self.discovered.store(limit, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The cap advertised in `headers`.
+pub(crate) fn advertised(headers: &http::HeaderMap) -> Option<u32> {
+    headers
+        .get(MAX_PAGE_SIZE_HEADER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&n| n > 0)
+}
+
+/// Run `list_req` as pages of at most `limit` objects via `fetch`, merging
+/// them until `max_results` objects are collected or the listing ends.
+/// Requests within the limit (or without `max_results`) are sent as is.
+pub(crate) async fn list_split<F, Fut>(
+    list_req: ListRequest,
+    limit: u32,
+    fetch: F,
+) -> Result<ListResponse>
+where
+    F: Fn(ListRequest) -> Fut,
+    Fut: Future<Output = Result<ListResponse>>,
+{
+    let wanted = match list_req.max_results {
+        Some(wanted) if wanted > 0 && wanted as u32 > limit => wanted as usize,
+        _ => return fetch(list_req).await,
+    };
+
+    let mut merged = ListResponse::default();
+    let mut req = list_req;
+    loop {
+        let remaining = wanted - merged.objects.len();
+        req.max_results = Some(remaining.min(limit as usize) as i32);
+        let page = fetch(req.clone()).await?;
+        merged.objects.extend(page.objects);
+        merged.common_prefixes.extend(page.common_prefixes);
+        merged.truncated = page.truncated;
+        merged.next_token = page.next_token;
+        let token = merged.next_token.clone().filter(|t| !t.is_empty());
+        match token {
+            Some(token) if merged.truncated && merged.objects.len() < wanted => {
+                req.continue_from = Some(token);
+            }
+            _ => return Ok(merged),
+        }
+    }
+}
+
+impl ObjectStoreClient {
+    /// The page size `list` requests are split at.  See the
+    /// [`paging`](crate::paging) module.
+    pub fn max_page_size(&self) -> u32 {
+        match self {
+            ObjectStoreClient::Rest(client) => client.page_limit().get(),
+            ObjectStoreClient::Quic(client) => client.page_limit().get(),
+            ObjectStoreClient::Grpc(client) => client.page_limit().get(),
+            ObjectStoreClient::Mcp(_) | ObjectStoreClient::Unix(_) => DEFAULT_MAX_PAGE_SIZE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use crate::{ObjectStore, ObjectStoreClient};
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn large_requests_are_split_at_the_discovered_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let page = |objects: &str, next: &str| {
+            format!(r#"{{"objects":{objects},"next_token":"{next}","truncated":true}}"#)
+        };
+        let mut mocks = Vec::new();
+        let answers = [
+            // Asked for 5, the server returns its cap of 2 and says so.
+            (
+                "limit=5",
+                r#"[{"key":"a","size":1},{"key":"b","size":1}]"#,
+                "t1",
+            ),
+            (
+                "limit=2",
+                r#"[{"key":"a","size":1},{"key":"b","size":1}]"#,
+                "t1",
+            ),
+            (
+                "limit=2&token=t1",
+                r#"[{"key":"c","size":1},{"key":"d","size":1}]"#,
+                "t2",
+            ),
+            ("limit=1&token=t2", r#"[{"key":"e","size":1}]"#, "t3"),
+        ];
+        for (query, objects, next) in answers {
+            let mock = server
+                .mock("GET", "/objects")
+                .match_query(Matcher::Exact(query.into()))
+                .with_header(MAX_PAGE_SIZE_HEADER, "2")
+                .with_body(page(objects, next))
+                .expect(1)
+                .create_async()
+                .await;
+            mocks.push(mock);
+        }
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+        let request = ListRequest {
+            max_results: Some(5),
+            ..Default::default()
+        };
+
+        assert_eq!(client.max_page_size(), DEFAULT_MAX_PAGE_SIZE);
+        let clamped = client.list(request.clone()).await.unwrap();
+        assert_eq!(clamped.objects.len(), 2);
+        assert_eq!(client.max_page_size(), 2);
+
+        let page = client.list(request).await.unwrap();
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        let keys: Vec<_> = page.objects.iter().map(|o| o.key.as_str()).collect();
+        assert_eq!(keys, ["a", "b", "c", "d", "e"]);
+        assert!(page.truncated);
+        assert_eq!(page.next_token.as_deref(), Some("t3"));
+    }
+
+    #[tokio::test]
+    async fn small_requests_pass_through() {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let request = ListRequest {
+            max_results: Some(10),
+            ..Default::default()
+        };
+        let page = list_split(request, 10, |req| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            async move {
+                assert_eq!(req.max_results, Some(10));
+                Ok(ListResponse::default())
+            }
+        })
+        .await
+        .unwrap();
+        assert!(page.objects.is_empty());
+        assert_eq!(calls.into_inner(), 1);
+        assert_eq!(PageLimit::new(Some(0)).get(), DEFAULT_MAX_PAGE_SIZE);
+    }
+}
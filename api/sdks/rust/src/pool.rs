@@ -0,0 +1,229 @@
+//! Bounded worker pool for bulk object operations.
+//!
+//! [`WorkerPool`] runs at most `concurrency` tasks at once on a
+//! [`JoinSet`].  [`submit`](WorkerPool::submit) waits for a free slot, so a
+//! producer feeding hundreds of thousands of keys is slowed to the pool's
+//! pace instead of spawning them all; each result is reported with the key
+//! it belongs to:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::pool::WorkerPool;
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Arc::new(ObjectStoreClient::rest("http://localhost:8080")?);
+//! let mut pool = WorkerPool::new(64);
+//! for i in 0..100_000 {
+//!     let key = format!("bulk/{i}");
+//!     let client = client.clone();
+//!     let task_key = key.clone();
+//!     pool.submit(key, async move { client.put(&task_key, Bytes::from("x"), None).await })
+//!         .await?;
+//!     // Drain finished work as we go.
+//!     while let Some((key, result)) = pool.try_next() {
+//!         if let Err(e) = result {
+//!             eprintln!("{key}: {e}");
+//!         }
+//!     }
+//! }
+//! for (key, result) in pool.join_all().await {
+//!     result.map_err(|e| format!("{key}: {e}"))?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Cancelling the pool's [`CancellationToken`] (or calling
+//! [`cancel`](WorkerPool::cancel)) stops in-flight tasks, which then report
+//! [`Error::Cancelled`], and makes further submissions fail.
+//!
+//! Tasks are spawned, so they must own what they use (an `Arc` of the
+//! client, as above).  The crate's own bulk helpers such as
+//! [`delete_many`](crate::delete::delete_many) borrow the store instead and
+//! do not use the pool: each bounds its requests in flight on the calling
+//! task and documents its own limit.
+
+use crate::error::{Error, Result};
+use std::collections::VecDeque;
+use std::future::Future;
+use tokio::task::{JoinError, JoinSet};
+use tokio_util::sync::CancellationToken;
+
+/// A key and the outcome of the task submitted for it.
+pub type KeyedResult<T> = (String, Result<T>);
+
+/// Runs keyed tasks with bounded concurrency.  See the [module docs](self).
+#[derive(Debug)]
+pub struct WorkerPool<T> {
+    tasks: JoinSet<KeyedResult<T>>,
+    /// Results reaped while waiting for a slot, not yet returned.
+    finished: VecDeque<KeyedResult<T>>,
+    concurrency: usize,
+    cancel: CancellationToken,
+}
+
+impl<T: Send + 'static> WorkerPool<T> {
+    /// A pool running at most `concurrency` tasks (minimum 1).
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            finished: VecDeque::new(),
+            concurrency: concurrency.max(1),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Token that cancels this pool when triggered; clone it into signal
+    /// handlers or parent tasks.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Cancel all running tasks and reject further submissions.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Number of tasks running.
+    pub fn running(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Return true when nothing is running or waiting to be collected.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty() && self.finished.is_empty()
+    }
+
+    /// Start `task` for `key`, first waiting for a free slot.
+    ///
+    /// Fails with [`Error::Cancelled`] once the pool is cancelled.  Results
+    /// of tasks that finish while waiting are kept for
+    /// [`try_next`](Self::try_next) / [`next`](Self::next).
+    pub async fn submit<F>(&mut self, key: impl Into<String>, task: F) -> Result<()>
+    where
+        F: Future<Output = Result<T>> + Send + 'static,
+    {
+        while self.tasks.len() >= self.concurrency {
+            if self.cancel.is_cancelled() {
+                break;
+            }
+            if let Some(joined) = self.tasks.join_next().await {
+                self.finished.push_back(flatten(joined));
+            }
+        }
+        if self.cancel.is_cancelled() {
+            return Err(Error::Cancelled("worker pool cancelled".into()));
+        }
+
+        let key = key.into();
+        let cancel = self.cancel.clone();
+        self.tasks.spawn(async move {
+            let result = tokio::select! {
+                result = task => result,
+                _ = cancel.cancelled() => Err(Error::Cancelled(format!("{key}: cancelled"))),
+            };
+            (key, result)
+        });
+        Ok(())
+    }
+
+    /// A finished result, if one is ready, without waiting.
+    pub fn try_next(&mut self) -> Option<KeyedResult<T>> {
+        if let Some(done) = self.finished.pop_front() {
+            return Some(done);
+        }
+        self.tasks.try_join_next().map(flatten)
+    }
+
+    /// The next finished result, waiting for one; `None` when the pool is
+    /// empty.
+    pub async fn next(&mut self) -> Option<KeyedResult<T>> {
+        if let Some(done) = self.finished.pop_front() {
+            return Some(done);
+        }
+        self.tasks.join_next().await.map(flatten)
+    }
+
+    /// Wait for every task and return all uncollected results.
+    pub async fn join_all(mut self) -> Vec<KeyedResult<T>> {
+        let mut results: Vec<_> = self.finished.drain(..).collect();
+        while let Some(joined) = self.tasks.join_next().await {
+            results.push(flatten(joined));
+        }
+        results
+    }
+}
+
+fn flatten<T>(joined: std::result::Result<KeyedResult<T>, JoinError>) -> KeyedResult<T> {
+    // Keys travel inside the task, so a panicked task's key is unknown.
+    joined.unwrap_or_else(|e| {
+        let error = if e.is_cancelled() {
+            Error::Cancelled(e.to_string())
+        } else {
+            Error::Generic(format!("worker task panicked: {e}"))
+        };
+        (String::new(), Err(error))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn bounds_concurrency_and_keys_results() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut pool = WorkerPool::new(3);
+        for i in 0..20 {
+            let (running, peak) = (running.clone(), peak.clone());
+            pool.submit(format!("k{i}"), async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(2)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if i == 7 {
+                    Err(Error::NotFound(format!("k{i}")))
+                } else {
+                    Ok(i)
+                }
+            })
+            .await
+            .unwrap();
+            assert!(pool.running() <= 3);
+        }
+
+        let mut results = pool.join_all().await;
+        results.sort_by_key(|(key, _)| key[1..].parse::<u32>().unwrap());
+        assert_eq!(results.len(), 20);
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert!(matches!(&results[7], (k, Err(Error::NotFound(_))) if k == "k7"));
+        assert!(matches!(results[8], (_, Ok(8))));
+    }
+
+    #[tokio::test]
+    async fn cancellation_stops_tasks_and_submissions() {
+        let mut pool: WorkerPool<()> = WorkerPool::new(2);
+        pool.submit("slow", async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        pool.cancellation_token().cancel();
+        assert!(matches!(
+            pool.submit("late", async { Ok(()) }).await,
+            Err(Error::Cancelled(_))
+        ));
+        let (key, result) = pool.next().await.unwrap();
+        assert_eq!(key, "slow");
+        assert!(matches!(result, Err(Error::Cancelled(_))));
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+//! Presigned object URLs.
+//!
+//! [`presign`] issues a time-limited URL for one method on one object, and
+//! [`verify_presigned`] lets a Rust gateway in front of go-objstore validate
+//! such URLs before forwarding the request:
+//!
+//! ```no_run
+//! use chrono::{Duration, Utc};
+//! use go_objstore::presign::{presign, verify_presigned};
+//!
+//! # fn example() -> go_objstore::Result<()> {
+//! let secret = b"shared gateway secret";
+//! let url = presign(
+//!     "https://gw.example.com",
+//!     "GET",
+//!     "reports/q3.pdf",
+//!     secret,
+//!     Utc::now() + Duration::minutes(15),
+//! )?;
+//!
+//! // In the gateway:
+//! let request = verify_presigned(&url, secret, Utc::now())?;
+//! assert_eq!(request.key, "reports/q3.pdf");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Scheme
+//!
+//! A presigned URL is the REST object URL (`{base}/objects/{key}`, key
+//! percent-encoded) with three query parameters appended:
+//!
+//! * `method` — the HTTP method the URL is valid for,
+//! * `expires` — expiry as Unix seconds,
+//! * `signature` — lowercase hex HMAC-SHA256, keyed with the shared secret,
+//!   over `"{method}\n{path}\n{expires}"`, where `path` is the URL path
+//!   exactly as it appears in the URL.
+//!
+//! The Go server does not issue or check presigned URLs; both halves of the
+//! scheme live here so that issuers and gateways built on this SDK agree.
+
+use crate::error::{Error, Result};
+use crate::manifest::{from_hex, to_hex};
+use chrono::{DateTime, TimeZone, Utc};
+use ring::hmac;
+use url::Url;
+
+/// A request authorized by a valid presigned URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresignedRequest {
+    /// HTTP method the URL was issued for; the gateway must check that the
+    /// incoming request uses it.
+    pub method: String,
+    /// Decoded object key.
+    pub key: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Issue a URL granting `method` on `key` until `expires_at`.
+pub fn presign(
+    base_url: &str,
+    method: &str,
+    key: &str,
+    secret: &[u8],
+    expires_at: DateTime<Utc>,
+) -> Result<String> {
+    let method = method.to_ascii_uppercase();
+    let mut url = Url::parse(&format!(
+        "{}/objects/{}",
+        base_url.trim_end_matches('/'),
+        urlencoding::encode(key)
+    ))?;
+    let expires = expires_at.timestamp().to_string();
+    let signature = sign(secret, &method, url.path(), &expires);
+    url.query_pairs_mut()
+        .append_pair("method", &method)
+        .append_pair("expires", &expires)
+        .append_pair("signature", &signature);
+    Ok(url.into())
+}
+
+/// Validate a presigned URL at time `now`.
+///
+/// Returns [`Error::InvalidArgument`] when the URL is not a presigned object
+/// URL, and [`Error::Forbidden`] when the signature does not match or the URL
+/// has expired.  Query parameters other than the three signed ones are
+/// ignored.
+pub fn verify_presigned(url: &str, secret: &[u8], now: DateTime<Utc>) -> Result<PresignedRequest> {
+    let url = Url::parse(url)?;
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+            .ok_or_else(|| Error::InvalidArgument(format!("presigned URL has no {name}")))
+    };
+    let method = param("method")?;
+    let expires = param("expires")?;
+    let signature = param("signature")?;
+
+    let tag = from_hex(&signature)
+        .ok_or_else(|| Error::InvalidArgument("malformed presigned URL signature".into()))?;
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+    hmac::verify(
+        &key,
+        canonical(&method, url.path(), &expires).as_bytes(),
+        &tag,
+    )
+    .map_err(|_| Error::Forbidden("presigned URL signature mismatch".into()))?;
+
+    let expires_at = expires
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        .ok_or_else(|| Error::InvalidArgument(format!("invalid expires {expires:?}")))?;
+    if now >= expires_at {
+        return Err(Error::Forbidden(format!(
+            "presigned URL expired at {}",
+            expires_at.to_rfc3339()
+        )));
+    }
+
+    let encoded_key = url
+        .path()
+        .rsplit_once("/objects/")
+        .map(|(_, key)| key)
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| Error::InvalidArgument("presigned URL is not an object URL".into()))?;
+    let key = urlencoding::decode(encoded_key)
+        .map_err(|_| Error::InvalidArgument("presigned URL key is not UTF-8".into()))?
+        .into_owned();
+
+    Ok(PresignedRequest {
+        method,
+        key,
+        expires_at,
+    })
+}
+
+fn canonical(method: &str, path: &str, expires: &str) -> String {
+    format!("{method}\n{path}\n{expires}")
+}
+
+fn sign(secret: &[u8], method: &str, path: &str, expires: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+    to_hex(hmac::sign(&key, canonical(method, path, expires).as_bytes()).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const SECRET: &[u8] = b"s3cret";
+
+    fn issued() -> (String, DateTime<Utc>) {
+        let expires = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let url = presign("http://gw:8080/", "get", "dir/a b.txt", SECRET, expires).unwrap();
+        (url, expires)
+    }
+
+    #[test]
+    fn round_trips() {
+        let (url, expires) = issued();
+        assert!(url.starts_with("http://gw:8080/objects/dir%2Fa%20b.txt?method=GET&expires="));
+
+        let request = verify_presigned(&url, SECRET, expires - Duration::seconds(1)).unwrap();
+        assert_eq!(
+            request,
+            PresignedRequest {
+                method: "GET".into(),
+                key: "dir/a b.txt".into(),
+                expires_at: expires,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_expired_tampered_and_wrong_secret() {
+        let (url, expires) = issued();
+        let before = expires - Duration::seconds(1);
+
+        assert!(matches!(
+            verify_presigned(&url, SECRET, expires),
+            Err(Error::Forbidden(m)) if m.contains("expired")
+        ));
+        assert!(matches!(
+            verify_presigned(&url, b"other", before),
+            Err(Error::Forbidden(_))
+        ));
+        let tampered = url.replace("method=GET", "method=DELETE");
+        assert!(matches!(
+            verify_presigned(&tampered, SECRET, before),
+            Err(Error::Forbidden(_))
+        ));
+        let other_key = url.replace("a%20b.txt", "c.txt");
+        assert!(matches!(
+            verify_presigned(&other_key, SECRET, before),
+            Err(Error::Forbidden(_))
+        ));
+        assert!(matches!(
+            verify_presigned("http://gw/objects/k", SECRET, before),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
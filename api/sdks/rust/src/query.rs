@@ -0,0 +1,351 @@
+//! Finding objects by custom metadata.
+//!
+//! A [`MetadataQuery`] combines conditions on custom metadata values —
+//! exact match, string prefix, and numeric range — and [`query`] returns
+//! the objects meeting all of them, sorted by key:
+//!
+//! ```no_run
+//! use go_objstore::query::MetadataQuery;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let query = MetadataQuery::new()
+//!     .prefix("exports/")
+//!     .equals("owner", "data-platform")
+//!     .starts_with("region", "eu-")
+//!     .at_least("rows", 1_000_000.0);
+//! for object in client.query(query).await? {
+//!     println!("{}", object.key);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Over REST, [`ObjectStoreClient::query`] sends the query to the server's
+//! `POST /search` endpoint.  Servers without one, and every other
+//! transport, list the prefix and filter client-side instead, fetching the
+//! metadata of objects whose listing entry carries none; the results are
+//! the same, only the cost differs.
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::Result;
+use crate::stat::STAT_CONCURRENCY;
+use crate::types::{ListRequest, Metadata, ObjectInfo};
+use futures::StreamExt;
+use serde_json::{json, Value};
+
+/// A test on one custom metadata value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The value equals this string.
+    Equals(String),
+    /// The value starts with this string.
+    Prefix(String),
+    /// The value parses as a number within these inclusive bounds; an
+    /// unset bound is open.
+    Range { min: Option<f64>, max: Option<f64> },
+}
+
+impl Condition {
+    /// Whether `value` satisfies the condition.  Range conditions accept
+    /// any value that parses as a finite number after trimming whitespace.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Condition::Equals(expected) => value == expected,
+            Condition::Prefix(prefix) => value.starts_with(prefix.as_str()),
+            Condition::Range { min, max } => match value.trim().parse::<f64>() {
+                Ok(n) if n.is_finite() => {
+                    min.is_none_or(|min| n >= min) && max.is_none_or(|max| n <= max)
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn to_json(&self, key: &str) -> Value {
+        match self {
+            Condition::Equals(value) => json!({"key": key, "op": "eq", "value": value}),
+            Condition::Prefix(value) => json!({"key": key, "op": "prefix", "value": value}),
+            Condition::Range { min, max } => {
+                json!({"key": key, "op": "range", "min": min, "max": max})
+            }
+        }
+    }
+}
+
+/// Conditions an object's custom metadata must all meet.  See the
+/// [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataQuery {
+    prefix: Option<String>,
+    conditions: Vec<(String, Condition)>,
+    limit: Option<usize>,
+}
+
+impl MetadataQuery {
+    /// A query matching every object.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only consider keys under `prefix`.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into()).filter(|p| !p.is_empty());
+        self
+    }
+
+    /// Require custom metadata `key` to satisfy `condition`.
+    pub fn filter(mut self, key: impl Into<String>, condition: Condition) -> Self {
+        self.conditions.push((key.into(), condition));
+        self
+    }
+
+    /// Require `key` to equal `value`.
+    pub fn equals(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.filter(key, Condition::Equals(value.into()))
+    }
+
+    /// Require `key` to start with `prefix`.
+    pub fn starts_with(self, key: impl Into<String>, prefix: impl Into<String>) -> Self {
+        self.filter(key, Condition::Prefix(prefix.into()))
+    }
+
+    /// Require `key` to be a number in `min..=max`.
+    pub fn between(self, key: impl Into<String>, min: f64, max: f64) -> Self {
+        let range = Condition::Range {
+            min: Some(min),
+            max: Some(max),
+        };
+        self.filter(key, range)
+    }
+
+    /// Require `key` to be a number no smaller than `min`.
+    pub fn at_least(self, key: impl Into<String>, min: f64) -> Self {
+        let range = Condition::Range {
+            min: Some(min),
+            max: None,
+        };
+        self.filter(key, range)
+    }
+
+    /// Require `key` to be a number no larger than `max`.
+    pub fn at_most(self, key: impl Into<String>, max: f64) -> Self {
+        let range = Condition::Range {
+            min: None,
+            max: Some(max),
+        };
+        self.filter(key, range)
+    }
+
+    /// Return at most `limit` objects (the first by key).
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `metadata` meets every condition.  A missing key fails its
+    /// condition.
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        self.conditions.iter().all(|(key, condition)| {
+            metadata
+                .custom
+                .get(key)
+                .is_some_and(|value| condition.matches(value))
+        })
+    }
+
+    /// The `POST /search` request body for the page after `token`.
+    pub(crate) fn search_body(&self, token: Option<&str>) -> Value {
+        let filters: Vec<Value> = self
+            .conditions
+            .iter()
+            .map(|(key, condition)| condition.to_json(key))
+            .collect();
+        json!({
+            "prefix": self.prefix,
+            "filters": filters,
+            "limit": self.limit,
+            "token": token,
+        })
+    }
+
+    fn is_full(&self, found: &[ObjectInfo]) -> bool {
+        self.limit.is_some_and(|limit| found.len() >= limit)
+    }
+
+    fn finish(&self, mut found: Vec<ObjectInfo>) -> Vec<ObjectInfo> {
+        found.sort_by(|a, b| a.key.cmp(&b.key));
+        if let Some(limit) = self.limit {
+            found.truncate(limit);
+        }
+        found
+    }
+}
+
+/// Objects meeting `query`, sorted by key.
+///
+/// Lists the query's prefix and filters client-side; works with any store.
+/// Objects whose listing entry has no custom metadata are looked up with
+/// `get_metadata` before being tested (unless the query has no conditions).
+pub async fn query(store: &dyn ObjectStore, query: &MetadataQuery) -> Result<Vec<ObjectInfo>> {
+    let request = ListRequest {
+        prefix: query.prefix.clone(),
+        max_results: Some(LIST_PAGE_SIZE),
+        ..Default::default()
+    };
+    let mut found = Vec::new();
+    let mut pages = list_pages(store, request);
+    while let Some(page) = pages.next().await {
+        let objects = complete_metadata(store, query, page?.objects).await?;
+        found.extend(objects.into_iter().filter(|o| query.matches(&o.metadata)));
+        if query.is_full(&found) {
+            break;
+        }
+    }
+    Ok(query.finish(found))
+}
+
+/// Fill in the metadata of listing entries that carry no custom metadata,
+/// when the query needs it.
+async fn complete_metadata(
+    store: &dyn ObjectStore,
+    query: &MetadataQuery,
+    objects: Vec<ObjectInfo>,
+) -> Result<Vec<ObjectInfo>> {
+    if query.conditions.is_empty() {
+        return Ok(objects);
+    }
+    futures::stream::iter(objects)
+        .map(|mut object| async move {
+            if object.metadata.custom.is_empty() {
+                object.metadata = store.get_metadata(&object.key).await?;
+            }
+            Ok(object)
+        })
+        .buffered(STAT_CONCURRENCY)
+        .collect::<Vec<Result<ObjectInfo>>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+impl ObjectStoreClient {
+    /// Objects meeting `query`, sorted by key.  See the
+    /// [`query`](crate::query) module.
+    pub async fn query(&self, query: MetadataQuery) -> Result<Vec<ObjectInfo>> {
+        let ObjectStoreClient::Rest(client) = self else {
+            return self::query(self, &query).await;
+        };
+
+        let mut found = Vec::new();
+        let mut token = None;
+        loop {
+            let Some(page) = client.search(&query, token.as_deref()).await? else {
+                return self::query(self, &query).await;
+            };
+            found.extend(page.objects);
+            match page.next_token {
+                Some(next) if page.truncated && !next.is_empty() && !query.is_full(&found) => {
+                    token = Some(next);
+                }
+                _ => break,
+            }
+        }
+        Ok(query.finish(found))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    fn keys(objects: &[ObjectInfo]) -> Vec<&str> {
+        objects.iter().map(|o| o.key.as_str()).collect()
+    }
+
+    #[test]
+    fn conditions_match_values() {
+        assert!(Condition::Equals("a".into()).matches("a"));
+        assert!(!Condition::Equals("a".into()).matches("ab"));
+        assert!(Condition::Prefix("eu-".into()).matches("eu-west-1"));
+        let range = Condition::Range {
+            min: Some(10.0),
+            max: Some(20.0),
+        };
+        assert!(range.matches(" 10 "));
+        assert!(range.matches("2e1"));
+        assert!(!range.matches("20.5"));
+        assert!(!range.matches("NaN"));
+        assert!(!range.matches("ten"));
+
+        let query = MetadataQuery::new()
+            .equals("owner", "ops")
+            .at_most("rows", 5.0);
+        let mut metadata = Metadata::default();
+        metadata.custom.insert("owner".into(), "ops".into());
+        assert!(!query.matches(&metadata), "missing key fails");
+        metadata.custom.insert("rows".into(), "3".into());
+        assert!(query.matches(&metadata));
+    }
+
+    #[tokio::test]
+    async fn rest_uses_search_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let search = server
+            .mock("POST", "/search")
+            .match_body(Matcher::PartialJson(json!({
+                "prefix": "p/",
+                "filters": [{"key": "owner", "op": "eq", "value": "ops"}],
+            })))
+            .with_body(r#"{"objects":[{"key":"p/b","size":1},{"key":"p/a","size":1}]}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let query = MetadataQuery::new().prefix("p/").equals("owner", "ops");
+        let found = client.query(query).await.unwrap();
+        search.assert_async().await;
+        assert_eq!(keys(&found), ["p/a", "p/b"]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_filtering_listings() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/search")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "p/".into()))
+            .with_body(
+                r#"{"objects":[
+                    {"key":"p/big","size":1,"metadata":{"rows":"5000","owner":"ops"}},
+                    {"key":"p/small","size":1,"metadata":{"rows":"12","owner":"ops"}},
+                    {"key":"p/bare","size":1}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let stat = server
+            .mock("GET", "/metadata/p%2Fbare")
+            .with_body(r#"{"key":"p/bare","size":1,"metadata":{"rows":"9000","owner":"ops"}}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let query = MetadataQuery::new()
+            .prefix("p/")
+            .equals("owner", "ops")
+            .at_least("rows", 1000.0);
+        let found = client.query(query.clone()).await.unwrap();
+        stat.assert_async().await;
+        assert_eq!(keys(&found), ["p/bare", "p/big"]);
+        let limited = client.query(query.limit(1)).await.unwrap();
+        assert_eq!(keys(&limited), ["p/bare"]);
+    }
+}
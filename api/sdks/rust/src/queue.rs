@@ -0,0 +1,221 @@
+//! Per-key ordering for concurrent writers.
+//!
+//! [`KeyedUploadQueue`] wraps any [`ObjectStore`] so that calls on different
+//! keys run fully in parallel while calls on the same key run one at a time,
+//! in the order they were issued.  This prevents a `delete` racing ahead of
+//! an earlier `put` of the same key (or two puts landing out of order) when
+//! many tasks share one client:
+//!
+//! ```no_run
+//! use go_objstore::queue::KeyedUploadQueue;
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//! use bytes::Bytes;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let store = Arc::new(KeyedUploadQueue::new(ObjectStoreClient::rest("http://localhost:8080")?));
+//!
+//! let writer = store.clone();
+//! let put = tokio::spawn(async move { writer.put("k", Bytes::from("v1"), None).await });
+//! tokio::task::yield_now().await;
+//! // Runs after the put above has finished.
+//! store.delete("k").await?;
+//! put.await??;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Ordering is by the time a call reaches the queue; calls on one key that
+//! are issued concurrently from different tasks run in the order they
+//! arrive.
+
+use crate::client::{ByteStream, ObjectStore};
+use crate::error::Result;
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
+
+/// An [`ObjectStore`] that serializes operations targeting the same key.
+///
+/// Every key-addressed operation (`put`, `put_stream`, `get`, `delete`,
+/// `exists`, `get_metadata`, `update_metadata`, and opening a `get_stream`)
+/// waits for earlier operations on that key.  `list` and `health` pass straight
+/// through.
+#[derive(Debug)]
+pub struct KeyedUploadQueue<S> {
+    inner: S,
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+/// Holds a key's slot; frees the map entry when no one else is waiting.
+struct KeyGuard<'a> {
+    key: &'a str,
+    locks: &'a Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for KeyGuard<'_> {
+    fn drop(&mut self) {
+        let Some(guard) = self.guard.take() else {
+            return;
+        };
+        let lock = Arc::clone(OwnedMutexGuard::mutex(&guard));
+        drop(guard);
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        // One reference in the map and one here: nobody else holds or
+        // awaits this key.
+        if Arc::strong_count(&lock) == 2 {
+            locks.remove(self.key);
+        }
+    }
+}
+
+impl<S: ObjectStore> KeyedUploadQueue<S> {
+    /// Wrap `inner`.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Number of keys with an operation running or waiting.
+    pub fn active_keys(&self) -> usize {
+        self.locks.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    async fn acquire<'a>(&'a self, key: &'a str) -> KeyGuard<'a> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(locks.entry(key.to_string()).or_default())
+        };
+        // tokio's mutex is fair, so waiters are served in arrival order.
+        let guard = lock.lock_owned().await;
+        KeyGuard {
+            key,
+            locks: &self.locks,
+            guard: Some(guard),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for KeyedUploadQueue<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        let _slot = self.acquire(key).await;
+        self.inner.put(key, data, metadata).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        let _slot = self.acquire(key).await;
+        self.inner.get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        let _slot = self.acquire(key).await;
+        self.inner.delete(key).await
+    }
+
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.inner.list(list_req).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let _slot = self.acquire(key).await;
+        self.inner.exists(key).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        let _slot = self.acquire(key).await;
+        self.inner.get_metadata(key).await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        let _slot = self.acquire(key).await;
+        self.inner.update_metadata(key, metadata).await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.inner.health().await
+    }
+
+    /// Waits for earlier operations on `key`, but releases the key once the
+    /// stream is open rather than when it is drained.
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        let _slot = self.acquire(key).await;
+        self.inner.get_stream(key).await
+    }
+
+    /// Holds the key until the whole stream is stored.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        let _slot = self.acquire(key).await;
+        self.inner.put_stream(key, stream, metadata).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::test_support::MemoryStore;
+    use std::time::Duration;
+
+    fn recorder() -> MemoryStore {
+        // Earlier puts sleep longer and would finish last without the queue.
+        MemoryStore::default().with_delay(|operation, _, len| match operation {
+            Operation::Put => Duration::from_millis(40 - 10 * len as u64),
+            _ => Duration::ZERO,
+        })
+    }
+
+    #[tokio::test]
+    async fn same_key_operations_run_in_issue_order() {
+        let queue = Arc::new(KeyedUploadQueue::new(recorder()));
+        let mut puts = Vec::new();
+        for len in 1..=3 {
+            let queue = queue.clone();
+            puts.push(tokio::spawn(async move {
+                queue.put("k", Bytes::from(vec![0; len]), None).await
+            }));
+            tokio::task::yield_now().await;
+        }
+        queue.delete("k").await.unwrap();
+        for put in puts {
+            put.await.unwrap().unwrap();
+        }
+
+        assert_eq!(
+            queue.inner().log(),
+            ["put k 1", "put k 2", "put k 3", "delete k"]
+        );
+        assert_eq!(queue.inner().peak(), 1);
+        assert_eq!(queue.active_keys(), 0);
+    }
+
+    #[tokio::test]
+    async fn different_keys_run_in_parallel() {
+        let queue = Arc::new(KeyedUploadQueue::new(recorder()));
+        let puts = (0..4).map(|i| {
+            let queue = queue.clone();
+            async move { queue.put(&format!("k{i}"), Bytes::from("x"), None).await }
+        });
+        for result in futures::future::join_all(puts).await {
+            result.unwrap();
+        }
+        assert_eq!(queue.inner().peak(), 4);
+        assert_eq!(queue.active_keys(), 0);
+    }
+}
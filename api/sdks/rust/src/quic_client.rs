@@ -1,15 +1,95 @@
+//! QUIC/HTTP3 transport.
+//!
+//! [`QuicClient`] sends the REST API over HTTP/3 with quinn and h3, opening
+//! a connection per call.  Mutual TLS is configured with a
+//! [`ClientIdentity`](crate::ClientIdentity); see the [`tls`](crate::tls)
+//! module.
+//!
+//! # Handshake retries
+//!
+//! UDP is often filtered or slow to come up in containers, so QUIC
+//! handshakes can time out where TCP connects.
+//! [`ClientConfig::quic_handshake`] gives each handshake attempt a timeout
+//! and retries timeouts and resets with exponential backoff.  Once the
+//! attempts run out, the call fails with [`Error::QuicUnavailable`] naming
+//! the [`fallback`](HandshakeRetry::fallback) protocol (REST by default),
+//! so callers can switch transports:
+//!
+//! ```no_run
+//! use go_objstore::{
+//!     ClientConfig, Error, HandshakeRetry, ObjectStore, ObjectStoreClient, Protocol, QuicClient,
+//!     TlsVerification,
+//! };
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     quic_handshake: Some(HandshakeRetry::default()),
+//!     ..Default::default()
+//! };
+//! let quic =
+//!     QuicClient::new_with_config("objstore.local", 4433, TlsVerification::Enabled, config).await?;
+//! let (data, _) = match quic.get("report.csv").await {
+//!     Err(Error::QuicUnavailable { suggest: Protocol::Rest, .. }) => {
+//!         ObjectStoreClient::rest("https://objstore.local")?.get("report.csv").await?
+//!     }
+//!     other => other?,
+//! };
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With `fallback: None` the last connection error is returned instead.
+//!
+//! # Session loss
+//!
+//! When the session closes under a request — the server sends GOAWAY or
+//! rejects the request, the connection times out, or a stateless reset
+//! follows a NAT rebind that migration could not survive — the call fails
+//! with [`Error::QuicSessionClosed`].  Idempotent calls (`get`, `list`,
+//! `exists`, `get_metadata`, `update_metadata`, `delete`, `health`, and
+//! policy reads) are re-sent on a fresh connection up to twice; writes only
+//! when `unprocessed` is set, i.e. the server guarantees it never acted on
+//! the request.  Other HTTP/3 failures are [`Error::H3`], carrying the
+//! [`H3ErrorCode`](crate::H3ErrorCode) the peer sent and whether one
+//! request stream or the whole connection failed.  The default retry
+//! classifier retries session loss, connection errors, and stream errors
+//! with a transient code.
+//!
+//! [`ClientConfig::quic_handshake`]: crate::ClientConfig::quic_handshake
+
+use crate::auth::AuthConfig;
+use crate::budget::Budget;
+use crate::chunking::ChunkSizing;
+use crate::client::Protocol;
+use crate::compression::{decompress, ACCEPT_ENCODING};
+use crate::config::ClientConfig;
+use crate::decoder::ResponseDecoder;
+use crate::downlevel;
 use crate::duration::parse_go_duration_ms;
-use crate::error::{error_from_http_status, Error, Result};
+use crate::error::{error_from_http_status, Error, Result, TimeoutStage};
+use crate::features::{check_acknowledged, ServerFeatures};
+use crate::in_flight::{InFlight, Slot};
+use crate::list_parser::collect_list;
+use crate::paging::{list_split, PageLimit};
+use crate::response::{record, start_timing, RawResponse, ResponseExt};
 use crate::rest_client::replication_policy_to_rest_json;
+use crate::retry::RetryPolicy;
+use crate::timestamp::parse_timestamp;
+use crate::timing::{timed, Phase};
+use crate::tls::TlsInfo;
 use crate::types::*;
 use bytes::{Buf, Bytes};
+use futures::stream::{BoxStream, StreamExt};
 use h3::client::SendRequest;
 use http::{HeaderMap, Method, Request, StatusCode};
-use quinn::{ClientConfig, Endpoint};
+use quinn::Endpoint;
+use rustls_pki_types::CertificateDer;
 use serde::Deserialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// TLS verification mode for QUIC connections
 #[derive(Debug, Clone, Copy)]
@@ -21,10 +101,122 @@ pub enum TlsVerification {
 }
 
 /// QUIC/HTTP3 client for go-objstore
+///
+/// Cloning is cheap: clones share the underlying QUIC endpoint, which is
+/// closed once the last clone is dropped.
+#[derive(Clone)]
 pub struct QuicClient {
     endpoint: Endpoint,
     server_addr: SocketAddr,
     server_name: String,
+    config: ClientConfig,
+    verify_server: bool,
+    /// Results of the latest handshake, shared by clones.
+    handshake: Arc<Mutex<Option<Handshake>>>,
+    in_flight: InFlight,
+    page_limit: PageLimit,
+    features: ServerFeatures,
+    budget: Budget,
+    _guard: Arc<EndpointGuard>,
+}
+
+/// The HTTP/3 connection a single call runs on.
+struct CallConnection {
+    send_request: SendRequest<h3_quinn::OpenStreams, Bytes>,
+    _slot: Option<Slot>,
+}
+
+impl std::ops::Deref for CallConnection {
+    type Target = SendRequest<h3_quinn::OpenStreams, Bytes>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.send_request
+    }
+}
+
+impl std::ops::DerefMut for CallConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.send_request
+    }
+}
+
+/// What [`QuicClient::tls_info`] reports about the latest handshake.
+#[derive(Debug, Clone, Default)]
+struct Handshake {
+    alpn: Option<String>,
+    peer_certificate: Option<CertificateDer<'static>>,
+}
+
+/// Retrying of failed QUIC handshakes.
+///
+/// UDP is often filtered or slow to come up in containerized and
+/// corporate networks, so a handshake may time out where TCP would
+/// connect.  Each attempt gets `timeout`; attempts that time out or are
+/// reset are retried with exponential backoff (with jitter) between
+/// `initial_backoff` and `max_backoff`.  Other failures, such as a
+/// certificate the client rejects, are returned at once.  When the
+/// attempts run out and `fallback` is set, the call fails with
+/// [`Error::QuicUnavailable`] suggesting that protocol instead of the
+/// last connection error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakeRetry {
+    /// Total handshake attempts per connection, including the first
+    /// (minimum 1).
+    pub attempts: u32,
+    /// How long a single attempt may take.
+    pub timeout: Duration,
+    /// Backoff before the first retry; doubled for each further retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for a single backoff.
+    pub max_backoff: Duration,
+    /// Protocol to suggest once the attempts are exhausted.
+    pub fallback: Option<Protocol>,
+}
+
+impl Default for HandshakeRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            timeout: Duration::from_secs(5),
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(2),
+            fallback: Some(Protocol::Rest),
+        }
+    }
+}
+
+impl HandshakeRetry {
+    /// Whether a failed attempt is worth repeating.
+    fn is_transient(error: &quinn::ConnectionError) -> bool {
+        matches!(
+            error,
+            quinn::ConnectionError::TimedOut | quinn::ConnectionError::Reset
+        )
+    }
+}
+
+/// Times a request is re-sent after the HTTP/3 session closed under it.
+const SESSION_REPLAYS: u32 = 2;
+
+/// When a request may be re-sent after [`Error::QuicSessionClosed`].
+#[derive(Debug, Clone, Copy)]
+enum Replay {
+    /// Idempotent requests: after any session loss.
+    Always,
+    /// Only when the server did not process the request (GOAWAY or
+    /// `H3_REQUEST_REJECTED`).
+    IfUnprocessed,
+}
+
+/// Closes the shared endpoint when the last [`QuicClient`] clone is dropped.
+struct EndpointGuard(Endpoint);
+
+impl Drop for EndpointGuard {
+    fn drop(&mut self) {
+        // Best-effort: signal connections to close. The runtime drives the
+        // actual shutdown; we do not block in Drop.
+        self.0.close(0u32.into(), b"client dropped");
+    }
 }
 
 impl QuicClient {
@@ -42,6 +234,66 @@ impl QuicClient {
         server_addr: SocketAddr,
         server_name: impl Into<String>,
         tls_verification: TlsVerification,
+    ) -> Result<Self> {
+        Self::new_with_auth(
+            server_addr,
+            server_name,
+            tls_verification,
+            AuthConfig::default(),
+        )
+        .await
+    }
+
+    /// Create a new QUIC/HTTP3 client from a [`ClientConfig`].
+    ///
+    /// `server_name` is resolved through the config's resolver (the first
+    /// address is dialled) and is also used as the TLS server name, so a
+    /// static override can point a certificate's name at a private address.
+    pub async fn new_with_config(
+        server_name: impl Into<String>,
+        port: u16,
+        tls_verification: TlsVerification,
+        config: ClientConfig,
+    ) -> Result<Self> {
+        let server_name = server_name.into();
+        let server_addr = config
+            .resolver
+            .lookup(&server_name, port)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Error::Configuration(format!("no addresses found for host {server_name}"))
+            })?;
+        Self::build(server_addr, server_name, tls_verification, config)
+    }
+
+    /// Create a new QUIC/HTTP3 client with custom TLS verification and
+    /// authentication configuration.
+    ///
+    /// The token, tenant, and extra headers are sent on every HTTP/3 request.
+    pub async fn new_with_auth(
+        server_addr: SocketAddr,
+        server_name: impl Into<String>,
+        tls_verification: TlsVerification,
+        auth: AuthConfig,
+    ) -> Result<Self> {
+        Self::build(
+            server_addr,
+            server_name.into(),
+            tls_verification,
+            ClientConfig {
+                auth,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn build(
+        server_addr: SocketAddr,
+        server_name: String,
+        tls_verification: TlsVerification,
+        config: ClientConfig,
     ) -> Result<Self> {
         let crypto = match tls_verification {
             TlsVerification::Enabled => {
@@ -57,25 +309,25 @@ impl QuicClient {
                         .add(cert)
                         .map_err(|e| Error::Tls(e.to_string()))?;
                 }
-                rustls::ClientConfig::builder_with_provider(Arc::new(
+                let builder = rustls::ClientConfig::builder_with_provider(Arc::new(
                     rustls::crypto::ring::default_provider(),
                 ))
                 .with_safe_default_protocol_versions()
                 .map_err(|e| Error::Tls(e.to_string()))?
-                .with_root_certificates(root_store)
-                .with_no_client_auth()
+                .with_root_certificates(root_store);
+                with_client_auth(builder, &config)?
             }
             TlsVerification::Disabled => {
                 // INSECURE: Skip certificate verification (testing only)
                 eprintln!("WARNING: TLS certificate verification is DISABLED. This is INSECURE and should only be used for testing!");
-                rustls::ClientConfig::builder_with_provider(Arc::new(
+                let builder = rustls::ClientConfig::builder_with_provider(Arc::new(
                     rustls::crypto::ring::default_provider(),
                 ))
                 .with_safe_default_protocol_versions()
                 .map_err(|e| Error::Tls(e.to_string()))?
                 .dangerous()
-                .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
-                .with_no_client_auth()
+                .with_custom_certificate_verifier(Arc::new(SkipServerVerification));
+                with_client_auth(builder, &config)?
             }
         };
 
@@ -85,7 +337,7 @@ impl QuicClient {
 
         let quic_crypto = quinn::crypto::rustls::QuicClientConfig::try_from(crypto)
             .map_err(|e| Error::Configuration(e.to_string()))?;
-        let mut client_config = ClientConfig::new(Arc::new(quic_crypto));
+        let mut client_config = quinn::ClientConfig::new(Arc::new(quic_crypto));
 
         let mut transport_config = quinn::TransportConfig::default();
         transport_config.max_idle_timeout(Some(
@@ -102,28 +354,232 @@ impl QuicClient {
         endpoint.set_default_client_config(client_config);
 
         Ok(Self {
+            _guard: Arc::new(EndpointGuard(endpoint.clone())),
             endpoint,
             server_addr,
-            server_name: server_name.into(),
+            server_name,
+            verify_server: matches!(tls_verification, TlsVerification::Enabled),
+            handshake: Arc::default(),
+            in_flight: InFlight::new(config.max_in_flight),
+            page_limit: PageLimit::new(config.max_page_size),
+            features: ServerFeatures::default(),
+            budget: Budget::new(config.memory_budget.clone()),
+            config,
         })
     }
 
-    /// Establish a connection and return an HTTP3 client
-    async fn connect(&self) -> Result<SendRequest<h3_quinn::OpenStreams, Bytes>> {
-        let conn = self
-            .endpoint
-            .connect(self.server_addr, &self.server_name)
-            .map_err(|e| Error::Configuration(e.to_string()))?
-            .await?;
+    /// Return a copy of this client that sends `X-Tenant-ID: <tenant_id>` on
+    /// every request.  The QUIC endpoint is shared with the original.
+    pub fn with_tenant(&self, tenant_id: impl Into<String>) -> Self {
+        let mut scoped = self.clone();
+        scoped.config.auth.tenant_id = Some(tenant_id.into());
+        scoped
+    }
+
+    /// Chunk sizing used by the streaming extension.
+    pub(crate) fn chunk_sizing(&self) -> ChunkSizing {
+        self.config.chunking
+    }
 
-        let h3_conn = h3_quinn::Connection::new(conn);
-        let (mut driver, send_request) = h3::client::new(h3_conn).await?;
+    /// The configured [`ResponseDecoder`](crate::decoder::ResponseDecoder).
+    pub(crate) fn response_decoder(&self) -> Option<Arc<dyn ResponseDecoder>> {
+        self.config.response_decoder.clone()
+    }
+
+    /// The page size list requests are split at.
+    pub(crate) fn page_limit(&self) -> &PageLimit {
+        &self.page_limit
+    }
+
+    /// Check that the server honours the write features `options` needs.
+    async fn require_features(&self, options: &RequestOptions) -> Result<()> {
+        self.features
+            .require(options, Protocol::Quic, || async {
+                Ok(self.health().with_headers().await?.1)
+            })
+            .await
+    }
+
+    /// Start an HTTP/3 request with the configured auth headers applied.
+    fn request_builder(&self) -> http::request::Builder {
+        let mut builder = Request::builder();
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(self.config.auth.to_header_map());
+        }
+        builder
+    }
+
+    /// The server address being dialled.
+    pub fn server_addr(&self) -> SocketAddr {
+        self.server_addr
+    }
+
+    /// The server name, also used for TLS verification.
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    /// `key` encoded for a request path per the configured
+    /// [`KeyEncoding`](crate::KeyEncoding).
+    fn key_path<'a>(&self, key: &'a str) -> Result<Cow<'a, str>> {
+        self.config.key_encoding.encode(key)
+    }
+
+    /// The endpoint as a URL, `https://<server name>:<port>`.
+    pub fn endpoint(&self) -> String {
+        format!("https://{}:{}", self.server_name, self.server_addr.port())
+    }
+
+    /// TLS settings, plus the ALPN protocol and server certificate of the
+    /// latest handshake once a request has been made.
+    pub fn tls_info(&self) -> TlsInfo {
+        let handshake = self
+            .handshake
+            .lock()
+            .map(|h| h.clone().unwrap_or_default())
+            .unwrap_or_default();
+        TlsInfo {
+            server_name: Some(self.server_name.clone()),
+            verify_server: self.verify_server,
+            client_certificate: self.config.client_identity.is_some(),
+            alpn: handshake.alpn,
+            peer_certificate: handshake.peer_certificate,
+        }
+    }
+
+    fn record_handshake(&self, conn: &quinn::Connection) {
+        let alpn = conn
+            .handshake_data()
+            .and_then(|d| d.downcast::<quinn::crypto::rustls::HandshakeData>().ok())
+            .and_then(|d| d.protocol)
+            .map(|p| String::from_utf8_lossy(&p).into_owned());
+        let peer_certificate = conn
+            .peer_identity()
+            .and_then(|i| i.downcast::<Vec<CertificateDer<'static>>>().ok())
+            .and_then(|certs| certs.into_iter().next());
+        if let Ok(mut handshake) = self.handshake.lock() {
+            *handshake = Some(Handshake {
+                alpn,
+                peer_certificate,
+            });
+        }
+    }
+
+    /// Complete a QUIC handshake, retrying per
+    /// [`ClientConfig::quic_handshake`].
+    async fn handshake(&self) -> Result<quinn::Connection> {
+        let Some(retry) = self.config.quic_handshake else {
+            let connecting = self
+                .endpoint
+                .connect(self.server_addr, &self.server_name)
+                .map_err(|e| Error::Configuration(e.to_string()))?;
+            return Ok(connecting.await?);
+        };
+        let backoff = RetryPolicy::default().with_backoff(retry.initial_backoff, retry.max_backoff);
+        let attempts = retry.attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let connecting = self
+                .endpoint
+                .connect(self.server_addr, &self.server_name)
+                .map_err(|e| Error::Configuration(e.to_string()))?;
+            let error = match tokio::time::timeout(retry.timeout, connecting).await {
+                Ok(Ok(conn)) => return Ok(conn),
+                Ok(Err(e)) if !HandshakeRetry::is_transient(&e) => return Err(e.into()),
+                Ok(Err(e)) => e,
+                Err(_) => quinn::ConnectionError::TimedOut,
+            };
+            if attempt >= attempts {
+                return Err(match retry.fallback {
+                    Some(suggest) => Error::QuicUnavailable {
+                        attempts,
+                        reason: error.to_string(),
+                        suggest,
+                    },
+                    None => error.into(),
+                });
+            }
+            tracing::debug!(
+                server = %self.server_addr,
+                attempt,
+                %error,
+                "QUIC handshake failed, retrying"
+            );
+            tokio::time::sleep(backoff.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Run `call`, re-sending it on a fresh connection when the HTTP/3
+    /// session closes under it (see [`Error::QuicSessionClosed`]) and
+    /// `replay` allows it.
+    async fn replaying<T, F, Fut>(&self, replay: Replay, call: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut replays = 0;
+        loop {
+            match call().await {
+                Err(Error::QuicSessionClosed {
+                    ref reason,
+                    unprocessed,
+                }) if replays < SESSION_REPLAYS
+                    && (unprocessed || matches!(replay, Replay::Always)) =>
+                {
+                    replays += 1;
+                    tracing::debug!(
+                        server = %self.server_addr,
+                        replays,
+                        reason,
+                        "HTTP/3 session closed, replaying request"
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Run one stage of a call within its
+    /// [`timeouts`](ClientConfig::timeouts) limit.
+    async fn within<T, E>(
+        &self,
+        stage: TimeoutStage,
+        fut: impl std::future::Future<Output = std::result::Result<T, E>>,
+    ) -> Result<T>
+    where
+        Error: From<E>,
+    {
+        self.config.timeouts.within(stage, fut).await
+    }
+
+    /// Establish a connection and return an HTTP3 client.  The connection
+    /// holds one of the client's
+    /// [`max_in_flight`](ClientConfig::max_in_flight) slots until dropped.
+    async fn connect(&self) -> Result<CallConnection> {
+        let slot = self.in_flight.acquire().await;
+        if self.config.collect_timings {
+            start_timing();
+        }
+        let (mut driver, send_request) = self
+            .within(TimeoutStage::Connect, async {
+                let handshake = self.within(TimeoutStage::TlsHandshake, self.handshake());
+                let conn = timed(Phase::Tls, handshake).await?;
+                self.record_handshake(&conn);
+                h3::client::new(h3_quinn::Connection::new(conn))
+                    .await
+                    .map_err(Error::from)
+            })
+            .await?;
 
         tokio::spawn(async move {
             let _ = futures::future::poll_fn(|cx| driver.poll_close(cx)).await;
         });
 
-        Ok(send_request)
+        Ok(CallConnection {
+            send_request,
+            _slot: slot,
+        })
     }
 
     /// Put an object into storage
@@ -132,13 +588,55 @@ impl QuicClient {
         key: &str,
         data: Bytes,
         metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.put_with_options(key, data, metadata, &RequestOptions::default())
+            .await
+    }
+
+    /// Put an object with per-call [`RequestOptions`].  A non-default
+    /// [`Durability`] is sent as an `X-Durability` header and a
+    /// [`region`](RequestOptions::region) as `X-Region`, and a
+    /// [`precondition`](RequestOptions::precondition) as `If-Match` or
+    /// `If-None-Match: *`; a returned `X-Consistency-Token` and `X-Region`
+    /// become [`PutResponse::consistency_token`] and [`PutResponse::region`];
+    /// `body_format` is REST-only and ignored.  A precondition or non-default
+    /// durability is only sent to servers that advertise support for it,
+    /// and the durability must be acknowledged (see
+    /// [`features`](crate::features)).
+    pub async fn put_with_options(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: Option<Metadata>,
+        options: &RequestOptions,
+    ) -> Result<PutResponse> {
+        let metadata = self.config.put_metadata(&data, metadata);
+        self.require_features(options).await?;
+        self.replaying(Replay::IfUnprocessed, || {
+            let data = data.clone();
+            let body = futures::stream::once(async { Ok(data) });
+            self.put_body(key, body, metadata.clone(), options)
+        })
+        .await
+    }
+
+    /// Put an object whose body arrives as a stream of chunks, each sent as
+    /// its own HTTP/3 DATA frame.
+    ///
+    /// Shared by [`put`](Self::put) and the streaming `put_stream` extension.
+    pub(crate) async fn put_body(
+        &self,
+        key: &str,
+        body: impl futures::Stream<Item = Result<Bytes>> + Send,
+        metadata: Option<Metadata>,
+        options: &RequestOptions,
     ) -> Result<PutResponse> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!(
             "https://{}/objects/{}",
             self.server_name,
-            urlencoding::encode(key)
+            self.key_path(key)?
         )
         .parse()
         .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
@@ -149,16 +647,33 @@ impl QuicClient {
             .and_then(|m| m.content_type.clone())
             .unwrap_or_else(|| "application/octet-stream".to_string());
 
-        let mut builder = Request::builder()
+        let mut builder = self
+            .request_builder()
             .method(Method::PUT)
             .uri(uri)
             .header("content-type", content_type);
+        for (name, value) in options.headers() {
+            builder = builder.header(name, value);
+        }
 
         if let Some(meta) = &metadata {
             if let Some(content_encoding) = &meta.content_encoding {
                 builder = builder.header("content-encoding", content_encoding);
             }
-            for (k, v) in &meta.custom {
+            if meta.custom.keys().any(|k| canonical_header_key(k) != *k) {
+                downlevel::warn_once(
+                    Protocol::Quic,
+                    "metadata key case",
+                    "custom metadata keys travel as X-Meta-* header names and are stored \
+                     in canonical header case (e.g. `my-key` becomes `My-Key`)",
+                );
+            }
+            for (name, value) in meta.web_headers() {
+                builder = builder.header(name, value);
+            }
+            // The web-serving fields travel as custom metadata too, so the
+            // server stores them.
+            for (k, v) in meta.wire_custom().iter() {
                 builder = builder.header(format!("X-Meta-{}", k), v);
             }
         }
@@ -167,37 +682,41 @@ impl QuicClient {
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .send_data(data)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        futures::pin_mut!(body);
+        while let Some(chunk) = body.next().await {
+            self.within(TimeoutStage::RequestSend, stream.send_data(chunk?))
+                .await?;
+        }
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
-        if response.status() == StatusCode::CREATED {
-            let etag = response
-                .headers()
-                .get("etag")
-                .and_then(|v| v.to_str().ok())
-                .map(String::from);
+        if self.config.accepts_write(response.status().as_u16()) {
+            check_acknowledged(key, options, response.headers())?;
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from)
+            };
 
             Ok(PutResponse {
                 success: true,
                 message: None,
-                etag,
+                etag: header("etag"),
+                consistency_token: header(CONSISTENCY_TOKEN_HEADER),
+                region: header(REGION_HEADER),
             })
         } else {
             Err(error_from_http_status(
@@ -210,36 +729,91 @@ impl QuicClient {
 
     /// Get an object from storage
     pub async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.get_with_options(key, &RequestOptions::default()).await
+    }
+
+    /// Get an object with per-call [`RequestOptions`]; see
+    /// [`RestClient::get_with_options`](crate::RestClient::get_with_options).
+    pub async fn get_with_options(
+        &self,
+        key: &str,
+        options: &RequestOptions,
+    ) -> Result<(Bytes, Metadata)> {
+        self.replaying(Replay::Always, || self.get_once(key, options))
+            .await
+    }
+
+    async fn get_once(&self, key: &str, options: &RequestOptions) -> Result<(Bytes, Metadata)> {
+        let (body, mut metadata) = self.get_body(key, options, self.config.decompress).await?;
+
+        let length = u64::try_from(metadata.size).ok().filter(|&size| size > 0);
+        let mut data = self.budget.collect(key, length, body).await?;
+
+        if metadata.size == 0 {
+            metadata.size = data.len() as i64;
+        }
+
+        if self.config.decompress {
+            data = decompress(&mut metadata, data)?;
+        }
+
+        Ok((data, metadata))
+    }
+
+    /// Open a get request for the streaming `get_stream` extension,
+    /// replaying it after a session loss like [`get`](Self::get).
+    pub(crate) async fn open_get(
+        &self,
+        key: &str,
+    ) -> Result<(BoxStream<'static, Result<Bytes>>, Metadata)> {
+        let options = RequestOptions::default();
+        self.replaying(Replay::Always, || self.get_body(key, &options, false))
+            .await
+    }
+
+    /// Issue a get request and return its body as a stream of chunks with
+    /// the object's metadata, asking for gzip / zstd encoding if
+    /// `accept_encoding`.  The stream owns the connection handle for its
+    /// lifetime.
+    async fn get_body(
+        &self,
+        key: &str,
+        options: &RequestOptions,
+        accept_encoding: bool,
+    ) -> Result<(BoxStream<'static, Result<Bytes>>, Metadata)> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!(
             "https://{}/objects/{}",
             self.server_name,
-            urlencoding::encode(key)
+            self.key_path(key)?
         )
         .parse()
         .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
 
-        let request = Request::builder()
-            .method(Method::GET)
-            .uri(uri)
+        let mut builder = self.request_builder().method(Method::GET).uri(uri);
+        for (name, value) in options.headers() {
+            builder = builder.header(name, value);
+        }
+        if accept_encoding {
+            builder = builder.header(http::header::ACCEPT_ENCODING, ACCEPT_ENCODING);
+        }
+        let request = builder
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -249,60 +823,61 @@ impl QuicClient {
             ));
         }
 
-        let mut metadata = metadata_from_headers(response.headers());
-
-        let mut data = Vec::new();
-        while let Some(mut chunk) = stream
-            .recv_data()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?
-        {
-            while chunk.has_remaining() {
-                let bytes = chunk.chunk();
-                data.extend_from_slice(bytes);
-                chunk.advance(bytes.len());
-            }
-        }
-
-        if metadata.size == 0 {
-            metadata.size = data.len() as i64;
-        }
-
-        Ok((Bytes::from(data), metadata))
+        let metadata = metadata_from_headers(response.headers())?;
+
+        let timeouts = self.config.timeouts;
+        let body =
+            futures::stream::try_unfold((client, stream), move |(client, mut stream)| async move {
+                match timeouts
+                    .within(TimeoutStage::BodyTransfer, stream.recv_data())
+                    .await?
+                {
+                    Some(mut chunk) => {
+                        let bytes = chunk.copy_to_bytes(chunk.remaining());
+                        Ok(Some((bytes, (client, stream))))
+                    }
+                    None => Ok(None),
+                }
+            });
+        Ok((body.boxed(), metadata))
     }
 
     /// Delete an object from storage
     pub async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.replaying(Replay::Always, || self.delete_once(key))
+            .await
+    }
+
+    async fn delete_once(&self, key: &str) -> Result<DeleteResponse> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!(
             "https://{}/objects/{}",
             self.server_name,
-            urlencoding::encode(key)
+            self.key_path(key)?
         )
         .parse()
         .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
 
-        let request = Request::builder()
+        let request = self
+            .request_builder()
             .method(Method::DELETE)
             .uri(uri)
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
         if response.status().is_success() {
             Ok(DeleteResponse {
@@ -320,36 +895,40 @@ impl QuicClient {
 
     /// Check if an object exists
     pub async fn exists(&self, key: &str) -> Result<bool> {
+        self.replaying(Replay::Always, || self.exists_once(key))
+            .await
+    }
+
+    async fn exists_once(&self, key: &str) -> Result<bool> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!(
             "https://{}/objects/{}",
             self.server_name,
-            urlencoding::encode(key)
+            self.key_path(key)?
         )
         .parse()
         .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
 
-        let request = Request::builder()
+        let request = self
+            .request_builder()
             .method(Method::HEAD)
             .uri(uri)
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
         if response.status() == StatusCode::NOT_FOUND {
             return Ok(false);
@@ -368,6 +947,44 @@ impl QuicClient {
 
     /// List objects with optional prefix filtering
     pub async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.list_with_options(list_req, &RequestOptions::default())
+            .await
+    }
+
+    /// List objects with per-call [`RequestOptions`].
+    pub async fn list_with_options(
+        &self,
+        list_req: ListRequest,
+        options: &RequestOptions,
+    ) -> Result<ListResponse> {
+        list_split(list_req, self.page_limit.get(), |req| async move {
+            self.replaying(Replay::Always, || async {
+                collect_list(
+                    self.list_body_with(&req, options).await?,
+                    self.response_decoder(),
+                )
+                .await
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Issue a list request and return the response body as a stream of
+    /// chunks.  The stream owns the connection handle for its lifetime.
+    pub(crate) async fn list_body(
+        &self,
+        list_req: &ListRequest,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        self.list_body_with(list_req, &RequestOptions::default())
+            .await
+    }
+
+    async fn list_body_with(
+        &self,
+        list_req: &ListRequest,
+        options: &RequestOptions,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
         let mut client = self.connect().await?;
 
         let mut url = format!("https://{}/objects", self.server_name);
@@ -398,26 +1015,26 @@ impl QuicClient {
             .parse()
             .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
 
-        let request = Request::builder()
-            .method(Method::GET)
-            .uri(uri)
+        let mut builder = self.request_builder().method(Method::GET).uri(uri);
+        for (name, value) in options.headers() {
+            builder = builder.header(name, value);
+        }
+        let request = builder
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -426,98 +1043,61 @@ impl QuicClient {
                 format!("Failed to list objects: {}", response.status()),
             ));
         }
-
-        let mut data = Vec::new();
-        while let Some(mut chunk) = stream
-            .recv_data()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?
-        {
-            while chunk.has_remaining() {
-                let bytes = chunk.chunk();
-                data.extend_from_slice(bytes);
-                chunk.advance(bytes.len());
-            }
-        }
-
-        #[derive(serde::Deserialize)]
-        struct RestListResponse {
-            objects: Vec<RestObjectResponse>,
-            common_prefixes: Option<Vec<String>>,
-            next_token: Option<String>,
-            truncated: bool,
-        }
-
-        #[derive(serde::Deserialize)]
-        struct RestObjectResponse {
-            #[allow(dead_code)]
-            key: String,
-            size: i64,
-            modified: Option<String>,
-            etag: Option<String>,
-            metadata: Option<HashMap<String, String>>,
-        }
-
-        let list_response: RestListResponse = serde_json::from_slice(&data)?;
-
-        Ok(ListResponse {
-            objects: list_response
-                .objects
-                .into_iter()
-                .map(|obj| ObjectInfo {
-                    key: obj.key,
-                    metadata: Metadata {
-                        content_type: None,
-                        content_encoding: None,
-                        size: obj.size,
-                        last_modified: obj.modified.and_then(|s| {
-                            chrono::DateTime::parse_from_rfc3339(&s)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&chrono::Utc))
-                        }),
-                        etag: obj.etag,
-                        custom: obj.metadata.unwrap_or_default(),
-                    },
-                })
-                .collect(),
-            common_prefixes: list_response.common_prefixes.unwrap_or_default(),
-            next_token: list_response.next_token,
-            truncated: list_response.truncated,
-        })
+        self.page_limit.observe(response.headers());
+
+        let timeouts = self.config.timeouts;
+        let body =
+            futures::stream::try_unfold((client, stream), move |(client, mut stream)| async move {
+                match timeouts
+                    .within(TimeoutStage::BodyTransfer, stream.recv_data())
+                    .await?
+                {
+                    Some(mut chunk) => {
+                        let bytes = chunk.copy_to_bytes(chunk.remaining());
+                        Ok(Some((bytes, (client, stream))))
+                    }
+                    None => Ok(None),
+                }
+            });
+        Ok(body.boxed())
     }
 
     /// Get metadata for an object via HEAD on `/objects/{key}`, reading headers.
     pub async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.replaying(Replay::Always, || self.get_metadata_once(key))
+            .await
+    }
+
+    async fn get_metadata_once(&self, key: &str) -> Result<Metadata> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!(
             "https://{}/objects/{}",
             self.server_name,
-            urlencoding::encode(key)
+            self.key_path(key)?
         )
         .parse()
         .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
 
-        let request = Request::builder()
+        let request = self
+            .request_builder()
             .method(Method::HEAD)
             .uri(uri)
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -527,17 +1107,24 @@ impl QuicClient {
             ));
         }
 
-        Ok(metadata_from_headers(response.headers()))
+        metadata_from_headers(response.headers())
     }
 
     /// Update metadata for an object via PATCH on `/objects/{key}`.
     pub async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.replaying(Replay::Always, || {
+            self.update_metadata_once(key, metadata.clone())
+        })
+        .await
+    }
+
+    async fn update_metadata_once(&self, key: &str, metadata: Metadata) -> Result<()> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!(
             "https://{}/objects/{}",
             self.server_name,
-            urlencoding::encode(key)
+            self.key_path(key)?
         )
         .parse()
         .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
@@ -555,40 +1142,38 @@ impl QuicClient {
                 serde_json::Value::String(content_encoding.clone()),
             );
         }
-        if !metadata.custom.is_empty() {
-            body.insert(
-                "custom".to_string(),
-                serde_json::to_value(&metadata.custom)?,
-            );
+        let custom = metadata.wire_custom();
+        if !custom.is_empty() {
+            body.insert("custom".to_string(), serde_json::to_value(&*custom)?);
         }
         let body = serde_json::to_vec(&serde_json::Value::Object(body))?;
 
-        let request = Request::builder()
+        let request = self
+            .request_builder()
             .method(Method::PATCH)
             .uri(uri)
             .header("content-type", "application/json")
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .send_data(Bytes::from(body))
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(
+            TimeoutStage::RequestSend,
+            stream.send_data(Bytes::from(body)),
+        )
+        .await?;
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -603,32 +1188,35 @@ impl QuicClient {
 
     /// Health check
     pub async fn health(&self) -> Result<HealthResponse> {
+        self.replaying(Replay::Always, || self.health_once()).await
+    }
+
+    async fn health_once(&self) -> Result<HealthResponse> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!("https://{}/health", self.server_name)
             .parse()
             .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
 
-        let request = Request::builder()
+        let request = self
+            .request_builder()
             .method(Method::GET)
             .uri(uri)
             .body(())
             .map_err(|e| Error::Configuration(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
 
         if response.status().is_success() {
             Ok(HealthResponse {
@@ -650,50 +1238,128 @@ impl QuicClient {
         method: Method,
         path: &str,
         body: Option<Vec<u8>>,
+    ) -> Result<(StatusCode, Vec<u8>)> {
+        let replay = if method == Method::POST {
+            Replay::IfUnprocessed
+        } else {
+            Replay::Always
+        };
+        self.replaying(replay, || {
+            self.request_json_once(method.clone(), path, body.clone())
+        })
+        .await
+    }
+
+    async fn request_json_once(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Vec<u8>>,
     ) -> Result<(StatusCode, Vec<u8>)> {
         let mut client = self.connect().await?;
 
         let uri: http::Uri = format!("https://{}{}", self.server_name, path)
             .parse()
-            .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
+            .map_err(|_: http::uri::InvalidUri| Error::InvalidUrl(url::ParseError::EmptyHost))?;
+
+        let mut builder = self.request_builder().method(method).uri(uri);
+        if body.is_some() {
+            builder = builder.header("content-type", "application/json");
+        }
+        let request = builder
+            .body(())
+            .map_err(|e| Error::Configuration(e.to_string()))?;
+
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
+
+        if let Some(body) = body {
+            self.within(
+                TimeoutStage::RequestSend,
+                stream.send_data(Bytes::from(body)),
+            )
+            .await?;
+        }
+
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
+
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
+
+        record(response.headers());
+
+        let status = response.status();
+
+        let mut data = Vec::new();
+        while let Some(mut chunk) = self
+            .within(TimeoutStage::BodyTransfer, stream.recv_data())
+            .await?
+        {
+            while chunk.has_remaining() {
+                let bytes = chunk.chunk();
+                data.extend_from_slice(bytes);
+                chunk.advance(bytes.len());
+            }
+        }
+
+        Ok((status, data))
+    }
+
+    /// Send a request to `path` (e.g. `/objects?prefix=a`) with the
+    /// client's auth headers applied, for server endpoints that have no
+    /// typed method yet.
+    ///
+    /// Error statuses are returned in the [`RawResponse`], not as errors.
+    pub async fn raw_request(
+        &self,
+        method: Method,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: Option<Bytes>,
+    ) -> Result<RawResponse> {
+        if !path.starts_with('/') {
+            return Err(Error::InvalidArgument(format!(
+                "raw request path must start with '/': {path}"
+            )));
+        }
+        let uri: http::Uri = format!("https://{}{}", self.server_name, path)
+            .parse()
+            .map_err(|e: http::uri::InvalidUri| Error::InvalidArgument(e.to_string()))?;
 
-        let mut builder = Request::builder().method(method).uri(uri);
-        if body.is_some() {
-            builder = builder.header("content-type", "application/json");
+        let mut builder = self.request_builder().method(method).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
         }
         let request = builder
             .body(())
-            .map_err(|e| Error::Configuration(e.to_string()))?;
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
 
-        let mut stream = client
-            .send_request(request)
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let mut client = self.connect().await?;
+        let mut stream = self
+            .within(TimeoutStage::RequestSend, client.send_request(request))
+            .await?;
 
         if let Some(body) = body {
-            stream
-                .send_data(Bytes::from(body))
-                .await
-                .map_err(|e| Error::H3(e.to_string()))?;
+            self.within(TimeoutStage::RequestSend, stream.send_data(body))
+                .await?;
         }
 
-        stream
-            .finish()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        self.within(TimeoutStage::RequestSend, stream.finish())
+            .await?;
 
-        let response = stream
-            .recv_response()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?;
+        let response = self
+            .within(TimeoutStage::ResponseHeaders, stream.recv_response())
+            .await?;
 
-        let status = response.status();
+        record(response.headers());
 
         let mut data = Vec::new();
-        while let Some(mut chunk) = stream
-            .recv_data()
-            .await
-            .map_err(|e| Error::H3(e.to_string()))?
+        while let Some(mut chunk) = self
+            .within(TimeoutStage::BodyTransfer, stream.recv_data())
+            .await?
         {
             while chunk.has_remaining() {
                 let bytes = chunk.chunk();
@@ -702,7 +1368,11 @@ impl QuicClient {
             }
         }
 
-        Ok((status, data))
+        Ok(RawResponse {
+            status: response.status().as_u16(),
+            headers: response.headers().clone(),
+            body: Bytes::from(data),
+        })
     }
 
     /// Archive an object to a different storage backend
@@ -833,8 +1503,8 @@ impl QuicClient {
             .collect())
     }
 
-    /// Apply all lifecycle policies, returning (policies_count, objects_processed)
-    pub async fn apply_policies(&self) -> Result<(i32, i32)> {
+    /// Apply all lifecycle policies.
+    pub async fn apply_policies(&self) -> Result<ApplyPoliciesResult> {
         let (status, data) = self
             .request_json(Method::POST, "/policies/apply", None)
             .await?;
@@ -847,16 +1517,7 @@ impl QuicClient {
             ));
         }
 
-        #[derive(Deserialize)]
-        struct ApplyResponse {
-            #[serde(default)]
-            policies_count: i32,
-            #[serde(default)]
-            objects_processed: i32,
-        }
-
-        let parsed: ApplyResponse = serde_json::from_slice(&data)?;
-        Ok((parsed.policies_count, parsed.objects_processed))
+        Ok(serde_json::from_slice(&data)?)
     }
 
     /// Add a replication policy
@@ -1010,18 +1671,30 @@ impl QuicClient {
     }
 }
 
-impl Drop for QuicClient {
-    fn drop(&mut self) {
-        // Best-effort: signal connections to close. The runtime drives the
-        // actual shutdown; we do not block in Drop.
-        self.endpoint.close(0u32.into(), b"client dropped");
-    }
+/// The form the Go server stores an `X-Meta-{key}` header key in: HTTP/3
+/// lowercases header names and Go canonicalises them (`my-key` → `My-Key`).
+fn canonical_header_key(key: &str) -> String {
+    let mut upper = true;
+    key.chars()
+        .map(|c| {
+            let c = if upper {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            };
+            upper = c == '-';
+            c
+        })
+        .collect()
 }
 
 /// Build a [`Metadata`] from QUIC/HTTP response headers, reading
 /// Content-Type, Content-Encoding, ETag, Content-Length, Last-Modified and
 /// any `X-Meta-*` custom headers.
-fn metadata_from_headers(headers: &HeaderMap) -> Metadata {
+/// Read object metadata from response headers, failing with
+/// [`Error::InvalidResponse`] on a malformed `Content-Length` or
+/// `Last-Modified`.
+pub(crate) fn metadata_from_headers(headers: &HeaderMap) -> Result<Metadata> {
     let header_str = |name: &str| {
         headers
             .get(name)
@@ -1029,18 +1702,23 @@ fn metadata_from_headers(headers: &HeaderMap) -> Metadata {
             .map(String::from)
     };
 
-    let size = headers
-        .get("content-length")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(0);
-
-    let last_modified = header_str("last-modified").and_then(|s| {
-        chrono::DateTime::parse_from_rfc2822(&s)
-            .or_else(|_| chrono::DateTime::parse_from_rfc3339(&s))
+    let size = match headers.get("content-length") {
+        Some(v) => v
+            .to_str()
             .ok()
-            .map(|dt| dt.with_timezone(&chrono::Utc))
-    });
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .and_then(|n| i64::try_from(n).ok())
+            .ok_or_else(|| Error::InvalidResponse(format!("malformed Content-Length: {v:?}")))?,
+        None => 0,
+    };
+
+    let last_modified =
+        match headers.get("last-modified") {
+            Some(v) => Some(v.to_str().ok().and_then(parse_timestamp).ok_or_else(|| {
+                Error::InvalidResponse(format!("malformed Last-Modified: {v:?}"))
+            })?),
+            None => None,
+        };
 
     let mut custom = HashMap::new();
     for (name, value) in headers.iter() {
@@ -1052,14 +1730,19 @@ fn metadata_from_headers(headers: &HeaderMap) -> Metadata {
         }
     }
 
-    Metadata {
+    Ok(Metadata {
         content_type: header_str("content-type"),
         content_encoding: header_str("content-encoding"),
         size,
         last_modified,
         etag: header_str("etag"),
         custom,
+        compressed_size: None,
+        cache_control: header_str("cache-control"),
+        content_disposition: header_str("content-disposition"),
+        content_language: header_str("content-language"),
     }
+    .lift_web_headers())
 }
 
 /// Wire representation of a replication policy returned by the QUIC server
@@ -1132,6 +1815,8 @@ struct QuicReplicationStatus {
     average_sync_duration: Option<String>,
     #[serde(default)]
     sync_count: i64,
+    #[serde(default)]
+    recent_errors: Option<Vec<ReplicationErrorWire>>,
 }
 
 fn quic_replication_policy_into(p: QuicReplicationPolicy) -> ReplicationPolicy {
@@ -1143,7 +1828,7 @@ fn quic_replication_policy_into(p: QuicReplicationPolicy) -> ReplicationPolicy {
         destination_backend: p.destination_backend,
         destination_settings: p.destination_settings.unwrap_or_default(),
         check_interval_seconds: p.check_interval,
-        last_sync_time: p.last_sync_time.and_then(parse_rfc3339),
+        last_sync_time: p.last_sync_time.as_deref().and_then(parse_timestamp),
         enabled: p.enabled,
         encryption: p.encryption,
         replication_mode: match p.replication_mode.as_deref() {
@@ -1175,18 +1860,31 @@ fn quic_replication_status_into(s: QuicReplicationStatus) -> ReplicationStatus {
         total_objects_deleted: s.total_objects_deleted,
         total_bytes_synced: s.total_bytes_synced,
         total_errors: s.total_errors,
-        last_sync_time: s.last_sync_time.and_then(parse_rfc3339),
+        last_sync_time: s.last_sync_time.as_deref().and_then(parse_timestamp),
         average_sync_duration_ms: parse_go_duration_ms(
             s.average_sync_duration.as_deref().unwrap_or(""),
         ),
         sync_count: s.sync_count,
+        recent_errors: s
+            .recent_errors
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect(),
     }
 }
 
-fn parse_rfc3339(s: String) -> Option<chrono::DateTime<chrono::Utc>> {
-    chrono::DateTime::parse_from_rfc3339(&s)
-        .ok()
-        .map(|dt| dt.with_timezone(&chrono::Utc))
+/// Finish a rustls client config, presenting the configured client
+/// certificate (mutual TLS) when there is one.  The certificate is resolved
+/// per handshake, so reloads apply to new connections.
+fn with_client_auth(
+    builder: rustls::ConfigBuilder<rustls::ClientConfig, rustls::client::WantsClientCert>,
+    config: &ClientConfig,
+) -> Result<rustls::ClientConfig> {
+    match &config.client_identity {
+        Some(identity) => Ok(builder.with_client_cert_resolver(identity.resolver())),
+        None => Ok(builder.with_no_client_auth()),
+    }
 }
 
 // Custom certificate verifier that skips verification (for testing only)
@@ -1244,6 +1942,13 @@ mod tests {
         // Basic sanity check - verifier should be constructable
     }
 
+    #[test]
+    fn test_canonical_header_key() {
+        assert_eq!(canonical_header_key("Owner"), "Owner");
+        assert_eq!(canonical_header_key("my-key"), "My-Key");
+        assert_eq!(canonical_header_key("camelCase"), "Camelcase");
+    }
+
     #[test]
     fn test_metadata_from_headers_quic() {
         let mut headers = HeaderMap::new();
@@ -1252,26 +1957,52 @@ mod tests {
         headers.insert("content-length", "7".parse().unwrap());
         headers.insert("etag", "\"abc\"".parse().unwrap());
         headers.insert("x-meta-owner", "alice".parse().unwrap());
+        headers.insert("x-meta-content-language", "de".parse().unwrap());
         headers.insert(
             "last-modified",
             "Mon, 06 May 2024 07:08:09 GMT".parse().unwrap(),
         );
 
-        let meta = metadata_from_headers(&headers);
+        let meta = metadata_from_headers(&headers).unwrap();
         assert_eq!(meta.content_type.as_deref(), Some("application/json"));
         assert_eq!(meta.content_encoding.as_deref(), Some("gzip"));
         assert_eq!(meta.size, 7);
         assert_eq!(meta.etag.as_deref(), Some("\"abc\""));
         assert_eq!(meta.custom.get("owner").map(String::as_str), Some("alice"));
+        // Web-serving fields stored as custom metadata are lifted out.
+        assert_eq!(meta.content_language.as_deref(), Some("de"));
+        assert_eq!(meta.custom.len(), 1);
         assert!(meta.last_modified.is_some());
     }
 
+    #[test]
+    fn test_metadata_from_headers_quic_rejects_malformed_fields() {
+        for (name, value) in [
+            ("content-length", "-1"),
+            ("content-length", "seven"),
+            ("last-modified", "yesterday"),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, value.parse().unwrap());
+            let err = metadata_from_headers(&headers).unwrap_err();
+            assert!(matches!(err, Error::InvalidResponse(_)), "{name}: {err:?}");
+        }
+    }
+
     /// A canned HTTP/3 response for a route.
     #[derive(Clone)]
     struct MockResponse {
         status: StatusCode,
         body: Vec<u8>,
         headers: Vec<(String, String)>,
+        /// Requests still to be answered by closing the connection with
+        /// `close_code` instead of responding.
+        closes: Arc<std::sync::atomic::AtomicUsize>,
+        close_code: u32,
+        /// Answer by resetting the request stream with this code.
+        reset: Option<h3::error::Code>,
+        /// Wait this long before sending the response headers.
+        delay: Duration,
     }
 
     impl MockResponse {
@@ -1280,9 +2011,34 @@ mod tests {
                 status: StatusCode::from_u16(status).unwrap(),
                 body: Vec::new(),
                 headers: Vec::new(),
+                closes: Arc::default(),
+                close_code: 0x100,
+                reset: None,
+                delay: Duration::ZERO,
             }
         }
 
+        fn closing(self, times: usize) -> Self {
+            self.closes
+                .store(times, std::sync::atomic::Ordering::SeqCst);
+            self
+        }
+
+        fn closing_with(mut self, times: usize, code: u32) -> Self {
+            self.close_code = code;
+            self.closing(times)
+        }
+
+        fn resetting(mut self, code: h3::error::Code) -> Self {
+            self.reset = Some(code);
+            self
+        }
+
+        fn delaying(mut self, delay: Duration) -> Self {
+            self.delay = delay;
+            self
+        }
+
         fn body(mut self, body: &str) -> Self {
             self.body = body.as_bytes().to_vec();
             self
@@ -1304,20 +2060,40 @@ mod tests {
 
     impl MockH3Server {
         async fn start(routes: HashMap<String, MockResponse>) -> Self {
+            Self::start_with_client_ca(routes, None).await
+        }
+
+        /// Start a server that, when `client_ca` is set, requires clients to
+        /// present a certificate issued by it (mutual TLS).
+        async fn start_with_client_ca(
+            routes: HashMap<String, MockResponse>,
+            client_ca: Option<rustls_pki_types::CertificateDer<'static>>,
+        ) -> Self {
             let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
             let cert_der = cert.cert.der().to_vec();
             let key_der = cert.key_pair.serialize_der();
 
             let cert_der = rustls_pki_types::CertificateDer::from(cert_der);
             let key_der = rustls_pki_types::PrivateKeyDer::try_from(key_der).unwrap();
-            let mut tls = rustls::ServerConfig::builder_with_provider(Arc::new(
-                rustls::crypto::ring::default_provider(),
-            ))
-            .with_safe_default_protocol_versions()
-            .unwrap()
-            .with_no_client_auth()
-            .with_single_cert(vec![cert_der], key_der)
-            .unwrap();
+            let provider = Arc::new(rustls::crypto::ring::default_provider());
+            let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+                .with_safe_default_protocol_versions()
+                .unwrap();
+            let builder = match client_ca {
+                Some(ca) => {
+                    let mut roots = rustls::RootCertStore::empty();
+                    roots.add(ca).unwrap();
+                    let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+                        Arc::new(roots),
+                        provider,
+                    )
+                    .build()
+                    .unwrap();
+                    builder.with_client_cert_verifier(verifier)
+                }
+                None => builder.with_no_client_auth(),
+            };
+            let mut tls = builder.with_single_cert(vec![cert_der], key_der).unwrap();
             tls.alpn_protocols = vec![b"h3".to_vec()];
 
             let quic_server_crypto =
@@ -1346,6 +2122,7 @@ mod tests {
                             Ok(c) => c,
                             Err(_) => return,
                         };
+                        let quic_conn = conn.clone();
                         let mut h3_conn: h3::server::Connection<_, Bytes> =
                             match h3::server::Connection::new(h3_quinn::Connection::new(conn)).await
                             {
@@ -1381,6 +2158,23 @@ mod tests {
                                 .or_else(|| routes.get(&format!("{} {}", method, req.uri().path())))
                                 .cloned()
                                 .unwrap_or_else(|| MockResponse::new(404));
+                            let close = route
+                                .closes
+                                .fetch_update(
+                                    std::sync::atomic::Ordering::SeqCst,
+                                    std::sync::atomic::Ordering::SeqCst,
+                                    |n| n.checked_sub(1),
+                                )
+                                .is_ok();
+                            if close {
+                                quic_conn.close(route.close_code.into(), b"shutting down");
+                                break;
+                            }
+                            if let Some(code) = route.reset {
+                                stream.stop_stream(code);
+                                continue;
+                            }
+                            tokio::time::sleep(route.delay).await;
 
                             let mut builder = Response::builder().status(route.status);
                             for (name, value) in &route.headers {
@@ -1460,7 +2254,7 @@ mod tests {
     // Disabled seam); no live server is required.
     //
     // Documented impl behaviors:
-    //  - PUT treats only HTTP 201 CREATED as success.
+    //  - PUT accepts any 2xx unless `ClientConfig::write_statuses` narrows it.
     //  - `exists` returns Ok(true) only for 200; any other status (404 or 5xx)
     //    returns Ok(false) and never errors.
     //  - The QUIC client performs NO client-side empty-key validation.
@@ -1480,8 +2274,10 @@ mod tests {
         )
         .await;
         let client = server.client().await;
-        let mut meta = Metadata::default();
-        meta.content_type = Some("application/octet-stream".to_string());
+        let meta = Metadata {
+            content_type: Some("application/octet-stream".to_string()),
+            ..Default::default()
+        };
         let put = client
             .put("up.bin", Bytes::from_static(b"payload"), Some(meta))
             .await
@@ -1490,6 +2286,17 @@ mod tests {
         assert_eq!(put.etag.as_deref(), Some("\"q1\""));
     }
 
+    #[tokio::test]
+    async fn quic_put_accepts_any_2xx() {
+        let server = one("PUT /objects/up.bin", MockResponse::new(204)).await;
+        let client = server.client().await;
+        let put = client
+            .put("up.bin", Bytes::from_static(b"d"), None)
+            .await
+            .unwrap();
+        assert!(put.success);
+    }
+
     #[tokio::test]
     async fn quic_put_error() {
         let server = one("PUT /objects/up.bin", MockResponse::new(500)).await;
@@ -1524,6 +2331,32 @@ mod tests {
         assert_eq!(meta.size, 7);
     }
 
+    #[tokio::test]
+    async fn quic_get_stream() {
+        let server = one(
+            "GET /objects/up.bin",
+            MockResponse::new(200)
+                .header("content-type", "application/octet-stream")
+                .body("payload"),
+        )
+        .await;
+        let client = server.client().await;
+        let (stream, meta) = client.get_stream("up.bin").await.unwrap();
+        assert_eq!(
+            meta.content_type.as_deref(),
+            Some("application/octet-stream")
+        );
+        let data: Vec<u8> = stream.map(|chunk| chunk.unwrap().to_vec()).concat().await;
+        assert_eq!(data, b"payload");
+
+        let missing = one("GET /objects/gone", MockResponse::new(404)).await;
+        let client = missing.client().await;
+        assert!(matches!(
+            client.get_stream("gone").await.map(|_| ()),
+            Err(Error::NotFound(_))
+        ));
+    }
+
     #[tokio::test]
     async fn quic_get_error() {
         let server = one("GET /objects/boom", MockResponse::new(500)).await;
@@ -1544,6 +2377,33 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn quic_get_respects_the_memory_budget() {
+        let big = "x".repeat(4096);
+        let server = MockH3Server::start(routes(vec![
+            ("GET /objects/big", MockResponse::new(200).body(&big)),
+            ("GET /objects/small", MockResponse::new(200).body("hello")),
+        ]))
+        .await;
+        let client = QuicClient::build(
+            server.addr,
+            "localhost".into(),
+            TlsVerification::Disabled,
+            ClientConfig {
+                memory_budget: Some(crate::MemoryBudget::new(1024)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(client.get("small").await.unwrap().0, "hello");
+        let err = client.get("big").await.unwrap_err();
+        assert!(
+            matches!(err, Error::PayloadTooLarge { limit: 1024, .. }),
+            "{err:?}"
+        );
+    }
+
     // ---- delete ----
 
     #[tokio::test]
@@ -1590,6 +2450,7 @@ mod tests {
             delimiter: Some("/".to_string()),
             max_results: Some(5),
             continue_from: Some("tok".to_string()),
+            ..Default::default()
         };
         let resp = client.list(req).await.unwrap();
         assert_eq!(resp.objects.len(), 1);
@@ -1609,6 +2470,147 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn quic_list_stream_follows_pages() {
+        use futures::StreamExt;
+
+        let server = MockH3Server::start(routes(vec![
+            (
+                "GET /objects?prefix=p%2F",
+                MockResponse::new(200).body(
+                    r#"{"objects":[{"key":"p/a","size":1}],"next_token":"t1","truncated":true}"#,
+                ),
+            ),
+            (
+                "GET /objects?prefix=p%2F&continue=t1",
+                MockResponse::new(200)
+                    .body(r#"{"objects":[{"key":"p/b","size":2}],"truncated":false}"#),
+            ),
+        ]))
+        .await;
+        let client = server.client().await;
+        let keys: Vec<String> = client
+            .list_stream(ListRequest {
+                prefix: Some("p/".to_string()),
+                ..Default::default()
+            })
+            .map(|r| r.unwrap().key)
+            .collect()
+            .await;
+        assert_eq!(keys, vec!["p/a", "p/b"]);
+    }
+
+    #[tokio::test]
+    async fn quic_with_headers_exposes_response_headers() {
+        use crate::response::ResponseExt;
+
+        let server = one(
+            "HEAD /objects/k",
+            MockResponse::new(200).header("x-cache", "hit"),
+        )
+        .await;
+        let client = server.client().await;
+        let (exists, headers) = client.exists("k").with_headers().await.unwrap();
+        assert!(exists);
+        assert_eq!(headers.get("x-cache"), Some("hit"));
+    }
+
+    #[tokio::test]
+    async fn quic_preserve_slashes_keeps_nested_keys_readable() {
+        let server = MockH3Server::start(routes(vec![
+            ("HEAD /objects/logs/2024/app.log", MockResponse::new(200)),
+            (
+                "HEAD /objects/logs%2F2024%2Fapp.log",
+                MockResponse::new(404),
+            ),
+        ]))
+        .await;
+        let config = ClientConfig {
+            key_encoding: crate::KeyEncoding::PreserveSlashes,
+            ..Default::default()
+        };
+        let nested = QuicClient::build(
+            server.addr,
+            "localhost".to_string(),
+            TlsVerification::Disabled,
+            config,
+        )
+        .unwrap();
+
+        assert!(nested.exists("logs/2024/app.log").await.unwrap());
+        assert!(!server
+            .client()
+            .await
+            .exists("logs/2024/app.log")
+            .await
+            .unwrap());
+        assert!(matches!(
+            nested.exists("logs/./app.log").await,
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    // ---- mutual TLS ----
+
+    /// A CA plus a client identity it issued.
+    fn client_ca_and_identity() -> (
+        rustls_pki_types::CertificateDer<'static>,
+        crate::tls::ClientIdentity,
+    ) {
+        let ca_key = rcgen::KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let client_key = rcgen::KeyPair::generate().unwrap();
+        let client = rcgen::CertificateParams::new(vec!["client".to_string()])
+            .unwrap()
+            .signed_by(&client_key, &ca, &ca_key)
+            .unwrap();
+        let identity = crate::tls::ClientIdentity::from_pem(
+            client.pem().as_bytes(),
+            client_key.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+        (ca.der().clone(), identity)
+    }
+
+    #[tokio::test]
+    async fn quic_mtls_presents_client_certificate() {
+        let (ca, identity) = client_ca_and_identity();
+        let server = MockH3Server::start_with_client_ca(
+            routes(vec![("HEAD /objects/k", MockResponse::new(200))]),
+            Some(ca),
+        )
+        .await;
+
+        let config = ClientConfig {
+            client_identity: Some(identity),
+            ..Default::default()
+        };
+        let client = QuicClient::build(
+            server.addr,
+            "localhost".to_string(),
+            TlsVerification::Disabled,
+            config,
+        )
+        .unwrap();
+        assert!(client.exists("k").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn quic_mtls_without_client_certificate_fails() {
+        let (ca, _) = client_ca_and_identity();
+        let server = MockH3Server::start_with_client_ca(
+            routes(vec![("HEAD /objects/k", MockResponse::new(200))]),
+            Some(ca),
+        )
+        .await;
+
+        let client = server.client().await;
+        assert!(client.exists("k").await.is_err());
+    }
+
     // ---- exists ----
 
     #[tokio::test]
@@ -1679,9 +2681,11 @@ mod tests {
     async fn quic_update_metadata_success() {
         let server = one("PATCH /objects/m.txt", MockResponse::new(200)).await;
         let client = server.client().await;
-        let mut update = Metadata::default();
-        update.content_type = Some("text/plain".to_string());
-        update.content_encoding = Some("gzip".to_string());
+        let mut update = Metadata {
+            content_type: Some("text/plain".to_string()),
+            content_encoding: Some("gzip".to_string()),
+            ..Default::default()
+        };
         update.custom.insert("k".to_string(), "v".to_string());
         client.update_metadata("m.txt", update).await.unwrap();
     }
@@ -1732,6 +2736,42 @@ mod tests {
         assert!(health.message.is_some());
     }
 
+    #[tokio::test]
+    async fn quic_tls_info_reports_latest_handshake() {
+        let server = one("GET /health", MockResponse::new(200)).await;
+        let client = server.client().await;
+        let before = client.tls_info();
+        assert_eq!((before.alpn, before.peer_certificate), (None, None));
+        assert!(client.endpoint().starts_with("https://localhost:"));
+
+        client.health().await.unwrap();
+        let after = client.tls_info();
+        assert_eq!(after.alpn.as_deref(), Some("h3"));
+        assert!(after.peer_certificate.is_some());
+        assert!(!after.verify_server);
+    }
+
+    // ---- raw_request ----
+
+    #[tokio::test]
+    async fn quic_raw_request_returns_status_headers_and_body() {
+        let server = one(
+            "DELETE /v2/sessions",
+            MockResponse::new(409)
+                .header("x-reason", "busy")
+                .body("locked"),
+        )
+        .await;
+        let client = server.client().await;
+        let resp = client
+            .raw_request(Method::DELETE, "/v2/sessions", &[("x-probe", "1")], None)
+            .await
+            .unwrap();
+        assert_eq!(resp.status, 409);
+        assert_eq!(resp.headers["x-reason"], "busy");
+        assert_eq!(resp.body, "locked");
+    }
+
     // ---- archive ----
 
     #[tokio::test]
@@ -1868,9 +2908,9 @@ mod tests {
         )
         .await;
         let client = server.client().await;
-        let (count, processed) = client.apply_policies().await.unwrap();
-        assert_eq!(count, 2);
-        assert_eq!(processed, 17);
+        let result = client.apply_policies().await.unwrap();
+        assert_eq!(result.policies_evaluated, 2);
+        assert_eq!(result.objects_processed, 17);
     }
 
     #[tokio::test]
@@ -2144,9 +3184,11 @@ mod tests {
         .await;
         let client = server.client().await;
 
-        let mut meta = Metadata::default();
-        meta.content_type = Some("application/octet-stream".to_string());
-        meta.content_encoding = Some("gzip".to_string());
+        let mut meta = Metadata {
+            content_type: Some("application/octet-stream".to_string()),
+            content_encoding: Some("gzip".to_string()),
+            ..Default::default()
+        };
         meta.custom.insert("owner".to_string(), "dave".to_string());
         client
             .put("obj", Bytes::from_static(b"payload"), Some(meta))
@@ -2190,7 +3232,8 @@ mod tests {
         // mocked transport: 400 InvalidArgument, 401 Unauthenticated,
         // 403 Forbidden, 404 NotFound, 409 AlreadyExists, 429 RateLimited,
         // 5xx OperationFailed.
-        let cases: [(u16, fn(&Error) -> bool); 7] = [
+        type IsVariant = fn(&Error) -> bool;
+        let cases: [(u16, IsVariant); 7] = [
             (400, |e| matches!(e, Error::InvalidArgument(_))),
             (401, |e| matches!(e, Error::Unauthenticated(_))),
             (403, |e| matches!(e, Error::Forbidden(_))),
@@ -2214,4 +3257,213 @@ mod tests {
         client.health().await.unwrap();
         client.close().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn quic_new_with_config_dials_resolver_override() {
+        let server = one("GET /health", MockResponse::new(200)).await;
+        let config = ClientConfig {
+            resolver: crate::Resolver::new().with_override("objstore.invalid", server.addr.ip()),
+            ..Default::default()
+        };
+        let client = QuicClient::new_with_config(
+            "objstore.invalid",
+            server.addr.port(),
+            TlsVerification::Disabled,
+            config,
+        )
+        .await
+        .unwrap();
+        let health = client.health().await.unwrap();
+        assert!(matches!(health.status, HealthStatus::Serving));
+    }
+
+    #[tokio::test]
+    async fn quic_handshake_retries_then_suggests_fallback() {
+        // Bound but never answered, so every handshake times out.
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let retry = HandshakeRetry {
+            attempts: 3,
+            timeout: Duration::from_millis(100),
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            fallback: Some(Protocol::Rest),
+        };
+        let client = |retry| {
+            QuicClient::build(
+                silent.local_addr().unwrap(),
+                "localhost".into(),
+                TlsVerification::Disabled,
+                ClientConfig {
+                    quic_handshake: Some(retry),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let started = std::time::Instant::now();
+        let err = client(retry).health().await.unwrap_err();
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert!(matches!(
+            err,
+            Error::QuicUnavailable {
+                attempts: 3,
+                suggest: Protocol::Rest,
+                ..
+            }
+        ));
+
+        let retry = HandshakeRetry {
+            attempts: 1,
+            fallback: None,
+            ..retry
+        };
+        let err = client(retry).health().await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::QuicConnection(quinn::ConnectionError::TimedOut)
+        ));
+    }
+
+    #[tokio::test]
+    async fn quic_replays_idempotent_requests_after_session_close() {
+        let server = MockH3Server::start(routes(vec![
+            (
+                "GET /objects/k",
+                MockResponse::new(200).body("v").closing(1),
+            ),
+            ("PUT /objects/k", MockResponse::new(201).closing(1)),
+            ("GET /health", MockResponse::new(200).closing(10)),
+        ]))
+        .await;
+        let client = server.client().await;
+
+        let (data, _) = client.get("k").await.unwrap();
+        assert_eq!(data, "v");
+
+        let err = client
+            .put("k", Bytes::from_static(b"v"), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::QuicSessionClosed {
+                unprocessed: false,
+                ..
+            }
+        ));
+
+        let err = client.health().await.unwrap_err();
+        assert!(matches!(err, Error::QuicSessionClosed { .. }));
+    }
+
+    #[tokio::test]
+    async fn quic_timeouts_name_the_stage() {
+        use crate::error::TimeoutStage;
+        use crate::timeouts::Timeouts;
+
+        let client = |addr, timeouts| {
+            QuicClient::build(
+                addr,
+                "localhost".into(),
+                TlsVerification::Disabled,
+                ClientConfig {
+                    timeouts,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        let short = Some(Duration::from_millis(100));
+
+        // Bound but never answered, so the handshake never completes.
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let silent = silent.local_addr().unwrap();
+        let connect = Timeouts {
+            connect: short,
+            ..Default::default()
+        };
+        let err = client(silent, connect).health().await.unwrap_err();
+        assert!(
+            matches!(err, Error::Timeout(TimeoutStage::Connect)),
+            "{err:?}"
+        );
+        let handshake = Timeouts {
+            connect: Some(Duration::from_secs(5)),
+            tls_handshake: short,
+            ..Default::default()
+        };
+        let err = client(silent, handshake).health().await.unwrap_err();
+        assert!(
+            matches!(err, Error::Timeout(TimeoutStage::TlsHandshake)),
+            "{err:?}"
+        );
+
+        let server = one(
+            "GET /objects/slow",
+            MockResponse::new(200)
+                .body("late")
+                .delaying(Duration::from_millis(500)),
+        )
+        .await;
+        let headers = Timeouts {
+            response_headers: short,
+            ..Default::default()
+        };
+        let err = client(server.addr, headers).get("slow").await.unwrap_err();
+        assert!(
+            matches!(err, Error::Timeout(TimeoutStage::ResponseHeaders)),
+            "{err:?}"
+        );
+        let patient = Timeouts {
+            response_headers: Some(Duration::from_secs(5)),
+            ..headers
+        };
+        let (data, _) = client(server.addr, patient).get("slow").await.unwrap();
+        assert_eq!(data, "late");
+    }
+
+    #[tokio::test]
+    async fn quic_h3_errors_keep_code_and_scope() {
+        use crate::error::{H3ErrorCode, H3ErrorScope};
+
+        let server = MockH3Server::start(routes(vec![
+            (
+                "GET /objects/busy",
+                MockResponse::new(200).resetting(h3::error::Code::H3_EXCESSIVE_LOAD),
+            ),
+            (
+                "GET /objects/bad",
+                MockResponse::new(200).closing_with(1, 0x106),
+            ),
+        ]))
+        .await;
+        let client = server.client().await;
+
+        let err = client.get("busy").await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::H3 {
+                    code: H3ErrorCode::EXCESSIVE_LOAD,
+                    scope: H3ErrorScope::Stream,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+
+        let err = client.get("bad").await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::H3 {
+                    code: H3ErrorCode::FRAME_ERROR,
+                    scope: H3ErrorScope::Connection,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
 }
@@ -0,0 +1,478 @@
+//! Random access to an object through `AsyncRead + AsyncSeek`.
+//!
+//! [`ObjectStoreClient::reader`] returns an [`ObjectReader`] that fetches
+//! the object in aligned blocks of [`ReaderOptions::block_size`] bytes and
+//! keeps the most recently used [`ReaderOptions::cache_blocks`] of them, so
+//! formats that make many small reads at scattered offsets (Parquet and ORC
+//! footers, zip central directories) cost one request per block instead of
+//! one per read.  A read spanning several missing blocks fetches them with
+//! a single request, and [`ObjectReader::prefetch`] coalesces a whole list
+//! of ranges (e.g. the column chunks a query needs) into as few requests as
+//! their adjacency allows:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//! use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let mut reader = client.reader("tables/orders.parquet").await?;
+//! let mut tail = [0u8; 8];
+//! reader.seek(SeekFrom::End(-8)).await?;
+//! reader.read_exact(&mut tail).await?;
+//! println!("{} requests so far", reader.requests());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The REST client fetches blocks with `Range` requests.  Other transports,
+//! and servers that answer a `Range` request with the whole object, have
+//! the reader download the object once and serve every read from it.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::collections::{HashMap, VecDeque};
+use std::io::SeekFrom;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+/// Block size and cache capacity of an [`ObjectReader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderOptions {
+    /// Bytes fetched per block; reads are rounded out to whole blocks.
+    /// Defaults to 64 KiB.
+    pub block_size: u64,
+    /// Blocks kept in memory.  Defaults to 64 (4 MiB at the default block
+    /// size).  A single read fetches at most this many blocks at once.
+    pub cache_blocks: usize,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        Self {
+            block_size: 64 * 1024,
+            cache_blocks: 64,
+        }
+    }
+}
+
+/// What a fetch returned.
+pub(crate) enum Fetched {
+    /// The bytes starting at an offset.
+    At(u64, Bytes),
+    /// The whole object.
+    Whole(Bytes),
+}
+
+pub(crate) type FetchFn =
+    Arc<dyn Fn(Range<u64>) -> BoxFuture<'static, Result<Fetched>> + Send + Sync>;
+
+/// Least-recently-used cache of fetched blocks.
+struct BlockCache {
+    block_size: u64,
+    capacity: usize,
+    blocks: HashMap<u64, Bytes>,
+    /// Block indexes, least recently used first.
+    order: VecDeque<u64>,
+    whole: Option<Bytes>,
+}
+
+impl BlockCache {
+    fn contains(&self, block: u64) -> bool {
+        self.whole.is_some() || self.blocks.contains_key(&block)
+    }
+
+    fn touch(&mut self, block: u64) {
+        if let Some(i) = self.order.iter().position(|&b| b == block) {
+            self.order.remove(i);
+            self.order.push_back(block);
+        }
+    }
+
+    fn insert(&mut self, fetched: Fetched, size: u64) {
+        let (start, data) = match fetched {
+            Fetched::Whole(data) => {
+                self.whole = Some(data);
+                self.blocks.clear();
+                self.order.clear();
+                return;
+            }
+            Fetched::At(start, data) => (start, data),
+        };
+        let end = start + data.len() as u64;
+        // Only keep whole blocks (or the object's short last block).
+        let mut block = start.div_ceil(self.block_size);
+        loop {
+            let from = block * self.block_size;
+            let to = (from + self.block_size).min(size);
+            if from >= to || to > end {
+                break;
+            }
+            let chunk = data.slice((from - start) as usize..(to - start) as usize);
+            if self.blocks.insert(block, chunk).is_none() {
+                self.order.push_back(block);
+            } else {
+                self.touch(block);
+            }
+            while self.order.len() > self.capacity {
+                if let Some(evicted) = self.order.pop_front() {
+                    self.blocks.remove(&evicted);
+                }
+            }
+            block += 1;
+        }
+    }
+
+    /// Copy cached bytes from `pos` into `buf`, returning how many.
+    fn read_at(&mut self, pos: u64, buf: &mut ReadBuf<'_>) -> usize {
+        if let Some(whole) = &self.whole {
+            let from = (pos as usize).min(whole.len());
+            let n = buf.remaining().min(whole.len() - from);
+            buf.put_slice(&whole[from..from + n]);
+            return n;
+        }
+        let mut copied = 0;
+        while buf.remaining() > 0 {
+            let at = pos + copied as u64;
+            let block = at / self.block_size;
+            let Some(data) = self.blocks.get(&block) else {
+                break;
+            };
+            let offset = (at - block * self.block_size) as usize;
+            if offset >= data.len() {
+                break;
+            }
+            let n = buf.remaining().min(data.len() - offset);
+            buf.put_slice(&data[offset..offset + n]);
+            copied += n;
+            self.touch(block);
+        }
+        copied
+    }
+}
+
+/// Seekable reader over one object.  See the [module docs](self).
+pub struct ObjectReader {
+    key: String,
+    size: u64,
+    pos: u64,
+    cache: BlockCache,
+    fetch: FetchFn,
+    pending: Option<BoxFuture<'static, Result<Fetched>>>,
+    requests: u64,
+}
+
+impl ObjectReader {
+    pub(crate) fn new(key: &str, size: u64, options: ReaderOptions, fetch: FetchFn) -> Self {
+        Self {
+            key: key.to_string(),
+            size,
+            pos: 0,
+            cache: BlockCache {
+                block_size: options.block_size.max(1),
+                capacity: options.cache_blocks.max(1),
+                blocks: HashMap::new(),
+                order: VecDeque::new(),
+                whole: None,
+            },
+            fetch,
+            pending: None,
+            requests: 0,
+        }
+    }
+
+    /// The object's key.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The object's size in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Requests made so far.
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// The byte range of the blocks from `first` up to (not including)
+    /// `last`.
+    fn block_range(&self, first: u64, last: u64) -> Range<u64> {
+        let bs = self.cache.block_size;
+        first * bs..(last * bs).min(self.size)
+    }
+
+    /// Missing blocks covering `ranges`, as runs of adjacent block indexes.
+    fn missing_runs(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        let bs = self.cache.block_size;
+        let mut blocks: Vec<u64> = ranges
+            .iter()
+            .map(|r| r.start..r.end.min(self.size))
+            .filter(|r| !r.is_empty())
+            .flat_map(|r| r.start / bs..(r.end - 1) / bs + 1)
+            .filter(|&b| !self.cache.contains(b))
+            .collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+
+        let max_run = self.cache.capacity as u64;
+        let mut runs: Vec<Range<u64>> = Vec::new();
+        for block in blocks {
+            match runs.last_mut() {
+                Some(run) if run.end == block && run.end - run.start < max_run => run.end += 1,
+                _ => runs.push(block..block + 1),
+            }
+        }
+        runs
+    }
+
+    /// Fetch the blocks covering `ranges` that are not cached yet, merging
+    /// adjacent ones into single requests issued concurrently.  Blocks
+    /// beyond the cache's capacity evict earlier ones.
+    pub async fn prefetch(&mut self, ranges: &[Range<u64>]) -> Result<()> {
+        let fetches: Vec<_> = self
+            .missing_runs(ranges)
+            .into_iter()
+            .map(|run| (self.fetch)(self.block_range(run.start, run.end)))
+            .collect();
+        self.requests += fetches.len() as u64;
+        for fetched in futures::future::try_join_all(fetches).await? {
+            self.cache.insert(fetched, self.size);
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ObjectReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectReader")
+            .field("key", &self.key)
+            .field("size", &self.size)
+            .field("pos", &self.pos)
+            .field("requests", &self.requests)
+            .finish()
+    }
+}
+
+impl AsyncRead for ObjectReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        loop {
+            if this.pos >= this.size || buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            let copied = this.cache.read_at(this.pos, buf);
+            if copied > 0 {
+                this.pos += copied as u64;
+                return Poll::Ready(Ok(()));
+            }
+            if this.pending.is_none() {
+                let end = (this.pos + buf.remaining() as u64).min(this.size);
+                let wanted = this.pos..end;
+                let run = this.missing_runs(std::slice::from_ref(&wanted)).remove(0);
+                this.pending = Some((this.fetch)(this.block_range(run.start, run.end)));
+                this.requests += 1;
+            }
+            let fetched = match this.pending.as_mut().map(|f| f.poll_unpin(cx)) {
+                Some(Poll::Ready(fetched)) => fetched,
+                _ => return Poll::Pending,
+            };
+            this.pending = None;
+            match fetched {
+                Ok(fetched) => this.cache.insert(fetched, this.size),
+                Err(e) => return Poll::Ready(Err(std::io::Error::other(e))),
+            }
+        }
+    }
+}
+
+impl AsyncSeek for ObjectReader {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let pos = match position {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.size.checked_add_signed(n),
+            SeekFrom::Current(n) => self.pos.checked_add_signed(n),
+        };
+        self.pos = pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek before the start of the object",
+            )
+        })?;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        Poll::Ready(Ok(self.pos))
+    }
+}
+
+impl ObjectStoreClient {
+    /// Open `key` for seekable, block-cached reads with default
+    /// [`ReaderOptions`].  See the [`reader`](crate::reader) module.
+    pub async fn reader(&self, key: &str) -> Result<ObjectReader> {
+        self.reader_with_options(key, ReaderOptions::default())
+            .await
+    }
+
+    /// Open `key` for seekable reads with the given block size and cache
+    /// capacity.
+    pub async fn reader_with_options(
+        &self,
+        key: &str,
+        options: ReaderOptions,
+    ) -> Result<ObjectReader> {
+        let size = u64::try_from(self.get_metadata(key).await?.size)
+            .map_err(|_| Error::InvalidResponse(format!("negative size for {key}")))?;
+        let client = self.clone();
+        let owned = key.to_string();
+        let fetch: FetchFn = Arc::new(move |range: Range<u64>| {
+            let client = client.clone();
+            let key = owned.clone();
+            async move {
+                match &client {
+                    ObjectStoreClient::Rest(rest) => match rest.get_range(&key, range).await? {
+                        (data, Some(start)) => Ok(Fetched::At(start, data)),
+                        // The server ignored the Range header.
+                        (data, None) => Ok(Fetched::Whole(data)),
+                    },
+                    _ => Ok(Fetched::Whole(client.get(&key).await?.0)),
+                }
+            }
+            .boxed()
+        });
+        Ok(ObjectReader::new(key, size, options, fetch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    fn object() -> Bytes {
+        (0..10_000u32)
+            .map(|i| (i % 256) as u8)
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// A reader over `object()` that serves ranges and counts bytes sent.
+    fn ranged(options: ReaderOptions) -> (ObjectReader, Arc<AtomicU64>) {
+        let data = object();
+        let sent = Arc::new(AtomicU64::new(0));
+        let counter = sent.clone();
+        let fetch: FetchFn = Arc::new(move |range: Range<u64>| {
+            let chunk = data.slice(range.start as usize..range.end as usize);
+            counter.fetch_add(chunk.len() as u64, Ordering::SeqCst);
+            async move { Ok(Fetched::At(range.start, chunk)) }.boxed()
+        });
+        let size = object().len() as u64;
+        (ObjectReader::new("k", size, options, fetch), sent)
+    }
+
+    #[tokio::test]
+    async fn small_reads_share_blocks() {
+        let options = ReaderOptions {
+            block_size: 1024,
+            cache_blocks: 4,
+        };
+        let (mut reader, _) = ranged(options);
+        let expected = object();
+        for offset in (0..1000u64).step_by(10) {
+            let mut buf = [0u8; 10];
+            reader.seek(SeekFrom::Start(offset)).await.unwrap();
+            reader.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf[..], &expected[offset as usize..offset as usize + 10]);
+        }
+        assert_eq!(reader.requests(), 1);
+
+        // A read spanning three missing blocks is one request.
+        let mut buf = vec![0u8; 2500];
+        reader.seek(SeekFrom::Start(1500)).await.unwrap();
+        reader.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], &expected[1500..4000]);
+        assert_eq!(reader.requests(), 2);
+
+        let mut tail = Vec::new();
+        reader.seek(SeekFrom::End(-16)).await.unwrap();
+        reader.read_to_end(&mut tail).await.unwrap();
+        assert_eq!(&tail[..], &expected[expected.len() - 16..]);
+        assert!(reader.seek(SeekFrom::Current(-20_000)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn prefetch_coalesces_adjacent_ranges() {
+        let options = ReaderOptions {
+            block_size: 100,
+            cache_blocks: 100,
+        };
+        let (mut reader, sent) = ranged(options);
+        reader
+            .prefetch(&[0..50, 150..320, 320..390, 5000..5010, 9990..10_000])
+            .await
+            .unwrap();
+        // Blocks 0-3 form one run; 50 and 99 are separate.
+        assert_eq!(reader.requests(), 3);
+        assert_eq!(sent.load(Ordering::SeqCst), 400 + 100 + 100);
+
+        let mut buf = [0u8; 200];
+        reader.seek(SeekFrom::Start(100)).await.unwrap();
+        reader.read_exact(&mut buf).await.unwrap();
+        assert_eq!(reader.requests(), 3);
+    }
+
+    #[tokio::test]
+    async fn rest_reader_uses_range_requests() {
+        let data = object();
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/metadata/t.parquet")
+            .with_status(200)
+            .with_body(r#"{"key":"t.parquet","size":10000}"#)
+            .create_async()
+            .await;
+        let tail = server
+            .mock("GET", "/objects/t.parquet")
+            .match_header("range", "bytes=8192-9999")
+            .with_status(206)
+            .with_header("content-range", "bytes 8192-9999/10000")
+            .with_body(&data[8192..])
+            .expect(1)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let mut reader = client
+            .reader_with_options(
+                "t.parquet",
+                ReaderOptions {
+                    block_size: 4096,
+                    cache_blocks: 8,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(reader.size(), 10_000);
+        let mut footer = [0u8; 8];
+        for _ in 0..3 {
+            reader.seek(SeekFrom::End(-8)).await.unwrap();
+            reader.read_exact(&mut footer).await.unwrap();
+        }
+        assert_eq!(&footer[..], &data[9992..]);
+        tail.assert_async().await;
+    }
+}
@@ -0,0 +1,185 @@
+//! Renaming objects.
+//!
+//! [`rename`] moves an object to a new key, keeping its data and
+//! metadata — the copy, delete, and error handling every application
+//! otherwise writes by hand:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let report = client.rename("uploads/tmp-7f3a", "photos/beach.jpg").await?;
+//! if !report.server_side {
+//!     println!("renamed by copying");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Over REST, [`ObjectStoreClient::rename`] first asks the server to rename
+//! in one `POST /rename` request, which servers that offer it perform
+//! atomically.  Servers without the endpoint, and every other transport,
+//! are renamed client-side: the object is copied to the new key — streamed
+//! on REST and QUIC, buffered elsewhere — and the old key is deleted once
+//! the copy is stored.  That path is not atomic.  Readers can see the
+//! object under both keys in between, a write to the old key during the
+//! copy is lost, and a failure after the copy leaves the object under both
+//! keys; it never leaves it under neither.
+//!
+//! An existing object at the destination is replaced.  Renaming a key to
+//! itself does nothing.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::Result;
+
+/// How [`rename`] moved the object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenameReport {
+    /// Whether the server renamed the object in a single request.
+    pub server_side: bool,
+}
+
+/// Rename `src` to `dst` by copying and deleting.  Works with any store;
+/// the object is buffered in memory.
+pub async fn rename(store: &dyn ObjectStore, src: &str, dst: &str) -> Result<RenameReport> {
+    if src != dst {
+        let (data, metadata) = store.get(src).await?;
+        store.put(dst, data, Some(metadata)).await?;
+        store.delete(src).await?;
+    }
+    Ok(RenameReport::default())
+}
+
+impl ObjectStoreClient {
+    /// Rename `src` to `dst`, atomically where the server supports it.  See
+    /// the [`rename`](crate::rename) module.
+    pub async fn rename(&self, src: &str, dst: &str) -> Result<RenameReport> {
+        if src == dst {
+            return Ok(RenameReport::default());
+        }
+        if let ObjectStoreClient::Rest(client) = self {
+            if client.rename(src, dst).await? {
+                return Ok(RenameReport { server_side: true });
+            }
+        }
+        match self {
+            ObjectStoreClient::Rest(_) | ObjectStoreClient::Quic(_) => {
+                let (body, metadata) = self.get_stream(src).await?;
+                self.put_stream(dst, body, Some(metadata)).await?;
+                self.delete(src).await?;
+                Ok(RenameReport::default())
+            }
+            _ => rename(self, src, dst).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::rest_client::RestClient;
+    use crate::test_support::MemoryStore;
+    use crate::types::*;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn copies_then_deletes() {
+        let store = MemoryStore::default();
+        let metadata = Metadata {
+            content_type: Some("image/jpeg".into()),
+            ..Default::default()
+        };
+        store.put("a", "data".into(), Some(metadata)).await.unwrap();
+
+        let report = rename(&store, "a", "b").await.unwrap();
+        assert!(!report.server_side);
+        assert!(!store.contains("a"));
+        let (data, metadata) = store.object("b").unwrap();
+        assert_eq!(data, "data");
+        assert_eq!(metadata.content_type.as_deref(), Some("image/jpeg"));
+    }
+
+    #[tokio::test]
+    async fn missing_source_leaves_destination_alone() {
+        let store = MemoryStore::default();
+        store.put("b", "keep".into(), None).await.unwrap();
+
+        let err = rename(&store, "a", "b").await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+        rename(&store, "b", "b").await.unwrap();
+        assert_eq!(store.data("b"), "keep");
+    }
+
+    #[tokio::test]
+    async fn rest_renames_server_side() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/rename")
+            .match_body(Matcher::Json(
+                serde_json::json!({"source": "tmp/1", "destination": "photos/1.jpg"}),
+            ))
+            .with_body(r#"{"message":"renamed"}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client.rename("tmp/1", "photos/1.jpg").await.unwrap();
+        assert!(report.server_side);
+    }
+
+    #[tokio::test]
+    async fn rest_falls_back_to_copy_and_delete() {
+        let mut server = mockito::Server::new_async().await;
+        // The router's bare 404: no rename endpoint.
+        server
+            .mock("POST", "/rename")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/tmp%2F1")
+            .with_header("content-type", "image/jpeg")
+            .with_body("jpeg bytes")
+            .create_async()
+            .await;
+        let copy = server
+            .mock("PUT", "/objects/photos%2F1.jpg")
+            .match_body(Matcher::Regex("jpeg bytes".into()))
+            .with_status(201)
+            .with_body(r#"{"message":"ok"}"#)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/objects/tmp%2F1")
+            .with_body(r#"{"message":"deleted"}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client.rename("tmp/1", "photos/1.jpg").await.unwrap();
+        assert!(!report.server_side);
+        copy.assert_async().await;
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_reports_a_missing_source() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/rename")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":"object not found"}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let err = client.rename("tmp/1", "photos/1.jpg").await.unwrap_err();
+        assert!(
+            matches!(err, Error::NotFound(ref key) if key == "tmp/1"),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Replicating a single object on demand.
+//!
+//! [`ObjectStoreClient::replicate_object`] pushes one object through an
+//! existing replication policy before returning, for applications that must
+//! not acknowledge a write until it is durable in the secondary region:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! client.put("orders/1234.json", Bytes::from("{}"), None).await?;
+//! client.replicate_object("orders/1234.json", "orders-dr").await?;
+//! // Safe to acknowledge the order now.
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Over REST, the object is copied on its own with one
+//! `POST /replication/replicate` request.  go-objstore servers do not
+//! serve that endpoint yet; against them, and over every other transport,
+//! this checks that the key is covered by the policy and exists, then runs
+//! a sequential sync of the whole policy, which copies the object along
+//! with anything else changed under the policy's prefix and takes as long
+//! as that sync does.  Failures of other objects in that sync are not
+//! errors; a failure naming `key`, or one the server does not attribute to
+//! any object, is.
+//!
+//! [`ObjectStoreClient::get_replication_errors`] lists the objects a policy
+//! recently failed to sync, newest first, from the error samples servers
+//! include in [`ReplicationStatus::recent_errors`].  go-objstore servers do
+//! not keep such samples yet and only report `total_errors`, so the list is
+//! empty against them; the per-object failures of a sync run are still
+//! available from [`SyncResult::object_errors`].
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+#[cfg(doc)]
+use crate::types::ReplicationStatus;
+use crate::types::{ReplicationError, SyncResult};
+
+impl ObjectStoreClient {
+    /// Replicate `key` through the replication policy `policy_id` and wait
+    /// for the copy to finish.  See the [`replicate`](crate::replicate)
+    /// module.
+    pub async fn replicate_object(&self, key: &str, policy_id: &str) -> Result<SyncResult> {
+        if let ObjectStoreClient::Rest(client) = self {
+            if let Some(result) = client.replicate_object(policy_id, key).await? {
+                if result.failed > 0 {
+                    return Err(Error::OperationFailed(format!(
+                        "replication policy {policy_id} failed for {key}: {}",
+                        result.errors.join("; ")
+                    )));
+                }
+                return Ok(result);
+            }
+        }
+
+        let policy = self.get_replication_policy(policy_id).await?;
+        if !key.starts_with(&policy.source_prefix) {
+            return Err(Error::InvalidArgument(format!(
+                "{key} is outside replication policy {policy_id} (prefix {:?})",
+                policy.source_prefix
+            )));
+        }
+        self.get_metadata(key).await?;
+
+        let result = self
+            .trigger_replication(Some(policy_id.to_string()), false, 1)
+            .await?;
+        let ours: Vec<String> = result
+            .object_errors()
+            .into_iter()
+            .filter(|e| e.key == key || e.key.is_empty())
+            .map(|e| e.error)
+            .collect();
+        let unattributed = usize::try_from(result.failed).unwrap_or(0) > result.errors.len();
+        if !ours.is_empty() || unattributed {
+            return Err(Error::OperationFailed(format!(
+                "replication policy {policy_id} failed, {key} may not be replicated: {}",
+                if ours.is_empty() {
+                    format!("{} unreported failure(s)", result.failed)
+                } else {
+                    ours.join("; ")
+                }
+            )));
+        }
+        Ok(result)
+    }
+
+    /// The most recent per-object failures of replication policy
+    /// `policy_id`, newest first, at most `limit` of them.  See the
+    /// [`replicate`](crate::replicate) module.
+    pub async fn get_replication_errors(
+        &self,
+        policy_id: &str,
+        limit: usize,
+    ) -> Result<Vec<ReplicationError>> {
+        let mut errors = self.get_replication_status(policy_id).await?.recent_errors;
+        errors.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        errors.truncate(limit);
+        Ok(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::{Matcher, ServerGuard};
+
+    async fn server(failed: i32, errors: &str) -> ServerGuard {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/replication/policies/dr")
+            .with_status(200)
+            .with_body(
+                r#"{"id":"dr","source_backend":"local","source_prefix":"orders/","destination_backend":"s3"}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/metadata/orders%2F1.json")
+            .with_status(200)
+            .with_body(r#"{"size":2}"#)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/replication/trigger")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"policy_id":"dr","parallel":false}"#.into(),
+            ))
+            .with_status(200)
+            .with_body(format!(
+                r#"{{"result":{{"policy_id":"dr","synced":1,"failed":{failed},"errors":{errors}}}}}"#
+            ))
+            .create_async()
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn syncs_the_covering_policy() {
+        let server = server(0, "[]").await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let result = client
+            .replicate_object("orders/1.json", "dr")
+            .await
+            .unwrap();
+        assert_eq!(result.synced, 1);
+        assert!(matches!(
+            client.replicate_object("users/1.json", "dr").await,
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn sync_failures_are_errors() {
+        for errors in [r#"["orders/1.json: put: timeout"]"#, r#"["timeout"]"#, "[]"] {
+            let server = server(1, errors).await;
+            let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+            let err = client
+                .replicate_object("orders/1.json", "dr")
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, Error::OperationFailed(_)),
+                "{errors}: {err:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn other_objects_failing_is_not_an_error() {
+        let server = server(1, r#"["orders/2.json: put: timeout"]"#).await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let result = client
+            .replicate_object("orders/1.json", "dr")
+            .await
+            .unwrap();
+        assert_eq!(result.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn rest_copies_the_object_alone() {
+        let mut server = mockito::Server::new_async().await;
+        let replicate = server
+            .mock("POST", "/replication/replicate")
+            .match_body(Matcher::Json(
+                serde_json::json!({"policy_id": "dr", "key": "orders/1.json"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"result":{"policy_id":"dr","synced":1,"failed":0,"errors":[]}}"#)
+            .create_async()
+            .await;
+        let sync = server
+            .mock("POST", "/replication/trigger")
+            .expect(0)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let result = client
+            .replicate_object("orders/1.json", "dr")
+            .await
+            .unwrap();
+        assert_eq!(result.synced, 1);
+        replicate.assert_async().await;
+        sync.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_reports_a_missing_object() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/replication/replicate")
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":"object not found"}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let err = client
+            .replicate_object("orders/1.json", "dr")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::NotFound(ref key) if key == "orders/1.json"),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn recent_errors_newest_first() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/replication/status/dr")
+            .with_status(200)
+            .with_body(
+                r#"{"policy_id":"dr","total_errors":9,"recent_errors":[
+                    {"key":"a","error":"timeout","timestamp":"2026-10-15T09:00:00Z"},
+                    {"key":"b","error":"denied","timestamp":"2026-10-15T11:00:00Z"},
+                    {"key":"c","error":"timeout","timestamp":"2026-10-15T10:00:00Z"}]}"#,
+            )
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let errors = client.get_replication_errors("dr", 2).await.unwrap();
+        let keys: Vec<_> = errors.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, ["b", "c"]);
+        assert_eq!(errors[0].error, "denied");
+    }
+
+    #[test]
+    fn sync_errors_split_into_objects() {
+        let result = SyncResult {
+            policy_id: "dr".into(),
+            synced: 0,
+            deleted: 0,
+            failed: 2,
+            bytes_total: 0,
+            duration_ms: 0,
+            errors: vec![
+                "orders/1.json: put: timeout".into(),
+                "dr: no backend".into(),
+            ],
+        };
+        let errors = result.object_errors();
+        assert_eq!(errors[0].key, "orders/1.json");
+        assert_eq!(errors[0].error, "put: timeout");
+        assert_eq!(errors[1].key, "dr");
+    }
+}
@@ -0,0 +1,251 @@
+//! Host name resolution shared by the network transports.
+//!
+//! By default host names are resolved with the system resolver.  A
+//! [`Resolver`] can pin individual hosts to fixed addresses (like curl's
+//! `--resolve`) and/or delegate to a custom [`Resolve`] implementation, which
+//! is useful in test environments and split-horizon DNS setups where the TLS
+//! server name does not resolve publicly.
+//!
+//! The TLS server name and HTTP `Host` / `:authority` are left untouched;
+//! only the socket address that is dialled changes.
+//!
+//! ```no_run
+//! use go_objstore::{ClientConfig, ObjectStoreClient, Resolver};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     resolver: Resolver::new().with_override("objstore.internal", "10.0.0.7".parse()?),
+//!     ..Default::default()
+//! };
+//! let client = ObjectStoreClient::rest_with_config("http://objstore.internal:8080", config)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::config::ClientConfig;
+use crate::error::{Error, Result};
+use crate::timing::{ConnectTimingLayer, TimedResolve};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// A custom host name resolver.
+#[async_trait]
+pub trait Resolve: Send + Sync {
+    /// Resolve `host` to one or more IP addresses.
+    async fn resolve(&self, host: &str) -> std::io::Result<Vec<IpAddr>>;
+}
+
+/// Host resolution settings: static overrides, then an optional custom
+/// resolver, then the system resolver.
+#[derive(Clone, Default)]
+pub struct Resolver {
+    overrides: HashMap<String, Vec<IpAddr>>,
+    custom: Option<Arc<dyn Resolve>>,
+}
+
+impl fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resolver")
+            .field("overrides", &self.overrides)
+            .field("custom", &self.custom.is_some())
+            .finish()
+    }
+}
+
+impl Resolver {
+    /// Create a resolver that uses the system resolver for every host.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pin `host` to `addr`.  Calling this repeatedly for the same host adds
+    /// further addresses, which are tried in order.
+    pub fn with_override(mut self, host: impl AsRef<str>, addr: IpAddr) -> Self {
+        self.overrides
+            .entry(normalize_host(host.as_ref()))
+            .or_default()
+            .push(addr);
+        self
+    }
+
+    /// Resolve hosts without an override through `resolver` instead of the
+    /// system resolver.
+    pub fn with_resolver(mut self, resolver: impl Resolve + 'static) -> Self {
+        self.custom = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Return true when neither overrides nor a custom resolver are set.
+    pub fn is_system(&self) -> bool {
+        self.overrides.is_empty() && self.custom.is_none()
+    }
+
+    /// Resolve `host` to socket addresses with the given `port`.
+    ///
+    /// IP literals are returned as-is.  An empty result is reported as an
+    /// error so callers always have at least one address to dial.
+    pub async fn lookup(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+        let host = normalize_host(host);
+
+        let addrs: Vec<SocketAddr> = if let Ok(ip) = host.parse::<IpAddr>() {
+            vec![SocketAddr::new(ip, port)]
+        } else if let Some(ips) = self.overrides.get(&host) {
+            ips.iter().map(|ip| SocketAddr::new(*ip, port)).collect()
+        } else if let Some(custom) = &self.custom {
+            custom
+                .resolve(&host)
+                .await?
+                .into_iter()
+                .map(|ip| SocketAddr::new(ip, port))
+                .collect()
+        } else {
+            tokio::net::lookup_host((host.as_str(), port))
+                .await?
+                .collect()
+        };
+
+        if addrs.is_empty() {
+            return Err(Error::Configuration(format!(
+                "no addresses found for host {host}"
+            )));
+        }
+        Ok(addrs)
+    }
+}
+
+/// Lower-case `host` and strip IPv6 brackets.
+fn normalize_host(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase()
+}
+
+/// Adapter plugging a [`Resolver`] into reqwest's connector.
+pub(crate) struct ReqwestResolver(pub(crate) Resolver);
+
+impl reqwest::dns::Resolve for ReqwestResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let resolver = self.0.clone();
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            // Port 0 is replaced by reqwest with the URL's port.
+            let addrs = resolver
+                .lookup(&host, 0)
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Build a reqwest client honouring `config`'s resolver and its connect
+/// and body timeouts.
+pub(crate) fn http_client(config: &ClientConfig) -> Result<reqwest::Client> {
+    let (resolver, collect_timings) = (&config.resolver, config.collect_timings);
+    let mut builder = reqwest::Client::builder();
+    if let Some(limit) = config.timeouts.connect {
+        builder = builder.connect_timeout(limit);
+    }
+    if let Some(limit) = config.timeouts.body_transfer {
+        builder = builder.read_timeout(limit);
+    }
+    if collect_timings {
+        // Route even system lookups through `Resolver` so they can be timed.
+        let inner = Arc::new(ReqwestResolver(resolver.clone()));
+        builder = builder
+            .dns_resolver(Arc::new(TimedResolve(inner)))
+            .connector_layer(ConnectTimingLayer);
+    } else if !resolver.is_system() {
+        builder = builder.dns_resolver(Arc::new(ReqwestResolver(resolver.clone())));
+    }
+    builder
+        .build()
+        .map_err(|e| Error::Configuration(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(IpAddr);
+
+    #[async_trait]
+    impl Resolve for Fixed {
+        async fn resolve(&self, _host: &str) -> std::io::Result<Vec<IpAddr>> {
+            Ok(vec![self.0])
+        }
+    }
+
+    struct Empty;
+
+    #[async_trait]
+    impl Resolve for Empty {
+        async fn resolve(&self, _host: &str) -> std::io::Result<Vec<IpAddr>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn override_wins_over_custom_resolver() {
+        let resolver = Resolver::new()
+            .with_override("Store.Example", "10.0.0.1".parse().unwrap())
+            .with_override("store.example", "10.0.0.2".parse().unwrap())
+            .with_resolver(Fixed("192.0.2.1".parse().unwrap()));
+
+        let addrs = resolver.lookup("store.example", 443).await.unwrap();
+        assert_eq!(
+            addrs,
+            vec![
+                "10.0.0.1:443".parse::<SocketAddr>().unwrap(),
+                "10.0.0.2:443".parse().unwrap()
+            ]
+        );
+
+        let other = resolver.lookup("other.example", 80).await.unwrap();
+        assert_eq!(other, vec!["192.0.2.1:80".parse::<SocketAddr>().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn ip_literals_bypass_resolution() {
+        let resolver = Resolver::new().with_resolver(Empty);
+        assert!(!resolver.is_system());
+        let addrs = resolver.lookup("[::1]", 9000).await.unwrap();
+        assert_eq!(addrs, vec!["[::1]:9000".parse::<SocketAddr>().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn empty_resolution_is_an_error() {
+        let resolver = Resolver::new().with_resolver(Empty);
+        let err = resolver.lookup("nowhere.example", 80).await.unwrap_err();
+        assert!(matches!(err, Error::Configuration(_)));
+    }
+
+    #[tokio::test]
+    async fn rest_requests_dial_overridden_address() {
+        // The host name does not resolve; the override points it at the
+        // local mock server.
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/health")
+            .match_header("host", mockito::Matcher::Regex("^objstore.invalid".into()))
+            .with_status(200)
+            .with_body(r#"{"status":"healthy"}"#)
+            .create_async()
+            .await;
+
+        let port = server.socket_address().port();
+        let config = crate::ClientConfig {
+            resolver: Resolver::new()
+                .with_override("objstore.invalid", "127.0.0.1".parse().unwrap()),
+            ..Default::default()
+        };
+        let client =
+            crate::RestClient::new_with_config(format!("http://objstore.invalid:{port}"), config)
+                .unwrap();
+        client.health().await.unwrap();
+        mock.assert_async().await;
+    }
+}
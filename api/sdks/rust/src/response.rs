@@ -0,0 +1,327 @@
+//! Raw response header passthrough.
+//!
+//! The typed API only models the headers it understands.  Wrapping any client
+//! call with [`ResponseExt::with_headers`] additionally returns the raw
+//! headers of the response that produced the result — HTTP response headers
+//! for REST, QUIC, and MCP, and response metadata (including trailers) for
+//! gRPC — so custom server headers such as cache hints or shard information
+//! can be read without waiting for a typed accessor:
+//!
+//! ```no_run
+//! use go_objstore::{ObjectStore, ObjectStoreClient, ResponseExt};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let ((data, _meta), headers) = client.get("report.csv").with_headers().await?;
+//! if let Some(shard) = headers.get("x-shard-id") {
+//!     println!("served by shard {shard}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! When an operation issues several requests (for example a paginated
+//! `list_stream`), the headers of the last response are returned.  The
+//! Unix-socket transport has no headers, so its responses yield an empty
+//! [`ResponseHeaders`].
+//!
+//! Clients configured with `collect_timings` also attach a [`Timing`]
+//! breakdown of the request; see the [`timing`](crate::timing) module.
+//!
+//! For endpoints with no typed method at all, `raw_request` (REST, QUIC)
+//! and `grpc_raw` (gRPC) send a request as given and return a
+//! [`RawResponse`].
+
+use crate::error::Result;
+use crate::timing::{Phase, Timing, TimingState};
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static CAPTURE: Arc<Mutex<Capture>>;
+}
+
+/// What an enclosing [`ResponseExt::with_headers`] call has seen so far.
+#[derive(Default)]
+struct Capture {
+    headers: Option<ResponseHeaders>,
+    timing: Option<TimingState>,
+}
+
+/// Raw headers of a response.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseHeaders {
+    map: HeaderMap,
+    timing: Option<Timing>,
+}
+
+impl ResponseHeaders {
+    /// The first value of header `name` (case-insensitive), if it is valid
+    /// UTF-8.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.map.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// Every UTF-8 value of header `name`, in the order received.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.map
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect()
+    }
+
+    /// Return true when no headers were captured.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// The underlying header map.
+    pub fn as_map(&self) -> &HeaderMap {
+        &self.map
+    }
+
+    /// Timing breakdown of the request, when the client was configured
+    /// with `collect_timings`.
+    pub fn timing(&self) -> Option<&Timing> {
+        self.timing.as_ref()
+    }
+}
+
+impl From<HeaderMap> for ResponseHeaders {
+    fn from(map: HeaderMap) -> Self {
+        Self { map, timing: None }
+    }
+}
+
+/// Response to a `raw_request` or `grpc_raw` call.
+#[derive(Debug, Clone, Default)]
+pub struct RawResponse {
+    /// HTTP status for `raw_request`, which returns error statuses rather
+    /// than failing; always `0` (gRPC `OK`) for `grpc_raw`, which fails
+    /// with [`Error::GrpcStatus`](crate::Error::GrpcStatus) instead.
+    pub status: u16,
+    /// Response headers, or gRPC response metadata.
+    pub headers: HeaderMap,
+    /// Response body, or the encoded gRPC response message.
+    pub body: Bytes,
+}
+
+impl RawResponse {
+    /// Return true for a 2xx HTTP status or a gRPC response.
+    pub fn is_success(&self) -> bool {
+        self.status == 0 || (200..300).contains(&self.status)
+    }
+}
+
+/// Capture the raw response headers of a client call.
+pub trait ResponseExt<T>: Future<Output = Result<T>> + Sized {
+    /// Run the call, returning its result together with the raw headers of
+    /// the response it received.
+    fn with_headers(self) -> impl Future<Output = Result<(T, ResponseHeaders)>> {
+        let slot = Arc::new(Mutex::new(Capture::default()));
+        let fut = CAPTURE.scope(slot.clone(), self);
+        async move {
+            let value = fut.await?;
+            let end = Instant::now();
+            let capture = slot.lock().map(|mut s| std::mem::take(&mut *s));
+            let Ok(capture) = capture else {
+                return Ok((value, ResponseHeaders::default()));
+            };
+            let mut headers = capture.headers.unwrap_or_default();
+            headers.timing = capture.timing.map(|t| t.finish(end));
+            Ok((value, headers))
+        }
+    }
+
+    /// Run the call, returning its result together with the timing of the
+    /// last request it issued, when the client was configured with
+    /// `collect_timings`.
+    fn with_timing(self) -> impl Future<Output = Result<(T, Option<Timing>)>> {
+        async move {
+            let (value, headers) = self.with_headers().await?;
+            Ok((value, headers.timing))
+        }
+    }
+}
+
+impl<T, F: Future<Output = Result<T>>> ResponseExt<T> for F {}
+
+/// Carry the enclosing capture, if any, into `fut`, so that requests a call
+/// hands to a task of its own are still recorded.
+pub(crate) fn carry<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let slot = CAPTURE.try_with(Arc::clone).ok();
+    async move {
+        match slot {
+            Some(slot) => CAPTURE.scope(slot, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+/// Apply `f` to the enclosing capture.  A no-op when nothing is capturing.
+fn with_capture(f: impl FnOnce(&mut Capture)) {
+    let _ = CAPTURE.try_with(|slot| {
+        if let Ok(mut slot) = slot.lock() {
+            f(&mut slot);
+        }
+    });
+}
+
+/// Record HTTP response headers for an enclosing [`ResponseExt::with_headers`]
+/// call.  A no-op when nothing is capturing.
+pub(crate) fn record(headers: &HeaderMap) {
+    with_capture(|capture| {
+        capture.headers = Some(ResponseHeaders::from(headers.clone()));
+        mark_first_byte(capture);
+    });
+}
+
+/// Start timing a request, replacing the timing of any earlier request in
+/// the same call.  Clients call this when `collect_timings` is set.
+pub(crate) fn start_timing() {
+    with_capture(|capture| capture.timing = Some(TimingState::new()));
+}
+
+/// Add a connection-setup phase to the request being timed.
+pub(crate) fn record_phase(phase: Phase, elapsed: Duration) {
+    with_capture(|capture| {
+        if let Some(timing) = capture.timing.as_mut() {
+            timing.record(phase, elapsed);
+        }
+    });
+}
+
+/// Mark the response headers of the request being timed as received, for
+/// transports that record headers only after reading the body.
+pub(crate) fn response_started() {
+    with_capture(mark_first_byte);
+}
+
+fn mark_first_byte(capture: &mut Capture) {
+    if let Some(timing) = capture.timing.as_mut() {
+        timing.first_byte();
+    }
+}
+
+/// Send an HTTP request, recording its response headers.
+pub(crate) async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+    let response = request.send().await?;
+    record(response.headers());
+    Ok(response)
+}
+
+/// Record gRPC response metadata (headers and, once merged, trailers).
+pub(crate) fn record_grpc(metadata: &tonic::metadata::MetadataMap) {
+    with_capture(|capture| {
+        capture.headers = Some(ResponseHeaders::from(grpc_headers(metadata)));
+        mark_first_byte(capture);
+    });
+}
+
+/// gRPC metadata as an HTTP header map (tonic uses an older `http`).
+pub(crate) fn grpc_headers(metadata: &tonic::metadata::MetadataMap) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in metadata.clone().into_headers().iter() {
+        if let (Ok(n), Ok(v)) = (
+            HeaderName::from_bytes(name.as_str().as_bytes()),
+            HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            map.append(n, v);
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn records_headers_only_inside_scope() {
+        let mut map = HeaderMap::new();
+        map.insert("x-shard", HeaderValue::from_static("7"));
+        map.append("x-multi", HeaderValue::from_static("a"));
+        map.append("x-multi", HeaderValue::from_static("b"));
+
+        // Outside a capture scope recording is a no-op.
+        record(&map);
+
+        let (value, headers) = async {
+            record(&map);
+            Ok(42)
+        }
+        .with_headers()
+        .await
+        .unwrap();
+        assert_eq!(value, 42);
+        assert_eq!(headers.get("X-Shard"), Some("7"));
+        assert_eq!(headers.get_all("x-multi"), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn no_response_yields_empty_headers() {
+        let ((), headers) = async { Ok(()) }.with_headers().await.unwrap();
+        assert!(headers.is_empty());
+        assert!(headers.timing().is_none());
+    }
+
+    #[tokio::test]
+    async fn timing_covers_last_request() {
+        let ((), headers) = async {
+            start_timing();
+            record_phase(Phase::Connect, Duration::from_secs(60));
+            record(&HeaderMap::new());
+            // A second request in the same call replaces the first.
+            start_timing();
+            record_phase(Phase::Dns, Duration::from_millis(1));
+            record(&HeaderMap::new());
+            Ok(())
+        }
+        .with_headers()
+        .await
+        .unwrap();
+        let timing = headers.timing().unwrap();
+        assert_eq!(timing.dns, Some(Duration::from_millis(1)));
+        assert_eq!(timing.connect, None);
+    }
+
+    #[tokio::test]
+    async fn timing_follows_requests_handed_to_other_tasks() {
+        let ((), timing) = async {
+            let task = tokio::spawn(carry(async {
+                start_timing();
+                record_phase(Phase::Tls, Duration::from_millis(3));
+                record(&HeaderMap::new());
+            }));
+            task.await.unwrap();
+            Ok(())
+        }
+        .with_timing()
+        .await
+        .unwrap();
+        assert_eq!(timing.unwrap().tls, Some(Duration::from_millis(3)));
+
+        // Outside a capture scope the carried future still runs.
+        tokio::spawn(carry(async { record(&HeaderMap::new()) }))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn grpc_metadata_is_converted() {
+        let mut md = tonic::metadata::MetadataMap::new();
+        md.insert("x-trailer", "done".parse().unwrap());
+        let ((), headers) = async {
+            record_grpc(&md);
+            Ok(())
+        }
+        .with_headers()
+        .await
+        .unwrap();
+        assert_eq!(headers.get("x-trailer"), Some("done"));
+    }
+}
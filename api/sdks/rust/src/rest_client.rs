@@ -1,16 +1,110 @@
+//! REST transport.
+//!
+//! [`RestClient`] talks to the go-objstore HTTP API with reqwest.
+//! [`RestClient::with_client`] reuses a `reqwest::Client` configured
+//! elsewhere (connection pool, TLS, proxy); the `_and_config` variant also
+//! takes a [`ClientConfig`].
+//!
+//! Gateways that only accept `multipart/form-data` uploads can be written
+//! to with [`BodyFormat::Form`]; streamed uploads stay streamed inside the
+//! form's `file` part:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::{BodyFormat, RequestOptions, RestClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = RestClient::new("http://localhost:8080")?;
+//! let options = RequestOptions::default().body_format(BodyFormat::Form);
+//! client
+//!     .put_with_options("hello.txt", Bytes::from("hi"), None, &options)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Several helpers try an optional server endpoint first — `POST /delete`,
+//! `POST /search`, `POST /tags`, `POST /rename`, `GET /usage` — and fall
+//! back to listing and per-object calls when it is missing.  A client
+//! remembers each endpoint the server answered 404, 405, or 501 for, and
+//! it and its clones go straight to the fallback on later calls.
+
+use crate::auth::{apply_auth, AuthConfig};
+use crate::budget::Budget;
+use crate::chunking::ChunkSizing;
+use crate::client::Protocol;
+use crate::compression::{decompress, ACCEPT_ENCODING};
+use crate::config::ClientConfig;
+use crate::decoder::{decode_metadata, ResponseDecoder};
+use crate::delete::DeleteResult;
 use crate::duration::parse_go_duration_ms;
 use crate::error::{error_from_http_status, Error, Result};
+use crate::features::{check_acknowledged, ServerFeatures};
+use crate::in_flight::{body_stream, InFlight};
+use crate::list_parser::collect_list;
+use crate::paging::{list_split, PageLimit};
+use crate::resolver::http_client;
+use crate::response::{start_timing, RawResponse, ResponseExt};
+use crate::timestamp::parse_timestamp;
+use crate::tls::TlsInfo;
+use crate::transform::{query_string, Transform, TransformCapabilities};
 use crate::types::*;
+use crate::usage::PrefixUsage;
 use bytes::Bytes;
-use reqwest::{Client, StatusCode};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use reqwest::{Client, Method, StatusCode};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
 
 /// REST client for go-objstore
 #[derive(Clone)]
 pub struct RestClient {
     base_url: String,
     client: Client,
+    config: ClientConfig,
+    in_flight: InFlight,
+    budget: Budget,
+    page_limit: PageLimit,
+    /// Transform capabilities, discovered on first use.
+    transforms: Arc<tokio::sync::OnceCell<Option<TransformCapabilities>>>,
+    features: ServerFeatures,
+    /// Optional endpoints the server turned out not to serve.
+    unsupported: Unsupported,
+}
+
+/// An optional endpoint that servers may not serve.
+#[derive(Debug, Clone, Copy)]
+enum Endpoint {
+    Search = 1,
+    Tags = 2,
+    Usage = 4,
+    Rename = 8,
+    DeleteBatch = 16,
+    Replicate = 32,
+}
+
+/// The optional endpoints a server answered 404, 405, or 501 for, so
+/// later calls fall back without asking again.  Shared by clones.
+#[derive(Debug, Clone, Default)]
+struct Unsupported(Arc<AtomicU8>);
+
+impl Unsupported {
+    fn contains(&self, endpoint: Endpoint) -> bool {
+        self.0.load(Ordering::Relaxed) & endpoint as u8 != 0
+    }
+
+    fn insert(&self, endpoint: Endpoint) {
+        self.0.fetch_or(endpoint as u8, Ordering::Relaxed);
+    }
+}
+
+/// Whether `status` means the server does not serve an optional endpoint.
+fn not_served(status: u16) -> bool {
+    matches!(status, 404 | 405 | 501)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,41 +117,7 @@ struct RestMetadata {
     custom: Option<HashMap<String, String>>,
 }
 
-/// Wire response from `GET /objects/{key}` list items.
-#[derive(Debug, Deserialize)]
-struct RestObjectResponse {
-    key: String,
-    size: i64,
-    modified: Option<String>,
-    etag: Option<String>,
-    metadata: Option<HashMap<String, String>>,
-}
-
-/// Wire response from `GET /metadata/{key}`.
-///
-/// The server's `RespondWithObject` sends an `ObjectResponse` JSON body whose
-/// `size` is the stored object size and `metadata` is the custom key/value map.
-/// The HTTP `Content-Length` header reflects the length of the JSON payload, not
-/// the stored object, so we must parse from the body.
-#[derive(Debug, Deserialize)]
-struct MetadataObjectResponse {
-    #[serde(default)]
-    content_type: Option<String>,
-    size: i64,
-    modified: Option<String>,
-    etag: Option<String>,
-    /// Custom metadata is returned as the `metadata` JSON field (not `custom`).
-    metadata: Option<HashMap<String, String>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct RestListResponse {
-    objects: Vec<RestObjectResponse>,
-    common_prefixes: Option<Vec<String>>,
-    next_token: Option<String>,
-    truncated: bool,
-}
-
+/// Wire response from `GET /health`.
 #[derive(Debug, Deserialize)]
 struct RestHealthResponse {
     status: String,
@@ -67,12 +127,106 @@ struct RestHealthResponse {
 impl RestClient {
     /// Create a new REST client
     pub fn new(base_url: impl Into<String>) -> Result<Self> {
-        let base_url = base_url.into();
-        let client = Client::builder()
-            .build()
-            .map_err(|e| Error::Configuration(e.to_string()))?;
+        Self::new_with_auth(base_url, AuthConfig::default())
+    }
+
+    /// Create a new REST client with authentication configuration.
+    pub fn new_with_auth(base_url: impl Into<String>, auth: AuthConfig) -> Result<Self> {
+        Self::new_with_config(
+            base_url,
+            ClientConfig {
+                auth,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create a new REST client from a [`ClientConfig`].
+    pub fn new_with_config(base_url: impl Into<String>, config: ClientConfig) -> Result<Self> {
+        Ok(Self {
+            base_url: base_url.into(),
+            client: http_client(&config)?,
+            in_flight: InFlight::new(config.max_in_flight),
+            budget: Budget::new(config.memory_budget.clone()),
+            page_limit: PageLimit::new(config.max_page_size),
+            transforms: Arc::default(),
+            features: ServerFeatures::default(),
+            unsupported: Unsupported::default(),
+            config,
+        })
+    }
 
-        Ok(Self { base_url, client })
+    /// Create a REST client on top of an existing [`reqwest::Client`].
+    ///
+    /// Use this to share a connection pool, custom TLS roots, or proxy
+    /// settings configured elsewhere in the application.
+    pub fn with_client(client: Client, base_url: impl Into<String>) -> Self {
+        Self::with_client_and_config(client, base_url, ClientConfig::default())
+    }
+
+    /// Create a REST client on top of an existing [`reqwest::Client`] with a
+    /// [`ClientConfig`].  `config.resolver` is ignored; name resolution is up
+    /// to the supplied client.
+    pub fn with_client_and_config(
+        client: Client,
+        base_url: impl Into<String>,
+        config: ClientConfig,
+    ) -> Self {
+        Self {
+            base_url: base_url.into(),
+            client,
+            in_flight: InFlight::new(config.max_in_flight),
+            budget: Budget::new(config.memory_budget.clone()),
+            page_limit: PageLimit::new(config.max_page_size),
+            transforms: Arc::default(),
+            features: ServerFeatures::default(),
+            unsupported: Unsupported::default(),
+            config,
+        }
+    }
+
+    /// Return a copy of this client that sends `X-Tenant-ID: <tenant_id>` on
+    /// every request.  The connection pool is shared with the original.
+    pub fn with_tenant(&self, tenant_id: impl Into<String>) -> Self {
+        let mut scoped = self.clone();
+        scoped.config.auth.tenant_id = Some(tenant_id.into());
+        scoped
+    }
+
+    /// Chunk sizing used by the streaming extension.
+    pub(crate) fn chunk_sizing(&self) -> ChunkSizing {
+        self.config.chunking
+    }
+
+    /// The [`memory_budget`](ClientConfig::memory_budget) buffered
+    /// downloads are read within.
+    pub(crate) fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// The configured [`ResponseDecoder`](crate::decoder::ResponseDecoder).
+    pub(crate) fn response_decoder(&self) -> Option<Arc<dyn ResponseDecoder>> {
+        self.config.response_decoder.clone()
+    }
+
+    /// The page size list requests are split at.
+    pub(crate) fn page_limit(&self) -> &PageLimit {
+        &self.page_limit
+    }
+
+    /// Start a request with the configured auth headers applied.
+    pub(crate) fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        if self.config.collect_timings {
+            start_timing();
+        }
+        apply_auth(self.client.request(method, url), &self.config.auth)
+    }
+
+    /// Send a request built with [`request`](Self::request) within the
+    /// client's [`max_in_flight`](ClientConfig::max_in_flight) limit and
+    /// [`timeouts`](ClientConfig::timeouts).
+    pub(crate) async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        self.in_flight.send(request, &self.config.timeouts).await
     }
 
     /// Put an object into storage
@@ -82,47 +236,94 @@ impl RestClient {
         data: Bytes,
         metadata: Option<Metadata>,
     ) -> Result<PutResponse> {
-        self.put_body(key, data.into(), metadata).await
+        self.put_with_options(key, data, metadata, &RequestOptions::default())
+            .await
+    }
+
+    /// Put an object with per-call [`RequestOptions`].
+    ///
+    /// With [`BodyFormat::Form`] the object is uploaded as
+    /// `multipart/form-data`: a `metadata` field carrying the metadata as
+    /// JSON, followed by a `file` part with the data.  A non-default
+    /// [`Durability`] is sent as an `X-Durability` header and a
+    /// [`region`](RequestOptions::region) as `X-Region`, and a
+    /// [`precondition`](RequestOptions::precondition) as `If-Match` or
+    /// `If-None-Match: *`; a returned `X-Consistency-Token` and `X-Region`
+    /// become [`PutResponse::consistency_token`] and [`PutResponse::region`].
+    /// A precondition or non-default durability is only sent to servers that
+    /// advertise support for it, and the durability must be acknowledged
+    /// (see [`features`](crate::features)).
+    pub async fn put_with_options(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: Option<Metadata>,
+        options: &RequestOptions,
+    ) -> Result<PutResponse> {
+        let metadata = self.config.put_metadata(&data, metadata);
+        self.put_body(key, data.into(), metadata, options).await
     }
 
     /// Put an object using an arbitrary request body (buffered or streamed).
     ///
     /// Shared by [`put`](Self::put) and the streaming `put_stream` extension;
-    /// a streaming body is sent with chunked transfer encoding.
+    /// a streaming body is sent with chunked transfer encoding, also when
+    /// wrapped in a multipart form.
     pub(crate) async fn put_body(
         &self,
         key: &str,
         body: reqwest::Body,
         metadata: Option<Metadata>,
+        options: &RequestOptions,
     ) -> Result<PutResponse> {
-        let url = format!("{}/objects/{}", self.base_url, urlencoding::encode(key));
+        let url = format!("{}/objects/{}", self.base_url, self.key_path(key)?);
+        self.features
+            .require(options, Protocol::Rest, || async {
+                Ok(self.health().with_headers().await?.1)
+            })
+            .await?;
 
-        let mut request = self.client.put(&url);
+        let mut request = self.request(Method::PUT, &url);
+        for (name, value) in options.headers() {
+            request = request.header(name, value);
+        }
 
-        // Apply the canonical X-Object-Metadata contract: Content-Type and
-        // Content-Encoding travel as standard HTTP headers, while the custom
-        // string->string map is JSON-encoded into X-Object-Metadata.
-        if let Some(meta) = &metadata {
-            for (name, value) in put_metadata_headers(meta) {
-                request = request.header(name, value);
+        match options.body_format {
+            BodyFormat::Raw => {
+                // Apply the canonical X-Object-Metadata contract: Content-Type
+                // and Content-Encoding travel as standard HTTP headers, while
+                // the custom string->string map is JSON-encoded into
+                // X-Object-Metadata.
+                if let Some(meta) = &metadata {
+                    for (name, value) in put_metadata_headers(meta) {
+                        request = request.header(name, value);
+                    }
+                }
+                request = request.body(body);
+            }
+            BodyFormat::Form => {
+                request = request.multipart(put_metadata_form(key, body, metadata.as_ref())?);
             }
         }
 
-        request = request.body(body);
-
-        let response = request.send().await?;
+        let response = self.send(request).await?;
 
-        if response.status() == StatusCode::CREATED {
-            let etag = response
-                .headers()
-                .get("etag")
-                .and_then(|v| v.to_str().ok())
-                .map(String::from);
+        if self.config.accepts_write(response.status().as_u16()) {
+            check_acknowledged(key, options, response.headers())?;
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from)
+            };
 
             Ok(PutResponse {
                 success: true,
                 message: None,
-                etag,
+                etag: header("etag"),
+                consistency_token: header(CONSISTENCY_TOKEN_HEADER),
+                region: header(REGION_HEADER),
             })
         } else {
             Err(error_from_http_status(
@@ -135,9 +336,26 @@ impl RestClient {
 
     /// Get an object from storage
     pub async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
-        let url = format!("{}/objects/{}", self.base_url, urlencoding::encode(key));
+        self.get_with_options(key, &RequestOptions::default()).await
+    }
 
-        let response = self.client.get(&url).send().await?;
+    /// Get an object with per-call [`RequestOptions`], e.g. a
+    /// [`min_consistency_token`](RequestOptions::min_consistency_token).
+    pub async fn get_with_options(
+        &self,
+        key: &str,
+        options: &RequestOptions,
+    ) -> Result<(Bytes, Metadata)> {
+        let url = format!("{}/objects/{}", self.base_url, self.key_path(key)?);
+
+        let mut request = self.request(Method::GET, &url);
+        for (name, value) in options.headers() {
+            request = request.header(name, value);
+        }
+        if self.config.decompress {
+            request = request.header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING);
+        }
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -147,18 +365,67 @@ impl RestClient {
             ));
         }
 
-        let metadata = metadata_from_headers(response.headers());
+        let mut metadata = metadata_from_headers(response.headers())?;
 
-        let data = response.bytes().await?;
+        let mut data = self.budget.read(key, response).await?;
+        if self.config.decompress {
+            data = decompress(&mut metadata, data)?;
+        }
 
         Ok((data, metadata))
     }
 
+    /// Get an object unless its ETag still matches `etag`, sending
+    /// `If-None-Match`.
+    ///
+    /// Returns `None` on `304 Not Modified`.  Servers that ignore the
+    /// header answer `200`; when that response's ETag matches, the body is
+    /// dropped unread and `None` is returned as well (see
+    /// [`crate::conditional`]).
+    pub async fn get_if_none_match(
+        &self,
+        key: &str,
+        etag: &str,
+    ) -> Result<Option<(Bytes, Metadata)>> {
+        let url = format!("{}/objects/{}", self.base_url, self.key_path(key)?);
+
+        let mut request = self
+            .request(Method::GET, &url)
+            .header(reqwest::header::IF_NONE_MATCH, etag);
+        if self.config.decompress {
+            request = request.header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING);
+        }
+        let response = self.send(request).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(error_from_http_status(
+                response.status().as_u16(),
+                Some(key),
+                format!("Failed to get object: {}", response.status()),
+            ));
+        }
+
+        let mut metadata = metadata_from_headers(response.headers())?;
+        if crate::conditional::etag_matches(metadata.etag.as_deref(), etag) {
+            return Ok(None);
+        }
+
+        let mut data = self.budget.read(key, response).await?;
+        if self.config.decompress {
+            data = decompress(&mut metadata, data)?;
+        }
+
+        Ok(Some((data, metadata)))
+    }
+
     /// Delete an object from storage
     pub async fn delete(&self, key: &str) -> Result<DeleteResponse> {
-        let url = format!("{}/objects/{}", self.base_url, urlencoding::encode(key));
+        let url = format!("{}/objects/{}", self.base_url, self.key_path(key)?);
 
-        let response = self.client.delete(&url).send().await?;
+        let response = self.send(self.request(Method::DELETE, &url)).await?;
 
         if response.status().is_success() {
             Ok(DeleteResponse {
@@ -175,7 +442,338 @@ impl RestClient {
     }
 
     /// List objects with optional prefix filtering
+    ///
+    /// The response body is parsed incrementally as it arrives rather than
+    /// buffered whole.
     pub async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.list_with_options(list_req, &RequestOptions::default())
+            .await
+    }
+
+    /// List objects with per-call [`RequestOptions`].
+    pub async fn list_with_options(
+        &self,
+        list_req: ListRequest,
+        options: &RequestOptions,
+    ) -> Result<ListResponse> {
+        self.list_pages_since(list_req, None, options).await
+    }
+
+    /// List objects modified at or after `since`.
+    ///
+    /// Sends `modified_since` so servers with a time-indexed listing can
+    /// skip older objects; servers without one ignore the parameter and
+    /// return a full page, so callers must still filter (see
+    /// [`recent::list_recent`](crate::recent::list_recent)).
+    pub async fn list_modified_since(
+        &self,
+        list_req: ListRequest,
+        since: DateTime<Utc>,
+    ) -> Result<ListResponse> {
+        self.list_pages_since(list_req, Some(since), &RequestOptions::default())
+            .await
+    }
+
+    /// A listing of up to `max_results` objects, fetched as pages within
+    /// the server's limit (see [`paging`](crate::paging)).
+    async fn list_pages_since(
+        &self,
+        list_req: ListRequest,
+        since: Option<DateTime<Utc>>,
+        options: &RequestOptions,
+    ) -> Result<ListResponse> {
+        list_split(list_req, self.page_limit.get(), |req| async move {
+            let body = self.list_body_with(&req, since, options).await?;
+            collect_list(body, self.response_decoder()).await
+        })
+        .await
+    }
+
+    /// One page of objects matching `query` from the server's `POST /search`
+    /// endpoint, continuing from `token`.  Returns `None` when the server
+    /// has no search endpoint (404, 405, or 501), so callers can fall back
+    /// to filtering listings (see [`query`](crate::query)).  Once the server
+    /// has answered so, later calls return `None` without a request.
+    pub async fn search(
+        &self,
+        query: &crate::query::MetadataQuery,
+        token: Option<&str>,
+    ) -> Result<Option<ListResponse>> {
+        if self.unsupported.contains(Endpoint::Search) {
+            return Ok(None);
+        }
+        let url = format!("{}/search", self.base_url);
+        let request = self
+            .request(Method::POST, &url)
+            .json(&query.search_body(token));
+        let response = self.send(request).await?;
+
+        if not_served(response.status().as_u16()) {
+            self.unsupported.insert(Endpoint::Search);
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(error_from_http_status(
+                response.status().as_u16(),
+                None,
+                format!("Failed to search objects: {}", response.status()),
+            ));
+        }
+        collect_list(body_stream(response), self.response_decoder())
+            .await
+            .map(Some)
+    }
+
+    /// Ask the server to merge `tags` into the custom metadata of every
+    /// object under `prefix` in one `POST /tags` request.
+    ///
+    /// Returns the number of objects updated, or `None` if the server has
+    /// no bulk tagging endpoint (404, 405, or 501), in which case callers
+    /// fall back to tagging objects one by one (see [`crate::tags`]).  Once
+    /// the server has answered so, later calls return `None` without a
+    /// request.
+    pub async fn tag_prefix(
+        &self,
+        prefix: &str,
+        tags: &HashMap<String, String>,
+    ) -> Result<Option<u64>> {
+        if self.unsupported.contains(Endpoint::Tags) {
+            return Ok(None);
+        }
+        #[derive(Deserialize)]
+        struct TagResponse {
+            updated: u64,
+        }
+
+        let url = format!("{}/tags", self.base_url);
+        let body = serde_json::json!({ "prefix": prefix, "tags": tags });
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
+
+        if not_served(response.status().as_u16()) {
+            self.unsupported.insert(Endpoint::Tags);
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(error_from_http_status(
+                response.status().as_u16(),
+                None,
+                format!("Failed to tag objects: {}", response.status()),
+            ));
+        }
+        let body: TagResponse = response.json().await?;
+        Ok(Some(body.updated))
+    }
+
+    /// Ask the server for the size and object count under `prefix`,
+    /// grouped `depth` levels deep, in one `GET /usage` request.
+    ///
+    /// Returns `None` if the server has no usage endpoint (404, 405, or
+    /// 501), in which case callers fall back to listing (see
+    /// [`crate::usage`]).  Once the server has answered so, later calls
+    /// return `None` without a request.
+    pub async fn usage(&self, prefix: &str, depth: usize) -> Result<Option<Vec<PrefixUsage>>> {
+        if self.unsupported.contains(Endpoint::Usage) {
+            return Ok(None);
+        }
+        #[derive(Deserialize)]
+        struct UsageResponse {
+            #[serde(default)]
+            groups: Vec<PrefixUsage>,
+        }
+
+        let url = format!(
+            "{}/usage?prefix={}&depth={depth}",
+            self.base_url,
+            urlencoding::encode(prefix)
+        );
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        if not_served(response.status().as_u16()) {
+            self.unsupported.insert(Endpoint::Usage);
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(error_from_http_status(
+                response.status().as_u16(),
+                None,
+                format!("Failed to get usage: {}", response.status()),
+            ));
+        }
+        let body: UsageResponse = response.json().await?;
+        Ok(Some(body.groups))
+    }
+
+    /// The transforms the server advertises at `GET /transforms`, or `None`
+    /// if it does not serve the endpoint.  Asked once; clones share the
+    /// answer.
+    pub async fn transform_capabilities(&self) -> Result<Option<TransformCapabilities>> {
+        self.transforms
+            .get_or_try_init(|| async {
+                let url = format!("{}/transforms", self.base_url);
+                let response = self.send(self.request(Method::GET, &url)).await?;
+
+                if not_served(response.status().as_u16()) {
+                    return Ok(None);
+                }
+                if !response.status().is_success() {
+                    return Err(error_from_http_status(
+                        response.status().as_u16(),
+                        None,
+                        format!("Failed to get transforms: {}", response.status()),
+                    ));
+                }
+                Ok(Some(response.json().await?))
+            })
+            .await
+            .cloned()
+    }
+
+    /// Get an object with `transforms` applied, sent as query parameters.
+    /// The transforms are not checked against
+    /// [`transform_capabilities`](Self::transform_capabilities).
+    pub async fn get_transformed(
+        &self,
+        key: &str,
+        transforms: &[Transform],
+    ) -> Result<(Bytes, Metadata)> {
+        let url = format!(
+            "{}/objects/{}?{}",
+            self.base_url,
+            self.key_path(key)?,
+            query_string(transforms)
+        );
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        if !response.status().is_success() {
+            return Err(error_from_http_status(
+                response.status().as_u16(),
+                Some(key),
+                format!("Failed to get transformed object: {}", response.status()),
+            ));
+        }
+
+        let metadata = metadata_from_headers(response.headers())?;
+        Ok((self.budget.read(key, response).await?, metadata))
+    }
+
+    /// Rename `src` to `dst` with `POST /rename`.  Returns `false` if the
+    /// server does not serve the endpoint; a 404 with a JSON error body is
+    /// the handler's, and means `src` does not exist.  Once the server has
+    /// answered so, later calls return `false` without a request.
+    pub async fn rename(&self, src: &str, dst: &str) -> Result<bool> {
+        if self.unsupported.contains(Endpoint::Rename) {
+            return Ok(false);
+        }
+        let url = format!("{}/rename", self.base_url);
+        let body = serde_json::json!({ "source": src, "destination": dst });
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
+
+        let json = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        match response.status().as_u16() {
+            status if not_served(status) && !(status == 404 && json) => {
+                self.unsupported.insert(Endpoint::Rename);
+                Ok(false)
+            }
+            _ if response.status().is_success() => Ok(true),
+            status => Err(error_from_http_status(
+                status,
+                Some(src),
+                format!("Failed to rename object: {}", response.status()),
+            )),
+        }
+    }
+
+    /// Delete `keys` with one `POST /delete` request, returning a result per
+    /// key in the same order, or `None` if the server does not serve the
+    /// endpoint.  A key the response does not mention was not deleted.
+    /// Once the server has answered that it lacks the endpoint, later calls
+    /// return `None` without a request.
+    pub async fn delete_batch(&self, keys: &[String]) -> Result<Option<Vec<DeleteResult>>> {
+        if self.unsupported.contains(Endpoint::DeleteBatch) {
+            return Ok(None);
+        }
+        #[derive(Deserialize)]
+        struct KeyError {
+            key: String,
+            #[serde(default)]
+            code: u16,
+            #[serde(default)]
+            message: String,
+        }
+        #[derive(Deserialize)]
+        struct BatchResponse {
+            #[serde(default)]
+            deleted: Vec<String>,
+            #[serde(default)]
+            errors: Vec<KeyError>,
+        }
+
+        let url = format!("{}/delete", self.base_url);
+        let body = serde_json::json!({ "keys": keys });
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
+
+        if not_served(response.status().as_u16()) {
+            self.unsupported.insert(Endpoint::DeleteBatch);
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(error_from_http_status(
+                response.status().as_u16(),
+                None,
+                format!("Failed to delete objects: {}", response.status()),
+            ));
+        }
+        let body: BatchResponse = response.json().await?;
+        let deleted: HashSet<String> = body.deleted.into_iter().collect();
+        let mut errors: HashMap<String, KeyError> = body
+            .errors
+            .into_iter()
+            .map(|e| (e.key.clone(), e))
+            .collect();
+        let results = keys
+            .iter()
+            .map(|key| {
+                let error = match errors.remove(key) {
+                    Some(e) => Some(error_from_http_status(e.code, Some(key), e.message)),
+                    None if deleted.contains(key) => None,
+                    None => Some(Error::InvalidResponse(format!(
+                        "bulk delete did not report {key}"
+                    ))),
+                };
+                DeleteResult {
+                    key: key.clone(),
+                    error,
+                }
+            })
+            .collect();
+        Ok(Some(results))
+    }
+
+    /// Issue a list request and return the raw response body stream.
+    pub(crate) async fn list_body(
+        &self,
+        list_req: &ListRequest,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        self.list_body_with(list_req, None, &RequestOptions::default())
+            .await
+    }
+
+    async fn list_body_with(
+        &self,
+        list_req: &ListRequest,
+        modified_since: Option<DateTime<Utc>>,
+        options: &RequestOptions,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
         let mut url = format!("{}/objects", self.base_url);
         let mut params = Vec::new();
 
@@ -195,12 +793,23 @@ impl RestClient {
             params.push(format!("token={}", urlencoding::encode(token)));
         }
 
+        if let Some(since) = modified_since {
+            params.push(format!(
+                "modified_since={}",
+                urlencoding::encode(&since.to_rfc3339_opts(SecondsFormat::Secs, true))
+            ));
+        }
+
         if !params.is_empty() {
             url.push('?');
             url.push_str(&params.join("&"));
         }
 
-        let response = self.client.get(&url).send().await?;
+        let mut request = self.request(Method::GET, &url);
+        for (name, value) in options.headers() {
+            request = request.header(name, value);
+        }
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -209,40 +818,16 @@ impl RestClient {
                 format!("Failed to list objects: {}", response.status()),
             ));
         }
+        self.page_limit.observe(response.headers());
 
-        let rest_response: RestListResponse = response.json().await?;
-
-        Ok(ListResponse {
-            objects: rest_response
-                .objects
-                .into_iter()
-                .map(|obj| ObjectInfo {
-                    key: obj.key,
-                    metadata: Metadata {
-                        content_type: None,
-                        content_encoding: None,
-                        size: obj.size,
-                        last_modified: obj.modified.and_then(|s| {
-                            chrono::DateTime::parse_from_rfc3339(&s)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&chrono::Utc))
-                        }),
-                        etag: obj.etag,
-                        custom: obj.metadata.unwrap_or_default(),
-                    },
-                })
-                .collect(),
-            common_prefixes: rest_response.common_prefixes.unwrap_or_default(),
-            next_token: rest_response.next_token,
-            truncated: rest_response.truncated,
-        })
+        Ok(body_stream(response).boxed())
     }
 
     /// Check if an object exists
     pub async fn exists(&self, key: &str) -> Result<bool> {
-        let url = format!("{}/objects/{}", self.base_url, urlencoding::encode(key));
+        let url = format!("{}/objects/{}", self.base_url, self.key_path(key)?);
 
-        let response = self.client.head(&url).send().await?;
+        let response = self.send(self.request(Method::HEAD, &url)).await?;
 
         if response.status() == StatusCode::NOT_FOUND {
             return Ok(false);
@@ -259,11 +844,33 @@ impl RestClient {
         Ok(true)
     }
 
+    /// Get an object's metadata from the headers of `HEAD /objects/{key}`.
+    ///
+    /// Cheaper than [`get_metadata`](Self::get_metadata) on servers that
+    /// answer HEAD from their index, and carries the ETag, size, and custom
+    /// metadata; `size` is 0 for empty objects, whose `Content-Length` the
+    /// server omits.
+    pub async fn head(&self, key: &str) -> Result<Metadata> {
+        let url = format!("{}/objects/{}", self.base_url, self.key_path(key)?);
+
+        let response = self.send(self.request(Method::HEAD, &url)).await?;
+
+        if !response.status().is_success() {
+            return Err(error_from_http_status(
+                response.status().as_u16(),
+                Some(key),
+                format!("Failed to head object: {}", response.status()),
+            ));
+        }
+
+        metadata_from_headers(response.headers())
+    }
+
     /// Get metadata for an object
     pub async fn get_metadata(&self, key: &str) -> Result<Metadata> {
-        let url = format!("{}/metadata/{}", self.base_url, urlencoding::encode(key));
+        let url = format!("{}/metadata/{}", self.base_url, self.key_path(key)?);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -277,39 +884,35 @@ impl RestClient {
         // `size` field carries the stored object's size.  Parsing
         // Content-Length from the HTTP response would give the size of the
         // JSON payload itself, not the stored object.
-        let body: MetadataObjectResponse = response.json().await?;
-        Ok(Metadata {
-            content_type: body.content_type,
-            content_encoding: None,
-            size: body.size,
-            last_modified: body.modified.and_then(|s| {
-                chrono::DateTime::parse_from_rfc3339(&s)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-            }),
-            etag: body.etag,
-            custom: body.metadata.unwrap_or_default(),
-        })
+        let body = response.bytes().await?;
+        let metadata = match &self.config.response_decoder {
+            Some(decoder) => decoder.decode_metadata(&body)?,
+            None => decode_metadata(&body)?,
+        };
+        Ok(metadata.lift_web_headers())
     }
 
     /// Update metadata for an object
     pub async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
-        let url = format!("{}/metadata/{}", self.base_url, urlencoding::encode(key));
+        let url = format!("{}/metadata/{}", self.base_url, self.key_path(key)?);
 
+        let custom = metadata.wire_custom().into_owned();
         let rest_metadata = RestMetadata {
             content_type: metadata.content_type,
             content_encoding: metadata.content_encoding,
             size: metadata.size,
             last_modified: metadata.last_modified.map(|dt| dt.to_rfc3339()),
             etag: metadata.etag,
-            custom: if metadata.custom.is_empty() {
+            custom: if custom.is_empty() {
                 None
             } else {
-                Some(metadata.custom)
+                Some(custom)
             },
         };
 
-        let response = self.client.put(&url).json(&rest_metadata).send().await?;
+        let response = self
+            .send(self.request(Method::PUT, &url).json(&rest_metadata))
+            .await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -326,7 +929,7 @@ impl RestClient {
     pub async fn health(&self) -> Result<HealthResponse> {
         let url = format!("{}/health", self.base_url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -362,7 +965,9 @@ impl RestClient {
             "destination_settings": destination_settings,
         });
 
-        let response = self.client.post(&url).json(&body).send().await?;
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
 
         if response.status().is_success() {
             Ok(())
@@ -392,7 +997,9 @@ impl RestClient {
             body["destination_settings"] = serde_json::to_value(&policy.destination_settings)?;
         }
 
-        let response = self.client.post(&url).json(&body).send().await?;
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
 
         if response.status().is_success() {
             Ok(())
@@ -409,7 +1016,7 @@ impl RestClient {
     pub async fn remove_policy(&self, id: &str) -> Result<()> {
         let url = format!("{}/policies/{}", self.base_url, urlencoding::encode(id));
 
-        let response = self.client.delete(&url).send().await?;
+        let response = self.send(self.request(Method::DELETE, &url)).await?;
 
         if response.status().is_success() {
             Ok(())
@@ -429,7 +1036,7 @@ impl RestClient {
             url.push_str(&format!("?prefix={}", urlencoding::encode(prefix)));
         }
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -476,11 +1083,11 @@ impl RestClient {
             .collect())
     }
 
-    /// Apply all lifecycle policies, returning (policies_count, objects_processed)
-    pub async fn apply_policies(&self) -> Result<(i32, i32)> {
+    /// Apply all lifecycle policies.
+    pub async fn apply_policies(&self) -> Result<ApplyPoliciesResult> {
         let url = format!("{}/policies/apply", self.base_url);
 
-        let response = self.client.post(&url).send().await?;
+        let response = self.send(self.request(Method::POST, &url)).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -490,16 +1097,7 @@ impl RestClient {
             ));
         }
 
-        #[derive(Deserialize)]
-        struct ApplyResponse {
-            #[serde(default)]
-            policies_count: i32,
-            #[serde(default)]
-            objects_processed: i32,
-        }
-
-        let parsed: ApplyResponse = response.json().await?;
-        Ok((parsed.policies_count, parsed.objects_processed))
+        Ok(response.json().await?)
     }
 
     /// Add a replication policy
@@ -508,7 +1106,9 @@ impl RestClient {
 
         let body = replication_policy_to_rest_json(&policy, "check_interval_seconds");
 
-        let response = self.client.post(&url).json(&body).send().await?;
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
 
         if response.status().is_success() {
             Ok(())
@@ -529,7 +1129,7 @@ impl RestClient {
             urlencoding::encode(id)
         );
 
-        let response = self.client.delete(&url).send().await?;
+        let response = self.send(self.request(Method::DELETE, &url)).await?;
 
         if response.status().is_success() {
             Ok(())
@@ -546,7 +1146,7 @@ impl RestClient {
     pub async fn get_replication_policies(&self) -> Result<Vec<ReplicationPolicy>> {
         let url = format!("{}/replication/policies", self.base_url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -578,7 +1178,7 @@ impl RestClient {
             urlencoding::encode(id)
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -609,7 +1209,9 @@ impl RestClient {
             body["policy_id"] = serde_json::Value::String(id.clone());
         }
 
-        let response = self.client.post(&url).json(&body).send().await?;
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -632,6 +1234,56 @@ impl RestClient {
             .ok_or_else(|| Error::InvalidResponse("Missing sync result".to_string()))
     }
 
+    /// Copy the one object `key` through replication policy `policy_id`
+    /// with `POST /replication/replicate`.  Returns `None` if the server
+    /// does not serve the endpoint; a 404 with a JSON error body is the
+    /// handler's, and means the policy or the object does not exist.  Once
+    /// the server has answered so, later calls return `None` without a
+    /// request.
+    pub async fn replicate_object(&self, policy_id: &str, key: &str) -> Result<Option<SyncResult>> {
+        if self.unsupported.contains(Endpoint::Replicate) {
+            return Ok(None);
+        }
+        let url = format!("{}/replication/replicate", self.base_url);
+        let body = serde_json::json!({ "policy_id": policy_id, "key": key });
+        let response = self
+            .send(self.request(Method::POST, &url).json(&body))
+            .await?;
+
+        let json = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        match response.status().as_u16() {
+            status if not_served(status) && !(status == 404 && json) => {
+                self.unsupported.insert(Endpoint::Replicate);
+                return Ok(None);
+            }
+            _ if response.status().is_success() => {}
+            status => {
+                return Err(error_from_http_status(
+                    status,
+                    Some(key),
+                    format!("Failed to replicate object: {}", response.status()),
+                ))
+            }
+        }
+
+        #[derive(Deserialize)]
+        struct ReplicateResponse {
+            #[serde(default)]
+            result: Option<RestSyncResult>,
+        }
+
+        let parsed: ReplicateResponse = response.json().await?;
+        parsed
+            .result
+            .map(rest_sync_result_into)
+            .map(Some)
+            .ok_or_else(|| Error::InvalidResponse("Missing sync result".to_string()))
+    }
+
     /// Get replication status for a policy
     pub async fn get_replication_status(&self, id: &str) -> Result<ReplicationStatus> {
         let url = format!(
@@ -640,7 +1292,7 @@ impl RestClient {
             urlencoding::encode(id)
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         if !response.status().is_success() {
             return Err(error_from_http_status(
@@ -654,6 +1306,41 @@ impl RestClient {
         Ok(rest_replication_status_into(parsed))
     }
 
+    /// Send a request to `path` (relative to the base URL, e.g.
+    /// `/objects?prefix=a`) with the client's auth headers applied, for
+    /// server endpoints that have no typed method yet.
+    ///
+    /// Error statuses are returned in the [`RawResponse`], not as errors.
+    pub async fn raw_request(
+        &self,
+        method: Method,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: Option<Bytes>,
+    ) -> Result<RawResponse> {
+        if !path.starts_with('/') {
+            return Err(Error::InvalidArgument(format!(
+                "raw request path must start with '/': {path}"
+            )));
+        }
+        let mut req = self.request(method, &format!("{}{}", self.base_url, path));
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        if let Some(body) = body {
+            req = req.body(body);
+        }
+        let response = self.send(req).await?;
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        Ok(RawResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
     /// Close the client, releasing any underlying resources.
     ///
     /// The REST client holds no persistent connections beyond reqwest's
@@ -667,9 +1354,20 @@ impl RestClient {
         &self.base_url
     }
 
-    /// Return a reference to the underlying HTTP client (used by the streaming extension).
-    pub(crate) fn http_client_ref(&self) -> &reqwest::Client {
-        &self.client
+    /// `key` encoded for a request path per the configured
+    /// [`KeyEncoding`](crate::KeyEncoding).
+    pub(crate) fn key_path<'a>(&self, key: &'a str) -> Result<Cow<'a, str>> {
+        self.config.key_encoding.encode(key)
+    }
+
+    /// The server base URL.
+    pub fn endpoint(&self) -> &str {
+        &self.base_url
+    }
+
+    /// TLS settings, or `None` for a plain `http://` URL.
+    pub fn tls_info(&self) -> Option<TlsInfo> {
+        TlsInfo::for_url(&self.base_url)
     }
 }
 
@@ -677,7 +1375,7 @@ impl RestClient {
 /// part of the public API.
 pub(crate) fn metadata_from_headers_pub(
     headers: &reqwest::header::HeaderMap,
-) -> crate::types::Metadata {
+) -> Result<crate::types::Metadata> {
     metadata_from_headers(headers)
 }
 
@@ -752,6 +1450,8 @@ struct RestReplicationStatus {
     average_sync_duration: Option<String>,
     #[serde(default)]
     sync_count: i64,
+    #[serde(default)]
+    recent_errors: Option<Vec<ReplicationErrorWire>>,
 }
 
 fn rest_replication_policy_into(p: RestReplicationPolicy) -> ReplicationPolicy {
@@ -763,7 +1463,7 @@ fn rest_replication_policy_into(p: RestReplicationPolicy) -> ReplicationPolicy {
         destination_backend: p.destination_backend,
         destination_settings: p.destination_settings.unwrap_or_default(),
         check_interval_seconds: p.check_interval_seconds,
-        last_sync_time: p.last_sync_time.and_then(parse_rfc3339),
+        last_sync_time: p.last_sync_time.as_deref().and_then(parse_timestamp),
         enabled: p.enabled,
         encryption: p.encryption,
         replication_mode: match p.replication_mode.as_deref() {
@@ -795,20 +1495,20 @@ fn rest_replication_status_into(s: RestReplicationStatus) -> ReplicationStatus {
         total_objects_deleted: s.total_objects_deleted,
         total_bytes_synced: s.total_bytes_synced,
         total_errors: s.total_errors,
-        last_sync_time: s.last_sync_time.and_then(parse_rfc3339),
+        last_sync_time: s.last_sync_time.as_deref().and_then(parse_timestamp),
         average_sync_duration_ms: parse_go_duration_ms(
             s.average_sync_duration.as_deref().unwrap_or(""),
         ),
         sync_count: s.sync_count,
+        recent_errors: s
+            .recent_errors
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect(),
     }
 }
 
-fn parse_rfc3339(s: String) -> Option<chrono::DateTime<chrono::Utc>> {
-    chrono::DateTime::parse_from_rfc3339(&s)
-        .ok()
-        .map(|dt| dt.with_timezone(&chrono::Utc))
-}
-
 /// Serialize a [`ReplicationPolicy`] into the JSON body expected by the server.
 ///
 /// `interval_field` is the JSON key used for the check interval: REST uses
@@ -855,10 +1555,11 @@ pub(crate) fn replication_policy_to_rest_json(
 /// `X-Object-Metadata` contract.
 ///
 /// `Content-Type` and `Content-Encoding` are emitted as standard headers (the
-/// latter only when present). The custom string->string map is serialized to
-/// JSON and placed in `X-Object-Metadata`; the header is omitted entirely when
-/// there is no custom metadata. `content_type`/`content_encoding` are never
-/// duplicated into the JSON body.
+/// latter only when present), as are `Cache-Control`, `Content-Disposition`
+/// and `Content-Language`. The custom string->string map, which also carries
+/// those three so the server stores them, is serialized to JSON and placed in
+/// `X-Object-Metadata`; the header is omitted entirely when the map is empty.
+/// `content_type`/`content_encoding` are never duplicated into the JSON body.
 fn put_metadata_headers(meta: &Metadata) -> Vec<(&'static str, String)> {
     let mut headers = Vec::new();
 
@@ -868,8 +1569,12 @@ fn put_metadata_headers(meta: &Metadata) -> Vec<(&'static str, String)> {
     if let Some(content_encoding) = &meta.content_encoding {
         headers.push(("Content-Encoding", content_encoding.clone()));
     }
-    if !meta.custom.is_empty() {
-        if let Ok(json) = serde_json::to_string(&meta.custom) {
+    for (name, value) in meta.web_headers() {
+        headers.push((name, value.to_string()));
+    }
+    let custom = meta.wire_custom();
+    if !custom.is_empty() {
+        if let Ok(json) = serde_json::to_string(&*custom) {
             headers.push(("X-Object-Metadata", json));
         }
     }
@@ -877,14 +1582,52 @@ fn put_metadata_headers(meta: &Metadata) -> Vec<(&'static str, String)> {
     headers
 }
 
+/// Build the `multipart/form-data` upload accepted by the server's PUT
+/// handler: an optional `metadata` field (JSON `common.Metadata`) and the
+/// `file` part.  Metadata goes first so streaming gateways see it before the
+/// payload.
+fn put_metadata_form(
+    key: &str,
+    body: reqwest::Body,
+    meta: Option<&Metadata>,
+) -> Result<reqwest::multipart::Form> {
+    let mut form = reqwest::multipart::Form::new();
+    let mut file = reqwest::multipart::Part::stream(body)
+        .file_name(key.rsplit('/').next().unwrap_or(key).to_string());
+
+    if let Some(meta) = meta {
+        let mut json = serde_json::Map::new();
+        if let Some(content_type) = &meta.content_type {
+            json.insert("content_type".into(), content_type.clone().into());
+            file = file
+                .mime_str(content_type)
+                .map_err(|e| Error::InvalidArgument(format!("invalid content type: {e}")))?;
+        }
+        if let Some(content_encoding) = &meta.content_encoding {
+            json.insert("content_encoding".into(), content_encoding.clone().into());
+        }
+        let custom = meta.wire_custom();
+        if !custom.is_empty() {
+            json.insert("custom".into(), serde_json::to_value(&*custom)?);
+        }
+        form = form.text("metadata", serde_json::Value::Object(json).to_string());
+    }
+
+    Ok(form.part("file", file))
+}
+
 /// Parse object [`Metadata`] from HTTP response headers following the canonical
 /// `X-Object-Metadata` contract.
 ///
-/// `Content-Type`, `Content-Encoding`, `Content-Length`, `ETag` and
-/// `Last-Modified` are read from their standard headers. The custom
+/// `Content-Type`, `Content-Encoding`, `Content-Length`, `ETag`,
+/// `Last-Modified`, `Cache-Control`, `Content-Disposition` and
+/// `Content-Language` are read from their standard headers. The custom
 /// string->string map is parsed from the JSON-encoded `X-Object-Metadata`
-/// header, if present.
-fn metadata_from_headers(headers: &reqwest::header::HeaderMap) -> Metadata {
+/// header, if present; the last three fall back to their keys in it.
+///
+/// A malformed `Content-Length` or `Last-Modified` fails with
+/// [`Error::InvalidResponse`].
+fn metadata_from_headers(headers: &reqwest::header::HeaderMap) -> Result<Metadata> {
     let header_str = |name: &str| {
         headers
             .get(name)
@@ -895,19 +1638,25 @@ fn metadata_from_headers(headers: &reqwest::header::HeaderMap) -> Metadata {
     let content_type = header_str("content-type");
     let content_encoding = header_str("content-encoding");
 
-    let size = headers
-        .get("content-length")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(0);
+    let size = match headers.get("content-length") {
+        Some(v) => v
+            .to_str()
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .and_then(|n| i64::try_from(n).ok())
+            .ok_or_else(|| Error::InvalidResponse(format!("malformed Content-Length: {v:?}")))?,
+        None => 0,
+    };
 
     let etag = header_str("etag");
 
-    let last_modified = headers
-        .get("last-modified")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| chrono::DateTime::parse_from_rfc2822(s).ok())
-        .map(|dt| dt.with_timezone(&chrono::Utc));
+    let last_modified =
+        match headers.get("last-modified") {
+            Some(v) => Some(v.to_str().ok().and_then(parse_timestamp).ok_or_else(|| {
+                Error::InvalidResponse(format!("malformed Last-Modified: {v:?}"))
+            })?),
+            None => None,
+        };
 
     let custom = headers
         .get("x-object-metadata")
@@ -915,19 +1664,25 @@ fn metadata_from_headers(headers: &reqwest::header::HeaderMap) -> Metadata {
         .and_then(|s| serde_json::from_str::<HashMap<String, String>>(s).ok())
         .unwrap_or_default();
 
-    Metadata {
+    Ok(Metadata {
         content_type,
         content_encoding,
         size,
         last_modified,
         etag,
         custom,
+        compressed_size: None,
+        cache_control: header_str("cache-control"),
+        content_disposition: header_str("content-disposition"),
+        content_language: header_str("content-language"),
     }
+    .lift_web_headers())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::key_encoding::KeyEncoding;
     use mockito::Server;
 
     // =========================================================================
@@ -939,7 +1694,7 @@ mod tests {
     // no live server is required.
     //
     // Documented impl behaviors:
-    //  - PUT only treats HTTP 201 CREATED as success.
+    //  - PUT accepts any 2xx unless `ClientConfig::write_statuses` narrows it.
     //  - `exists` returns Ok(false) only for 404 and Ok(true) for a success
     //    status; any other status (e.g. 5xx) returns an Err -> see
     //    rest_exists_error / rest_exists_not_found.
@@ -983,6 +1738,32 @@ mod tests {
         assert!(RestClient::new("https://localhost:8443").is_ok());
     }
 
+    #[tokio::test]
+    async fn rest_with_client_uses_supplied_client() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("HEAD", "/objects/k")
+            .match_header("x-app", "shared")
+            .match_header("authorization", "Bearer t")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let mut defaults = reqwest::header::HeaderMap::new();
+        defaults.insert("x-app", "shared".parse().unwrap());
+        let shared = Client::builder().default_headers(defaults).build().unwrap();
+        let config = ClientConfig {
+            auth: AuthConfig {
+                token: Some("t".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let client = RestClient::with_client_and_config(shared, server.url(), config);
+        assert!(client.exists("k").await.unwrap());
+        mock.assert_async().await;
+    }
+
     #[test]
     fn rest_put_metadata_headers_scheme() {
         // Content-Type and Content-Encoding are standard headers; custom map is
@@ -1005,8 +1786,10 @@ mod tests {
         assert!(!parsed.contains_key("content_encoding"));
 
         // No custom -> no X-Object-Metadata; no content-encoding when absent.
-        let mut bare = Metadata::default();
-        bare.content_type = Some("text/plain".to_string());
+        let bare = Metadata {
+            content_type: Some("text/plain".to_string()),
+            ..Default::default()
+        };
         let headers = put_metadata_headers(&bare);
         assert!(!headers.iter().any(|(n, _)| *n == "X-Object-Metadata"));
         assert!(!headers.iter().any(|(n, _)| *n == "Content-Encoding"));
@@ -1024,7 +1807,7 @@ mod tests {
             "x-object-metadata",
             HeaderValue::from_str(r#"{"owner":"alice"}"#).unwrap(),
         );
-        let meta = metadata_from_headers(&headers);
+        let meta = metadata_from_headers(&headers).unwrap();
         assert_eq!(meta.content_type.as_deref(), Some("application/json"));
         assert_eq!(meta.content_encoding.as_deref(), Some("gzip"));
         assert_eq!(meta.size, 42);
@@ -1034,7 +1817,55 @@ mod tests {
         // Malformed custom JSON is tolerated -> empty custom map.
         let mut bad = HeaderMap::new();
         bad.insert("x-object-metadata", HeaderValue::from_static("not-json"));
-        assert!(metadata_from_headers(&bad).custom.is_empty());
+        assert!(metadata_from_headers(&bad).unwrap().custom.is_empty());
+    }
+
+    #[test]
+    fn rest_metadata_from_headers_rejects_malformed_fields() {
+        use reqwest::header::HeaderMap;
+        for (name, value) in [
+            ("content-length", "-1"),
+            ("content-length", "seven"),
+            ("last-modified", "yesterday"),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, value.parse().unwrap());
+            let err = metadata_from_headers(&headers).unwrap_err();
+            assert!(matches!(err, Error::InvalidResponse(_)), "{name}: {err:?}");
+        }
+    }
+
+    #[test]
+    fn rest_web_headers_round_trip() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        let meta = Metadata {
+            cache_control: Some("max-age=3600".to_string()),
+            content_disposition: Some("attachment; filename=\"a.pdf\"".to_string()),
+            ..Default::default()
+        };
+        let headers = put_metadata_headers(&meta);
+        assert!(headers.contains(&("cache-control", "max-age=3600".to_string())));
+        assert!(!headers.iter().any(|(n, _)| *n == "content-language"));
+        // Also stored as custom metadata, since the server keeps no headers.
+        let object_meta = &headers
+            .iter()
+            .find(|(n, _)| *n == "X-Object-Metadata")
+            .expect("X-Object-Metadata header present")
+            .1;
+        let parsed: HashMap<String, String> = serde_json::from_str(object_meta).unwrap();
+        assert_eq!(parsed["cache-control"], "max-age=3600");
+
+        // Read back from the custom map; a response header wins.
+        let mut headers = HeaderMap::new();
+        headers.insert("cache-control", HeaderValue::from_static("no-store"));
+        headers.insert(
+            "x-object-metadata",
+            HeaderValue::from_str(object_meta).unwrap(),
+        );
+        let got = metadata_from_headers(&headers).unwrap();
+        assert_eq!(got.cache_control.as_deref(), Some("no-store"));
+        assert_eq!(got.content_disposition, meta.content_disposition);
+        assert!(got.custom.is_empty());
     }
 
     // ---- put ----
@@ -1058,6 +1889,182 @@ mod tests {
         assert_eq!(resp.etag.as_deref(), Some("\"e1\""));
     }
 
+    #[tokio::test]
+    async fn rest_with_headers_exposes_response_headers() {
+        use crate::response::ResponseExt;
+
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("PUT", "/objects/k")
+            .with_status(201)
+            .with_header("x-shard-id", "3")
+            .create_async()
+            .await;
+        let client = RestClient::new(server.url()).unwrap();
+        let (resp, headers) = client
+            .put("k", Bytes::from_static(b"data"), None)
+            .with_headers()
+            .await
+            .unwrap();
+        assert!(resp.success);
+        assert_eq!(headers.get("x-shard-id"), Some("3"));
+    }
+
+    #[tokio::test]
+    async fn rest_put_durability_header() {
+        use mockito::Matcher;
+
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_header("x-objstore-features", "durability")
+            .with_body(r#"{"status":"healthy"}"#)
+            .create_async()
+            .await;
+        let replicated = server
+            .mock("PUT", "/objects/k")
+            .match_header("x-durability", "replicated=2")
+            .with_status(201)
+            .with_header("x-durability", "replicated=2")
+            .create_async()
+            .await;
+        let unacknowledged = server
+            .mock("PUT", "/objects/fsync")
+            .match_header("x-durability", "fsync")
+            .with_status(201)
+            .create_async()
+            .await;
+        let local = server
+            .mock("PUT", "/objects/default")
+            .match_header("x-durability", Matcher::Missing)
+            .with_status(201)
+            .create_async()
+            .await;
+        let client = RestClient::new(server.url()).unwrap();
+
+        let options = RequestOptions::default().durability(Durability::Replicated(2));
+        client
+            .put_with_options("k", Bytes::new(), None, &options)
+            .await
+            .unwrap();
+        client.put("default", Bytes::new(), None).await.unwrap();
+        let options = RequestOptions::default().durability(Durability::Fsync);
+        let err = client
+            .put_with_options("fsync", Bytes::new(), None, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::OperationFailed(_)), "{err:?}");
+        replicated.assert_async().await;
+        local.assert_async().await;
+        unacknowledged.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_consistency_token_round_trip() {
+        use mockito::Matcher;
+
+        let mut server = Server::new_async().await;
+        server
+            .mock("PUT", "/objects/k")
+            .with_status(201)
+            .with_header("x-consistency-token", "seq-42")
+            .create_async()
+            .await;
+        let get = server
+            .mock("GET", "/objects/k")
+            .match_header("x-min-consistency-token", "seq-42")
+            .with_status(200)
+            .with_body("v")
+            .create_async()
+            .await;
+        let list = server
+            .mock("GET", "/objects")
+            .match_query(Matcher::Any)
+            .match_header("x-min-consistency-token", "seq-42")
+            .with_status(200)
+            .with_body(r#"{"objects":[{"key":"k","size":1}],"truncated":false}"#)
+            .create_async()
+            .await;
+        let client = RestClient::new(server.url()).unwrap();
+
+        let put = client
+            .put("k", Bytes::from_static(b"v"), None)
+            .await
+            .unwrap();
+        let token = put.consistency_token.unwrap();
+        let options = RequestOptions::default().min_consistency_token(token);
+        let (data, _) = client.get_with_options("k", &options).await.unwrap();
+        assert_eq!(data, "v");
+        let page = client
+            .list_with_options(ListRequest::default(), &options)
+            .await
+            .unwrap();
+        assert_eq!(page.objects.len(), 1);
+        get.assert_async().await;
+        list.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_put_region_pinning() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/objects/k")
+            .match_header("x-region", "eu-west-1")
+            .with_status(201)
+            .with_header("x-region", "eu-west-1")
+            .create_async()
+            .await;
+        let client = RestClient::new(server.url()).unwrap();
+
+        let options = RequestOptions::default().region("eu-west-1");
+        let put = client
+            .put_with_options("k", Bytes::from_static(b"v"), None, &options)
+            .await
+            .unwrap();
+        assert_eq!(put.region.as_deref(), Some("eu-west-1"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_put_form_body_format() {
+        use mockito::Matcher;
+
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/objects/dir%2Fk.txt")
+            .match_header(
+                "content-type",
+                Matcher::Regex("^multipart/form-data; boundary=".into()),
+            )
+            .match_header("x-object-metadata", Matcher::Missing)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex(r#"name="metadata"\r\n\r\n\{"content_type":"text/plain","custom":\{"a":"1"\}\}"#.into()),
+                Matcher::Regex(r#"name="file"; filename="k.txt"\r\nContent-Type: text/plain\r\n\r\npayload\r\n"#.into()),
+            ]))
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let client = RestClient::new(server.url()).unwrap();
+        let meta = Metadata {
+            content_type: Some("text/plain".to_string()),
+            custom: HashMap::from([("a".to_string(), "1".to_string())]),
+            ..Default::default()
+        };
+        let options = RequestOptions::default().body_format(BodyFormat::Form);
+        let resp = client
+            .put_with_options(
+                "dir/k.txt",
+                Bytes::from_static(b"payload"),
+                Some(meta),
+                &options,
+            )
+            .await
+            .unwrap();
+        mock.assert_async().await;
+        assert!(resp.success);
+    }
+
     #[tokio::test]
     async fn rest_put_error() {
         let mut server = Server::new_async().await;
@@ -1075,6 +2082,30 @@ mod tests {
         assert!(matches!(err, Error::OperationFailed(_)));
     }
 
+    #[tokio::test]
+    async fn rest_put_write_statuses() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("PUT", "/objects/k")
+            .with_status(204)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = RestClient::new(server.url()).unwrap();
+        assert!(client.put("k", Bytes::new(), None).await.unwrap().success);
+
+        let strict = RestClient::new_with_config(
+            server.url(),
+            ClientConfig {
+                write_statuses: Some(vec![201]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let err = strict.put("k", Bytes::new(), None).await.unwrap_err();
+        assert!(matches!(err, Error::OperationFailed(_)));
+    }
+
     // ---- get ----
 
     #[tokio::test]
@@ -1187,6 +2218,7 @@ mod tests {
             delimiter: Some("/".to_string()),
             max_results: Some(5),
             continue_from: Some("abc".to_string()),
+            ..Default::default()
         };
         let resp = client.list(req).await.unwrap();
         mock.assert_async().await;
@@ -1317,8 +2349,10 @@ mod tests {
             .create_async()
             .await;
         let client = RestClient::new(server.url()).unwrap();
-        let mut meta = Metadata::default();
-        meta.content_type = Some("text/plain".to_string());
+        let mut meta = Metadata {
+            content_type: Some("text/plain".to_string()),
+            ..Default::default()
+        };
         meta.custom.insert("k".to_string(), "v".to_string());
         client.update_metadata("k", meta).await.unwrap();
         mock.assert_async().await;
@@ -1390,6 +2424,44 @@ mod tests {
         assert!(matches!(err, Error::OperationFailed(_)));
     }
 
+    // ---- raw_request ----
+
+    #[tokio::test]
+    async fn rest_raw_request_returns_error_statuses() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v2/compact")
+            .match_query(mockito::Matcher::UrlEncoded("dry".into(), "1".into()))
+            .match_header("x-probe", "yes")
+            .match_body("{}")
+            .with_status(418)
+            .with_header("x-reason", "teapot")
+            .with_body("short and stout")
+            .create_async()
+            .await;
+        let client = RestClient::new(server.url()).unwrap();
+        let resp = client
+            .raw_request(
+                Method::POST,
+                "/v2/compact?dry=1",
+                &[("x-probe", "yes")],
+                Some(Bytes::from_static(b"{}")),
+            )
+            .await
+            .unwrap();
+        mock.assert_async().await;
+        assert_eq!(resp.status, 418);
+        assert!(!resp.is_success());
+        assert_eq!(resp.headers["x-reason"], "teapot");
+        assert_eq!(resp.body, "short and stout");
+
+        let err = client
+            .raw_request(Method::GET, "health", &[], None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)));
+    }
+
     // ---- archive ----
 
     #[tokio::test]
@@ -1572,14 +2644,20 @@ mod tests {
         let mock = server
             .mock("POST", "/policies/apply")
             .with_status(200)
-            .with_body(r#"{"policies_count":3,"objects_processed":42}"#)
+            .with_body(
+                r#"{"policies_count":3,"objects_processed":42,"objects_deleted":40,
+                    "objects_archived":2,"errors":null}"#,
+            )
             .create_async()
             .await;
         let client = RestClient::new(server.url()).unwrap();
-        let (count, processed) = client.apply_policies().await.unwrap();
+        let result = client.apply_policies().await.unwrap();
         mock.assert_async().await;
-        assert_eq!(count, 3);
-        assert_eq!(processed, 42);
+        assert_eq!(result.policies_evaluated, 3);
+        assert_eq!(result.objects_processed, 42);
+        assert_eq!(result.objects_deleted, Some(40));
+        assert_eq!(result.objects_archived, Some(2));
+        assert!(result.errors.is_empty());
     }
 
     #[tokio::test]
@@ -1974,7 +3052,8 @@ mod tests {
         // mocked transport: 400 InvalidArgument, 401 Unauthenticated,
         // 403 Forbidden, 404 NotFound, 409 AlreadyExists, 429 RateLimited,
         // 5xx OperationFailed.
-        let cases: [(usize, fn(&Error) -> bool); 7] = [
+        type IsVariant = fn(&Error) -> bool;
+        let cases: [(usize, IsVariant); 7] = [
             (400, |e| matches!(e, Error::InvalidArgument(_))),
             (401, |e| matches!(e, Error::Unauthenticated(_))),
             (403, |e| matches!(e, Error::Forbidden(_))),
@@ -2002,4 +3081,177 @@ mod tests {
         let client = RestClient::new("http://localhost:9").unwrap();
         client.close().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn rest_max_in_flight_caps_concurrent_requests() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // A server that answers each request slowly and records the peak
+        // number it was handling at once.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let current = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (cur, max) = (current.clone(), peak.clone());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (cur, max) = (cur.clone(), max.clone());
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = socket.read(&mut buf).await;
+                    let now = cur.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+                    cur.fetch_sub(1, Ordering::SeqCst);
+                    let _ = socket
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                        )
+                        .await;
+                });
+            }
+        });
+
+        let client = RestClient::new_with_config(
+            format!("http://{addr}"),
+            ClientConfig {
+                max_in_flight: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let calls = (0..8).map(|i| {
+            let client = client.clone();
+            async move { client.exists(&format!("k{i}")).await }
+        });
+        for result in futures::future::join_all(calls).await {
+            assert!(result.unwrap());
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn rest_timeouts_name_the_stage() {
+        use crate::error::TimeoutStage;
+        use crate::timeouts::Timeouts;
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // `/objects/slow` answers late; `/objects/stall` sends its headers
+        // and part of the body, then stops.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                    if request.starts_with("GET /objects/slow ") {
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                    }
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 8\r\n\r\nlate")
+                        .await;
+                    if request.starts_with("GET /objects/stall ") {
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    }
+                    let _ = socket.write_all(b"body").await;
+                });
+            }
+        });
+
+        let client = |timeouts| {
+            RestClient::new_with_config(
+                format!("http://{addr}"),
+                ClientConfig {
+                    timeouts,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        let short = Some(Duration::from_millis(100));
+        let headers = client(Timeouts {
+            response_headers: short,
+            ..Default::default()
+        });
+        let err = headers.get("slow").await.unwrap_err();
+        assert!(
+            matches!(err, Error::Timeout(TimeoutStage::ResponseHeaders)),
+            "{err:?}"
+        );
+        let (data, _) = headers.get("stall-free").await.unwrap();
+        assert_eq!(data, "latebody");
+
+        let body = client(Timeouts {
+            body_transfer: short,
+            ..Default::default()
+        });
+        let err = body.get("stall").await.unwrap_err();
+        assert!(
+            matches!(err, Error::Timeout(TimeoutStage::BodyTransfer)),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn rest_put_sniffs_missing_content_type() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/logo")
+            .match_header("content-type", "image/png")
+            .with_status(201)
+            .create_async()
+            .await;
+        let client = RestClient::new_with_config(
+            server.url(),
+            ClientConfig {
+                sniff_content_type: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let png = Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+        client.put("logo", png, None).await.unwrap();
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rest_preserve_slashes_keeps_nested_keys_readable() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/logs/2024/app%201.log")
+            .with_status(201)
+            .create_async()
+            .await;
+        let get = server
+            .mock("GET", "/objects/logs%2F2024%2Fapp%201.log")
+            .with_body("x")
+            .create_async()
+            .await;
+        let nested = RestClient::new_with_config(
+            server.url(),
+            ClientConfig {
+                key_encoding: KeyEncoding::PreserveSlashes,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let default = RestClient::new(server.url()).unwrap();
+
+        nested
+            .put("logs/2024/app 1.log", Bytes::from_static(b"x"), None)
+            .await
+            .unwrap();
+        default.get("logs/2024/app 1.log").await.unwrap();
+        put.assert_async().await;
+        get.assert_async().await;
+
+        let err = nested.get("logs/../secret").await.unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)), "{err:?}");
+    }
 }
@@ -0,0 +1,183 @@
+//! Predicate-driven cleanup of a prefix.
+//!
+//! [`retain`] keeps the objects under a prefix for which a predicate holds
+//! and deletes the rest, like `Vec::retain`:
+//!
+//! ```no_run
+//! use chrono::{Duration, Utc};
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let cutoff = Utc::now() - Duration::days(30);
+//! let report = client
+//!     .retain("tmp/", |o| o.metadata.last_modified.is_none_or(|t| t >= cutoff))
+//!     .await?;
+//! println!("deleted {} of {}", report.deleted.len(), report.scanned);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Deleting while paging through a listing can make servers skip or repeat
+//! objects, since continuation tokens may be offsets into a listing that
+//! has since shrunk.  `retain` therefore lists the whole prefix first and
+//! only starts deleting once the listing is complete; a listing error
+//! deletes nothing.  Objects written after the listing are left alone, as
+//! are versions recorded by [`put_versioned`](crate::versions::put_versioned),
+//! which are neither scanned nor offered to the predicate.
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::types::{ListRequest, ObjectInfo};
+use crate::versions::is_version_key;
+use futures::StreamExt;
+
+/// Deletes issued concurrently.
+const DELETE_CONCURRENCY: usize = 16;
+
+/// What [`retain`] did.
+#[derive(Debug, Default)]
+pub struct RetainReport {
+    /// Objects listed under the prefix.
+    pub scanned: u64,
+    /// Objects the predicate kept.
+    pub kept: u64,
+    /// Keys deleted, including ones already gone when deleted.
+    pub deleted: Vec<String>,
+    /// Keys whose delete failed, with the error.
+    pub failed: Vec<(String, Error)>,
+}
+
+impl RetainReport {
+    /// Whether every rejected object was deleted.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Delete every object under `prefix` for which `keep` returns false.
+pub async fn retain<F>(store: &dyn ObjectStore, prefix: &str, mut keep: F) -> Result<RetainReport>
+where
+    F: FnMut(&ObjectInfo) -> bool,
+{
+    let mut report = RetainReport::default();
+    let mut doomed = Vec::new();
+    let mut pages = list_pages(
+        store,
+        ListRequest {
+            prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+            max_results: Some(LIST_PAGE_SIZE),
+            ..Default::default()
+        },
+    );
+    while let Some(page) = pages.next().await {
+        for object in page?.objects {
+            if is_version_key(&object.key) {
+                continue;
+            }
+            report.scanned += 1;
+            if keep(&object) {
+                report.kept += 1;
+            } else {
+                doomed.push(object.key);
+            }
+        }
+    }
+
+    let mut results = futures::stream::iter(doomed)
+        .map(|key| async move {
+            let result = store.delete(&key).await;
+            (key, result)
+        })
+        .buffer_unordered(DELETE_CONCURRENCY);
+    while let Some((key, result)) = results.next().await {
+        match result {
+            Ok(_) | Err(Error::NotFound(_)) => report.deleted.push(key),
+            Err(e) => report.failed.push((key, e)),
+        }
+    }
+    report.deleted.sort();
+    Ok(report)
+}
+
+impl ObjectStoreClient {
+    /// Delete every object under `prefix` for which `keep` returns false.
+    /// See the [`retain`](crate::retain) module.
+    pub async fn retain<F>(&self, prefix: &str, keep: F) -> Result<RetainReport>
+    where
+        F: FnMut(&ObjectInfo) -> bool,
+    {
+        retain(self, prefix, keep).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn lists_everything_before_deleting() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "tmp/".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"objects":[{"key":"tmp/a","size":1},{"key":"tmp/big","size":900}],
+                    "next_token":"p2","truncated":true}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("token".into(), "p2".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"objects":[{"key":"tmp/b","size":2},{"key":"tmp/gone","size":3}],
+                    "truncated":false}"#,
+            )
+            .create_async()
+            .await;
+        for (key, status) in [("tmp%2Fa", 200), ("tmp%2Fb", 500), ("tmp%2Fgone", 404)] {
+            server
+                .mock("DELETE", format!("/objects/{key}").as_str())
+                .with_status(status)
+                .expect(1)
+                .create_async()
+                .await;
+        }
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client
+            .retain("tmp/", |o| o.metadata.size > 100)
+            .await
+            .unwrap();
+        assert_eq!((report.scanned, report.kept), (4, 1));
+        assert_eq!(report.deleted, ["tmp/a", "tmp/gone"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "tmp/b");
+        assert!(!report.is_complete());
+    }
+
+    #[tokio::test]
+    async fn listing_error_deletes_nothing() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::Any)
+            .with_status(500)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        assert!(client.retain("", |_| false).await.is_err());
+        delete.assert_async().await;
+    }
+}
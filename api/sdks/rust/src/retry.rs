@@ -0,0 +1,855 @@
+//! Retry layer with protocol-aware error classification.
+//!
+//! [`Retrying`] wraps any [`ObjectStore`] and re-issues failed calls with
+//! exponential backoff.  Whether an error is retried is decided by a
+//! [`RetryClassifier`], which sees the [`Protocol`] that produced the error
+//! and may also replace the error that is finally surfaced:
+//!
+//! ```no_run
+//! use go_objstore::retry::{DefaultRetryClassifier, RetryClassifier, RetryDecision, RetryPolicy};
+//! use go_objstore::{Error, ObjectStore, ObjectStoreClient, Protocol};
+//!
+//! /// Retry as usual, but treat gRPC `DataLoss` as a permanent failure.
+//! struct DataLossIsFatal;
+//!
+//! impl RetryClassifier for DataLossIsFatal {
+//!     fn classify(&self, protocol: Protocol, error: &Error) -> RetryDecision {
+//!         match error {
+//!             Error::GrpcStatus(s) if s.code() == tonic::Code::DataLoss => {
+//!                 RetryDecision::FailWith(Error::OperationFailed(format!("data loss: {}", s.message())))
+//!             }
+//!             _ => DefaultRetryClassifier.classify(protocol, error),
+//!         }
+//!     }
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::grpc("http://localhost:50051")
+//!     .await?
+//!     .with_retry(RetryPolicy::default().with_classifier(DataLossIsFatal));
+//! let (data, _) = client.get("report.csv").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Retries are otherwise silent.  [`RetryPolicy::subscribe`] returns a
+//! channel of [`RetryEvent`]s — one per scheduled retry, plus one when the
+//! attempts run out — for applications to log or alert on:
+//!
+//! ```no_run
+//! # use go_objstore::retry::RetryPolicy;
+//! # use go_objstore::ObjectStoreClient;
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let policy = RetryPolicy::default();
+//! let mut events = policy.subscribe();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         eprintln!("{} attempt {} failed: {}", event.operation, event.attempt, event.error);
+//!     }
+//! });
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?.with_retry(policy);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! During a partial outage every caller retrying every failure multiplies
+//! the load on a struggling server.  A [`RetryBudget`] caps retries at a
+//! share of recent calls (20% over 10 seconds by default); once it is spent,
+//! failures are returned without retrying until calls succeed again or the
+//! window moves on.  Clones of the policy — and so every clone of the
+//! client — share the budget, and [`Metrics`](crate::metrics::Metrics) can
+//! export its state:
+//!
+//! ```no_run
+//! # use go_objstore::retry::{RetryBudget, RetryPolicy};
+//! # use go_objstore::metrics::Metrics;
+//! # use go_objstore::ObjectStoreClient;
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let budget = RetryBudget::default();
+//! let metrics = Metrics::new();
+//! metrics.track_retry_budget(&budget);
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?
+//!     .with_retry(RetryPolicy::default().with_budget(budget));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{ByteStream, ObjectStore, Protocol};
+use crate::error::{Error, H3ErrorScope, Result};
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::fmt;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+/// What to do with a failed call.
+#[derive(Debug)]
+pub enum RetryDecision {
+    /// The failure is transient; retry after backoff.
+    Retry,
+    /// The failure is permanent; return the error unchanged.
+    Fail,
+    /// The failure is permanent; return this error instead.
+    FailWith(Error),
+}
+
+/// Decides which errors are retryable, per protocol.
+pub trait RetryClassifier: Send + Sync {
+    /// Classify `error`, produced by a call over `protocol`.
+    fn classify(&self, protocol: Protocol, error: &Error) -> RetryDecision;
+}
+
+/// The built-in classification table.
+///
+/// Retried: transport/connection failures on every protocol (reqwest
+/// connect errors, [timeouts](Error::Timeout) in any stage, tonic transport
+/// errors, QUIC connection, read and write errors, closed HTTP/3 sessions,
+/// connection-level I/O errors), HTTP/3 connection errors and stream errors
+/// with a [transient](crate::H3ErrorCode::is_transient) code, rate limiting,
+/// HTTP 502/503/504, and gRPC `Unavailable`, `DeadlineExceeded`, and
+/// `Aborted`.
+/// Everything else — including not-found, auth, and validation errors and
+/// HTTP/3 protocol violations on a stream — fails immediately.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRetryClassifier;
+
+impl RetryClassifier for DefaultRetryClassifier {
+    fn classify(&self, _protocol: Protocol, error: &Error) -> RetryDecision {
+        let retry = match error {
+            Error::Http(e) => {
+                e.is_connect()
+                    || e.is_timeout()
+                    || e.status().is_some_and(|s| matches!(s.as_u16(), 502..=504))
+            }
+            Error::GrpcTransport(_)
+            | Error::Timeout(_)
+            | Error::QuicConnection(_)
+            | Error::QuicRead(_)
+            | Error::QuicWrite(_)
+            | Error::QuicSessionClosed { .. }
+            | Error::RateLimited(_) => true,
+            Error::H3 { code, scope, .. } => {
+                *scope == H3ErrorScope::Connection || code.is_transient()
+            }
+            Error::GrpcStatus(s) => matches!(
+                s.code(),
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded | tonic::Code::Aborted
+            ),
+            Error::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::UnexpectedEof
+            ),
+            // Non-canonical HTTP statuses are reported as OperationFailed
+            // with the status line in the message.
+            Error::OperationFailed(message) => [
+                "502 Bad Gateway",
+                "503 Service Unavailable",
+                "504 Gateway Timeout",
+            ]
+            .iter()
+            .any(|status| message.contains(status)),
+            _ => false,
+        };
+        if retry {
+            RetryDecision::Retry
+        } else {
+            RetryDecision::Fail
+        }
+    }
+}
+
+/// Events buffered per subscriber before the oldest are dropped.
+const EVENT_CAPACITY: usize = 256;
+
+/// A failed attempt observed by a [`RetryPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryEvent {
+    /// The trait operation, e.g. `"get"`.
+    pub operation: &'static str,
+    pub protocol: Protocol,
+    /// The attempt that failed (1-based).
+    pub attempt: u32,
+    /// The attempt's error, rendered with `Display`.
+    pub error: String,
+    /// Backoff before the next attempt, or `None` if the attempts or the
+    /// retry budget are exhausted and the error is being returned.
+    pub delay: Option<Duration>,
+}
+
+/// Slots a [`RetryBudget`] window is divided into.
+const BUDGET_SLOTS: usize = 10;
+
+/// A cap on retries as a share of recent calls, shared by every clone.
+///
+/// Over a sliding `window`, retries are allowed while they number fewer
+/// than `ratio` times the calls made (first attempts only), or fewer than
+/// `min_retries`, so clients that make few calls can still retry.  See
+/// the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    ratio: f64,
+    min_retries: u64,
+    slot: Duration,
+    state: Arc<Mutex<BudgetWindow>>,
+}
+
+#[derive(Debug)]
+struct BudgetWindow {
+    started: Instant,
+    /// `(slot index, calls, retries)`, one entry per slot of the window.
+    slots: [(u64, u64, u64); BUDGET_SLOTS],
+    exhausted: u64,
+}
+
+/// A [`RetryBudget`]'s state over its current window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryBudgetState {
+    /// Calls made (first attempts).
+    pub requests: u64,
+    /// Retries made.
+    pub retries: u64,
+    /// Retries still allowed.
+    pub available: u64,
+    /// Retries refused since the budget was created.
+    pub exhausted: u64,
+}
+
+impl Default for RetryBudget {
+    /// Retries up to 20% of calls over 10 seconds, and at least 10.
+    fn default() -> Self {
+        Self::new(0.2, Duration::from_secs(10))
+    }
+}
+
+impl RetryBudget {
+    /// Allow retries up to `ratio` of the calls made over `window`, and at
+    /// least 10 per window.
+    pub fn new(ratio: f64, window: Duration) -> Self {
+        Self {
+            ratio: ratio.max(0.0),
+            min_retries: 10,
+            slot: (window / BUDGET_SLOTS as u32).max(Duration::from_millis(1)),
+            state: Arc::new(Mutex::new(BudgetWindow {
+                started: Instant::now(),
+                slots: [(0, 0, 0); BUDGET_SLOTS],
+                exhausted: 0,
+            })),
+        }
+    }
+
+    /// Set the retries allowed per window however few calls were made.
+    pub fn with_min_retries(mut self, min_retries: u64) -> Self {
+        self.min_retries = min_retries;
+        self
+    }
+
+    /// The budget's state over the current window.
+    pub fn state(&self) -> RetryBudgetState {
+        self.with_window(|window, current| {
+            let (requests, retries) = window.totals(current);
+            RetryBudgetState {
+                requests,
+                retries,
+                available: self.allowed(requests).saturating_sub(retries),
+                exhausted: window.exhausted,
+            }
+        })
+    }
+
+    /// Count a call.
+    pub(crate) fn record_request(&self) {
+        self.with_window(|window, current| window.slot(current).1 += 1);
+    }
+
+    /// Take a retry from the budget, or count a refusal if it is spent.
+    pub(crate) fn try_retry(&self) -> bool {
+        self.with_window(|window, current| {
+            let (requests, retries) = window.totals(current);
+            if retries < self.allowed(requests) {
+                window.slot(current).2 += 1;
+                true
+            } else {
+                window.exhausted += 1;
+                false
+            }
+        })
+    }
+
+    fn allowed(&self, requests: u64) -> u64 {
+        ((requests as f64 * self.ratio) as u64).max(self.min_retries)
+    }
+
+    fn with_window<T>(&self, f: impl FnOnce(&mut BudgetWindow, u64) -> T) -> T {
+        let mut window = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let current = (window.started.elapsed().as_nanos() / self.slot.as_nanos()) as u64;
+        f(&mut window, current)
+    }
+}
+
+impl BudgetWindow {
+    /// Calls and retries in the slots of the window ending at `current`.
+    fn totals(&self, current: u64) -> (u64, u64) {
+        self.slots
+            .iter()
+            .filter(|(index, ..)| current - index < BUDGET_SLOTS as u64)
+            .fold((0, 0), |(calls, retries), (_, c, r)| {
+                (calls + c, retries + r)
+            })
+    }
+
+    /// The counters of slot `current`, reset if they belong to an older one.
+    fn slot(&mut self, current: u64) -> &mut (u64, u64, u64) {
+        let slot = &mut self.slots[(current % BUDGET_SLOTS as u64) as usize];
+        if slot.0 != current {
+            *slot = (current, 0, 0);
+        }
+        slot
+    }
+}
+
+/// Retry settings: attempt limit, backoff, and classifier.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Total attempts per call, including the first (minimum 1).
+    pub max_attempts: u32,
+    /// Backoff before the first retry; doubled for each further retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for a single backoff.
+    pub max_backoff: Duration,
+    /// Randomise each backoff between half and the full value, so clients
+    /// that failed together do not retry in lockstep.
+    pub jitter: bool,
+    classifier: Arc<dyn RetryClassifier>,
+    events: broadcast::Sender<RetryEvent>,
+    budget: Option<RetryBudget>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            classifier: Arc::new(DefaultRetryClassifier),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            budget: None,
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .field("budget", &self.budget)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries (classification still applies).
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Set the total number of attempts per call.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set the initial and maximum backoff.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Replace the error classifier.
+    pub fn with_classifier(mut self, classifier: impl RetryClassifier + 'static) -> Self {
+        self.classifier = Arc::new(classifier);
+        self
+    }
+
+    /// The classifier in use.
+    pub fn classifier(&self) -> &dyn RetryClassifier {
+        self.classifier.as_ref()
+    }
+
+    /// Cap retries with `budget`, shared with every clone of the policy.
+    pub fn with_budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// The retry budget in use, if any.
+    pub fn budget(&self) -> Option<&RetryBudget> {
+        self.budget.as_ref()
+    }
+
+    /// Receive a [`RetryEvent`] for every failed attempt that is retried
+    /// or exhausts the attempts.  Clones of the policy share the channel;
+    /// a slow subscriber misses the oldest events (`RecvError::Lagged`).
+    pub fn subscribe(&self) -> broadcast::Receiver<RetryEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: RetryEvent) {
+        // No subscribers is the common case, not an error.
+        let _ = self.events.send(event);
+    }
+
+    /// Backoff before retry number `retry` (1-based).
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let exp = self
+            .initial_backoff
+            .saturating_mul(1u32 << retry.saturating_sub(1).min(16));
+        let capped = exp.min(self.max_backoff);
+        if !self.jitter || capped.is_zero() {
+            return capped;
+        }
+        let half = capped / 2;
+        let spread = half.as_nanos() as u64;
+        let r = std::collections::hash_map::RandomState::new().hash_one(retry);
+        half + Duration::from_nanos(r % spread.max(1))
+    }
+
+    /// Run `op` until it succeeds, fails permanently, or the attempts (or
+    /// the retry budget) are exhausted.
+    pub(crate) async fn run<T, F, Fut>(
+        &self,
+        protocol: Protocol,
+        operation: &'static str,
+        mut op: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if let Some(budget) = &self.budget {
+            budget.record_request();
+        }
+        let mut attempt = 1;
+        loop {
+            let error = match op().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let event = |delay| RetryEvent {
+                operation,
+                protocol,
+                attempt,
+                error: error.to_string(),
+                delay,
+            };
+            match self.classifier.classify(protocol, &error) {
+                RetryDecision::Retry
+                    if attempt < self.max_attempts.max(1)
+                        && self.budget.as_ref().is_none_or(RetryBudget::try_retry) =>
+                {
+                    let delay = self.backoff(attempt);
+                    tracing::debug!(operation, attempt, ?delay, %error, "retrying object store call");
+                    self.emit(event(Some(delay)));
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                RetryDecision::Retry => {
+                    self.emit(event(None));
+                    return Err(error);
+                }
+                RetryDecision::FailWith(replacement) => return Err(replacement),
+                RetryDecision::Fail => return Err(error),
+            }
+        }
+    }
+}
+
+/// An [`ObjectStore`] that retries failed calls according to a
+/// [`RetryPolicy`].
+///
+/// Every trait operation is retried, including `put`: the server's
+/// put/update operations are idempotent overwrites.
+#[derive(Debug, Clone)]
+pub struct Retrying<S> {
+    inner: S,
+    protocol: Protocol,
+    policy: RetryPolicy,
+}
+
+impl<S: ObjectStore> Retrying<S> {
+    /// Wrap `inner`, whose calls travel over `protocol`.
+    pub fn new(inner: S, protocol: Protocol, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            protocol,
+            policy,
+        }
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The retry policy in use.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for Retrying<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        self.policy
+            .run(self.protocol, "put", || {
+                self.inner.put(key, data.clone(), metadata.clone())
+            })
+            .await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.policy
+            .run(self.protocol, "get", || self.inner.get(key))
+            .await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.policy
+            .run(self.protocol, "delete", || self.inner.delete(key))
+            .await
+    }
+
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.policy
+            .run(self.protocol, "list", || self.inner.list(list_req.clone()))
+            .await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.policy
+            .run(self.protocol, "exists", || self.inner.exists(key))
+            .await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.policy
+            .run(self.protocol, "get_metadata", || {
+                self.inner.get_metadata(key)
+            })
+            .await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.policy
+            .run(self.protocol, "update_metadata", || {
+                self.inner.update_metadata(key, metadata.clone())
+            })
+            .await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.policy
+            .run(self.protocol, "health", || self.inner.health())
+            .await
+    }
+
+    /// Only opening the stream is retried; errors while reading it are
+    /// returned to the caller.
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        self.policy
+            .run(self.protocol, "get_stream", || self.inner.get_stream(key))
+            .await
+    }
+
+    /// Sent once without retrying: a stream cannot be replayed.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.inner.put_stream(key, stream, metadata).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::error::H3ErrorCode;
+    use crate::test_support::MemoryStore;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast() -> RetryPolicy {
+        RetryPolicy::default().with_backoff(Duration::from_millis(1), Duration::from_millis(2))
+    }
+
+    #[test]
+    fn default_table() {
+        let c = DefaultRetryClassifier;
+        let retry = |e: Error| matches!(c.classify(Protocol::Grpc, &e), RetryDecision::Retry);
+
+        assert!(retry(Error::GrpcStatus(Box::new(
+            tonic::Status::unavailable("down")
+        ))));
+        assert!(retry(Error::RateLimited("slow down".into())));
+        let h3 = |code, scope| Error::H3 {
+            code,
+            scope,
+            message: String::new(),
+        };
+        assert!(retry(h3(H3ErrorCode::EXCESSIVE_LOAD, H3ErrorScope::Stream)));
+        assert!(retry(h3(
+            H3ErrorCode::FRAME_ERROR,
+            H3ErrorScope::Connection
+        )));
+        assert!(!retry(h3(H3ErrorCode::MESSAGE_ERROR, H3ErrorScope::Stream)));
+        assert!(retry(Error::OperationFailed(
+            "Failed to get object: 503 Service Unavailable".into()
+        )));
+        assert!(retry(Error::Io(std::io::ErrorKind::ConnectionReset.into())));
+
+        assert!(!retry(Error::NotFound("k".into())));
+        assert!(!retry(Error::GrpcStatus(Box::new(
+            tonic::Status::internal("boom")
+        ))));
+        assert!(!retry(Error::OperationFailed(
+            "Failed to get object: 500 Internal Server Error".into()
+        )));
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+                .with_backoff(Duration::from_millis(100), Duration::from_millis(350))
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+
+        let jittered = RetryPolicy::default();
+        let d = jittered.backoff(1);
+        assert!(d >= Duration::from_millis(50) && d <= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_until_success() {
+        let calls = AtomicU32::new(0);
+        let result = fast()
+            .run(Protocol::Rest, "test", || async {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(Error::RateLimited("busy".into()))
+                } else {
+                    Ok(7)
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts_and_on_permanent_errors() {
+        let calls = AtomicU32::new(0);
+        let err = fast()
+            .with_max_attempts(2)
+            .run(Protocol::Rest, "test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Error::RateLimited("busy".into()))
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::RateLimited(_)));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        calls.store(0, Ordering::SeqCst);
+        let err = fast()
+            .run(Protocol::Rest, "test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Error::Forbidden("no".into()))
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Forbidden(_)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn emits_an_event_per_failed_attempt() {
+        let policy = fast().with_max_attempts(2);
+        let mut events = policy.subscribe();
+        let err = policy
+            .clone()
+            .run(Protocol::Grpc, "get", || async {
+                Err::<(), _>(Error::RateLimited("busy".into()))
+            })
+            .await;
+        assert!(err.is_err());
+
+        let first = events.try_recv().unwrap();
+        assert_eq!((first.operation, first.attempt), ("get", 1));
+        assert_eq!(first.protocol, Protocol::Grpc);
+        assert!(first.error.contains("busy"));
+        assert!(first.delay.is_some());
+        let last = events.try_recv().unwrap();
+        assert_eq!((last.attempt, last.delay), (2, None));
+        assert!(events.try_recv().is_err());
+
+        // Permanent failures are not retries.
+        policy
+            .run(Protocol::Rest, "get", || async {
+                Err::<(), _>(Error::NotFound("k".into()))
+            })
+            .await
+            .unwrap_err();
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn budget_caps_retries_over_the_window() {
+        let budget = RetryBudget::new(0.5, Duration::from_millis(500)).with_min_retries(0);
+        let policy = fast().with_budget(budget.clone());
+        let calls = AtomicU32::new(0);
+        let failing = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(Error::RateLimited("busy".into()))
+        };
+
+        // Nothing to spend yet: one attempt only.
+        policy
+            .run(Protocol::Rest, "get", failing)
+            .await
+            .unwrap_err();
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+
+        for _ in 0..4 {
+            policy
+                .run(Protocol::Rest, "get", || async { Ok(()) })
+                .await
+                .unwrap();
+        }
+        // Six calls allow three retries: two for this call...
+        policy
+            .run(Protocol::Rest, "get", failing)
+            .await
+            .unwrap_err();
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 3);
+        // ...and one for the next, after which the budget refuses.
+        policy
+            .clone()
+            .run(Protocol::Rest, "get", failing)
+            .await
+            .unwrap_err();
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+        assert_eq!(
+            budget.state(),
+            RetryBudgetState {
+                requests: 7,
+                retries: 3,
+                available: 0,
+                exhausted: 2,
+            }
+        );
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        let state = budget.state();
+        assert_eq!((state.requests, state.retries, state.exhausted), (0, 0, 2));
+    }
+
+    struct GrpcMissingFile;
+
+    impl RetryClassifier for GrpcMissingFile {
+        fn classify(&self, protocol: Protocol, error: &Error) -> RetryDecision {
+            match (protocol, error) {
+                (Protocol::Grpc, Error::GrpcStatus(s)) if s.message().contains("no such file") => {
+                    RetryDecision::FailWith(Error::NotFound(s.message().to_string()))
+                }
+                _ => DefaultRetryClassifier.classify(protocol, error),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_classifier_can_replace_errors_per_protocol() {
+        let policy = fast().with_classifier(GrpcMissingFile);
+        let op = || async {
+            Err::<(), _>(Error::GrpcStatus(Box::new(tonic::Status::internal(
+                "open /data/k: no such file or directory",
+            ))))
+        };
+
+        let err = policy.run(Protocol::Grpc, "test", op).await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+        let err = policy.run(Protocol::Rest, "test", op).await.unwrap_err();
+        assert!(matches!(err, Error::GrpcStatus(_)));
+    }
+
+    #[tokio::test]
+    async fn retrying_store_retries_503() {
+        let flaky = MemoryStore::default();
+        flaky.insert("k", "data");
+        flaky.fail_next(Operation::Get, 2, || {
+            Error::OperationFailed("Failed to get object: 503 Service Unavailable".into())
+        });
+        let store = Retrying::new(flaky, Protocol::Rest, fast());
+        let (data, _) = store.get("k").await.unwrap();
+        assert_eq!(&data[..], b"data");
+        assert_eq!(store.inner().calls(Operation::Get), 3);
+    }
+
+    #[tokio::test]
+    async fn with_retry_fails_fast_on_permanent_rest_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/objects/k")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = crate::ObjectStoreClient::rest(server.url())
+            .unwrap()
+            .with_retry(fast());
+        assert!(matches!(client.get("k").await, Err(Error::NotFound(_))));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn with_retry_streams_put_stream_bodies() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/objects/k")
+            .match_header("transfer-encoding", "chunked")
+            .match_header("content-length", mockito::Matcher::Missing)
+            .match_body("hello world")
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = crate::ObjectStoreClient::rest(server.url())
+            .unwrap()
+            .with_retry(fast());
+        let chunks = ["hello", " ", "world"].map(|c| Ok(Bytes::from_static(c.as_bytes())));
+        let stream = futures::StreamExt::boxed(futures::stream::iter(chunks));
+        assert!(client.put_stream("k", stream, None).await.unwrap().success);
+        mock.assert_async().await;
+    }
+}
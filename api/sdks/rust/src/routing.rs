@@ -0,0 +1,270 @@
+//! Prefix-based routing of keys to different stores.
+//!
+//! [`RoutingObjectStore`] puts several stores behind one [`ObjectStore`],
+//! sending each key to the store registered for its longest matching
+//! prefix, or to the default route:
+//!
+//! ```no_run
+//! use go_objstore::routing::RoutingObjectStore;
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let store = RoutingObjectStore::new()
+//!     .route("logs/", ObjectStoreClient::rest("http://cheap-store:8080")?)
+//!     .route("hot/", ObjectStoreClient::grpc("http://fast-store:50051").await?)
+//!     .with_default(ObjectStoreClient::rest("http://objstore:8080")?);
+//!
+//! store.put("logs/2024-05-06.log", "...".into(), None).await?; // cheap store
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Without a default route, keys matching no prefix fail with
+//! [`Error::InvalidArgument`].  `list` queries every store whose keys can
+//! fall under the requested prefix and merges the results, keeping from
+//! each store only the keys routed to it.
+
+use crate::client::{ByteStream, ObjectStore};
+use crate::error::{Error, Result};
+use crate::sharded::merge_listings;
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::try_join_all;
+use std::fmt;
+use std::sync::Arc;
+
+/// Name of the default route; never a routed prefix, which is non-empty.
+const DEFAULT_ROUTE: &str = "";
+
+/// Stores selected by key prefix.  See the [module docs](self).
+#[derive(Clone, Default)]
+pub struct RoutingObjectStore {
+    /// Sorted longest prefix first, so the first match is the longest.
+    routes: Vec<(String, Arc<dyn ObjectStore>)>,
+    default: Option<Arc<dyn ObjectStore>>,
+}
+
+impl fmt::Debug for RoutingObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutingObjectStore")
+            .field(
+                "routes",
+                &self.routes.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+            )
+            .field("default", &self.default.is_some())
+            .finish()
+    }
+}
+
+impl RoutingObjectStore {
+    /// A router with no routes and no default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send keys starting with `prefix` to `store`.  Replaces any store
+    /// already routed at `prefix`; an empty prefix sets the default.
+    pub fn route(mut self, prefix: impl Into<String>, store: impl ObjectStore + 'static) -> Self {
+        let prefix = prefix.into();
+        if prefix.is_empty() {
+            return self.with_default(store);
+        }
+        self.routes.retain(|(p, _)| *p != prefix);
+        self.routes.push((prefix, store.into_shared()));
+        self.routes
+            .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+        self
+    }
+
+    /// Send keys matching no route to `store`.
+    pub fn with_default(mut self, store: impl ObjectStore + 'static) -> Self {
+        self.default = Some(store.into_shared());
+        self
+    }
+
+    /// The routed prefixes, longest first.
+    pub fn prefixes(&self) -> impl Iterator<Item = &str> {
+        self.routes.iter().map(|(p, _)| p.as_str())
+    }
+
+    /// The prefix routing `key`, or `None` if it takes the default route.
+    pub fn route_for(&self, key: &str) -> Option<&str> {
+        self.routes
+            .iter()
+            .find(|(p, _)| key.starts_with(p.as_str()))
+            .map(|(p, _)| p.as_str())
+    }
+
+    /// Route name and store for `key`.
+    fn target(&self, key: &str) -> Result<(&str, &dyn ObjectStore)> {
+        if let Some((prefix, store)) = self
+            .routes
+            .iter()
+            .find(|(p, _)| key.starts_with(p.as_str()))
+        {
+            return Ok((prefix, store.as_ref()));
+        }
+        self.default
+            .as_deref()
+            .map(|store| (DEFAULT_ROUTE, store))
+            .ok_or_else(|| Error::InvalidArgument(format!("no route for key: {key}")))
+    }
+
+    fn store(&self, key: &str) -> Result<&dyn ObjectStore> {
+        self.target(key).map(|(_, store)| store)
+    }
+
+    /// Every configured store, default last.
+    fn all(&self) -> impl Iterator<Item = (&str, &dyn ObjectStore)> {
+        self.routes
+            .iter()
+            .map(|(p, s)| (p.as_str(), s.as_ref()))
+            .chain(self.default.as_deref().map(|s| (DEFAULT_ROUTE, s)))
+    }
+}
+
+#[async_trait]
+impl ObjectStore for RoutingObjectStore {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        self.store(key)?.put(key, data, metadata).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.store(key)?.get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.store(key)?.delete(key).await
+    }
+
+    /// Lists the store owning the requested prefix plus every route nested
+    /// under it, and merges the results.
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        let prefix = list_req.prefix.clone().unwrap_or_default();
+        let owner = match self.target(&prefix) {
+            Ok((name, _)) => Some(name),
+            // No default: only the nested routes can hold matching keys.
+            Err(_) => None,
+        };
+        let targets: Vec<(&str, &dyn ObjectStore)> = self
+            .all()
+            .filter(|&(name, _)| {
+                Some(name) == owner || (name != DEFAULT_ROUTE && name.starts_with(&prefix))
+            })
+            .collect();
+        if targets.is_empty() {
+            return Err(Error::InvalidArgument(format!(
+                "no route for prefix: {prefix}"
+            )));
+        }
+        merge_listings(&targets, list_req, |name, key| {
+            self.target(key).is_ok_and(|(owner, _)| owner == name)
+        })
+        .await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.store(key)?.exists(key).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.store(key)?.get_metadata(key).await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.store(key)?.update_metadata(key, metadata).await
+    }
+
+    /// Serving only if every route is; otherwise the first route that is
+    /// not, with its prefix in the message.
+    async fn health(&self) -> Result<HealthResponse> {
+        let routes: Vec<_> = self.all().collect();
+        let responses = try_join_all(routes.iter().map(|(_, s)| s.health())).await?;
+        for ((name, _), response) in routes.iter().zip(responses) {
+            if response.status != HealthStatus::Serving {
+                let name = if name.is_empty() { "default" } else { name };
+                return Ok(HealthResponse {
+                    status: response.status,
+                    message: Some(format!(
+                        "route {name}: {}",
+                        response.message.as_deref().unwrap_or("not serving")
+                    )),
+                });
+            }
+        }
+        Ok(HealthResponse {
+            status: HealthStatus::Serving,
+            message: None,
+        })
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        self.store(key)?.get_stream(key).await
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.store(key)?.put_stream(key, stream, metadata).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+
+    async fn keys(store: &RoutingObjectStore, prefix: &str) -> Vec<String> {
+        let req = ListRequest {
+            prefix: Some(prefix.into()),
+            ..Default::default()
+        };
+        let page = store.list(req).await.unwrap();
+        page.objects.into_iter().map(|o| o.key).collect()
+    }
+
+    #[tokio::test]
+    async fn routes_by_longest_prefix_and_merges_listings() {
+        let (logs, hot_logs, default) = (
+            MemoryStore::default(),
+            MemoryStore::default(),
+            MemoryStore::default(),
+        );
+        let store = RoutingObjectStore::new()
+            .route("logs/", logs.clone())
+            .route("logs/hot/", hot_logs.clone())
+            .with_default(default.clone());
+
+        for key in ["logs/a", "logs/hot/b", "data/c"] {
+            store
+                .put(key, Bytes::from_static(b"x"), None)
+                .await
+                .unwrap();
+        }
+        assert!(logs.exists("logs/a").await.unwrap());
+        assert!(hot_logs.exists("logs/hot/b").await.unwrap());
+        assert!(default.exists("data/c").await.unwrap());
+        assert_eq!(store.route_for("logs/hot/b"), Some("logs/hot/"));
+        assert_eq!(store.route_for("data/c"), None);
+
+        // A stray copy in the wrong store is not listed.
+        default.put("logs/stray", Bytes::new(), None).await.unwrap();
+        assert_eq!(keys(&store, "").await, ["data/c", "logs/a", "logs/hot/b"]);
+        assert_eq!(keys(&store, "logs/").await, ["logs/a", "logs/hot/b"]);
+        assert_eq!(keys(&store, "logs/hot/").await, ["logs/hot/b"]);
+    }
+
+    #[tokio::test]
+    async fn unrouted_keys_fail_without_default() {
+        let store = RoutingObjectStore::new().route("logs/", MemoryStore::default());
+        assert!(matches!(
+            store.get("other").await,
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(keys(&store, "").await.is_empty());
+    }
+}
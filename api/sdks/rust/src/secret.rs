@@ -0,0 +1,84 @@
+//! Redacted, zeroizing storage for credentials.
+
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Placeholder printed instead of a secret.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A string holding a credential such as a bearer token.
+///
+/// `Debug` and `Display` print `[REDACTED]`, so secrets never reach logs or
+/// error messages by accident, and the buffer is zeroed when the value is
+/// dropped.  Call [`expose_secret`](Self::expose_secret) where the raw value
+/// is genuinely needed.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wrap `secret`.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    /// The raw secret.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// Return true when the secret is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatting_is_redacted() {
+        let secret = SecretString::from("hunter2");
+        assert_eq!(format!("{secret}"), REDACTED);
+        assert_eq!(format!("{secret:?}"), REDACTED);
+        assert_eq!(format!("{:?}", Some(&secret)), "Some([REDACTED])");
+        assert_eq!(secret.expose_secret(), "hunter2");
+    }
+}
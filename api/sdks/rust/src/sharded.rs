@@ -0,0 +1,393 @@
+//! Consistent-hash sharding of keys across several servers.
+//!
+//! [`ShardedObjectStore`] addresses a horizontally partitioned fleet: each
+//! key is owned by exactly one named shard, chosen by consistent hashing
+//! with virtual nodes, so adding or removing a shard only moves the keys
+//! that hash to it:
+//!
+//! ```no_run
+//! use go_objstore::sharded::{migrate, ShardedObjectStore};
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let store = ShardedObjectStore::new([
+//!     ("a", ObjectStoreClient::rest("http://objstore-a:8080")?),
+//!     ("b", ObjectStoreClient::rest("http://objstore-b:8080")?),
+//! ])?;
+//! store.put("users/42.json", "{}".into(), None).await?;
+//!
+//! // Grow the fleet, then move the keys whose owner changed.
+//! let grown = ShardedObjectStore::new([
+//!     ("a", ObjectStoreClient::rest("http://objstore-a:8080")?),
+//!     ("b", ObjectStoreClient::rest("http://objstore-b:8080")?),
+//!     ("c", ObjectStoreClient::rest("http://objstore-c:8080")?),
+//! ])?;
+//! let stats = migrate(&store, &grown, None).await?;
+//! println!("moved {} of {} objects", stats.moved, stats.scanned);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Shard names, not endpoints, determine placement: keep a shard's name when
+//! its address changes.  `list` fans out to every shard and merges the
+//! results; `max_results` applies per shard, so a page holds up to
+//! `max_results` objects from each.
+
+use crate::client::{list_pages, ByteStream, ObjectStore, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::try_join_all;
+use futures::TryStreamExt;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Virtual nodes per shard unless overridden.
+pub const DEFAULT_VIRTUAL_NODES: usize = 160;
+
+/// Keys spread over named shards by consistent hashing.  See the
+/// [module docs](self).
+#[derive(Debug, Clone)]
+pub struct ShardedObjectStore<S> {
+    shards: Vec<(String, S)>,
+    /// (point, shard index), sorted by point.
+    ring: Vec<(u64, usize)>,
+}
+
+impl<S: ObjectStore> ShardedObjectStore<S> {
+    /// Shard across `shards`, given as `(name, store)` pairs.  Names must be
+    /// unique and there must be at least one shard.
+    pub fn new<N: Into<String>>(shards: impl IntoIterator<Item = (N, S)>) -> Result<Self> {
+        let shards: Vec<(String, S)> = shards.into_iter().map(|(n, s)| (n.into(), s)).collect();
+        if shards.is_empty() {
+            return Err(Error::InvalidArgument("no shards configured".into()));
+        }
+        let mut names = BTreeSet::new();
+        if let Some((dup, _)) = shards.iter().find(|(n, _)| !names.insert(n.as_str())) {
+            return Err(Error::InvalidArgument(format!(
+                "duplicate shard name: {dup}"
+            )));
+        }
+        let mut store = Self {
+            shards,
+            ring: Vec::new(),
+        };
+        store.build_ring(DEFAULT_VIRTUAL_NODES);
+        Ok(store)
+    }
+
+    /// Place each shard at `vnodes` points on the ring (minimum 1).  More
+    /// points spread keys more evenly.  All clients of a fleet must agree.
+    pub fn with_virtual_nodes(mut self, vnodes: usize) -> Self {
+        self.build_ring(vnodes.max(1));
+        self
+    }
+
+    fn build_ring(&mut self, vnodes: usize) {
+        self.ring = self
+            .shards
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (name, _))| (0..vnodes).map(move |v| (hash(&format!("{name}#{v}")), i)))
+            .collect();
+        self.ring.sort_unstable();
+    }
+
+    fn index_for(&self, key: &str) -> usize {
+        let point = hash(key);
+        let pos = self.ring.partition_point(|&(p, _)| p < point);
+        self.ring[pos % self.ring.len()].1
+    }
+
+    /// Name of the shard owning `key`.
+    pub fn shard_for(&self, key: &str) -> &str {
+        &self.shards[self.index_for(key)].0
+    }
+
+    /// The store owning `key`.
+    pub fn shard(&self, key: &str) -> &S {
+        &self.shards[self.index_for(key)].1
+    }
+
+    /// All shards, as `(name, store)`, in configuration order.
+    pub fn shards(&self) -> impl Iterator<Item = (&str, &S)> {
+        self.shards.iter().map(|(n, s)| (n.as_str(), s))
+    }
+}
+
+/// First eight bytes of SHA-256: stable across processes and platforms.
+fn hash(s: &str) -> u64 {
+    let digest = ring::digest::digest(&ring::digest::SHA256, s.as_bytes());
+    u64::from_be_bytes(digest.as_ref()[..8].try_into().unwrap())
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for ShardedObjectStore<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        self.shard(key).put(key, data, metadata).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.shard(key).get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.shard(key).delete(key).await
+    }
+
+    /// Lists every shard and merges the pages; see [`merge_listings`].
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        let targets: Vec<(&str, &dyn ObjectStore)> = self
+            .shards
+            .iter()
+            .map(|(n, s)| (n.as_str(), s as &dyn ObjectStore))
+            .collect();
+        merge_listings(&targets, list_req, |_, _| true).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.shard(key).exists(key).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.shard(key).get_metadata(key).await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.shard(key).update_metadata(key, metadata).await
+    }
+
+    /// Serving only if every shard is; otherwise the first shard that is
+    /// not, with its name prefixed to the message.
+    async fn health(&self) -> Result<HealthResponse> {
+        let responses = try_join_all(self.shards.iter().map(|(_, s)| s.health())).await?;
+        for ((name, _), response) in self.shards.iter().zip(&responses) {
+            if response.status != HealthStatus::Serving {
+                return Ok(HealthResponse {
+                    status: response.status,
+                    message: Some(format!(
+                        "shard {name}: {}",
+                        response.message.as_deref().unwrap_or("not serving")
+                    )),
+                });
+            }
+        }
+        Ok(HealthResponse {
+            status: HealthStatus::Serving,
+            message: None,
+        })
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        self.shard(key).get_stream(key).await
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.shard(key).put_stream(key, stream, metadata).await
+    }
+}
+
+/// List several named stores with the same request and merge the pages,
+/// keeping the objects for which `keep(name, key)` holds.
+///
+/// The continuation token carries each unfinished store's own token;
+/// stores absent from it are done and are not queried again.
+pub(crate) async fn merge_listings(
+    targets: &[(&str, &dyn ObjectStore)],
+    list_req: ListRequest,
+    keep: impl Fn(&str, &str) -> bool,
+) -> Result<ListResponse> {
+    let pending: BTreeMap<String, String> = match &list_req.continue_from {
+        Some(token) if !token.is_empty() => serde_json::from_str(token).map_err(|e| {
+            Error::InvalidArgument(format!("invalid merged continuation token: {e}"))
+        })?,
+        _ => targets
+            .iter()
+            .map(|(n, _)| (n.to_string(), String::new()))
+            .collect(),
+    };
+
+    let pages = try_join_all(
+        targets
+            .iter()
+            .filter_map(|&(name, store)| Some((name, store, pending.get(name)?)))
+            .map(|(name, store, token)| {
+                let req = ListRequest {
+                    continue_from: (!token.is_empty()).then(|| token.clone()),
+                    ..list_req.clone()
+                };
+                async move { Ok::<_, Error>((name, store.list(req).await?)) }
+            }),
+    )
+    .await?;
+
+    let mut objects = Vec::new();
+    let mut prefixes = BTreeSet::new();
+    let mut next = BTreeMap::new();
+    for (name, page) in pages {
+        objects.extend(page.objects.into_iter().filter(|o| keep(name, &o.key)));
+        prefixes.extend(page.common_prefixes);
+        if let Some(token) = page.next_token.filter(|t| page.truncated && !t.is_empty()) {
+            next.insert(name, token);
+        }
+    }
+    objects.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let truncated = !next.is_empty();
+    Ok(ListResponse {
+        objects,
+        common_prefixes: prefixes.into_iter().collect(),
+        next_token: truncated
+            .then(|| serde_json::to_string(&next))
+            .transpose()
+            .map_err(|e| Error::Generic(e.to_string()))?,
+        truncated,
+    })
+}
+
+/// What [`migrate`] moved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationStats {
+    /// Objects listed across the old shards.
+    pub scanned: u64,
+    /// Objects copied to a new owner and deleted from the old one.
+    pub moved: u64,
+    /// Bytes copied.
+    pub bytes: u64,
+}
+
+/// Move every object under `prefix` whose owning shard differs between
+/// `from` and `to`: copy it to its new owner, then delete the old copy.
+///
+/// Shards are matched by name, so a shard present in both layouts keeps
+/// the keys it still owns.  Safe to re-run after an interruption; readers
+/// should use `from` until it completes and `to` afterwards.
+pub async fn migrate<S, T>(
+    from: &ShardedObjectStore<S>,
+    to: &ShardedObjectStore<T>,
+    prefix: Option<&str>,
+) -> Result<MigrationStats>
+where
+    S: ObjectStore,
+    T: ObjectStore,
+{
+    let mut stats = MigrationStats::default();
+    for (name, shard) in from.shards() {
+        let request = ListRequest {
+            prefix: prefix.map(str::to_string),
+            max_results: Some(LIST_PAGE_SIZE),
+            ..Default::default()
+        };
+        let keys: Vec<String> = list_pages(shard, request)
+            .map_ok(|page| {
+                futures::stream::iter(page.objects.into_iter().map(|o| Ok::<_, Error>(o.key)))
+            })
+            .try_flatten()
+            .try_collect()
+            .await?;
+
+        for key in keys {
+            stats.scanned += 1;
+            if to.shard_for(&key) == name {
+                continue;
+            }
+            let (data, metadata) = shard.get(&key).await?;
+            stats.bytes += data.len() as u64;
+            to.shard(&key).put(&key, data, Some(metadata)).await?;
+            shard.delete(&key).await?;
+            stats.moved += 1;
+        }
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+    use std::collections::HashMap;
+
+    fn fleet(
+        names: &[&str],
+        stores: &HashMap<&str, MemoryStore>,
+    ) -> ShardedObjectStore<MemoryStore> {
+        ShardedObjectStore::new(names.iter().map(|&n| (n, stores[n].clone()))).unwrap()
+    }
+
+    #[test]
+    fn rejects_empty_and_duplicate_shards() {
+        assert!(ShardedObjectStore::<MemoryStore>::new(Vec::<(&str, MemoryStore)>::new()).is_err());
+        assert!(ShardedObjectStore::new([
+            ("a", MemoryStore::default()),
+            ("a", MemoryStore::default())
+        ])
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn routes_keys_and_merges_paged_listings() {
+        let stores: HashMap<_, _> = ["a", "b", "c"]
+            .into_iter()
+            .map(|n| (n, MemoryStore::default()))
+            .collect();
+        let store = fleet(&["a", "b", "c"], &stores);
+        for i in 0..60 {
+            let key = format!("k{i:02}");
+            store
+                .put(&key, Bytes::from(key.clone()), None)
+                .await
+                .unwrap();
+            assert!(stores[store.shard_for(&key)].exists(&key).await.unwrap());
+        }
+        assert!(stores.values().all(|s| !s.is_empty()));
+
+        let mut listed = Vec::new();
+        let mut req = ListRequest {
+            max_results: Some(7),
+            ..Default::default()
+        };
+        loop {
+            let page = store.list(req.clone()).await.unwrap();
+            listed.extend(page.objects.into_iter().map(|o| o.key));
+            match page.next_token {
+                Some(token) if page.truncated => req.continue_from = Some(token),
+                _ => break,
+            }
+        }
+        listed.sort();
+        assert_eq!(listed.len(), 60);
+        listed.dedup();
+        assert_eq!(listed.len(), 60);
+    }
+
+    #[tokio::test]
+    async fn migrate_moves_only_reassigned_keys() {
+        let stores: HashMap<_, _> = ["a", "b", "c"]
+            .into_iter()
+            .map(|n| (n, MemoryStore::default()))
+            .collect();
+        let old = fleet(&["a", "b"], &stores);
+        for i in 0..200 {
+            old.put(&format!("k{i}"), Bytes::from_static(b"x"), None)
+                .await
+                .unwrap();
+        }
+
+        let new = fleet(&["a", "b", "c"], &stores);
+        let stats = migrate(&old, &new, None).await.unwrap();
+        assert_eq!(stats.scanned, 200);
+        assert_eq!(stats.moved, stores["c"].len() as u64);
+        // Consistent hashing: only keys now owned by the new shard move.
+        assert!(stats.moved > 0 && stats.moved < 120);
+        for i in 0..200 {
+            let key = format!("k{i}");
+            assert!(stores[new.shard_for(&key)].exists(&key).await.unwrap());
+        }
+        assert_eq!(migrate(&old, &new, None).await.unwrap().moved, 0);
+    }
+}
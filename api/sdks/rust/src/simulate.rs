@@ -0,0 +1,247 @@
+//! Dry runs of lifecycle policies.
+//!
+//! [`simulate_policy`] lists the objects a [`LifecyclePolicy`] covers and
+//! reports what the server would do to each and when, using the same rule
+//! as the server's apply pass: an object is acted on once it is older than
+//! the retention, measured from its last-modified time.  Nothing is
+//! registered or modified, so retention rules can be checked before
+//! `add_policy`:
+//!
+//! ```no_run
+//! use go_objstore::simulate::simulate_policy;
+//! use go_objstore::{LifecyclePolicy, ObjectStoreClient};
+//! use std::collections::HashMap;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let policy = LifecyclePolicy {
+//!     id: "expire-logs".into(),
+//!     prefix: "logs/".into(),
+//!     retention_seconds: 30 * 86400,
+//!     action: "delete".into(),
+//!     destination_type: None,
+//!     destination_settings: HashMap::new(),
+//! };
+//!
+//! let simulation = simulate_policy(&client, &policy, "").await?;
+//! for action in simulation.due() {
+//!     println!("would {} {} now", action.action, action.key);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::types::{LifecyclePolicy, ListRequest};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+
+/// What a policy would do to one object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedAction {
+    pub key: String,
+    /// `"delete"` or `"archive"`.
+    pub action: String,
+    pub last_modified: DateTime<Utc>,
+    /// When the object becomes eligible: last-modified plus retention.
+    pub due_at: DateTime<Utc>,
+}
+
+/// Result of [`simulate_policy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicySimulation {
+    /// The time the simulation was evaluated at.
+    pub as_of: DateTime<Utc>,
+    /// Objects listed under the effective prefix.
+    pub scanned: u64,
+    /// Every covered object, soonest due first.
+    pub actions: Vec<SimulatedAction>,
+    /// Covered objects without a last-modified time; the server skips them.
+    pub skipped: Vec<String>,
+}
+
+impl PolicySimulation {
+    /// Actions the server would take if policies were applied at `as_of`.
+    pub fn due(&self) -> impl Iterator<Item = &SimulatedAction> {
+        self.actions.iter().filter(|a| a.due_at < self.as_of)
+    }
+
+    /// Actions that only become due later.
+    pub fn pending(&self) -> impl Iterator<Item = &SimulatedAction> {
+        self.actions.iter().filter(|a| a.due_at >= self.as_of)
+    }
+}
+
+/// Simulate `policy` now over the objects under `prefix` (empty for all
+/// the objects the policy covers).
+pub async fn simulate_policy(
+    store: &dyn ObjectStore,
+    policy: &LifecyclePolicy,
+    prefix: &str,
+) -> Result<PolicySimulation> {
+    simulate_policy_at(store, policy, prefix, Utc::now()).await
+}
+
+/// Like [`simulate_policy`], evaluated as if policies were applied at
+/// `as_of`, e.g. to preview next week's deletions.
+///
+/// Fails with [`Error::InvalidArgument`] for policies the server would
+/// never act on: an unknown action, a negative retention, or `archive`
+/// without a destination.
+pub async fn simulate_policy_at(
+    store: &dyn ObjectStore,
+    policy: &LifecyclePolicy,
+    prefix: &str,
+    as_of: DateTime<Utc>,
+) -> Result<PolicySimulation> {
+    validate(policy)?;
+    let mut simulation = PolicySimulation {
+        as_of,
+        scanned: 0,
+        actions: Vec::new(),
+        skipped: Vec::new(),
+    };
+    // The policy covers keys under its own prefix; list the narrower one.
+    let listing = if prefix.starts_with(&policy.prefix) {
+        prefix
+    } else if policy.prefix.starts_with(prefix) {
+        &policy.prefix
+    } else {
+        return Ok(simulation);
+    };
+
+    let retention = chrono::Duration::seconds(policy.retention_seconds);
+    let request = ListRequest {
+        prefix: Some(listing.to_string()).filter(|p| !p.is_empty()),
+        max_results: Some(LIST_PAGE_SIZE),
+        ..Default::default()
+    };
+    let mut pages = list_pages(store, request);
+    while let Some(page) = pages.next().await {
+        for object in page?.objects {
+            simulation.scanned += 1;
+            match object.metadata.last_modified {
+                Some(last_modified) => simulation.actions.push(SimulatedAction {
+                    key: object.key,
+                    action: policy.action.clone(),
+                    last_modified,
+                    due_at: last_modified + retention,
+                }),
+                None => simulation.skipped.push(object.key),
+            }
+        }
+    }
+    simulation
+        .actions
+        .sort_by(|a, b| (a.due_at, &a.key).cmp(&(b.due_at, &b.key)));
+    Ok(simulation)
+}
+
+fn validate(policy: &LifecyclePolicy) -> Result<()> {
+    let invalid = |reason: &str| {
+        Err(Error::InvalidArgument(format!(
+            "policy {}: {reason}",
+            policy.id
+        )))
+    };
+    let has_destination = policy
+        .destination_type
+        .as_deref()
+        .is_some_and(|d| !d.is_empty());
+    match policy.action.as_str() {
+        "delete" => {}
+        "archive" if has_destination => {}
+        "archive" => return invalid("archive action without a destination"),
+        other => return invalid(&format!("unknown action {other:?}")),
+    }
+    if policy.retention_seconds < 0 {
+        return invalid("negative retention");
+    }
+    Ok(())
+}
+
+impl ObjectStoreClient {
+    /// Report what `policy` would do to the objects under `prefix`.  See
+    /// the [`simulate`](crate::simulate) module.
+    pub async fn simulate_policy(
+        &self,
+        policy: &LifecyclePolicy,
+        prefix: &str,
+    ) -> Result<PolicySimulation> {
+        simulate_policy(self, policy, prefix).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use chrono::TimeZone;
+    use mockito::Matcher;
+    use std::collections::HashMap;
+
+    fn policy(prefix: &str, action: &str) -> LifecyclePolicy {
+        LifecyclePolicy {
+            id: "p".into(),
+            prefix: prefix.into(),
+            retention_seconds: 7 * 86400,
+            action: action.into(),
+            destination_type: None,
+            destination_settings: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_due_pending_and_skipped_objects() {
+        let mut server = mockito::Server::new_async().await;
+        let list = server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "logs/app/".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"objects":[
+                    {"key":"logs/app/new","size":1,"modified":"2024-05-09T00:00:00Z"},
+                    {"key":"logs/app/old","size":1,"modified":"2024-04-01T00:00:00Z"},
+                    {"key":"logs/app/unknown","size":1}
+                ],"truncated":false}"#,
+            )
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+        let as_of = Utc.with_ymd_and_hms(2024, 5, 10, 0, 0, 0).unwrap();
+
+        let sim = simulate_policy_at(&client, &policy("logs/", "delete"), "logs/app/", as_of)
+            .await
+            .unwrap();
+        list.assert_async().await;
+        assert_eq!(sim.scanned, 3);
+        assert_eq!(sim.skipped, ["logs/app/unknown"]);
+        let due: Vec<_> = sim.due().map(|a| a.key.as_str()).collect();
+        assert_eq!(due, ["logs/app/old"]);
+        let pending: Vec<_> = sim.pending().collect();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(
+            pending[0].due_at,
+            Utc.with_ymd_and_hms(2024, 5, 16, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_inert_policies_and_disjoint_prefixes() {
+        let client = ObjectStoreClient::Rest(RestClient::new("http://127.0.0.1:1").unwrap());
+        for bad in [policy("", "archive"), policy("", "expire")] {
+            assert!(matches!(
+                client.simulate_policy(&bad, "").await,
+                Err(Error::InvalidArgument(_))
+            ));
+        }
+
+        // No listing needed when the prefixes cannot overlap.
+        let sim = client
+            .simulate_policy(&policy("logs/", "delete"), "data/")
+            .await
+            .unwrap();
+        assert_eq!((sim.scanned, sim.actions.len()), (0, 0));
+    }
+}
@@ -0,0 +1,76 @@
+//! Content-type detection from payload bytes.
+//!
+//! With [`ClientConfig::sniff_content_type`] set, the REST, gRPC, QUIC,
+//! and MCP clients inspect the leading bytes of every buffered `put` whose
+//! metadata has no `content_type` and record the detected type, so objects
+//! uploaded without one are still served with an accurate `Content-Type`:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::{ClientConfig, ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example(png: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     sniff_content_type: true,
+//!     ..Default::default()
+//! };
+//! let client = ObjectStoreClient::rest_with_config("http://localhost:8080", config)?;
+//! client.put("images/logo", Bytes::from(png), None).await?; // stored as image/png
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Detection uses magic numbers (via the `infer` crate), so it recognizes
+//! binary formats — images, audio and video, archives, documents,
+//! executables, fonts — but not text formats such as JSON, CSV, or HTML;
+//! those are stored without a content type as before.  Streamed uploads
+//! (`put_stream`) are not inspected.
+//!
+//! [`ClientConfig::sniff_content_type`]: crate::ClientConfig::sniff_content_type
+
+use crate::types::Metadata;
+
+/// The MIME type identified by `data`'s leading bytes, if any.
+pub fn detect(data: &[u8]) -> Option<&'static str> {
+    infer::get(data).map(|kind| kind.mime_type())
+}
+
+/// `metadata` with `content_type` set from [`detect`] when it is unset and
+/// the payload is recognized; otherwise `metadata` unchanged.
+pub fn with_detected_type(data: &[u8], metadata: Option<Metadata>) -> Option<Metadata> {
+    if metadata.as_ref().is_some_and(|m| m.content_type.is_some()) {
+        return metadata;
+    }
+    let Some(detected) = detect(data) else {
+        return metadata;
+    };
+    let mut metadata = metadata.unwrap_or_default();
+    metadata.content_type = Some(detected.to_string());
+    Some(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn fills_only_missing_types() {
+        assert_eq!(detect(PNG), Some("image/png"));
+        assert_eq!(detect(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(detect(b"{\"json\": true}"), None);
+
+        let sniffed = with_detected_type(PNG, None).unwrap();
+        assert_eq!(sniffed.content_type.as_deref(), Some("image/png"));
+
+        let given = Metadata {
+            content_type: Some("application/octet-stream".into()),
+            ..Default::default()
+        };
+        let kept = with_detected_type(PNG, Some(given.clone())).unwrap();
+        assert_eq!(kept.content_type, given.content_type);
+
+        assert!(with_detected_type(b"plain", None).is_none());
+    }
+}
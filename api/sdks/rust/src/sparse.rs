@@ -0,0 +1,277 @@
+//! Hole-aware storage of sparse files such as VM disk images.
+//!
+//! [`put_sparse`] reads a file in [`BLOCK_SIZE`] blocks and drops the blocks
+//! that are entirely zero, storing only the data extents together with an
+//! extent table.  [`get_sparse`] writes the extents back at their offsets
+//! and seeks over the holes, so the restored file is sparse again on file
+//! systems that support it:
+//!
+//! ```no_run
+//! use go_objstore::sparse::{get_sparse, put_sparse};
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//!
+//! let image = tokio::fs::File::open("disk.img").await?;
+//! let stats = put_sparse(&client, "images/disk.img", image).await?;
+//! println!("{} of {} bytes stored", stats.stored_bytes, stats.logical_size);
+//!
+//! let mut restored = tokio::fs::File::create("restored.img").await?;
+//! get_sparse(&client, "images/disk.img", &mut restored).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! go-objstore servers have no range or patch upload, so the zeros are
+//! elided inside the object itself: it is stored in a small container
+//! format (magic, logical size, extent table, extent data), tagged with the
+//! [`SPARSE_METADATA`] custom metadata field.  Such objects must be read
+//! back with [`get_sparse`]; a plain `get` returns the container.
+
+use crate::client::ObjectStore;
+use crate::error::{Error, Result};
+use crate::types::Metadata;
+use bytes::{BufMut, Bytes, BytesMut};
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+/// Granularity of hole detection; a block is a hole only if all zero.
+pub const BLOCK_SIZE: usize = 4096;
+
+/// Custom metadata key marking a sparse container, holding its version.
+pub const SPARSE_METADATA: &str = "sparse";
+
+/// Content type of sparse containers.
+pub const SPARSE_CONTENT_TYPE: &str = "application/vnd.objstore.sparse";
+
+const MAGIC: &[u8; 8] = b"OSSPARS1";
+const VERSION: &str = "1";
+
+/// A run of data at `offset` in the logical file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Extent {
+    offset: u64,
+    len: u64,
+}
+
+/// What [`put_sparse`] stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseStats {
+    /// Size of the original file.
+    pub logical_size: u64,
+    /// Bytes of extent data stored (excluding the extent table).
+    pub stored_bytes: u64,
+    /// Number of data extents.
+    pub extents: usize,
+}
+
+/// Read `reader` to the end and store it at `key` with zero blocks elided.
+pub async fn put_sparse<R>(store: &dyn ObjectStore, key: &str, mut reader: R) -> Result<SparseStats>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut extents: Vec<Extent> = Vec::new();
+    let mut data = BytesMut::new();
+    let mut block = vec![0u8; BLOCK_SIZE];
+    let mut offset = 0u64;
+
+    loop {
+        let n = read_block(&mut reader, &mut block).await?;
+        if n == 0 {
+            break;
+        }
+        let chunk = &block[..n];
+        if chunk.iter().any(|&b| b != 0) {
+            match extents.last_mut() {
+                Some(last) if last.offset + last.len == offset => last.len += n as u64,
+                _ => extents.push(Extent {
+                    offset,
+                    len: n as u64,
+                }),
+            }
+            data.extend_from_slice(chunk);
+        }
+        offset += n as u64;
+    }
+
+    let stats = SparseStats {
+        logical_size: offset,
+        stored_bytes: data.len() as u64,
+        extents: extents.len(),
+    };
+    let metadata = Metadata {
+        content_type: Some(SPARSE_CONTENT_TYPE.to_string()),
+        custom: HashMap::from([(SPARSE_METADATA.to_string(), VERSION.to_string())]),
+        ..Default::default()
+    };
+    store
+        .put(key, encode(offset, &extents, &data), Some(metadata))
+        .await?;
+    Ok(stats)
+}
+
+/// Restore the sparse object at `key` into `writer`, seeking over holes.
+///
+/// `writer` should start out empty (e.g. a newly created file).  Returns
+/// the logical size written.
+pub async fn get_sparse<W>(store: &dyn ObjectStore, key: &str, writer: &mut W) -> Result<u64>
+where
+    W: AsyncWrite + AsyncSeek + Unpin,
+{
+    let (body, metadata) = store.get(key).await?;
+    if metadata.custom.get(SPARSE_METADATA).map(String::as_str) != Some(VERSION) {
+        return Err(Error::InvalidResponse(format!(
+            "{key} is not a sparse object (missing {SPARSE_METADATA}={VERSION} metadata)"
+        )));
+    }
+    let (logical_size, extents, data) = decode(&body)?;
+
+    let mut position = 0usize;
+    let mut end = 0u64;
+    for extent in &extents {
+        let len = extent.len as usize;
+        writer.seek(SeekFrom::Start(extent.offset)).await?;
+        writer.write_all(&data[position..position + len]).await?;
+        position += len;
+        end = extent.offset + extent.len;
+    }
+    // A trailing hole still has to extend the file to its full size.
+    if end < logical_size {
+        writer.seek(SeekFrom::Start(logical_size - 1)).await?;
+        writer.write_all(&[0]).await?;
+    }
+    writer.flush().await?;
+    Ok(logical_size)
+}
+
+/// Fill `block` as far as possible; short only at end of input.
+async fn read_block<R: AsyncRead + Unpin>(reader: &mut R, block: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < block.len() {
+        let n = reader.read(&mut block[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+fn encode(logical_size: u64, extents: &[Extent], data: &[u8]) -> Bytes {
+    let mut out = BytesMut::with_capacity(24 + extents.len() * 16 + data.len());
+    out.put_slice(MAGIC);
+    out.put_u64(logical_size);
+    out.put_u64(extents.len() as u64);
+    for extent in extents {
+        out.put_u64(extent.offset);
+        out.put_u64(extent.len);
+    }
+    out.put_slice(data);
+    out.freeze()
+}
+
+fn decode(body: &[u8]) -> Result<(u64, Vec<Extent>, &[u8])> {
+    let corrupt = |what: &str| Error::InvalidResponse(format!("corrupt sparse object: {what}"));
+    let mut fields = Fields(body);
+
+    if fields
+        .take(MAGIC.len())
+        .ok_or_else(|| corrupt("truncated header"))?
+        != MAGIC
+    {
+        return Err(corrupt("bad magic"));
+    }
+    let logical_size = fields.u64().ok_or_else(|| corrupt("truncated header"))?;
+    let count = fields.u64().ok_or_else(|| corrupt("truncated header"))?;
+
+    let mut extents = Vec::new();
+    let mut data_len = 0u64;
+    let mut end = 0u64;
+    for _ in 0..count {
+        let (Some(offset), Some(len)) = (fields.u64(), fields.u64()) else {
+            return Err(corrupt("truncated extent table"));
+        };
+        let extent_end = offset
+            .checked_add(len)
+            .ok_or_else(|| corrupt("extent overflows"))?;
+        if offset < end || extent_end > logical_size {
+            return Err(corrupt("extents out of order or beyond logical size"));
+        }
+        end = extent_end;
+        data_len += len;
+        extents.push(Extent { offset, len });
+    }
+    if data_len != fields.0.len() as u64 {
+        return Err(corrupt("extent data length mismatch"));
+    }
+    Ok((logical_size, extents, fields.0))
+}
+
+/// Big-endian field reader over the container header.
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn round_trips_and_skips_zero_blocks() {
+        // data, hole, data (partial last block), trailing hole
+        let mut image = vec![0u8; BLOCK_SIZE * 6];
+        image[..BLOCK_SIZE].fill(1);
+        image[BLOCK_SIZE * 3 + 5] = 7;
+        image.truncate(BLOCK_SIZE * 5 + 100);
+
+        let store = MemoryStore::default();
+        let stats = put_sparse(&store, "img", Cursor::new(image.clone()))
+            .await
+            .unwrap();
+        assert_eq!(stats.logical_size, image.len() as u64);
+        assert_eq!(stats.extents, 2);
+        assert_eq!(stats.stored_bytes, 2 * BLOCK_SIZE as u64);
+
+        let mut restored = Cursor::new(Vec::new());
+        let size = get_sparse(&store, "img", &mut restored).await.unwrap();
+        assert_eq!(size, image.len() as u64);
+        assert_eq!(restored.into_inner(), image);
+    }
+
+    #[tokio::test]
+    async fn rejects_plain_and_corrupt_objects() {
+        let store = MemoryStore::default();
+        store
+            .put("plain", Bytes::from_static(b"data"), None)
+            .await
+            .unwrap();
+        let mut out = Cursor::new(Vec::new());
+        assert!(matches!(
+            get_sparse(&store, "plain", &mut out).await,
+            Err(Error::InvalidResponse(_))
+        ));
+
+        let mut bad = encode(10, &[Extent { offset: 8, len: 4 }], b"abcd").to_vec();
+        assert!(decode(&bad).is_err());
+        bad.truncate(12);
+        assert!(decode(&bad).is_err());
+    }
+}
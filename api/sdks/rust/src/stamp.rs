@@ -0,0 +1,309 @@
+//! Automatic provenance metadata on uploads.
+//!
+//! A [`MetadataStamper`] holds the standard custom metadata fields every
+//! object should carry — who wrote it, when, and from which build — and
+//! [`Stamped`] adds them to every `put` so call sites cannot forget:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::stamp::MetadataStamper;
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let stamper = MetadataStamper::from_env().app_version(env!("CARGO_PKG_VERSION"));
+//! let store = ObjectStoreClient::rest("http://localhost:8080")?.with_stamper(stamper);
+//!
+//! // Stored with created-by, created-at, sdk-version, app-version, and
+//! // (when the environment names one) git-sha.
+//! store.put("reports/q3.csv", Bytes::from("..."), None).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Values the caller sets on a `put` win over stamped ones.  Only `put` is
+//! stamped: `update_metadata` replaces an object's metadata as given, so
+//! carry the fields over from `get_metadata` when rewriting it.
+
+use crate::client::{ByteStream, ObjectStore, ObjectStoreClient};
+use crate::error::Result;
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{SecondsFormat, Utc};
+use std::collections::BTreeMap;
+
+/// Custom metadata key naming the user or service that wrote an object.
+pub const CREATED_BY_METADATA: &str = "created-by";
+
+/// Custom metadata key holding the upload time as RFC 3339.
+pub const CREATED_AT_METADATA: &str = "created-at";
+
+/// Custom metadata key naming the SDK build, e.g. `go-objstore-rust/0.2.0`.
+pub const SDK_VERSION_METADATA: &str = "sdk-version";
+
+/// Custom metadata key holding the writing application's version.
+pub const APP_VERSION_METADATA: &str = "app-version";
+
+/// Custom metadata key holding the writing application's git commit.
+pub const GIT_SHA_METADATA: &str = "git-sha";
+
+/// Environment variable naming the writer for [`MetadataStamper::from_env`];
+/// `USER` / `USERNAME` are used when it is unset.
+pub const CREATED_BY_ENV: &str = "OBJSTORE_CREATED_BY";
+
+/// Environment variable holding the application version for
+/// [`MetadataStamper::from_env`].
+pub const APP_VERSION_ENV: &str = "OBJSTORE_APP_VERSION";
+
+/// Environment variables checked, in order, for the git commit by
+/// [`MetadataStamper::from_env`]: an explicit one first, then those set by
+/// common CI systems and platforms.
+pub const GIT_SHA_ENVS: [&str; 5] = [
+    "GIT_SHA",
+    "GIT_COMMIT",
+    "GITHUB_SHA",
+    "CI_COMMIT_SHA",
+    "SOURCE_VERSION",
+];
+
+/// The fields stamped on uploads.  See the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataStamper {
+    fields: BTreeMap<String, String>,
+    created_at: bool,
+}
+
+impl Default for MetadataStamper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataStamper {
+    /// Stamp `sdk-version` and `created-at` only.
+    pub fn new() -> Self {
+        let sdk = concat!("go-objstore-rust/", env!("CARGO_PKG_VERSION"));
+        Self {
+            fields: BTreeMap::from([(SDK_VERSION_METADATA.to_string(), sdk.to_string())]),
+            created_at: true,
+        }
+    }
+
+    /// Like [`new`](Self::new), plus `created-by`, `app-version`, and
+    /// `git-sha` from [`CREATED_BY_ENV`], [`APP_VERSION_ENV`], and
+    /// [`GIT_SHA_ENVS`] where set.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let lookup = |name: &str| lookup(name).filter(|v| !v.is_empty());
+        let mut stamper = Self::new();
+        let created_by = lookup(CREATED_BY_ENV)
+            .or_else(|| lookup("USER"))
+            .or_else(|| lookup("USERNAME"));
+        let fields = [
+            (CREATED_BY_METADATA, created_by),
+            (APP_VERSION_METADATA, lookup(APP_VERSION_ENV)),
+            (
+                GIT_SHA_METADATA,
+                GIT_SHA_ENVS.iter().find_map(|n| lookup(n)),
+            ),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                stamper = stamper.field(key, value);
+            }
+        }
+        stamper
+    }
+
+    /// Stamp `created-by` with this user or service identity.
+    pub fn created_by(self, identity: impl Into<String>) -> Self {
+        self.field(CREATED_BY_METADATA, identity)
+    }
+
+    /// Stamp `app-version`.
+    pub fn app_version(self, version: impl Into<String>) -> Self {
+        self.field(APP_VERSION_METADATA, version)
+    }
+
+    /// Stamp `git-sha`.
+    pub fn git_sha(self, sha: impl Into<String>) -> Self {
+        self.field(GIT_SHA_METADATA, sha)
+    }
+
+    /// Stamp an arbitrary custom metadata field, replacing any earlier
+    /// value for `key`.
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// Do not stamp `created-at`.
+    pub fn without_created_at(mut self) -> Self {
+        self.created_at = false;
+        self
+    }
+
+    /// `metadata` with the stamped fields added where not already set.
+    pub fn stamp(&self, metadata: Option<Metadata>) -> Metadata {
+        let mut metadata = metadata.unwrap_or_default();
+        for (key, value) in &self.fields {
+            metadata
+                .custom
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        if self.created_at {
+            metadata
+                .custom
+                .entry(CREATED_AT_METADATA.to_string())
+                .or_insert_with(|| Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+        }
+        metadata
+    }
+}
+
+/// A store that adds a [`MetadataStamper`]'s fields to every `put`.
+#[derive(Debug, Clone)]
+pub struct Stamped<S> {
+    inner: S,
+    stamper: MetadataStamper,
+}
+
+impl<S: ObjectStore> Stamped<S> {
+    /// Wrap `inner`, stamping uploads with `stamper`.
+    pub fn new(inner: S, stamper: MetadataStamper) -> Self {
+        Self { inner, stamper }
+    }
+
+    /// The wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The stamper in use.
+    pub fn stamper(&self) -> &MetadataStamper {
+        &self.stamper
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for Stamped<S> {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        let metadata = self.stamper.stamp(metadata);
+        self.inner.put(key, data, Some(metadata)).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.inner.get(key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.inner.delete(key).await
+    }
+
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.inner.list(list_req).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.inner.get_metadata(key).await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.inner.update_metadata(key, metadata).await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.inner.health().await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        let metadata = self.stamper.stamp(metadata);
+        self.inner.put_stream(key, stream, Some(metadata)).await
+    }
+}
+
+impl ObjectStoreClient {
+    /// Wrap this client so every `put` is stamped by `stamper`.  See the
+    /// [`stamp`](crate::stamp) module.
+    pub fn with_stamper(self, stamper: MetadataStamper) -> Stamped<Self> {
+        Stamped::new(self, stamper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+    use std::collections::HashMap;
+
+    #[test]
+    fn env_fields_and_caller_values() {
+        let env = HashMap::from([
+            ("USER", "alice"),
+            ("GIT_SHA", ""),
+            ("GITHUB_SHA", "abc123"),
+            (APP_VERSION_ENV, "1.4.2"),
+        ]);
+        let stamper = MetadataStamper::from_lookup(|n| env.get(n).map(|v| v.to_string()));
+        let mut given = Metadata::default();
+        given
+            .custom
+            .insert(APP_VERSION_METADATA.into(), "9.9".into());
+
+        let custom = stamper.stamp(Some(given)).custom;
+        assert_eq!(custom[CREATED_BY_METADATA], "alice");
+        assert_eq!(custom[GIT_SHA_METADATA], "abc123");
+        assert_eq!(custom[APP_VERSION_METADATA], "9.9");
+        assert!(custom[SDK_VERSION_METADATA].starts_with("go-objstore-rust/"));
+        assert!(chrono::DateTime::parse_from_rfc3339(&custom[CREATED_AT_METADATA]).is_ok());
+
+        let bare = MetadataStamper::from_lookup(|_| None).without_created_at();
+        assert_eq!(bare.stamp(None).custom.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn puts_are_stamped() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/k")
+            .match_header(
+                "x-object-metadata",
+                Matcher::AllOf(vec![
+                    Matcher::Regex(r#""created-by":"batch-job""#.into()),
+                    Matcher::Regex(r#""created-at":"\d{4}-"#.into()),
+                    Matcher::Regex(r#""owner":"ops""#.into()),
+                ]),
+            )
+            .with_status(201)
+            .create_async()
+            .await;
+        let store = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap())
+            .with_stamper(MetadataStamper::new().created_by("batch-job"));
+
+        let mut metadata = Metadata::default();
+        metadata.custom.insert("owner".into(), "ops".into());
+        store
+            .put("k", Bytes::from("x"), Some(metadata))
+            .await
+            .unwrap();
+        put.assert_async().await;
+    }
+}
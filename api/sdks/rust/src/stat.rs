@@ -0,0 +1,95 @@
+//! Metadata for many keys at once.
+//!
+//! [`get_metadata_many`] stats a set of keys with bounded concurrency and
+//! returns each key's own result, so one missing object does not fail the
+//! rest:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let stats = client.get_metadata_many(["a.txt", "b.txt", "c.txt"]).await;
+//! for (key, result) in &stats {
+//!     match result {
+//!         Ok(meta) => println!("{key}: {} bytes", meta.size),
+//!         Err(e) => println!("{key}: {e}"),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! No go-objstore transport has a batched metadata call yet, so every key
+//! costs one request; the fan-out keeps [`STAT_CONCURRENCY`] of them in
+//! flight over the client's shared connection.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::Result;
+use crate::types::Metadata;
+use futures::StreamExt;
+use std::collections::{BTreeSet, HashMap};
+
+/// Metadata requests kept in flight by
+/// [`ObjectStoreClient::get_metadata_many`].
+pub const STAT_CONCURRENCY: usize = 32;
+
+/// Fetch the metadata of every key in `keys`, with at most `concurrency`
+/// (minimum 1) requests in flight.  Duplicate keys are fetched once.
+pub async fn get_metadata_many<I, K>(
+    store: &dyn ObjectStore,
+    keys: I,
+    concurrency: usize,
+) -> HashMap<String, Result<Metadata>>
+where
+    I: IntoIterator<Item = K>,
+    K: Into<String>,
+{
+    let keys: BTreeSet<String> = keys.into_iter().map(Into::into).collect();
+    futures::stream::iter(keys)
+        .map(|key| async move {
+            let result = store.get_metadata(&key).await;
+            (key, result)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+impl ObjectStoreClient {
+    /// Fetch the metadata of every key in `keys`.  See the
+    /// [`stat`](crate::stat) module.
+    pub async fn get_metadata_many<I, K>(&self, keys: I) -> HashMap<String, Result<Metadata>>
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        get_metadata_many(self, keys, STAT_CONCURRENCY).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::error::Error;
+    use crate::test_support::MemoryStore;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn returns_per_key_results_with_bounded_concurrency() {
+        let store = MemoryStore::default().with_delay(|_, _, _| Duration::from_millis(5));
+        let mut keys: Vec<String> = (0..20).map(|i| format!("ok-{i}")).collect();
+        for key in &keys {
+            store.insert(key, key.clone());
+        }
+        keys.extend(["missing".to_string(), "ok-0".to_string()]);
+
+        let results = get_metadata_many(&store, keys, 4).await;
+        assert_eq!(results.len(), 21);
+        assert_eq!(store.calls(Operation::GetMetadata), 21);
+        assert!(store.peak() <= 4);
+        assert_eq!(results["ok-12"].as_ref().unwrap().size, 5);
+        assert!(matches!(results["missing"], Err(Error::NotFound(_))));
+    }
+}
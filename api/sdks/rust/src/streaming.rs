@@ -3,27 +3,47 @@
 //! Each protocol client gains two extension methods:
 //!
 //! - `get_stream` – returns the raw bytes of an object as an async byte
-//!   stream ([`bytes::Bytes`] chunks from reqwest / gRPC / QUIC).
+//!   stream ([`bytes::Bytes`] chunks from reqwest / gRPC / QUIC), together
+//!   with its [`Metadata`], so large objects can be processed as they
+//!   arrive.
 //! - `put_stream` – accepts a [`futures::Stream`] of [`bytes::Bytes`]
 //!   chunks and stores the concatenated payload under `key`.  The REST
-//!   client streams the chunks as a chunked-transfer-encoded request body;
-//!   gRPC and QUIC buffer the stream before sending.
+//!   client streams the chunks as a chunked-transfer-encoded request body
+//!   and QUIC sends them as successive HTTP/3 DATA frames; gRPC buffers the
+//!   stream before sending.
 //!
-//! Unix and MCP clients do not have streaming variants; they buffer data
-//! in memory via their existing `put`/`get` methods.
+//! REST and QUIC re-chunk streamed uploads with the adaptive (AIMD) sizing
+//! configured by [`ClientConfig::chunking`](crate::ClientConfig::chunking).
+//! Downloads are yielded as the transport delivers them.
+//!
+//! REST and QUIC clients additionally offer `list_stream`, which yields
+//! each [`ObjectInfo`] as soon as it has been parsed from the response body
+//! and follows continuation tokens across pages.
+//!
+//! Unix and MCP clients carry an object in a single JSON-RPC message, so
+//! their `get_stream` buffers it and yields one chunk; they have no
+//! `put_stream` of their own, and
+//! [`ObjectStore::put_stream`](crate::ObjectStore::put_stream) buffers the
+//! stream for them.
 
+use crate::chunking::adaptive_chunks;
 use crate::error::{error_from_http_status, Error, Result};
 use crate::grpc_client::GrpcClient;
+use crate::in_flight::body_stream;
+use crate::list_parser::object_stream;
+use crate::mcp_client::McpClient;
 use crate::quic_client::QuicClient;
 use crate::rest_client::RestClient;
-use crate::types::Metadata;
+use crate::types::{ListRequest, Metadata, ObjectInfo, RequestOptions};
+use crate::unix_client::UnixClient;
 use bytes::Bytes;
 use futures::Stream;
 use futures::StreamExt;
+use std::ops::Range;
 
 /// Collect an async byte stream into a single [`Bytes`] allocation. Shared by
-/// the buffered `put_stream` implementations of the gRPC and QUIC transports.
-async fn collect_stream(
+/// the buffered `put_stream` implementations.
+pub(crate) async fn collect_stream(
     stream: impl Stream<Item = Result<Bytes>> + Send + 'static,
 ) -> Result<Bytes> {
     let mut chunks = Vec::new();
@@ -45,13 +65,9 @@ impl RestClient {
         &self,
         key: &str,
     ) -> Result<(impl Stream<Item = Result<Bytes>>, Metadata)> {
-        let url = format!(
-            "{}/objects/{}",
-            self.base_url_ref(),
-            urlencoding::encode(key)
-        );
+        let url = format!("{}/objects/{}", self.base_url_ref(), self.key_path(key)?);
 
-        let resp = self.http_client_ref().get(&url).send().await?;
+        let resp = self.send(self.request(reqwest::Method::GET, &url)).await?;
 
         if !resp.status().is_success() {
             return Err(error_from_http_status(
@@ -61,12 +77,111 @@ impl RestClient {
             ));
         }
 
-        let metadata = crate::rest_client::metadata_from_headers_pub(resp.headers());
-        let stream = resp.bytes_stream().map(|r| r.map_err(Error::Http));
+        let metadata = crate::rest_client::metadata_from_headers_pub(resp.headers())?;
+        let stream = body_stream(resp);
 
         Ok((stream, metadata))
     }
 
+    /// Like [`get_stream`](Self::get_stream), asking for the bytes from
+    /// `offset` on with a `Range` request.  `if_range` makes the request
+    /// conditional on the object's etag, so a changed object comes back
+    /// whole.
+    ///
+    /// Also returns the offset the stream actually starts at: `offset` for
+    /// a `206 Partial Content` answer, `0` when the server sends the whole
+    /// object (go-objstore servers currently ignore `Range`).  The metadata
+    /// size is the full object size in both cases.
+    pub async fn get_stream_from(
+        &self,
+        key: &str,
+        offset: u64,
+        if_range: Option<&str>,
+    ) -> Result<(impl Stream<Item = Result<Bytes>>, Metadata, u64)> {
+        let url = format!("{}/objects/{}", self.base_url_ref(), self.key_path(key)?);
+        let mut req = self
+            .request(reqwest::Method::GET, &url)
+            .header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        if let Some(etag) = if_range {
+            req = req.header(reqwest::header::IF_RANGE, etag);
+        }
+        let resp = self.send(req).await?;
+
+        if !resp.status().is_success() {
+            return Err(error_from_http_status(
+                resp.status().as_u16(),
+                Some(key),
+                format!("Failed to get object stream: {}", resp.status()),
+            ));
+        }
+
+        let mut metadata = crate::rest_client::metadata_from_headers_pub(resp.headers())?;
+        let start = if resp.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+            let (start, total) = resp
+                .headers()
+                .get(reqwest::header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range)
+                .ok_or_else(|| {
+                    Error::InvalidResponse(format!(
+                        "206 response for {key} without a valid Content-Range"
+                    ))
+                })?;
+            if let Some(total) = total {
+                metadata.size = total as i64;
+            }
+            start
+        } else {
+            0
+        };
+        let stream = body_stream(resp);
+
+        Ok((stream, metadata, start))
+    }
+
+    /// Fetch the bytes of `range` with a `Range` request.
+    ///
+    /// Also returns the offset the body starts at for a `206 Partial
+    /// Content` answer, or `None` when the server sends the whole object
+    /// instead.
+    pub(crate) async fn get_range(
+        &self,
+        key: &str,
+        range: Range<u64>,
+    ) -> Result<(Bytes, Option<u64>)> {
+        let url = format!("{}/objects/{}", self.base_url_ref(), self.key_path(key)?);
+        let req = self.request(reqwest::Method::GET, &url).header(
+            reqwest::header::RANGE,
+            format!("bytes={}-{}", range.start, range.end.saturating_sub(1)),
+        );
+        let resp = self.send(req).await?;
+
+        if !resp.status().is_success() {
+            return Err(error_from_http_status(
+                resp.status().as_u16(),
+                Some(key),
+                format!("Failed to get object range: {}", resp.status()),
+            ));
+        }
+
+        let start = if resp.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+            let (start, _) = resp
+                .headers()
+                .get(reqwest::header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range)
+                .ok_or_else(|| {
+                    Error::InvalidResponse(format!(
+                        "206 response for {key} without a valid Content-Range"
+                    ))
+                })?;
+            Some(start)
+        } else {
+            None
+        };
+        Ok((self.budget().read(key, resp).await?, start))
+    }
+
     /// Upload a stream of bytes to the REST server under `key`.
     ///
     /// The stream is sent as a chunked-transfer-encoded request body, so the
@@ -77,9 +192,55 @@ impl RestClient {
         stream: impl Stream<Item = Result<Bytes>> + Send + 'static,
         metadata: Option<Metadata>,
     ) -> Result<crate::types::PutResponse> {
-        self.put_body(key, reqwest::Body::wrap_stream(stream), metadata)
+        self.put_stream_with_options(key, stream, metadata, &RequestOptions::default())
             .await
     }
+
+    /// Upload a stream of bytes with per-call [`RequestOptions`].
+    ///
+    /// With [`BodyFormat::Form`](crate::types::BodyFormat::Form) the stream
+    /// becomes the `file` part of a `multipart/form-data` body and is still
+    /// sent without buffering.
+    pub async fn put_stream_with_options(
+        &self,
+        key: &str,
+        stream: impl Stream<Item = Result<Bytes>> + Send + 'static,
+        metadata: Option<Metadata>,
+        options: &RequestOptions,
+    ) -> Result<crate::types::PutResponse> {
+        let chunks = adaptive_chunks(stream, self.chunk_sizing());
+        self.put_body(key, reqwest::Body::wrap_stream(chunks), metadata, options)
+            .await
+    }
+
+    /// Stream every object matching `list_req`.
+    ///
+    /// Entries are yielded as they are parsed from the response body, and
+    /// further pages are requested automatically while the server reports
+    /// the listing as truncated.  `common_prefixes` are not reported; use
+    /// [`RestClient::list`] for delimiter queries.
+    pub fn list_stream(
+        &self,
+        list_req: ListRequest,
+    ) -> impl Stream<Item = Result<ObjectInfo>> + Send + 'static {
+        let client = self.clone();
+        object_stream(list_req, self.response_decoder(), move |req| {
+            let client = client.clone();
+            async move { client.list_body(&req).await }
+        })
+    }
+}
+
+/// Start offset and total size (`None` for `*`) of a
+/// `Content-Range: bytes <start>-<end>/<total>` header.
+pub(crate) fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _end) = range.split_once('-')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start.parse().ok()?, total))
 }
 
 // ── gRPC streaming ────────────────────────────────────────────────────────────
@@ -87,14 +248,14 @@ impl RestClient {
 impl GrpcClient {
     /// Stream the bytes of an object via the gRPC server-streaming `Get` RPC.
     ///
-    /// The gRPC `Get` method is already defined as server-streaming in the
-    /// protobuf schema.  This wrapper collects the stream of chunks into a
-    /// single Bytes value so the caller receives a one-item stream.  True
-    /// progressive streaming is available by calling `get` directly and
-    /// reading the underlying tonic stream.
-    pub async fn get_stream(&self, key: String) -> Result<impl Stream<Item = Result<Bytes>>> {
-        let (data, _meta) = self.get(key).await?;
-        Ok(futures::stream::once(async move { Ok(data) }))
+    /// Waits for the first message, which carries the metadata, then yields
+    /// each message's data as it arrives, so the object is never buffered
+    /// whole.
+    pub async fn get_stream(
+        &self,
+        key: String,
+    ) -> Result<(impl Stream<Item = Result<Bytes>>, Metadata)> {
+        self.open_get(key).await
     }
 
     /// Upload a stream of bytes via the gRPC `Put` RPC.
@@ -116,27 +277,72 @@ impl GrpcClient {
 impl QuicClient {
     /// Stream the bytes of an object from the QUIC/HTTP3 server.
     ///
-    /// The response body is yielded as a single [`Bytes`] chunk after the
-    /// QUIC stream has been fully read.  True chunk-by-chunk streaming from
-    /// QUIC would require lower-level HTTP/3 framing; the buffered approach
-    /// keeps the interface consistent across transports.
-    pub async fn get_stream(&self, key: &str) -> Result<impl Stream<Item = Result<Bytes>>> {
-        let (data, _meta) = self.get(key).await?;
-        let stream = futures::stream::once(async move { Ok(data) });
-        Ok(stream)
+    /// Chunks are yielded as HTTP/3 DATA frames arrive, so the object is
+    /// never buffered whole.  The stream holds
+    /// its connection (and [`max_in_flight`](crate::ClientConfig::max_in_flight)
+    /// slot) until it ends or is dropped.
+    pub async fn get_stream(
+        &self,
+        key: &str,
+    ) -> Result<(impl Stream<Item = Result<Bytes>>, Metadata)> {
+        let (body, metadata) = self.open_get(key).await?;
+        Ok((body, metadata))
     }
 
     /// Upload a stream of bytes via QUIC/HTTP3.
     ///
-    /// The stream is buffered before sending; see [`RestClient::put_stream`]
-    /// for the rationale.
+    /// Chunks are re-sized adaptively and each is sent as an HTTP/3 DATA
+    /// frame as soon as it is available, so the payload is never buffered
+    /// whole.
     pub async fn put_stream(
         &self,
         key: &str,
         stream: impl Stream<Item = Result<Bytes>> + Send + 'static,
         metadata: Option<Metadata>,
     ) -> Result<crate::types::PutResponse> {
-        self.put(key, collect_stream(stream).await?, metadata).await
+        let chunks = adaptive_chunks(stream, self.chunk_sizing());
+        self.put_body(key, chunks, metadata, &RequestOptions::default())
+            .await
+    }
+
+    /// Stream every object matching `list_req`; see
+    /// [`RestClient::list_stream`].
+    pub fn list_stream(
+        &self,
+        list_req: ListRequest,
+    ) -> impl Stream<Item = Result<ObjectInfo>> + Send + 'static {
+        let client = self.clone();
+        object_stream(list_req, self.response_decoder(), move |req| {
+            let client = client.clone();
+            async move { client.list_body(&req).await }
+        })
+    }
+}
+
+// ── MCP and Unix-socket ───────────────────────────────────────────────────────
+
+impl McpClient {
+    /// Get an object as a stream, for parity with the other transports.
+    /// The JSON-RPC response holds the whole object, so it is buffered and
+    /// yielded as one chunk.
+    pub async fn get_stream(
+        &self,
+        key: &str,
+    ) -> Result<(impl Stream<Item = Result<Bytes>>, Metadata)> {
+        let (data, metadata) = self.get(key).await?;
+        Ok((futures::stream::once(async move { Ok(data) }), metadata))
+    }
+}
+
+impl UnixClient {
+    /// Get an object as a stream; buffered like
+    /// [`McpClient::get_stream`].
+    pub async fn get_stream(
+        &self,
+        key: &str,
+    ) -> Result<(impl Stream<Item = Result<Bytes>>, Metadata)> {
+        let (data, metadata) = self.get(key).await?;
+        Ok((futures::stream::once(async move { Ok(data) }), metadata))
     }
 }
 
@@ -195,6 +401,36 @@ mod tests {
         assert!(resp.success);
     }
 
+    #[tokio::test]
+    async fn rest_put_stream_form_body_format() {
+        let mut server = Server::new_async().await;
+        let put_mock = server
+            .mock("PUT", "/objects/k")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data".into()),
+            )
+            .match_body(mockito::Matcher::Regex(
+                "name=\"file\"; filename=\"k\"\r\n\r\nhello world\r\n".into(),
+            ))
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let client = RestClient::new(server.url()).unwrap();
+        let stream = futures::stream::iter(vec![
+            Ok::<Bytes, Error>(Bytes::from_static(b"hello")),
+            Ok(Bytes::from_static(b" world")),
+        ]);
+        let options = RequestOptions::default().body_format(crate::types::BodyFormat::Form);
+        let resp = client
+            .put_stream_with_options("k", stream, None, &options)
+            .await
+            .unwrap();
+        put_mock.assert_async().await;
+        assert!(resp.success);
+    }
+
     #[tokio::test]
     async fn rest_put_stream_propagates_stream_error() {
         // An error yielded mid-stream must surface to the caller instead of
@@ -273,4 +509,59 @@ mod tests {
         let err = result.err().expect("expected error");
         assert!(matches!(err, Error::OperationFailed(_)));
     }
+
+    #[tokio::test]
+    async fn rest_list_stream_follows_pages() {
+        let mut server = Server::new_async().await;
+        let first = server
+            .mock("GET", "/objects")
+            .match_query(mockito::Matcher::Exact("prefix=p%2F".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"objects":[{"key":"p/a","size":1},{"key":"p/b","size":2}],
+                   "next_token":"t1","truncated":true}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/objects")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("prefix".into(), "p/".into()),
+                mockito::Matcher::UrlEncoded("token".into(), "t1".into()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"objects":[{"key":"p/c","size":3}],"truncated":false}"#)
+            .create_async()
+            .await;
+
+        let client = RestClient::new(server.url()).unwrap();
+        let keys: Vec<String> = client
+            .list_stream(ListRequest {
+                prefix: Some("p/".to_string()),
+                ..Default::default()
+            })
+            .map(|r| r.unwrap().key)
+            .collect()
+            .await;
+        second.assert_async().await;
+        first.assert_async().await;
+        assert_eq!(keys, vec!["p/a", "p/b", "p/c"]);
+    }
+
+    #[tokio::test]
+    async fn rest_list_stream_error() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/objects")
+            .with_status(403)
+            .create_async()
+            .await;
+
+        let client = RestClient::new(server.url()).unwrap();
+        let results: Vec<_> = client.list_stream(ListRequest::default()).collect().await;
+        mock.assert_async().await;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(Error::Forbidden(_))));
+    }
 }
@@ -0,0 +1,274 @@
+//! Tagging every object under a prefix.
+//!
+//! [`tag_prefix`] merges a set of custom metadata tags into the metadata of
+//! every object under a prefix — the labeling step of data lifecycle
+//! workflows ("mark everything under `exports/2023/` as `tier=cold`"):
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//! use std::collections::HashMap;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let tags = HashMap::from([("tier".to_string(), "cold".to_string())]);
+//! let report = client
+//!     .tag_prefix_with_progress("exports/2023/", tags, |progress| {
+//!         eprintln!("{} tagged, {} unchanged", progress.tagged, progress.unchanged);
+//!     })
+//!     .await?;
+//! assert!(report.is_complete());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Over REST, [`ObjectStoreClient::tag_prefix`] first asks the server to
+//! apply the tags in one `POST /tags` request, which servers that offer it
+//! apply atomically.  Servers without the endpoint, and every other
+//! transport, are tagged client-side: each object's metadata is read,
+//! merged, and written back, [`TAG_CONCURRENCY`] objects at a time.  That
+//! path is not atomic — an object updated between the read and the write
+//! loses the other update, and a failure part-way leaves the prefix
+//! partially tagged, with the failures listed in the [`TagReport`].
+//! Objects that already carry every tag are not rewritten, so rerunning
+//! after a failure only touches what is left.  The client-side path skips
+//! versions recorded by [`put_versioned`](crate::versions::put_versioned).
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::types::ListRequest;
+use crate::versions::is_version_key;
+use futures::StreamExt;
+use std::collections::HashMap;
+
+/// Objects tagged concurrently by the client-side path.
+pub const TAG_CONCURRENCY: usize = 16;
+
+/// What [`tag_prefix`] did, so far or in total.
+#[derive(Debug, Default)]
+pub struct TagReport {
+    /// Objects listed under the prefix.
+    pub scanned: u64,
+    /// Objects whose metadata was updated.
+    pub tagged: u64,
+    /// Objects that already carried every tag.
+    pub unchanged: u64,
+    /// Keys that could not be tagged, with the error.
+    pub failed: Vec<(String, Error)>,
+    /// Whether the server applied the tags in a single bulk request.
+    pub server_side: bool,
+}
+
+impl TagReport {
+    /// Whether every object under the prefix now carries the tags.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Merge `tags` into the custom metadata of every object under `prefix`,
+/// client-side.  Works with any store.
+pub async fn tag_prefix(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    tags: &HashMap<String, String>,
+) -> Result<TagReport> {
+    tag_prefix_with_progress(store, prefix, tags, |_| {}).await
+}
+
+/// Like [`tag_prefix`], calling `progress` with the running totals after
+/// each object.  Fails only if listing the prefix fails.
+pub async fn tag_prefix_with_progress<F>(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    tags: &HashMap<String, String>,
+    mut progress: F,
+) -> Result<TagReport>
+where
+    F: FnMut(&TagReport),
+{
+    let mut report = TagReport::default();
+    let request = ListRequest {
+        prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+        max_results: Some(LIST_PAGE_SIZE),
+        ..Default::default()
+    };
+    let mut pages = list_pages(store, request);
+    while let Some(page) = pages.next().await {
+        let keys: Vec<String> = page?
+            .objects
+            .into_iter()
+            .map(|o| o.key)
+            .filter(|key| !is_version_key(key))
+            .collect();
+        report.scanned += keys.len() as u64;
+        let mut results = futures::stream::iter(keys)
+            .map(|key| async move {
+                let result = tag_object(store, &key, tags).await;
+                (key, result)
+            })
+            .buffer_unordered(TAG_CONCURRENCY);
+        while let Some((key, result)) = results.next().await {
+            match result {
+                Ok(true) => report.tagged += 1,
+                Ok(false) => report.unchanged += 1,
+                Err(e) => report.failed.push((key, e)),
+            }
+            progress(&report);
+        }
+    }
+    report.failed.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(report)
+}
+
+/// Merge `tags` into `key`'s metadata; returns whether it changed.
+async fn tag_object(
+    store: &dyn ObjectStore,
+    key: &str,
+    tags: &HashMap<String, String>,
+) -> Result<bool> {
+    let mut metadata = store.get_metadata(key).await?;
+    let missing = tags
+        .iter()
+        .any(|(name, value)| metadata.custom.get(name) != Some(value));
+    if !missing {
+        return Ok(false);
+    }
+    metadata
+        .custom
+        .extend(tags.iter().map(|(k, v)| (k.clone(), v.clone())));
+    store.update_metadata(key, metadata).await?;
+    Ok(true)
+}
+
+impl ObjectStoreClient {
+    /// Merge `tags` into the custom metadata of every object under
+    /// `prefix`.  See the [`tags`](crate::tags) module.
+    pub async fn tag_prefix(
+        &self,
+        prefix: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<TagReport> {
+        self.tag_prefix_with_progress(prefix, tags, |_| {}).await
+    }
+
+    /// Like [`tag_prefix`](Self::tag_prefix), calling `progress` with the
+    /// running totals as objects are tagged (once, at the end, when the
+    /// server tags them in bulk).
+    pub async fn tag_prefix_with_progress<F>(
+        &self,
+        prefix: &str,
+        tags: HashMap<String, String>,
+        mut progress: F,
+    ) -> Result<TagReport>
+    where
+        F: FnMut(&TagReport),
+    {
+        if let ObjectStoreClient::Rest(client) = self {
+            if let Some(tagged) = client.tag_prefix(prefix, &tags).await? {
+                let report = TagReport {
+                    scanned: tagged,
+                    tagged,
+                    server_side: true,
+                    ..Default::default()
+                };
+                progress(&report);
+                return Ok(report);
+            }
+        }
+        tag_prefix_with_progress(self, prefix, &tags, progress).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn tier_cold() -> HashMap<String, String> {
+        HashMap::from([("tier".to_string(), "cold".to_string())])
+    }
+
+    #[tokio::test]
+    async fn rest_uses_bulk_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let bulk = server
+            .mock("POST", "/tags")
+            .match_body(Matcher::Json(
+                json!({"prefix": "p/", "tags": {"tier": "cold"}}),
+            ))
+            .with_body(r#"{"updated": 42}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let mut calls = 0;
+        let report = client
+            .tag_prefix_with_progress("p/", tier_cold(), |_| calls += 1)
+            .await
+            .unwrap();
+        bulk.assert_async().await;
+        assert!(report.server_side);
+        assert_eq!(report.tagged, 42);
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_tagging_each_object() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/tags")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "p/".into()))
+            .with_body(r#"{"objects":[{"key":"p/a","size":1},{"key":"p/b","size":1},{"key":"p/c","size":1}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/metadata/p%2Fa")
+            .with_body(
+                r#"{"key":"p/a","size":1,"content_type":"text/csv","metadata":{"owner":"ops"}}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/metadata/p%2Fb")
+            .with_body(r#"{"key":"p/b","size":1,"metadata":{"tier":"cold"}}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/metadata/p%2Fc")
+            .with_status(500)
+            .create_async()
+            .await;
+        // Only p/a needs writing; its other metadata is kept.
+        let update = server
+            .mock("PUT", "/metadata/p%2Fa")
+            .match_body(Matcher::PartialJson(json!({
+                "content_type": "text/csv",
+                "custom": {"owner": "ops", "tier": "cold"},
+            })))
+            .expect(1)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let mut seen = Vec::new();
+        let report = client
+            .tag_prefix_with_progress("p/", tier_cold(), |p| {
+                seen.push(p.tagged + p.unchanged + p.failed.len() as u64)
+            })
+            .await
+            .unwrap();
+        update.assert_async().await;
+        assert!(!report.server_side);
+        assert_eq!((report.scanned, report.tagged, report.unchanged), (3, 1, 1));
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "p/c");
+        assert_eq!(seen, [1, 2, 3]);
+    }
+}
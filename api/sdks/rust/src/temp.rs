@@ -0,0 +1,181 @@
+//! Temporary objects deleted when their guard is dropped.
+//!
+//! [`TempObject::create`] uploads data under a random key below a prefix
+//! and returns a guard.  Dropping the guard deletes the object in a
+//! background task; [`delete`](TempObject::delete) does the same and waits
+//! for the result, and [`persist`](TempObject::persist) keeps the object:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let scratch = client.temp_object("scratch/", Bytes::from("intermediate")).await?;
+//! println!("staged at {}", scratch.key());
+//! // ... hand scratch.key() to the next pipeline stage ...
+//! drop(scratch); // deleted in the background
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Cleanup on drop is best effort: it needs a Tokio runtime to run on and
+//! is lost if the process exits first, so pair temporary prefixes with a
+//! lifecycle policy when leftovers matter.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::Result;
+use crate::key_template::random_suffix;
+use bytes::Bytes;
+
+/// Length of the random name given to temporary objects.
+pub const TEMP_NAME_LEN: usize = 16;
+
+/// Guard over an object that is deleted when the guard is dropped.
+pub struct TempObject<S: ObjectStore + 'static> {
+    /// `None` once deleted or persisted.
+    store: Option<S>,
+    key: String,
+}
+
+impl<S: ObjectStore + 'static> TempObject<S> {
+    /// Upload `data` under `prefix` followed by a random name.
+    pub async fn create(store: S, prefix: &str, data: Bytes) -> Result<Self> {
+        let key = format!("{prefix}{}", random_suffix(TEMP_NAME_LEN)?);
+        store.put(&key, data, None).await?;
+        Ok(Self {
+            store: Some(store),
+            key,
+        })
+    }
+
+    /// The object's key.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Keep the object and return its key.
+    pub fn persist(mut self) -> String {
+        self.store = None;
+        std::mem::take(&mut self.key)
+    }
+
+    /// Delete the object now, reporting failures the drop would swallow.
+    pub async fn delete(mut self) -> Result<()> {
+        if let Some(store) = self.store.take() {
+            store.delete(&self.key).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: ObjectStore + 'static> Drop for TempObject<S> {
+    fn drop(&mut self) {
+        let Some(store) = self.store.take() else {
+            return;
+        };
+        let key = std::mem::take(&mut self.key);
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(async move {
+                    if let Err(error) = store.delete(&key).await {
+                        tracing::warn!(%key, %error, "failed to delete temporary object");
+                    }
+                });
+            }
+            Err(_) => {
+                tracing::warn!(%key, "no Tokio runtime to delete temporary object");
+            }
+        }
+    }
+}
+
+impl<S: ObjectStore + 'static> std::fmt::Debug for TempObject<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TempObject")
+            .field("key", &self.key)
+            .field("armed", &self.store.is_some())
+            .finish()
+    }
+}
+
+impl ObjectStoreClient {
+    /// Upload `data` as a temporary object under `prefix`.  See the
+    /// [`temp`](crate::temp) module.
+    pub async fn temp_object(&self, prefix: &str, data: Bytes) -> Result<TempObject<Self>> {
+        TempObject::create(self.clone(), prefix, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::{Matcher, Mock};
+    use std::time::Duration;
+
+    async fn deleted(mock: &Mock) -> bool {
+        for _ in 0..100 {
+            if mock.matched_async().await {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn drop_deletes_and_persist_keeps() {
+        let mut server = mockito::Server::new_async().await;
+        let path = Matcher::Regex(r"^/objects/scratch%2F[a-z0-9]{16}$".into());
+        server
+            .mock("PUT", path.clone())
+            .with_status(201)
+            .expect(2)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", path)
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let temp = client
+            .temp_object("scratch/", Bytes::from("x"))
+            .await
+            .unwrap();
+        assert!(temp.key().starts_with("scratch/"));
+        drop(temp);
+        assert!(deleted(&delete).await);
+
+        let kept = client
+            .temp_object("scratch/", Bytes::from("y"))
+            .await
+            .unwrap();
+        let key = kept.persist();
+        assert_eq!(key.len(), "scratch/".len() + TEMP_NAME_LEN);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn explicit_delete_reports_errors() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("PUT", Matcher::Any)
+            .with_status(201)
+            .create_async()
+            .await;
+        server
+            .mock("DELETE", Matcher::Any)
+            .with_status(500)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let temp = client.temp_object("t/", Bytes::new()).await.unwrap();
+        assert!(temp.delete().await.is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Tenant-scoped client handles.
+//!
+//! [`ObjectStoreClient::for_tenant`] returns a [`TenantClient`] that carries
+//! the tenant identifier on every request and, optionally, confines all keys
+//! to a per-tenant prefix:
+//!
+//! ```no_run
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//! use bytes::Bytes;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let acme = client.for_tenant("acme")?.with_key_prefix("tenants/acme/");
+//!
+//! // Sent as PUT /objects/tenants%2Facme%2Freport.csv with X-Tenant-ID: acme
+//! acme.put("report.csv", Bytes::from("a,b"), None).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The tenant travels as `X-Tenant-ID` (gRPC: `x-tenant-id` metadata).
+//! Unix-socket requests cannot carry one, so `for_tenant` fails with
+//! [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol) on
+//! a Unix client.
+
+use crate::client::{ByteStream, ObjectStore, ObjectStoreClient};
+use crate::error::Result;
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+
+/// An [`ObjectStoreClient`] bound to a single tenant.
+///
+/// Created by [`ObjectStoreClient::for_tenant`].  Cloning is cheap and
+/// clones share the parent client's connections.
+#[derive(Clone)]
+pub struct TenantClient {
+    client: ObjectStoreClient,
+    tenant_id: String,
+    key_prefix: Option<String>,
+}
+
+impl TenantClient {
+    pub(crate) fn new(client: ObjectStoreClient, tenant_id: String) -> Self {
+        Self {
+            client,
+            tenant_id,
+            key_prefix: None,
+        }
+    }
+
+    /// Prefix every key with `prefix`.
+    ///
+    /// Keys passed in are prefixed before they reach the server, and keys
+    /// returned by `list` have the prefix stripped, so callers work in the
+    /// tenant's own key space.
+    pub fn with_key_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        self.key_prefix = if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix)
+        };
+        self
+    }
+
+    /// The tenant identifier sent with every request.
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// The key prefix applied to every key, if any.
+    pub fn key_prefix(&self) -> Option<&str> {
+        self.key_prefix.as_deref()
+    }
+
+    /// The underlying tenant-tagged client, for operations not covered by
+    /// [`ObjectStore`] (policies, replication, archive).  Keys passed to it
+    /// are not prefixed.
+    pub fn client(&self) -> &ObjectStoreClient {
+        &self.client
+    }
+
+    fn scoped_key(&self, key: &str) -> String {
+        match &self.key_prefix {
+            Some(prefix) => format!("{prefix}{key}"),
+            None => key.to_string(),
+        }
+    }
+
+    fn unscoped_key(&self, key: String) -> String {
+        match &self.key_prefix {
+            Some(prefix) => match key.strip_prefix(prefix.as_str()) {
+                Some(rest) => rest.to_string(),
+                None => key,
+            },
+            None => key,
+        }
+    }
+}
+
+#[async_trait]
+impl ObjectStore for TenantClient {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        self.client.put(&self.scoped_key(key), data, metadata).await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.client.get(&self.scoped_key(key)).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.client.delete(&self.scoped_key(key)).await
+    }
+
+    async fn list(&self, mut list_req: ListRequest) -> Result<ListResponse> {
+        if self.key_prefix.is_some() {
+            let prefix = list_req.prefix.take().unwrap_or_default();
+            list_req.prefix = Some(self.scoped_key(&prefix));
+        }
+
+        let mut resp = self.client.list(list_req).await?;
+        for object in &mut resp.objects {
+            object.key = self.unscoped_key(std::mem::take(&mut object.key));
+        }
+        resp.common_prefixes = resp
+            .common_prefixes
+            .into_iter()
+            .map(|p| self.unscoped_key(p))
+            .collect();
+        Ok(resp)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.client.exists(&self.scoped_key(key)).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.client.get_metadata(&self.scoped_key(key)).await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.client
+            .update_metadata(&self.scoped_key(key), metadata)
+            .await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.client.health().await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        self.client.get_stream(&self.scoped_key(key)).await
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        self.client
+            .put_stream(&self.scoped_key(key), stream, metadata)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    fn tenant(url: String) -> TenantClient {
+        ObjectStoreClient::rest(url)
+            .unwrap()
+            .for_tenant("acme")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn tenant_header_sent_on_every_call() {
+        let mut server = Server::new_async().await;
+        let put_mock = server
+            .mock("PUT", "/objects/k")
+            .match_header("x-tenant-id", "acme")
+            .with_status(201)
+            .create_async()
+            .await;
+        let head_mock = server
+            .mock("HEAD", "/objects/k")
+            .match_header("x-tenant-id", "acme")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let client = tenant(server.url());
+        assert_eq!(client.tenant_id(), "acme");
+        client.put("k", Bytes::from("v"), None).await.unwrap();
+        assert!(client.exists("k").await.unwrap());
+        put_mock.assert_async().await;
+        head_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn parent_client_is_not_tagged() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("DELETE", "/objects/k")
+            .match_header("x-tenant-id", Matcher::Missing)
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let parent = ObjectStoreClient::rest(server.url()).unwrap();
+        let _scoped = parent.for_tenant("acme").unwrap();
+        parent.delete("k").await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn key_prefix_applied_to_keys() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/objects/t%2Facme%2Fk")
+            .match_header("x-tenant-id", "acme")
+            .with_status(200)
+            .with_body("v")
+            .create_async()
+            .await;
+
+        let client = tenant(server.url()).with_key_prefix("t/acme/");
+        assert_eq!(client.key_prefix(), Some("t/acme/"));
+        let (data, _) = client.get("k").await.unwrap();
+        assert_eq!(data, Bytes::from("v"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn list_prefixes_request_and_strips_results() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "t/acme/logs/".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"objects":[{"key":"t/acme/logs/a.txt","size":1}],
+                   "common_prefixes":["t/acme/logs/2024/"],"truncated":false}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = tenant(server.url()).with_key_prefix("t/acme/");
+        let resp = client
+            .list(ListRequest {
+                prefix: Some("logs/".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        mock.assert_async().await;
+        assert_eq!(resp.objects[0].key, "logs/a.txt");
+        assert_eq!(resp.common_prefixes, vec!["logs/2024/".to_string()]);
+    }
+
+    #[test]
+    fn empty_key_prefix_is_ignored() {
+        let client = tenant("http://localhost".to_string()).with_key_prefix("");
+        assert_eq!(client.key_prefix(), None);
+        assert_eq!(client.scoped_key("k"), "k");
+    }
+
+    #[test]
+    fn unix_clients_refuse_tenants() {
+        let client = ObjectStoreClient::unix("/tmp/objstore.sock").unwrap();
+        let err = client.for_tenant("acme").err().unwrap();
+        assert!(
+            matches!(
+                err,
+                crate::Error::UnsupportedByProtocol {
+                    operation: crate::client::Operation::ForTenant,
+                    protocol: crate::client::Protocol::Unix,
+                }
+            ),
+            "{err:?}"
+        );
+    }
+}
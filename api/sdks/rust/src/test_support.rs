@@ -0,0 +1,366 @@
+//! An in-memory [`ObjectStore`] for unit tests.
+//!
+//! [`MemoryStore`] keeps objects in key order and lists them the way the
+//! servers do: `prefix`, `delimiter`, and `max_results` are honoured, and a
+//! truncated page's `next_token` is its last key, which `continue_from`
+//! resumes after.  Tests can inject failures per operation, count calls,
+//! and slow calls down to observe concurrency.  Clones share everything.
+
+use crate::client::{ObjectStore, Operation};
+use crate::error::{Error, Result};
+use crate::types::{
+    DeleteResponse, HealthResponse, HealthStatus, ListRequest, ListResponse, Metadata, ObjectInfo,
+    PutResponse,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+type Failure = Box<dyn FnMut(&str) -> Option<Error> + Send>;
+type Delay = Arc<dyn Fn(Operation, &str, usize) -> Duration + Send + Sync>;
+
+/// An in-memory object store.  See the [module](self) docs.
+#[derive(Clone, Default)]
+pub(crate) struct MemoryStore(Arc<Mutex<State>>);
+
+#[derive(Default)]
+struct State {
+    objects: BTreeMap<String, (Bytes, Metadata)>,
+    page_size: Option<usize>,
+    restart_unknown_tokens: bool,
+    failures: Vec<(Operation, Failure)>,
+    delay: Option<Delay>,
+    calls: HashMap<Operation, usize>,
+    log: Vec<String>,
+    active: usize,
+    peak: usize,
+}
+
+impl MemoryStore {
+    /// List at most `size` entries per page, like a server capping page
+    /// sizes, whatever `max_results` asks for.
+    pub(crate) fn with_page_size(self, size: usize) -> Self {
+        self.state().page_size = Some(size);
+        self
+    }
+
+    /// Resume listings the way the local backend does: a `continue_from`
+    /// key that no longer exists restarts the listing from the beginning.
+    pub(crate) fn restarting_unknown_tokens(self) -> Self {
+        self.state().restart_unknown_tokens = true;
+        self
+    }
+
+    /// Hold each call's answer back for `delay(operation, key, body
+    /// length)`: the call takes effect at once and returns after the delay.
+    pub(crate) fn with_delay(
+        self,
+        delay: impl Fn(Operation, &str, usize) -> Duration + Send + Sync + 'static,
+    ) -> Self {
+        self.state().delay = Some(Arc::new(delay));
+        self
+    }
+
+    /// Fail calls of `operation` for which `failure` returns an error.
+    /// Rules are consulted in the order they were added.
+    pub(crate) fn fail(
+        &self,
+        operation: Operation,
+        failure: impl FnMut(&str) -> Option<Error> + Send + 'static,
+    ) {
+        self.state().failures.push((operation, Box::new(failure)));
+    }
+
+    /// Fail the next `times` calls of `operation` with `error()`.
+    pub(crate) fn fail_next(
+        &self,
+        operation: Operation,
+        times: usize,
+        error: impl Fn() -> Error + Send + 'static,
+    ) {
+        let mut left = times;
+        self.fail(operation, move |_| {
+            left = left.checked_sub(1)?;
+            Some(error())
+        });
+    }
+
+    /// Drop every injected failure.
+    pub(crate) fn heal(&self) {
+        self.state().failures.clear();
+    }
+
+    /// Store an object as a put would, without counting a call.
+    pub(crate) fn insert(&self, key: &str, data: impl Into<Bytes>) {
+        self.insert_at(key, data, Utc::now());
+    }
+
+    /// [`insert`](Self::insert) with a given modification time.
+    pub(crate) fn insert_at(&self, key: &str, data: impl Into<Bytes>, modified: DateTime<Utc>) {
+        let data = data.into();
+        let metadata = Metadata {
+            last_modified: Some(modified),
+            ..stamped(&data, Metadata::default())
+        };
+        self.state().objects.insert(key.into(), (data, metadata));
+    }
+
+    /// Change an object's bytes in place, leaving its metadata alone.
+    pub(crate) fn tamper(&self, key: &str, change: impl FnOnce(&mut Vec<u8>)) {
+        let mut state = self.state();
+        let (data, _) = state.objects.get_mut(key).expect("no such object");
+        let mut bytes = data.to_vec();
+        change(&mut bytes);
+        *data = bytes.into();
+    }
+
+    /// The stored object, exactly as held.
+    pub(crate) fn object(&self, key: &str) -> Option<(Bytes, Metadata)> {
+        self.state().objects.get(key).cloned()
+    }
+
+    /// The stored bytes of `key`; panics if it is missing.
+    pub(crate) fn data(&self, key: &str) -> Bytes {
+        self.object(key).expect("no such object").0
+    }
+
+    pub(crate) fn contains(&self, key: &str) -> bool {
+        self.state().objects.contains_key(key)
+    }
+
+    /// Remove an object without counting a call.
+    pub(crate) fn remove(&self, key: &str) -> bool {
+        self.state().objects.remove(key).is_some()
+    }
+
+    /// Replace every object with zero-filled ones of the given sizes.
+    pub(crate) fn reset(&self, objects: &[(&str, usize)]) {
+        self.state().objects.clear();
+        for (key, size) in objects {
+            self.insert(key, vec![0; *size]);
+        }
+    }
+
+    /// Every key, in order.
+    pub(crate) fn keys(&self) -> Vec<String> {
+        self.state().objects.keys().cloned().collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.state().objects.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many times `operation` was called, failed calls included.
+    pub(crate) fn calls(&self, operation: Operation) -> usize {
+        self.state().calls.get(&operation).copied().unwrap_or(0)
+    }
+
+    /// Calls that got past failure injection, in the order they completed:
+    /// `"put k 3"` (with the body length), `"delete k"`, and so on.
+    pub(crate) fn log(&self) -> Vec<String> {
+        self.state().log.clone()
+    }
+
+    /// The most calls that were in progress at once.
+    pub(crate) fn peak(&self) -> usize {
+        self.state().peak
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.0.lock().unwrap()
+    }
+
+    /// Run `operation` on `key`: count it, apply any injected failure,
+    /// `apply` it to the state, and wait out any delay.
+    async fn call<T>(
+        &self,
+        operation: Operation,
+        key: &str,
+        body: Option<usize>,
+        apply: impl FnOnce(&mut State) -> Result<T>,
+    ) -> Result<T> {
+        let (result, delay) = {
+            let mut state = self.state();
+            *state.calls.entry(operation).or_default() += 1;
+            let injected = state
+                .failures
+                .iter_mut()
+                .filter(|(op, _)| *op == operation)
+                .find_map(|(_, failure)| failure(key));
+            if let Some(error) = injected {
+                return Err(error);
+            }
+            state.active += 1;
+            state.peak = state.peak.max(state.active);
+            (apply(&mut state), state.delay.clone())
+        };
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay(operation, key, body.unwrap_or(0))).await;
+        }
+        let mut state = self.state();
+        state.active -= 1;
+        state.log.push(match body {
+            Some(len) => format!("{} {key} {len}", operation.as_str()),
+            None => format!("{} {key}", operation.as_str()),
+        });
+        result
+    }
+}
+
+/// `metadata` with the size, etag, and modification time a server records.
+fn stamped(data: &Bytes, metadata: Metadata) -> Metadata {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    Metadata {
+        size: data.len() as i64,
+        etag: Some(format!("{:016x}", hasher.finish())),
+        last_modified: Some(Utc::now()),
+        ..metadata
+    }
+}
+
+fn found<'a>(state: &'a State, key: &str) -> Result<&'a (Bytes, Metadata)> {
+    state
+        .objects
+        .get(key)
+        .ok_or_else(|| Error::NotFound(key.into()))
+}
+
+#[async_trait]
+impl ObjectStore for MemoryStore {
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        self.call(Operation::Put, key, Some(data.len()), |state| {
+            let metadata = stamped(&data, metadata.unwrap_or_default());
+            let etag = metadata.etag.clone();
+            state.objects.insert(key.into(), (data, metadata));
+            Ok(PutResponse {
+                success: true,
+                message: None,
+                etag,
+                consistency_token: None,
+                region: None,
+            })
+        })
+        .await
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        self.call(Operation::Get, key, None, |state| {
+            found(state, key).cloned()
+        })
+        .await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        self.call(Operation::Delete, key, None, |state| {
+            state
+                .objects
+                .remove(key)
+                .ok_or_else(|| Error::NotFound(key.into()))?;
+            Ok(DeleteResponse {
+                success: true,
+                message: None,
+            })
+        })
+        .await
+    }
+
+    async fn list(&self, req: ListRequest) -> Result<ListResponse> {
+        let prefix = req.prefix.unwrap_or_default();
+        self.call(Operation::List, &prefix, None, |state| {
+            let limit = match req.max_results {
+                Some(n) if n > 0 => n as usize,
+                _ => usize::MAX,
+            }
+            .min(state.page_size.unwrap_or(usize::MAX));
+            let after = req
+                .continue_from
+                .filter(|token| !state.restart_unknown_tokens || state.objects.contains_key(token));
+            let mut page = ListResponse::default();
+            let mut entries = 0;
+            for (key, (_, metadata)) in &state.objects {
+                if !key.starts_with(&prefix) || after.as_ref().is_some_and(|a| key <= a) {
+                    continue;
+                }
+                let rest = &key[prefix.len()..];
+                let common = req
+                    .delimiter
+                    .as_deref()
+                    .filter(|d| !d.is_empty())
+                    .and_then(|d| rest.find(d).map(|at| &key[..prefix.len() + at + d.len()]));
+                if common.is_some() && page.common_prefixes.last().map(String::as_str) == common {
+                    page.next_token = Some(key.clone());
+                    continue;
+                }
+                if entries == limit {
+                    page.truncated = true;
+                    break;
+                }
+                match common {
+                    Some(common) => page.common_prefixes.push(common.to_string()),
+                    None => page.objects.push(ObjectInfo {
+                        key: key.clone(),
+                        metadata: metadata.clone(),
+                    }),
+                }
+                entries += 1;
+                page.next_token = Some(key.clone());
+            }
+            if !page.truncated {
+                page.next_token = None;
+            }
+            Ok(page)
+        })
+        .await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.call(Operation::Exists, key, None, |state| {
+            Ok(state.objects.contains_key(key))
+        })
+        .await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        self.call(Operation::GetMetadata, key, None, |state| {
+            Ok(found(state, key)?.1.clone())
+        })
+        .await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.call(Operation::UpdateMetadata, key, None, |state| {
+            let (_, stored) = state
+                .objects
+                .get_mut(key)
+                .ok_or_else(|| Error::NotFound(key.into()))?;
+            *stored = Metadata {
+                size: stored.size,
+                etag: stored.etag.take(),
+                last_modified: stored.last_modified,
+                ..metadata
+            };
+            Ok(())
+        })
+        .await
+    }
+
+    async fn health(&self) -> Result<HealthResponse> {
+        self.call(Operation::Health, "", None, |_| {
+            Ok(HealthResponse {
+                status: HealthStatus::Serving,
+                message: None,
+            })
+        })
+        .await
+    }
+}
@@ -0,0 +1,323 @@
+//! Read-through / write-through composition of two stores.
+//!
+//! [`TieredObjectStore`] puts a fast store — typically a local go-objstore
+//! instance — in front of a slow, authoritative one.  Reads try the fast
+//! tier first and populate it on a miss; writes go to the slow tier and then
+//! through to the fast one:
+//!
+//! ```no_run
+//! use go_objstore::tiered::TieredObjectStore;
+//! use go_objstore::{ObjectStore, ObjectStoreClient};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let local = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let remote = ObjectStoreClient::rest("https://objstore.example.com")?;
+//! let store = TieredObjectStore::new(local, remote).with_ttl(Duration::from_secs(3600));
+//!
+//! let (data, _) = store.get("reports/latest.json").await?; // cached after this
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The slow tier is the source of truth: failed writes to it fail the call,
+//! while fast-tier errors are treated as cache misses and never surface.
+//! [`TierHooks`], set with
+//! [`with_hooks`](TieredObjectStore::with_hooks), can veto caching an
+//! object and observe evictions.
+
+use crate::client::{ByteStream, ObjectStore};
+use crate::error::{Error, Result};
+use crate::types::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::Utc;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Hooks consulted by [`TieredObjectStore`] when filling and evicting the
+/// fast tier.  Both methods have no-op defaults.
+pub trait TierHooks: Send + Sync {
+    /// Return false to keep `key` out of the fast tier, e.g. for objects too
+    /// large to cache.  Consulted on read misses and on writes.
+    fn should_cache(&self, _key: &str, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    /// Called after `key` was removed from the fast tier because it expired
+    /// or was evicted with [`TieredObjectStore::evict`].
+    fn on_evict(&self, _key: &str) {}
+}
+
+/// Default hooks: cache everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheAll;
+
+impl TierHooks for CacheAll {}
+
+/// A fast store caching a slow one.  See the [module docs](self).
+pub struct TieredObjectStore<F, S> {
+    fast: F,
+    slow: S,
+    ttl: Option<Duration>,
+    hooks: Arc<dyn TierHooks>,
+}
+
+impl<F: ObjectStore, S: ObjectStore> TieredObjectStore<F, S> {
+    /// Cache `slow` in `fast`, with no expiry.
+    pub fn new(fast: F, slow: S) -> Self {
+        Self {
+            fast,
+            slow,
+            ttl: None,
+            hooks: Arc::new(CacheAll),
+        }
+    }
+
+    /// Treat fast-tier copies older than `ttl` (by their last-modified
+    /// time) as misses, refreshing them from the slow tier.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Install fill and eviction hooks.
+    pub fn with_hooks(mut self, hooks: impl TierHooks + 'static) -> Self {
+        self.hooks = Arc::new(hooks);
+        self
+    }
+
+    /// The fast (cache) tier.
+    pub fn fast(&self) -> &F {
+        &self.fast
+    }
+
+    /// The slow (authoritative) tier.
+    pub fn slow(&self) -> &S {
+        &self.slow
+    }
+
+    /// Remove `key` from the fast tier only.
+    pub async fn evict(&self, key: &str) -> Result<()> {
+        match self.fast.delete(key).await {
+            Ok(_) | Err(Error::NotFound(_)) => {
+                self.hooks.on_evict(key);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn expired(&self, metadata: &Metadata) -> bool {
+        let (Some(ttl), Some(modified)) = (self.ttl, metadata.last_modified) else {
+            return false;
+        };
+        Utc::now()
+            .signed_duration_since(modified)
+            .to_std()
+            .is_ok_and(|age| age > ttl)
+    }
+
+    /// Copy an object into the fast tier, ignoring failures.
+    async fn populate(&self, key: &str, data: Bytes, metadata: &Metadata) {
+        if self.hooks.should_cache(key, metadata) {
+            let _ = self.fast.put(key, data, Some(metadata.clone())).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<F: ObjectStore, S: ObjectStore> ObjectStore for TieredObjectStore<F, S> {
+    /// Writes to the slow tier, then through to the fast tier.  A failed
+    /// fast-tier write evicts any stale cached copy.
+    async fn put(&self, key: &str, data: Bytes, metadata: Option<Metadata>) -> Result<PutResponse> {
+        let response = self.slow.put(key, data.clone(), metadata.clone()).await?;
+        let metadata = metadata.unwrap_or_default();
+        let cached = self.hooks.should_cache(key, &metadata)
+            && self.fast.put(key, data, Some(metadata)).await.is_ok();
+        if !cached {
+            let _ = self.evict(key).await;
+        }
+        Ok(response)
+    }
+
+    async fn get(&self, key: &str) -> Result<(Bytes, Metadata)> {
+        if let Ok((data, metadata)) = self.fast.get(key).await {
+            if !self.expired(&metadata) {
+                return Ok((data, metadata));
+            }
+            let _ = self.evict(key).await;
+        }
+        let (data, metadata) = self.slow.get(key).await?;
+        self.populate(key, data.clone(), &metadata).await;
+        Ok((data, metadata))
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteResponse> {
+        let response = self.slow.delete(key).await?;
+        let _ = self.evict(key).await;
+        Ok(response)
+    }
+
+    /// Lists the slow tier.
+    async fn list(&self, list_req: ListRequest) -> Result<ListResponse> {
+        self.slow.list(list_req).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        if let Ok(metadata) = self.fast.get_metadata(key).await {
+            if !self.expired(&metadata) {
+                return Ok(true);
+            }
+        }
+        self.slow.exists(key).await
+    }
+
+    async fn get_metadata(&self, key: &str) -> Result<Metadata> {
+        if let Ok(metadata) = self.fast.get_metadata(key).await {
+            if !self.expired(&metadata) {
+                return Ok(metadata);
+            }
+        }
+        self.slow.get_metadata(key).await
+    }
+
+    async fn update_metadata(&self, key: &str, metadata: Metadata) -> Result<()> {
+        self.slow.update_metadata(key, metadata.clone()).await?;
+        if self.fast.update_metadata(key, metadata).await.is_err() {
+            let _ = self.evict(key).await;
+        }
+        Ok(())
+    }
+
+    /// Health of the slow tier.
+    async fn health(&self) -> Result<HealthResponse> {
+        self.slow.health().await
+    }
+
+    /// Streams from the fast tier on a hit; misses stream from the slow tier
+    /// without populating the cache.
+    async fn get_stream(&self, key: &str) -> Result<(ByteStream, Metadata)> {
+        if let Ok(metadata) = self.fast.get_metadata(key).await {
+            if !self.expired(&metadata) {
+                if let Ok(stream) = self.fast.get_stream(key).await {
+                    return Ok(stream);
+                }
+            }
+        }
+        self.slow.get_stream(key).await
+    }
+
+    /// Streams to the slow tier only, then evicts any cached copy; the
+    /// next read fills the fast tier again.
+    async fn put_stream(
+        &self,
+        key: &str,
+        stream: ByteStream,
+        metadata: Option<Metadata>,
+    ) -> Result<PutResponse> {
+        let response = self.slow.put_stream(key, stream, metadata).await?;
+        let _ = self.evict(key).await;
+        Ok(response)
+    }
+}
+
+impl<F: fmt::Debug, S: fmt::Debug> fmt::Debug for TieredObjectStore<F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TieredObjectStore")
+            .field("fast", &self.fast)
+            .field("slow", &self.slow)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::test_support::MemoryStore;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recording(Arc<Mutex<Vec<String>>>);
+
+    impl TierHooks for Recording {
+        fn should_cache(&self, key: &str, _: &Metadata) -> bool {
+            !key.starts_with("big/")
+        }
+        fn on_evict(&self, key: &str) {
+            self.0.lock().unwrap().push(key.into());
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_populate_fast_tier() {
+        let store = TieredObjectStore::new(MemoryStore::default(), MemoryStore::default());
+        store.slow().insert("k", "v");
+
+        assert_eq!(store.get("k").await.unwrap().0, "v");
+        assert!(store.fast().contains("k"));
+        assert_eq!(store.get("k").await.unwrap().0, "v");
+        assert_eq!(store.slow().calls(Operation::Get), 1);
+
+        assert!(matches!(
+            store.get("missing").await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn writes_go_through_to_both_tiers() {
+        let evicted = Recording::default();
+        let log = evicted.0.clone();
+        let store = TieredObjectStore::new(MemoryStore::default(), MemoryStore::default())
+            .with_hooks(evicted);
+
+        store.put("k", Bytes::from("v"), None).await.unwrap();
+        assert!(store.fast().contains("k") && store.slow().contains("k"));
+
+        store.put("big/k", Bytes::from("v"), None).await.unwrap();
+        assert!(!store.fast().contains("big/k") && store.slow().contains("big/k"));
+
+        store.delete("k").await.unwrap();
+        assert!(!store.fast().contains("k") && !store.slow().contains("k"));
+        assert_eq!(*log.lock().unwrap(), ["big/k", "k"]);
+    }
+
+    #[tokio::test]
+    async fn streamed_writes_evict_the_fast_tier() {
+        let store = TieredObjectStore::new(MemoryStore::default(), MemoryStore::default());
+        store.fast().insert("k", "old");
+        store.slow().insert("k", "old");
+
+        let body = futures::stream::iter([Ok(Bytes::from("new"))]);
+        store
+            .put_stream("k", futures::StreamExt::boxed(body), None)
+            .await
+            .unwrap();
+        assert!(!store.fast().contains("k"));
+        assert_eq!(store.slow().data("k"), "new");
+        assert_eq!(store.get("k").await.unwrap().0, "new");
+        assert!(store.fast().contains("k"));
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_refreshed() {
+        let evicted = Recording::default();
+        let log = evicted.0.clone();
+        let store = TieredObjectStore::new(MemoryStore::default(), MemoryStore::default())
+            .with_ttl(Duration::from_secs(60))
+            .with_hooks(evicted);
+        store
+            .fast()
+            .insert_at("k", "old", Utc::now() - chrono::Duration::minutes(5));
+        store.slow().insert("k", "new");
+
+        assert_eq!(store.get("k").await.unwrap().0, "new");
+        assert_eq!(*log.lock().unwrap(), ["k"]);
+        // Refilled with a fresh copy.
+        assert_eq!(store.fast().get("k").await.unwrap().0, "new");
+    }
+}
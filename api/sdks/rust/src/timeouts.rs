@@ -0,0 +1,132 @@
+//! Per-stage request timeouts.
+//!
+//! [`Timeouts`] bounds each stage of a request separately, and a request
+//! that runs out of time fails with [`Error::Timeout`] naming the stage, so
+//! "the server is slow to start answering" ([`TimeoutStage::ResponseHeaders`])
+//! can be told apart from "the network is gone" ([`TimeoutStage::Connect`])
+//! or "the download stalled" ([`TimeoutStage::BodyTransfer`]):
+//!
+//! ```no_run
+//! use go_objstore::{ClientConfig, Error, ObjectStore, RestClient, TimeoutStage, Timeouts};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     timeouts: Timeouts {
+//!         connect: Some(Duration::from_secs(3)),
+//!         response_headers: Some(Duration::from_secs(10)),
+//!         body_transfer: Some(Duration::from_secs(30)),
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! };
+//! let client = RestClient::new_with_config("http://localhost:8080", config)?;
+//! match client.get("report.csv").await {
+//!     Err(Error::Timeout(TimeoutStage::ResponseHeaders)) => eprintln!("server is slow"),
+//!     Err(Error::Timeout(stage)) => eprintln!("network trouble during {stage}"),
+//!     result => {
+//!         result?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! How each stage maps onto a transport:
+//!
+//! | Stage | REST / MCP | gRPC | QUIC |
+//! |---|---|---|---|
+//! | `connect` | TCP connect and TLS | channel connect | whole connection setup |
+//! | `tls_handshake` | covered by `connect` | covered by `connect` | QUIC/TLS handshake |
+//! | `request_send` | covered by `response_headers` | covered by `response_headers` | opening the stream, sending the body |
+//! | `response_headers` | send until headers arrive | call until the response arrives | waiting for headers |
+//! | `body_transfer` | longest gap between body reads | longest gap between stream messages | each body read |
+//!
+//! Where a stage is "covered" the transport cannot observe it separately;
+//! its time counts toward the named stage and its limit is ignored.  REST
+//! and MCP enforce `body_transfer` as a read timeout on the connection, so
+//! it also bounds each gap while waiting for the response headers (a
+//! timeout there is still reported as `ResponseHeaders`).
+//!
+//! The [default retry classifier](crate::retry::DefaultRetryClassifier)
+//! retries timeouts in every stage.
+
+use crate::error::{Error, Result, TimeoutStage};
+use std::future::Future;
+use std::time::Duration;
+
+/// Time limits for the stages of a request.  `None` (the default for every
+/// stage) leaves the stage unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Opening a connection, including TLS where the transport cannot
+    /// separate the two.
+    pub connect: Option<Duration>,
+    /// The QUIC/TLS handshake on its own (QUIC only).
+    pub tls_handshake: Option<Duration>,
+    /// Sending the request headers and body (QUIC only).
+    pub request_send: Option<Duration>,
+    /// Waiting for the response headers once the request is sent.
+    pub response_headers: Option<Duration>,
+    /// Receiving the response body: for streaming transports, the longest
+    /// wait for the next chunk or message.
+    pub body_transfer: Option<Duration>,
+}
+
+impl Timeouts {
+    /// The limit configured for `stage`.
+    pub fn get(&self, stage: TimeoutStage) -> Option<Duration> {
+        match stage {
+            TimeoutStage::Connect => self.connect,
+            TimeoutStage::TlsHandshake => self.tls_handshake,
+            TimeoutStage::RequestSend => self.request_send,
+            TimeoutStage::ResponseHeaders => self.response_headers,
+            TimeoutStage::BodyTransfer => self.body_transfer,
+        }
+    }
+
+    /// Run `fut` within the limit for `stage`, failing with
+    /// [`Error::Timeout`] if it runs out.
+    pub(crate) async fn within<T, E>(
+        &self,
+        stage: TimeoutStage,
+        fut: impl Future<Output = std::result::Result<T, E>>,
+    ) -> Result<T>
+    where
+        Error: From<E>,
+    {
+        match self.get(stage) {
+            Some(limit) => match tokio::time::timeout(limit, fut).await {
+                Ok(result) => Ok(result?),
+                Err(_) => Err(Error::Timeout(stage)),
+            },
+            None => Ok(fut.await?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn within_reports_the_stage() {
+        let timeouts = Timeouts {
+            response_headers: Some(Duration::from_millis(10)),
+            ..Default::default()
+        };
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, Error>(())
+        };
+        assert!(matches!(
+            timeouts.within(TimeoutStage::ResponseHeaders, slow).await,
+            Err(Error::Timeout(TimeoutStage::ResponseHeaders))
+        ));
+        // Unbounded stages just run the future.
+        let value = timeouts
+            .within(TimeoutStage::BodyTransfer, async { Ok::<_, Error>(7) })
+            .await;
+        assert_eq!(value.unwrap(), 7);
+    }
+}
@@ -0,0 +1,164 @@
+//! Parsing of the timestamp forms servers and proxies send.
+//!
+//! go-objstore reports times as RFC 3339 in JSON bodies and as HTTP dates
+//! (RFC 2822 style) in `Last-Modified` headers, but proxies and older
+//! servers also emit offsets other than `Z`, a space instead of the `T`, or
+//! Unix epochs.  Every client parses through [`parse_timestamp`], so a
+//! value means the same instant whichever transport delivered it, always
+//! in UTC and with sub-second precision kept.  A value in none of these
+//! forms is left as `None` and logged at debug level.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Epoch values at or above this are taken as milliseconds; as seconds they
+/// would be past the year 5000.
+const EPOCH_MILLIS_THRESHOLD: u64 = 100_000_000_000;
+
+/// Parse an RFC 3339, RFC 2822 / HTTP-date, or Unix epoch (seconds or
+/// milliseconds, optionally fractional) timestamp, normalized to UTC.
+///
+/// Returns `None` for an empty or unrecognised value; the latter is logged
+/// at debug level so a misbehaving server shows up in traces.
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    let parsed = DateTime::parse_from_rfc3339(s)
+        .or_else(|_| DateTime::parse_from_rfc2822(s))
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| parse_loose(s))
+        .or_else(|| parse_epoch(s));
+    if parsed.is_none() {
+        tracing::debug!(value = s, "ignoring unparseable timestamp");
+    }
+    parsed
+}
+
+/// RFC 3339 with a space separator, and offset-less date-times (taken as
+/// UTC).
+fn parse_loose(s: &str) -> Option<DateTime<Utc>> {
+    let with_t = s.replacen(' ', "T", 1);
+    if let Ok(dt) = DateTime::parse_from_rfc3339(&with_t) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(&with_t, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+fn parse_epoch(s: &str) -> Option<DateTime<Utc>> {
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    let digits = whole.strip_prefix('-').unwrap_or(whole);
+    if digits.is_empty()
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+        || !frac.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let whole: i64 = whole.parse().ok()?;
+    // Fractional digits past nanosecond precision are dropped.
+    let frac_nanos = |scale: u32| -> i64 {
+        let mut padded: String = frac.chars().take(9).collect();
+        while padded.len() < 9 {
+            padded.push('0');
+        }
+        let nanos: i64 = padded.parse().unwrap_or(0) / 10i64.pow(scale);
+        if whole < 0 {
+            -nanos
+        } else {
+            nanos
+        }
+    };
+    let nanos = if whole.unsigned_abs() >= EPOCH_MILLIS_THRESHOLD {
+        whole.checked_mul(1_000_000)?.checked_add(frac_nanos(3))?
+    } else {
+        whole
+            .checked_mul(1_000_000_000)?
+            .checked_add(frac_nanos(0))?
+    };
+    Some(DateTime::from_timestamp_nanos(nanos))
+}
+
+/// Convert a protobuf timestamp, rejecting out-of-range values rather than
+/// turning them into the Unix epoch.
+pub(crate) fn from_proto(ts: &prost_types::Timestamp) -> Option<DateTime<Utc>> {
+    let parsed = u32::try_from(ts.nanos)
+        .ok()
+        .and_then(|nanos| DateTime::from_timestamp(ts.seconds, nanos));
+    if parsed.is_none() {
+        tracing::debug!(?ts, "ignoring out-of-range protobuf timestamp");
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{SecondsFormat, TimeZone};
+
+    fn expected() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 5, 14, 30, 15).unwrap() + chrono::Duration::milliseconds(250)
+    }
+
+    #[test]
+    fn accepts_every_form_as_the_same_instant() {
+        for s in [
+            "2024-03-05T14:30:15.250Z",
+            "2024-03-05T16:30:15.250+02:00",
+            "2024-03-05T09:30:15.25-05:00",
+            "2024-03-05 14:30:15.250Z",
+            "2024-03-05T14:30:15.250",
+            "1709649015.25",
+            "1709649015250",
+        ] {
+            assert_eq!(parse_timestamp(s), Some(expected()), "{s}");
+        }
+        let whole = expected() - chrono::Duration::milliseconds(250);
+        for s in [
+            "Tue, 05 Mar 2024 14:30:15 GMT",
+            "Tue, 5 Mar 2024 15:30:15 +0100",
+            "1709649015",
+        ] {
+            assert_eq!(parse_timestamp(s), Some(whole), "{s}");
+        }
+    }
+
+    #[test]
+    fn round_trips_rfc3339_and_http_dates() {
+        let t = expected();
+        let rfc3339 = t.to_rfc3339_opts(SecondsFormat::Millis, true);
+        assert_eq!(parse_timestamp(&rfc3339), Some(t));
+        let http_date = t.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        assert_eq!(
+            parse_timestamp(&http_date),
+            Some(t - chrono::Duration::milliseconds(250))
+        );
+        let proto = prost_types::Timestamp {
+            seconds: t.timestamp(),
+            nanos: t.timestamp_subsec_nanos() as i32,
+        };
+        assert_eq!(from_proto(&proto), Some(t));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for s in [
+            "",
+            "  ",
+            "yesterday",
+            "2024-13-01T00:00:00Z",
+            "NaN",
+            "1e9",
+            "99999999999999999999",
+        ] {
+            assert_eq!(parse_timestamp(s), None, "{s}");
+        }
+        let bad = prost_types::Timestamp {
+            seconds: 0,
+            nanos: -1,
+        };
+        assert_eq!(from_proto(&bad), None);
+    }
+}
@@ -0,0 +1,256 @@
+//! Per-request timing breakdown.
+//!
+//! With [`ClientConfig::collect_timings`](crate::ClientConfig::collect_timings)
+//! set, [`ResponseExt::with_timing`](crate::ResponseExt::with_timing) returns
+//! a [`Timing`] for the request alongside the result, splitting its latency
+//! into name resolution, connection setup, server time, and body transfer.
+//! The headers captured by
+//! [`ResponseExt::with_headers`](crate::ResponseExt::with_headers) carry the
+//! same timing:
+//!
+//! ```no_run
+//! use go_objstore::{ClientConfig, ObjectStore, ObjectStoreClient, ResponseExt};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ClientConfig {
+//!     collect_timings: true,
+//!     ..Default::default()
+//! };
+//! let client = ObjectStoreClient::rest_with_config("http://localhost:8080", config)?;
+//! let (_, timing) = client.get("report.csv").with_timing().await?;
+//! if let Some(timing) = timing {
+//!     println!("{timing:?} ({:?} total)", timing.total());
+//! }
+//!
+//! // The timing is a plain value, so calls spawned onto other tasks can
+//! // hand it back with their result.
+//! let task = tokio::spawn(async move { client.get("other.csv").with_timing().await });
+//! let (_, timing) = task.await??;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A call is timed in whichever task runs it, so wrap the call itself
+//! rather than a future that awaits a spawned task.  Work a call hands to a
+//! task of its own, such as the upload behind
+//! [`put_writer`](crate::ObjectStoreClient::put_writer), is still recorded.
+//!
+//! Which phases are measured depends on the transport:
+//!
+//! - **REST and MCP**: `dns` and `connect` when the request opened a new
+//!   connection (a pooled connection reports neither).  reqwest performs the
+//!   TLS handshake inside its connector, so for `https` URLs `connect`
+//!   includes it and `tls` is `None`.  Clients built on a caller-supplied
+//!   `reqwest::Client` only report `ttfb` and `transfer`.
+//! - **QUIC**: every call opens a connection; `tls` is the QUIC handshake.
+//!   The address is resolved when the client is created, so `dns` is `None`.
+//! - **gRPC**: the channel connects when the client is created, so only
+//!   `ttfb` and `transfer` are reported.
+//!
+//! The Unix-socket transport records no timings.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Where the time of one request went.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timing {
+    /// Host name resolution, if the request resolved a name.
+    pub dns: Option<Duration>,
+    /// Connection establishment, if the request opened a connection.
+    pub connect: Option<Duration>,
+    /// TLS handshake, where it is measured separately from `connect`.
+    pub tls: Option<Duration>,
+    /// From sending the request (after any connection setup) to the
+    /// response headers: server processing plus one round trip.
+    pub ttfb: Duration,
+    /// From the response headers until the call returned: reading and
+    /// decoding the body.
+    pub transfer: Duration,
+}
+
+impl Timing {
+    /// Sum of all phases.
+    pub fn total(&self) -> Duration {
+        self.setup() + self.ttfb + self.transfer
+    }
+
+    fn setup(&self) -> Duration {
+        [self.dns, self.connect, self.tls]
+            .into_iter()
+            .flatten()
+            .sum()
+    }
+}
+
+/// A connection-setup phase recorded while a request is in flight.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Phase {
+    Dns,
+    Connect,
+    Tls,
+}
+
+/// Timing of the request in flight, finished into a [`Timing`].
+#[derive(Debug, Clone)]
+pub(crate) struct TimingState {
+    start: Instant,
+    dns: Option<Duration>,
+    /// Measured around the whole connector, so it still includes `dns`.
+    connect: Option<Duration>,
+    tls: Option<Duration>,
+    first_byte: Option<Instant>,
+}
+
+impl TimingState {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            dns: None,
+            connect: None,
+            tls: None,
+            first_byte: None,
+        }
+    }
+
+    pub(crate) fn record(&mut self, phase: Phase, elapsed: Duration) {
+        let slot = match phase {
+            Phase::Dns => &mut self.dns,
+            Phase::Connect => &mut self.connect,
+            Phase::Tls => &mut self.tls,
+        };
+        *slot = Some(slot.unwrap_or_default() + elapsed);
+    }
+
+    /// Mark the response headers as received, once per request.
+    pub(crate) fn first_byte(&mut self) {
+        self.first_byte.get_or_insert_with(Instant::now);
+    }
+
+    pub(crate) fn finish(&self, end: Instant) -> Timing {
+        let mut timing = Timing {
+            dns: self.dns,
+            connect: self
+                .connect
+                .map(|c| c.saturating_sub(self.dns.unwrap_or_default())),
+            tls: self.tls,
+            ..Default::default()
+        };
+        let first_byte = self.first_byte.unwrap_or(end);
+        timing.ttfb = first_byte
+            .saturating_duration_since(self.start)
+            .saturating_sub(timing.setup());
+        timing.transfer = end.saturating_duration_since(first_byte);
+        timing
+    }
+}
+
+/// Time `fut`, recording its duration as `phase` of the current request.
+pub(crate) async fn timed<F: std::future::Future>(phase: Phase, fut: F) -> F::Output {
+    let start = Instant::now();
+    let output = fut.await;
+    crate::response::record_phase(phase, start.elapsed());
+    output
+}
+
+/// reqwest resolver wrapper recording [`Phase::Dns`].
+pub(crate) struct TimedResolve(pub(crate) Arc<dyn reqwest::dns::Resolve>);
+
+impl reqwest::dns::Resolve for TimedResolve {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(timed(Phase::Dns, self.0.resolve(name)))
+    }
+}
+
+/// reqwest connector layer recording [`Phase::Connect`].
+#[derive(Clone, Copy)]
+pub(crate) struct ConnectTimingLayer;
+
+impl<S> tower_layer::Layer<S> for ConnectTimingLayer {
+    type Service = ConnectTiming<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTiming(inner)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ConnectTiming<S>(S);
+
+impl<S, R> tower_service::Service<R> for ConnectTiming<S>
+where
+    S: tower_service::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = futures::future::BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        Box::pin(timed(Phase::Connect, self.0.call(request)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finish_splits_setup_from_server_time() {
+        let mut state = TimingState::new();
+        state.record(Phase::Dns, Duration::from_millis(5));
+        state.record(Phase::Connect, Duration::from_millis(20));
+        state.first_byte = Some(state.start + Duration::from_millis(50));
+
+        let timing = state.finish(state.start + Duration::from_millis(80));
+        assert_eq!(timing.dns, Some(Duration::from_millis(5)));
+        assert_eq!(timing.connect, Some(Duration::from_millis(15)));
+        assert_eq!(timing.tls, None);
+        assert_eq!(timing.ttfb, Duration::from_millis(30));
+        assert_eq!(timing.transfer, Duration::from_millis(30));
+        assert_eq!(timing.total(), Duration::from_millis(80));
+    }
+
+    #[tokio::test]
+    async fn rest_reports_dns_and_connect_when_enabled() {
+        use crate::{ClientConfig, Resolver, ResponseExt, RestClient};
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_status(200)
+            .with_body(r#"{"status":"healthy"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let port = server
+            .host_with_port()
+            .rsplit(':')
+            .next()
+            .unwrap()
+            .to_string();
+        let config = ClientConfig {
+            resolver: Resolver::new()
+                .with_override("objstore.invalid", "127.0.0.1".parse().unwrap()),
+            collect_timings: true,
+            ..Default::default()
+        };
+        let client =
+            RestClient::new_with_config(format!("http://objstore.invalid:{port}"), config).unwrap();
+
+        let (_, first) = client.health().with_headers().await.unwrap();
+        let first = first.timing().unwrap();
+        assert!(first.dns.is_some());
+        assert!(first.connect.is_some());
+        assert_eq!(first.tls, None);
+
+        let untimed = RestClient::new(server.url()).unwrap();
+        let (_, headers) = untimed.health().with_headers().await.unwrap();
+        assert!(headers.timing().is_none());
+    }
+}
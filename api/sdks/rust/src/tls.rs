@@ -0,0 +1,424 @@
+//! Client certificates for mutual TLS.
+//!
+//! A [`ClientIdentity`] set on [`ClientConfig::client_identity`] is presented
+//! during the QUIC handshake, for deployments that authenticate QUIC clients
+//! with mTLS:
+//!
+//! ```no_run
+//! use go_objstore::{ClientConfig, ClientIdentity, QuicClient, TlsVerification};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let identity = ClientIdentity::from_files("client.crt", "client.key")?;
+//! // Pick up rotated certificates (e.g. from cert-manager) without a restart.
+//! identity.watch(Duration::from_secs(30))?;
+//! let config = ClientConfig {
+//!     client_identity: Some(identity),
+//!     ..Default::default()
+//! };
+//! let client =
+//!     QuicClient::new_with_config("objstore.internal", 4433, TlsVerification::Enabled, config)
+//!         .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Certificates and keys may be PEM or DER, from files or in-memory bytes.
+//! Clones of an identity share it, so a [`reload`](ClientIdentity::reload)
+//! or a [`watch`](ClientIdentity::watch)ed file change applies to every
+//! client holding one, from its next handshake; connections already open
+//! keep the certificate they were established with.
+//!
+//! [`TlsInfo`], from `ObjectStoreClient::tls_info`, describes how a
+//! client's connections are secured.
+//!
+//! [`ClientConfig::client_identity`]: crate::ClientConfig::client_identity
+
+use crate::error::{Error, Result};
+use rustls::client::ResolvesClientCert;
+use rustls::sign::CertifiedKey;
+use rustls::SignatureScheme;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, SystemTime};
+
+/// A client certificate chain and its private key.
+///
+/// Cloning is cheap and clones share the loaded certificate, including
+/// later reloads.
+#[derive(Clone)]
+pub struct ClientIdentity {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    current: RwLock<Arc<Loaded>>,
+    files: Option<(PathBuf, PathBuf)>,
+}
+
+struct Loaded {
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+impl ClientIdentity {
+    /// Build an identity from DER-encoded certificates (leaf first) and a
+    /// DER-encoded private key.
+    pub fn new(cert_chain: Vec<CertificateDer<'static>>, key: PrivateKeyDer<'static>) -> Self {
+        Self::from_loaded(Loaded { cert_chain, key }, None)
+    }
+
+    fn from_loaded(loaded: Loaded, files: Option<(PathBuf, PathBuf)>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                current: RwLock::new(Arc::new(loaded)),
+                files,
+            }),
+        }
+    }
+
+    /// Parse a PEM certificate chain (leaf first) and a PEM private key
+    /// (PKCS#8, PKCS#1, or SEC1).
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self> {
+        Ok(Self::from_loaded(
+            Loaded::parse(cert_pem, key_pem, Some(Encoding::Pem))?,
+            None,
+        ))
+    }
+
+    /// Parse a single DER certificate and a DER private key (PKCS#8,
+    /// PKCS#1, or SEC1).
+    pub fn from_der(cert_der: &[u8], key_der: &[u8]) -> Result<Self> {
+        Ok(Self::from_loaded(
+            Loaded::parse(cert_der, key_der, Some(Encoding::Der))?,
+            None,
+        ))
+    }
+
+    /// Parse a certificate and private key, each in PEM or DER (detected
+    /// from the content).
+    pub fn from_bytes(cert: &[u8], key: &[u8]) -> Result<Self> {
+        Ok(Self::from_loaded(Loaded::parse(cert, key, None)?, None))
+    }
+
+    /// Read and parse a PEM certificate chain and private key from files.
+    pub fn from_pem_files(cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> Result<Self> {
+        Self::load_files(cert_path.as_ref(), key_path.as_ref(), Some(Encoding::Pem))
+    }
+
+    /// Read and parse a certificate and private key from files, each in PEM
+    /// or DER.  The identity remembers the paths for
+    /// [`reload`](Self::reload) and [`watch`](Self::watch).
+    pub fn from_files(cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> Result<Self> {
+        Self::load_files(cert_path.as_ref(), key_path.as_ref(), None)
+    }
+
+    fn load_files(cert_path: &Path, key_path: &Path, encoding: Option<Encoding>) -> Result<Self> {
+        let loaded = Loaded::read(cert_path, key_path, encoding)?;
+        let files = (cert_path.to_path_buf(), key_path.to_path_buf());
+        Ok(Self::from_loaded(loaded, Some(files)))
+    }
+
+    /// The certificate chain, leaf first.
+    pub fn cert_chain(&self) -> Vec<CertificateDer<'static>> {
+        self.current().cert_chain.clone()
+    }
+
+    /// The certificate and key paths, for identities loaded from files.
+    pub fn files(&self) -> Option<(&Path, &Path)> {
+        self.shared
+            .files
+            .as_ref()
+            .map(|(cert, key)| (cert.as_path(), key.as_path()))
+    }
+
+    /// Re-read the certificate and key files.  On failure the current
+    /// certificate stays in use.  Fails with [`Error::Configuration`] for
+    /// identities not loaded from files.
+    pub fn reload(&self) -> Result<()> {
+        let (cert_path, key_path) = self.files().ok_or_else(|| {
+            Error::Configuration("client identity was not loaded from files".into())
+        })?;
+        let loaded = Loaded::read(cert_path, key_path, None)?;
+        *self
+            .shared
+            .current
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Arc::new(loaded);
+        Ok(())
+    }
+
+    /// Check the certificate and key files every `interval` and
+    /// [`reload`](Self::reload) when either changes (by modification time
+    /// or size).  A failed reload, e.g. while a rotation is half-written,
+    /// is logged and retried at the next change; the previous certificate
+    /// stays in use meanwhile.
+    ///
+    /// Must be called within a Tokio runtime.  The task ends once every
+    /// clone of the identity is dropped, or when the returned handle is
+    /// aborted.
+    pub fn watch(&self, interval: Duration) -> Result<tokio::task::JoinHandle<()>> {
+        let (cert_path, key_path) = self.files().ok_or_else(|| {
+            Error::Configuration("client identity was not loaded from files".into())
+        })?;
+        let stamp = |cert: &Path, key: &Path| (file_stamp(cert), file_stamp(key));
+        let mut seen = stamp(cert_path, key_path);
+        let shared: Weak<Shared> = Arc::downgrade(&self.shared);
+        Ok(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(shared) = shared.upgrade() else {
+                    return;
+                };
+                let identity = ClientIdentity { shared };
+                let Some((cert_path, key_path)) = identity.files() else {
+                    return;
+                };
+                let current = stamp(cert_path, key_path);
+                if current == seen {
+                    continue;
+                }
+                match identity.reload() {
+                    Ok(()) => {
+                        tracing::info!("reloaded client certificate from {}", cert_path.display());
+                        seen = current;
+                    }
+                    Err(e) => tracing::warn!(
+                        "failed to reload client certificate from {}: {}",
+                        cert_path.display(),
+                        e
+                    ),
+                }
+            }
+        }))
+    }
+
+    fn current(&self) -> Arc<Loaded> {
+        self.shared
+            .current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// A rustls resolver presenting the current certificate at each
+    /// handshake.
+    pub(crate) fn resolver(&self) -> Arc<dyn ResolvesClientCert> {
+        Arc::new(self.clone())
+    }
+}
+
+impl ResolvesClientCert for ClientIdentity {
+    fn resolve(
+        &self,
+        _root_hint_subjects: &[&[u8]],
+        _sigschemes: &[SignatureScheme],
+    ) -> Option<Arc<CertifiedKey>> {
+        let loaded = self.current();
+        match rustls::crypto::ring::sign::any_supported_type(&loaded.key) {
+            Ok(signer) => Some(Arc::new(CertifiedKey::new(
+                loaded.cert_chain.clone(),
+                signer,
+            ))),
+            Err(e) => {
+                tracing::warn!("client certificate key is unusable: {}", e);
+                None
+            }
+        }
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+/// How certificate and key bytes are encoded.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Pem,
+    Der,
+}
+
+impl Encoding {
+    fn detect(data: &[u8]) -> Self {
+        let pem = data.windows(11).any(|w| w == b"-----BEGIN ");
+        if pem {
+            Encoding::Pem
+        } else {
+            Encoding::Der
+        }
+    }
+}
+
+impl Loaded {
+    fn read(cert_path: &Path, key_path: &Path, encoding: Option<Encoding>) -> Result<Self> {
+        let cert = std::fs::read(cert_path)?;
+        let key = std::fs::read(key_path)?;
+        Self::parse(&cert, &key, encoding)
+    }
+
+    fn parse(cert: &[u8], key: &[u8], encoding: Option<Encoding>) -> Result<Self> {
+        let cert_chain = match encoding.unwrap_or_else(|| Encoding::detect(cert)) {
+            Encoding::Pem => rustls_pemfile::certs(&mut &cert[..])
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| Error::Tls(format!("invalid client certificate PEM: {e}")))?,
+            Encoding::Der if cert.is_empty() => Vec::new(),
+            Encoding::Der => vec![CertificateDer::from(cert.to_vec())],
+        };
+        if cert_chain.is_empty() {
+            return Err(Error::Tls("no client certificate found".into()));
+        }
+
+        let key = match encoding.unwrap_or_else(|| Encoding::detect(key)) {
+            Encoding::Pem => rustls_pemfile::private_key(&mut &key[..])
+                .map_err(|e| Error::Tls(format!("invalid client key PEM: {e}")))?
+                .ok_or_else(|| Error::Tls("no private key found in client key PEM".into()))?,
+            Encoding::Der => PrivateKeyDer::try_from(key.to_vec())
+                .map_err(|e| Error::Tls(format!("invalid client key DER: {e}")))?,
+        };
+        rustls::crypto::ring::sign::any_supported_type(&key)
+            .map_err(|e| Error::Tls(format!("unsupported client key: {e}")))?;
+
+        Ok(Self { cert_chain, key })
+    }
+}
+
+/// A file's modification time and size, for change detection.
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print key material.
+        f.debug_struct("ClientIdentity")
+            .field("certificates", &self.current().cert_chain.len())
+            .field("files", &self.shared.files)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How a client's connections are secured.
+///
+/// The handshake fields come from the most recent connection and are only
+/// known for QUIC, which performs its own handshake; the HTTP clients leave
+/// them empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsInfo {
+    /// Name the server certificate is checked against (SNI).
+    pub server_name: Option<String>,
+    /// Whether the server certificate is verified.
+    pub verify_server: bool,
+    /// Whether a client certificate is presented (mutual TLS).
+    pub client_certificate: bool,
+    /// Application protocol negotiated by ALPN, e.g. `"h3"`.
+    pub alpn: Option<String>,
+    /// Leaf certificate the server presented.
+    pub peer_certificate: Option<CertificateDer<'static>>,
+}
+
+impl TlsInfo {
+    /// Info for an `https://` URL, from its settings alone.
+    pub(crate) fn for_url(url: &str) -> Option<Self> {
+        let url = url::Url::parse(url).ok()?;
+        (url.scheme() == "https").then(|| Self {
+            server_name: url.host_str().map(str::to_string),
+            verify_server: true,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_pem_parses_chain_and_key() {
+        let cert = rcgen::generate_simple_self_signed(vec!["client".to_string()]).unwrap();
+        let identity = ClientIdentity::from_pem(
+            cert.cert.pem().as_bytes(),
+            cert.key_pair.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(identity.cert_chain().len(), 1);
+        assert_eq!(identity.cert_chain()[0].as_ref(), cert.cert.der().as_ref());
+
+        let debug = format!("{identity:?}");
+        assert!(!debug.contains("PRIVATE"));
+    }
+
+    #[test]
+    fn from_pem_rejects_missing_parts() {
+        let cert = rcgen::generate_simple_self_signed(vec!["client".to_string()]).unwrap();
+        let key = cert.key_pair.serialize_pem();
+
+        let err = ClientIdentity::from_pem(b"", key.as_bytes()).unwrap_err();
+        assert!(matches!(err, Error::Tls(_)));
+        let err = ClientIdentity::from_pem(cert.cert.pem().as_bytes(), b"").unwrap_err();
+        assert!(matches!(err, Error::Tls(_)));
+    }
+
+    #[test]
+    fn der_and_detected_encodings() {
+        let cert = rcgen::generate_simple_self_signed(vec!["client".to_string()]).unwrap();
+        let der = cert.cert.der().to_vec();
+        let key_der = cert.key_pair.serialize_der();
+
+        let identity = ClientIdentity::from_der(&der, &key_der).unwrap();
+        assert_eq!(identity.cert_chain()[0].as_ref(), der.as_slice());
+        // Mixed encodings are detected per input.
+        let identity =
+            ClientIdentity::from_bytes(&der, cert.key_pair.serialize_pem().as_bytes()).unwrap();
+        assert_eq!(identity.cert_chain()[0].as_ref(), der.as_slice());
+        let identity = ClientIdentity::from_bytes(cert.cert.pem().as_bytes(), &key_der).unwrap();
+        assert_eq!(identity.cert_chain()[0].as_ref(), der.as_slice());
+
+        let err = ClientIdentity::from_der(&der, b"not a key").unwrap_err();
+        assert!(matches!(err, Error::Tls(_)));
+        assert!(identity.reload().is_err(), "not loaded from files");
+    }
+
+    #[tokio::test]
+    async fn watched_files_reload_on_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert_path, key_path) = (dir.path().join("tls.crt"), dir.path().join("tls.key"));
+        let write = |name: &str| {
+            let cert = rcgen::generate_simple_self_signed(vec![name.to_string()]).unwrap();
+            std::fs::write(&cert_path, cert.cert.der()).unwrap();
+            std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+            cert.cert.der().to_vec()
+        };
+
+        let first = write("first");
+        let identity = ClientIdentity::from_files(&cert_path, &key_path).unwrap();
+        let shared = identity.clone();
+        let watcher = identity.watch(Duration::from_millis(10)).unwrap();
+
+        // A half-written rotation keeps the current certificate.
+        std::fs::write(&key_path, b"").unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(shared.cert_chain()[0].as_ref(), first.as_slice());
+
+        let second = write("second");
+        for _ in 0..200 {
+            if shared.cert_chain()[0].as_ref() == second.as_slice() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(shared.cert_chain()[0].as_ref(), second.as_slice());
+
+        drop((identity, shared));
+        tokio::time::timeout(Duration::from_secs(1), watcher)
+            .await
+            .expect("watcher stops once the identity is dropped")
+            .unwrap();
+    }
+}
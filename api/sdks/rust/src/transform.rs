@@ -0,0 +1,364 @@
+//! Server-side image transformations.
+//!
+//! Servers that resize or re-encode images on the fly take the
+//! transformation as query parameters on the object URL.
+//! [`ObjectStoreClient::get_transformed`] builds that query from typed
+//! [`Transform`]s and checks them against what the server advertises, so an
+//! unsupported request fails before it is sent instead of silently
+//! returning the original image:
+//!
+//! ```no_run
+//! use go_objstore::transform::{ImageFormat, Transform};
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let (thumbnail, metadata) = client
+//!     .get_transformed(
+//!         "photos/beach.jpg",
+//!         &[
+//!             Transform::Resize { width: 320, height: 240 },
+//!             Transform::Format(ImageFormat::Webp),
+//!         ],
+//!     )
+//!     .await?;
+//! println!("{} bytes of {:?}", thumbnail.len(), metadata.content_type);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Transforms are REST-only.  Supported transforms are discovered with
+//! `GET /transforms`, once per client, from a body like
+//! `{"transforms": ["resize", "format"], "formats": ["jpeg", "webp"],
+//! "max_width": 4096, "max_height": 4096}`.  The object is then fetched with
+//! `GET /objects/{key}?w=320&h=240&format=webp`.  A server without the
+//! discovery endpoint supports no transforms, and requests for them fail
+//! with [`Error::InvalidArgument`](crate::Error::InvalidArgument), as do
+//! transforms it does not list, sizes over its limits, and repeated
+//! transforms of one kind.  Other transports fail with
+//! [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol).
+
+use crate::client::{ObjectStoreClient, Operation};
+use crate::error::{Error, Result};
+use crate::types::Metadata;
+use bytes::Bytes;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// An image encoding a [`Transform::Format`] converts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+    Avif,
+    Gif,
+}
+
+impl ImageFormat {
+    /// The query parameter value, e.g. `"webp"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpeg",
+            ImageFormat::Png => "png",
+            ImageFormat::Webp => "webp",
+            ImageFormat::Avif => "avif",
+            ImageFormat::Gif => "gif",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = Error;
+
+    /// Parses case-insensitively; `jpg` is accepted for JPEG.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "jpeg" | "jpg" => Ok(ImageFormat::Jpeg),
+            "png" => Ok(ImageFormat::Png),
+            "webp" => Ok(ImageFormat::Webp),
+            "avif" => Ok(ImageFormat::Avif),
+            "gif" => Ok(ImageFormat::Gif),
+            _ => Err(Error::InvalidArgument(format!("unknown image format: {s}"))),
+        }
+    }
+}
+
+/// One transformation applied to an image object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// Fit within `width` x `height` pixels (`w=` and `h=`).
+    Resize { width: u32, height: u32 },
+    /// Re-encode in another format (`format=`).
+    Format(ImageFormat),
+}
+
+impl Transform {
+    /// The capability name a server lists for this kind of transform.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transform::Resize { .. } => "resize",
+            Transform::Format(_) => "format",
+        }
+    }
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        match *self {
+            Transform::Resize { width, height } => {
+                vec![("w", width.to_string()), ("h", height.to_string())]
+            }
+            Transform::Format(format) => vec![("format", format.to_string())],
+        }
+    }
+}
+
+/// The transforms a server advertises.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct TransformCapabilities {
+    /// Transform names, as in [`Transform::name`].
+    #[serde(default)]
+    pub transforms: Vec<String>,
+    /// Formats [`Transform::Format`] can convert to; names this client
+    /// does not know are dropped.
+    #[serde(default, deserialize_with = "known_formats")]
+    pub formats: Vec<ImageFormat>,
+    /// Largest resize width, if limited.
+    #[serde(default)]
+    pub max_width: Option<u32>,
+    /// Largest resize height, if limited.
+    #[serde(default)]
+    pub max_height: Option<u32>,
+}
+
+impl TransformCapabilities {
+    /// Check that the server can apply every transform in `transforms`.
+    pub fn check(&self, transforms: &[Transform]) -> Result<()> {
+        if transforms.is_empty() {
+            return Err(Error::InvalidArgument("no transforms given".to_string()));
+        }
+        for (i, transform) in transforms.iter().enumerate() {
+            let name = transform.name();
+            if transforms[..i].iter().any(|t| t.name() == name) {
+                return Err(Error::InvalidArgument(format!(
+                    "{name} given more than once"
+                )));
+            }
+            if !self.transforms.iter().any(|t| t == name) {
+                return Err(Error::InvalidArgument(format!(
+                    "server does not support the {name} transform"
+                )));
+            }
+            match *transform {
+                Transform::Resize { width, height } => {
+                    if width == 0 || height == 0 {
+                        return Err(Error::InvalidArgument(format!(
+                            "cannot resize to {width}x{height}"
+                        )));
+                    }
+                    if self.max_width.is_some_and(|max| width > max)
+                        || self.max_height.is_some_and(|max| height > max)
+                    {
+                        return Err(Error::InvalidArgument(format!(
+                            "{width}x{height} exceeds the server's {}x{} limit",
+                            self.max_width.map_or("any".to_string(), |w| w.to_string()),
+                            self.max_height.map_or("any".to_string(), |h| h.to_string()),
+                        )));
+                    }
+                }
+                Transform::Format(format) => {
+                    if !self.formats.contains(&format) {
+                        return Err(Error::InvalidArgument(format!(
+                            "server cannot convert to {format}"
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn known_formats<'de, D>(deserializer: D) -> std::result::Result<Vec<ImageFormat>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let names = Vec::<String>::deserialize(deserializer)?;
+    Ok(names.iter().filter_map(|n| n.parse().ok()).collect())
+}
+
+/// The query string for `transforms`, without the leading `?`.
+pub(crate) fn query_string(transforms: &[Transform]) -> String {
+    transforms
+        .iter()
+        .flat_map(Transform::query)
+        .map(|(name, value)| format!("{name}={}", urlencoding::encode(&value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+impl ObjectStoreClient {
+    /// The transforms the server advertises, or `None` if it offers none.
+    /// Discovered once per client; always `None` except over REST.
+    pub async fn transform_capabilities(&self) -> Result<Option<TransformCapabilities>> {
+        match self {
+            ObjectStoreClient::Rest(client) => client.transform_capabilities().await,
+            _ => Ok(None),
+        }
+    }
+
+    /// Get `key` with `transforms` applied by the server.  See the
+    /// [`transform`](crate::transform) module.
+    pub async fn get_transformed(
+        &self,
+        key: &str,
+        transforms: &[Transform],
+    ) -> Result<(Bytes, Metadata)> {
+        let ObjectStoreClient::Rest(client) = self else {
+            return Err(self.unsupported(Operation::GetTransformed));
+        };
+        client
+            .transform_capabilities()
+            .await?
+            .ok_or_else(|| Error::InvalidArgument("server offers no image transforms".to_string()))?
+            .check(transforms)?;
+        client.get_transformed(key, transforms).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    const CAPABILITIES: &str = r#"{"transforms":["resize","format"],
+        "formats":["jpeg","webp","heic"],"max_width":1024,"max_height":1024}"#;
+
+    #[test]
+    fn builds_query_strings() {
+        let transforms = [
+            Transform::Resize {
+                width: 320,
+                height: 240,
+            },
+            Transform::Format(ImageFormat::Webp),
+        ];
+        assert_eq!(query_string(&transforms), "w=320&h=240&format=webp");
+        assert_eq!("JPG".parse::<ImageFormat>().unwrap(), ImageFormat::Jpeg);
+    }
+
+    #[test]
+    fn checks_against_capabilities() {
+        let caps: TransformCapabilities = serde_json::from_str(CAPABILITIES).unwrap();
+        assert_eq!(caps.formats, [ImageFormat::Jpeg, ImageFormat::Webp]);
+        let resize = |width, height| Transform::Resize { width, height };
+
+        assert!(caps.check(&[resize(1024, 10)]).is_ok());
+        for bad in [
+            vec![],
+            vec![resize(2048, 10)],
+            vec![resize(0, 10)],
+            vec![Transform::Format(ImageFormat::Png)],
+            vec![resize(10, 10), resize(20, 20)],
+        ] {
+            assert!(
+                matches!(caps.check(&bad), Err(Error::InvalidArgument(_))),
+                "{bad:?}"
+            );
+        }
+        let resize_only = TransformCapabilities {
+            transforms: vec!["resize".to_string()],
+            ..Default::default()
+        };
+        assert!(resize_only
+            .check(&[Transform::Format(ImageFormat::Jpeg)])
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn rest_sends_transform_query() {
+        let mut server = mockito::Server::new_async().await;
+        let discovery = server
+            .mock("GET", "/transforms")
+            .with_body(CAPABILITIES)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects/photos%2Fa.jpg")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("w".into(), "320".into()),
+                Matcher::UrlEncoded("h".into(), "240".into()),
+                Matcher::UrlEncoded("format".into(), "webp".into()),
+            ]))
+            .with_header("content-type", "image/webp")
+            .with_body("thumb")
+            .expect(2)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+        let transforms = [
+            Transform::Resize {
+                width: 320,
+                height: 240,
+            },
+            Transform::Format(ImageFormat::Webp),
+        ];
+
+        for _ in 0..2 {
+            let (data, metadata) = client
+                .get_transformed("photos/a.jpg", &transforms)
+                .await
+                .unwrap();
+            assert_eq!(data, "thumb");
+            assert_eq!(metadata.content_type.as_deref(), Some("image/webp"));
+        }
+        discovery.assert_async().await;
+
+        let err = client
+            .get_transformed("photos/a.jpg", &[Transform::Format(ImageFormat::Gif)])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn fails_without_server_support() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/transforms")
+            .with_status(404)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+        assert_eq!(client.transform_capabilities().await.unwrap(), None);
+        let err = client
+            .get_transformed("a.jpg", &[Transform::Format(ImageFormat::Png)])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)), "{err:?}");
+
+        let mcp = ObjectStoreClient::mcp("http://localhost:1").unwrap();
+        let err = mcp
+            .get_transformed("a.jpg", &[Transform::Format(ImageFormat::Png)])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::UnsupportedByProtocol {
+                    operation: Operation::GetTransformed,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Self-expiring objects.
+//!
+//! [`ObjectStoreClient::put_with_ttl`] stores an object with its expiry
+//! recorded in the [`EXPIRES_AT_METADATA`] custom metadata field, which
+//! readers can check with [`expires_at`] / [`is_expired`].
+//! [`ObjectStoreClient::put_with_ttl_policy`] additionally makes sure a
+//! lifecycle `delete` policy with the same retention covers the key's
+//! prefix, so the server removes the object once it is older than the TTL
+//! and lifecycle policies are applied:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::ObjectStoreClient;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! client
+//!     .put_with_ttl_policy("tmp/upload-42.part", Bytes::from("..."), Duration::from_secs(3600))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Lifecycle retention is measured from an object's last-modified time and
+//! applies to every object under the prefix, so keep TTL objects under a
+//! dedicated prefix such as `tmp/`.  A prefix takes one TTL: a `delete`
+//! policy on it or a parent prefix with a retention at least the TTL
+//! already covers the key (the object may then outlive its recorded
+//! expiry), and one with a shorter retention is refused, since it would
+//! delete the object before its TTL is up.
+
+use crate::client::{ObjectStore, ObjectStoreClient};
+use crate::error::{Error, Result};
+use crate::types::{LifecyclePolicy, Metadata, PutResponse};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Custom metadata key holding an object's expiry as RFC 3339.
+pub const EXPIRES_AT_METADATA: &str = "expires-at";
+
+/// The expiry recorded by `put_with_ttl`, if any.
+pub fn expires_at(metadata: &Metadata) -> Option<DateTime<Utc>> {
+    metadata
+        .custom
+        .get(EXPIRES_AT_METADATA)
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Return true when `metadata` records an expiry at or before `now`.
+pub fn is_expired(metadata: &Metadata, now: DateTime<Utc>) -> bool {
+    expires_at(metadata).is_some_and(|t| t <= now)
+}
+
+/// Whole seconds of `ttl`, rejecting TTLs under one second.
+fn ttl_seconds(ttl: Duration) -> Result<i64> {
+    match i64::try_from(ttl.as_secs()) {
+        Ok(secs) if secs > 0 => Ok(secs),
+        _ => Err(Error::InvalidArgument(format!(
+            "TTL must be at least one second, got {ttl:?}"
+        ))),
+    }
+}
+
+impl ObjectStoreClient {
+    /// Put an object whose expiry (now + `ttl`) is recorded in its custom
+    /// metadata.  Nothing deletes it; see
+    /// [`put_with_ttl_policy`](Self::put_with_ttl_policy) for that.
+    pub async fn put_with_ttl(&self, key: &str, data: Bytes, ttl: Duration) -> Result<PutResponse> {
+        let secs = ttl_seconds(ttl)?;
+        let expires = Utc::now() + chrono::Duration::seconds(secs);
+        let metadata = Metadata {
+            custom: HashMap::from([(EXPIRES_AT_METADATA.to_string(), expires.to_rfc3339())]),
+            ..Default::default()
+        };
+        self.put(key, data, Some(metadata)).await
+    }
+
+    /// Like [`put_with_ttl`](Self::put_with_ttl), and first ensure a
+    /// lifecycle `delete` policy covers the key's prefix (everything up to
+    /// the last `/`), adding one with retention `ttl` if none does.
+    ///
+    /// Fails with [`Error::InvalidArgument`] for keys without a `/`: a
+    /// policy on the empty prefix would expire the whole store.  Fails
+    /// with [`Error::PreconditionFailed`], without writing the object, if a
+    /// `delete` policy on the prefix or a parent has a shorter retention.
+    pub async fn put_with_ttl_policy(
+        &self,
+        key: &str,
+        data: Bytes,
+        ttl: Duration,
+    ) -> Result<PutResponse> {
+        let secs = ttl_seconds(ttl)?;
+        let prefix = key.rfind('/').map(|i| &key[..=i]).ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "TTL policies need a key prefix; {key:?} has no '/'"
+            ))
+        })?;
+
+        // Parent prefixes count too, so list every policy.
+        let policies = self.get_policies(None).await?;
+        let deleting = policies
+            .iter()
+            .filter(|p| p.action == "delete" && prefix.starts_with(p.prefix.as_str()));
+        if let Some(shorter) = deleting.clone().find(|p| p.retention_seconds < secs) {
+            return Err(Error::PreconditionFailed(format!(
+                "lifecycle policy {} deletes objects under {:?} after {}s, before the {secs}s TTL",
+                shorter.id, shorter.prefix, shorter.retention_seconds
+            )));
+        }
+        if deleting.count() == 0 {
+            let added = self
+                .add_policy(LifecyclePolicy {
+                    id: format!("ttl:{prefix}:{secs}s"),
+                    prefix: prefix.to_string(),
+                    retention_seconds: secs,
+                    action: "delete".to_string(),
+                    destination_type: None,
+                    destination_settings: HashMap::new(),
+                })
+                .await;
+            match added {
+                // A concurrent first call added the same policy.
+                Ok(()) | Err(Error::AlreadyExists(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.put_with_ttl(key, data, ttl).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    fn client(server: &mockito::ServerGuard) -> ObjectStoreClient {
+        ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn put_with_ttl_records_expiry() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/objects/tmp%2Fa")
+            .match_header("x-object-metadata", Matcher::Regex("expires-at".into()))
+            .with_status(201)
+            .create_async()
+            .await;
+
+        client(&server)
+            .put_with_ttl("tmp/a", Bytes::from("x"), Duration::from_secs(60))
+            .await
+            .unwrap();
+        put.assert_async().await;
+
+        assert!(matches!(
+            client(&server)
+                .put_with_ttl("tmp/a", Bytes::new(), Duration::from_millis(10))
+                .await,
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_with_ttl_policy_adds_missing_policy() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/policies")
+            .with_status(200)
+            .with_body(r#"{"policies":[]}"#)
+            .create_async()
+            .await;
+        let add = server
+            .mock("POST", "/policies")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "id": "ttl:tmp/:3600s",
+                "prefix": "tmp/",
+                "retention_seconds": 3600,
+                "action": "delete",
+            })))
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("PUT", "/objects/tmp%2Fb")
+            .with_status(201)
+            .create_async()
+            .await;
+
+        client(&server)
+            .put_with_ttl_policy("tmp/b", Bytes::from("x"), Duration::from_secs(3600))
+            .await
+            .unwrap();
+        add.assert_async().await;
+
+        assert!(matches!(
+            client(&server)
+                .put_with_ttl_policy("b", Bytes::new(), Duration::from_secs(3600))
+                .await,
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_with_ttl_policy_respects_existing_policies() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/policies")
+            .with_status(200)
+            .with_body(
+                r#"{"policies":[{"id":"tmp","prefix":"tmp/","retention_seconds":3600,
+                    "action":"delete"}]}"#,
+            )
+            .create_async()
+            .await;
+        let add = server
+            .mock("POST", "/policies")
+            .expect(0)
+            .create_async()
+            .await;
+        let put = server
+            .mock("PUT", "/objects/tmp%2Fjobs%2Fa")
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+
+        // The parent prefix's policy waits at least as long.
+        client(&server)
+            .put_with_ttl_policy("tmp/jobs/a", Bytes::from("x"), Duration::from_secs(60))
+            .await
+            .unwrap();
+        // It would delete the object before a longer TTL is up.
+        assert!(matches!(
+            client(&server)
+                .put_with_ttl_policy("tmp/jobs/a", Bytes::from("x"), Duration::from_secs(7200))
+                .await,
+            Err(Error::PreconditionFailed(_))
+        ));
+        add.assert_async().await;
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn put_with_ttl_policy_tolerates_a_concurrent_add() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/policies")
+            .with_status(200)
+            .with_body(r#"{"policies":[]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/policies")
+            .with_status(409)
+            .create_async()
+            .await;
+        let put = server
+            .mock("PUT", "/objects/tmp%2Fc")
+            .with_status(201)
+            .create_async()
+            .await;
+
+        client(&server)
+            .put_with_ttl_policy("tmp/c", Bytes::from("x"), Duration::from_secs(3600))
+            .await
+            .unwrap();
+        put.assert_async().await;
+    }
+
+    #[test]
+    fn expiry_helpers() {
+        let now = Utc::now();
+        let mut metadata = Metadata::default();
+        assert!(!is_expired(&metadata, now));
+        metadata.custom.insert(
+            EXPIRES_AT_METADATA.into(),
+            (now - chrono::Duration::seconds(1)).to_rfc3339(),
+        );
+        assert!(is_expired(&metadata, now));
+        assert!(expires_at(&metadata).is_some());
+    }
+}
@@ -1,9 +1,60 @@
+//! Request and response types shared by every transport.
+//!
+//! # Request options
+//!
+//! [`RequestOptions`] carries per-call settings for `put_with_options`,
+//! `get_with_options`, and `list_with_options`.  REST and QUIC send them
+//! as headers and gRPC as request metadata; MCP and Unix-socket clients
+//! fail with [`Error::UnsupportedByProtocol`](crate::Error::UnsupportedByProtocol)
+//! for any option they cannot send rather than ignore it.
+//!
+//! - [`durability`](RequestOptions::durability) asks the server to
+//!   acknowledge a write only once it is durable at that level.  It is only
+//!   sent to servers that advertise support, and must be confirmed; see the
+//!   [`features`](crate::features) module.
+//! - [`min_consistency_token`](RequestOptions::min_consistency_token) makes
+//!   a read wait for a replica that has applied the write that returned
+//!   [`PutResponse::consistency_token`].  go-objstore servers do not issue
+//!   tokens yet, so the field is `None` against them.
+//! - [`region`](RequestOptions::region) asks a multi-region gateway to
+//!   serve the call from a region.  Gateways that echo the region that took
+//!   a write fill in [`PutResponse::region`]; a single go-objstore server
+//!   echoes nothing, so treat `None` as unconfirmed placement.
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::{ObjectStore, ObjectStoreClient, RequestOptions};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let options = RequestOptions::default().region("eu-west-1");
+//! let put = client
+//!     .put_with_options("patients/7.json", Bytes::from("{}"), None, &options)
+//!     .await?;
+//! if let Some(token) = put.consistency_token {
+//!     let options = options.min_consistency_token(token);
+//!     let (data, _) = client.get_with_options("patients/7.json", &options).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Web-serving headers
+//!
+//! [`Metadata`] has typed `cache_control`, `content_disposition`, and
+//! `content_language` fields for objects served to browsers.  REST and
+//! QUIC send them as the matching HTTP headers; every transport also stores
+//! them in the custom map under `cache-control`, `content-disposition`, and
+//! `content-language`, since go-objstore servers keep no other headers.
+//! Reads fill the fields from response headers or, failing that, from those
+//! custom keys, which are then removed from `custom`.
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Metadata for an object in storage
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Metadata {
     pub content_type: Option<String>,
     pub content_encoding: Option<String>,
@@ -11,18 +62,82 @@ pub struct Metadata {
     pub last_modified: Option<DateTime<Utc>>,
     pub etag: Option<String>,
     pub custom: HashMap<String, String>,
+    /// Size of the body as transferred when the client decompressed it
+    /// (see `ClientConfig::decompress`); `size` is then the decoded size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed_size: Option<i64>,
+    /// `Cache-Control` to serve the object with, e.g. `max-age=3600`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+    /// `Content-Disposition`, e.g. `attachment; filename="report.pdf"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_disposition: Option<String>,
+    /// `Content-Language`, e.g. `en-US`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_language: Option<String>,
 }
 
-impl Default for Metadata {
-    fn default() -> Self {
-        Self {
-            content_type: None,
-            content_encoding: None,
-            size: 0,
-            last_modified: None,
-            etag: None,
-            custom: HashMap::new(),
+/// Custom metadata key holding [`Metadata::cache_control`].
+pub const CACHE_CONTROL_KEY: &str = "cache-control";
+/// Custom metadata key holding [`Metadata::content_disposition`].
+pub const CONTENT_DISPOSITION_KEY: &str = "content-disposition";
+/// Custom metadata key holding [`Metadata::content_language`].
+pub const CONTENT_LANGUAGE_KEY: &str = "content-language";
+
+impl Metadata {
+    /// The web-serving fields that are set, with their custom metadata
+    /// keys (which are also their HTTP header names).
+    ///
+    /// go-objstore servers have no dedicated storage for these headers, so
+    /// every client also stores them in the custom map under these keys;
+    /// REST and QUIC additionally send them as HTTP headers for gateways
+    /// and CDNs in front of the server.
+    pub fn web_headers(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            (CACHE_CONTROL_KEY, &self.cache_control),
+            (CONTENT_DISPOSITION_KEY, &self.content_disposition),
+            (CONTENT_LANGUAGE_KEY, &self.content_language),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value.as_deref()?)))
+    }
+
+    /// `custom` plus the set web-serving fields under their keys: the map
+    /// to send to the server.
+    pub(crate) fn wire_custom(&self) -> std::borrow::Cow<'_, HashMap<String, String>> {
+        if self.web_headers().next().is_none() {
+            return std::borrow::Cow::Borrowed(&self.custom);
         }
+        let mut custom = self.custom.clone();
+        for (key, value) in self.web_headers() {
+            custom.insert(key.to_string(), value.to_string());
+        }
+        std::borrow::Cow::Owned(custom)
+    }
+
+    /// Move the web-serving keys out of `custom` (matched ignoring case)
+    /// into their fields, keeping values already set from response headers.
+    pub(crate) fn lift_web_headers(mut self) -> Self {
+        let custom = &mut self.custom;
+        let fields = [
+            (CACHE_CONTROL_KEY, &mut self.cache_control),
+            (CONTENT_DISPOSITION_KEY, &mut self.content_disposition),
+            (CONTENT_LANGUAGE_KEY, &mut self.content_language),
+        ];
+        for (key, field) in fields {
+            let stored: Vec<String> = custom
+                .keys()
+                .filter(|k| k.eq_ignore_ascii_case(key))
+                .cloned()
+                .collect();
+            for name in stored {
+                let value = custom.remove(&name);
+                if field.is_none() {
+                    *field = value;
+                }
+            }
+        }
+        self
     }
 }
 
@@ -39,6 +154,156 @@ pub struct PutResponse {
     pub success: bool,
     pub message: Option<String>,
     pub etag: Option<String>,
+    /// Token identifying this write, from servers that issue them; pass it
+    /// as [`RequestOptions::min_consistency_token`] to read the write back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consistency_token: Option<String>,
+    /// Region the write landed in, from gateways that echo it; compare it
+    /// with [`RequestOptions::region`] to confirm placement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+/// How an upload body is encoded on the wire (REST)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BodyFormat {
+    /// Raw request body; metadata travels in headers
+    #[default]
+    Raw,
+    /// `multipart/form-data` with a `metadata` JSON field and a `file` part,
+    /// for gateways that only accept form uploads
+    Form,
+}
+
+/// How durable a write must be before the server acknowledges it.
+///
+/// Anything but `Local` needs a server that advertises and acknowledges
+/// it; see [`features`](crate::features).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Durability {
+    /// The backend accepted the write (the server default)
+    #[default]
+    Local,
+    /// The write reached at least this many replicas
+    Replicated(u32),
+    /// The write was flushed to stable storage
+    Fsync,
+}
+
+impl std::fmt::Display for Durability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Durability::Local => f.write_str("local"),
+            Durability::Replicated(n) => write!(f, "replicated={n}"),
+            Durability::Fsync => f.write_str("fsync"),
+        }
+    }
+}
+
+/// Header (REST, QUIC) or metadata key (gRPC) carrying a non-default
+/// [`Durability`].
+pub(crate) const DURABILITY_HEADER: &str = "x-durability";
+
+/// Header or metadata key on which servers return a write's consistency
+/// token.
+pub(crate) const CONSISTENCY_TOKEN_HEADER: &str = "x-consistency-token";
+
+/// Header or metadata key carrying [`RequestOptions::min_consistency_token`].
+pub(crate) const MIN_CONSISTENCY_TOKEN_HEADER: &str = "x-min-consistency-token";
+
+/// Header or metadata key carrying [`RequestOptions::region`], and on which
+/// gateways echo the region that served a write.
+pub(crate) const REGION_HEADER: &str = "x-region";
+
+/// Header or metadata key carrying [`Precondition::Match`].
+pub(crate) const IF_MATCH_HEADER: &str = "if-match";
+
+/// Header or metadata key carrying [`Precondition::NotExists`] as `*`.
+pub(crate) const IF_NONE_MATCH_HEADER: &str = "if-none-match";
+
+/// A condition the stored object must meet for a write to proceed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Precondition {
+    /// The key must not exist yet (`If-None-Match: *`)
+    NotExists,
+    /// The stored object's ETag must be this one (`If-Match`)
+    Match(String),
+}
+
+/// Per-call request options
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub body_format: BodyFormat,
+    pub durability: Durability,
+    /// Only answer reads from a replica that has applied the write with
+    /// this [`PutResponse::consistency_token`].
+    pub min_consistency_token: Option<String>,
+    /// Region a multi-region gateway must serve the call from, for data
+    /// residency rules.
+    pub region: Option<String>,
+    /// Only write if the stored object meets this condition; the server
+    /// rejects the write with
+    /// [`Error::PreconditionFailed`](crate::Error::PreconditionFailed)
+    /// otherwise.  Only sent to servers that advertise conditional writes
+    /// (see [`features`](crate::features)).
+    pub precondition: Option<Precondition>,
+}
+
+impl RequestOptions {
+    /// Set how the upload body is encoded.
+    pub fn body_format(mut self, format: BodyFormat) -> Self {
+        self.body_format = format;
+        self
+    }
+
+    /// Set the acknowledgement level for writes.
+    pub fn durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Require reads to observe the write that returned `token`.
+    pub fn min_consistency_token(mut self, token: impl Into<String>) -> Self {
+        self.min_consistency_token = Some(token.into());
+        self
+    }
+
+    /// Pin the call to a region, e.g. `"eu-west-1"`.
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// Make the write conditional on the stored object.
+    pub fn precondition(mut self, precondition: Precondition) -> Self {
+        self.precondition = Some(precondition);
+        self
+    }
+
+    /// Whether any option needs a transport that sends these headers.
+    pub(crate) fn needs_headers(&self) -> bool {
+        !self.headers().is_empty()
+    }
+
+    /// Request headers for the options that differ from server defaults.
+    pub(crate) fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::new();
+        if self.durability != Durability::Local {
+            headers.push((DURABILITY_HEADER, self.durability.to_string()));
+        }
+        if let Some(token) = &self.min_consistency_token {
+            headers.push((MIN_CONSISTENCY_TOKEN_HEADER, token.clone()));
+        }
+        if let Some(region) = &self.region {
+            headers.push((REGION_HEADER, region.clone()));
+        }
+        match &self.precondition {
+            Some(Precondition::NotExists) => headers.push((IF_NONE_MATCH_HEADER, "*".to_string())),
+            Some(Precondition::Match(etag)) => headers.push((IF_MATCH_HEADER, etag.clone())),
+            None => {}
+        }
+        headers
+    }
 }
 
 /// Response from a Delete operation
@@ -48,6 +313,16 @@ pub struct DeleteResponse {
     pub message: Option<String>,
 }
 
+/// Whether listings include zero-byte directory marker objects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DirMarkers {
+    /// Return markers like any other object
+    #[default]
+    Show,
+    /// Drop markers (see `dir_markers::is_dir_marker`) from the results
+    Hide,
+}
+
 /// Request for listing objects
 #[derive(Debug, Clone, Default)]
 pub struct ListRequest {
@@ -55,10 +330,13 @@ pub struct ListRequest {
     pub delimiter: Option<String>,
     pub max_results: Option<i32>,
     pub continue_from: Option<String>,
+    /// Applied client-side by `ObjectStoreClient`; the servers always
+    /// return markers.
+    pub dir_markers: DirMarkers,
 }
 
 /// Response from a List operation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ListResponse {
     pub objects: Vec<ObjectInfo>,
     pub common_prefixes: Vec<String>,
@@ -132,6 +410,35 @@ pub struct ReplicationPolicy {
     pub replication_mode: ReplicationMode,
 }
 
+/// Outcome of applying the lifecycle policies.
+///
+/// `policies_evaluated` and `objects_processed` are reported over every
+/// protocol. The per-action breakdown (`objects_deleted`,
+/// `objects_archived`) and `errors` are read from the REST and QUIC JSON
+/// responses when a server includes them; the gRPC `ApplyPoliciesResponse`
+/// has no fields for them, so over gRPC they are always `None` and empty.
+/// When a server does report both counts, `objects_processed` is their sum.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApplyPoliciesResult {
+    /// Policies evaluated (the server's `policies_count`).
+    #[serde(alias = "policies_count")]
+    pub policies_evaluated: i32,
+    /// Objects a policy acted on.
+    pub objects_processed: i32,
+    pub objects_deleted: Option<i32>,
+    pub objects_archived: Option<i32>,
+    /// Per-object failures, when reported.
+    #[serde(deserialize_with = "null_as_empty")]
+    pub errors: Vec<String>,
+}
+
+fn null_as_empty<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<String>, D::Error> {
+    Ok(Option::<Vec<String>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 /// Sync result for replication
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncResult {
@@ -144,6 +451,62 @@ pub struct SyncResult {
     pub errors: Vec<String>,
 }
 
+impl SyncResult {
+    /// The per-object failures in `errors`, which servers report as
+    /// `"<key>: <error>"`.
+    pub fn object_errors(&self) -> Vec<ReplicationError> {
+        self.errors
+            .iter()
+            .map(|e| ReplicationError::parse(e, None))
+            .collect()
+    }
+}
+
+/// A failure to replicate one object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicationError {
+    pub key: String,
+    pub error: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl ReplicationError {
+    /// Split a `"<key>: <error>"` message; one without a key is kept whole
+    /// with an empty `key`.
+    pub(crate) fn parse(message: &str, timestamp: Option<DateTime<Utc>>) -> Self {
+        let (key, error) = message.split_once(": ").unwrap_or(("", message));
+        Self {
+            key: key.to_string(),
+            error: error.to_string(),
+            timestamp,
+        }
+    }
+}
+
+/// Wire form of a [`ReplicationError`] sample in a JSON status response.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReplicationErrorWire {
+    #[serde(default)]
+    key: String,
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    timestamp: Option<String>,
+}
+
+impl From<ReplicationErrorWire> for ReplicationError {
+    fn from(w: ReplicationErrorWire) -> Self {
+        Self {
+            key: w.key,
+            error: w.error,
+            timestamp: w
+                .timestamp
+                .as_deref()
+                .and_then(crate::timestamp::parse_timestamp),
+        }
+    }
+}
+
 /// Replication status and metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReplicationStatus {
@@ -158,6 +521,10 @@ pub struct ReplicationStatus {
     pub last_sync_time: Option<DateTime<Utc>>,
     pub average_sync_duration_ms: i64,
     pub sync_count: i64,
+    /// Recent per-object failures, from servers that report them; empty
+    /// otherwise.
+    #[serde(default)]
+    pub recent_errors: Vec<ReplicationError>,
 }
 
 #[cfg(test)]
@@ -173,8 +540,10 @@ mod tests {
 
     #[test]
     fn test_metadata_serialization() {
-        let mut metadata = Metadata::default();
-        metadata.content_type = Some("application/json".to_string());
+        let mut metadata = Metadata {
+            content_type: Some("application/json".to_string()),
+            ..Default::default()
+        };
         metadata
             .custom
             .insert("key".to_string(), "value".to_string());
@@ -1,5 +1,6 @@
 use crate::error::{Error, Result};
 use crate::jsonrpc::{string_map, JsonRpcRequest, JsonRpcResponse};
+use crate::timestamp::parse_timestamp;
 use crate::types::*;
 use base64::Engine as _;
 use bytes::Bytes;
@@ -8,6 +9,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufStream};
 use tokio::net::UnixStream;
 use tokio::sync::Mutex;
@@ -21,6 +23,8 @@ use tokio::sync::Mutex;
 /// data is base64-encoded in transit.  Authentication is handled server-side
 /// via peer credentials; the client sends no auth headers.
 ///
+/// Cloning is cheap: clones share the same connection.
+///
 /// # Example
 ///
 /// ```no_run
@@ -34,11 +38,12 @@ use tokio::sync::Mutex;
 /// # Ok(())
 /// # }
 /// ```
+#[derive(Clone)]
 pub struct UnixClient {
     socket_path: PathBuf,
-    next_id: AtomicU64,
+    next_id: Arc<AtomicU64>,
     /// Persistent connection, established lazily and serialized via the mutex.
-    conn: Mutex<Option<BufStream<UnixStream>>>,
+    conn: Arc<Mutex<Option<BufStream<UnixStream>>>>,
 }
 
 // ── response shapes from the server (protocol.go) ─────────────────────────────
@@ -77,12 +82,6 @@ struct ObjectInfoResult {
     etag: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ApplyPoliciesResult {
-    policies_count: i32,
-    objects_processed: i32,
-}
-
 #[derive(Debug, Deserialize)]
 struct TriggerReplicationResult {
     objects_synced: i32,
@@ -115,8 +114,8 @@ impl UnixClient {
     pub fn new(socket_path: impl AsRef<Path>) -> Result<Self> {
         Ok(Self {
             socket_path: socket_path.as_ref().to_path_buf(),
-            next_id: AtomicU64::new(1),
-            conn: Mutex::new(None),
+            next_id: Arc::new(AtomicU64::new(1)),
+            conn: Arc::new(Mutex::new(None)),
         })
     }
 
@@ -237,9 +236,9 @@ impl UnixClient {
             if let Some(ce) = &m.content_encoding {
                 obj.insert("content_encoding".to_string(), Value::String(ce.clone()));
             }
-            if !m.custom.is_empty() {
-                let custom: serde_json::Map<String, Value> = m
-                    .custom
+            let custom = m.wire_custom();
+            if !custom.is_empty() {
+                let custom: serde_json::Map<String, Value> = custom
                     .iter()
                     .map(|(k, v)| (k.clone(), Value::String(v.clone())))
                     .collect();
@@ -258,6 +257,8 @@ impl UnixClient {
             success: true,
             message: None,
             etag: None,
+            consistency_token: None,
+            region: None,
         })
     }
 
@@ -321,11 +322,7 @@ impl UnixClient {
                         } else {
                             o.etag
                         },
-                        last_modified: o.last_modified.and_then(|s| {
-                            chrono::DateTime::parse_from_rfc3339(&s)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&chrono::Utc))
-                        }),
+                        last_modified: o.last_modified.and_then(|s| parse_timestamp(&s)),
                         ..Default::default()
                     },
                 })
@@ -365,11 +362,11 @@ impl UnixClient {
         if let Some(ce) = &metadata.content_encoding {
             meta_obj.insert("content_encoding".to_string(), Value::String(ce.clone()));
         }
-        if !metadata.custom.is_empty() {
-            let custom: serde_json::Map<String, Value> = metadata
-                .custom
-                .into_iter()
-                .map(|(k, v)| (k, Value::String(v)))
+        let custom = metadata.wire_custom();
+        if !custom.is_empty() {
+            let custom: serde_json::Map<String, Value> = custom
+                .iter()
+                .map(|(k, v)| (k.clone(), Value::String(v.clone())))
                 .collect();
             meta_obj.insert("custom".to_string(), Value::Object(custom));
         }
@@ -468,10 +465,9 @@ impl UnixClient {
     }
 
     /// Apply all lifecycle policies.
-    pub async fn apply_policies(&self) -> Result<(i32, i32)> {
+    pub async fn apply_policies(&self) -> Result<ApplyPoliciesResult> {
         let result = self.call("apply_policies", serde_json::json!({})).await?;
-        let r: ApplyPoliciesResult = serde_json::from_value(result)?;
-        Ok((r.policies_count, r.objects_processed))
+        Ok(serde_json::from_value(result)?)
     }
 
     /// Add a replication policy.
@@ -564,16 +560,18 @@ impl UnixClient {
             total_objects_deleted: 0,
             total_bytes_synced: 0,
             total_errors: r.objects_failed as i64,
-            last_sync_time: r.last_sync_time.and_then(|s| {
-                chrono::DateTime::parse_from_rfc3339(&s)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-            }),
+            last_sync_time: r.last_sync_time.and_then(|s| parse_timestamp(&s)),
             average_sync_duration_ms: 0,
             sync_count: 0,
+            recent_errors: Vec::new(),
         })
     }
 
+    /// Path of the server's socket.
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
     /// Close the client, dropping the persistent connection if one is open.
     /// A subsequent call reconnects automatically.
     pub async fn close(&self) -> Result<()> {
@@ -592,7 +590,9 @@ fn metadata_from_result(m: MetadataResult) -> Metadata {
         last_modified: None,
         etag: None,
         custom: m.custom.unwrap_or_default(),
+        ..Default::default()
     }
+    .lift_web_headers()
 }
 
 fn replication_policy_from_value(v: Value) -> ReplicationPolicy {
@@ -927,9 +927,9 @@ mod tests {
         }));
         let server = spawn_mock_server(resp);
         let client = UnixClient::new(&server.path).unwrap();
-        let (count, processed) = client.apply_policies().await.unwrap();
-        assert_eq!(count, 2);
-        assert_eq!(processed, 5);
+        let result = client.apply_policies().await.unwrap();
+        assert_eq!(result.policies_evaluated, 2);
+        assert_eq!(result.objects_processed, 5);
     }
 
     #[tokio::test]
@@ -0,0 +1,650 @@
+//! Resumable uploads from local files.
+//!
+//! [`resume_upload`] uploads a file in parts and records each finished part
+//! in a checkpoint file, so an upload interrupted by a network failure or a
+//! process restart continues with the parts that are still missing.  Call
+//! it again after any failure until it succeeds:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let stats = loop {
+//!     match client
+//!         .resume_upload("datasets/big.parquet", "big.parquet", "big.parquet.upload")
+//!         .await
+//!     {
+//!         Ok(stats) => break stats,
+//!         Err(e) => eprintln!("upload interrupted, resuming: {e}"),
+//!     }
+//! };
+//! println!("{} of {} parts were already uploaded", stats.resumed_parts, stats.parts);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The checkpoint is an [`UploadCheckpoint`] serialized as JSON: the part
+//! size, the source file's size and modification time, and the offset,
+//! length, and ETag of every uploaded part.  It is rewritten after each part
+//! and removed once the object is complete.  A checkpoint for another key,
+//! or for a source file that has changed since, is discarded and the upload
+//! starts over.
+//!
+//! go-objstore servers have no multipart upload or compose API, so parts
+//! are staged as objects under `<key>.upload-<id>/` and concatenated into
+//! the key once all are uploaded, then deleted.  The concatenation streams
+//! the staged parts back through the client into
+//! [`ObjectStore::put_stream`], one part at a time, so the object crosses
+//! the network three times: up as parts, down, and up again.  Only the
+//! part uploads are resumable.  The concatenation is one request, and if
+//! it fails the staged parts and the checkpoint are deleted, so the next
+//! call starts over.  Staged parts whose ETag no longer matches the
+//! checkpoint are uploaded again.
+//!
+//! [`ObjectStoreClient::resume_upload`] streams the concatenation over
+//! REST and QUIC and fails with
+//! [`Error::UnsupportedByProtocol`] on gRPC, MCP, and Unix clients, whose
+//! `put_stream` holds the whole object in memory.  The free functions
+//! accept any [`ObjectStore`] and put whatever its `put_stream` does.
+//!
+//! Staged parts are deleted by the upload that recorded them: on success,
+//! when the concatenation fails, and when a stale checkpoint is discarded.
+//! Parts of an upload whose checkpoint file was lost or that is never
+//! resumed, and a part uploaded just before a crash but not yet recorded,
+//! are left under `<key>.upload-*/`; remove them with
+//! [`delete_prefix`](crate::delete::delete_prefix) on `<key>.upload-` once
+//! no upload of the key is running.
+
+use crate::client::{ObjectStore, ObjectStoreClient, Operation};
+use crate::error::{Error, Result};
+use crate::key_template::random_suffix;
+use crate::stat::STAT_CONCURRENCY;
+use crate::types::Metadata;
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, SeekFrom};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Part size for new uploads.
+pub const DEFAULT_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Parts uploaded concurrently.
+pub const UPLOAD_CONCURRENCY: usize = 4;
+
+/// Length of the random id naming an upload's staged parts.
+const UPLOAD_ID_LEN: usize = 12;
+
+/// How [`resume_upload_with`] uploads.
+#[derive(Debug, Clone)]
+pub struct UploadOptions {
+    /// Size of each part but the last.  Only applies to new uploads; a
+    /// resumed upload keeps the checkpoint's.
+    pub part_size: u64,
+    /// Metadata for the completed object.
+    pub metadata: Option<Metadata>,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            part_size: DEFAULT_PART_SIZE,
+            metadata: None,
+        }
+    }
+}
+
+impl UploadOptions {
+    /// Set the part size (at least one byte).
+    pub fn with_part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size.max(1);
+        self
+    }
+
+    /// Set the completed object's metadata.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// The progress of an upload, as persisted between attempts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadCheckpoint {
+    /// The key being uploaded.
+    pub key: String,
+    /// Names the staged parts (`<key>.upload-<id>/`).
+    pub upload_id: String,
+    pub part_size: u64,
+    /// Size of the source file when the upload started.
+    pub source_size: u64,
+    /// Modification time of the source file, in nanoseconds since the Unix
+    /// epoch, if the file system reports one.
+    pub source_modified: Option<u64>,
+    /// Uploaded parts, by part number.
+    pub parts: Vec<UploadedPart>,
+}
+
+/// One uploaded part of an [`UploadCheckpoint`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadedPart {
+    /// Position of the part, from 0.
+    pub number: u64,
+    /// Offset of the part in the source file.
+    pub offset: u64,
+    pub len: u64,
+    /// ETag the store returned for the staged part.
+    pub etag: Option<String>,
+}
+
+impl UploadCheckpoint {
+    /// Read the checkpoint at `path`, or `None` if there is none.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        match fs::read(path).await {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the checkpoint to `path`, replacing it atomically.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?).await?;
+        fs::rename(&tmp, path).await?;
+        Ok(())
+    }
+
+    /// Number of parts the source file is split into.
+    pub fn part_count(&self) -> u64 {
+        self.source_size.div_ceil(self.part_size)
+    }
+
+    /// Bytes uploaded so far.
+    pub fn uploaded_bytes(&self) -> u64 {
+        self.parts.iter().map(|p| p.len).sum()
+    }
+
+    /// Key of the staged part `number`.
+    pub fn part_key(&self, number: u64) -> String {
+        format!("{}.upload-{}/{number:06}", self.key, self.upload_id)
+    }
+
+    /// Offset and length of part `number`.
+    fn part_range(&self, number: u64) -> (u64, u64) {
+        let offset = number * self.part_size;
+        (offset, self.part_size.min(self.source_size - offset))
+    }
+
+    /// Whether the checkpoint was made for `key` from the file `source`
+    /// describes.
+    fn matches(&self, key: &str, source: &Source) -> bool {
+        self.key == key
+            && self.source_size == source.size
+            && self.source_modified == source.modified
+    }
+}
+
+/// What [`resume_upload`] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadStats {
+    /// Parts the file was split into.
+    pub parts: u64,
+    /// Parts uploaded by this call.
+    pub uploaded_parts: u64,
+    /// Parts an earlier call had uploaded.
+    pub resumed_parts: u64,
+    /// Bytes of part data uploaded by this call.
+    pub uploaded_bytes: u64,
+    /// Whether an existing checkpoint was discarded because it did not
+    /// match the key or the source file.
+    pub restarted: bool,
+}
+
+/// Size and modification time of a source file.
+struct Source {
+    size: u64,
+    modified: Option<u64>,
+}
+
+impl Source {
+    async fn stat(path: &Path) -> Result<Self> {
+        let meta = fs::metadata(path).await?;
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64);
+        Ok(Self {
+            size: meta.len(),
+            modified,
+        })
+    }
+}
+
+/// Upload the file at `source` under `key` in parts, continuing from the
+/// checkpoint at `checkpoint`.
+pub async fn resume_upload(
+    store: &dyn ObjectStore,
+    key: &str,
+    source: impl AsRef<Path>,
+    checkpoint: impl AsRef<Path>,
+) -> Result<UploadStats> {
+    resume_upload_with(store, key, source, checkpoint, UploadOptions::default()).await
+}
+
+/// Like [`resume_upload`], with [`UploadOptions`].
+///
+/// The concatenated object goes through `store`'s
+/// [`put_stream`](ObjectStore::put_stream); on a store that buffers
+/// streams it is held in memory in full.
+pub async fn resume_upload_with(
+    store: &dyn ObjectStore,
+    key: &str,
+    source: impl AsRef<Path>,
+    checkpoint: impl AsRef<Path>,
+    options: UploadOptions,
+) -> Result<UploadStats> {
+    let checkpoint_path = checkpoint.as_ref();
+    let (state, stats) =
+        upload_parts(store, key, source.as_ref(), checkpoint_path, &options).await?;
+
+    let (tx, rx) = futures::channel::mpsc::channel(4);
+    let upload = store.put_stream(key, rx.boxed(), options.metadata);
+    let feed = async {
+        // Owned, so the body ends after the last part.
+        let mut tx = tx;
+        for part in &state.parts {
+            let staged = async {
+                let (stream, metadata) = store.get_stream(&state.part_key(part.number)).await?;
+                check_part(&state, part, metadata.etag.as_deref())?;
+                Ok::<_, Error>(stream)
+            };
+            let mut stream = match staged.await {
+                Ok(stream) => stream,
+                Err(e) => return cut_short(&mut tx, e).await,
+            };
+            while let Some(chunk) = stream.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => return cut_short(&mut tx, e).await,
+                };
+                if tx.send(Ok(chunk)).await.is_err() {
+                    // The upload failed and says why.
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    };
+    let (uploaded, fed) = tokio::join!(upload, feed);
+    if let Err(e) = fed.and(uploaded) {
+        // A retry would repeat the whole concatenation anyway; do not
+        // leave the parts behind in case there is none.
+        if let Err(cleanup) = finish(store, &state, checkpoint_path).await {
+            tracing::debug!(%key, "could not clean up failed upload: {cleanup}");
+        }
+        return Err(e);
+    }
+
+    finish(store, &state, checkpoint_path).await?;
+    Ok(stats)
+}
+
+/// Fail the concatenated upload rather than let it end short, then return
+/// `e`.
+async fn cut_short(tx: &mut futures::channel::mpsc::Sender<Result<Bytes>>, e: Error) -> Result<()> {
+    let cut = Error::OperationFailed(format!("reading staged parts failed: {e}"));
+    let _ = tx.send(Err(cut)).await;
+    Err(e)
+}
+
+/// Upload the parts missing from the checkpoint, returning the checkpoint
+/// with every part recorded.
+async fn upload_parts(
+    store: &dyn ObjectStore,
+    key: &str,
+    source: &Path,
+    checkpoint_path: &Path,
+    options: &UploadOptions,
+) -> Result<(UploadCheckpoint, UploadStats)> {
+    let file = Source::stat(source).await?;
+    let mut stats = UploadStats::default();
+    let mut state = match UploadCheckpoint::load(checkpoint_path).await? {
+        Some(state) if state.matches(key, &file) => state,
+        stale => {
+            if let Some(stale) = stale {
+                stats.restarted = true;
+                discard(store, &stale).await;
+            }
+            let state = UploadCheckpoint {
+                key: key.to_string(),
+                upload_id: random_suffix(UPLOAD_ID_LEN)?,
+                part_size: options.part_size.max(1),
+                source_size: file.size,
+                source_modified: file.modified,
+                parts: Vec::new(),
+            };
+            state.save(checkpoint_path).await?;
+            state
+        }
+    };
+
+    // Drop recorded parts that are gone or were overwritten.
+    let staged: Vec<bool> = futures::stream::iter(&state.parts)
+        .map(|part| async {
+            match store.get_metadata(&state.part_key(part.number)).await {
+                Ok(metadata) => Ok(check_part(&state, part, metadata.etag.as_deref()).is_ok()),
+                Err(Error::NotFound(_)) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
+        .buffered(STAT_CONCURRENCY)
+        .try_collect()
+        .await?;
+    let mut staged = staged.into_iter();
+    state.parts.retain(|_| staged.next().unwrap_or(false));
+    stats.parts = state.part_count();
+    stats.resumed_parts = state.parts.len() as u64;
+
+    let missing: Vec<u64> = (0..state.part_count())
+        .filter(|n| !state.parts.iter().any(|p| p.number == *n))
+        .collect();
+    let uploads: Vec<_> = missing
+        .into_iter()
+        .map(|number| {
+            let (offset, len) = state.part_range(number);
+            let part_key = state.part_key(number);
+            async move {
+                let data = read_part(source, offset, len).await?;
+                let response = store.put(&part_key, data, None).await?;
+                Ok::<_, Error>(UploadedPart {
+                    number,
+                    offset,
+                    len,
+                    etag: response.etag,
+                })
+            }
+        })
+        .collect();
+    let mut uploads = futures::stream::iter(uploads).buffer_unordered(UPLOAD_CONCURRENCY);
+    while let Some(part) = uploads.next().await {
+        let part = part?;
+        stats.uploaded_parts += 1;
+        stats.uploaded_bytes += part.len;
+        state.parts.push(part);
+        state.parts.sort_by_key(|p| p.number);
+        state.save(checkpoint_path).await?;
+    }
+    Ok((state, stats))
+}
+
+async fn read_part(path: &Path, offset: u64, len: u64) -> Result<Bytes> {
+    let mut file = File::open(path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    let mut data = vec![0; len as usize];
+    file.read_exact(&mut data).await?;
+    Ok(data.into())
+}
+
+/// Fail if a staged part's ETag differs from the one recorded for it.
+fn check_part(state: &UploadCheckpoint, part: &UploadedPart, etag: Option<&str>) -> Result<()> {
+    match (&part.etag, etag) {
+        (Some(expected), Some(actual)) if expected != actual => {
+            Err(Error::OperationFailed(format!(
+                "staged part {} changed during upload",
+                state.part_key(part.number)
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Delete the staged parts and the checkpoint of a completed or failed
+/// upload.
+async fn finish(
+    store: &dyn ObjectStore,
+    state: &UploadCheckpoint,
+    checkpoint_path: &Path,
+) -> Result<()> {
+    discard(store, state).await;
+    match fs::remove_file(checkpoint_path).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Delete an upload's staged parts, best effort.
+async fn discard(store: &dyn ObjectStore, state: &UploadCheckpoint) {
+    futures::stream::iter(&state.parts)
+        .for_each_concurrent(STAT_CONCURRENCY, |part| async move {
+            let key = state.part_key(part.number);
+            if let Err(e) = store.delete(&key).await {
+                tracing::debug!(%key, "could not delete staged part: {e}");
+            }
+        })
+        .await;
+}
+
+impl ObjectStoreClient {
+    /// Upload the file at `source` under `key` in parts, continuing from
+    /// the checkpoint at `checkpoint`.  See the [`upload`](crate::upload)
+    /// module.
+    pub async fn resume_upload(
+        &self,
+        key: &str,
+        source: impl AsRef<Path>,
+        checkpoint: impl AsRef<Path>,
+    ) -> Result<UploadStats> {
+        self.resume_upload_with(key, source, checkpoint, UploadOptions::default())
+            .await
+    }
+
+    /// Like [`resume_upload`](Self::resume_upload), with [`UploadOptions`].
+    ///
+    /// Fails with [`Error::UnsupportedByProtocol`] on gRPC, MCP, and Unix
+    /// clients, which would buffer the concatenated object.
+    pub async fn resume_upload_with(
+        &self,
+        key: &str,
+        source: impl AsRef<Path>,
+        checkpoint: impl AsRef<Path>,
+        options: UploadOptions,
+    ) -> Result<UploadStats> {
+        match self {
+            ObjectStoreClient::Rest(_) | ObjectStoreClient::Quic(_) => {
+                resume_upload_with(self, key, source, checkpoint, options).await
+            }
+            _ => Err(self.unsupported(Operation::ResumeUpload)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::test_support::MemoryStore;
+
+    /// Fail every put after the next `successes`, as if the connection
+    /// dropped.
+    fn drop_after(store: &MemoryStore, successes: usize) {
+        let mut left = successes;
+        store.fail(Operation::Put, move |_| match left.checked_sub(1) {
+            Some(rest) => {
+                left = rest;
+                None
+            }
+            None => Some(Error::RateLimited("connection lost".into())),
+        });
+    }
+
+    #[tokio::test]
+    async fn interrupted_upload_resumes_from_the_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("big.bin");
+        let checkpoint = dir.path().join("big.bin.upload");
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        tokio::fs::write(&source, &data).await.unwrap();
+        let store = MemoryStore::default();
+        let options = UploadOptions::default().with_part_size(100);
+
+        drop_after(&store, 6);
+        let interrupted =
+            resume_upload_with(&store, "big.bin", &source, &checkpoint, options.clone()).await;
+        assert!(interrupted.is_err());
+        let saved = UploadCheckpoint::load(&checkpoint).await.unwrap().unwrap();
+        assert_eq!(saved.part_count(), 10);
+        assert_eq!(saved.parts.len(), 6);
+        assert_eq!(saved.uploaded_bytes(), 600);
+
+        // One staged part disappeared in the meantime.
+        store.remove(&saved.part_key(0));
+        store.heal();
+        let stats = resume_upload_with(&store, "big.bin", &source, &checkpoint, options)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            UploadStats {
+                parts: 10,
+                uploaded_parts: 5,
+                resumed_parts: 5,
+                uploaded_bytes: 500,
+                restarted: false,
+            }
+        );
+        assert_eq!(store.data("big.bin"), data);
+        assert_eq!(store.len(), 1, "staged parts are deleted");
+        assert!(!checkpoint.exists());
+    }
+
+    #[tokio::test]
+    async fn changed_source_restarts_the_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("f");
+        let checkpoint = dir.path().join("f.upload");
+        tokio::fs::write(&source, b"first version").await.unwrap();
+        let store = MemoryStore::default();
+        let options = UploadOptions::default().with_part_size(4);
+
+        drop_after(&store, 2);
+        resume_upload_with(&store, "f", &source, &checkpoint, options.clone())
+            .await
+            .unwrap_err();
+        tokio::fs::write(&source, b"second").await.unwrap();
+        store.heal();
+
+        let stats = resume_upload_with(&store, "f", &source, &checkpoint, options)
+            .await
+            .unwrap();
+        assert!(stats.restarted);
+        assert_eq!((stats.parts, stats.resumed_parts), (2, 0));
+        assert_eq!(store.data("f"), "second");
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_failed_concatenation_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("f");
+        let checkpoint = dir.path().join("f.upload");
+        tokio::fs::write(&source, b"0123456789").await.unwrap();
+        let store = MemoryStore::default();
+        let options = UploadOptions::default().with_part_size(4);
+
+        store.fail_next(Operation::Get, 1, || {
+            Error::OperationFailed("503 Service Unavailable".into())
+        });
+        resume_upload_with(&store, "f", &source, &checkpoint, options.clone())
+            .await
+            .unwrap_err();
+        assert!(!store.contains("f"));
+        assert!(store.is_empty(), "staged parts are deleted");
+        assert!(!checkpoint.exists());
+
+        let stats = resume_upload_with(&store, "f", &source, &checkpoint, options)
+            .await
+            .unwrap();
+        assert_eq!((stats.resumed_parts, stats.uploaded_parts), (0, 3));
+        assert_eq!(store.data("f"), "0123456789");
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rest_streams_the_staged_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("f");
+        let checkpoint = dir.path().join("f.upload");
+        tokio::fs::write(&source, b"hello world").await.unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        let state = UploadCheckpoint {
+            key: "f".into(),
+            upload_id: "id".into(),
+            part_size: 6,
+            source_size: 11,
+            source_modified: Source::stat(&source).await.unwrap().modified,
+            parts: Vec::new(),
+        };
+        state.save(&checkpoint).await.unwrap();
+        for (number, body) in [(0, "hello "), (1, "world")] {
+            let path = format!("/objects/f.upload-id%2F00000{number}");
+            for (method, status) in [("PUT", 201), ("GET", 200), ("DELETE", 200)] {
+                let mock = server
+                    .mock(method, path.as_str())
+                    .with_status(status)
+                    .with_body(if method == "GET" { body } else { "" })
+                    .expect(1)
+                    .create_async()
+                    .await;
+                mocks.push(mock);
+            }
+        }
+        let put = server
+            .mock("PUT", "/objects/f")
+            .match_body("hello world")
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(crate::RestClient::new(server.url()).unwrap());
+
+        let stats = client
+            .resume_upload("f", &source, &checkpoint)
+            .await
+            .unwrap();
+        assert_eq!((stats.parts, stats.uploaded_parts), (2, 2));
+        put.assert_async().await;
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        assert!(!checkpoint.exists());
+    }
+
+    #[tokio::test]
+    async fn buffering_protocols_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("f");
+        let checkpoint = dir.path().join("f.upload");
+        tokio::fs::write(&source, b"0123456789").await.unwrap();
+        let client = ObjectStoreClient::mcp("http://localhost:1").unwrap();
+
+        let err = client
+            .resume_upload("f", &source, &checkpoint)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::UnsupportedByProtocol { .. }),
+            "{err:?}"
+        );
+        assert!(!checkpoint.exists());
+    }
+}
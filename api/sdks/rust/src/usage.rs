@@ -0,0 +1,270 @@
+//! Storage usage grouped by prefix, for per-team billing and quotas.
+//!
+//! [`usage_by_prefix`] totals the size and object count under a prefix,
+//! grouped a number of `/`-separated levels deep — the object store
+//! equivalent of `du -d <depth>`:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! // One group per team: teams/alpha/, teams/beta/, ...
+//! let report = client.usage_under("teams/", 1).await?;
+//! for group in &report.groups {
+//!     println!("{:>12} bytes {:>8} objects  {}", group.bytes, group.objects, group.prefix);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Groups are the prefix followed by `depth` more directory levels.
+//! Objects that sit fewer levels down count toward the deepest directory
+//! holding them, so at depth 1 `teams/notes.txt` counts toward `teams/`.
+//! Depth 0 yields a single group for the whole prefix.
+//!
+//! Over REST, [`ObjectStoreClient::usage_under`] first asks the server to
+//! aggregate in one `GET /usage` request.  Servers without the endpoint,
+//! and every other transport, are totalled client-side: the prefix is
+//! listed one level deep with a `/` delimiter, then each directory found
+//! is listed in full, [`USAGE_CONCURRENCY`] at a time.  That reads every
+//! object's listing entry, and totals objects that change during the walk
+//! as of whenever their page was read.  Client-side totals leave out the
+//! copies kept by [`put_versioned`](crate::versions::put_versioned);
+//! server-side totals include them.
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::types::{ListRequest, ObjectInfo};
+use crate::versions::is_version_key;
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Directories listed concurrently by the client-side path.
+pub const USAGE_CONCURRENCY: usize = 8;
+
+/// Size and object count under one prefix.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefixUsage {
+    pub prefix: String,
+    pub objects: u64,
+    /// Total object size in bytes.
+    pub bytes: u64,
+}
+
+/// What [`usage_by_prefix`] found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageReport {
+    /// Usage per group, sorted by prefix.
+    pub groups: Vec<PrefixUsage>,
+    /// Whether the server aggregated the usage.
+    pub server_side: bool,
+}
+
+impl UsageReport {
+    /// Objects across all groups.
+    pub fn objects(&self) -> u64 {
+        self.groups.iter().map(|g| g.objects).sum()
+    }
+
+    /// Bytes across all groups.
+    pub fn bytes(&self) -> u64 {
+        self.groups.iter().map(|g| g.bytes).sum()
+    }
+}
+
+/// Total the usage of the whole store, grouped `depth` levels deep, by
+/// listing it.  Works with any store.
+pub async fn usage_by_prefix(store: &dyn ObjectStore, depth: usize) -> Result<UsageReport> {
+    usage_under(store, "", depth).await
+}
+
+/// Like [`usage_by_prefix`], for the objects under `prefix`.
+pub async fn usage_under(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    depth: usize,
+) -> Result<UsageReport> {
+    let mut groups = BTreeMap::new();
+    let top = ListRequest {
+        prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+        delimiter: Some("/".to_string()),
+        max_results: Some(LIST_PAGE_SIZE),
+        ..Default::default()
+    };
+    let mut directories = Vec::new();
+    let mut pages = list_pages(store, top);
+    while let Some(page) = pages.next().await {
+        let page = page?;
+        for object in &page.objects {
+            add(&mut groups, prefix, depth, object);
+        }
+        directories.extend(page.common_prefixes);
+    }
+    drop(pages);
+
+    let mut listings = futures::stream::iter(directories)
+        .map(|directory| async move {
+            let request = ListRequest {
+                prefix: Some(directory),
+                max_results: Some(LIST_PAGE_SIZE),
+                ..Default::default()
+            };
+            list_pages(store, request)
+                .map_ok(|page| futures::stream::iter(page.objects).map(Ok::<_, Error>))
+                .try_flatten()
+                .try_collect::<Vec<ObjectInfo>>()
+                .await
+        })
+        .buffer_unordered(USAGE_CONCURRENCY);
+    while let Some(objects) = listings.next().await {
+        for object in &objects? {
+            add(&mut groups, prefix, depth, object);
+        }
+    }
+
+    Ok(UsageReport {
+        groups: groups.into_values().collect(),
+        server_side: false,
+    })
+}
+
+fn add(
+    groups: &mut BTreeMap<String, PrefixUsage>,
+    prefix: &str,
+    depth: usize,
+    object: &ObjectInfo,
+) {
+    if is_version_key(&object.key) {
+        return;
+    }
+    let group = group_of(prefix, &object.key, depth);
+    let usage = groups
+        .entry(group.to_string())
+        .or_insert_with(|| PrefixUsage {
+            prefix: group.to_string(),
+            ..Default::default()
+        });
+    usage.objects += 1;
+    usage.bytes += object.metadata.size.max(0) as u64;
+}
+
+/// The group `key` counts toward: `prefix` and up to `depth` more
+/// directory levels.
+fn group_of<'a>(prefix: &str, key: &'a str, depth: usize) -> &'a str {
+    let rest = key.strip_prefix(prefix).unwrap_or(key);
+    let end = rest
+        .match_indices('/')
+        .take(depth)
+        .last()
+        .map_or(0, |(i, _)| i + 1);
+    &key[..key.len() - rest.len() + end]
+}
+
+impl ObjectStoreClient {
+    /// Total the usage of the whole store, grouped `depth` levels deep.
+    /// See the [`usage`](crate::usage) module.
+    pub async fn usage_by_prefix(&self, depth: usize) -> Result<UsageReport> {
+        self.usage_under("", depth).await
+    }
+
+    /// Total the usage under `prefix`, grouped `depth` levels deep.  See
+    /// the [`usage`](crate::usage) module.
+    pub async fn usage_under(&self, prefix: &str, depth: usize) -> Result<UsageReport> {
+        if let ObjectStoreClient::Rest(client) = self {
+            if let Some(mut groups) = client.usage(prefix, depth).await? {
+                groups.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+                return Ok(UsageReport {
+                    groups,
+                    server_side: true,
+                });
+            }
+        }
+        usage_under(self, prefix, depth).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    #[test]
+    fn groups_by_depth() {
+        assert_eq!(group_of("", "a/b/c", 1), "a/");
+        assert_eq!(group_of("", "a/b/c", 2), "a/b/");
+        assert_eq!(group_of("", "a/b/c", 5), "a/b/");
+        assert_eq!(group_of("", "top", 1), "");
+        assert_eq!(group_of("teams/", "teams/x/y", 0), "teams/");
+        assert_eq!(group_of("teams/", "teams/x/y", 1), "teams/x/");
+        assert_eq!(group_of("teams/", "teams/notes", 1), "teams/");
+    }
+
+    #[tokio::test]
+    async fn rest_uses_server_aggregation() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/usage")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("prefix".into(), "teams/".into()),
+                Matcher::UrlEncoded("depth".into(), "1".into()),
+            ]))
+            .with_body(
+                r#"{"groups":[{"prefix":"teams/b/","objects":2,"bytes":30},
+                              {"prefix":"teams/a/","objects":1,"bytes":5}]}"#,
+            )
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client.usage_under("teams/", 1).await.unwrap();
+        assert!(report.server_side);
+        assert_eq!(report.groups[0].prefix, "teams/a/");
+        assert_eq!((report.objects(), report.bytes()), (3, 35));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_listing_each_directory() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/usage")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("delimiter".into(), "/".into()))
+            .with_body(r#"{"objects":[{"key":"readme","size":5}],"common_prefixes":["a/","b/"]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "a/".into()))
+            .with_body(r#"{"objects":[{"key":"a/x/1","size":10},{"key":"a/2","size":1}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "b/".into()))
+            .with_body(r#"{"objects":[{"key":"b/1","size":100}]}"#)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let report = client.usage_by_prefix(1).await.unwrap();
+        assert!(!report.server_side);
+        let groups: Vec<_> = report
+            .groups
+            .iter()
+            .map(|g| (g.prefix.as_str(), g.objects, g.bytes))
+            .collect();
+        assert_eq!(groups, [("", 1, 5), ("a/", 2, 11), ("b/", 1, 100)]);
+
+        let deeper = client.usage_by_prefix(2).await.unwrap();
+        assert_eq!(deeper.groups.len(), 4);
+        assert_eq!(deeper.bytes(), 116);
+    }
+}
@@ -0,0 +1,303 @@
+//! Checking a prefix against the ETags a bulk write expects.
+//!
+//! After uploading many objects, [`verify_prefix`] confirms that each key
+//! exists with the ETag the upload returned, and reports the ones that
+//! drifted:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example(uploaded: Vec<(String, String)>) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let report = client.verify_prefix("batch-42/", uploaded).await?;
+//! for (key, drift) in &report.drift {
+//!     eprintln!("{key}: {drift}");
+//! }
+//! assert!(report.is_clean());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Small sets are checked key by key — with `HEAD /objects/{key}` over REST
+//! and metadata lookups elsewhere — [`STAT_CONCURRENCY`] at a time.  From
+//! [`LIST_THRESHOLD`] expected keys on, the prefix is listed instead, which
+//! answers a page of keys per request; keys the listing reports without an
+//! ETag are then looked up individually.  Listing reads every object under
+//! the prefix, so prefixes holding far more than the expected keys are best
+//! verified through a narrower prefix.
+//!
+//! ETags are compared without surrounding quotes or a weak `W/` marker.
+//! Objects under the prefix that were not expected are not reported.
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::stat::STAT_CONCURRENCY;
+use crate::types::{ListRequest, Metadata};
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Expected keys from which [`verify_prefix`] lists the prefix instead of
+/// checking keys one by one.
+pub const LIST_THRESHOLD: usize = 256;
+
+/// How an expected object differs from what the store holds.
+#[derive(Debug)]
+pub enum Drift {
+    /// The key does not exist.
+    Missing,
+    /// The key exists with another ETag (`None` if the store reports none).
+    EtagMismatch {
+        expected: String,
+        actual: Option<String>,
+    },
+    /// The key could not be checked.
+    Failed(Error),
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::Missing => f.write_str("missing"),
+            Drift::EtagMismatch { expected, actual } => write!(
+                f,
+                "etag {} (expected {expected})",
+                actual.as_deref().unwrap_or("<none>")
+            ),
+            Drift::Failed(e) => write!(f, "not checked: {e}"),
+        }
+    }
+}
+
+/// What [`verify_prefix`] found.
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Expected keys checked.
+    pub checked: u64,
+    /// Keys that exist with the expected ETag.
+    pub matched: u64,
+    /// Keys that drifted, sorted by key.
+    pub drift: Vec<(String, Drift)>,
+    /// Whether the prefix was listed rather than checked key by key.
+    pub listed: bool,
+}
+
+impl VerifyReport {
+    /// Whether every expected key exists with the expected ETag.
+    pub fn is_clean(&self) -> bool {
+        self.drift.is_empty()
+    }
+}
+
+/// Check that each `(key, etag)` in `expected` exists under `prefix` with
+/// that ETag, using `store.get_metadata` for individual keys.  See the
+/// [module docs](self).
+///
+/// Fails only if a key lies outside `prefix` or listing the prefix fails;
+/// per-key errors are reported as [`Drift::Failed`].
+pub async fn verify_prefix<I, K, E>(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    expected: I,
+) -> Result<VerifyReport>
+where
+    I: IntoIterator<Item = (K, E)>,
+    K: Into<String>,
+    E: Into<String>,
+{
+    verify(store, prefix, collect(prefix, expected)?, |key| {
+        async move { store.get_metadata(&key).await }.boxed()
+    })
+    .await
+}
+
+impl ObjectStoreClient {
+    /// Check that each `(key, etag)` in `expected` exists under `prefix`
+    /// with that ETag.  See the [`verify`](crate::verify) module.
+    pub async fn verify_prefix<I, K, E>(&self, prefix: &str, expected: I) -> Result<VerifyReport>
+    where
+        I: IntoIterator<Item = (K, E)>,
+        K: Into<String>,
+        E: Into<String>,
+    {
+        let expected = collect(prefix, expected)?;
+        match self {
+            ObjectStoreClient::Rest(client) => {
+                verify(self, prefix, expected, |key| {
+                    async move { client.head(&key).await }.boxed()
+                })
+                .await
+            }
+            _ => {
+                verify(self, prefix, expected, |key| {
+                    async move { self.get_metadata(&key).await }.boxed()
+                })
+                .await
+            }
+        }
+    }
+}
+
+fn collect<I, K, E>(prefix: &str, expected: I) -> Result<HashMap<String, String>>
+where
+    I: IntoIterator<Item = (K, E)>,
+    K: Into<String>,
+    E: Into<String>,
+{
+    let expected: HashMap<String, String> = expected
+        .into_iter()
+        .map(|(k, e)| (k.into(), e.into()))
+        .collect();
+    if let Some(key) = expected.keys().find(|k| !k.starts_with(prefix)) {
+        return Err(Error::InvalidArgument(format!(
+            "{key} is not under prefix {prefix}"
+        )));
+    }
+    Ok(expected)
+}
+
+async fn verify<'a, F>(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    mut expected: HashMap<String, String>,
+    stat: F,
+) -> Result<VerifyReport>
+where
+    F: Fn(String) -> BoxFuture<'a, Result<Metadata>>,
+{
+    let mut report = VerifyReport {
+        checked: expected.len() as u64,
+        ..Default::default()
+    };
+
+    if expected.len() >= LIST_THRESHOLD {
+        report.listed = true;
+        let request = ListRequest {
+            prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+            max_results: Some(LIST_PAGE_SIZE),
+            ..Default::default()
+        };
+        let mut pages = list_pages(store, request);
+        let mut unlisted = HashMap::new();
+        while let Some(page) = pages.next().await {
+            for object in page?.objects {
+                let Some(etag) = expected.remove(&object.key) else {
+                    continue;
+                };
+                match object.metadata.etag.filter(|e| !e.is_empty()) {
+                    Some(actual) => compare(&mut report, object.key, etag, Some(actual)),
+                    // Fall back to a lookup for backends that list no ETags.
+                    None => {
+                        unlisted.insert(object.key, etag);
+                    }
+                }
+            }
+        }
+        for (key, _) in expected.drain() {
+            report.drift.push((key, Drift::Missing));
+        }
+        expected = unlisted;
+    }
+
+    let mut results = futures::stream::iter(expected)
+        .map(|(key, etag)| {
+            let lookup = stat(key.clone());
+            async move { (key, etag, lookup.await) }
+        })
+        .buffer_unordered(STAT_CONCURRENCY);
+    while let Some((key, etag, result)) = results.next().await {
+        match result {
+            Ok(metadata) => compare(&mut report, key, etag, metadata.etag),
+            Err(Error::NotFound(_)) => report.drift.push((key, Drift::Missing)),
+            Err(e) => report.drift.push((key, Drift::Failed(e))),
+        }
+    }
+
+    report.drift.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(report)
+}
+
+fn compare(report: &mut VerifyReport, key: String, expected: String, actual: Option<String>) {
+    if actual.as_deref().map(normalize) == Some(normalize(&expected)) {
+        report.matched += 1;
+    } else {
+        report
+            .drift
+            .push((key, Drift::EtagMismatch { expected, actual }));
+    }
+}
+
+/// An ETag without its weak marker and quotes.
+pub(crate) fn normalize(etag: &str) -> &str {
+    etag.trim_start_matches("W/").trim_matches('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest_client::RestClient;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn rest_heads_each_key() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("HEAD", "/objects/b%2F1")
+            .with_header("etag", "\"e1\"")
+            .create_async()
+            .await;
+        server
+            .mock("HEAD", "/objects/b%2F2")
+            .with_header("etag", "\"stale\"")
+            .create_async()
+            .await;
+        server
+            .mock("HEAD", "/objects/b%2F3")
+            .with_status(404)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let expected = [("b/1", "e1"), ("b/2", "e2"), ("b/3", "e3")];
+        let report = client.verify_prefix("b/", expected).await.unwrap();
+        assert!(!report.listed);
+        assert_eq!((report.checked, report.matched), (3, 1));
+        assert!(matches!(
+            &report.drift[0],
+            (k, Drift::EtagMismatch { actual: Some(a), .. }) if k == "b/2" && a == "\"stale\""
+        ));
+        assert!(matches!(&report.drift[1], (k, Drift::Missing) if k == "b/3"));
+
+        let outside = client.verify_prefix("b/", [("c/1", "e")]).await;
+        assert!(matches!(outside, Err(Error::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn large_sets_are_checked_against_the_listing() {
+        let mut server = mockito::Server::new_async().await;
+        let objects: Vec<String> = (0..LIST_THRESHOLD)
+            .map(|i| format!(r#"{{"key":"b/{i:04}","size":1,"etag":"e{i}"}}"#))
+            .collect();
+        let list = server
+            .mock("GET", "/objects")
+            .match_query(Matcher::UrlEncoded("prefix".into(), "b/".into()))
+            .with_body(format!(r#"{{"objects":[{}]}}"#, objects.join(",")))
+            .expect(1)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let mut expected: Vec<(String, String)> = (0..LIST_THRESHOLD)
+            .map(|i| (format!("b/{i:04}"), format!("e{i}")))
+            .collect();
+        expected[0].1 = "other".into();
+        expected.push(("b/gone".into(), "x".into()));
+        let report = client.verify_prefix("b/", expected).await.unwrap();
+        list.assert_async().await;
+        assert!(report.listed);
+        assert_eq!(report.matched as usize, LIST_THRESHOLD - 1);
+        let drifted: Vec<_> = report.drift.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(drifted, ["b/0000", "b/gone"]);
+    }
+}
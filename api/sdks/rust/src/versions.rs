@@ -0,0 +1,252 @@
+//! Point-in-time reads of versioned objects.
+//!
+//! [`put_versioned`] writes an object as usual and also keeps an immutable
+//! copy at `<key>.versions/<timestamp>`.  [`get_at`] and [`metadata_at`]
+//! then resolve the version that was current at a given time, so a data
+//! pipeline can be pinned to the inputs it saw on a previous run:
+//!
+//! ```no_run
+//! use bytes::Bytes;
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let pinned = client.put_versioned("models/weights.bin", Bytes::from("v1"), None).await?;
+//! client.put_versioned("models/weights.bin", Bytes::from("v2"), None).await?;
+//!
+//! let (data, _) = client.get_at("models/weights.bin", pinned).await?;
+//! assert_eq!(data, "v1");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! go-objstore servers do not keep object versions, so this is a client
+//! convention: only writes made through [`put_versioned`] are recorded,
+//! and version timestamps come from the writer's clock.  The current
+//! object answers for any time at or after its last modification, so a
+//! plain `put` that replaced the last recorded version is not hidden by
+//! it; a plain `put` that was itself replaced leaves no trace.
+//!
+//! Versions are ordinary objects stored next to the key, so every
+//! versioned write stores the data twice, and nothing expires old versions
+//! until [`delete_versions`] removes them.  The crate's listing helpers
+//! ([`usage`](crate::usage), [`delete_prefix`](crate::delete::delete_prefix),
+//! [`tag_prefix`](crate::tags::tag_prefix), [`retain`](crate::retain) and
+//! [`list_recent`](crate::recent::list_recent)) skip them, but a plain
+//! `list` returns them, server-side usage totals count them, and
+//! replication policies covering the prefix copy them.  Use
+//! [`is_version_key`] to skip them when walking a listing yourself.
+
+use crate::client::{list_pages, ObjectStore, ObjectStoreClient, LIST_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::types::{ListRequest, Metadata};
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures::StreamExt;
+
+/// Appended to a key to form the prefix its versions are stored under.
+pub const VERSIONS_SUFFIX: &str = ".versions/";
+
+/// Fixed-width, so version keys sort in time order.
+const VERSION_FORMAT: &str = "%Y%m%dT%H%M%S%.9fZ";
+
+/// Key of the version of `key` written at `at`.
+pub fn version_key(key: &str, at: DateTime<Utc>) -> String {
+    format!("{key}{VERSIONS_SUFFIX}{}", at.format(VERSION_FORMAT))
+}
+
+/// Whether `key` is a version recorded by [`put_versioned`] rather than an
+/// object written directly.
+pub fn is_version_key(key: &str) -> bool {
+    key.rsplit_once(VERSIONS_SUFFIX)
+        .is_some_and(|(_, stamp)| NaiveDateTime::parse_from_str(stamp, VERSION_FORMAT).is_ok())
+}
+
+/// Put `data` at `key` and record it as a new version.  Returns the
+/// version's timestamp, which [`get_at`] resolves back to this write.
+pub async fn put_versioned(
+    store: &dyn ObjectStore,
+    key: &str,
+    data: Bytes,
+    metadata: Option<Metadata>,
+) -> Result<DateTime<Utc>> {
+    let at = Utc::now();
+    // Version first: a failure leaves the current object unchanged.
+    store
+        .put(&version_key(key, at), data.clone(), metadata.clone())
+        .await?;
+    store.put(key, data, metadata).await?;
+    Ok(at)
+}
+
+/// Timestamps of the recorded versions of `key`, oldest first.
+pub async fn list_versions(store: &dyn ObjectStore, key: &str) -> Result<Vec<DateTime<Utc>>> {
+    let prefix = format!("{key}{VERSIONS_SUFFIX}");
+    let request = ListRequest {
+        prefix: Some(prefix.clone()),
+        max_results: Some(LIST_PAGE_SIZE),
+        ..Default::default()
+    };
+    let mut versions = Vec::new();
+    let mut pages = list_pages(store, request);
+    while let Some(page) = pages.next().await {
+        for object in page?.objects {
+            let stamp = object.key.strip_prefix(&prefix).unwrap_or_default();
+            // Nested keys and foreign objects under the prefix are not versions.
+            if let Ok(at) = NaiveDateTime::parse_from_str(stamp, VERSION_FORMAT) {
+                versions.push(at.and_utc());
+            }
+        }
+    }
+    versions.sort();
+    Ok(versions)
+}
+
+/// Delete every recorded version of `key`, returning how many there were.
+/// The current object is left alone.
+pub async fn delete_versions(store: &dyn ObjectStore, key: &str) -> Result<usize> {
+    let versions = list_versions(store, key).await?;
+    for at in &versions {
+        match store.delete(&version_key(key, *at)).await {
+            Ok(_) | Err(Error::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(versions.len())
+}
+
+/// Fetch `key` as it was at `at`.
+///
+/// Fails with [`Error::NotFound`] if the key had no version yet at `at`.
+pub async fn get_at(
+    store: &dyn ObjectStore,
+    key: &str,
+    at: DateTime<Utc>,
+) -> Result<(Bytes, Metadata)> {
+    store.get(&resolve(store, key, at).await?).await
+}
+
+/// Metadata of `key` as it was at `at`.  See [`get_at`].
+pub async fn metadata_at(
+    store: &dyn ObjectStore,
+    key: &str,
+    at: DateTime<Utc>,
+) -> Result<Metadata> {
+    store.get_metadata(&resolve(store, key, at).await?).await
+}
+
+/// The key holding the version of `key` current at `at`.
+async fn resolve(store: &dyn ObjectStore, key: &str, at: DateTime<Utc>) -> Result<String> {
+    // Unchanged since `at`, so it was current then, whatever was recorded.
+    match store.get_metadata(key).await {
+        Ok(metadata) if metadata.last_modified.is_some_and(|m| m <= at) => {
+            return Ok(key.to_string())
+        }
+        Ok(_) | Err(Error::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    let versions = list_versions(store, key).await?;
+    match versions.iter().rev().find(|v| **v <= at) {
+        Some(version) => Ok(version_key(key, *version)),
+        None => Err(Error::NotFound(format!("{key} at {}", at.to_rfc3339()))),
+    }
+}
+
+impl ObjectStoreClient {
+    /// Put an object and record it as a new version.  See the
+    /// [`versions`](crate::versions) module.
+    pub async fn put_versioned(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: Option<Metadata>,
+    ) -> Result<DateTime<Utc>> {
+        put_versioned(self, key, data, metadata).await
+    }
+
+    /// Delete every recorded version of `key`.
+    pub async fn delete_versions(&self, key: &str) -> Result<usize> {
+        delete_versions(self, key).await
+    }
+
+    /// Fetch the version of `key` that was current at `at`.
+    pub async fn get_at(&self, key: &str, at: DateTime<Utc>) -> Result<(Bytes, Metadata)> {
+        get_at(self, key, at).await
+    }
+
+    /// Metadata of the version of `key` that was current at `at`.
+    pub async fn metadata_at(&self, key: &str, at: DateTime<Utc>) -> Result<Metadata> {
+        metadata_at(self, key, at).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+    use chrono::TimeZone;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, d, 0, 0, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn resolves_the_version_current_at_a_time() {
+        let store = MemoryStore::default();
+        let first = put_versioned(&store, "data/a", Bytes::from("v1"), None)
+            .await
+            .unwrap();
+        let second = put_versioned(&store, "data/a", Bytes::from("v2"), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            list_versions(&store, "data/a").await.unwrap(),
+            [first, second]
+        );
+
+        assert_eq!(get_at(&store, "data/a", first).await.unwrap().0, "v1");
+        assert_eq!(get_at(&store, "data/a", second).await.unwrap().0, "v2");
+        assert_eq!(get_at(&store, "data/a", Utc::now()).await.unwrap().0, "v2");
+        assert!(matches!(
+            get_at(&store, "data/a", first - chrono::Duration::nanoseconds(1)).await,
+            Err(Error::NotFound(_))
+        ));
+
+        assert_eq!(delete_versions(&store, "data/a").await.unwrap(), 2);
+        assert_eq!(store.keys(), ["data/a"]);
+    }
+
+    #[tokio::test]
+    async fn a_later_plain_put_is_not_hidden_by_older_versions() {
+        let store = MemoryStore::default();
+        let first = put_versioned(&store, "data/a", Bytes::from("v1"), None)
+            .await
+            .unwrap();
+        store.put("data/a", Bytes::from("v2"), None).await.unwrap();
+
+        assert_eq!(get_at(&store, "data/a", first).await.unwrap().0, "v1");
+        assert_eq!(get_at(&store, "data/a", Utc::now()).await.unwrap().0, "v2");
+    }
+
+    #[tokio::test]
+    async fn unversioned_keys_fall_back_to_last_modified() {
+        let store = MemoryStore::default();
+        store.insert_at("data/b", "plain", day(5));
+        // Not a version: the timestamp does not parse.
+        store.insert_at("data/b.versions/notes", "", day(1));
+
+        let metadata = metadata_at(&store, "data/b", day(6)).await.unwrap();
+        assert_eq!(metadata.last_modified, Some(day(5)));
+        assert!(matches!(
+            metadata_at(&store, "data/b", day(4)).await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn version_keys_are_recognised() {
+        assert!(is_version_key(&version_key("data/a", day(1))));
+        assert!(!is_version_key("data/a"));
+        assert!(!is_version_key("data/b.versions/notes"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Change notifications by listing diff.
+//!
+//! go-objstore servers do not push change events (there is no gRPC watch
+//! stream or REST server-sent-events endpoint), so [`watch`] polls: it lists
+//! the prefix every [`WatchOptions::interval`], compares the listing with
+//! the previous one, and yields an event per created, updated, or deleted
+//! object.  Because it only needs `list`, it behaves the same over every
+//! protocol:
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use go_objstore::watch::{watch, WatchEvent, WatchOptions};
+//! use go_objstore::ObjectStoreClient;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let options = WatchOptions::default().with_interval(Duration::from_secs(10));
+//! let mut events = watch(&client, "incoming/", options);
+//! while let Some(event) = events.next().await {
+//!     match event? {
+//!         WatchEvent::Created(object) => println!("new: {}", object.key),
+//!         WatchEvent::Updated(object) => println!("changed: {}", object.key),
+//!         WatchEvent::Deleted(object) => println!("gone: {}", object.key),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Changes that are undone between two polls are not seen, and each poll
+//! lists the whole prefix, so pick the interval with the prefix size in
+//! mind.
+
+use crate::client::{list_pages, ObjectStore, LIST_PAGE_SIZE};
+use crate::error::Result;
+use crate::types::{ListRequest, ObjectInfo};
+use futures::stream::{BoxStream, StreamExt};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// A change observed between two polls.
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    /// A key that was not present before.
+    Created(ObjectInfo),
+    /// A key whose size, ETag, or modification time changed.
+    Updated(ObjectInfo),
+    /// A key that is no longer present; carries its last known state.
+    Deleted(ObjectInfo),
+}
+
+impl WatchEvent {
+    /// The object key.
+    pub fn key(&self) -> &str {
+        match self {
+            WatchEvent::Created(o) | WatchEvent::Updated(o) | WatchEvent::Deleted(o) => &o.key,
+        }
+    }
+}
+
+/// Polling settings for [`watch`].
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Delay between listings.  Defaults to 5 seconds.
+    pub interval: Duration,
+    /// Report every object present at the first poll as
+    /// [`WatchEvent::Created`].  Defaults to false: the first poll only
+    /// records the baseline.
+    pub emit_initial: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            emit_initial: false,
+        }
+    }
+}
+
+impl WatchOptions {
+    /// Set the delay between listings.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Report pre-existing objects as created on the first poll.
+    pub fn with_initial_events(mut self, emit_initial: bool) -> Self {
+        self.emit_initial = emit_initial;
+        self
+    }
+}
+
+struct State<'a> {
+    store: &'a dyn ObjectStore,
+    prefix: String,
+    options: WatchOptions,
+    /// `None` until the first successful poll.
+    snapshot: Option<HashMap<String, ObjectInfo>>,
+    pending: VecDeque<WatchEvent>,
+}
+
+/// Watch `prefix` for changes.  See the [module docs](self).
+///
+/// The stream never ends on its own.  A failed poll yields the error and
+/// polling continues at the next interval, so drop the stream (or stop on
+/// the first error) to end watching.
+pub fn watch<'a>(
+    store: &'a dyn ObjectStore,
+    prefix: &str,
+    options: WatchOptions,
+) -> BoxStream<'a, Result<WatchEvent>> {
+    let state = State {
+        store,
+        prefix: prefix.to_string(),
+        options,
+        snapshot: None,
+        pending: VecDeque::new(),
+    };
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((Ok(event), state));
+            }
+            if state.snapshot.is_some() {
+                tokio::time::sleep(state.options.interval).await;
+            }
+            match snapshot(state.store, &state.prefix).await {
+                Ok(current) => state.observe(current),
+                Err(e) => {
+                    // Wait out the interval before retrying a failed first poll.
+                    if state.snapshot.is_none() {
+                        tokio::time::sleep(state.options.interval).await;
+                    }
+                    return Some((Err(e), state));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+impl State<'_> {
+    fn observe(&mut self, current: HashMap<String, ObjectInfo>) {
+        let Some(previous) = self.snapshot.as_mut() else {
+            if self.options.emit_initial {
+                let mut created: Vec<_> = current.values().cloned().collect();
+                created.sort_by(|a, b| a.key.cmp(&b.key));
+                self.pending
+                    .extend(created.into_iter().map(WatchEvent::Created));
+            }
+            self.snapshot = Some(current);
+            return;
+        };
+
+        let mut events = Vec::new();
+        for (key, object) in &current {
+            match previous.remove(key) {
+                None => events.push(WatchEvent::Created(object.clone())),
+                Some(old) if changed(&old, object) => {
+                    events.push(WatchEvent::Updated(object.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        events.extend(previous.drain().map(|(_, old)| WatchEvent::Deleted(old)));
+        events.sort_by(|a, b| a.key().cmp(b.key()));
+        self.pending.extend(events);
+        self.snapshot = Some(current);
+    }
+}
+
+/// Whether `new` differs from `old` in size, ETag, or modification time.
+pub(crate) fn changed(old: &ObjectInfo, new: &ObjectInfo) -> bool {
+    let (a, b) = (&old.metadata, &new.metadata);
+    a.size != b.size || a.etag != b.etag || a.last_modified != b.last_modified
+}
+
+/// Every object under `prefix`, by key.
+pub(crate) async fn snapshot(
+    store: &dyn ObjectStore,
+    prefix: &str,
+) -> Result<HashMap<String, ObjectInfo>> {
+    let mut objects = HashMap::new();
+    let mut pages = list_pages(
+        store,
+        ListRequest {
+            prefix: Some(prefix.to_string()).filter(|p| !p.is_empty()),
+            max_results: Some(LIST_PAGE_SIZE),
+            ..Default::default()
+        },
+    );
+    while let Some(page) = pages.next().await {
+        objects.extend(page?.objects.into_iter().map(|o| (o.key.clone(), o)));
+    }
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Operation;
+    use crate::error::Error;
+    use crate::test_support::MemoryStore;
+
+    fn summary(event: &WatchEvent) -> String {
+        let kind = match event {
+            WatchEvent::Created(_) => "created",
+            WatchEvent::Updated(_) => "updated",
+            WatchEvent::Deleted(_) => "deleted",
+        };
+        format!("{kind} {}", event.key())
+    }
+
+    #[tokio::test]
+    async fn reports_changes_between_polls() {
+        let store = MemoryStore::default();
+        store.reset(&[("a", 1), ("b", 2)]);
+        let options = WatchOptions::default()
+            .with_interval(Duration::from_millis(10))
+            .with_initial_events(true);
+        let mut events = watch(&store, "", options);
+
+        assert_eq!(summary(&events.next().await.unwrap().unwrap()), "created a");
+        assert_eq!(summary(&events.next().await.unwrap().unwrap()), "created b");
+
+        store.reset(&[("b", 3), ("c", 1)]);
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            seen.push(summary(&events.next().await.unwrap().unwrap()));
+        }
+        assert_eq!(seen, ["deleted a", "updated b", "created c"]);
+    }
+
+    #[tokio::test]
+    async fn errors_do_not_end_the_stream() {
+        let store = MemoryStore::default();
+        store.reset(&[("a", 1)]);
+        let options = WatchOptions::default()
+            .with_interval(Duration::from_millis(10))
+            .with_initial_events(true);
+        let mut events = watch(&store, "", options);
+        assert_eq!(summary(&events.next().await.unwrap().unwrap()), "created a");
+
+        store.fail(Operation::List, |_| {
+            Some(Error::OperationFailed("listing unavailable".into()))
+        });
+        assert!(events.next().await.unwrap().is_err());
+
+        store.heal();
+        store.reset(&[]);
+        assert_eq!(summary(&events.next().await.unwrap().unwrap()), "deleted a");
+    }
+}
@@ -0,0 +1,631 @@
+//! Random access to zip archives stored as objects.
+//!
+//! [`ObjectStoreClient::open_zip`] reads a remote `.zip` object's central
+//! directory with range requests and extracts single entries on demand,
+//! so pulling one file out of a large dataset or model package costs a
+//! few requests instead of a download of the whole archive:
+//!
+//! ```no_run
+//! use go_objstore::ObjectStoreClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ObjectStoreClient::rest("http://localhost:8080")?;
+//! let mut archive = client.open_zip("models/bert-base.zip").await?;
+//! for entry in archive.entries() {
+//!     println!("{:>12} {}", entry.size, entry.name);
+//! }
+//! let config = archive.read("bert-base/config.json").await?;
+//! println!("{} bytes in {} requests", config.len(), archive.get_ref().requests());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The archive is read through an [`ObjectReader`], so the end-of-archive
+//! record and a central directory near it usually arrive in one request,
+//! and each entry read fetches only the blocks holding it.  Transports
+//! without range reads download the archive once (see the
+//! [`reader`](crate::reader) module).  [`ZipArchive::new`] also opens any
+//! other `AsyncRead + AsyncSeek`, such as a local file.
+//!
+//! Stored and deflated entries are supported, including zip64 archives.
+//! Extracted data is checked against the entry's CRC-32.  Encrypted
+//! entries and other compression methods fail with
+//! [`Error::InvalidArgument`](crate::Error::InvalidArgument), as does an
+//! archive whose structure cannot be parsed.
+
+use crate::client::ObjectStoreClient;
+use crate::error::{Error, Result};
+use crate::reader::ObjectReader;
+use bytes::Bytes;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::io::{Read, SeekFrom};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+/// Compression method of stored entries.
+pub const METHOD_STORED: u16 = 0;
+/// Compression method of deflated entries.
+pub const METHOD_DEFLATED: u16 = 8;
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const EOCD_LEN: u64 = 22;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_LOCATOR_LEN: u64 = 20;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x0606_4b50;
+const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+const LOCAL_HEADER_LEN: u64 = 30;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+const FLAG_ENCRYPTED: u16 = 1;
+/// Largest archive comment, which bounds the search for the
+/// end-of-archive record.
+const MAX_COMMENT: u64 = 0xffff;
+
+/// One file or directory in a [`ZipArchive`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    /// Path inside the archive; directories end with `/`.
+    pub name: String,
+    /// Uncompressed size in bytes.
+    pub size: u64,
+    /// Size of the stored data in bytes.
+    pub compressed_size: u64,
+    /// [`METHOD_STORED`], [`METHOD_DEFLATED`], or another zip method id.
+    pub method: u16,
+    pub crc32: u32,
+    /// Modification time as recorded, in the archiver's local time.
+    pub modified: Option<NaiveDateTime>,
+    flags: u16,
+    header_offset: u64,
+}
+
+impl ZipEntry {
+    /// Whether the entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+
+    /// Whether the entry is encrypted and cannot be extracted.
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & FLAG_ENCRYPTED != 0
+    }
+}
+
+/// A zip archive opened for reading single entries.  See the
+/// [module docs](self).
+#[derive(Debug)]
+pub struct ZipArchive<R = ObjectReader> {
+    reader: R,
+    len: u64,
+    entries: Vec<ZipEntry>,
+}
+
+impl<R> ZipArchive<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin + Send,
+{
+    /// Read the central directory of the archive in `reader`.
+    pub async fn new(mut reader: R) -> Result<Self> {
+        let len = reader.seek(SeekFrom::End(0)).await?;
+        let tail_len = len.min(EOCD_LEN + MAX_COMMENT);
+        let tail = read_at(&mut reader, len - tail_len, tail_len).await?;
+        let eocd = find_eocd(&tail).ok_or_else(|| malformed("no end of central directory"))?;
+        let eocd_offset = len - tail_len + eocd as u64;
+        let mut record = Cursor(&tail[eocd..]);
+        record.skip(10)?;
+        let mut count = u64::from(record.u16()?);
+        let mut cd_size = u64::from(record.u32()?);
+        let mut cd_offset = u64::from(record.u32()?);
+
+        if count == 0xffff || cd_size == 0xffff_ffff || cd_offset == 0xffff_ffff {
+            if let Some(zip64) = read_zip64_eocd(&mut reader, eocd_offset).await? {
+                (count, cd_size, cd_offset) = zip64;
+            }
+        }
+        if cd_offset
+            .checked_add(cd_size)
+            .is_none_or(|end| end > eocd_offset)
+        {
+            return Err(malformed("central directory out of bounds"));
+        }
+
+        let directory = read_at(&mut reader, cd_offset, cd_size).await?;
+        let mut cursor = Cursor(&directory);
+        // The count only sizes the allocation; the directory's own length
+        // bounds how many entries are parsed.
+        let mut entries = Vec::with_capacity(count.min(cd_size / 46) as usize);
+        for _ in 0..count {
+            entries.push(parse_central_entry(&mut cursor)?);
+        }
+        Ok(Self {
+            reader,
+            len,
+            entries,
+        })
+    }
+
+    /// Every entry, in central directory order.
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    /// The entry named `name`, if any.
+    pub fn entry(&self, name: &str) -> Option<&ZipEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Extract the entry named `name`, failing with
+    /// [`Error::NotFound`](crate::Error::NotFound) if there is none.
+    pub async fn read(&mut self, name: &str) -> Result<Bytes> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.name == name)
+            .ok_or_else(|| Error::NotFound(format!("{name} in zip archive")))?;
+        self.read_index(index).await
+    }
+
+    /// Extract the entry at `index` in [`entries`](Self::entries).
+    pub async fn read_index(&mut self, index: usize) -> Result<Bytes> {
+        let entry = self
+            .entries
+            .get(index)
+            .ok_or_else(|| Error::InvalidArgument(format!("no zip entry at index {index}")))?
+            .clone();
+        if entry.is_encrypted() {
+            return Err(Error::InvalidArgument(format!(
+                "zip entry {} is encrypted",
+                entry.name
+            )));
+        }
+        if entry.method != METHOD_STORED && entry.method != METHOD_DEFLATED {
+            return Err(Error::InvalidArgument(format!(
+                "zip entry {} uses unsupported compression method {}",
+                entry.name, entry.method
+            )));
+        }
+
+        let header = read_at(&mut self.reader, entry.header_offset, LOCAL_HEADER_LEN).await?;
+        let mut cursor = Cursor(&header);
+        if cursor.u32()? != LOCAL_SIGNATURE {
+            return Err(malformed(&format!("bad local header for {}", entry.name)));
+        }
+        cursor.skip(22)?;
+        let name_len = u64::from(cursor.u16()?);
+        let extra_len = u64::from(cursor.u16()?);
+        let start = entry.header_offset + LOCAL_HEADER_LEN + name_len + extra_len;
+        if start
+            .checked_add(entry.compressed_size)
+            .is_none_or(|end| end > self.len)
+        {
+            return Err(malformed(&format!("data of {} out of bounds", entry.name)));
+        }
+        let stored = read_at(&mut self.reader, start, entry.compressed_size).await?;
+
+        let data = if entry.method == METHOD_STORED {
+            stored
+        } else {
+            let mut data = Vec::with_capacity(entry.size.min(stored.len() as u64 * 4) as usize);
+            flate2::read::DeflateDecoder::new(&stored[..])
+                .take(entry.size.saturating_add(1))
+                .read_to_end(&mut data)
+                .map_err(|e| malformed(&format!("bad deflate data in {}: {e}", entry.name)))?;
+            data
+        };
+        if data.len() as u64 != entry.size {
+            return Err(malformed(&format!(
+                "{} is {} bytes, expected {}",
+                entry.name,
+                data.len(),
+                entry.size
+            )));
+        }
+        let mut crc = flate2::Crc::new();
+        crc.update(&data);
+        if crc.sum() != entry.crc32 {
+            return Err(malformed(&format!("CRC mismatch in {}", entry.name)));
+        }
+        Ok(data.into())
+    }
+
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Give back the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl ObjectStoreClient {
+    /// Open the zip archive stored under `key` for reading single entries.
+    /// See the [`zip`](crate::zip) module.
+    pub async fn open_zip(&self, key: &str) -> Result<ZipArchive> {
+        ZipArchive::new(self.reader(key).await?).await
+    }
+}
+
+async fn read_at<R>(reader: &mut R, offset: u64, len: u64) -> Result<Vec<u8>>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    reader.seek(SeekFrom::Start(offset)).await?;
+    let mut buf = Vec::new();
+    let read = (&mut *reader).take(len).read_to_end(&mut buf).await?;
+    if read as u64 != len {
+        return Err(malformed("archive truncated"));
+    }
+    Ok(buf)
+}
+
+/// Offset in `tail` of the end-of-central-directory record whose comment
+/// runs exactly to the end.
+fn find_eocd(tail: &[u8]) -> Option<usize> {
+    let last = tail.len().checked_sub(EOCD_LEN as usize)?;
+    (0..=last).rev().find(|&i| {
+        let record = &tail[i..];
+        let comment_len = u16::from_le_bytes([record[20], record[21]]) as usize;
+        record[..4] == EOCD_SIGNATURE.to_le_bytes()
+            && EOCD_LEN as usize + comment_len == record.len()
+    })
+}
+
+/// The entry count, size, and offset of the central directory from the
+/// zip64 records before `eocd_offset`, if there are any.
+async fn read_zip64_eocd<R>(reader: &mut R, eocd_offset: u64) -> Result<Option<(u64, u64, u64)>>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    let Some(locator_offset) = eocd_offset.checked_sub(ZIP64_LOCATOR_LEN) else {
+        return Ok(None);
+    };
+    let locator = read_at(reader, locator_offset, ZIP64_LOCATOR_LEN).await?;
+    let mut cursor = Cursor(&locator);
+    if cursor.u32()? != ZIP64_LOCATOR_SIGNATURE {
+        return Ok(None);
+    }
+    cursor.skip(4)?;
+    let record_offset = cursor.u64()?;
+    if record_offset >= locator_offset {
+        return Err(malformed("zip64 end of central directory out of bounds"));
+    }
+    let record = read_at(reader, record_offset, 56).await?;
+    let mut cursor = Cursor(&record);
+    if cursor.u32()? != ZIP64_EOCD_SIGNATURE {
+        return Err(malformed("bad zip64 end of central directory"));
+    }
+    cursor.skip(28)?;
+    let count = cursor.u64()?;
+    let size = cursor.u64()?;
+    let offset = cursor.u64()?;
+    Ok(Some((count, size, offset)))
+}
+
+fn parse_central_entry(cursor: &mut Cursor<'_>) -> Result<ZipEntry> {
+    if cursor.u32()? != CENTRAL_SIGNATURE {
+        return Err(malformed("bad central directory entry"));
+    }
+    cursor.skip(4)?;
+    let flags = cursor.u16()?;
+    let method = cursor.u16()?;
+    let time = cursor.u16()?;
+    let date = cursor.u16()?;
+    let crc32 = cursor.u32()?;
+    let mut compressed_size = u64::from(cursor.u32()?);
+    let mut size = u64::from(cursor.u32()?);
+    let name_len = cursor.u16()? as usize;
+    let extra_len = cursor.u16()? as usize;
+    let comment_len = cursor.u16()? as usize;
+    cursor.skip(8)?;
+    let mut header_offset = u64::from(cursor.u32()?);
+    let name = String::from_utf8_lossy(cursor.take(name_len)?).into_owned();
+    let mut extra = Cursor(cursor.take(extra_len)?);
+    cursor.skip(comment_len)?;
+
+    // Zip64 sizes and offset follow, in this order, for each field that
+    // overflowed.
+    while extra.0.len() >= 4 {
+        let id = extra.u16()?;
+        let len = extra.u16()? as usize;
+        let mut field = Cursor(extra.take(len)?);
+        if id != ZIP64_EXTRA_ID {
+            continue;
+        }
+        if size == 0xffff_ffff {
+            size = field.u64()?;
+        }
+        if compressed_size == 0xffff_ffff {
+            compressed_size = field.u64()?;
+        }
+        if header_offset == 0xffff_ffff {
+            header_offset = field.u64()?;
+        }
+    }
+
+    Ok(ZipEntry {
+        name,
+        size,
+        compressed_size,
+        method,
+        crc32,
+        modified: dos_time(date, time),
+        flags,
+        header_offset,
+    })
+}
+
+/// An MS-DOS date and time, or `None` for an invalid one.
+fn dos_time(date: u16, time: u16) -> Option<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(
+        1980 + i32::from(date >> 9),
+        u32::from((date >> 5) & 0xf),
+        u32::from(date & 0x1f),
+    )?
+    .and_hms_opt(
+        u32::from(time >> 11),
+        u32::from((time >> 5) & 0x3f),
+        u32::from(time & 0x1f) * 2,
+    )
+}
+
+fn malformed(message: &str) -> Error {
+    Error::InvalidArgument(format!("malformed zip archive: {message}"))
+}
+
+/// Little-endian field reader over a byte slice.
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(malformed("record truncated"));
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<()> {
+        self.take(n).map(drop)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::{FetchFn, Fetched, ReaderOptions};
+    use crate::rest_client::RestClient;
+    use futures::FutureExt;
+    use std::io::Write;
+    use std::ops::Range;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    /// Build a zip of `(name, data, deflate)` entries, optionally with the
+    /// zip64 end records and a padding entry pushing the rest back.
+    fn build(files: &[(&str, &[u8], bool)], zip64: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for &(name, data, deflate) in files {
+            let mut crc = flate2::Crc::new();
+            crc.update(data);
+            let stored = if deflate {
+                let mut enc =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+                enc.write_all(data).unwrap();
+                enc.finish().unwrap()
+            } else {
+                data.to_vec()
+            };
+            let method: u16 = if deflate { 8 } else { 0 };
+            let offset = out.len() as u32;
+            // 2024-03-05 12:34:56
+            let (time, date) = ((12 << 11) | (34 << 5) | 28, (44 << 9) | (3 << 5) | 5);
+            out.extend(LOCAL_SIGNATURE.to_le_bytes());
+            out.extend([20, 0, 0, 0]);
+            out.extend(method.to_le_bytes());
+            out.extend([0; 4]);
+            out.extend(crc.sum().to_le_bytes());
+            out.extend((stored.len() as u32).to_le_bytes());
+            out.extend((data.len() as u32).to_le_bytes());
+            out.extend((name.len() as u16).to_le_bytes());
+            out.extend([0, 0]);
+            out.extend(name.as_bytes());
+            out.extend(&stored);
+
+            central.extend(CENTRAL_SIGNATURE.to_le_bytes());
+            central.extend([20, 0, 20, 0, 0, 0]);
+            central.extend(method.to_le_bytes());
+            central.extend((time as u16).to_le_bytes());
+            central.extend((date as u16).to_le_bytes());
+            central.extend(crc.sum().to_le_bytes());
+            if zip64 {
+                central.extend([0xff; 8]);
+            } else {
+                central.extend((stored.len() as u32).to_le_bytes());
+                central.extend((data.len() as u32).to_le_bytes());
+            }
+            central.extend((name.len() as u16).to_le_bytes());
+            central.extend((if zip64 { 20u16 } else { 0 }).to_le_bytes());
+            // Comment length, disk, internal and external attributes.
+            central.extend([0; 10]);
+            central.extend(offset.to_le_bytes());
+            central.extend(name.as_bytes());
+            if zip64 {
+                central.extend(ZIP64_EXTRA_ID.to_le_bytes());
+                central.extend(16u16.to_le_bytes());
+                central.extend((data.len() as u64).to_le_bytes());
+                central.extend((stored.len() as u64).to_le_bytes());
+            }
+        }
+        let cd_offset = out.len() as u64;
+        out.extend(&central);
+        let count = files.len() as u64;
+        if zip64 {
+            let record = out.len() as u64;
+            out.extend(ZIP64_EOCD_SIGNATURE.to_le_bytes());
+            out.extend(44u64.to_le_bytes());
+            out.extend([45, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            out.extend(count.to_le_bytes());
+            out.extend(count.to_le_bytes());
+            out.extend((central.len() as u64).to_le_bytes());
+            out.extend(cd_offset.to_le_bytes());
+            out.extend(ZIP64_LOCATOR_SIGNATURE.to_le_bytes());
+            out.extend([0; 4]);
+            out.extend(record.to_le_bytes());
+            out.extend(1u32.to_le_bytes());
+        }
+        out.extend(EOCD_SIGNATURE.to_le_bytes());
+        out.extend([0; 4]);
+        let (count, size, offset) = if zip64 {
+            (0xffff, 0xffff_ffff, 0xffff_ffff)
+        } else {
+            (count as u16, central.len() as u32, cd_offset as u32)
+        };
+        out.extend(count.to_le_bytes());
+        out.extend(count.to_le_bytes());
+        out.extend(size.to_le_bytes());
+        out.extend(offset.to_le_bytes());
+        out.extend(7u16.to_le_bytes());
+        out.extend(b"comment");
+        out
+    }
+
+    fn files() -> Vec<(&'static str, &'static [u8], bool)> {
+        vec![
+            ("data/", b"", false),
+            ("data/readme.txt", b"hello zip", false),
+            ("model/weights.bin", &[7u8; 50_000], true),
+            ("model/config.json", br#"{"layers":12}"#, true),
+        ]
+    }
+
+    #[tokio::test]
+    async fn lists_and_extracts_entries() {
+        let archive = build(&files(), false);
+        let mut zip = ZipArchive::new(std::io::Cursor::new(archive))
+            .await
+            .unwrap();
+
+        let names: Vec<_> = zip.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "data/",
+                "data/readme.txt",
+                "model/weights.bin",
+                "model/config.json"
+            ]
+        );
+        assert!(zip.entries()[0].is_dir());
+        let weights = zip.entry("model/weights.bin").unwrap();
+        assert_eq!((weights.size, weights.method), (50_000, METHOD_DEFLATED));
+        assert!(weights.compressed_size < 1_000);
+        assert_eq!(weights.modified.unwrap().to_string(), "2024-03-05 12:34:56");
+
+        assert_eq!(zip.read("data/readme.txt").await.unwrap(), "hello zip");
+        assert_eq!(
+            zip.read("model/config.json").await.unwrap(),
+            r#"{"layers":12}"#
+        );
+        assert_eq!(
+            zip.read("model/weights.bin").await.unwrap(),
+            vec![7u8; 50_000]
+        );
+        assert!(matches!(zip.read("missing").await, Err(Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn reads_zip64_records() {
+        let archive = build(&files(), true);
+        let mut zip = ZipArchive::new(std::io::Cursor::new(archive))
+            .await
+            .unwrap();
+        assert_eq!(zip.entries().len(), 4);
+        assert_eq!(zip.entry("model/weights.bin").unwrap().size, 50_000);
+        assert_eq!(zip.read("model/weights.bin").await.unwrap().len(), 50_000);
+    }
+
+    #[tokio::test]
+    async fn rejects_corrupt_archives() {
+        let mut archive = build(&files(), false);
+        assert!(matches!(
+            ZipArchive::new(std::io::Cursor::new(archive[..archive.len() - 30].to_vec())).await,
+            Err(Error::InvalidArgument(_))
+        ));
+        // Flip a byte of the stored readme.
+        let at = archive.windows(9).position(|w| w == b"hello zip").unwrap();
+        archive[at] ^= 1;
+        let mut zip = ZipArchive::new(std::io::Cursor::new(archive))
+            .await
+            .unwrap();
+        let err = zip.read("data/readme.txt").await.unwrap_err();
+        assert!(err.to_string().contains("CRC mismatch"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn fetches_only_the_needed_ranges() {
+        let padding = vec![1u8; 400_000];
+        let mut entries = files();
+        entries.insert(0, ("padding.bin", &padding, false));
+        let archive = Bytes::from(build(&entries, false));
+        let sent = Arc::new(AtomicU64::new(0));
+        let counter = sent.clone();
+        let data = archive.clone();
+        let fetch: FetchFn = Arc::new(move |range: Range<u64>| {
+            let chunk = data.slice(range.start as usize..range.end as usize);
+            counter.fetch_add(chunk.len() as u64, Ordering::SeqCst);
+            async move { Ok(Fetched::At(range.start, chunk)) }.boxed()
+        });
+        let options = ReaderOptions {
+            block_size: 16 * 1024,
+            cache_blocks: 8,
+        };
+        let reader = ObjectReader::new("k", archive.len() as u64, options, fetch);
+
+        let mut zip = ZipArchive::new(reader).await.unwrap();
+        assert_eq!(zip.read("data/readme.txt").await.unwrap(), "hello zip");
+        assert!(sent.load(Ordering::SeqCst) < archive.len() as u64 / 2);
+    }
+
+    #[tokio::test]
+    async fn opens_archives_over_rest() {
+        let archive = build(&files(), false);
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/metadata/bundle.zip")
+            .with_body(format!(
+                r#"{{"key":"bundle.zip","size":{}}}"#,
+                archive.len()
+            ))
+            .create_async()
+            .await;
+        // A server that ignores Range and sends the whole archive.
+        server
+            .mock("GET", "/objects/bundle.zip")
+            .with_body(archive)
+            .create_async()
+            .await;
+        let client = ObjectStoreClient::Rest(RestClient::new(server.url()).unwrap());
+
+        let mut zip = client.open_zip("bundle.zip").await.unwrap();
+        assert_eq!(
+            zip.read("model/config.json").await.unwrap(),
+            r#"{"layers":12}"#
+        );
+        assert_eq!(zip.get_ref().requests(), 1);
+    }
+}
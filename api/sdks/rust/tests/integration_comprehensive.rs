@@ -39,38 +39,19 @@
 
 use bytes::Bytes;
 use go_objstore::{
-    LifecyclePolicy, ListRequest, Metadata, ObjectStore, ObjectStoreClient, ReplicationMode,
-    ReplicationPolicy,
+    LifecyclePolicy, ListRequest, Metadata, ObjectStore, ObjectStoreClient, Protocol,
+    ReplicationMode, ReplicationPolicy,
 };
 use std::collections::HashMap;
 use std::env;
 use std::net::SocketAddr;
-use tonic;
 
 // ============================================================================
 // Protocol configuration
 // ============================================================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Protocol {
-    Rest,
-    Grpc,
-    Quic,
-}
-
-impl Protocol {
-    const fn all() -> &'static [Protocol] {
-        &[Protocol::Rest, Protocol::Grpc, Protocol::Quic]
-    }
-
-    const fn name(&self) -> &'static str {
-        match self {
-            Protocol::Rest => "rest",
-            Protocol::Grpc => "grpc",
-            Protocol::Quic => "quic",
-        }
-    }
-}
+/// The protocols the integration server exposes.
+const TESTED_PROTOCOLS: [Protocol; 3] = [Protocol::Rest, Protocol::Grpc, Protocol::Quic];
 
 fn rest_endpoint() -> String {
     env::var("REST_HOST")
@@ -137,6 +118,7 @@ async fn try_create_client(
                 }
             }
         }
+        other => Err(format!("{other} is not part of the integration matrix").into()),
     }
 }
 
@@ -194,7 +176,7 @@ fn canonical_replication_policy(id: &str) -> ReplicationPolicy {
 // ============================================================================
 
 fn key(protocol: Protocol, suffix: &str) -> String {
-    format!("integration-{}-{}", protocol.name(), suffix)
+    format!("integration-{}-{}", protocol.as_str(), suffix)
 }
 
 fn data(content: &str) -> Bytes {
@@ -332,7 +314,7 @@ async fn test_list(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let prefix = format!("integration-{}-list/", protocol.name());
+    let prefix = format!("integration-{}-list/", protocol.as_str());
     let mut keys: Vec<String> = Vec::new();
 
     for i in 0..5 {
@@ -474,7 +456,7 @@ async fn test_add_policy(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-add-policy", protocol.name());
+    let id = format!("integ-{}-add-policy", protocol.as_str());
     let policy = LifecyclePolicy {
         id: id.clone(),
         prefix: "integration/lifecycle/".to_string(),
@@ -501,8 +483,8 @@ async fn test_get_policies(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id1 = format!("integ-{}-get-pol-1", protocol.name());
-    let id2 = format!("integ-{}-get-pol-2", protocol.name());
+    let id1 = format!("integ-{}-get-pol-1", protocol.as_str());
+    let id2 = format!("integ-{}-get-pol-2", protocol.as_str());
 
     for id in [&id1, &id2] {
         client
@@ -540,7 +522,7 @@ async fn test_remove_policy(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-rm-policy", protocol.name());
+    let id = format!("integ-{}-rm-policy", protocol.as_str());
     client
         .add_policy(LifecyclePolicy {
             id: id.clone(),
@@ -567,7 +549,7 @@ async fn test_apply_policies(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-apply-pol", protocol.name());
+    let id = format!("integ-{}-apply-pol", protocol.as_str());
     client
         .add_policy(LifecyclePolicy {
             id: id.clone(),
@@ -584,18 +566,18 @@ async fn test_apply_policies(
         .put(obj_key, data("apply-policies marker"), None)
         .await?;
 
-    let (processed, deleted) = client.apply_policies().await?;
-    // processed and deleted are counts — they must be non-negative integers.
+    let applied = client.apply_policies().await?;
+    // Both are counts — they must be non-negative integers.
     // i32 is signed in the SDK type; assert the invariant explicitly.
     assert!(
-        processed >= 0,
-        "applyPolicies: processed count must be >= 0, got {}",
-        processed
+        applied.policies_evaluated >= 0,
+        "applyPolicies: policies count must be >= 0, got {}",
+        applied.policies_evaluated
     );
     assert!(
-        deleted >= 0,
-        "applyPolicies: deleted count must be >= 0, got {}",
-        deleted
+        applied.objects_processed >= 0,
+        "applyPolicies: processed count must be >= 0, got {}",
+        applied.objects_processed
     );
 
     client.remove_policy(&id).await?;
@@ -617,7 +599,7 @@ async fn test_archive(
     let mut dest = HashMap::new();
     dest.insert(
         "path".to_string(),
-        format!("/tmp/archive-{}", protocol.name()),
+        format!("/tmp/archive-{}", protocol.as_str()),
     );
 
     let result = client.archive(&k, "local".to_string(), dest).await;
@@ -638,13 +620,13 @@ async fn test_archive(
             {
                 eprintln!(
                     "[SKIP] archive on {} protocol: backend lacks archiver capability: {:?}",
-                    protocol.name(),
+                    protocol.as_str(),
                     e
                 );
             } else {
                 return Err(format!(
                     "archive on {} protocol returned unexpected error: {:?}",
-                    protocol.name(),
+                    protocol.as_str(),
                     e
                 )
                 .into());
@@ -675,7 +657,7 @@ async fn test_add_replication_policy(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-add-repl", protocol.name());
+    let id = format!("integ-{}-add-repl", protocol.as_str());
     let policy = canonical_replication_policy(&id);
 
     client.add_replication_policy(policy).await?;
@@ -695,8 +677,8 @@ async fn test_get_replication_policies(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id1 = format!("integ-{}-get-repl-1", protocol.name());
-    let id2 = format!("integ-{}-get-repl-2", protocol.name());
+    let id1 = format!("integ-{}-get-repl-1", protocol.as_str());
+    let id2 = format!("integ-{}-get-repl-2", protocol.as_str());
 
     client
         .add_replication_policy(canonical_replication_policy(&id1))
@@ -731,7 +713,7 @@ async fn test_get_replication_policy(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-get-repl-single", protocol.name());
+    let id = format!("integ-{}-get-repl-single", protocol.as_str());
     let policy = canonical_replication_policy(&id);
 
     client.add_replication_policy(policy).await?;
@@ -759,7 +741,7 @@ async fn test_trigger_replication(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-trigger-repl", protocol.name());
+    let id = format!("integ-{}-trigger-repl", protocol.as_str());
     client
         .add_replication_policy(canonical_replication_policy(&id))
         .await?;
@@ -795,7 +777,7 @@ async fn test_get_replication_status(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-repl-status", protocol.name());
+    let id = format!("integ-{}-repl-status", protocol.as_str());
     client
         .add_replication_policy(canonical_replication_policy(&id))
         .await?;
@@ -836,7 +818,7 @@ async fn test_remove_replication_policy(
     client: ObjectStoreClient,
     protocol: Protocol,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let id = format!("integ-{}-rm-repl", protocol.name());
+    let id = format!("integ-{}-rm-repl", protocol.as_str());
     client
         .add_replication_policy(canonical_replication_policy(&id))
         .await?;
@@ -856,22 +838,13 @@ async fn test_remove_replication_policy(
 // 7. Error-path operations
 // ============================================================================
 
-/// Returns true if `err` represents a "not found" condition for the given protocol.
+/// Returns true if `err` represents a "not found" condition.
 ///
-/// Over REST the SDK produces `Error::NotFound`.  Over gRPC tonic status codes
-/// are surfaced as `Error::GrpcStatus`; the server returns `code: NotFound` for
-/// most missing-object operations and `code: Internal` for `UpdateMetadata` on a
-/// missing key (a server-side mapping quirk).  Both are accepted here.
-fn is_not_found_error(protocol: Protocol, err: &go_objstore::Error) -> bool {
-    match err {
-        go_objstore::Error::NotFound(_) => true,
-        go_objstore::Error::GrpcStatus(s)
-            if matches!(protocol, Protocol::Grpc | Protocol::Quic) =>
-        {
-            matches!(s.code(), tonic::Code::NotFound | tonic::Code::Internal)
-        }
-        _ => false,
-    }
+/// Every protocol must surface a missing key as `Error::NotFound`; over gRPC
+/// this includes the `Internal` "no such file or directory" statuses the SDK
+/// normalizes, so a raw `Error::GrpcStatus` here is a failure.
+fn is_not_found_error(err: &go_objstore::Error) -> bool {
+    matches!(err, go_objstore::Error::NotFound(_))
 }
 
 /// get_nonexistent: GET of a key that was never stored must return a not-found error.
@@ -885,16 +858,16 @@ async fn test_get_nonexistent(
 
     let result = client.get(&k).await;
     match result {
-        Err(ref e) if is_not_found_error(protocol, e) => Ok(()),
+        Err(ref e) if is_not_found_error(e) => Ok(()),
         Err(e) => Err(format!(
             "get_nonexistent on {}: expected NotFound, got {:?}",
-            protocol.name(),
+            protocol.as_str(),
             e
         )
         .into()),
         Ok(_) => Err(format!(
             "get_nonexistent on {}: expected NotFound, got Ok (key should not exist)",
-            protocol.name()
+            protocol.as_str()
         )
         .into()),
     }
@@ -912,10 +885,10 @@ async fn test_delete_nonexistent(
     let result = client.delete(&k).await;
     match result {
         Ok(_) => Ok(()), // idempotent delete — acceptable
-        Err(ref e) if is_not_found_error(protocol, e) => Ok(()),
+        Err(ref e) if is_not_found_error(e) => Ok(()),
         Err(e) => Err(format!(
             "delete_nonexistent on {}: expected NotFound or Ok, got {:?}",
-            protocol.name(),
+            protocol.as_str(),
             e
         )
         .into()),
@@ -937,16 +910,16 @@ async fn test_update_metadata_nonexistent(
     let meta = metadata_with(&[("test", "value")]);
     let result = client.update_metadata(&k, meta).await;
     match result {
-        Err(ref e) if is_not_found_error(protocol, e) => Ok(()),
+        Err(ref e) if is_not_found_error(e) => Ok(()),
         Err(e) => Err(format!(
             "update_metadata_nonexistent on {}: expected NotFound, got {:?}",
-            protocol.name(),
+            protocol.as_str(),
             e
         )
         .into()),
         Ok(_) => Err(format!(
             "update_metadata_nonexistent on {}: expected NotFound, got Ok (key should not exist)",
-            protocol.name()
+            protocol.as_str()
         )
         .into()),
     }
@@ -965,7 +938,7 @@ async fn test_large_object(
     assert!(
         resp.success,
         "large_object on {}: PUT must return success==true",
-        protocol.name()
+        protocol.as_str()
     );
 
     let (retrieved, meta) = client.get(&k).await?;
@@ -973,19 +946,19 @@ async fn test_large_object(
         retrieved.len(),
         large_data.len(),
         "large_object on {}: retrieved length must equal original",
-        protocol.name()
+        protocol.as_str()
     );
     assert_eq!(
         retrieved,
         large_data,
         "large_object on {}: retrieved bytes must equal original bytes",
-        protocol.name()
+        protocol.as_str()
     );
     assert_eq!(
         meta.size,
         1024 * 1024,
         "large_object on {}: metadata.size must equal 1 MiB",
-        protocol.name()
+        protocol.as_str()
     );
 
     client.delete(&k).await?;
@@ -1162,7 +1135,7 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
     // Collect available clients.  REST/gRPC failures propagate as hard errors;
     // QUIC returning None (logged skip) is silently omitted from the matrix.
     let mut available: Vec<(Protocol, ObjectStoreClient)> = Vec::new();
-    for &protocol in Protocol::all() {
+    for protocol in TESTED_PROTOCOLS {
         if let Some(client) = try_create_client(protocol).await? {
             available.push((protocol, client));
         }
@@ -1188,7 +1161,11 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
             let (proto_a, client_a) = &available[i];
             let (proto_b, client_b) = &available[j];
 
-            let k = format!("cross-proto-{}-to-{}.txt", proto_a.name(), proto_b.name());
+            let k = format!(
+                "cross-proto-{}-to-{}.txt",
+                proto_a.as_str(),
+                proto_b.as_str()
+            );
 
             // Step 1: put via A
             let put_meta = Metadata {
@@ -1204,23 +1181,23 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
                     assert!(
                         resp.success,
                         "cross-proto: put via {} must succeed",
-                        proto_a.name()
+                        proto_a.as_str()
                     );
                 }
                 Err(ref e) if is_quic_docker_skip(*proto_a, e) => {
                     eprintln!(
                         "[SKIP] cross-proto {}->{}: QUIC put timed out",
-                        proto_a.name(),
-                        proto_b.name()
+                        proto_a.as_str(),
+                        proto_b.as_str()
                     );
                     continue;
                 }
                 Err(e) => {
                     return Err(format!(
                         "cross-proto {}->{}: put via {} failed: {:?}",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_a.name(),
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_a.as_str(),
                         e
                     )
                     .into());
@@ -1234,17 +1211,17 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
                         got_bytes,
                         content_bytes,
                         "cross-proto {}->{}: bytes read via {} must equal bytes written via {}",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_b.name(),
-                        proto_a.name()
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_b.as_str(),
+                        proto_a.as_str()
                     );
                 }
                 Err(ref e) if is_quic_docker_skip(*proto_b, e) => {
                     eprintln!(
                         "[SKIP] cross-proto {}->{}: QUIC get timed out",
-                        proto_a.name(),
-                        proto_b.name()
+                        proto_a.as_str(),
+                        proto_b.as_str()
                     );
                     let _ = client_a.delete(&k).await;
                     continue;
@@ -1253,9 +1230,9 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
                     let _ = client_a.delete(&k).await;
                     return Err(format!(
                         "cross-proto {}->{}: get via {} failed: {:?}",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_b.name(),
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_b.as_str(),
                         e
                     )
                     .into());
@@ -1269,24 +1246,24 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
                         meta.size,
                         content.len() as i64,
                         "cross-proto {}->{}: metadata.size via {} must equal payload length",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_b.name()
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_b.as_str()
                     );
                     assert_eq!(
                         meta.content_type.as_deref(),
                         Some("application/octet-stream"),
                         "cross-proto {}->{}: content_type via {} must match",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_b.name()
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_b.as_str()
                     );
                 }
                 Err(ref e) if is_quic_docker_skip(*proto_b, e) => {
                     eprintln!(
                         "[SKIP] cross-proto {}->{}: QUIC getMetadata timed out",
-                        proto_a.name(),
-                        proto_b.name()
+                        proto_a.as_str(),
+                        proto_b.as_str()
                     );
                     let _ = client_a.delete(&k).await;
                     continue;
@@ -1295,9 +1272,9 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
                     let _ = client_a.delete(&k).await;
                     return Err(format!(
                         "cross-proto {}->{}: getMetadata via {} failed: {:?}",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_b.name(),
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_b.as_str(),
                         e
                     )
                     .into());
@@ -1310,17 +1287,17 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
                 Err(ref e) if is_quic_docker_skip(*proto_a, e) => {
                     eprintln!(
                         "[SKIP] cross-proto {}->{}: QUIC delete timed out",
-                        proto_a.name(),
-                        proto_b.name()
+                        proto_a.as_str(),
+                        proto_b.as_str()
                     );
                     continue;
                 }
                 Err(e) => {
                     return Err(format!(
                         "cross-proto {}->{}: delete via {} failed: {:?}",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_a.name(),
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_a.as_str(),
                         e
                     )
                     .into());
@@ -1332,26 +1309,26 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
                     assert!(
                         !present,
                         "cross-proto {}->{}: exists via {} must be false after delete via {}",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_b.name(),
-                        proto_a.name()
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_b.as_str(),
+                        proto_a.as_str()
                     );
                 }
                 Err(ref e) if is_quic_docker_skip(*proto_b, e) => {
                     eprintln!(
                         "[SKIP] cross-proto {}->{}: QUIC exists timed out",
-                        proto_a.name(),
-                        proto_b.name()
+                        proto_a.as_str(),
+                        proto_b.as_str()
                     );
                     continue;
                 }
                 Err(e) => {
                     return Err(format!(
                         "cross-proto {}->{}: exists via {} failed: {:?}",
-                        proto_a.name(),
-                        proto_b.name(),
-                        proto_b.name(),
+                        proto_a.as_str(),
+                        proto_b.as_str(),
+                        proto_b.as_str(),
                         e
                     )
                     .into());
@@ -1360,8 +1337,8 @@ async fn test_cross_protocol_consistency() -> Result<(), Box<dyn std::error::Err
 
             eprintln!(
                 "[OK] cross-proto consistency: write via {}, read/delete via {}",
-                proto_a.name(),
-                proto_b.name()
+                proto_a.as_str(),
+                proto_b.as_str()
             );
         }
     }
@@ -1410,7 +1387,7 @@ fn test_operation_coverage() {
 
 #[test]
 fn test_protocol_coverage() {
-    let protocols = Protocol::all();
+    let protocols = TESTED_PROTOCOLS;
     assert_eq!(protocols.len(), 3);
     assert!(protocols.contains(&Protocol::Rest));
     assert!(protocols.contains(&Protocol::Grpc));
@@ -65,7 +65,7 @@ fn unified_mcp_constructor_ok() {
 #[test]
 fn unified_mcp_with_auth_constructor_ok() {
     let auth = AuthConfig {
-        token: Some("tok".to_string()),
+        token: Some("tok".into()),
         tenant_id: Some("t1".to_string()),
         ..Default::default()
     };
@@ -264,7 +264,10 @@ async fn unified_mcp_policy_ops_delegation() {
     client.add_policy(sample_lifecycle_policy()).await.unwrap();
     client.remove_policy("lc1").await.unwrap();
     assert!(client.get_policies(None).await.unwrap().is_empty());
-    assert_eq!(client.apply_policies().await.unwrap(), (0, 0));
+    assert_eq!(
+        client.apply_policies().await.unwrap(),
+        go_objstore::ApplyPoliciesResult::default()
+    );
 }
 
 #[tokio::test]
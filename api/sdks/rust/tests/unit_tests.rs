@@ -4,9 +4,9 @@
 //! without requiring a running server.
 
 use go_objstore::{
-    DeleteResponse, EncryptionConfig, EncryptionPolicy, Error, HealthResponse, HealthStatus,
-    LifecyclePolicy, ListRequest, ListResponse, Metadata, ObjectInfo, PutResponse, ReplicationMode,
-    ReplicationPolicy, ReplicationStatus, SyncResult,
+    DeleteResponse, DirMarkers, EncryptionConfig, EncryptionPolicy, Error, HealthResponse,
+    HealthStatus, LifecyclePolicy, ListRequest, ListResponse, Metadata, ObjectInfo, PutResponse,
+    ReplicationMode, ReplicationPolicy, ReplicationStatus, SyncResult,
 };
 use std::collections::HashMap;
 
@@ -41,6 +41,10 @@ mod metadata_tests {
             last_modified: None,
             etag: Some("abc123".to_string()),
             custom,
+            compressed_size: None,
+            cache_control: None,
+            content_disposition: None,
+            content_language: None,
         };
 
         assert_eq!(metadata.content_type, Some("application/json".to_string()));
@@ -60,6 +64,10 @@ mod metadata_tests {
             last_modified: None,
             etag: Some("def456".to_string()),
             custom: HashMap::new(),
+            compressed_size: None,
+            cache_control: None,
+            content_disposition: None,
+            content_language: None,
         };
 
         let cloned = metadata.clone();
@@ -110,6 +118,7 @@ mod list_request_tests {
         assert_eq!(req.delimiter, None);
         assert_eq!(req.max_results, None);
         assert_eq!(req.continue_from, None);
+        assert_eq!(req.dir_markers, DirMarkers::Show);
     }
 
     #[test]
@@ -119,12 +128,14 @@ mod list_request_tests {
             delimiter: Some("/".to_string()),
             max_results: Some(100),
             continue_from: Some("token123".to_string()),
+            dir_markers: DirMarkers::Hide,
         };
 
         assert_eq!(req.prefix, Some("test/".to_string()));
         assert_eq!(req.delimiter, Some("/".to_string()));
         assert_eq!(req.max_results, Some(100));
         assert_eq!(req.continue_from, Some("token123".to_string()));
+        assert_eq!(req.dir_markers, DirMarkers::Hide);
     }
 }
 
@@ -181,6 +192,8 @@ mod put_response_tests {
             success: true,
             message: Some("Object created".to_string()),
             etag: Some("\"abc123\"".to_string()),
+            consistency_token: None,
+            region: None,
         };
 
         assert!(resp.success);
@@ -194,6 +207,8 @@ mod put_response_tests {
             success: false,
             message: Some("Storage full".to_string()),
             etag: None,
+            consistency_token: None,
+            region: None,
         };
 
         assert!(!resp.success);
@@ -430,6 +445,7 @@ mod replication_status_tests {
             last_sync_time: None,
             average_sync_duration_ms: 5000,
             sync_count: 100,
+            recent_errors: Vec::new(),
         };
 
         assert_eq!(status.policy_id, "repl-1");
@@ -572,6 +588,10 @@ mod serialization_tests {
             last_modified: None,
             etag: Some("abc".to_string()),
             custom: HashMap::new(),
+            compressed_size: None,
+            cache_control: None,
+            content_disposition: None,
+            content_language: None,
         };
 
         // Test that we can serialize to JSON
@@ -667,6 +687,8 @@ mod trait_tests {
             success: true,
             message: None,
             etag: None,
+            consistency_token: None,
+            region: None,
         };
         let _ = put.clone();
 